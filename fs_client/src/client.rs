@@ -1,34 +1,65 @@
 use glutin::event::WindowEvent;
 use rapier2d::{na::Vector2, prelude::RigidBodyHandle};
-use specs::{Entities, Join, ReadStorage, WorldExt, WriteStorage};
-
-use fs_common::game::common::world::{
-    chunk_access::FSChunkAccess,
-    chunk_handler::ChunkHandler,
-    entity::{
-        CollisionDetector, CutCopy, GameEntity, Hitbox, PhysicsEntity, Player,
-        PlayerClipboardState, PlayerGrappleState, PlayerJumpState, PlayerLaunchState,
-        PlayerMovementMode,
+use specs::{Entities, Join, ReadStorage, WorldExt, Write, WriteStorage};
+
+use fs_common::game::common::{
+    world::{
+        chunk_access::FSChunkAccess,
+        chunk_handler::ChunkHandler,
+        destroy::destroy_pixel,
+        edit_events::{WorldEditCause, WorldEditEvents},
+        entity::{
+            grapple_anchor_holds, grapple_attaches_to, grapple_hook_step, grapple_wrap_pivot,
+            CutCopy, GameEntity, Hitbox, Inventory, PhysicsEntity, Player, PlayerClipboardState,
+            PlayerGrappleState, PlayerJumpState, PlayerLaunchState, PlayerMovementMode,
+        },
+        material::{
+            buf::MaterialBuf,
+            placer::{self, MaterialPlacerSampler},
+            MaterialInstance, PhysicsType,
+        },
+        particle::Particle,
+        timelapse::Timelapse,
+        Camera, Position, Velocity, World,
     },
-    material::{buf::MaterialBuf, MaterialInstance, PhysicsType},
-    Camera, Position, Velocity, World,
+    FileHelper, Rect, Registries, Settings,
 };
 
-use crate::{render::Renderer, ui::DebugUIs};
+use crate::{
+    render::Renderer,
+    ui::{DebugUIs, HotbarUI},
+};
 
 use super::{
     input::{Controls, InputEvent},
     ui::MainMenu,
-    world::{ClientChunk, ClientWorld},
+    world::{
+        edit_prediction::EditPredictions,
+        spectator::{movement_dir, pan_delta, Spectator},
+        ClientChunk, ClientWorld,
+    },
 };
 
+/// How far around the cursor the pour and siphon tools reach, in pixels.
+const LIQUID_TOOL_RADIUS: u16 = 3;
+
 pub struct Client {
     pub world: Option<ClientWorld>,
     pub controls: Controls,
     pub camera_scale: f64,
+    /// Where smooth zoom is heading, and the cursor position (in the window) it zooms towards,
+    ///   see [`Settings::camera_zoom_duration`].
+    pub zoom_target: Option<(f64, (f64, f64))>,
     pub mouse_joint: Option<(RigidBodyHandle, Vector2<f32>)>,
     pub main_menu: MainMenu,
     pub debug_ui: Option<DebugUIs>,
+    pub hotbar: HotbarUI,
+    /// Set while the camera is detached from the player, see [`Spectator`].
+    pub spectator: Option<Spectator>,
+    /// Set while [`Settings::timelapse`] is on.
+    pub timelapse: Option<Timelapse>,
+    /// Edits made while connected to a server that it hasn't confirmed yet.
+    pub edit_predictions: EditPredictions,
 }
 
 impl Client {
@@ -37,12 +68,17 @@ impl Client {
             world: None,
             controls: Controls::default(),
             camera_scale: 2.0,
+            zoom_target: None,
             mouse_joint: None,
             main_menu: MainMenu {
                 state: super::ui::MainMenuState::Main,
                 action_queue: Vec::new(),
             },
             debug_ui: None,
+            hotbar: HotbarUI::new(),
+            spectator: None,
+            timelapse: None,
+            edit_predictions: EditPredictions::new(),
         }
     }
 
@@ -50,16 +86,209 @@ impl Client {
         self.debug_ui = Some(DebugUIs::new());
     }
 
-    pub fn tick(&mut self, world: &mut World<ClientChunk>, renderer: &mut Renderer) {
+    pub fn tick(
+        &mut self,
+        world: &mut World<ClientChunk>,
+        renderer: &mut Renderer,
+        settings: &Settings,
+    ) {
         if let Some(cw) = &mut self.world {
             cw.tick(world);
 
-            tick_player(world, cw, renderer, &mut self.controls, self.camera_scale);
+            // with nothing to follow, the movement keys pan the camera instead
+            if self.spectator.is_some() || cw.local_entity.is_none() {
+                let delta = pan_delta(
+                    movement_dir(&mut self.controls),
+                    self.controls.cur_modifiers.shift(),
+                    self.camera_scale,
+                    settings,
+                );
+                Spectator::fly(&world.ecs, delta);
+            } else {
+                tick_player(
+                    world,
+                    cw,
+                    renderer,
+                    &mut self.controls,
+                    self.camera_scale,
+                    settings,
+                );
+            }
 
             world.ecs.maintain();
         }
     }
 
+    /// Zooms by `scroll` notches of the scroll wheel towards `cursor` (in window coordinates), see [`zoom_camera`].
+    ///
+    /// With smooth zoom on, this only moves the target, and [`Self::update_zoom`] animates towards it.
+    pub fn scroll_zoom(
+        &mut self,
+        scroll: i32,
+        snap: bool,
+        cursor: (f64, f64),
+        settings: &Settings,
+    ) {
+        if settings.camera_zoom_duration > 0.0 {
+            // scrolling again before getting there zooms further from where it was heading
+            let from = self
+                .zoom_target
+                .map_or(self.camera_scale, |(target, _)| target);
+            self.zoom_target = Some((zoom_camera(from, scroll, snap, settings), cursor));
+        } else {
+            self.camera_scale = zoom_camera(self.camera_scale, scroll, snap, settings);
+        }
+    }
+
+    /// Moves [`Self::camera_scale`] `dt` seconds closer to [`Self::zoom_target`], moving the camera
+    ///   so whatever is under the cursor stays there.
+    pub fn update_zoom(
+        &mut self,
+        world: Option<&World<ClientChunk>>,
+        window_size: (f64, f64),
+        dt: f64,
+        settings: &Settings,
+    ) {
+        let Some((target, cursor)) = self.zoom_target else {
+            return;
+        };
+
+        let scale = ease_zoom(self.camera_scale, target, dt, settings.camera_zoom_duration);
+        if let Some(world) = world {
+            let (mut position_storage, camera_storage) = world
+                .ecs
+                .system_data::<(WriteStorage<Position>, ReadStorage<Camera>)>();
+            if let Some(camera) = (&mut position_storage, &camera_storage)
+                .join()
+                .map(|(p, _c)| p)
+                .next()
+            {
+                zoom_toward(camera, cursor, window_size, self.camera_scale, scale);
+            }
+        }
+
+        self.camera_scale = scale;
+        if scale == target {
+            self.zoom_target = None;
+        }
+    }
+
+    /// Switches between following the player and flying the camera around freely.
+    pub fn toggle_spectator(&mut self, world: &World<ClientChunk>) {
+        match self.spectator.take() {
+            Some(spectator) => spectator.stop(&world.ecs),
+            None => self.spectator = Some(Spectator::start(&world.ecs)),
+        }
+    }
+
+    /// Starts and stops recording a [`Timelapse`] to match [`Settings::timelapse`], and captures
+    ///   a frame of the screen (or [`Settings::timelapse_region`]) when one is due.
+    ///
+    /// Each recording goes in its own folder under `timelapse/` in the game directory.
+    pub fn tick_timelapse(
+        &mut self,
+        world: &World<ClientChunk>,
+        settings: &Settings,
+        tick_time: u32,
+        file_helper: &FileHelper,
+    ) {
+        if !settings.timelapse {
+            self.timelapse = None;
+            return;
+        }
+
+        if self.timelapse.is_none() {
+            let started = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            match Timelapse::new(file_helper.game_path(format!("timelapse/{started}/"))) {
+                Ok(timelapse) => {
+                    log::info!("Recording timelapse to {:?}", timelapse.dir);
+                    self.timelapse = Some(timelapse);
+                },
+                Err(e) => {
+                    log::error!("{e}");
+                    return;
+                },
+            }
+        }
+
+        let region = if settings.timelapse_fixed_region {
+            settings.timelapse_region
+        } else {
+            let (cameras, positions) = world
+                .ecs
+                .system_data::<(ReadStorage<Camera>, ReadStorage<Position>)>();
+            let Some((_, pos)) = (&cameras, &positions).join().next() else {
+                return;
+            };
+            world.chunk_handler.get_screen_zone((pos.x, pos.y))
+        };
+
+        if let Some(timelapse) = &mut self.timelapse {
+            timelapse.capture(
+                &world.chunk_handler,
+                tick_time,
+                settings.timelapse_interval,
+                region,
+            );
+        }
+    }
+
+    /// Pours the active hotbar material at the cursor while [`Controls::pour`] is held (if it's a
+    ///   liquid), and siphons liquid away while [`Controls::siphon`] is held.
+    pub fn tick_liquid_tools(
+        &mut self,
+        world: &mut World<ClientChunk>,
+        renderer: &Renderer,
+        registries: &Registries,
+    ) {
+        let Some(cw) = &self.world else {
+            return;
+        };
+        let (pour, siphon) = (self.controls.pour.get(), self.controls.siphon.get());
+        if !pour && !siphon {
+            return;
+        }
+
+        let camera_pos = {
+            let (positions, cameras) = world
+                .ecs
+                .system_data::<(ReadStorage<Position>, ReadStorage<Camera>)>();
+            (&positions, &cameras)
+                .join()
+                .map(|(p, _c)| p.clone())
+                .next()
+        };
+        let Some(camera_pos) = camera_pos else {
+            return;
+        };
+        let (world_x, world_y) = screen_to_world(
+            &camera_pos,
+            (self.controls.cursor_pos.x, self.controls.cursor_pos.y),
+            renderer.window_size(),
+            self.camera_scale,
+        );
+        let (x, y) = (world_x as i64, world_y as i64);
+
+        if pour {
+            let liquid = cw
+                .local_entity
+                .and_then(|eid| {
+                    let inventories = world.ecs.read_storage::<Inventory>();
+                    inventories.get(eid).and_then(|inv| inv.active().cloned())
+                })
+                .and_then(|id| registries.material_placers.get(&id))
+                .map(|placer| placer.pixel(x, y));
+            if let Some(liquid) = liquid.filter(|m| m.physics == PhysicsType::Liquid) {
+                let _ = world.pour_liquid(x, y, LIQUID_TOOL_RADIUS, &liquid, &registries.materials);
+            }
+        }
+        if siphon {
+            let _ = world.siphon_liquid(x, y, LIQUID_TOOL_RADIUS);
+        }
+    }
+
     pub fn on_event(&mut self, event: &WindowEvent) -> bool {
         self.controls.process(&InputEvent::GlutinEvent(event));
         false
@@ -79,6 +308,7 @@ fn tick_player(
     renderer: &mut Renderer,
     controls: &mut Controls,
     camera_scale: f64,
+    settings: &Settings,
 ) {
     if let Some(eid) = cw.local_entity {
         let (
@@ -89,8 +319,9 @@ fn tick_player(
             mut velocity_storage,
             mut position_storage,
             mut hitbox_storage,
-            mut collision_storage,
             camera_storage,
+            mut inventory_storage,
+            mut edit_events,
         ) = world.ecs.system_data::<(
             Entities,
             WriteStorage<Player>,
@@ -99,8 +330,9 @@ fn tick_player(
             WriteStorage<Velocity>,
             WriteStorage<Position>,
             WriteStorage<Hitbox>,
-            WriteStorage<CollisionDetector>,
             ReadStorage<Camera>,
+            WriteStorage<Inventory>,
+            Write<WorldEditEvents>,
         )>();
 
         let player = player
@@ -218,25 +450,23 @@ fn tick_player(
                                             Hitbox { x1: -4.0, y1: -4.0, x2: 4.0, y2: 4.0 },
                                             &mut hitbox_storage,
                                         )
+                                        // the hook attaches by raycasting along its path instead
                                         .with(
                                             PhysicsEntity {
                                                 gravity: 0.0,
                                                 on_ground: false,
                                                 edge_clip_distance: 0.0,
-                                                collision: true,
+                                                collision: false,
                                                 collide_with_sand: false,
                                             },
                                             &mut phys_ent_storage,
                                         )
                                         .with(GameEntity, &mut game_ent_storage)
-                                        .with(
-                                            CollisionDetector { collided: false },
-                                            &mut collision_storage,
-                                        )
                                         .build();
 
                                     *grapple_state = PlayerGrappleState::Out {
                                         entity,
+                                        anchor: None,
                                         can_cancel: false,
                                         tether_length: 0.0,
                                         desired_tether_length: 0.0,
@@ -247,12 +477,35 @@ fn tick_player(
                         },
                         PlayerGrappleState::Out {
                             entity,
+                            anchor,
                             can_cancel,
                             tether_length,
                             desired_tether_length,
                             pivots,
                         } => {
-                            if let Some(col) = collision_storage.get_mut(*entity) {
+                            let mut released = false;
+                            if let (Some(hook_pos), Some(hook_vel)) = (
+                                position_storage.get_mut(*entity),
+                                velocity_storage.get_mut(*entity),
+                            ) {
+                                match *anchor {
+                                    None => {
+                                        *anchor = grapple_hook_step(
+                                            &world.chunk_handler,
+                                            hook_pos,
+                                            hook_vel,
+                                        );
+                                    },
+                                    Some(a) => {
+                                        released = !grapple_anchor_holds(&world.chunk_handler, a);
+                                    },
+                                }
+                            }
+
+                            if released {
+                                // the pixel holding it was destroyed
+                                *grapple_state = PlayerGrappleState::Cancelled { entity: *entity };
+                            } else if position_storage.get(*entity).is_some() {
                                 let dx = pivots
                                     .last()
                                     .unwrap_or_else(|| position_storage.get(*entity).unwrap())
@@ -266,40 +519,17 @@ fn tick_player(
                                 let mag = (dx * dx + dy * dy).sqrt();
 
                                 let raycast_filter = |_pos: (i64, i64), mat: &MaterialInstance| {
-                                    mat.physics == PhysicsType::Solid
+                                    grapple_attaches_to(mat)
                                 };
 
-                                if let Some(r) = world.raycast(
-                                    position_storage.get(eid).unwrap().x as i64,
-                                    position_storage.get(eid).unwrap().y as i64,
-                                    pivots
-                                        .last()
-                                        .unwrap_or_else(|| position_storage.get(*entity).unwrap())
-                                        .x as i64,
+                                if let Some(pivot) = grapple_wrap_pivot(
+                                    &world.chunk_handler,
+                                    position_storage.get(eid).unwrap(),
                                     pivots
                                         .last()
-                                        .unwrap_or_else(|| position_storage.get(*entity).unwrap())
-                                        .y as i64,
-                                    raycast_filter,
+                                        .unwrap_or_else(|| position_storage.get(*entity).unwrap()),
                                 ) {
-                                    let side_1 = world.chunk_handler.pixel(
-                                        r.0 .0 + ((dy / mag) * 2.0) as i64,
-                                        r.0 .1 + ((-dx / mag) * 2.0) as i64,
-                                    );
-                                    // let side_2 = world.chunk_handler.get(r.0.0 + ((-dy / mag) * 1.0) as i64, r.0.1 + ((dx / mag) * 1.0) as i64);
-
-                                    if side_1.is_ok() && side_1.unwrap().physics != PhysicsType::Air
-                                    {
-                                        pivots.push(Position {
-                                            x: r.0 .0 as f64 + (-dy / mag) * 2.0,
-                                            y: r.0 .1 as f64 + (dx / mag) * 2.0,
-                                        });
-                                    } else {
-                                        pivots.push(Position {
-                                            x: r.0 .0 as f64 + (dy / mag) * 2.0,
-                                            y: r.0 .1 as f64 + (-dx / mag) * 2.0,
-                                        });
-                                    }
+                                    pivots.push(pivot);
                                 }
 
                                 #[allow(clippy::collapsible_if)]
@@ -331,7 +561,7 @@ fn tick_player(
                                     }
                                 }
 
-                                if col.collided {
+                                if anchor.is_some() {
                                     if *desired_tether_length == 0.0 {
                                         *desired_tether_length = mag - 10.0;
                                         *tether_length = mag;
@@ -613,9 +843,14 @@ fn tick_player(
             },
         }
 
+        if let Some(inventory) = inventory_storage.get_mut(eid) {
+            select_hotbar_slot(inventory, controls, settings);
+        }
+
         tick_player_clipboard(
             player,
             &mut world.chunk_handler,
+            &mut edit_events,
             renderer,
             position_storage,
             camera_storage,
@@ -625,6 +860,139 @@ fn tick_player(
     }
 }
 
+/// Converts a cursor position in the window to world coordinates, looking through a camera at `camera`.
+pub fn screen_to_world(
+    camera: &Position,
+    cursor: (f64, f64),
+    window_size: (f64, f64),
+    camera_scale: f64,
+) -> (f64, f64) {
+    (
+        camera.x + (cursor.0 - window_size.0 / 2.0) / camera_scale,
+        camera.y + (cursor.1 - window_size.1 / 2.0) / camera_scale,
+    )
+}
+
+/// The camera scale after scrolling `scroll` notches (positive zooms in).
+///
+/// Normally each notch zooms by [`Settings::camera_zoom_step`], zooming out by the inverse of zooming in so
+///   a notch each way gets back to the same scale. With `snap` (holding shift) it moves to the next
+///   whole scale instead, never going below 1.
+pub fn zoom_camera(camera_scale: f64, scroll: i32, snap: bool, settings: &Settings) -> f64 {
+    let (min, max) = (settings.camera_zoom_min, settings.camera_zoom_max);
+    if snap {
+        let v = camera_scale + settings.camera_zoom_step * f64::from(scroll);
+        let v = if scroll > 0 { v.ceil() } else { v.floor() };
+        let hi = max.floor().max(1.0);
+        v.clamp(min.ceil().max(1.0).min(hi), hi)
+    } else {
+        (camera_scale * (1.0 + settings.camera_zoom_step).powi(scroll)).clamp(min, max)
+    }
+}
+
+/// The camera scale after zooming from `scale` towards `target` for `dt` seconds, taking about
+///   `duration` seconds to get there no matter the framerate.
+///
+/// Eases out, evenly in terms of how many times bigger things get, and snaps to `target` once it's close.
+pub fn ease_zoom(scale: f64, target: f64, dt: f64, duration: f64) -> f64 {
+    if duration <= 0.0 {
+        return target;
+    }
+
+    // ~99% of the way there after `duration`
+    let t = 1.0 - (-dt * 4.6 / duration).exp();
+    let next = (scale.ln() + (target.ln() - scale.ln()) * t).exp();
+    if (next / target - 1.0).abs() < 0.001 {
+        target
+    } else {
+        next
+    }
+}
+
+/// Moves `camera` so the world position under `cursor` (in window coordinates) stays put
+///   when the camera scale changes from `old_scale` to `new_scale`.
+pub fn zoom_toward(
+    camera: &mut Position,
+    cursor: (f64, f64),
+    window_size: (f64, f64),
+    old_scale: f64,
+    new_scale: f64,
+) {
+    let (world_x, world_y) = screen_to_world(camera, cursor, window_size, old_scale);
+    camera.x = world_x - (cursor.0 - window_size.0 / 2.0) / new_scale;
+    camera.y = world_y - (cursor.1 - window_size.1 / 2.0) / new_scale;
+}
+
+/// Applies the paint tool to one pixel using the inventory's active material.
+///
+/// In creative this overwrites the pixel and never runs out. In survival, painting with air digs
+/// the pixel out into the inventory, and anything else can only be placed into air and consumes
+/// one from the inventory.
+///
+/// Painting with air destroys the pixel (see [`destroy_pixel`]), adding anything it bursts into to `particles`.
+pub fn paint_pixel(
+    chunk_handler: &mut ChunkHandler<ClientChunk>,
+    inventory: &mut Inventory,
+    particles: &mut Vec<Particle>,
+    x: i64,
+    y: i64,
+    registries: &Registries,
+    survival: bool,
+) {
+    let Some(active) = inventory.active().cloned() else {
+        return;
+    };
+    let still = Velocity { x: 0.0, y: 0.0 };
+
+    if !survival {
+        if active == *placer::AIR_PLACER {
+            let _ = destroy_pixel(chunk_handler, x, y, &still, registries, particles);
+        } else if let Some(placer) = registries.material_placers.get(&active) {
+            let _ = chunk_handler.set_pixel(x, y, placer.pixel(x, y));
+        }
+        return;
+    }
+
+    let Ok(current) = chunk_handler.pixel(x, y) else {
+        return;
+    };
+
+    if active == *placer::AIR_PLACER {
+        if !current.is_empty(&registries.materials) {
+            if let Some(collected) = registries
+                .materials
+                .get(&current.material_id)
+                .and_then(|m| m.placer.clone())
+            {
+                inventory.collect(collected, 1);
+            }
+            let _ = destroy_pixel(chunk_handler, x, y, &still, registries, particles);
+        }
+    } else if current.is_empty(&registries.materials) {
+        if let Some(placer) = inventory
+            .take_active(false)
+            .and_then(|id| registries.material_placers.get(&id))
+        {
+            let _ = chunk_handler.set_pixel(x, y, placer.pixel(x, y));
+        }
+    }
+}
+
+/// Selects the hotbar slot of the number key that was pressed.
+///
+/// Outside of survival this also puts that key's material from [`Settings::brush_palette`] in the slot.
+fn select_hotbar_slot(inventory: &mut Inventory, controls: &mut Controls, settings: &Settings) {
+    for (slot, control) in controls.hotbar.iter_mut().enumerate() {
+        if control.get() {
+            if settings.survival {
+                inventory.select_slot(slot);
+            } else {
+                inventory.select_brush(slot, &settings.brush_palette);
+            }
+        }
+    }
+}
+
 fn tick_player_free_fly(vel: &mut Velocity, controls: &mut Controls) {
     if controls.up.get() {
         vel.y -= 0.7;
@@ -640,9 +1008,11 @@ fn tick_player_free_fly(vel: &mut Velocity, controls: &mut Controls) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tick_player_clipboard(
     player: &mut Player,
     chunk_handler: &mut ChunkHandler<ClientChunk>,
+    edit_events: &mut WorldEditEvents,
     renderer: &mut Renderer,
     position_storage: WriteStorage<Position>,
     camera_storage: ReadStorage<Camera>,
@@ -720,6 +1090,10 @@ fn tick_player_clipboard(
                     };
 
                     if let Ok(buf) = buf {
+                        if *cut_copy == CutCopy::Cut {
+                            edit_events
+                                .emit(Rect::new_wh(x, y, width, height), WorldEditCause::Tool);
+                        }
                         player.clipboard.clipboard = Some(buf);
                     }
                 }
@@ -747,8 +1121,12 @@ fn tick_player_clipboard(
                             / camera_scale;
 
                     if let Some(buf) = &player.clipboard.clipboard {
-                        buf.paste(chunk_handler, world_x as i64, world_y as i64)
-                            .unwrap();
+                        let (x, y) = (world_x as i64, world_y as i64);
+                        buf.paste(chunk_handler, x, y).unwrap();
+                        edit_events.emit(
+                            Rect::new_wh(x, y, i64::from(buf.width), i64::from(buf.height)),
+                            WorldEditCause::Tool,
+                        );
                     }
 
                     player.clipboard.state = PlayerClipboardState::Idle;
@@ -759,3 +1137,168 @@ fn tick_player_clipboard(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        world::{entity::Inventory, material::placer, Position},
+        Settings,
+    };
+    use glutin::event::{
+        DeviceId, ElementState, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
+    };
+
+    use crate::input::{Controls, InputEvent};
+
+    use super::{ease_zoom, screen_to_world, select_hotbar_slot, zoom_camera, zoom_toward, Client};
+
+    #[allow(deprecated)]
+    fn press(controls: &mut Controls, key: VirtualKeyCode) {
+        let event = WindowEvent::KeyboardInput {
+            device_id: unsafe { DeviceId::dummy() },
+            input: KeyboardInput {
+                scancode: 0,
+                state: ElementState::Pressed,
+                virtual_keycode: Some(key),
+                modifiers: ModifiersState::empty(),
+            },
+            is_synthetic: true,
+        };
+        controls.process(&InputEvent::GlutinEvent(&event));
+    }
+
+    #[test]
+    fn hotkey_selects_palette_material() {
+        let mut settings = Settings::default();
+        settings.brush_palette.slots[2] = Some(placer::GLASS.clone());
+        let mut controls = Controls::default();
+        let mut inventory = Inventory::default();
+
+        press(&mut controls, VirtualKeyCode::Key3);
+        select_hotbar_slot(&mut inventory, &mut controls, &settings);
+        assert_eq!(inventory.selected_slot, 2);
+        assert_eq!(inventory.active(), Some(&*placer::GLASS));
+
+        // in survival the key only selects the slot, the hotbar holds what was collected
+        let mut controls = Controls::default();
+        let mut inventory = Inventory::default();
+        settings.survival = true;
+        press(&mut controls, VirtualKeyCode::Key3);
+        select_hotbar_slot(&mut inventory, &mut controls, &settings);
+        assert_eq!(inventory.selected_slot, 2);
+        assert_eq!(inventory.active(), None);
+    }
+
+    #[test]
+    fn zoom_honors_settings() {
+        let settings = Settings {
+            camera_zoom_min: 0.001,
+            camera_zoom_max: 32.0,
+            camera_zoom_step: 0.5,
+            ..Settings::default()
+        };
+
+        // smooth zoom is multiplicative and stops at the limits
+        assert_eq!(zoom_camera(2.0, 1, false, &settings), 3.0);
+        assert_eq!(zoom_camera(2.0, -1, false, &settings), 2.0 / 1.5);
+        assert_eq!(zoom_camera(2.0, 2, false, &settings), 4.5);
+        assert_eq!(zoom_camera(30.0, 1, false, &settings), 32.0);
+        let there_and_back =
+            zoom_camera(zoom_camera(2.0, 3, false, &settings), -3, false, &settings);
+        assert!((there_and_back - 2.0).abs() < 1e-9);
+
+        let mut scale = 1.0;
+        for _ in 0..20 {
+            scale = zoom_camera(scale, -1, false, &settings);
+        }
+        assert_eq!(scale, 0.001);
+
+        // snapping goes to whole values, up to the new max
+        assert_eq!(zoom_camera(2.3, 1, true, &settings), 3.0);
+        assert_eq!(zoom_camera(2.3, -1, true, &settings), 1.0);
+        assert_eq!(zoom_camera(31.8, 1, true, &settings), 32.0);
+        assert_eq!(zoom_camera(0.01, -1, true, &settings), 1.0);
+
+        // the old hardcoded limits are the defaults
+        let settings = Settings::default();
+        assert_eq!(zoom_camera(9.5, 1, true, &settings), 10.0);
+        assert_eq!(zoom_camera(10.0, 1, false, &settings), 10.0);
+        assert_eq!(zoom_camera(0.01, -1, false, &settings), 0.01);
+
+        // the biggest step halves the scale zooming out instead of going straight to the minimum
+        let settings = Settings { camera_zoom_step: 1.0, ..Settings::default() };
+        assert_eq!(zoom_camera(2.0, -1, false, &settings), 1.0);
+    }
+
+    #[test]
+    fn zoom_keeps_cursor_in_place() {
+        let window = (1920.0, 1080.0);
+        let cursor = (1500.0, 200.0);
+        let mut camera = Position { x: 40.0, y: -25.0 };
+
+        let before = screen_to_world(&camera, cursor, window, 2.0);
+        zoom_toward(&mut camera, cursor, window, 2.0, 5.0);
+        let after = screen_to_world(&camera, cursor, window, 5.0);
+        assert!((before.0 - after.0).abs() < 1e-9);
+        assert!((before.1 - after.1).abs() < 1e-9);
+
+        // zooming around the middle of the window doesn't move the camera
+        let mut camera = Position { x: 40.0, y: -25.0 };
+        zoom_toward(&mut camera, (960.0, 540.0), window, 2.0, 0.5);
+        assert_eq!((camera.x, camera.y), (40.0, -25.0));
+    }
+
+    #[test]
+    fn zoom_eases_independent_of_framerate() {
+        // the same amount of time gets about as far in big or small steps
+        let slow = ease_zoom(1.0, 8.0, 0.05, 0.15);
+        let mut fast = 1.0;
+        for _ in 0..10 {
+            fast = ease_zoom(fast, 8.0, 0.005, 0.15);
+        }
+        assert!((slow - fast).abs() < 1e-9);
+        assert!(slow > 1.0 && slow < 8.0);
+
+        // and gets there in the end, or right away without a duration
+        let mut scale = 1.0;
+        for _ in 0..60 {
+            scale = ease_zoom(scale, 8.0, 1.0 / 60.0, 0.15);
+        }
+        assert_eq!(scale, 8.0);
+        assert_eq!(ease_zoom(1.0, 8.0, 0.001, 0.0), 8.0);
+    }
+
+    #[test]
+    fn scrolling_accumulates_zoom_target() {
+        let settings = Settings {
+            camera_zoom_step: 0.5,
+            camera_zoom_max: 4.0,
+            camera_zoom_duration: 0.15,
+            ..Settings::default()
+        };
+        let mut client = Client::new();
+        client.camera_scale = 1.0;
+
+        // spinning the wheel quickly zooms further than one notch, up to the limit
+        client.scroll_zoom(1, false, (0.0, 0.0), &settings);
+        client.scroll_zoom(1, false, (0.0, 0.0), &settings);
+        assert_eq!(client.camera_scale, 1.0);
+        assert_eq!(client.zoom_target.map(|(t, _)| t), Some(2.25));
+        for _ in 0..3 {
+            client.scroll_zoom(1, false, (0.0, 0.0), &settings);
+        }
+        assert_eq!(client.zoom_target.map(|(t, _)| t), Some(4.0));
+
+        for _ in 0..60 {
+            client.update_zoom(None, (1920.0, 1080.0), 1.0 / 60.0, &settings);
+        }
+        assert_eq!(client.camera_scale, 4.0);
+        assert!(client.zoom_target.is_none());
+
+        // no duration zooms instantly like before
+        let settings = Settings { camera_zoom_duration: 0.0, ..settings };
+        client.scroll_zoom(-1, false, (0.0, 0.0), &settings);
+        assert_eq!(client.camera_scale, 4.0 / 1.5);
+        assert!(client.zoom_target.is_none());
+    }
+}
@@ -1,9 +1,10 @@
 use std::{
-    io::{BufReader, Read},
     net::TcpStream,
-    time::Instant,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
+use chunksystem::ChunkQuery;
 use glutin::{
     dpi::PhysicalPosition,
     event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode},
@@ -18,39 +19,62 @@ use rapier2d::{
     },
 };
 // use salva2d::{integrations::rapier::ColliderSampling, object::Boundary};
-use specs::{Join, ReadStorage, WriteStorage};
+use specs::{Join, ReadStorage, Write, WriteStorage};
 use sysinfo::{ProcessExt, SystemExt};
 
 use fs_common::game::{
     common::{
         cli::CLArgs,
-        networking::{Packet, PacketType},
+        networking::{chunk_checksum, Packet, PacketType},
         world::{
-            chunk_access::FSChunkAccess, entity::Player, material::placer::MaterialPlacerSampler,
-            physics::PHYSICS_SCALE, Camera, Position, Target, World, WorldNetworkMode,
+            chunk_access::FSChunkAccess,
+            edit_events::{WorldEditCause, WorldEditEvents},
+            entity::{BrushPalette, Inventory, Player, BRUSH_PALETTE_FILE},
+            material::render_layer::RenderLayers,
+            particle::ParticleSystem,
+            physics::PHYSICS_SCALE,
+            Camera, Chunk, Position, Target, World, WorldNetworkMode, WorldPreset,
         },
-        FileHelper, Settings,
+        FileHelper, Rect, Registries, ScheduledTicks, Settings, TickRates, TickScheduler,
     },
-    BuildData, GameData,
+    format_title, BuildData, GameData, TitleStats,
 };
 
 use crate::{
+    network::ServerConnection,
     ui::MainMenuAction,
-    world::{ClientChunkHandlerExt, ClientWorld, ClientWorldExt},
+    window_state::WindowState,
+    world::{heat_haze::MaterialTemperatures, ClientChunkHandlerExt, ClientWorld, ClientWorldExt},
 };
 
-use super::{render::Renderer, world::ClientChunk, Client};
+use super::{
+    client::{paint_pixel, screen_to_world},
+    render::Renderer,
+    world::ClientChunk,
+    Client,
+};
 
 pub struct ClientGame {
     pub data: GameData<ClientChunk>,
     pub client: Client,
+    /// Which layer each material is drawn on, rebuilt when the materials are reloaded.
+    pub render_layers: RenderLayers,
+    /// How hot each material is, for [`Settings::heat_haze`]. Rebuilt along with `render_layers`.
+    pub material_temperatures: MaterialTemperatures,
 }
 
 impl ClientGame {
     pub fn new(file_helper: FileHelper, build_data: BuildData) -> Self {
+        let mut data = GameData::new(file_helper, build_data);
+        data.settings.brush_palette =
+            BrushPalette::load(&data.file_helper.game_path(BRUSH_PALETTE_FILE));
+        let render_layers = RenderLayers::from_registry(&data.registries.materials);
+        let material_temperatures = MaterialTemperatures::from_registry(&data.registries.materials);
         Self {
-            data: GameData::new(file_helper, build_data),
+            data,
             client: Client::new(),
+            render_layers,
+            material_temperatures,
         }
     }
 
@@ -71,14 +95,13 @@ impl ClientGame {
 
         if let Some(addr) = args.connect {
             info!("Connecting to {addr}...");
-            match TcpStream::connect(addr.to_string()).map(BufReader::new) {
-                Ok(mut r) => {
+            match TcpStream::connect(addr.to_string()).and_then(ServerConnection::spawn) {
+                Ok(connection) => {
                     info!("[CLIENT] Connected to server");
 
-                    r.get_mut().set_nonblocking(true).unwrap();
                     self.data.world.as_mut().unwrap().net_mode = WorldNetworkMode::Remote;
 
-                    network = Some(r);
+                    network = Some(connection);
                 },
                 Err(e) => {
                     error!("[CLIENT] Failed to connect to server: {}", e);
@@ -98,8 +121,7 @@ impl ClientGame {
 
         // sdl2::hint::set_video_minimize_on_focus_loss(self.data.settings.minimize_on_lost_focus);
 
-        let mut prev_tick_time = std::time::Instant::now();
-        let mut prev_tick_physics_time = std::time::Instant::now();
+        let mut tick_scheduler = TickScheduler::default();
 
         // let mut event_pump = renderer.sdl.as_ref().unwrap().sdl.event_pump().unwrap();
 
@@ -111,18 +133,14 @@ impl ClientGame {
 
         let mut sys = sysinfo::System::new();
 
-        let mut do_tick_next = false;
-        let mut do_tick_physics_next = false;
-
-        let mut bytes_to_read: Option<u32> = None;
-        let mut read_buffer: Option<Vec<u8>> = None;
-
         let mut cursor_pos: PhysicalPosition<f64> = PhysicalPosition::new(0.0, 0.0);
 
         let mut left_mouse_down = false;
         let mut right_mouse_down = false;
         let mut middle_mouse_down = false;
 
+        let mut window_state = WindowState::default();
+
         event_loop.run(move |event, _, control_flow| {
             profiling::scope!("loop");
 
@@ -136,6 +154,8 @@ impl ClientGame {
                         *control_flow = glutin::event_loop::ControlFlow::Exit;
                     },
                     _ => {
+                        window_state.on_event(w_event);
+
                         if renderer.egui_glium.on_event(w_event).consumed {
                             return;
                         }
@@ -153,6 +173,14 @@ impl ClientGame {
                                     KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F11), state: ElementState::Pressed, .. } => {
                                         self.data.settings.fullscreen = !self.data.settings.fullscreen;
                                     }
+                                    KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F5), state: ElementState::Pressed, .. } => {
+                                        self.reload_materials(&mut renderer);
+                                    }
+                                    KeyboardInput { virtual_keycode: Some(VirtualKeyCode::F6), state: ElementState::Pressed, .. } => {
+                                        if let Some(w) = &self.data.world {
+                                            self.client.toggle_spectator(w);
+                                        }
+                                    }
                                     KeyboardInput { virtual_keycode: Some(VirtualKeyCode::LShift | VirtualKeyCode::RShift), state: ElementState::Pressed, .. } => {
                                         shift_key = true
                                     }
@@ -187,38 +215,70 @@ impl ClientGame {
                                         }
                                     }
                                 } else if middle_mouse_down {
-                                    if let Some(debug_ui) = &self.client.debug_ui {
+                                    if let Some(eid) = self.client.world.as_ref().and_then(|cw| cw.local_entity) {
                                         if let Some(w) = &mut self.data.world {
                                             let (
                                                 position_storage,
                                                 camera_storage,
+                                                mut inventory_storage,
+                                                mut particle_system,
+                                                mut edit_events,
                                             ) = w.ecs.system_data::<(
                                                 ReadStorage<Position>,
                                                 ReadStorage<Camera>,
+                                                WriteStorage<Inventory>,
+                                                Write<ParticleSystem>,
+                                                Write<WorldEditEvents>,
                                             )>();
 
                                             let camera_pos = (&position_storage, &camera_storage)
                                                 .join().map(|(p, _c)| p).next();
 
-                                            if let Some(camera_pos) = camera_pos {
-                                                let world_x = camera_pos.x
-                                                    + (cursor_pos.x
-                                                        - f64::from(renderer.display.gl_window().window().inner_size().width) / 2.0)
-                                                        / self.client.camera_scale;
-                                                let world_y = camera_pos.y
-                                                    + (cursor_pos.y
-                                                        - f64::from(renderer.display.gl_window().window().inner_size().height) / 2.0)
-                                                        / self.client.camera_scale;
+                                            if let (Some(camera_pos), Some(inventory)) = (camera_pos, inventory_storage.get_mut(eid)) {
+                                                let (world_x, world_y) = screen_to_world(
+                                                    camera_pos,
+                                                    (cursor_pos.x, cursor_pos.y),
+                                                    renderer.window_size(),
+                                                    self.client.camera_scale,
+                                                );
 
+                                                let (x, y) = (world_x as i64, world_y as i64);
+                                                // connected to a server, the edits are sent to it too so it can confirm them
+                                                let mut edits = vec![];
                                                 for xx in -3..=3 {
                                                     for yy in -3..=3 {
-                                                        let x = world_x as i64 + xx;
-                                                        let y = world_y as i64 + yy;
-                                                        let _ = w.chunk_handler.set_pixel(x, y,
-                                                            self.data.registries.material_placers.get(&debug_ui.draw.selected).unwrap().pixel(x, y),
+                                                        let before = w.chunk_handler.pixel(x + xx, y + yy).ok().cloned();
+                                                        paint_pixel(
+                                                            &mut w.chunk_handler,
+                                                            inventory,
+                                                            &mut particle_system.active,
+                                                            x + xx,
+                                                            y + yy,
+                                                            &self.data.registries,
+                                                            self.data.settings.survival,
                                                         );
+                                                        if network.is_some() {
+                                                            if let Ok(after) = w.chunk_handler.pixel(x + xx, y + yy) {
+                                                                if before.as_ref() != Some(after) {
+                                                                    edits.push((x + xx, y + yy, after.clone()));
+                                                                }
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                                if let (Some(connection), false) = (&network, edits.is_empty()) {
+                                                    let id = self.client.edit_predictions.predict(edits.clone());
+                                                    let packet = Packet {
+                                                        packet_type: PacketType::EditPixelsPacket { id, pixels: edits },
+                                                    };
+                                                    if let Err(e) = connection.send(&packet) {
+                                                        warn!("[CLIENT] Failed to send edit: {}", e);
                                                     }
                                                 }
+                                                edit_events.emit(
+                                                    Rect::new(x - 3, y - 3, x + 4, y + 4),
+                                                    WorldEditCause::Tool,
+                                                );
                                             }
                                         }
                                     }
@@ -236,14 +296,12 @@ impl ClientGame {
                                             .join().map(|(p, _c)| p).next();
 
                                         if let Some(camera_pos) = camera_pos {
-                                            let world_x = camera_pos.x
-                                                + (cursor_pos.x
-                                                    - f64::from(renderer.display.gl_window().window().inner_size().width) / 2.0)
-                                                    / self.client.camera_scale;
-                                            let world_y = camera_pos.y
-                                                + (cursor_pos.y
-                                                    - f64::from(renderer.display.gl_window().window().inner_size().height) / 2.0)
-                                                    / self.client.camera_scale;
+                                            let (world_x, world_y) = screen_to_world(
+                                                camera_pos,
+                                                (cursor_pos.x, cursor_pos.y),
+                                                renderer.window_size(),
+                                                self.client.camera_scale,
+                                            );
 
                                             if let Some((rb_h, vel)) = &mut self.client.mouse_joint
                                             {
@@ -270,21 +328,12 @@ impl ClientGame {
                                     glutin::event::MouseScrollDelta::PixelDelta(PhysicalPosition { x: _, y }) => y.signum() as i32,
                                 };
 
-                                if shift_key {
-                                    let mut v = self.client.camera_scale + 0.1 * f64::from(y);
-                                    if y > 0 {
-                                        v = v.ceil();
-                                    } else {
-                                        v = v.floor();
-                                    }
-
-                                    v = v.clamp(1.0, 10.0);
-                                    self.client.camera_scale = v;
-                                } else {
-                                    self.client.camera_scale = (self.client.camera_scale
-                                        * (1.0 + 0.1 * f64::from(y)))
-                                    .clamp(0.01, 10.0);
-                                }
+                                self.client.scroll_zoom(
+                                    y,
+                                    shift_key,
+                                    (cursor_pos.x, cursor_pos.y),
+                                    &self.data.settings,
+                                );
 
                             },
                             glutin::event::WindowEvent::MouseInput { state, button, .. } => {
@@ -308,12 +357,12 @@ impl ClientGame {
                                             .join().map(|(p, _c)| p).next();
 
                                         if let Some(camera_pos) = camera_pos {
-                                            let world_x = camera_pos.x
-                                                + (cursor_pos.x - f64::from(renderer.display.gl_window().window().inner_size().width) / 2.0)
-                                                    / self.client.camera_scale;
-                                            let world_y = camera_pos.y
-                                                + (cursor_pos.y - f64::from(renderer.display.gl_window().window().inner_size().height) / 2.0)
-                                                    / self.client.camera_scale;
+                                            let (world_x, world_y) = screen_to_world(
+                                                camera_pos,
+                                                (cursor_pos.x, cursor_pos.y),
+                                                renderer.window_size(),
+                                                self.client.camera_scale,
+                                            );
                                             // let (chunk_x, chunk_y) = w.chunk_handler.pixel_to_chunk_pos(world_x as i64, world_y as i64);
                                             // w.chunk_handler.force_update_chunk(chunk_x, chunk_y);
 
@@ -429,13 +478,18 @@ impl ClientGame {
 
                     // tick
 
-                    let mut can_tick = self.data.settings.tick;
-
-                    let has_focus = true; // TODO
-                    can_tick = can_tick && (has_focus || !self.data.settings.pause_on_lost_focus);
-
-                    if do_tick_next && can_tick {
-                        prev_tick_time = now;
+                    // time spent paused is dropped rather than caught up on, since the scheduler never sees it
+                    let ticks = if window_state.should_tick(&self.data.settings) {
+                        tick_scheduler.advance(
+                            delta,
+                            &self.data.settings,
+                            self.data.world.as_ref().map(|w| &w.rules),
+                        )
+                    } else {
+                        ScheduledTicks::default()
+                    };
+
+                    for _ in 0..ticks.chunks {
                         let st = Instant::now();
                         self.tick(&mut renderer);
 
@@ -454,12 +508,15 @@ impl ClientGame {
                                     }
 
                                     info!("Load world \"{}\"...", world_meta.name);
-                                    self.data.world = Some(World::create(Some(
-                                        path.parent()
-                                            .expect("World meta file has no parent directory ??")
-                                            .to_path_buf(),
+                                    self.data.world = Some(World::load(
+                                        Some(
+                                            path.parent()
+                                                .expect("World meta file has no parent directory ??")
+                                                .to_path_buf(),
                                         ),
                                         Some(3), // TODO: save/load seed from file
+                                        WorldPreset::default(),
+                                        &self.data.registries,
                                     ));
 
                                     if let Some(w) = &mut self.data.world {
@@ -467,9 +524,10 @@ impl ClientGame {
 
                                         self.client.world =
                                             Some(ClientWorld { local_entity: Some(player) });
+                                        self.client.spectator = None;
                                     };
                                 },
-                                MainMenuAction::LoadRandomSeed => {
+                                MainMenuAction::NewWorld(preset) => {
                                     if let Some(w) = &mut self.data.world {
                                         info!("Unload current world...");
                                         w.save().expect("World save failed");
@@ -477,7 +535,14 @@ impl ClientGame {
                                     }
 
                                     info!("Loading new world...");
-                                    self.data.world = Some(World::create(None, None));
+                                    let path = self
+                                        .data
+                                        .file_helper
+                                        .save_roots()
+                                        .first()
+                                        .map(|root| World::<ClientChunk>::new_world_dir(root));
+                                    self.data.world =
+                                        Some(World::load(path, None, preset, &self.data.registries));
                                     info!("Seed is {}", self.data.world.as_ref().unwrap().seed);
 
                                     if let Some(w) = &mut self.data.world {
@@ -487,253 +552,231 @@ impl ClientGame {
 
                                         self.client.world =
                                             Some(ClientWorld { local_entity: Some(player) });
+                                        self.client.spectator = None;
                                     };
                                 }
                             }
                         }
 
-                        if let Some(stream) = &mut network {
-                            let start = Instant::now();
-
-                            // let mut n = 0;
-                            while Instant::now().saturating_duration_since(start).as_nanos() < 5_000_000 {
-                                if bytes_to_read.is_none() {
-                                    let mut buf = [0; 4];
-                                    if stream.read_exact(&mut buf).is_ok() {
-                                        let size: u32 = bincode::deserialize(&buf).unwrap();
-                                        // println!("[CLIENT] Incoming packet, size = {}.", size);
-
-                                        bytes_to_read = Some(size);
-                                        read_buffer = Some(Vec::with_capacity(size as usize));
-                                    }
-                                }
-
-                                if let (Some(size), Some(buf)) = (bytes_to_read, &mut read_buffer) {
-                                    // println!("[CLIENT] size = {}", size);
-                                    if size == 0 {
-                                        // trying_to_read = None;
-                                        panic!("[CLIENT] Zero length packet.");
-                                    } else {
-                                        assert!(size <= 2_000_000, "[CLIENT] Almost tried to read packet that is too big ({size} bytes)");
-
-                                        // let mut buf = vec![0; size as usize];
-
-                                        // println!("[CLIENT] read_to_end...");
-                                        let prev_size = buf.len();
-                                        match std::io::Read::by_ref(stream)
-                                            .take(u64::from(size))
-                                            .read_to_end(buf)
-                                        {
-                                            // match stream.read_exact(&mut buf) {
-                                            Ok(read) => {
-                                                if read != size as usize {
+                        if let Some(connection) = &network {
+                            for packet in connection.received() {
+                                #[allow(unreachable_patterns)]
+                                #[allow(clippy::match_same_arms)]
+                                match packet.packet_type {
+                                    PacketType::SyncChunkPacket {
+                                        chunk_x,
+                                        chunk_y,
+                                        mut pixels,
+                                        mut colors,
+                                    } => {
+                                        if let Some(w) = &mut self.data.world {
+                                            // the server hasn't got to some of our edits yet, keep showing them
+                                            self.client.edit_predictions.reconcile(
+                                                (chunk_x, chunk_y),
+                                                &mut pixels,
+                                                &mut colors,
+                                            );
+                                            if let Err(e) = w.sync_chunk(
+                                                chunk_x, chunk_y, pixels, colors,
+                                            ) {
+                                                warn!("[CLIENT] sync_chunk failed: {}", e);
+                                            }
+                                        }
+                                    },
+                                    PacketType::ChunkChecksumPacket {
+                                        chunk_x,
+                                        chunk_y,
+                                        checksum,
+                                    } => {
+                                        if let Some(w) = &self.data.world {
+                                            let local = w
+                                                .chunk_handler
+                                                .manager
+                                                .chunk_at((chunk_x, chunk_y))
+                                                .and_then(|ch| ch.pixels().as_ref())
+                                                .map(|px| chunk_checksum(&px[..]));
+                                            // missed an update (or never got the chunk), ask for all of it
+                                            if local != Some(checksum) {
+                                                debug!(
+                                                    "[CLIENT] Chunk ({}, {}) is out of sync",
+                                                    chunk_x, chunk_y
+                                                );
+                                                let packet = Packet {
+                                                    packet_type: PacketType::RequestChunkSyncPacket {
+                                                        chunk_x,
+                                                        chunk_y,
+                                                    },
+                                                };
+                                                if let Err(e) = connection.send(&packet) {
                                                     warn!(
-                                                        "[CLIENT] Couldn't read enough bytes! Read {}/{}.",
-                                                        read, size
+                                                        "[CLIENT] Failed to request chunk sync: {}",
+                                                        e
                                                     );
                                                 }
-
-                                                // println!("[CLIENT] Read {}/{} bytes", read, buf.len());
-
-                                                bytes_to_read = None;
-
-                                                // println!("[CLIENT] Read {} bytes.", buf.len());
-                                                match bincode::deserialize::<Packet>(buf) {
-                                                    // match serde_json::from_slice::<Packet>(&buf) {
-                                                    Ok(p) => {
-                                                        // n += 1;
-                                                        #[allow(unreachable_patterns)]
-                                                        #[allow(clippy::match_same_arms)]
-                                                        match p.packet_type {
-                                                            PacketType::SyncChunkPacket {
-                                                                chunk_x,
-                                                                chunk_y,
-                                                                pixels,
-                                                                colors,
-                                                            } => {
-                                                                if let Some(w) = &mut self.data.world {
-                                                                    if let Err(e) = w.sync_chunk(
-                                                                        chunk_x, chunk_y, pixels, colors,
-                                                                    ) {
-                                                                        warn!("[CLIENT] sync_chunk failed: {}", e);
-                                                                    }
-                                                                }
-                                                            },
-                                                            PacketType::SyncLiquidFunPacket {
-                                                                positions: _,
-                                                                velocities: _,
-                                                            } => {
-                                                                // TODO: reimplement for rapier/salva
-                                                                // println!("[CLIENT] Got SyncLiquidFunPacket");
-                                                                // if let Some(w) = &mut self.data.world {
-                                                                //     let mut particle_system = w
-                                                                //         .lqf_world
-                                                                //         .get_particle_system_list()
-                                                                //         .unwrap();
-
-                                                                //     let particle_count = particle_system
-                                                                //         .get_particle_count()
-                                                                //         as usize;
-                                                                //     // let particle_colors: &[b2ParticleColor] = particle_system.get_color_buffer();
-                                                                //     let particle_positions: &mut [Vec2] =
-                                                                //         particle_system
-                                                                //             .get_position_buffer_mut();
-                                                                //     for i in 0..particle_count
-                                                                //         .min(positions.len())
-                                                                //     {
-                                                                //         let dx = positions[i].x
-                                                                //             - particle_positions[i].x;
-                                                                //         let dy = positions[i].y
-                                                                //             - particle_positions[i].y;
-
-                                                                //         if dx.abs() > 1.0 || dy.abs() > 1.0
-                                                                //         {
-                                                                //             particle_positions[i].x += dx;
-                                                                //             particle_positions[i].y += dy;
-                                                                //         } else {
-                                                                //             particle_positions[i].x +=
-                                                                //                 dx / 2.0;
-                                                                //             particle_positions[i].y +=
-                                                                //                 dy / 2.0;
-                                                                //         }
-                                                                //     }
-
-                                                                //     let particle_velocities: &mut [Vec2] =
-                                                                //         particle_system
-                                                                //             .get_velocity_buffer_mut();
-                                                                //     for i in 0..particle_count
-                                                                //         .min(positions.len())
-                                                                //     {
-                                                                //         particle_velocities[i].x =
-                                                                //             velocities[i].x;
-                                                                //         particle_velocities[i].y =
-                                                                //             velocities[i].y;
-                                                                //     }
-                                                                // }
-                                                            },
-                                                            _ => {},
-                                                        }
-                                                    },
-                                                    Err(e) => {
-                                                        warn!(
-                                                            "[CLIENT] Failed to deserialize packet: {}",
-                                                            e
-                                                        );
-                                                        // println!("[CLIENT]     Raw: {:?}", buf);
-                                                        // let s = String::from_utf8(buf);
-                                                        // match s {
-                                                        //     Ok(st) => {
-                                                        //         // println!("[CLIENT]     Raw: {} <- raw", st)
-                                                        //         let mut file = std::fs::File::create("data.dat").expect("create failed");
-                                                        //         file.write_all(&st.into_bytes()).expect("write failed");
-                                                        //         panic!("[CLIENT] See data.dat");
-                                                        //     },
-                                                        //     Err(e) => {
-                                                        //         let index = e.utf8_error().valid_up_to();
-                                                        //         let len = e.utf8_error().error_len().unwrap();
-                                                        //         let sl = &e.as_bytes()[index .. index + len];
-
-                                                        //         let mut file = std::fs::File::create("data.dat").expect("create failed");
-                                                        //         file.write_all(e.as_bytes()).expect("write failed");
-
-                                                        //         panic!("[CLIENT] See data.dat: {} : {:?}", e, sl);
-                                                        //     },
-                                                        // }
-                                                    },
-                                                };
-                                                // println!("[CLIENT] Recieved packet : {:?}", match p.packet_type {
-                                                //     PacketType::SyncChunkPacket{..} => "SyncChunkPacket",
-                                                //     _ => "???",
-                                                // });
-                                            },
-                                            Err(_e) => {
-                                                let read = buf.len() - prev_size;
-                                                // println!("[CLIENT] read_to_end failed (but read {} bytes): {}", read, e);
-                                                bytes_to_read = Some(size - read as u32);
-                                            },
+                                            }
                                         }
-                                    }
+                                    },
+                                    PacketType::EditAckPacket { id } => {
+                                        self.client.edit_predictions.confirm(id);
+                                    },
+                                    PacketType::SyncLiquidFunPacket {
+                                        positions: _,
+                                        velocities: _,
+                                    } => {
+                                        // TODO: reimplement for rapier/salva
+                                        // println!("[CLIENT] Got SyncLiquidFunPacket");
+                                        // if let Some(w) = &mut self.data.world {
+                                        //     let mut particle_system = w
+                                        //         .lqf_world
+                                        //         .get_particle_system_list()
+                                        //         .unwrap();
+
+                                        //     let particle_count = particle_system
+                                        //         .get_particle_count()
+                                        //         as usize;
+                                        //     // let particle_colors: &[b2ParticleColor] = particle_system.get_color_buffer();
+                                        //     let particle_positions: &mut [Vec2] =
+                                        //         particle_system
+                                        //             .get_position_buffer_mut();
+                                        //     for i in 0..particle_count
+                                        //         .min(positions.len())
+                                        //     {
+                                        //         let dx = positions[i].x
+                                        //             - particle_positions[i].x;
+                                        //         let dy = positions[i].y
+                                        //             - particle_positions[i].y;
+
+                                        //         if dx.abs() > 1.0 || dy.abs() > 1.0
+                                        //         {
+                                        //             particle_positions[i].x += dx;
+                                        //             particle_positions[i].y += dy;
+                                        //         } else {
+                                        //             particle_positions[i].x +=
+                                        //                 dx / 2.0;
+                                        //             particle_positions[i].y +=
+                                        //                 dy / 2.0;
+                                        //         }
+                                        //     }
+
+                                        //     let particle_velocities: &mut [Vec2] =
+                                        //         particle_system
+                                        //             .get_velocity_buffer_mut();
+                                        //     for i in 0..particle_count
+                                        //         .min(positions.len())
+                                        //     {
+                                        //         particle_velocities[i].x =
+                                        //             velocities[i].x;
+                                        //         particle_velocities[i].y =
+                                        //             velocities[i].y;
+                                        //     }
+                                        // }
+                                    },
+                                    _ => {},
                                 }
                             }
-                            // println!("[CLIENT] Handled {} packets.", n);
                         }
 
                         self.data.fps_counter.tick_times.rotate_left(1);
                         self.data.fps_counter.tick_times[self.data.fps_counter.tick_times.len() - 1] =
                             Instant::now().saturating_duration_since(st).as_nanos() as f32;
                     }
-                    do_tick_next = can_tick
-                        && now.saturating_duration_since(prev_tick_time).as_nanos()
-                            > 1_000_000_000 / u128::from(self.data.settings.tick_speed); // intended is 30 ticks per second
 
-                    // tick liquidfun
+                    // tick particles
 
-                    let mut can_tick = self.data.settings.tick_physics;
+                    if let Some(w) = &mut self.data.world {
+                        for _ in 0..ticks.particles {
+                            w.tick_particles(&self.data.settings, &self.data.registries);
+                        }
+                    }
 
-                    let has_focus = true; // TODO
-                    can_tick = can_tick && !(self.data.settings.pause_on_lost_focus && has_focus);
+                    // tick liquidfun
 
-                    if do_tick_physics_next && can_tick {
-                        prev_tick_physics_time = now;
+                    for _ in 0..ticks.physics {
                         if let Some(w) = &mut self.data.world {
                             let st = Instant::now();
-                            w.tick_physics(&self.data.settings);
+                            w.tick_physics(&self.data.settings, &self.data.registries);
                             self.data.fps_counter.tick_physics_times.rotate_left(1);
                             self.data.fps_counter.tick_physics_times
                                 [self.data.fps_counter.tick_physics_times.len() - 1] =
                                 Instant::now().saturating_duration_since(st).as_nanos() as f32;
                         }
                     }
-                    do_tick_physics_next = can_tick
-                        && now
-                            .saturating_duration_since(prev_tick_physics_time)
-                            .as_nanos()
-                            > 1_000_000_000 / u128::from(self.data.settings.tick_physics_speed); // intended is 60 ticks per second
 
                     // render
 
+                    // nothing can be seen while minimized, so just check back in a bit
+                    if !window_state.should_render() {
+                        *control_flow = glutin::event_loop::ControlFlow::WaitUntil(
+                            now + std::time::Duration::from_millis(100),
+                        );
+                        counter_last_frame = Instant::now();
+                        return;
+                    }
+
                     if let Some(w) = &mut self.data.world {
-                        w.frame(delta); // this delta is more accurate than the one based on counter_last_frame
+                        Camera::apply_settings(&mut w.ecs, &self.data.settings);
+                        Player::apply_settings(&w.ecs, &self.data.settings);
+                        let (window_w, window_h) = renderer.window_size();
+                        let view_size = (
+                            window_w / self.client.camera_scale,
+                            window_h / self.client.camera_scale,
+                        );
+                        w.frame(delta, Some(view_size)); // this delta is more accurate than the one based on counter_last_frame
                     }
+                    self.client.update_zoom(
+                        self.data.world.as_ref(),
+                        renderer.window_size(),
+                        delta.as_secs_f64(),
+                        &self.data.settings,
+                    );
 
                     {
                         profiling::scope!("rendering");
 
-                        let partial_ticks = (now.saturating_duration_since(prev_tick_time).as_secs_f64()
-                            / (1.0 / f64::from(self.data.settings.tick_speed)))
-                        .clamp(0.0, 1.0);
+                        let rates = TickRates::new(&self.data.settings, self.data.world.as_ref().map(|w| &w.rules));
+                        let partial_ticks = tick_scheduler.chunks.partial_ticks(rates.chunks);
                         let delta_time = Instant::now().saturating_duration_since(counter_last_frame);
 
                         self.render(&mut renderer, delta_time.as_secs_f64(), partial_ticks);
 
                         self.data.frame_count += 1;
                         self.data.fps_counter.frames += 1;
-                        if now
-                            .saturating_duration_since(self.data.fps_counter.last_update)
-                            .as_millis()
-                            >= 1000
-                        {
-                            self.data.fps_counter.display_value = self.data.fps_counter.frames;
-                            self.data.fps_counter.frames = 0;
-                            self.data.fps_counter.last_update = now;
-                            renderer.display.gl_window().window().set_title(
-                                format!(
-                                    "FallingSandRust ({} FPS) ({})",
-                                    self.data.fps_counter.display_value,
-                                    self.data.world.as_ref().map_or_else(
-                                        || "unknown".to_owned(),
-                                        |w| format!("{:?}", w.net_mode)
-                                    )
-                                )
-                                .as_str(),
-                            );
-
+                        if self.data.fps_counter.sample(
+                            now,
+                            Duration::from_millis(self.data.settings.stats_interval_ms.into()),
+                        ) {
                             sys.refresh_process(sysinfo::get_current_pid().unwrap());
                             if let Some(pc) = sys.process(sysinfo::get_current_pid().unwrap()) {
-                                self.data.process_stats.cpu_usage =
-                                    Some(pc.cpu_usage() / sys.cpus().len() as f32 * 2.0);
-                                self.data.process_stats.memory = Some(pc.memory());
+                                self.data.process_stats.record(
+                                    pc.cpu_usage() / sys.cpus().len() as f32 * 2.0,
+                                    pc.memory(),
+                                );
                             }
+
+                            let net_mode = self.data.world.as_ref().map_or_else(
+                                || "unknown".to_owned(),
+                                |w| format!("{:?}", w.net_mode),
+                            );
+                            let world_name = self
+                                .data
+                                .world
+                                .as_ref()
+                                .and_then(|w| w.path.as_ref())
+                                .and_then(|p| p.file_name())
+                                .map_or_else(
+                                    || "unsaved".to_owned(),
+                                    |n| n.to_string_lossy().into_owned(),
+                                );
+                            renderer.display.gl_window().window().set_title(&format_title(
+                                &self.data.settings.window_title,
+                                &TitleStats {
+                                    fps: self.data.fps_counter.display_value,
+                                    net_mode: &net_mode,
+                                    world_name: &world_name,
+                                    tick_time: self.data.tick_time,
+                                    process: &self.data.process_stats,
+                                },
+                            ));
                         }
                     }
 
@@ -747,7 +790,7 @@ impl ClientGame {
                     profiling::finish_frame!();
 
                     // sleep a bit if we aren't going to tick next frame
-                    if !do_tick_next && !self.data.settings.vsync {
+                    if ticks.chunks == 0 && !self.data.settings.vsync {
                         profiling::scope!("sleep");
                         // ::std::thread::sleep(Duration::new(0, 1_000_000)); // 1ms sleep so the computer doesn't explode
                     }
@@ -770,19 +813,58 @@ impl ClientGame {
         renderer.render(&mut self.data, &mut self.client, delta_time, partial_ticks);
     }
 
+    /// Reloads the registries from disk and updates the world to match,
+    ///   so material changes show up without restarting.
+    ///
+    /// Anything still holding the old registries (like a world tick in progress) keeps using them
+    ///   until it's done.
+    fn reload_materials(&mut self, renderer: &mut Renderer) {
+        let registries = Arc::new(Registries::init(&self.data.file_helper));
+        self.render_layers = RenderLayers::from_registry(&registries.materials);
+        self.material_temperatures = MaterialTemperatures::from_registry(&registries.materials);
+        renderer.world_renderer.reload_materials();
+
+        if let Some(w) = &mut self.data.world {
+            let changed = w.remap_materials(&registries);
+            for ch in w.chunk_handler.manager.chunks_iter_mut() {
+                ch.graphics.relayer();
+            }
+            info!("Reloaded materials, {changed} pixels changed");
+        } else {
+            info!("Reloaded materials");
+        }
+        self.data.registries = registries;
+    }
+
     #[profiling::function]
     fn tick(&mut self, renderer: &mut Renderer) {
         self.data.tick_time += 1;
 
         if let Some(w) = &mut self.data.world {
-            self.client.tick(w, renderer);
+            self.client.tick(w, renderer, &self.data.settings);
+            self.client
+                .tick_liquid_tools(w, renderer, &self.data.registries);
             w.tick(
                 self.data.tick_time,
                 &self.data.settings,
                 self.data.registries.clone(),
                 &self.data.file_helper,
             );
-            w.chunk_handler.update_chunk_graphics(&renderer.shaders);
+            w.chunk_handler.update_chunk_graphics(
+                &renderer.shaders,
+                &renderer.chunk_textures,
+                &self.render_layers,
+                self.data
+                    .settings
+                    .heat_haze
+                    .then_some(&self.material_temperatures),
+            );
+            self.client.tick_timelapse(
+                w,
+                &self.data.settings,
+                self.data.tick_time,
+                &self.data.file_helper,
+            );
         }
     }
 }
@@ -25,10 +25,16 @@ pub struct Controls {
 
     pub free_fly: Box<dyn Control<bool>>,
 
+    pub pour: Box<dyn Control<bool>>,
+    pub siphon: Box<dyn Control<bool>>,
+
     pub copy: Box<dyn Control<bool>>,
     pub cut: Box<dyn Control<bool>>,
     pub paste: Box<dyn Control<bool>>,
     pub clipboard_action: Box<dyn Control<bool>>,
+
+    /// One per hotbar slot
+    pub hotbar: Vec<Box<dyn Control<bool>>>,
 }
 
 impl Controls {
@@ -56,10 +62,17 @@ impl Controls {
 
         self.free_fly.process(event, &self.cur_modifiers);
 
+        self.pour.process(event, &self.cur_modifiers);
+        self.siphon.process(event, &self.cur_modifiers);
+
         self.copy.process(event, &self.cur_modifiers);
         self.cut.process(event, &self.cur_modifiers);
         self.paste.process(event, &self.cur_modifiers);
         self.clipboard_action.process(event, &self.cur_modifiers);
+
+        for slot in &mut self.hotbar {
+            slot.process(event, &self.cur_modifiers);
+        }
     }
 }
 
@@ -171,6 +184,16 @@ impl Default for Controls {
                 KeyControlMode::Rising,
                 ModifiersState::empty(),
             )),
+            pour: Box::new(KeyControl::new(
+                VirtualKeyCode::Q,
+                KeyControlMode::Momentary,
+                ModifiersState::empty(),
+            )),
+            siphon: Box::new(KeyControl::new(
+                VirtualKeyCode::E,
+                KeyControlMode::Momentary,
+                ModifiersState::empty(),
+            )),
             copy: Box::new(MultiControl::new(
                 MultiControlMode::Or,
                 vec![Box::new(KeyControl::new(
@@ -200,6 +223,26 @@ impl Default for Controls {
                 MouseButtonControlMode::Momentary,
                 ModifiersState::CTRL,
             )),
+            hotbar: [
+                VirtualKeyCode::Key1,
+                VirtualKeyCode::Key2,
+                VirtualKeyCode::Key3,
+                VirtualKeyCode::Key4,
+                VirtualKeyCode::Key5,
+                VirtualKeyCode::Key6,
+                VirtualKeyCode::Key7,
+                VirtualKeyCode::Key8,
+                VirtualKeyCode::Key9,
+            ]
+            .into_iter()
+            .map(|key| {
+                Box::new(KeyControl::new(
+                    key,
+                    KeyControlMode::Rising,
+                    ModifiersState::empty(),
+                )) as Box<dyn Control<bool>>
+            })
+            .collect(),
         }
     }
 }
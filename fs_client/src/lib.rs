@@ -1,8 +1,10 @@
 mod client;
 mod game;
 pub mod input;
+mod network;
 pub mod render;
 pub mod ui;
+mod window_state;
 pub mod world;
 
 pub use client::*;
@@ -0,0 +1,92 @@
+use std::{
+    io::{ErrorKind, Read},
+    net::{Shutdown, TcpStream},
+    sync::mpsc::{self, Receiver},
+    thread::JoinHandle,
+};
+
+use fs_common::game::common::networking::{write_packet, Packet, PacketAssembler};
+use log::{error, info, warn};
+
+/// The connection to the server.
+///
+/// Packets are read on a worker thread, so a slow connection never holds up the tick.
+/// The worker blocks on the socket and sends every complete [`Packet`] over a channel,
+///   which the tick loop drains with [`ServerConnection::received`].
+pub struct ServerConnection {
+    stream: TcpStream,
+    packets: Receiver<Packet>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ServerConnection {
+    pub fn spawn(stream: TcpStream) -> std::io::Result<Self> {
+        let mut reader = stream.try_clone()?;
+        let (packets_tx, packets) = mpsc::channel();
+
+        let worker = std::thread::Builder::new()
+            .name("packet reader".to_string())
+            .spawn(move || {
+                let mut assembler = PacketAssembler::default();
+                let mut buf = vec![0; 64 * 1024];
+                loop {
+                    let read = match reader.read(&mut buf) {
+                        Ok(0) => {
+                            info!("[CLIENT] Server closed the connection");
+                            return;
+                        },
+                        Ok(read) => read,
+                        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                        Err(e) => {
+                            warn!("[CLIENT] Lost connection to server: {}", e);
+                            return;
+                        },
+                    };
+                    assembler.push(&buf[..read]);
+
+                    loop {
+                        let body = match assembler.next_body() {
+                            Ok(Some(body)) => body,
+                            Ok(None) => break,
+                            Err(e) => {
+                                error!("[CLIENT] Bad packet stream, disconnecting: {}", e);
+                                return;
+                            },
+                        };
+
+                        match bincode::deserialize::<Packet>(&body) {
+                            Ok(packet) => {
+                                // the client stopped listening
+                                if packets_tx.send(packet).is_err() {
+                                    return;
+                                }
+                            },
+                            Err(e) => warn!("[CLIENT] Failed to deserialize packet: {}", e),
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self { stream, packets, worker: Some(worker) })
+    }
+
+    /// Takes every packet that has arrived so far, oldest first.
+    pub fn received(&self) -> impl Iterator<Item = Packet> + '_ {
+        self.packets.try_iter()
+    }
+
+    /// Sends a packet to the server, blocking until it's written.
+    pub fn send(&self, packet: &Packet) -> std::io::Result<()> {
+        write_packet(&mut &self.stream, packet)
+    }
+}
+
+impl Drop for ServerConnection {
+    fn drop(&mut self) {
+        // wakes the worker up from the blocking read
+        let _ignore = self.stream.shutdown(Shutdown::Both);
+        if let Some(worker) = self.worker.take() {
+            let _ignore = worker.join();
+        }
+    }
+}
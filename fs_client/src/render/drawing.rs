@@ -1,12 +1,17 @@
 use std::sync::Arc;
 
 use fs_common::game::common::{
-    world::{material::color::Color, particle::Particle, CHUNK_SIZE},
-    Rect,
+    world::{material::color::Color, particle::Particle, CHUNK_HEIGHT, CHUNK_WIDTH},
+    PixelFilter, Rect,
 };
 use glium::{
-    implement_vertex, index::NoIndices, texture::Texture2dArray, uniform, Blend, Display,
-    DrawParameters, Frame, IndexBuffer, PolygonMode, Surface, SwapBuffersError, Texture2d,
+    implement_vertex,
+    index::NoIndices,
+    texture::Texture2dArray,
+    uniform,
+    uniforms::{MagnifySamplerFilter, SamplerWrapFunction},
+    Blend, Display, DrawParameters, Frame, IndexBuffer, PolygonMode, Surface, SwapBuffersError,
+    Texture2d,
 };
 use glium_glyph::{
     glyph_brush::{ab_glyph::FontVec, Section},
@@ -27,9 +32,19 @@ pub struct RenderTarget<'a, 'b> {
     pub transform: TransformStack,
     pub base_transform: TransformStack,
     pub shaders: &'a Shaders,
+    /// Used for chunk and image textures, set from the settings every frame.
+    pub pixel_filter: PixelFilter,
     glyph_brush: &'a mut GlyphBrush<'b, FontVec>,
 }
 
+/// The sampler filter used to draw pixels with `filter`.
+pub fn magnify_filter(filter: PixelFilter) -> MagnifySamplerFilter {
+    match filter {
+        PixelFilter::Nearest => MagnifySamplerFilter::Nearest,
+        PixelFilter::Linear => MagnifySamplerFilter::Linear,
+    }
+}
+
 pub trait Vertices {
     fn vertices(&self) -> Vec<Vertex2>;
 }
@@ -81,6 +96,7 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             transform: TransformStack::new(),
             base_transform: TransformStack::new(),
             shaders,
+            pixel_filter: PixelFilter::Nearest,
             glyph_brush,
         }
     }
@@ -330,6 +346,40 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
         }
     }
 
+    /// Draws a rectangle that fades from `top` at `y1` to `bottom` at `y2`.
+    pub fn rectangle_gradient(
+        &mut self,
+        rect: Rect<f32>,
+        top: Color,
+        bottom: Color,
+        param: DrawParameters,
+    ) {
+        let model_view =
+            *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
+        let view: [[f32; 4]; 4] = model_view.into();
+
+        let shape: Vec<Vertex2C> = vec![
+            ((rect.x1, rect.y1), top).into(),
+            ((rect.x2, rect.y1), top).into(),
+            ((rect.x2, rect.y2), bottom).into(),
+            ((rect.x1, rect.y1), top).into(),
+            ((rect.x2, rect.y2), bottom).into(),
+            ((rect.x1, rect.y2), bottom).into(),
+        ];
+        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
+        let indices = NoIndices(glium::index::PrimitiveType::TrianglesList);
+
+        self.frame
+            .draw(
+                &vertex_buffer,
+                indices,
+                &self.shaders.vertex_colors,
+                &uniform! { matrix: view },
+                &param,
+            )
+            .unwrap();
+    }
+
     pub fn rectangles_colored(&mut self, rects: &[(Rect<f32>, Color)], param: DrawParameters) {
         let model_view =
             *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
@@ -428,22 +478,7 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             .map(Vertex2T::from)
             .collect::<Vec<_>>();
 
-        let model_view =
-            *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
-        let view: [[f32; 4]; 4] = model_view.into();
-
-        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
-        let indices = IndexBuffer::new(
-            &self.display,
-            glium::index::PrimitiveType::TriangleStrip,
-            &[1_u16, 2, 0, 3],
-        )
-        .unwrap();
-
-        {
-            profiling::scope!("draw");
-            self.frame.draw(&vertex_buffer, &indices, &self.shaders.texture, &uniform! { matrix: view, tex: texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest) }, &param).unwrap();
-        }
+        self.draw_textured_quad(&shape, texture, param);
     }
 
     #[profiling::function]
@@ -461,11 +496,22 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             .map(Vertex2T::from)
             .collect::<Vec<_>>();
 
+        self.draw_textured_quad(&shape, texture, param);
+    }
+
+    /// Draws `texture` on a quad with the corners in the same order as [`Vertices::vertices`].
+    #[profiling::function]
+    pub fn draw_textured_quad(
+        &mut self,
+        shape: &[Vertex2T],
+        texture: &Texture2d,
+        param: DrawParameters,
+    ) {
         let model_view =
             *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
         let view: [[f32; 4]; 4] = model_view.into();
 
-        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
+        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, shape).unwrap();
         let indices = IndexBuffer::new(
             &self.display,
             glium::index::PrimitiveType::TriangleStrip,
@@ -475,7 +521,7 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
 
         {
             profiling::scope!("draw");
-            self.frame.draw(&vertex_buffer, &indices, &self.shaders.texture, &uniform! { matrix: view, tex: texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest) }, &param).unwrap();
+            self.frame.draw(&vertex_buffer, &indices, &self.shaders.texture, &uniform! { matrix: view, tex: texture.sampled().magnify_filter(magnify_filter(self.pixel_filter)) }, &param).unwrap();
         }
     }
 
@@ -515,10 +561,65 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
         )
         .unwrap();
 
-        self.frame.draw(&vertex_buffer, &indices, &self.shaders.texture_array, &uniform! { matrix: view, tex: texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest) }, &param).unwrap();
+        self.frame.draw(&vertex_buffer, &indices, &self.shaders.texture_array, &uniform! { matrix: view, tex: texture.sampled().magnify_filter(magnify_filter(self.pixel_filter)) }, &param).unwrap();
     }
 
-    pub fn draw_particles(&mut self, parts: &[Particle], partial_ticks: f32) {
+    /// Draws layers of `texture` on quads with the corners in the same order as
+    ///   [`Vertices::vertices`], repeating where texture coordinates go past 0.0 to 1.0.
+    #[profiling::function]
+    pub fn draw_texture_array_quads(
+        &mut self,
+        quads: &[[Vertex2TA; 4]],
+        texture: &Texture2dArray,
+        param: DrawParameters,
+    ) {
+        if quads.is_empty() {
+            return;
+        }
+
+        let model_view =
+            *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
+        let view: [[f32; 4]; 4] = model_view.into();
+
+        let shape = quads.iter().flatten().copied().collect::<Vec<_>>();
+        let index_data = (0..quads.len() as u32)
+            .flat_map(|i| [1, 2, 0, 0, 2, 3].map(|n| i * 4 + n))
+            .collect::<Vec<_>>();
+
+        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
+        let indices = IndexBuffer::new(
+            &self.display,
+            glium::index::PrimitiveType::TrianglesList,
+            &index_data,
+        )
+        .unwrap();
+
+        let sampler = texture
+            .sampled()
+            .wrap_function(SamplerWrapFunction::Repeat)
+            .magnify_filter(magnify_filter(self.pixel_filter));
+
+        {
+            profiling::scope!("draw");
+            self.frame
+                .draw(
+                    &vertex_buffer,
+                    &indices,
+                    &self.shaders.texture_array,
+                    &uniform! { matrix: view, tex: sampler },
+                    &param,
+                )
+                .unwrap();
+        }
+    }
+
+    /// Draws particles as `size` pixel squares.
+    pub fn draw_particles<'p>(
+        &mut self,
+        parts: impl Iterator<Item = &'p Particle>,
+        size: f32,
+        partial_ticks: f32,
+    ) {
         let model_view =
             *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
         let view: [[f32; 4]; 4] = model_view.into();
@@ -534,7 +635,6 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             implement_vertex!(Attr, p_pos, color);
 
             let data = parts
-                .iter()
                 .map(|p| Attr {
                     p_pos: (
                         p.pos.x as f32 + p.vel.x as f32 * partial_ticks,
@@ -547,7 +647,8 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             glium::vertex::VertexBuffer::immutable(&self.display, &data).unwrap()
         };
 
-        let shape = Rect::<f32>::new(-0.5, -0.5, 0.5, 0.5).vertices();
+        let half = size / 2.0;
+        let shape = Rect::<f32>::new(-half, -half, half, half).vertices();
         let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
         let indices = IndexBuffer::new(
             &self.display,
@@ -574,7 +675,7 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
         let view: [[f32; 4]; 4] = model_view.into();
 
-        let shape = Rect::<f32>::new(0.0, 0.0, CHUNK_SIZE as f32, CHUNK_SIZE as f32)
+        let shape = Rect::<f32>::new(0.0, 0.0, f32::from(CHUNK_WIDTH), f32::from(CHUNK_HEIGHT))
             .vertices()
             .into_iter()
             .zip([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]])
@@ -598,12 +699,58 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             self.frame.draw(&vertex_buffer, &indices, &self.shaders.chunk, &uniform! {
                 matrix: view,
                 c_pos: *p,
-                tex: data.texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-                tex_bg: data.background_texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
+                tex: data.texture.sampled().magnify_filter(magnify_filter(self.pixel_filter)),
+                tex_bg: data.background_texture.sampled().magnify_filter(magnify_filter(self.pixel_filter)),
             }, &params).unwrap();
         }
     }
 
+    /// Draws the pixels of chunks whose material is in the foreground render layer,
+    ///   after everything that should be behind them.
+    pub fn draw_chunks_foreground(&mut self, chunks: &[((f32, f32), Arc<ChunkGraphicsData>)]) {
+        profiling::scope!("RenderTarget::draw_chunks_foreground");
+
+        let model_view =
+            *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
+
+        let shape = Rect::<f32>::new(0.0, 0.0, f32::from(CHUNK_WIDTH), f32::from(CHUNK_HEIGHT))
+            .vertices()
+            .into_iter()
+            .zip([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]])
+            .map(Vertex2T::from)
+            .collect::<Vec<_>>();
+        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
+        let indices = IndexBuffer::new(
+            &self.display,
+            glium::index::PrimitiveType::TriangleStrip,
+            &[1_u16, 2, 0, 3],
+        )
+        .unwrap();
+
+        let params = DrawParameters {
+            blend: Blend::alpha_blending(),
+            ..DrawParameters::default()
+        };
+
+        for (p, data) in chunks {
+            profiling::scope!("draw chunk foreground");
+            let matrix: [[f32; 4]; 4] =
+                nalgebra_glm::translate(&model_view, &nalgebra_glm::vec3(p.0, p.1, 0.0)).into();
+            self.frame
+                .draw(
+                    &vertex_buffer,
+                    &indices,
+                    &self.shaders.texture,
+                    &uniform! {
+                        matrix: matrix,
+                        tex: data.foreground_texture.sampled().magnify_filter(magnify_filter(self.pixel_filter)),
+                    },
+                    &params,
+                )
+                .unwrap();
+        }
+    }
+
     pub fn draw_chunks_light(
         &mut self,
         chunks: &[((f32, f32), Arc<ChunkGraphicsData>)],
@@ -619,7 +766,7 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
             *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
         let view: [[f32; 4]; 4] = model_view.into();
 
-        let shape = Rect::<f32>::new(0.0, 0.0, CHUNK_SIZE as f32, CHUNK_SIZE as f32)
+        let shape = Rect::<f32>::new(0.0, 0.0, f32::from(CHUNK_WIDTH), f32::from(CHUNK_HEIGHT))
             .vertices()
             .into_iter()
             .zip([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]])
@@ -660,10 +807,96 @@ impl<'a, 'b> RenderTarget<'a, 'b> {
                 c_pos: *p,
                 smooth_lighting: smooth_lighting,
                 dithering: dithering,
-                chunk_size: CHUNK_SIZE as i32,
+                chunk_size: [f32::from(CHUNK_WIDTH), f32::from(CHUNK_HEIGHT)],
                 player_light_world_pos: player_light_world_pos,
                 tex: data.lighting_dst.sampled().magnify_filter(if lighting_linear_blend { glium::uniforms::MagnifySamplerFilter::Linear } else { glium::uniforms::MagnifySamplerFilter::Nearest }),
             }, &params).unwrap();
         }
     }
+
+    /// Copies what's been drawn so far into `texture`, which should be the size of the frame.
+    pub fn copy_to(&self, texture: &Texture2d) {
+        profiling::scope!("RenderTarget::copy_to");
+        let (width, height) = self.frame.get_dimensions();
+        texture.as_surface().blit_from_frame(
+            &glium::Rect { left: 0, bottom: 0, width, height },
+            &glium::BlitTarget {
+                left: 0,
+                bottom: 0,
+                width: width as i32,
+                height: height as i32,
+            },
+            MagnifySamplerFilter::Nearest,
+        );
+    }
+
+    /// Draws `screen` (a copy of the frame, see [`Self::copy_to`]) back over hot parts of chunks,
+    ///   shifted around by their heat texture.
+    ///
+    /// `phase` is how far through its loop the animation is, and `strength` the furthest anything
+    ///   gets moved in screen pixels.
+    pub fn draw_chunks_heat_haze(
+        &mut self,
+        chunks: &[((f32, f32), Arc<ChunkGraphicsData>)],
+        screen: &Texture2d,
+        phase: f32,
+        strength: f32,
+    ) {
+        profiling::scope!("RenderTarget::draw_chunks_heat_haze");
+
+        let model_view =
+            *self.base_transform.stack.last().unwrap() * *self.transform.stack.last().unwrap();
+        let view: [[f32; 4]; 4] = model_view.into();
+
+        let shape = Rect::<f32>::new(0.0, 0.0, f32::from(CHUNK_WIDTH), f32::from(CHUNK_HEIGHT))
+            .vertices()
+            .into_iter()
+            .zip([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]])
+            .map(Vertex2T::from)
+            .collect::<Vec<_>>();
+        let vertex_buffer = glium::VertexBuffer::immutable(&self.display, &shape).unwrap();
+        let indices = IndexBuffer::new(
+            &self.display,
+            glium::index::PrimitiveType::TriangleStrip,
+            &[1_u16, 2, 0, 3],
+        )
+        .unwrap();
+
+        // the copy replaces what's under it, it's already been blended
+        let params = DrawParameters::default();
+        let (width, height) = self.frame.get_dimensions();
+
+        for (p, data) in chunks {
+            profiling::scope!("draw chunk heat haze");
+            self.frame.draw(&vertex_buffer, &indices, &self.shaders.heat_haze, &uniform! {
+                matrix: view,
+                c_pos: *p,
+                heat: data.heat_texture.sampled().magnify_filter(MagnifySamplerFilter::Linear).wrap_function(SamplerWrapFunction::Clamp),
+                screen: screen.sampled().magnify_filter(MagnifySamplerFilter::Nearest).wrap_function(SamplerWrapFunction::Clamp),
+                screen_size: [width as f32, height as f32],
+                phase: phase,
+                strength: strength,
+            }, &params).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::PixelFilter;
+    use glium::uniforms::MagnifySamplerFilter;
+
+    use super::magnify_filter;
+
+    #[test]
+    fn pixel_filter_selection() {
+        assert_eq!(
+            magnify_filter(PixelFilter::Nearest),
+            MagnifySamplerFilter::Nearest
+        );
+        assert_eq!(
+            magnify_filter(PixelFilter::Linear),
+            MagnifySamplerFilter::Linear
+        );
+    }
 }
@@ -1,6 +1,26 @@
-use std::{borrow::Borrow, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::Arc,
+};
 
-use fs_common::game::common::{ChunkCollisionOverlay, Registries, Settings};
+use fs_common::game::common::{
+    world::material::{color::Color, PhysicsType, SandSpread},
+    BackgroundStyle, ChunkCollisionOverlay, ChunkUnloadPolicy, DebugColor, DebugPalette,
+    PixelFilter, Registries, Settings,
+};
+
+fn color_edit(ui: &mut egui::Ui, color: &mut Color, label: &str) {
+    ui.horizontal(|ui| {
+        let mut c = egui::Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a);
+        if ui.color_edit_button_srgba(&mut c).changed() {
+            let [r, g, b, a] = c.to_srgba_unmultiplied();
+            *color = Color::rgba(r, g, b, a);
+        }
+        ui.label(label);
+    });
+}
 
 pub trait DebugUI {
     fn debug_ui(&mut self, ui: &mut egui::Ui, registries: Arc<Registries>);
@@ -24,6 +44,31 @@ impl DebugUI for Settings {
                 });
             }
 
+            ui.checkbox(
+                &mut self.draw_chunk_sim_time_overlay,
+                "draw_chunk_sim_time_overlay",
+            );
+            if self.draw_chunk_sim_time_overlay {
+                ui.indent("draw_chunk_sim_time_overlay#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.draw_chunk_sim_time_overlay_alpha, 0.1..=1.0)
+                            .text("alpha")
+                            .clamp_to_range(true),
+                    );
+                });
+            }
+
+            ui.checkbox(&mut self.draw_chunk_hash_overlay, "draw_chunk_hash_overlay");
+            if self.draw_chunk_hash_overlay {
+                ui.indent("draw_chunk_hash_overlay#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.draw_chunk_hash_overlay_alpha, 0.1..=1.0)
+                            .text("alpha")
+                            .clamp_to_range(true),
+                    );
+                });
+            }
+
             ui.checkbox(&mut self.draw_chunk_dirty_rects, "draw_chunk_dirty_rects");
             ui.checkbox(&mut self.draw_chunk_grid, "draw_chunk_grid");
             ui.checkbox(&mut self.draw_origin, "draw_origin");
@@ -51,11 +96,72 @@ impl DebugUI for Settings {
                     }
                 });
 
+            ui.checkbox(&mut self.draw_liquid_foam, "draw_liquid_foam");
+            if self.draw_liquid_foam {
+                ui.indent("draw_liquid_foam#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.liquid_foam_alpha, 0.0..=1.0)
+                            .text("alpha")
+                            .clamp_to_range(true),
+                    );
+                });
+            }
+            ui.checkbox(&mut self.heat_haze, "heat_haze");
+            if self.heat_haze {
+                ui.indent("heat_haze#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.heat_haze_strength, 0.0..=4.0)
+                            .text("strength")
+                            .clamp_to_range(true),
+                    );
+                });
+            }
+            ui.checkbox(&mut self.draw_material_textures, "draw_material_textures");
+            ui.checkbox(&mut self.draw_flow_overlay, "draw_flow_overlay");
+            ui.checkbox(&mut self.fog_of_war, "fog_of_war");
+            if self.fog_of_war {
+                ui.indent("fog_of_war#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.fog_of_war_alpha, 0.0..=1.0)
+                            .text("alpha")
+                            .clamp_to_range(true),
+                    );
+                });
+            }
+
+            ui.add(
+                egui::Slider::new(&mut self.particle_lod_scale, 0.0..=4.0)
+                    .text("particle_lod_scale")
+                    .clamp_to_range(true),
+            );
+
             ui.checkbox(&mut self.draw_lighting, "draw_lighting");
             ui.checkbox(&mut self.lighting_smooth, "lighting_smooth");
             ui.checkbox(&mut self.lighting_dithering, "lighting_dithering");
             ui.checkbox(&mut self.lighting_overlay, "lighting_overlay");
             ui.checkbox(&mut self.lighting_linear_blend, "lighting_linear_blend");
+            egui::ComboBox::from_label("pixel_filter")
+                .selected_text(format!("{:?}", self.pixel_filter))
+                .show_ui(ui, |ui| {
+                    for v in PixelFilter::values() {
+                        ui.selectable_value(&mut self.pixel_filter, *v, format!("{v:?}"));
+                    }
+                });
+            ui.checkbox(&mut self.pixel_snap, "pixel_snap");
+            ui.checkbox(&mut self.sun_light, "sun_light");
+            ui.indent("sun_light#indent", |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.sun_slope, -2.0..=2.0)
+                        .text("slope")
+                        .clamp_to_range(true),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.sun_intensity, 0.0..=1.0)
+                        .text("intensity")
+                        .clamp_to_range(true),
+                );
+            });
+            ui.checkbox(&mut self.propagate_light, "propagate_light");
 
             ui.checkbox(&mut self.cull_chunks, "cull_chunks");
 
@@ -67,6 +173,36 @@ impl DebugUI for Settings {
                     }
                 });
 
+            egui::ComboBox::from_label("background")
+                .selected_text(format!("{:?}", self.background))
+                .show_ui(ui, |ui| {
+                    for v in BackgroundStyle::values() {
+                        ui.selectable_value(&mut self.background, *v, format!("{v:?}"));
+                    }
+                });
+            if self.background != BackgroundStyle::None {
+                ui.indent("background#indent", |ui| {
+                    color_edit(ui, &mut self.background_top, "top");
+                    if self.background == BackgroundStyle::Gradient {
+                        color_edit(ui, &mut self.background_bottom, "bottom");
+                        ui.add(
+                            egui::Slider::new(&mut self.background_horizon, -2000.0..=2000.0)
+                                .text("horizon"),
+                        );
+                        ui.add(
+                            egui::Slider::new(&mut self.background_gradient_height, 1.0..=4000.0)
+                                .text("gradient_height")
+                                .clamp_to_range(true),
+                        );
+                        ui.add(
+                            egui::Slider::new(&mut self.background_parallax, 0.0..=1.0)
+                                .text("parallax")
+                                .clamp_to_range(true),
+                        );
+                    }
+                });
+            }
+
             ui.checkbox(&mut self.physics_dbg_draw, "physics_dbg_draw");
 
             ui.indent("physics_dbg_draw#indent", |ui| {
@@ -77,6 +213,15 @@ impl DebugUI for Settings {
                 ui.checkbox(&mut self.physics_dbg_draw_center_of_mass, "center_of_mass");
                 ui.checkbox(&mut self.physics_dbg_draw_particle, "particle");
             });
+
+            ui.collapsing("debug_colors", |ui| {
+                if ui.button("reset").clicked() {
+                    self.debug_colors = DebugPalette::default();
+                }
+                for c in DebugColor::values() {
+                    color_edit(ui, self.debug_colors.get_mut(*c), &format!("{c:?}"));
+                }
+            });
         });
 
         ui.collapsing("display", |ui| {
@@ -94,6 +239,100 @@ impl DebugUI for Settings {
 
             ui.checkbox(&mut self.vsync, "vsync");
             ui.checkbox(&mut self.minimize_on_lost_focus, "minimize_on_lost_focus");
+            ui.horizontal(|ui| {
+                ui.label("window_title");
+                ui.text_edit_singleline(&mut self.window_title)
+                    .on_hover_text("{fps} {net_mode} {world} {tick} {cpu} {memory}");
+            });
+            ui.add(
+                egui::Slider::new(&mut self.stats_interval_ms, 50..=5000)
+                    .text("stats_interval_ms")
+                    .logarithmic(true)
+                    .clamp_to_range(true),
+            );
+
+            ui.add(
+                egui::Slider::new(&mut self.camera_offset_x, -200.0..=200.0)
+                    .text("camera_offset_x")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_offset_y, -200.0..=200.0)
+                    .text("camera_offset_y")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_look_ahead, 0.0..=30.0)
+                    .text("camera_look_ahead")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_smoothing, 0.0..=2.0)
+                    .text("camera_smoothing")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_zoom_min, 0.001..=1.0)
+                    .logarithmic(true)
+                    .text("camera_zoom_min")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_zoom_max, 1.0..=100.0)
+                    .logarithmic(true)
+                    .text("camera_zoom_max")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_zoom_step, 0.01..=1.0)
+                    .text("camera_zoom_step")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_zoom_duration, 0.0..=1.0)
+                    .text("camera_zoom_duration")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_pan_speed, 1.0..=64.0)
+                    .text("camera_pan_speed")
+                    .clamp_to_range(true),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.camera_pan_fast_multiplier, 1.0..=16.0)
+                    .text("camera_pan_fast_multiplier")
+                    .clamp_to_range(true),
+            );
+
+            ui.checkbox(&mut self.timelapse, "timelapse");
+            if self.timelapse {
+                ui.indent("timelapse#indent", |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut self.timelapse_interval, 1..=600)
+                            .logarithmic(true)
+                            .text("interval")
+                            .clamp_to_range(true),
+                    );
+                    ui.checkbox(&mut self.timelapse_fixed_region, "fixed_region");
+                    if self.timelapse_fixed_region {
+                        let r = &mut self.timelapse_region;
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut r.x1));
+                            ui.add(egui::DragValue::new(&mut r.y1));
+                            ui.label("from");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut r.x2).clamp_range(r.x1 + 1..=i32::MAX),
+                            );
+                            ui.add(
+                                egui::DragValue::new(&mut r.y2).clamp_range(r.y1 + 1..=i32::MAX),
+                            );
+                            ui.label("to");
+                        });
+                    }
+                });
+            }
         });
 
         ui.collapsing("simulation", |ui| {
@@ -130,12 +369,148 @@ impl DebugUI for Settings {
                 if ui.button("reset##tick_physics_timestep").clicked() {
                     self.tick_physics_timestep = 1.0 / 45.0;
                 }
+
+                ui.checkbox(&mut self.destructive_impacts, "destructive_impacts");
+                ui.add(
+                    egui::Slider::new(&mut self.rigidbody_mesh_tolerance, 0.0..=8.0)
+                        .text("rigidbody_mesh_tolerance")
+                        .clamp_to_range(true),
+                );
+                ui.checkbox(
+                    &mut self.rigidbody_passable_materials,
+                    "rigidbody_passable_materials",
+                );
             });
 
             ui.checkbox(&mut self.load_chunks, "load_chunks");
+            ui.indent("load_chunks#indent", |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.chunk_gen_budget_ms, 0.0..=16.0)
+                        .text("chunk_gen_budget_ms")
+                        .clamp_to_range(true),
+                );
+                egui::ComboBox::from_label("chunk_unload_policy")
+                    .selected_text(format!("{:?}", self.chunk_unload_policy))
+                    .show_ui(ui, |ui| {
+                        for v in ChunkUnloadPolicy::values() {
+                            ui.selectable_value(
+                                &mut self.chunk_unload_policy,
+                                *v,
+                                format!("{v:?}"),
+                            );
+                        }
+                    });
+                if self.chunk_unload_policy == ChunkUnloadPolicy::Cache {
+                    ui.add(
+                        egui::Slider::new(&mut self.chunk_cache_cap, 0..=4096)
+                            .text("chunk_cache_cap")
+                            .logarithmic(true)
+                            .clamp_to_range(true),
+                    );
+                }
+            });
             ui.checkbox(&mut self.simulate_chunks, "simulate_chunks");
+            ui.indent("simulate_chunks#indent", |ui| {
+                ui.checkbox(&mut self.simulate_chunks_parallel, "parallel");
+                ui.add_enabled(
+                    self.simulate_chunks_parallel,
+                    egui::Slider::new(&mut self.simulation_threads, 0..=32)
+                        .text("threads (0 = auto)")
+                        .clamp_to_range(true),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.simulate_chunks_substeps, 1..=8)
+                        .text("substeps")
+                        .clamp_to_range(true),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.sim_pixel_budget, 0..=1_000_000)
+                        .text("pixel budget (0 = unlimited)")
+                        .logarithmic(true)
+                        .clamp_to_range(true),
+                );
+                ui.horizontal(|ui| {
+                    for physics in [PhysicsType::Sand, PhysicsType::Liquid] {
+                        let mut enabled = self.simulate_physics_types.contains(physics);
+                        if ui.checkbox(&mut enabled, format!("{physics:?}")).changed() {
+                            self.simulate_physics_types =
+                                self.simulate_physics_types.with(physics, enabled);
+                        }
+                    }
+                });
+                ui.checkbox(&mut self.sand_settle_narrow_gaps, "sand_settle_narrow_gaps");
+                egui::ComboBox::from_label("sand_spread")
+                    .selected_text(format!("{:?}", self.sand_spread))
+                    .show_ui(ui, |ui| {
+                        for v in SandSpread::values() {
+                            ui.selectable_value(&mut self.sand_spread, *v, format!("{v:?}"));
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.simulation_seed));
+                    ui.label("simulation_seed");
+                    if ui.button("Reroll").clicked() {
+                        self.simulation_seed = RandomState::new().build_hasher().finish() as u32;
+                    }
+                    if ui.button("Copy").clicked() {
+                        ui.output().copied_text = self.simulation_seed.to_string();
+                    }
+                });
+            });
             ui.checkbox(&mut self.simulate_particles, "simulate_particles");
+            ui.indent("simulate_particles#indent", |ui| {
+                ui.add(
+                    egui::Slider::new(&mut self.tick_particles_speed, 1..=120)
+                        .text("tick_particles_speed")
+                        .clamp_to_range(true),
+                );
+
+                if ui.button("reset##tick_particles_speed").clicked() {
+                    self.tick_particles_speed = 30;
+                }
+
+                ui.add(
+                    egui::Slider::new(&mut self.particle_threads, 0..=32)
+                        .text("threads (0 = auto)")
+                        .clamp_to_range(true),
+                );
+
+                ui.checkbox(&mut self.particle_interactions.collide, "collide");
+                ui.indent("particle_interactions.collide#indent", |ui| {
+                    ui.add_enabled(
+                        self.particle_interactions.collide,
+                        egui::Checkbox::new(&mut self.particle_interactions.settle, "settle"),
+                    );
+                    ui.add_enabled(
+                        self.particle_interactions.collide,
+                        egui::Checkbox::new(&mut self.particle_interactions.displace, "displace"),
+                    );
+                });
+            });
             ui.checkbox(&mut self.pause_on_lost_focus, "pause_on_lost_focus");
+            ui.checkbox(&mut self.survival, "survival");
+            ui.collapsing("brush_palette", |ui| {
+                for (i, slot) in self.brush_palette.slots.iter_mut().enumerate() {
+                    let label = format!("key {}", i + 1);
+                    egui::ComboBox::from_label(label)
+                        .selected_text(slot.as_ref().map_or("none", |id| id.borrow()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(slot, None, "none");
+                            for (k, v) in &registries.material_placers {
+                                ui.selectable_value(
+                                    slot,
+                                    Some(k.clone()),
+                                    v.meta.display_name.clone(),
+                                );
+                            }
+                        });
+                }
+            });
+            ui.add(
+                egui::Slider::new(&mut self.player_edge_clip_distance, 0.0..=4.0)
+                    .text("player_edge_clip_distance")
+                    .clamp_to_range(true),
+            );
         });
     }
 }
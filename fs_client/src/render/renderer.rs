@@ -3,10 +3,14 @@ use std::fs;
 use egui::{plot::HLine, Align2, RichText, WidgetText};
 use fs_common::game::{
     common::{
-        world::{entity::Player, material::color::Color, Position, Velocity},
+        world::{
+            entity::{Inventory, Player, BRUSH_PALETTE_FILE},
+            material::color::Color,
+            Camera, Position, Velocity,
+        },
         FileHelper, Rect,
     },
-    GameData,
+    format_bytes, format_cpu, GameData,
 };
 use glium::{Blend, Display, DrawParameters, PolygonMode};
 use glium_glyph::{
@@ -14,12 +18,12 @@ use glium_glyph::{
     GlyphBrush, GlyphBrushBuilder,
 };
 use glutin::{dpi::LogicalSize, event_loop::EventLoop};
-use specs::{ReadStorage, WriteStorage};
+use specs::{Join, ReadStorage, WriteStorage};
 
 use crate::{
     render::egui::DebugUI,
-    ui::DebugUIsContext,
-    world::{ClientChunk, RenderContext, WorldRenderer},
+    ui::{cursor_coords::cursor_pixel, DebugUIsContext},
+    world::{texture_stream::ChunkTextureStreamer, ClientChunk, RenderContext, WorldRenderer},
     Client,
 };
 
@@ -31,6 +35,7 @@ pub struct Renderer<'a> {
     pub shaders: Shaders,
     pub display: Display,
     pub world_renderer: WorldRenderer,
+    pub chunk_textures: ChunkTextureStreamer,
     pub egui_glium: egui_glium::EguiGlium,
     // pub version_info_cache_1: Option<(u32, u32, GPUImage)>,
     // pub version_info_cache_2: Option<(u32, u32, GPUImage)>,
@@ -78,12 +83,19 @@ impl<'a> Renderer<'a> {
             shaders,
             display,
             world_renderer: WorldRenderer::new(),
+            chunk_textures: ChunkTextureStreamer::new(),
             egui_glium,
             // version_info_cache_1: None,
             // version_info_cache_2: None,
         })
     }
 
+    /// Size of the window's drawable area, in physical pixels.
+    pub fn window_size(&self) -> (f64, f64) {
+        let size = self.display.gl_window().window().inner_size();
+        (f64::from(size.width), f64::from(size.height))
+    }
+
     #[profiling::function]
     pub fn render(
         &mut self,
@@ -92,7 +104,9 @@ impl<'a> Renderer<'a> {
         delta_time: f64,
         partial_ticks: f64,
     ) {
+        let window_size = self.window_size();
         let mut target = RenderTarget::new(&mut self.display, &self.shaders, &mut self.glyph_brush);
+        target.pixel_filter = game.settings.pixel_filter;
         target.clear(Color::BLACK);
 
         Self::render_internal(
@@ -177,7 +191,14 @@ impl<'a> Renderer<'a> {
                                 }
                             }
 
+                            let palette = game.settings.brush_palette.clone();
                             game.settings.debug_ui(ui, game.registries.clone());
+                            if game.settings.brush_palette != palette {
+                                let path = game.file_helper.game_path(BRUSH_PALETTE_FILE);
+                                if let Err(e) = game.settings.brush_palette.save(&path) {
+                                    log::error!("{e}");
+                                }
+                            }
                         });
 
                     // TODO: this should be somewhere better
@@ -207,11 +228,11 @@ impl<'a> Renderer<'a> {
                     .default_width(200.0)
                     .show(egui_ctx, |ui| {
                         let a = match game.process_stats.cpu_usage {
-                            Some(c) => format!("CPU: {c:.0}%"),
+                            Some(c) => format!("CPU: {}", format_cpu(c)),
                             None => "CPU: n/a".to_string(),
                         };
                         let b = match game.process_stats.memory {
-                            Some(m) => format!(" mem: {:.1} MB", m as f32 / 1000000.0),
+                            Some(m) => format!(" mem: {}", format_bytes(m)),
                             None => " mem: n/a".to_string(),
                         };
 
@@ -338,20 +359,54 @@ impl<'a> Renderer<'a> {
                     });
 
                 client.main_menu.render(egui_ctx, &game.file_helper);
-                if let Some(debug_ui) = &mut client.debug_ui {
-                    if let (Some(cw), Some(gw)) = (&mut client.world, &mut game.world) {
-                        if let Some(eid) = cw.local_entity {
-                            let (mut player,) = gw.ecs.system_data::<(WriteStorage<Player>,)>();
+                if let (Some(cw), Some(gw)) = (&mut client.world, &mut game.world) {
+                    if let Some(eid) = cw.local_entity {
+                        let (mut player, mut inventory, position_storage, camera_storage) =
+                            gw.ecs.system_data::<(
+                                WriteStorage<Player>,
+                                WriteStorage<Inventory>,
+                                ReadStorage<Position>,
+                                ReadStorage<Camera>,
+                            )>();
+
+                        let player = player
+                            .get_mut(eid)
+                            .expect("Missing Player component on local_entity");
+                        // not every entity a client can control carries an inventory
+                        let inventory = inventory.get_mut(eid);
+
+                        if let Some(inventory) = &inventory {
+                            client.hotbar.render(
+                                egui_ctx,
+                                &game.registries,
+                                inventory,
+                                game.settings.survival,
+                            );
+                        }
 
-                            let player = player
-                                .get_mut(eid)
-                                .expect("Missing Player component on local_entity");
+                        if let Some(debug_ui) = &mut client.debug_ui {
+                            let cursor = client.controls.cursor_pos;
+                            let hovered_pixel = (&position_storage, &camera_storage)
+                                .join()
+                                .map(|(camera_pos, _)| {
+                                    cursor_pixel(
+                                        camera_pos,
+                                        (cursor.x, cursor.y),
+                                        window_size,
+                                        client.camera_scale,
+                                    )
+                                })
+                                .next();
 
                             debug_ui.render(
                                 egui_ctx,
                                 DebugUIsContext {
                                     registries: &game.registries,
                                     local_player: player,
+                                    local_inventory: inventory,
+                                    process_stats: &game.process_stats,
+                                    chunk_handler: &gw.chunk_handler,
+                                    hovered_pixel,
                                 },
                             );
                         }
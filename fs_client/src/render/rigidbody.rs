@@ -10,11 +10,7 @@ pub trait FSRigidBodyExt {
 impl FSRigidBodyExt for FSRigidBody {
     fn update_image(&mut self, target: &mut RenderTarget) {
         if self.image_dirty {
-            let pixel_data: Vec<_> = self
-                .pixels
-                .iter()
-                .flat_map(|m| vec![m.color.r, m.color.g, m.color.b, m.color.a])
-                .collect();
+            let pixel_data = self.image_data();
 
             let image = {
                 glium::texture::RawImage2d {
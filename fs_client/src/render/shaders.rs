@@ -1,9 +1,13 @@
 use std::fs;
 
-use fs_common::game::common::FileHelper;
+use fs_common::game::common::{
+    world::{LIGHT_HEIGHT, LIGHT_WIDTH},
+    FileHelper,
+};
 use glium::{
+    backend::Facade,
     program::{ComputeShader, ProgramChooserCreationError},
-    Display, ProgramCreationError,
+    ProgramCreationError,
 };
 
 pub struct Shaders {
@@ -14,12 +18,14 @@ pub struct Shaders {
     pub particle: glium::Program,
     pub chunk: glium::Program,
     pub chunk_light: glium::Program,
+    /// Distorts a copy of the screen over hot chunks, see `heat_haze`.
+    pub heat_haze: glium::Program,
     pub lighting_compute_propagate: ComputeShader,
     pub lighting_compute_prep: ComputeShader,
 }
 
 impl Shaders {
-    pub fn new(display: &Display, file_helper: &FileHelper) -> Self {
+    pub fn new<F: Facade + ?Sized>(display: &F, file_helper: &FileHelper) -> Self {
         profiling::scope!("Shaders::new");
         let helper = ShaderFileHelper { file_helper, display };
 
@@ -65,6 +71,13 @@ impl Shaders {
                     "data/shaders/chunk_light.frag",
                 )
                 .unwrap(),
+            heat_haze: helper
+                .load_from_files(
+                    140,
+                    "data/shaders/chunk.vert",
+                    "data/shaders/heat_haze.frag",
+                )
+                .unwrap(),
             lighting_compute_propagate: helper
                 .load_compute_from_files("data/shaders/lighting_propagate.comp")
                 .unwrap(),
@@ -75,12 +88,12 @@ impl Shaders {
     }
 }
 
-pub struct ShaderFileHelper<'a> {
+pub struct ShaderFileHelper<'a, F: Facade + ?Sized> {
     pub file_helper: &'a FileHelper,
-    pub display: &'a Display,
+    pub display: &'a F,
 }
 
-impl ShaderFileHelper<'_> {
+impl<F: Facade + ?Sized> ShaderFileHelper<'_, F> {
     pub fn load_from_files(
         &self,
         version: u32,
@@ -108,6 +121,140 @@ impl ShaderFileHelper<'_> {
     ) -> Result<glium::program::ComputeShader, ProgramCreationError> {
         let src = fs::read_to_string(self.file_helper.asset_path(src)).unwrap();
 
-        ComputeShader::from_source(self.display, &src)
+        ComputeShader::from_source(self.display, &with_light_defines(&src))
+    }
+}
+
+/// Defines `LIGHT_WIDTH` and `LIGHT_HEIGHT` right after the `#version` line,
+/// so the lighting compute shaders size their work group to the chunk's light grid.
+fn with_light_defines(src: &str) -> String {
+    let defines =
+        format!("#define LIGHT_WIDTH {LIGHT_WIDTH}\n#define LIGHT_HEIGHT {LIGHT_HEIGHT}\n");
+    match src.split_once('\n') {
+        Some((version, rest)) if version.starts_with("#version") => {
+            format!("{version}\n{defines}{rest}")
+        },
+        _ => format!("{defines}{src}"),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::{ffi::c_void, rc::Rc};
+
+    use fs_common::game::common::FileHelper;
+    use glium::{
+        backend::{Backend, Context},
+        debug::DebugCallbackBehavior,
+        SwapBuffersError,
+    };
+    use khronos_egl as egl;
+
+    use super::Shaders;
+
+    /// An OpenGL context without a window, so shaders can be compiled in tests.
+    struct Headless {
+        egl: Rc<egl::DynamicInstance<egl::EGL1_5>>,
+        display: egl::Display,
+        context: egl::Context,
+    }
+
+    unsafe impl Backend for Headless {
+        fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+            Ok(())
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+            self.egl
+                .get_proc_address(symbol)
+                .map_or(std::ptr::null(), |f| f as *const c_void)
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (1, 1)
+        }
+
+        fn is_current(&self) -> bool {
+            self.egl.get_current_context() == Some(self.context)
+        }
+
+        unsafe fn make_current(&self) {
+            self.egl
+                .make_current(self.display, None, None, Some(self.context))
+                .unwrap();
+        }
+    }
+
+    /// A context from Mesa's surfaceless EGL platform, or `None` if there's no usable libEGL
+    ///   (eg. on CI machines without Mesa).
+    fn headless_context() -> Option<Rc<Context>> {
+        /// `EGL_PLATFORM_SURFACELESS_MESA`, which doesn't need a display server.
+        const PLATFORM_SURFACELESS: egl::Enum = 0x31DD;
+
+        let egl = Rc::new(unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required() }.ok()?);
+        let display = unsafe {
+            egl.get_platform_display(
+                PLATFORM_SURFACELESS,
+                egl::DEFAULT_DISPLAY,
+                &[egl::ATTRIB_NONE],
+            )
+        }
+        .ok()?;
+        egl.initialize(display).ok()?;
+        egl.bind_api(egl::OPENGL_API).ok()?;
+        let config = egl
+            .choose_first_config(
+                display,
+                &[
+                    egl::SURFACE_TYPE,
+                    0,
+                    egl::RENDERABLE_TYPE,
+                    egl::OPENGL_BIT,
+                    egl::NONE,
+                ],
+            )
+            .ok()??;
+        let context = egl
+            .create_context(display, config, None, &[egl::NONE])
+            .ok()?;
+
+        unsafe {
+            Context::new(
+                Headless { egl, display, context },
+                false,
+                DebugCallbackBehavior::Ignore,
+            )
+        }
+        .ok()
+    }
+
+    #[test]
+    fn shaders_compile() {
+        let Some(context) = headless_context() else {
+            eprintln!(
+                "skipping shaders_compile, no OpenGL context without a window (needs libEGL)"
+            );
+            return;
+        };
+        let file_helper = FileHelper::new("../gamedir/".into(), "../gamedir/assets/".into());
+
+        // panics if any of them fail to compile or link
+        let shaders = Shaders::new(&context, &file_helper);
+
+        // and the heat haze actually uses what it's given, instead of having it optimized out
+        for name in [
+            "heat",
+            "screen",
+            "screen_size",
+            "phase",
+            "strength",
+            "matrix",
+            "c_pos",
+        ] {
+            assert!(
+                shaders.heat_haze.get_uniform(name).is_some(),
+                "heat_haze is missing uniform {name}"
+            );
+        }
     }
 }
@@ -0,0 +1,105 @@
+use fs_common::game::common::world::{pixel_to_chunk_pos, Position};
+
+use crate::screen_to_world;
+
+use super::DebugUIsContext;
+
+/// The world pixel under the cursor, looking through a camera at `camera`.
+///
+/// See [`screen_to_world`].
+pub fn cursor_pixel(
+    camera: &Position,
+    cursor: (f64, f64),
+    window_size: (f64, f64),
+    camera_scale: f64,
+) -> (i64, i64) {
+    let (x, y) = screen_to_world(camera, cursor, window_size, camera_scale);
+    (x.floor() as i64, y.floor() as i64)
+}
+
+/// A readout of the pixel and chunk under the cursor, in the bottom left corner.
+pub struct CursorCoordsUI;
+
+impl CursorCoordsUI {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn render(&mut self, egui_ctx: &egui::Context, ctx: &mut DebugUIsContext) {
+        let Some((x, y)) = ctx.hovered_pixel else {
+            return;
+        };
+        let (chunk_x, chunk_y) = pixel_to_chunk_pos(x, y);
+
+        egui::Area::new("Cursor Coords")
+            .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+            .interactable(false)
+            .show(egui_ctx, |ui| {
+                ui.label(format!("Pixel: ({x}, {y})"));
+                ui.label(format!("Chunk: ({chunk_x}, {chunk_y})"));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::world::{pixel_to_chunk_pos, Position};
+
+    use super::cursor_pixel;
+
+    #[test]
+    fn screen_to_chunk() {
+        let check = |camera: (f64, f64), scale, window, cursor, pixel, chunk| {
+            let camera = Position { x: camera.0, y: camera.1 };
+            let got = cursor_pixel(&camera, cursor, window, scale);
+            assert_eq!(
+                got, pixel,
+                "{camera:?}, scale {scale}, {window:?}, {cursor:?}"
+            );
+            assert_eq!(pixel_to_chunk_pos(got.0, got.1), chunk);
+        };
+
+        // camera, scale, window size, cursor, expected pixel, expected chunk
+        check(
+            (0.0, 0.0),
+            1.0,
+            (1280.0, 720.0),
+            (640.0, 360.0),
+            (0, 0),
+            (0, 0),
+        );
+        check(
+            (0.0, 0.0),
+            1.0,
+            (1280.0, 720.0),
+            (639.0, 359.0),
+            (-1, -1),
+            (-1, -1),
+        );
+        check(
+            (250.5, -30.0),
+            2.0,
+            (800.0, 600.0),
+            (0.0, 0.0),
+            (50, -180),
+            (0, -2),
+        );
+        check(
+            (1000.0, 1000.0),
+            4.0,
+            (800.0, 600.0),
+            (800.0, 600.0),
+            (1100, 1075),
+            (11, 10),
+        );
+        check(
+            (-5.0, 99.0),
+            0.5,
+            (200.0, 400.0),
+            (100.0, 450.0),
+            (-5, 599),
+            (-1, 5),
+        );
+    }
+}
@@ -3,26 +3,22 @@ use std::collections::BTreeMap;
 use egui::TextureOptions;
 use fs_common::game::common::{
     registry::RegistryID,
-    world::material::placer::{self, MaterialPlacer, MaterialPlacerSampler},
+    world::material::placer::{MaterialPlacer, MaterialPlacerSampler},
 };
 
 use super::DebugUIsContext;
 
 pub struct DrawUI {
     textures: BTreeMap<RegistryID<MaterialPlacer>, egui::TextureHandle>,
-    pub selected: RegistryID<MaterialPlacer>,
 }
 
 impl DrawUI {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {
-            textures: BTreeMap::new(),
-            selected: placer::AIR_PLACER.clone(),
-        }
+        Self { textures: BTreeMap::new() }
     }
 
-    pub fn render(&mut self, egui_ctx: &egui::Context, ctx: &DebugUIsContext) {
+    pub fn render(&mut self, egui_ctx: &egui::Context, ctx: &mut DebugUIsContext) {
         for (id, placer) in &ctx.registries.material_placers {
             self.textures.entry(id.clone()).or_insert_with(|| {
                 egui_ctx.load_texture(
@@ -43,10 +39,10 @@ impl DrawUI {
                     |ui| {
                         for (id, tex) in &self.textures {
                             if ui
-                                .add(
-                                    egui::ImageButton::new(tex, (40.0, 40.0))
-                                        .selected(*id == self.selected),
-                                )
+                                .add(egui::ImageButton::new(tex, (40.0, 40.0)).selected(
+                                    ctx.local_inventory.as_ref().and_then(|inv| inv.active())
+                                        == Some(id),
+                                ))
                                 .on_hover_text(
                                     ctx.registries
                                         .material_placers
@@ -58,7 +54,9 @@ impl DrawUI {
                                 )
                                 .clicked()
                             {
-                                self.selected = id.clone();
+                                if let Some(inventory) = &mut ctx.local_inventory {
+                                    inventory.set_active(id.clone());
+                                }
                             };
                         }
                     },
@@ -67,7 +65,7 @@ impl DrawUI {
     }
 }
 
-fn gen_material_preview(placer: &dyn MaterialPlacerSampler) -> egui::ColorImage {
+pub(crate) fn gen_material_preview(placer: &dyn MaterialPlacerSampler) -> egui::ColorImage {
     let width = 20;
     let height = 20;
     let fake_nearest_neighbor_scale = 3;
@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use egui::{Align2, TextureOptions};
+use fs_common::game::common::{
+    registry::RegistryID,
+    world::{entity::Inventory, material::placer::MaterialPlacer},
+    Registries,
+};
+
+use super::draw::gen_material_preview;
+
+pub struct HotbarUI {
+    textures: BTreeMap<RegistryID<MaterialPlacer>, egui::TextureHandle>,
+}
+
+impl HotbarUI {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { textures: BTreeMap::new() }
+    }
+
+    pub fn render(
+        &mut self,
+        egui_ctx: &egui::Context,
+        registries: &Registries,
+        inventory: &Inventory,
+        survival: bool,
+    ) {
+        for id in inventory.hotbar.iter().flatten() {
+            if let Some(placer) = registries.material_placers.get(id) {
+                self.textures.entry(id.clone()).or_insert_with(|| {
+                    egui_ctx.load_texture(
+                        format!("{id:?}"),
+                        gen_material_preview(placer),
+                        TextureOptions::LINEAR,
+                    )
+                });
+            }
+        }
+
+        egui::Window::new("Hotbar")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -8.0])
+            .show(egui_ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (slot, id) in inventory.hotbar.iter().enumerate() {
+                        let selected = slot == inventory.selected_slot;
+                        ui.vertical(|ui| {
+                            match id
+                                .as_ref()
+                                .and_then(|id| self.textures.get(id).map(|t| (id, t)))
+                            {
+                                Some((id, tex)) => {
+                                    ui.add(
+                                        egui::ImageButton::new(tex, (40.0, 40.0))
+                                            .selected(selected),
+                                    )
+                                    .on_hover_text(
+                                        registries
+                                            .material_placers
+                                            .get(id)
+                                            .unwrap()
+                                            .meta
+                                            .display_name
+                                            .to_string(),
+                                    );
+                                    ui.label(if survival {
+                                        inventory.count(id).to_string()
+                                    } else {
+                                        "∞".to_string()
+                                    });
+                                },
+                                None => {
+                                    ui.add(
+                                        egui::Button::new((slot + 1).to_string())
+                                            .min_size(egui::vec2(46.0, 46.0))
+                                            .fill(if selected {
+                                                ui.visuals().selection.bg_fill
+                                            } else {
+                                                ui.visuals().widgets.inactive.bg_fill
+                                            }),
+                                    );
+                                    ui.label("");
+                                },
+                            }
+                        });
+                    }
+                });
+            });
+    }
+}
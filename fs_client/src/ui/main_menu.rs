@@ -3,7 +3,10 @@ use std::path::PathBuf;
 use fs_common::game::{
     self,
     common::{
-        world::{WorldMeta, WorldTreeNode},
+        world::{
+            material::{self, color::Color},
+            WorldMeta, WorldPreset, WorldTreeNode,
+        },
         FileHelper,
     },
 };
@@ -25,7 +28,8 @@ pub enum MainMenuState {
 pub enum MainMenuAction {
     Quit,
     LoadWorld(PathBuf),
-    LoadRandomSeed,
+    /// Starts a new world with a random seed.
+    NewWorld(WorldPreset),
 }
 
 impl MainMenu {
@@ -88,8 +92,8 @@ impl MainMenu {
                 match &self.state {
                     MainMenuState::Main => {
                         if ui.button("Singleplayer").clicked() {
-                            let worlds = game::common::world::World::<ClientChunk>::find_files(
-                                file_helper.game_path("saves/"),
+                            let worlds = game::common::world::World::<ClientChunk>::find_worlds(
+                                file_helper.save_roots(),
                             )
                             .expect("Failed to load worlds list");
                             log::debug!("{:?}", worlds);
@@ -103,7 +107,20 @@ impl MainMenu {
                             new_state = Some(MainMenuState::WorldSelect { context: metas });
                         }
                         if ui.button("Random Seed").clicked() {
-                            self.action_queue.push(MainMenuAction::LoadRandomSeed);
+                            self.action_queue
+                                .push(MainMenuAction::NewWorld(WorldPreset::Generated));
+                        }
+                        if ui.button("Flat World").clicked() {
+                            self.action_queue
+                                .push(MainMenuAction::NewWorld(WorldPreset::Flat {
+                                    height: 100,
+                                    material: material::COBBLE_STONE.to_string(),
+                                    color: Color::rgb(127, 127, 127),
+                                }));
+                        }
+                        if ui.button("Empty World").clicked() {
+                            self.action_queue
+                                .push(MainMenuAction::NewWorld(WorldPreset::Empty));
                         }
                         if ui.button("Quit").clicked() {
                             self.action_queue.push(MainMenuAction::Quit);
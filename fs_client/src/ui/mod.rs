@@ -1,22 +1,49 @@
 pub mod clipboard;
+pub mod cursor_coords;
 pub mod draw;
+mod hotbar;
 mod main_menu;
+pub mod pixel_info;
+pub mod process;
 pub mod registries;
 
-use fs_common::game::common::{world::entity::Player, Registries};
+use fs_common::game::{
+    common::{
+        world::{
+            chunk_handler::ChunkHandler,
+            entity::{Inventory, Player},
+        },
+        Registries,
+    },
+    ProcessStats,
+};
+pub use hotbar::*;
 pub use main_menu::*;
 
-use self::{clipboard::ClipboardUI, draw::DrawUI, registries::RegistriesUI};
+use crate::world::ClientChunk;
+
+use self::{
+    clipboard::ClipboardUI, cursor_coords::CursorCoordsUI, draw::DrawUI, pixel_info::PixelInfoUI,
+    process::ProcessUI, registries::RegistriesUI,
+};
 
 pub struct DebugUIs {
     pub draw: DrawUI,
     pub clipboard: ClipboardUI,
     pub registries: RegistriesUI,
+    pub process: ProcessUI,
+    pub pixel_info: PixelInfoUI,
+    pub cursor_coords: CursorCoordsUI,
 }
 
 pub struct DebugUIsContext<'a> {
     pub registries: &'a Registries,
     pub local_player: &'a mut Player,
+    pub local_inventory: Option<&'a mut Inventory>,
+    pub process_stats: &'a ProcessStats,
+    pub chunk_handler: &'a ChunkHandler<ClientChunk>,
+    /// The world pixel under the cursor.
+    pub hovered_pixel: Option<(i64, i64)>,
 }
 
 impl DebugUIs {
@@ -26,12 +53,18 @@ impl DebugUIs {
             draw: DrawUI::new(),
             clipboard: ClipboardUI::new(),
             registries: RegistriesUI::new(),
+            process: ProcessUI::new(),
+            pixel_info: PixelInfoUI::new(),
+            cursor_coords: CursorCoordsUI::new(),
         }
     }
 
     pub fn render(&mut self, egui_ctx: &egui::Context, mut ctx: DebugUIsContext) {
-        self.draw.render(egui_ctx, &ctx);
+        self.draw.render(egui_ctx, &mut ctx);
         self.clipboard.render(egui_ctx, &mut ctx);
         self.registries.render(egui_ctx, &mut ctx);
+        self.process.render(egui_ctx, &mut ctx);
+        self.pixel_info.render(egui_ctx, &mut ctx);
+        self.cursor_coords.render(egui_ctx, &mut ctx);
     }
 }
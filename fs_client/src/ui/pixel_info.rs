@@ -0,0 +1,143 @@
+use fs_common::game::common::{
+    registry::RegistryID,
+    world::{
+        chunk_access::FSChunkAccess,
+        material::{color::Color, Material, PhysicsType},
+    },
+    Registries,
+};
+
+use super::DebugUIsContext;
+
+/// What is in a pixel of the world, for the hover tooltip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelInfo {
+    pub material_id: RegistryID<Material>,
+    /// The material's display name, `None` if it isn't registered.
+    pub display_name: Option<String>,
+    pub physics: PhysicsType,
+    /// Whether the pixel counts as empty, see [`MaterialInstance::is_empty`].
+    pub empty: bool,
+    pub color: Color,
+}
+
+/// Describes the pixel at `x`, `y`, or `None` if it isn't loaded.
+pub fn describe_pixel(
+    chunk_handler: &impl FSChunkAccess,
+    registries: &Registries,
+    x: i64,
+    y: i64,
+) -> Option<PixelInfo> {
+    let mat = chunk_handler.pixel(x, y).ok()?;
+    Some(PixelInfo {
+        material_id: mat.material_id.clone(),
+        display_name: registries
+            .materials
+            .get(&mat.material_id)
+            .map(|m| m.display_name.clone()),
+        physics: mat.physics,
+        empty: mat.is_empty(&registries.materials),
+        color: mat.color,
+    })
+}
+
+/// A tooltip describing the pixel under the cursor.
+pub struct PixelInfoUI;
+
+impl PixelInfoUI {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn render(&mut self, egui_ctx: &egui::Context, ctx: &mut DebugUIsContext) {
+        // the cursor is over a window, not the world
+        if egui_ctx.is_pointer_over_area() {
+            return;
+        }
+        let Some((x, y)) = ctx.hovered_pixel else {
+            return;
+        };
+
+        egui::show_tooltip_at_pointer(egui_ctx, egui::Id::new("Pixel Info"), |ui| {
+            ui.label(format!("({x}, {y})"));
+            match describe_pixel(ctx.chunk_handler, ctx.registries, x, y) {
+                Some(info) => {
+                    ui.label(format!(
+                        "{} ({})",
+                        info.display_name.as_deref().unwrap_or("Unregistered"),
+                        info.material_id
+                    ));
+                    ui.label(format!("{:?}", info.physics));
+                    if !info.empty {
+                        let Color { r, g, b, a } = info.color;
+                        ui.horizontal(|ui| {
+                            ui.colored_label(
+                                egui::Color32::from_rgba_unmultiplied(r, g, b, 255),
+                                "■",
+                            );
+                            ui.label(format!("rgba({r}, {g}, {b}, {a})"));
+                        });
+                    }
+                },
+                None => {
+                    ui.label("Not loaded");
+                },
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        world::{
+            chunk_access::FSChunkAccess,
+            chunk_handler::ChunkHandler,
+            gen::TestGenerator,
+            material::{self, color::Color, MaterialInstance, PhysicsType},
+            Chunk, ChunkState, CHUNK_AREA,
+        },
+        Registries,
+    };
+
+    use crate::world::ClientChunk;
+
+    use super::describe_pixel;
+
+    #[test]
+    fn describes_known_pixel() {
+        let mut ch = ChunkHandler::<ClientChunk>::new(TestGenerator::new(), None);
+        let mut chunk = ClientChunk::new_empty(0, 0);
+        chunk.set_pixels(
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap(),
+        );
+        chunk.set_state(ChunkState::Active);
+        ch.manager.insert((0, 0), chunk);
+
+        let registries = Registries {
+            materials: material::init_material_types(),
+            ..Registries::empty()
+        };
+        let color = Color::rgb(90, 90, 100);
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, color);
+        ch.set_pixel(5, 7, stone).unwrap();
+
+        let info = describe_pixel(&ch, &registries, 5, 7).unwrap();
+        assert_eq!(info.material_id, *material::SMOOTH_STONE);
+        assert_eq!(info.display_name.as_deref(), Some("Smoth Stone"));
+        assert_eq!(info.physics, PhysicsType::Solid);
+        assert!(!info.empty);
+        assert_eq!(info.color, color);
+
+        let air = describe_pixel(&ch, &registries, 6, 7).unwrap();
+        assert_eq!(air.material_id, *material::AIR);
+        assert_eq!(air.physics, PhysicsType::Air);
+        assert!(air.empty);
+
+        // outside the loaded chunk
+        assert!(describe_pixel(&ch, &registries, -5, 7).is_none());
+    }
+}
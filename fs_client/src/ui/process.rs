@@ -0,0 +1,79 @@
+use egui::{
+    collapsing_header::CollapsingState,
+    plot::{Line, Plot, PlotPoints},
+    Id,
+};
+use fs_common::game::{format_bytes, format_cpu};
+
+use super::DebugUIsContext;
+
+pub struct ProcessUI;
+
+impl ProcessUI {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn render(&mut self, egui_ctx: &egui::Context, ctx: &mut DebugUIsContext) {
+        let id = Id::new("Process");
+        CollapsingState::load_with_default_open(egui_ctx, id.with("collapsing"), false)
+            .store(egui_ctx);
+
+        egui::Window::new("Process")
+            .id(id)
+            .resizable(false)
+            .default_width(250.0)
+            .show(egui_ctx, |ui| {
+                let stats = ctx.process_stats;
+
+                ui.label(format!(
+                    "CPU: {}",
+                    stats
+                        .cpu_usage
+                        .map_or_else(|| "n/a".to_string(), format_cpu)
+                ));
+                Plot::new("process_cpu")
+                    .view_aspect(3.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_boxed_zoom(false)
+                    .show_axes([false, true])
+                    .include_y(0.0)
+                    .include_y(100.0)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(
+                            stats
+                                .cpu_history
+                                .iter()
+                                .enumerate()
+                                .map(|(i, c)| [i as f64, f64::from(*c)])
+                                .collect::<PlotPoints>(),
+                        ));
+                    });
+
+                ui.label(format!(
+                    "Memory: {}",
+                    stats.memory.map_or_else(|| "n/a".to_string(), format_bytes)
+                ));
+                Plot::new("process_memory")
+                    .view_aspect(3.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_boxed_zoom(false)
+                    .show_axes([false, true])
+                    .include_y(0.0)
+                    .show(ui, |plot_ui| {
+                        // in MB
+                        plot_ui.line(Line::new(
+                            stats
+                                .memory_history
+                                .iter()
+                                .enumerate()
+                                .map(|(i, m)| [i as f64, *m as f64 / 1_000_000.0])
+                                .collect::<PlotPoints>(),
+                        ));
+                    });
+            });
+    }
+}
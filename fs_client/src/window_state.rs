@@ -0,0 +1,93 @@
+use fs_common::game::common::Settings;
+use glutin::event::WindowEvent;
+
+/// What the window system has told us about the window, to decide whether to tick and render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowState {
+    focused: bool,
+    /// Some platforms resize minimized windows to 0x0.
+    zero_size: bool,
+    /// Others tell us the window can't be seen at all.
+    occluded: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self { focused: true, zero_size: false, occluded: false }
+    }
+}
+
+impl WindowState {
+    pub fn on_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Focused(focused) => self.focused = *focused,
+            WindowEvent::Resized(size) => self.zero_size = size.width == 0 || size.height == 0,
+            WindowEvent::Occluded(occluded) => self.occluded = *occluded,
+            _ => {},
+        }
+    }
+
+    pub fn minimized(self) -> bool {
+        self.zero_size || self.occluded
+    }
+
+    /// Whether the game should tick. Minimized always pauses, losing focus only if
+    ///   [`Settings::pause_on_lost_focus`] is set.
+    pub fn should_tick(self, settings: &Settings) -> bool {
+        !self.minimized() && (self.focused || !settings.pause_on_lost_focus)
+    }
+
+    /// Whether there's any point in rendering, nothing can be seen while minimized.
+    pub fn should_render(self) -> bool {
+        !self.minimized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::Settings;
+    use glutin::{dpi::PhysicalSize, event::WindowEvent};
+
+    use super::WindowState;
+
+    #[test]
+    fn tick_and_render_decisions() {
+        let pause = Settings { pause_on_lost_focus: true, ..Settings::default() };
+        let keep_going = Settings { pause_on_lost_focus: false, ..Settings::default() };
+
+        let state = |events: &[WindowEvent]| {
+            let mut state = WindowState::default();
+            for e in events {
+                state.on_event(e);
+            }
+            state
+        };
+
+        let normal = state(&[]);
+        assert!(normal.should_tick(&pause) && normal.should_render());
+
+        let unfocused = state(&[WindowEvent::Focused(false)]);
+        assert!(!unfocused.should_tick(&pause));
+        assert!(unfocused.should_tick(&keep_going));
+        assert!(unfocused.should_render());
+
+        for minimized in [
+            state(&[WindowEvent::Resized(PhysicalSize::new(0, 0))]),
+            state(&[WindowEvent::Occluded(true)]),
+            state(&[WindowEvent::Focused(false), WindowEvent::Occluded(true)]),
+        ] {
+            assert!(!minimized.should_tick(&pause));
+            assert!(!minimized.should_tick(&keep_going));
+            assert!(!minimized.should_render());
+        }
+
+        let restored = state(&[
+            WindowEvent::Resized(PhysicalSize::new(0, 0)),
+            WindowEvent::Occluded(true),
+            WindowEvent::Resized(PhysicalSize::new(1280, 720)),
+            WindowEvent::Occluded(false),
+            WindowEvent::Focused(true),
+        ]);
+        assert_eq!(restored, normal);
+    }
+}
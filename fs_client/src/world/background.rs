@@ -0,0 +1,115 @@
+use fs_common::game::common::{BackgroundStyle, Rect, Settings};
+use glium::DrawParameters;
+
+use crate::render::drawing::RenderTarget;
+
+/// Where a background layer's origin is in the world, for a camera at `camera`.
+///
+/// A `parallax` of 1 keeps the layer fixed in the world, 0 keeps it fixed on screen (it follows
+///   the camera around), and anything in between makes it look further away than the terrain.
+pub fn parallax_offset(camera: (f64, f64), parallax: f64) -> (f64, f64) {
+    (camera.0 * (1.0 - parallax), camera.1 * (1.0 - parallax))
+}
+
+/// Converts a world y coordinate on a parallax layer to a y coordinate on screen.
+pub fn parallax_screen_y(
+    world_y: f64,
+    camera_y: f64,
+    parallax: f64,
+    camera_scale: f64,
+    screen_height: f64,
+) -> f64 {
+    let (_, offset_y) = parallax_offset((0.0, camera_y), parallax);
+    (world_y + offset_y - camera_y) * camera_scale + screen_height / 2.0
+}
+
+/// Fills the screen with [`Settings::background`], before anything else in the world is drawn.
+///
+/// This is drawn in screen space under the chunks, so air pixels (which are transparent) show it.
+pub fn draw_background(
+    target: &mut RenderTarget,
+    settings: &Settings,
+    camera_y: f64,
+    camera_scale: f64,
+) {
+    profiling::scope!("draw_background");
+    let (w, h) = (target.width() as f32, target.height() as f32);
+
+    match settings.background {
+        BackgroundStyle::None => {},
+        BackgroundStyle::Solid => {
+            target.rectangle(
+                Rect::new(0.0, 0.0, w, h),
+                settings.background_top,
+                DrawParameters::default(),
+            );
+        },
+        BackgroundStyle::Gradient => {
+            let half = settings.background_gradient_height / 2.0;
+            let screen_y = |world_y: f64| {
+                parallax_screen_y(
+                    world_y,
+                    camera_y,
+                    settings.background_parallax,
+                    camera_scale,
+                    f64::from(h),
+                ) as f32
+            };
+            let top = screen_y(settings.background_horizon - half).clamp(0.0, h);
+            let bottom = screen_y(settings.background_horizon + half).clamp(0.0, h);
+
+            if top > 0.0 {
+                target.rectangle(
+                    Rect::new(0.0, 0.0, w, top),
+                    settings.background_top,
+                    DrawParameters::default(),
+                );
+            }
+            if bottom > top {
+                // only the part of the gradient that's on screen, with its colors cut to match
+                let full_top = screen_y(settings.background_horizon - half);
+                let full_bottom = screen_y(settings.background_horizon + half);
+                let color_at = |y: f32| {
+                    let t = ((y - full_top) / (full_bottom - full_top)).clamp(0.0, 1.0);
+                    settings.background_top.lerp(settings.background_bottom, t)
+                };
+                target.rectangle_gradient(
+                    Rect::new(0.0, top, w, bottom),
+                    color_at(top),
+                    color_at(bottom),
+                    DrawParameters::default(),
+                );
+            }
+            if bottom < h {
+                target.rectangle(
+                    Rect::new(0.0, bottom, w, h),
+                    settings.background_bottom,
+                    DrawParameters::default(),
+                );
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parallax_offset, parallax_screen_y};
+
+    #[test]
+    fn parallax() {
+        // stuck to the world doesn't move
+        assert_eq!(parallax_offset((300.0, -120.0), 1.0), (0.0, 0.0));
+        // stuck to the screen moves with the camera
+        assert_eq!(parallax_offset((300.0, -120.0), 0.0), (300.0, -120.0));
+        // in between moves some of the way
+        assert_eq!(parallax_offset((300.0, -120.0), 0.25), (225.0, -90.0));
+
+        // the horizon at y = 0 with the camera 100 pixels below it, on a 600 pixel tall screen
+        assert_eq!(parallax_screen_y(0.0, 100.0, 1.0, 1.0, 600.0), 200.0);
+        assert_eq!(parallax_screen_y(0.0, 100.0, 1.0, 2.0, 600.0), 100.0);
+        // a far away layer barely moves
+        assert_eq!(parallax_screen_y(0.0, 100.0, 0.5, 1.0, 600.0), 250.0);
+        assert_eq!(parallax_screen_y(0.0, 100.0, 0.0, 1.0, 600.0), 300.0);
+        assert_eq!(parallax_screen_y(0.0, 10_000.0, 0.0, 3.0, 600.0), 300.0);
+    }
+}
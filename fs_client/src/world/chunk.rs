@@ -1,4 +1,4 @@
-use chunksystem::ChunkQuery;
+use chunksystem::{ChunkKey, ChunkQuery};
 use core::slice;
 use fs_common::game::common::{
     world::{
@@ -9,15 +9,21 @@ use fs_common::game::common::{
     },
     ChunkCollisionOverlay,
 };
-use std::{borrow::Cow, convert::TryInto, sync::Arc};
+use std::{borrow::Cow, convert::TryInto, sync::Arc, time::Duration};
 
 use fs_common::game::common::{
     world::{
         chunk_data::{CommonChunkData, SidedChunkData},
-        material::{color::Color, MaterialInstance},
+        flow::FlowBuffer,
+        material::{
+            color::Color,
+            render_layer::{RenderLayer, RenderLayers},
+            MaterialInstance, AMBIENT_TEMPERATURE,
+        },
         mesh::{self, Mesh},
         tile_entity::{TileEntity, TileEntityCommon},
-        ChunkRigidBodyState, ChunkState, SidedChunk, CHUNK_SIZE, LIGHT_SCALE,
+        ChunkRigidBodyState, ChunkState, SidedChunk, CHUNK_HEIGHT, CHUNK_WIDTH, LIGHT_HEIGHT,
+        LIGHT_SCALE, LIGHT_WIDTH,
     },
     FileHelper, Rect, Settings,
 };
@@ -28,7 +34,13 @@ use glium::{
 
 use crate::render::{drawing::RenderTarget, shaders::Shaders};
 
-use super::chunk_data::tile_entity::TileEntityClient;
+use super::{
+    chunk_data::tile_entity::TileEntityClient,
+    heat_haze::{heat_map, update_temperatures, MaterialTemperatures},
+    texture_stream::{
+        add_dirty_rect, pack_rect, ChunkTextureStreamer, PackedTexture, TextureVersions,
+    },
+};
 
 pub struct ClientChunk {
     pub data: CommonChunkData<Self>,
@@ -50,11 +62,22 @@ impl Chunk for ClientChunk {
                 pixel_data: Box::new([Color::TRANSPARENT; CHUNK_AREA]),
                 lighting_data: Box::new([[0.0; 4]; CHUNK_AREA]),
                 background_data: Box::new([Color::TRANSPARENT; CHUNK_AREA]),
+                main_data: Box::new([Color::TRANSPARENT; CHUNK_AREA]),
+                foreground_data: Box::new([Color::TRANSPARENT; CHUNK_AREA]),
                 dirty: true,
+                pixel_dirty_rect: None,
+                texture_versions: TextureVersions::default(),
                 was_dirty: true,
                 lighting_dirty: true,
                 was_lighting_dirty: true,
                 background_dirty: true,
+                background_dirty_rect: None,
+                foreground_dirty: true,
+                foreground_dirty_rect: None,
+                temperature_data: Box::new([AMBIENT_TEMPERATURE; CHUNK_AREA]),
+                heat_dirty: true,
+                heat_dirty_rect: None,
+                hot: false,
                 pixels_updated_last_update: true,
                 lighting_updated_last_update: true,
                 dist_to_nearest_dirty_light: None,
@@ -211,6 +234,30 @@ impl Chunk for ClientChunk {
         &self.data.background
     }
 
+    fn flow_mut(&mut self) -> &mut Option<Box<FlowBuffer>> {
+        &mut self.data.flow
+    }
+
+    fn flow(&self) -> &Option<Box<FlowBuffer>> {
+        &self.data.flow
+    }
+
+    fn sim_time(&self) -> Option<Duration> {
+        self.data.sim_time
+    }
+
+    fn set_sim_time(&mut self, time: Option<Duration>) {
+        self.data.sim_time = time;
+    }
+
+    fn revealed(&self) -> bool {
+        self.data.revealed
+    }
+
+    fn set_revealed(&mut self, revealed: bool) {
+        self.data.revealed = revealed;
+    }
+
     #[profiling::function]
     fn set_background_pixel_colors(&mut self, colors: Box<[Color; CHUNK_AREA]>) {
         self.graphics.replace_background(colors);
@@ -226,7 +273,9 @@ impl Chunk for ClientChunk {
 
     fn mark_dirty(&mut self) {
         self.graphics.dirty = true;
+        self.graphics.pixel_dirty_rect = None;
         self.graphics.background_dirty = true;
+        self.graphics.background_dirty_rect = None;
         self.graphics.lighting_dirty = true;
     }
 
@@ -237,8 +286,11 @@ impl Chunk for ClientChunk {
 
         let vs: Vec<f64> = mesh::pixels_to_valuemap(self.data.pixels.as_ref().unwrap().as_ref());
 
-        let generated =
-            mesh::generate_mesh_with_simplified(&vs, u32::from(CHUNK_SIZE), u32::from(CHUNK_SIZE));
+        let generated = mesh::generate_mesh_with_simplified(
+            &vs,
+            u32::from(CHUNK_WIDTH),
+            u32::from(CHUNK_HEIGHT),
+        );
 
         if let Ok(r) = generated {
             self.mesh = Some(r.0);
@@ -338,11 +390,15 @@ pub struct ChunkGraphicsData {
     pub display: Display,
     pub texture: Texture2d,
     pub background_texture: Texture2d,
+    /// Pixels of materials on [`RenderLayer::Foreground`], which are left out of `texture`.
+    pub foreground_texture: Texture2d,
     pub lighting_src_buf: PixelBuffer<(f32, f32, f32, f32)>,
     pub lighting_src: Texture2d,
     pub lighting_dst: Texture2d,
     pub lighting_neighbors: Texture2d,
     pub lighting_constant_black: Texture2d,
+    /// How much each part of the chunk shimmers, laid out like the light grid (see [`heat_map`]).
+    pub heat_texture: Texture2d,
 }
 
 pub struct ChunkGraphics {
@@ -350,11 +406,33 @@ pub struct ChunkGraphics {
     pub pixel_data: Box<[Color; CHUNK_AREA]>,
     pub lighting_data: Box<[[f32; 4]; CHUNK_AREA]>,
     pub background_data: Box<[Color; CHUNK_AREA]>,
+    /// Colors of the pixels on [`RenderLayer::Main`] as they're drawn, see [`split_layers`].
+    ///
+    /// Only used (and kept up to date) while some material is translucent or outside the main layer,
+    ///   otherwise `pixel_data` is drawn as is.
+    pub main_data: Box<[Color; CHUNK_AREA]>,
+    /// Colors of the pixels on [`RenderLayer::Foreground`], see [`split_layers`].
+    pub foreground_data: Box<[Color; CHUNK_AREA]>,
     pub dirty: bool,
+    /** The part of `pixel_data` changed since the last upload, or the whole chunk if `None` while `dirty` */
+    pub pixel_dirty_rect: Option<Rect<i32>>,
+    pub texture_versions: TextureVersions,
     pub was_dirty: bool,
     pub lighting_dirty: bool,
     pub was_lighting_dirty: bool,
     pub background_dirty: bool,
+    /** Like `pixel_dirty_rect`, for `background_data` */
+    pub background_dirty_rect: Option<Rect<i32>>,
+    pub foreground_dirty: bool,
+    /** Like `pixel_dirty_rect`, for `foreground_data` */
+    pub foreground_dirty_rect: Option<Rect<i32>>,
+    /// The temperature of each pixel, see [`update_temperatures`].
+    pub temperature_data: Box<[f32; CHUNK_AREA]>,
+    pub heat_dirty: bool,
+    /** The part of `temperature_data` out of date since the last [`ChunkGraphics::update_heat`] */
+    pub heat_dirty_rect: Option<Rect<i32>>,
+    /// Whether any part of the chunk is hot enough to shimmer.
+    pub hot: bool,
 
     pub pixels_updated_last_update: bool,
     pub lighting_updated_last_update: bool,
@@ -375,6 +453,8 @@ impl ChunkGraphics {
                 self.lighting_dirty = true;
             }
             self.pixel_data[i] = color;
+
+            add_dirty_rect(&mut self.pixel_dirty_rect, self.dirty, pixel_rect(i));
             self.dirty = true;
         }
     }
@@ -398,95 +478,106 @@ impl ChunkGraphics {
         let i: ChunkLocalIndex = pos.into();
         if self.background_data[i] != color {
             self.background_data[i] = color;
+            add_dirty_rect(
+                &mut self.background_dirty_rect,
+                self.background_dirty,
+                pixel_rect(i),
+            );
             self.background_dirty = true;
         }
     }
 
     // #[profiling::function]
-    pub fn update_texture(&mut self) {
-        self.pixels_updated_last_update = false;
-        if self.dirty {
-            if let Some(data) = &mut self.data {
-                profiling::scope!("dirty");
-
-                let image = {
-                    profiling::scope!("RawImage2d");
-
-                    glium::texture::RawImage2d {
-                        data: Cow::Borrowed({
-                            let color_sl = self.pixel_data.as_slice();
-                            unsafe {
-                                // Safety: Color is statically guaranteed to be equivalent to four u8s
-                                core::slice::from_raw_parts(
-                                    color_sl.as_ptr().cast::<u8>(),
-                                    color_sl.len() * 4,
-                                )
-                            }
-                        }),
-                        width: CHUNK_SIZE.into(),
-                        height: CHUNK_SIZE.into(),
-                        format: glium::texture::ClientFormat::U8U8U8U8,
+    pub fn update_texture(
+        &mut self,
+        chunk: ChunkKey,
+        streamer: &ChunkTextureStreamer,
+        pixels: Option<&[MaterialInstance; CHUNK_AREA]>,
+        layers: &RenderLayers,
+    ) {
+        if self.dirty && self.data.is_some() {
+            profiling::scope!("dirty");
+
+            // packing happens on the streamer's thread, the upload itself in `apply_packed`
+            let rect = self.pixel_dirty_rect.take().unwrap_or_else(full_rect);
+            add_dirty_rect(&mut self.heat_dirty_rect, self.heat_dirty, rect);
+            self.heat_dirty = true;
+            let version = match pixels {
+                Some(pixels) if layers.any_outside_main() || layers.any_translucent() => {
+                    if split_layers(
+                        &self.pixel_data,
+                        &mut self.main_data,
+                        &mut self.foreground_data,
+                        pixels,
+                        layers,
+                        rect,
+                    ) {
+                        add_dirty_rect(
+                            &mut self.foreground_dirty_rect,
+                            self.foreground_dirty,
+                            rect,
+                        );
+                        self.foreground_dirty = true;
                     }
-                };
-
-                {
-                    profiling::scope!("write");
-                    data.texture.write(
-                        glium::Rect {
-                            left: 0,
-                            bottom: 0,
-                            width: CHUNK_SIZE.into(),
-                            height: CHUNK_SIZE.into(),
-                        },
-                        image,
-                    );
-                }
+                    streamer.submit(chunk, rect, &self.main_data)
+                },
+                _ => streamer.submit(chunk, rect, &self.pixel_data),
+            };
+            self.texture_versions.submitted(version);
+            self.dirty = false;
+        }
 
-                self.pixels_updated_last_update = true;
-                self.dirty = false;
+        if self.foreground_dirty {
+            if let Some(data) = &mut self.data {
+                profiling::scope!("foreground_dirty");
+                let rect = self.foreground_dirty_rect.take().unwrap_or_else(full_rect);
+                write_rect(&data.foreground_texture, &self.foreground_data, rect);
+                self.foreground_dirty = false;
             }
         }
 
         if self.background_dirty {
             if let Some(data) = &mut self.data {
                 profiling::scope!("background_dirty");
-                let image = {
-                    profiling::scope!("RawImage2d");
-
-                    glium::texture::RawImage2d {
-                        data: Cow::Borrowed({
-                            let color_sl = self.background_data.as_slice();
-                            unsafe {
-                                // Safety: Color is statically guaranteed to be equivalent to four u8s
-                                core::slice::from_raw_parts(
-                                    color_sl.as_ptr().cast::<u8>(),
-                                    color_sl.len() * 4,
-                                )
-                            }
-                        }),
-                        width: CHUNK_SIZE.into(),
-                        height: CHUNK_SIZE.into(),
-                        format: glium::texture::ClientFormat::U8U8U8U8,
-                    }
-                };
-
-                {
-                    profiling::scope!("write");
-                    data.background_texture.write(
-                        glium::Rect {
-                            left: 0,
-                            bottom: 0,
-                            width: CHUNK_SIZE.into(),
-                            height: CHUNK_SIZE.into(),
-                        },
-                        image,
-                    );
-                }
+                let rect = self.background_dirty_rect.take().unwrap_or_else(full_rect);
+                write_rect(&data.background_texture, &self.background_data, rect);
                 self.background_dirty = false;
             }
         }
     }
 
+    /// Brings `temperature_data` up to date with the pixels that changed and uploads the chunk's
+    ///   heat texture if anything did.
+    pub fn update_heat(
+        &mut self,
+        pixels: Option<&[MaterialInstance; CHUNK_AREA]>,
+        temperatures: &MaterialTemperatures,
+    ) {
+        if let (true, Some(data), Some(pixels)) = (self.heat_dirty, &self.data, pixels) {
+            profiling::scope!("heat_dirty");
+            let rect = self.heat_dirty_rect.take().unwrap_or_else(full_rect);
+            update_temperatures(&mut self.temperature_data, pixels, temperatures, rect);
+
+            let heat = heat_map(&self.temperature_data);
+            self.hot = heat.iter().any(|&h| h > 0.0);
+            data.heat_texture.write(
+                glium::Rect {
+                    left: 0,
+                    bottom: 0,
+                    width: LIGHT_WIDTH.into(),
+                    height: LIGHT_HEIGHT.into(),
+                },
+                glium::texture::RawImage2d {
+                    data: Cow::Owned(heat),
+                    width: LIGHT_WIDTH.into(),
+                    height: LIGHT_HEIGHT.into(),
+                    format: glium::texture::ClientFormat::F32,
+                },
+            );
+            self.heat_dirty = false;
+        }
+    }
+
     // #[profiling::function]
     pub fn update_lighting(
         &mut self,
@@ -513,8 +604,8 @@ impl ChunkGraphics {
                             };
                             sl
                         }),
-                        width: CHUNK_SIZE.into(),
-                        height: CHUNK_SIZE.into(),
+                        width: CHUNK_WIDTH.into(),
+                        height: CHUNK_HEIGHT.into(),
                         format: glium::texture::ClientFormat::F32F32F32F32,
                     }
                 };
@@ -525,8 +616,8 @@ impl ChunkGraphics {
                         glium::Rect {
                             left: 0,
                             bottom: 0,
-                            width: CHUNK_SIZE.into(),
-                            height: CHUNK_SIZE.into(),
+                            width: CHUNK_WIDTH.into(),
+                            height: CHUNK_HEIGHT.into(),
                         },
                         src_image,
                     );
@@ -627,10 +718,45 @@ impl ChunkGraphics {
         }
     }
 
+    /// Uploads a buffer packed by the [`ChunkTextureStreamer`], unless it's stale.
+    pub fn apply_packed(&mut self, packed: &PackedTexture) {
+        if let Some(data) = &self.data {
+            if self.texture_versions.accept(packed.version) {
+                profiling::scope!("write");
+                data.texture.write(
+                    glium::Rect {
+                        left: packed.rect.x1 as u32,
+                        bottom: packed.rect.y1 as u32,
+                        width: packed.rect.width() as u32,
+                        height: packed.rect.height() as u32,
+                    },
+                    glium::texture::RawImage2d {
+                        data: Cow::Borrowed(packed.rgba.as_slice()),
+                        width: packed.rect.width() as u32,
+                        height: packed.rect.height() as u32,
+                        format: glium::texture::ClientFormat::U8U8U8U8,
+                    },
+                );
+                self.pixels_updated_last_update = true;
+            }
+        }
+    }
+
+    /// Sorts every pixel into its layer again, for when which layer materials are on or their
+    ///   alpha changed (see [`RenderLayers`]).
+    pub fn relayer(&mut self) {
+        self.foreground_data.fill(Color::TRANSPARENT);
+        self.foreground_dirty = true;
+        self.foreground_dirty_rect = None;
+        self.pixel_dirty_rect = None;
+        self.dirty = true;
+    }
+
     #[profiling::function]
     #[allow(clippy::cast_lossless)]
     pub fn replace(&mut self, colors: Box<[Color; CHUNK_AREA]>) {
         self.pixel_data = colors;
+        self.pixel_dirty_rect = None;
         self.dirty = true;
     }
 
@@ -638,18 +764,92 @@ impl ChunkGraphics {
     #[allow(clippy::cast_lossless)]
     pub fn replace_background(&mut self, colors: Box<[Color; CHUNK_AREA]>) {
         self.background_data = colors;
+        self.background_dirty_rect = None;
         self.background_dirty = true;
     }
 }
 
+/// The whole chunk, for textures that changed without a dirty rect.
+fn full_rect() -> Rect<i32> {
+    Rect::new(0, 0, i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT))
+}
+
+/// The 1 pixel rect at `i`, to add to a dirty rect.
+fn pixel_rect(i: ChunkLocalIndex) -> Rect<i32> {
+    let (x, y) = *ChunkLocalPosition::from(i);
+    let (x, y) = (i32::from(x), i32::from(y));
+    Rect::new(x, y, x + 1, y + 1)
+}
+
+/// Uploads `rect` of `colors` into the same part of `texture`, leaving the rest as it was.
+fn write_rect(texture: &Texture2d, colors: &[Color; CHUNK_AREA], rect: Rect<i32>) {
+    profiling::scope!("write");
+    let (width, height) = (rect.width() as u32, rect.height() as u32);
+    texture.write(
+        glium::Rect {
+            left: rect.x1 as u32,
+            bottom: rect.y1 as u32,
+            width,
+            height,
+        },
+        glium::texture::RawImage2d {
+            data: Cow::Owned(pack_rect(colors, rect)),
+            width,
+            height,
+            format: glium::texture::ClientFormat::U8U8U8U8,
+        },
+    );
+}
+
+/// Sorts the colors of the pixels in `rect` into `main` and `foreground` by their [`RenderLayer`],
+///   with each material's alpha applied (see [`RenderLayers::color_of`]).
+///
+/// Pixels are left transparent in the layer they aren't on, and everything outside `rect` is left
+///   alone. Returns whether `foreground` changed.
+pub fn split_layers(
+    colors: &[Color; CHUNK_AREA],
+    main: &mut [Color; CHUNK_AREA],
+    foreground: &mut [Color; CHUNK_AREA],
+    pixels: &[MaterialInstance; CHUNK_AREA],
+    layers: &RenderLayers,
+    rect: Rect<i32>,
+) -> bool {
+    let mut changed = false;
+    for y in rect.range_tb() {
+        for x in rect.range_lr() {
+            let i = (x + y * i32::from(CHUNK_WIDTH)) as usize;
+            let color = layers.color_of(&pixels[i], colors[i]);
+            let (main_color, foreground_color) = match layers.layer_of(&pixels[i]) {
+                RenderLayer::Main => (color, Color::TRANSPARENT),
+                RenderLayer::Foreground => (Color::TRANSPARENT, color),
+            };
+            main[i] = main_color;
+            if foreground[i] != foreground_color {
+                foreground[i] = foreground_color;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
 impl ClientChunk {
     #[profiling::function]
     fn update_graphics(
         &mut self,
         surrounding: Option<[Option<&chunksystem::Chunk<Self>>; 4]>,
         shaders: &Shaders,
+        streamer: &ChunkTextureStreamer,
+        layers: &RenderLayers,
+        temperatures: Option<&MaterialTemperatures>,
     ) -> Result<(), String> {
-        self.graphics.update_texture();
+        let key = (self.chunk_x(), self.chunk_y());
+        self.graphics
+            .update_texture(key, streamer, self.data.pixels.as_deref(), layers);
+        if let Some(temperatures) = temperatures {
+            self.graphics
+                .update_heat(self.data.pixels.as_deref(), temperatures);
+        }
         self.graphics.update_lighting(surrounding, shaders);
 
         Ok(())
@@ -807,8 +1007,8 @@ impl ChunkGraphics {
                             )
                         }
                     }),
-                    width: CHUNK_SIZE.into(),
-                    height: CHUNK_SIZE.into(),
+                    width: CHUNK_WIDTH.into(),
+                    height: CHUNK_HEIGHT.into(),
                     format: glium::texture::ClientFormat::U8U8U8U8,
                 }
             };
@@ -832,8 +1032,8 @@ impl ChunkGraphics {
                             )
                         }
                     }),
-                    width: CHUNK_SIZE.into(),
-                    height: CHUNK_SIZE.into(),
+                    width: CHUNK_WIDTH.into(),
+                    height: CHUNK_HEIGHT.into(),
                     format: glium::texture::ClientFormat::U8U8U8U8,
                 }
             };
@@ -845,10 +1045,23 @@ impl ChunkGraphics {
             )
             .unwrap();
 
+            let foreground_texture = Texture2d::with_format(
+                &target.display,
+                glium::texture::RawImage2d {
+                    data: Cow::Owned(vec![0_u8; CHUNK_AREA * 4]),
+                    width: CHUNK_WIDTH.into(),
+                    height: CHUNK_HEIGHT.into(),
+                    format: glium::texture::ClientFormat::U8U8U8U8,
+                },
+                glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                glium::texture::MipmapsOption::NoMipmap,
+            )
+            .unwrap();
+
             let default_src = glium::texture::RawImage2d {
                 data: Cow::Owned(vec![0.0; CHUNK_AREA * 4]),
-                width: CHUNK_SIZE.into(),
-                height: CHUNK_SIZE.into(),
+                width: CHUNK_WIDTH.into(),
+                height: CHUNK_HEIGHT.into(),
                 format: glium::texture::ClientFormat::F32F32F32F32,
             };
 
@@ -861,14 +1074,9 @@ impl ChunkGraphics {
             .unwrap();
 
             let default_dst = glium::texture::RawImage2d {
-                data: Cow::Owned(vec![
-                    0.0;
-                    ((CHUNK_SIZE / (LIGHT_SCALE as u16)) * (CHUNK_SIZE / (LIGHT_SCALE as u16)))
-                        as usize
-                        * 4
-                ]),
-                width: (CHUNK_SIZE / (LIGHT_SCALE as u16)).into(),
-                height: (CHUNK_SIZE / (LIGHT_SCALE as u16)).into(),
+                data: Cow::Owned(vec![0.0; (LIGHT_WIDTH * LIGHT_HEIGHT) as usize * 4]),
+                width: LIGHT_WIDTH.into(),
+                height: LIGHT_HEIGHT.into(),
                 format: glium::texture::ClientFormat::F32F32F32F32,
             };
 
@@ -883,13 +1091,10 @@ impl ChunkGraphics {
             let default_neighbors = glium::texture::RawImage2d {
                 data: Cow::Owned(vec![
                     0.0;
-                    ((CHUNK_SIZE / (LIGHT_SCALE as u16) + 2)
-                        * (CHUNK_SIZE / (LIGHT_SCALE as u16) + 2))
-                        as usize
-                        * 4
+                    ((LIGHT_WIDTH + 2) * (LIGHT_HEIGHT + 2)) as usize * 4
                 ]),
-                width: (CHUNK_SIZE / (LIGHT_SCALE as u16) + 2).into(),
-                height: (CHUNK_SIZE / (LIGHT_SCALE as u16) + 2).into(),
+                width: (LIGHT_WIDTH + 2).into(),
+                height: (LIGHT_HEIGHT + 2).into(),
                 format: glium::texture::ClientFormat::F32F32F32F32,
             };
 
@@ -916,20 +1121,39 @@ impl ChunkGraphics {
             )
             .unwrap();
 
+            let heat_texture = Texture2d::with_format(
+                &target.display,
+                glium::texture::RawImage2d {
+                    data: Cow::Owned(vec![0.0_f32; (LIGHT_WIDTH * LIGHT_HEIGHT) as usize]),
+                    width: LIGHT_WIDTH.into(),
+                    height: LIGHT_HEIGHT.into(),
+                    format: glium::texture::ClientFormat::F32,
+                },
+                glium::texture::UncompressedFloatFormat::F32,
+                glium::texture::MipmapsOption::NoMipmap,
+            )
+            .unwrap();
+
             // lighting.write(rect, data)
-            // let lighting = Texture2d::empty(&target.display, CHUNK_SIZE.into(), CHUNK_SIZE.into()).unwrap();
+            // let lighting = Texture2d::empty(&target.display, CHUNK_WIDTH.into(), CHUNK_HEIGHT.into()).unwrap();
 
             self.data = Some(Arc::new(ChunkGraphicsData {
                 display: target.display.clone(),
                 texture,
                 background_texture,
+                foreground_texture,
                 lighting_src_buf: PixelBuffer::new_empty(&target.display, CHUNK_AREA),
                 lighting_src,
                 lighting_dst,
                 lighting_neighbors,
                 lighting_constant_black,
+                heat_texture,
             }));
             self.dirty = true;
+            self.heat_dirty = true;
+            self.heat_dirty_rect = None;
+            self.foreground_dirty = true;
+            self.foreground_dirty_rect = None;
         }
     }
 }
@@ -943,7 +1167,13 @@ pub trait ClientChunkHandlerExt {
         colors: Vec<Color>,
     ) -> Result<(), String>;
 
-    fn update_chunk_graphics(&mut self, shaders: &Shaders);
+    fn update_chunk_graphics(
+        &mut self,
+        shaders: &Shaders,
+        streamer: &ChunkTextureStreamer,
+        layers: &RenderLayers,
+        temperatures: Option<&MaterialTemperatures>,
+    );
 }
 
 impl ClientChunkHandlerExt for ChunkHandler<ClientChunk> {
@@ -962,11 +1192,11 @@ impl ClientChunkHandlerExt for ChunkHandler<ClientChunk> {
             ));
         }
 
-        if colors.len() != CHUNK_AREA * 4 {
+        if colors.len() != CHUNK_AREA {
             return Err(format!(
                 "colors Vec is the wrong size: {} (expected {})",
                 colors.len(),
-                CHUNK_AREA * 4
+                CHUNK_AREA
             ));
         }
 
@@ -988,15 +1218,30 @@ impl ClientChunkHandlerExt for ChunkHandler<ClientChunk> {
     }
 
     #[profiling::function]
-    fn update_chunk_graphics(&mut self, shaders: &Shaders) {
+    fn update_chunk_graphics(
+        &mut self,
+        shaders: &Shaders,
+        streamer: &ChunkTextureStreamer,
+        layers: &RenderLayers,
+        temperatures: Option<&MaterialTemperatures>,
+    ) {
         for ch in self.manager.chunks_iter_mut() {
             ch.graphics.was_dirty = ch.graphics.dirty;
             ch.graphics.was_lighting_dirty = ch.graphics.lighting_dirty;
+            ch.graphics.pixels_updated_last_update = false;
+        }
+
+        for packed in streamer.finished() {
+            if let Some(ch) = self.manager.chunk_at_mut(packed.chunk) {
+                ch.graphics.apply_packed(&packed);
+            }
         }
 
         self.manager
             .each_chunk_mut_with_surrounding_cardinal(|ch, others| {
-                ch.data.update_graphics(Some(others), shaders).unwrap();
+                ch.data
+                    .update_graphics(Some(others), shaders, streamer, layers, temperatures)
+                    .unwrap();
                 ch.graphics.prev_dist_to_nearest_dirty_light =
                     ch.graphics.dist_to_nearest_dirty_light;
             });
@@ -1017,3 +1262,72 @@ impl ClientChunkHandlerExt for ChunkHandler<ClientChunk> {
             });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        registry::Registry,
+        world::{
+            material::{
+                color::Color,
+                render_layer::{RenderLayer, RenderLayers},
+                Material, MaterialInstance,
+            },
+            CHUNK_AREA, CHUNK_WIDTH,
+        },
+        Rect,
+    };
+
+    use super::split_layers;
+
+    #[test]
+    fn split_layers_only_touches_rect() {
+        let mut registry = Registry::new();
+        registry.register(
+            "glass",
+            Material {
+                render_layer: RenderLayer::Foreground,
+                alpha: Some(128),
+                ..Material::default()
+            },
+        );
+        registry.register("stone", Material::default());
+        let layers = RenderLayers::from_registry(&registry);
+
+        let instance =
+            |id: &str| MaterialInstance { material_id: id.into(), ..MaterialInstance::air() };
+        let mut pixels = Box::new(std::array::from_fn::<_, CHUNK_AREA, _>(|_| {
+            instance("stone")
+        }));
+        pixels[1] = instance("glass");
+        let colors = Box::new([Color::WHITE; CHUNK_AREA]);
+        let mut main = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        let mut foreground = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+
+        let rect = Rect::new(0, 0, 2, 1);
+        assert!(split_layers(
+            &colors,
+            &mut main,
+            &mut foreground,
+            &pixels,
+            &layers,
+            rect
+        ));
+        assert_eq!(main[0], Color::WHITE);
+        assert_eq!(main[1], Color::TRANSPARENT);
+        assert_eq!(foreground[1], Color::WHITE.with_a(128));
+        // outside the rect is left for the next update that covers it
+        assert_eq!(main[2], Color::TRANSPARENT);
+        assert_eq!(main[usize::from(CHUNK_WIDTH)], Color::TRANSPARENT);
+
+        // nothing new for the foreground the second time
+        assert!(!split_layers(
+            &colors,
+            &mut main,
+            &mut foreground,
+            &pixels,
+            &layers,
+            rect
+        ));
+    }
+}
@@ -0,0 +1,70 @@
+use fs_common::game::common::world::material::color::Color;
+
+/// A color that only depends on a chunk's position, for telling chunks apart while debugging.
+///
+/// The hue comes from a hash of the coordinates and the brightness alternates like a checkerboard,
+///   so neighbouring chunks never look the same even when their hues happen to be close.
+pub fn chunk_debug_color(chunk_x: i32, chunk_y: i32) -> Color {
+    // splitmix64 finalizer
+    let mut h = (u64::from(chunk_x as u32) << 32) | u64::from(chunk_y as u32);
+    h = h.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+
+    let hue = (h % 360) as f32;
+    let value = if (chunk_x ^ chunk_y) & 1 == 0 {
+        1.0
+    } else {
+        0.5
+    };
+    hsv_to_color(hue, 0.8, value)
+}
+
+/// `hue` is in degrees, `saturation` and `value` from 0 to 1.
+fn hsv_to_color(hue: f32, saturation: f32, value: f32) -> Color {
+    let c = value * saturation;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = value - c;
+    let (r, g, b) = match (hue / 60.0) as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    Color::rgb(r + m, g + m, b + m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::chunk_debug_color;
+
+    #[test]
+    fn chunk_colors_are_stable_and_distinct() {
+        for x in -20..20 {
+            for y in -20..20 {
+                let color = chunk_debug_color(x, y);
+                assert_eq!(color, chunk_debug_color(x, y));
+                assert_eq!(color.a, 255);
+
+                for (dx, dy) in [(1, 0), (0, 1), (-1, 0), (0, -1)] {
+                    assert_ne!(
+                        color,
+                        chunk_debug_color(x + dx, y + dy),
+                        "({x}, {y}) looks the same as ({}, {})",
+                        x + dx,
+                        y + dy
+                    );
+                }
+            }
+        }
+
+        // works at the edges of the world too
+        assert_ne!(
+            chunk_debug_color(i32::MAX, 0),
+            chunk_debug_color(i32::MIN, 0)
+        );
+    }
+}
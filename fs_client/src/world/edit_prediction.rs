@@ -0,0 +1,115 @@
+use chunksystem::ChunkKey;
+use fs_common::game::common::world::{
+    chunk_index::ChunkLocalIndex,
+    material::{color::Color, MaterialInstance},
+    pixel_to_chunk,
+};
+
+/// Tool edits the client made to its own copy of the world before the server confirmed them,
+///   see [`PacketType::EditPixelsPacket`](fs_common::game::common::networking::PacketType::EditPixelsPacket).
+///
+/// Chunks from the server replace the client's copy, but the server can't have applied edits it
+///   hasn't answered yet, so those are put back on top until it does. The server answers right
+///   before sending the chunks an edit touched, so from then on its copy is used as is, and an
+///   edit it rejected or overwrote gets corrected instead of staying on the client.
+#[derive(Debug, Default)]
+pub struct EditPredictions {
+    next_id: u32,
+    /// Edited pixels (in world coordinates) by the id they were sent with, oldest first.
+    #[allow(clippy::type_complexity)]
+    pending: Vec<(u32, Vec<(i64, i64, MaterialInstance)>)>,
+}
+
+impl EditPredictions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remembers edited `pixels` until the server answers, returning the id to send them with.
+    pub fn predict(&mut self, pixels: Vec<(i64, i64, MaterialInstance)>) -> u32 {
+        let id = self.next_id;
+        self.next_id = id.wrapping_add(1);
+        self.pending.push((id, pixels));
+        id
+    }
+
+    /// The server handled the edit sent with `id`.
+    pub fn confirm(&mut self, id: u32) {
+        self.pending.retain(|(i, _)| *i != id);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Puts the edits the server hasn't answered yet back on top of its `pixels` and `colors`
+    ///   for `chunk`, in the order they were made.
+    pub fn reconcile(
+        &self,
+        chunk: ChunkKey,
+        pixels: &mut [MaterialInstance],
+        colors: &mut [Color],
+    ) {
+        for (_, edits) in &self.pending {
+            for (x, y, mat) in edits {
+                let (key, pos) = pixel_to_chunk(*x, *y);
+                if key != chunk {
+                    continue;
+                }
+
+                let i = *ChunkLocalIndex::from(pos);
+                if let (Some(p), Some(c)) = (pixels.get_mut(i), colors.get_mut(i)) {
+                    *p = mat.clone();
+                    *c = mat.color;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::world::{
+        material::{self, color::Color, MaterialInstance, PhysicsType},
+        CHUNK_AREA, CHUNK_WIDTH,
+    };
+
+    use super::EditPredictions;
+
+    #[test]
+    fn authoritative_chunk_replaces_prediction() {
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, Color::rgb(90, 90, 100));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 220, 100));
+        let server =
+            |mat: &MaterialInstance| (vec![mat.clone(); CHUNK_AREA], vec![mat.color; CHUNK_AREA]);
+        let i = 5 + 7 * usize::from(CHUNK_WIDTH);
+
+        let mut predictions = EditPredictions::new();
+        // painted stone at 5, 7 in chunk (0, 0), and something in the chunk to the left
+        let id = predictions.predict(vec![(5, 7, stone.clone()), (-1, 7, stone.clone())]);
+        assert!(predictions.is_pending());
+
+        // a copy of the chunk the server sent before it got the edit still shows it
+        let (mut pixels, mut colors) = server(&MaterialInstance::air());
+        predictions.reconcile((0, 0), &mut pixels, &mut colors);
+        assert_eq!(pixels[i], stone);
+        assert_eq!(colors[i], stone.color);
+        assert_eq!(pixels.iter().filter(|p| **p == stone).count(), 1);
+
+        // the edit to the left ends up on the right edge of its own chunk
+        let (mut left, mut left_colors) = server(&MaterialInstance::air());
+        predictions.reconcile((-1, 0), &mut left, &mut left_colors);
+        assert_eq!(
+            left[usize::from(CHUNK_WIDTH) - 1 + 7 * usize::from(CHUNK_WIDTH)],
+            stone
+        );
+
+        // once the server answered, its copy wins, even where it disagrees with the prediction
+        predictions.confirm(id);
+        assert!(!predictions.is_pending());
+        let (mut pixels, mut colors) = server(&sand);
+        predictions.reconcile((0, 0), &mut pixels, &mut colors);
+        assert_eq!(pixels[i], sand);
+        assert_eq!(colors[i], sand.color);
+    }
+}
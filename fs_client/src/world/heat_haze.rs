@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use fs_common::game::common::{
+    registry::RegistryID,
+    world::{
+        material::{Material, MaterialInstance, MaterialRegistry, AMBIENT_TEMPERATURE},
+        CHUNK_AREA, CHUNK_WIDTH, LIGHT_HEIGHT, LIGHT_SCALE, LIGHT_WIDTH,
+    },
+    Rect,
+};
+use glium::{Display, Texture2d};
+
+/// Pixels at or below this temperature don't shimmer at all, in degrees Celsius.
+pub const HAZE_MIN_TEMPERATURE: f32 = 150.0;
+/// Pixels at or above this temperature shimmer as much as they can, in degrees Celsius.
+pub const HAZE_FULL_TEMPERATURE: f32 = 800.0;
+/// How long the haze animation takes before it repeats, in seconds.
+pub const HAZE_PERIOD: f64 = 6.0;
+
+/// The [`Material::temperature`] of every material, so pixels can be looked up without going through the registry.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialTemperatures {
+    /// Only materials that aren't at [`AMBIENT_TEMPERATURE`].
+    temperatures: HashMap<RegistryID<Material>, f32>,
+}
+
+impl MaterialTemperatures {
+    pub fn from_registry(materials: &MaterialRegistry) -> Self {
+        Self {
+            temperatures: materials
+                .into_iter()
+                .filter(|(_, mat)| mat.temperature != AMBIENT_TEMPERATURE)
+                .map(|(id, mat)| (id.clone(), mat.temperature))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn temperature_of(&self, mat: &MaterialInstance) -> f32 {
+        self.temperatures
+            .get(&mat.material_id)
+            .copied()
+            .unwrap_or(AMBIENT_TEMPERATURE)
+    }
+}
+
+/// How much a pixel at `temperature` shimmers, from 0 (not at all) to 1.
+pub fn heat_of(temperature: f32) -> f32 {
+    ((temperature - HAZE_MIN_TEMPERATURE) / (HAZE_FULL_TEMPERATURE - HAZE_MIN_TEMPERATURE))
+        .clamp(0.0, 1.0)
+}
+
+/// Sets the temperature of the pixels in `rect` of a chunk's temperature buffer from their materials,
+///   leaving everything outside `rect` alone.
+pub fn update_temperatures(
+    temperatures: &mut [f32; CHUNK_AREA],
+    pixels: &[MaterialInstance; CHUNK_AREA],
+    lookup: &MaterialTemperatures,
+    rect: Rect<i32>,
+) {
+    for y in rect.range_tb() {
+        for x in rect.range_lr() {
+            let i = (x + y * i32::from(CHUNK_WIDTH)) as usize;
+            temperatures[i] = lookup.temperature_of(&pixels[i]);
+        }
+    }
+}
+
+/// The heat (see [`heat_of`]) of each [`LIGHT_SCALE`] square of pixels in a chunk, from the hottest
+///   pixel in it.
+///
+/// This is laid out like the light grid, [`LIGHT_WIDTH`] by [`LIGHT_HEIGHT`] and row-major,
+///   so it lines up with the chunk's texture when stretched over it.
+pub fn heat_map(temperatures: &[f32; CHUNK_AREA]) -> Vec<f32> {
+    let (width, scale) = (usize::from(CHUNK_WIDTH), usize::from(LIGHT_SCALE));
+    let mut heat = vec![0.0; usize::from(LIGHT_WIDTH) * usize::from(LIGHT_HEIGHT)];
+
+    for (i, h) in heat.iter_mut().enumerate() {
+        let cell_x = (i % usize::from(LIGHT_WIDTH)) * scale;
+        let cell_y = (i / usize::from(LIGHT_WIDTH)) * scale;
+        let hottest = (cell_y..cell_y + scale)
+            .flat_map(|y| (cell_x..cell_x + scale).map(move |x| temperatures[x + y * width]))
+            .fold(f32::MIN, f32::max);
+        *h = heat_of(hottest);
+    }
+
+    heat
+}
+
+/// Keeps the heat haze animation going, and the copy of the screen it distorts.
+#[derive(Default)]
+pub struct HeatHaze {
+    /// Seconds into the current loop of the animation.
+    time: f64,
+    screen: Option<Texture2d>,
+}
+
+impl HeatHaze {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the animation along by `delta_time` seconds.
+    ///
+    /// This only goes by real time, so it moves at the same speed no matter the framerate.
+    pub fn advance(&mut self, delta_time: f64) {
+        self.time = (self.time + delta_time).rem_euclid(HAZE_PERIOD);
+    }
+
+    /// How far through its loop the animation is, from 0 to 1.
+    pub fn phase(&self) -> f32 {
+        (self.time / HAZE_PERIOD) as f32
+    }
+
+    /// A texture the size of the screen to copy it into, made again when the size changes.
+    pub fn screen_texture(&mut self, display: &Display, size: (u32, u32)) -> &Texture2d {
+        if self.screen.as_ref().map(Texture2d::dimensions) != Some(size) {
+            self.screen = Some(Texture2d::empty(display, size.0, size.1).unwrap());
+        }
+        self.screen.as_ref().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        registry::Registry,
+        world::{
+            material::{
+                self, color::Color, Material, MaterialInstance, PhysicsType, AMBIENT_TEMPERATURE,
+            },
+            CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH, LIGHT_SCALE, LIGHT_WIDTH,
+        },
+        Rect,
+    };
+
+    use super::{heat_map, update_temperatures, HeatHaze, MaterialTemperatures, HAZE_PERIOD};
+
+    #[test]
+    fn heat_map_is_hot_only_over_hot_pixels() {
+        let mut registry = Registry::new();
+        registry.register(
+            material::LAVA.clone(),
+            Material { temperature: 1100.0, ..Material::default() },
+        );
+        registry.register(material::TEST.clone(), Material::default());
+        let lookup = MaterialTemperatures::from_registry(&registry);
+
+        let lava = material::LAVA.instance(PhysicsType::Liquid, Color::rgb(255, 96, 16));
+        let stone = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let mut pixels: Box<[MaterialInstance; CHUNK_AREA]> =
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap();
+        let width = usize::from(CHUNK_WIDTH);
+        // a pool of lava in a stone basin, starting at the second cell of the light grid
+        let scale = usize::from(LIGHT_SCALE);
+        for x in scale..scale * 3 {
+            pixels[x + scale * width] = lava.clone();
+            pixels[x + (scale + 1) * width] = stone.clone();
+        }
+
+        let mut temperatures = Box::new([0.0; CHUNK_AREA]);
+        update_temperatures(
+            &mut temperatures,
+            &pixels,
+            &lookup,
+            Rect::new(0, 0, i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT)),
+        );
+        assert_eq!(temperatures[scale + scale * width], 1100.0);
+        assert_eq!(
+            temperatures[scale + (scale + 1) * width],
+            AMBIENT_TEMPERATURE
+        );
+
+        let heat = heat_map(&temperatures);
+        let cell = |x: usize, y: usize| heat[x + y * usize::from(LIGHT_WIDTH)];
+        assert!(cell(1, 1) > 0.0);
+        assert!(cell(2, 1) > 0.0);
+        // the stone and air around it don't shimmer
+        assert_eq!(cell(0, 1), 0.0);
+        assert_eq!(cell(3, 1), 0.0);
+        assert_eq!(cell(1, 0), 0.0);
+        assert_eq!(cell(1, 2), 0.0);
+        assert_eq!(heat.iter().filter(|&&h| h > 0.0).count(), 2);
+    }
+
+    #[test]
+    fn update_temperatures_only_touches_rect() {
+        let mut registry = Registry::new();
+        registry.register(
+            material::LAVA.clone(),
+            Material { temperature: 1100.0, ..Material::default() },
+        );
+        let lookup = MaterialTemperatures::from_registry(&registry);
+
+        let lava = material::LAVA.instance(PhysicsType::Liquid, Color::rgb(255, 96, 16));
+        let pixels: Box<[MaterialInstance; CHUNK_AREA]> =
+            vec![lava; CHUNK_AREA].try_into().unwrap();
+        let mut temperatures = Box::new([AMBIENT_TEMPERATURE; CHUNK_AREA]);
+
+        update_temperatures(&mut temperatures, &pixels, &lookup, Rect::new(0, 0, 2, 1));
+        assert_eq!(temperatures[0], 1100.0);
+        assert_eq!(temperatures[1], 1100.0);
+        assert_eq!(temperatures[2], AMBIENT_TEMPERATURE);
+        assert_eq!(temperatures[usize::from(CHUNK_WIDTH)], AMBIENT_TEMPERATURE);
+    }
+
+    #[test]
+    fn nothing_hot_at_ambient_temperature() {
+        let heat = heat_map(&Box::new([AMBIENT_TEMPERATURE; CHUNK_AREA]));
+        assert!(heat.iter().all(|&h| h == 0.0));
+    }
+
+    #[test]
+    fn haze_animation_follows_real_time() {
+        // one second at 30 and at 144 fps ends up at the same point in the animation
+        let mut slow = HeatHaze::new();
+        for _ in 0..30 {
+            slow.advance(1.0 / 30.0);
+        }
+        let mut fast = HeatHaze::new();
+        for _ in 0..144 {
+            fast.advance(1.0 / 144.0);
+        }
+        assert!((slow.phase() - fast.phase()).abs() < 1e-4);
+        assert!((slow.phase() - (1.0 / HAZE_PERIOD) as f32).abs() < 1e-4);
+
+        // and it loops instead of losing precision over a long session
+        let mut long = HeatHaze::new();
+        long.advance(HAZE_PERIOD * 1000.0 + 1.0);
+        assert!((long.phase() - slow.phase()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,109 @@
+use fs_common::game::common::{
+    world::{
+        material::{MaterialInstance, MaterialRegistry, PhysicsType},
+        CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH,
+    },
+    Rect,
+};
+
+/// Whether `mat` is on the top surface of a body of liquid, with `above` being the pixel above it.
+pub fn is_liquid_surface(
+    mat: &MaterialInstance,
+    above: &MaterialInstance,
+    materials: &MaterialRegistry,
+) -> bool {
+    mat.physics == PhysicsType::Liquid && above.is_empty(materials)
+}
+
+/// Horizontal runs of liquid surface pixels in a chunk, as 1 pixel tall rects in chunk local coordinates.
+///
+/// `above` is the bottom row of the chunk above. If that chunk isn't loaded the top row is never
+///   counted as surface, since there's no way to tell.
+pub fn liquid_surface(
+    pixels: &[MaterialInstance; CHUNK_AREA],
+    above: Option<&[MaterialInstance]>,
+    materials: &MaterialRegistry,
+) -> Vec<Rect<i32>> {
+    let (width, height) = (CHUNK_WIDTH as usize, CHUNK_HEIGHT as usize);
+    let mut runs = vec![];
+
+    for y in 0..height {
+        let row_above = if y == 0 {
+            match above {
+                Some(above) => above,
+                None => continue,
+            }
+        } else {
+            &pixels[(y - 1) * width..y * width]
+        };
+        let row = &pixels[y * width..(y + 1) * width];
+
+        let mut start = None;
+        for x in 0..=width {
+            let surface = x < width && is_liquid_surface(&row[x], &row_above[x], materials);
+            match (surface, start) {
+                (true, None) => start = Some(x),
+                (false, Some(x1)) => {
+                    runs.push(Rect::new(x1 as i32, y as i32, x as i32, y as i32 + 1));
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::world::{
+        material::{self, color::Color, MaterialInstance, MaterialRegistry, PhysicsType},
+        CHUNK_AREA, CHUNK_WIDTH,
+    };
+
+    use super::liquid_surface;
+
+    #[test]
+    fn surface_detection() {
+        let materials = MaterialRegistry::new();
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(0, 0, 255));
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let mut pixels: Box<[MaterialInstance; CHUNK_AREA]> =
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap();
+        let mut set = |x: usize, y: usize, mat: &MaterialInstance| {
+            pixels[x + y * CHUNK_WIDTH as usize] = mat.clone();
+        };
+
+        // a pool of water from x = 10 to 20 and y = 50 to 55, with a rock sticking out of it
+        for x in 10..20 {
+            for y in 50..55 {
+                set(x, y, &water);
+            }
+        }
+        set(14, 49, &solid);
+
+        let runs = liquid_surface(&pixels, None, &materials);
+        let rows: Vec<_> = runs.iter().map(|r| (r.x1, r.x2, r.y1, r.y2)).collect();
+        // only the top row is flagged, except right under the rock
+        assert_eq!(rows, vec![(10, 14, 50, 51), (15, 20, 50, 51)]);
+
+        // the top row of the chunk depends on what's above it
+        let mut top: Box<[MaterialInstance; CHUNK_AREA]> =
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap();
+        for p in &mut top[..5] {
+            *p = water.clone();
+        }
+        assert!(liquid_surface(&top, None, &materials).is_empty());
+
+        let mut above = vec![MaterialInstance::air(); CHUNK_WIDTH as usize];
+        above[0] = solid;
+        let runs = liquid_surface(&top, Some(&above), &materials);
+        let rows: Vec<_> = runs.iter().map(|r| (r.x1, r.x2, r.y1, r.y2)).collect();
+        assert_eq!(rows, vec![(1, 5, 0, 1)]);
+    }
+}
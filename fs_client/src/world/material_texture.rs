@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use fs_common::game::common::{
+    registry::RegistryID,
+    world::{
+        material::{Material, MaterialInstance},
+        CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH,
+    },
+    FileHelper, Rect,
+};
+use glium::{
+    texture::{RawImage2d, Texture2dArray},
+    Display,
+};
+
+use crate::render::vertex::Vertex2TA;
+
+/// Where the world pixel at `world_x`, `world_y` starts in a tile of `tile_size` pixels
+///   repeating across the whole world, as texture coordinates from 0.0 to 1.0.
+///
+/// This only depends on the world position, so a tile carries on across chunk borders.
+pub fn atlas_uv(world_x: i64, world_y: i64, tile_size: (u16, u16)) -> (f32, f32) {
+    let (w, h) = (i64::from(tile_size.0), i64::from(tile_size.1));
+    (
+        world_x.rem_euclid(w) as f32 / w as f32,
+        world_y.rem_euclid(h) as f32 / h as f32,
+    )
+}
+
+/// Horizontal runs of pixels in a chunk that are drawn with the same atlas layer, as 1 pixel tall
+///   rects in chunk local coordinates.
+pub fn texture_runs(
+    pixels: &[MaterialInstance; CHUNK_AREA],
+    mut layer_of: impl FnMut(&MaterialInstance) -> Option<u32>,
+) -> Vec<(Rect<i32>, u32)> {
+    let (width, height) = (CHUNK_WIDTH as usize, CHUNK_HEIGHT as usize);
+    let mut runs = vec![];
+
+    for y in 0..height {
+        let mut run: Option<(usize, u32)> = None;
+        for x in 0..=width {
+            let layer = (x < width)
+                .then(|| layer_of(&pixels[x + y * width]))
+                .flatten();
+            if run.map(|(_, l)| l) != layer {
+                if let Some((start, l)) = run {
+                    runs.push((Rect::new(start as i32, y as i32, x as i32, y as i32 + 1), l));
+                }
+                run = layer.map(|l| (x, l));
+            }
+        }
+    }
+
+    runs
+}
+
+/// The quad for a run from [`texture_runs`] at `rect` (in world coordinates), in the corner order of
+///   [`RenderTarget::draw_texture_array_quads`](crate::render::drawing::RenderTarget::draw_texture_array_quads).
+///
+/// Texture coordinates go past 1.0 for runs wider than the rest of the tile, which the texture
+///   wraps around to repeat.
+pub fn run_quad(rect: Rect<i32>, layer: u32, tile_size: (u16, u16)) -> [Vertex2TA; 4] {
+    let (u1, v1) = atlas_uv(rect.x1.into(), rect.y1.into(), tile_size);
+    let u2 = u1 + rect.width() as f32 / f32::from(tile_size.0);
+    let v2 = v1 + rect.height() as f32 / f32::from(tile_size.1);
+    let (x1, y1, x2, y2) = (
+        rect.x1 as f32,
+        rect.y1 as f32,
+        rect.x2 as f32,
+        rect.y2 as f32,
+    );
+    let layer = layer as f32;
+    [
+        ((x1, y2), (u1, v2), layer).into(),
+        ((x2, y2), (u2, v2), layer).into(),
+        ((x2, y1), (u2, v1), layer).into(),
+        ((x1, y1), (u1, v1), layer).into(),
+    ]
+}
+
+/// Textures drawn over the flat colors of some materials' pixels, like brick or wood grain.
+///
+/// Every `<material id>.png` in `assets/texture/material/` is a layer of the atlas, and they
+///   all have to be the same size as the first one. Loaded the first time they are drawn.
+#[derive(Default)]
+pub struct MaterialTextures {
+    /// `None` until loaded. Stays empty if there are no textures or they failed to load.
+    loaded: Option<Atlas>,
+}
+
+struct Atlas {
+    layers: HashMap<RegistryID<Material>, u32>,
+    tile_size: (u16, u16),
+    texture: Option<Texture2dArray>,
+}
+
+impl MaterialTextures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops the atlas, so it's loaded from disk again the next time it's drawn.
+    pub fn reload(&mut self) {
+        self.loaded = None;
+    }
+
+    /// The atlas, its tile size, and a lookup for which layer a pixel is drawn with,
+    ///   or `None` if there are no material textures.
+    #[allow(clippy::type_complexity)]
+    pub fn get(
+        &mut self,
+        display: &Display,
+        file_helper: &FileHelper,
+    ) -> Option<(
+        &Texture2dArray,
+        (u16, u16),
+        impl Fn(&MaterialInstance) -> Option<u32> + '_,
+    )> {
+        let atlas = self.loaded.get_or_insert_with(|| {
+            Self::load(display, file_helper).unwrap_or_else(|e| {
+                log::error!("Failed to load material textures: {e}");
+                Atlas {
+                    layers: HashMap::new(),
+                    tile_size: (1, 1),
+                    texture: None,
+                }
+            })
+        });
+
+        let layers = &atlas.layers;
+        atlas.texture.as_ref().map(|tex| {
+            (tex, atlas.tile_size, move |mat: &MaterialInstance| {
+                layers.get(&mat.material_id).copied()
+            })
+        })
+    }
+
+    fn load(display: &Display, file_helper: &FileHelper) -> Result<Atlas, String> {
+        let dir = file_helper.asset_path("texture/material");
+        let mut layers = HashMap::new();
+        let mut images = vec![];
+        let mut tile_size = None;
+
+        if dir.exists() {
+            let mut paths = std::fs::read_dir(&dir)
+                .map_err(|e| e.to_string())?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().map_or(false, |e| e == "png"))
+                .collect::<Vec<_>>();
+            // so layers don't depend on the order the os lists them in
+            paths.sort();
+
+            for path in paths {
+                let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                let image = image::open(&path).map_err(|e| e.to_string())?.to_rgba8();
+                let (w, h) = image.dimensions();
+                let size = (
+                    u16::try_from(w).map_err(|e| e.to_string())?,
+                    u16::try_from(h).map_err(|e| e.to_string())?,
+                );
+                if *tile_size.get_or_insert(size) != size {
+                    log::warn!(
+                        "Skipping material texture {path:?}: {w}x{h} doesn't match the other textures"
+                    );
+                    continue;
+                }
+
+                layers.insert(RegistryID::from(id), images.len() as u32);
+                images.push(RawImage2d::from_raw_rgba(image.into_raw(), (w, h)));
+            }
+        }
+
+        let texture = if images.is_empty() {
+            None
+        } else {
+            Some(Texture2dArray::new(display, images).map_err(|e| e.to_string())?)
+        };
+
+        Ok(Atlas {
+            layers,
+            tile_size: tile_size.unwrap_or((1, 1)),
+            texture,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        world::{
+            material::{self, color::Color, MaterialInstance, PhysicsType},
+            CHUNK_AREA, CHUNK_WIDTH,
+        },
+        Rect,
+    };
+
+    use super::{atlas_uv, run_quad, texture_runs};
+
+    #[test]
+    fn atlas_uv_tiles_across_chunks() {
+        let tile = (16, 8);
+        assert_eq!(atlas_uv(0, 0, tile), (0.0, 0.0));
+        assert_eq!(atlas_uv(4, 2, tile), (0.25, 0.25));
+        assert_eq!(atlas_uv(20, 10, tile), (0.25, 0.25));
+        // negative positions keep counting up towards the next tile
+        assert_eq!(atlas_uv(-1, -1, tile), (15.0 / 16.0, 7.0 / 8.0));
+        assert_eq!(atlas_uv(-16, -8, tile), (0.0, 0.0));
+
+        // the first pixel of a chunk carries on from the last pixel of the one before it
+        let size = i64::from(CHUNK_WIDTH);
+        for chunk in -3..3 {
+            let (last, _) = atlas_uv(chunk * size - 1, 0, tile);
+            let (first, _) = atlas_uv(chunk * size, 0, tile);
+            let next = (last + 1.0 / 16.0) % 1.0;
+            assert!(
+                (next - first).abs() < 1e-6,
+                "chunk {chunk}: {last} then {first}"
+            );
+        }
+
+        // a run across a chunk border lines up with the same run split at the border
+        let whole = run_quad(Rect::new(90, 0, 110, 1), 0, tile);
+        let left = run_quad(Rect::new(90, 0, 100, 1), 0, tile);
+        let right = run_quad(Rect::new(100, 0, 110, 1), 0, tile);
+        assert_eq!(whole[0].tex_coord, left[0].tex_coord);
+        assert!((left[1].tex_coord[0] % 1.0 - right[0].tex_coord[0]).abs() < 1e-6);
+        assert!((whole[1].tex_coord[0] - (left[1].tex_coord[0] + 10.0 / 16.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn texture_runs_split_by_layer() {
+        let brick = material::TEST.instance(PhysicsType::Solid, Color::rgb(160, 60, 40));
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        let mut pixels: Box<[MaterialInstance; CHUNK_AREA]> =
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap();
+        let size = CHUNK_WIDTH as usize;
+        for x in 2..6 {
+            pixels[x + 3 * size] = brick.clone();
+        }
+        pixels[6 + 3 * size] = stone;
+        pixels[size - 1] = brick.clone();
+
+        let runs = texture_runs(&pixels, |m| {
+            (m.material_id == brick.material_id).then_some(2)
+        });
+        let runs: Vec<_> = runs
+            .into_iter()
+            .map(|(r, layer)| (r.x1, r.x2, r.y1, r.y2, layer))
+            .collect();
+        assert_eq!(runs, vec![(99, 100, 0, 1, 2), (2, 6, 3, 4, 2)]);
+    }
+}
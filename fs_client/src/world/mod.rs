@@ -1,6 +1,17 @@
+pub mod background;
 mod chunk;
 pub mod chunk_data;
+pub mod chunk_debug_color;
 mod client_world;
+pub mod edit_prediction;
+pub mod heat_haze;
+pub mod liquid_foam;
+pub mod material_texture;
+pub mod particle_lod;
+pub mod pixel_snap;
+pub mod spectator;
+pub mod sprite;
+pub mod texture_stream;
 mod world_renderer;
 
 pub use chunk::*;
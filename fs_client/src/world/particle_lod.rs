@@ -0,0 +1,115 @@
+use fs_common::game::common::world::particle::Particle;
+
+/// The fewest particles that get drawn when zoomed out, as a fraction of all of them.
+pub const MIN_PARTICLE_KEEP_RATIO: f64 = 1.0 / 64.0;
+
+/// What fraction of particles to draw at `camera_scale`.
+///
+/// At or above `lod_scale` every particle is drawn. Below it, the ratio falls off with the area
+///   a pixel takes up on screen, so the number of particles per screen pixel stays about the same.
+/// A `lod_scale` of 0 turns the LOD off.
+pub fn particle_keep_ratio(camera_scale: f64, lod_scale: f64) -> f64 {
+    if camera_scale >= lod_scale {
+        1.0
+    } else {
+        (camera_scale / lod_scale)
+            .powi(2)
+            .max(MIN_PARTICLE_KEEP_RATIO)
+    }
+}
+
+/// Whether the particle with spawn id `id` is drawn with `keep_ratio`.
+///
+/// Each id gets a fixed, evenly spread threshold, so zooming out drops particles one by one
+///   instead of halving them at once, and zooming back in brings the same ones back.
+/// The id stays with the particle, so other particles despawning doesn't change which ones are kept.
+pub fn keep_particle(id: u64, keep_ratio: f64) -> bool {
+    if keep_ratio >= 1.0 {
+        return true;
+    }
+    // fractional part of id * golden ratio
+    let threshold = id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 11;
+    (threshold as f64 / (1_u64 << 53) as f64) < keep_ratio
+}
+
+/// The particles to draw with `keep_ratio`, see [`keep_particle`].
+pub fn lod_particles(parts: &[Particle], keep_ratio: f64) -> impl Iterator<Item = &Particle> {
+    parts
+        .iter()
+        .filter(move |p| keep_particle(p.id, keep_ratio))
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::world::{
+        material::MaterialInstance, particle::Particle, Position, Velocity,
+    };
+
+    use super::{keep_particle, lod_particles, particle_keep_ratio, MIN_PARTICLE_KEEP_RATIO};
+
+    #[test]
+    fn subsampling_by_scale() {
+        let count = 100_000_u64;
+        let kept = |scale: f64| {
+            let ratio = particle_keep_ratio(scale, 1.0);
+            (0..count).filter(|i| keep_particle(*i, ratio)).count()
+        };
+
+        // near zoom draws everything
+        assert_eq!(kept(1.0), count as usize);
+        assert_eq!(kept(4.0), count as usize);
+        assert_eq!(particle_keep_ratio(0.25, 0.0), 1.0);
+
+        // zoomed out it goes with the area of a pixel, within 1%
+        for (scale, expected) in [
+            (0.5, 0.25),
+            (0.25, 1.0 / 16.0),
+            (0.01, MIN_PARTICLE_KEEP_RATIO),
+        ] {
+            let expected = (count as f64 * expected) as usize;
+            let got = kept(scale);
+            assert!(
+                got.abs_diff(expected) < count as usize / 100,
+                "scale {scale}: kept {got}, expected about {expected}"
+            );
+        }
+
+        // zooming out only ever drops particles
+        let ratio = particle_keep_ratio(0.5, 1.0);
+        let smaller = particle_keep_ratio(0.4, 1.0);
+        for i in 0..count {
+            assert!(!keep_particle(i, smaller) || keep_particle(i, ratio));
+        }
+    }
+
+    #[test]
+    fn kept_set_survives_despawns() {
+        let mut parts: Vec<Particle> = (0..1000)
+            .map(|_| {
+                Particle::new(
+                    MaterialInstance::air(),
+                    Position { x: 0.0, y: 0.0 },
+                    Velocity { x: 0.0, y: 0.0 },
+                )
+            })
+            .collect();
+        let ratio = 0.25;
+        let kept_ids = |parts: &[Particle]| -> Vec<u64> {
+            lod_particles(parts, ratio).map(|p| p.id).collect()
+        };
+
+        let before = kept_ids(&parts);
+        // despawn every third particle, the survivors that were drawn are still drawn
+        let mut i = 0;
+        parts.retain(|_| {
+            i += 1;
+            i % 3 != 0
+        });
+        let after = kept_ids(&parts);
+        let survivors: Vec<u64> = before
+            .into_iter()
+            .filter(|id| parts.iter().any(|p| p.id == *id))
+            .collect();
+        assert_eq!(after, survivors);
+    }
+}
@@ -0,0 +1,52 @@
+/// Rounds `camera` so it's a whole number of screen pixels away from the world origin at `camera_scale`.
+///
+/// Combined with [`snap_screen_center`], the world's offset on screen is then always a whole
+///   number of pixels, so pixels don't land on different fractions of screen pixels (and shimmer)
+///   as the camera moves.
+pub fn snap_camera(camera: (f64, f64), camera_scale: f64) -> (f64, f64) {
+    if camera_scale <= 0.0 {
+        return camera;
+    }
+    (
+        (camera.0 * camera_scale).round() / camera_scale,
+        (camera.1 * camera_scale).round() / camera_scale,
+    )
+}
+
+/// The center of a `width` by `height` screen, rounded down to a whole pixel.
+pub fn snap_screen_center(width: u32, height: u32) -> (f64, f64) {
+    (f64::from(width / 2), f64::from(height / 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{snap_camera, snap_screen_center};
+
+    #[test]
+    fn snapped_offset_is_whole_pixels() {
+        let center = snap_screen_center(1281, 719);
+        assert_eq!(center, (640.0, 359.0));
+
+        for scale in [1.0, 2.0, 3.0, 0.5, 1.7] {
+            for camera in [(0.0, 0.0), (10.3, -4.75), (-123.456, 789.012), (0.01, 0.99)] {
+                let snapped = snap_camera(camera, scale);
+
+                // where the world origin ends up on screen
+                let offset_x = -snapped.0 * scale + center.0;
+                let offset_y = -snapped.1 * scale + center.1;
+                assert!(
+                    (offset_x - offset_x.round()).abs() < 1e-9,
+                    "{camera:?} at {scale}"
+                );
+                assert!(
+                    (offset_y - offset_y.round()).abs() < 1e-9,
+                    "{camera:?} at {scale}"
+                );
+
+                // never moves by more than half a screen pixel
+                assert!((snapped.0 - camera.0).abs() * scale <= 0.5 + 1e-9);
+                assert!((snapped.1 - camera.1).abs() * scale <= 0.5 + 1e-9);
+            }
+        }
+    }
+}
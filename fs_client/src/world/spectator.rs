@@ -0,0 +1,175 @@
+use fs_common::game::common::{
+    world::{AutoTarget, Camera, Position, Velocity},
+    Settings,
+};
+use specs::{Entities, Entity, Join, ReadStorage, WorldExt, WriteStorage};
+
+use crate::input::Controls;
+
+/// Cameras detached from the player, flown around with the movement keys.
+///
+/// While spectating, the cameras' [`AutoTarget`]s are taken off so nothing snaps them back to
+///   the player. The player itself is left alone and keeps being simulated (and keeping its chunks
+///   loaded through its [`Loader`](fs_common::game::common::world::Loader)), it just doesn't get any input.
+#[derive(Debug, Default)]
+pub struct Spectator {
+    targets: Vec<(Entity, AutoTarget)>,
+}
+
+impl Spectator {
+    /// Detaches every camera from its target.
+    pub fn start(ecs: &specs::World) -> Self {
+        let (entities, cameras, mut auto_targets, mut velocities) = ecs.system_data::<(
+            Entities,
+            ReadStorage<Camera>,
+            WriteStorage<AutoTarget>,
+            WriteStorage<Velocity>,
+        )>();
+
+        let targets = (&entities, &cameras)
+            .join()
+            .filter_map(|(e, _)| auto_targets.remove(e).map(|at| (e, at)))
+            .collect();
+
+        // don't keep drifting after the target
+        for (_, vel) in (&cameras, &mut velocities).join() {
+            *vel = Velocity { x: 0.0, y: 0.0 };
+        }
+
+        Self { targets }
+    }
+
+    /// Gives every camera its target back.
+    pub fn stop(self, ecs: &specs::World) {
+        let mut auto_targets = ecs.write_storage::<AutoTarget>();
+        for (e, at) in self.targets {
+            // fails if the camera was deleted in the meantime, which is fine
+            let _ = auto_targets.insert(e, at);
+        }
+    }
+
+    /// Moves every camera by `delta` world pixels, see [`pan_delta`].
+    ///
+    /// This only adds to the cameras' positions, so it composes with dragging the camera around.
+    pub fn fly(ecs: &specs::World, delta: (f64, f64)) {
+        let (cameras, mut positions, mut velocities) = ecs.system_data::<(
+            ReadStorage<Camera>,
+            WriteStorage<Position>,
+            WriteStorage<Velocity>,
+        )>();
+
+        let vel = Velocity { x: delta.0, y: delta.1 };
+        for (_, pos, cam_vel) in (&cameras, &mut positions, (&mut velocities).maybe()).join() {
+            pos.x += vel.x;
+            pos.y += vel.y;
+            // lets the renderer interpolate between ticks
+            if let Some(cam_vel) = cam_vel {
+                *cam_vel = vel.clone();
+            }
+        }
+    }
+}
+
+/// Which way the movement keys point, from -1 to 1 on each axis.
+pub fn movement_dir(controls: &mut Controls) -> (f64, f64) {
+    let axis = |neg: bool, pos: bool| f64::from(i8::from(pos) - i8::from(neg));
+    (
+        axis(controls.left.get(), controls.right.get()),
+        axis(controls.up.get(), controls.down.get()),
+    )
+}
+
+/// How far a free camera pans in one tick, in world pixels.
+///
+/// `dir` is from [`movement_dir`]. The speed is in screen pixels (see [`Settings::camera_pan_speed`]),
+///   so it's divided by `camera_scale` to pan the same distance on screen at any zoom.
+///   With `fast` (holding shift) it's multiplied by [`Settings::camera_pan_fast_multiplier`].
+pub fn pan_delta(
+    dir: (f64, f64),
+    fast: bool,
+    camera_scale: f64,
+    settings: &Settings,
+) -> (f64, f64) {
+    let mut speed = settings.camera_pan_speed / camera_scale;
+    if fast {
+        speed *= settings.camera_pan_fast_multiplier;
+    }
+    (dir.0 * speed, dir.1 * speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::{
+        world::{
+            self, AutoTarget, Camera, Loader, Position, Target, TargetStyle, UpdateAutoTargets,
+            Velocity,
+        },
+        Settings,
+    };
+    use specs::{Builder, RunNow, WorldExt};
+
+    use super::{pan_delta, Spectator};
+
+    const SPECTATOR_SPEED: f64 = 6.0;
+
+    #[test]
+    fn spectator_moves_camera_not_player() {
+        let mut ecs = world::ecs();
+        let player = ecs
+            .create_entity()
+            .with(Position { x: 10.0, y: 20.0 })
+            .with(Velocity { x: 0.0, y: 0.0 })
+            .with(Loader)
+            .build();
+        let camera = ecs
+            .create_entity()
+            .with(Camera)
+            .with(Position { x: 10.0, y: 20.0 })
+            .with(Velocity { x: 0.0, y: 0.0 })
+            .with(AutoTarget {
+                target: Target::Entity(player),
+                offset: (0.0, 0.0),
+                look_ahead: 0.0,
+                style: TargetStyle::Locked,
+            })
+            .build();
+
+        let spectator = Spectator::start(&ecs);
+        for _ in 0..5 {
+            Spectator::fly(&ecs, (SPECTATOR_SPEED, -SPECTATOR_SPEED));
+            UpdateAutoTargets.run_now(&ecs);
+        }
+
+        let positions = ecs.read_storage::<Position>();
+        let cam = positions.get(camera).unwrap();
+        assert_eq!(
+            (cam.x, cam.y),
+            (10.0 + SPECTATOR_SPEED * 5.0, 20.0 - SPECTATOR_SPEED * 5.0)
+        );
+        assert_eq!(positions.get(player), Some(&Position { x: 10.0, y: 20.0 }));
+        assert!(ecs.read_storage::<Loader>().contains(player));
+        drop(positions);
+
+        // leaving spectator snaps back to the player
+        spectator.stop(&ecs);
+        UpdateAutoTargets.run_now(&ecs);
+        let positions = ecs.read_storage::<Position>();
+        assert_eq!(positions.get(camera), Some(&Position { x: 10.0, y: 20.0 }));
+    }
+
+    #[test]
+    fn pan_delta_scales_with_zoom() {
+        let settings = Settings {
+            camera_pan_speed: 12.0,
+            camera_pan_fast_multiplier: 4.0,
+            ..Settings::default()
+        };
+
+        assert_eq!(pan_delta((0.0, 0.0), true, 2.0, &settings), (0.0, 0.0));
+        assert_eq!(pan_delta((1.0, -1.0), false, 2.0, &settings), (6.0, -6.0));
+        // zoomed in, the same key press covers less of the world
+        assert_eq!(pan_delta((1.0, 0.0), false, 4.0, &settings), (3.0, 0.0));
+        assert_eq!(pan_delta((0.0, 1.0), true, 4.0, &settings), (0.0, 12.0));
+        assert_eq!(pan_delta((-1.0, 0.0), true, 0.5, &settings), (-96.0, 0.0));
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use fs_common::game::common::{world::entity::Sprite, FileHelper};
+use glium::{texture::RawImage2d, Display, Texture2d};
+
+use crate::render::vertex::Vertex2T;
+
+/// The quad for `sprite` on an entity at (`x`, `y`), in the corner order of
+///   [`RenderTarget::draw_textured_quad`](crate::render::drawing::RenderTarget::draw_textured_quad).
+///
+/// The image's first row is at the top (lowest y), like in the png.
+pub fn sprite_quad(x: f64, y: f64, sprite: &Sprite) -> [Vertex2T; 4] {
+    let bounds = sprite.bounds();
+    let (x1, y1) = (
+        (x + f64::from(bounds.x1)) as f32,
+        (y + f64::from(bounds.y1)) as f32,
+    );
+    let (x2, y2) = (
+        (x + f64::from(bounds.x2)) as f32,
+        (y + f64::from(bounds.y2)) as f32,
+    );
+    [
+        ((x1, y2), (0.0, 1.0)).into(),
+        ((x2, y2), (1.0, 1.0)).into(),
+        ((x2, y1), (1.0, 0.0)).into(),
+        ((x1, y1), (0.0, 0.0)).into(),
+    ]
+}
+
+/// Textures for [`Sprite`]s, loaded the first time they are drawn.
+#[derive(Default)]
+pub struct SpriteTextures {
+    /// `None` if the image failed to load, so it's only tried (and logged) once.
+    textures: HashMap<String, Option<Texture2d>>,
+}
+
+impl SpriteTextures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The texture at `path` (relative to `assets/texture/`), or `None` if it couldn't be loaded.
+    pub fn get(
+        &mut self,
+        path: &str,
+        display: &Display,
+        file_helper: &FileHelper,
+    ) -> Option<&Texture2d> {
+        self.textures
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                Self::load(path, display, file_helper)
+                    .map_err(|e| log::error!("Failed to load sprite texture {path:?}: {e}"))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    fn load(path: &str, display: &Display, file_helper: &FileHelper) -> Result<Texture2d, String> {
+        let bytes = std::fs::read(file_helper.asset_path(format!("texture/{path}")))
+            .map_err(|e| e.to_string())?;
+        let image = image::load_from_memory(&bytes)
+            .map_err(|e| e.to_string())?
+            .to_rgba8();
+        let dimensions = image.dimensions();
+        let raw = RawImage2d::from_raw_rgba(image.into_raw(), dimensions);
+        Texture2d::new(display, raw).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_common::game::common::world::entity::Sprite;
+
+    use super::sprite_quad;
+
+    #[test]
+    fn sprite_quad_anchors_at_position() {
+        let centered = sprite_quad(100.0, -50.0, &Sprite::new("test.png", 8.0, 12.0));
+        let positions = centered.map(|v| v.position);
+        assert_eq!(
+            positions,
+            [[96.0, -44.0], [104.0, -44.0], [104.0, -56.0], [96.0, -56.0]]
+        );
+
+        // standing on the position
+        let feet = sprite_quad(
+            100.0,
+            -50.0,
+            &Sprite::new("test.png", 8.0, 12.0).anchored(0.5, 1.0),
+        );
+        let positions = feet.map(|v| v.position);
+        assert_eq!(
+            positions,
+            [[96.0, -50.0], [104.0, -50.0], [104.0, -62.0], [96.0, -62.0]]
+        );
+
+        // the top of the image is at the top of the quad
+        let uvs = feet.map(|v| v.tex_coord);
+        assert_eq!(uvs, [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+    }
+}
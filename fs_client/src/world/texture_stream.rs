@@ -0,0 +1,313 @@
+use std::{
+    cell::Cell,
+    sync::mpsc::{self, Receiver, Sender},
+    thread::JoinHandle,
+};
+
+use chunksystem::ChunkKey;
+use fs_common::game::common::{
+    world::{material::color::Color, CHUNK_AREA, CHUNK_WIDTH},
+    Rect,
+};
+
+/// A chunk's pixel colors packed into a tightly packed RGBA buffer covering `rect`, ready to upload.
+#[derive(Debug)]
+pub struct PackedTexture {
+    pub chunk: ChunkKey,
+    pub version: u64,
+    pub rect: Rect<i32>,
+    pub rgba: Vec<u8>,
+}
+
+struct PackJob {
+    chunk: ChunkKey,
+    version: u64,
+    rect: Rect<i32>,
+    /// Only the part of the chunk's colors covered by `rect`, see [`copy_rect`].
+    colors: Vec<Color>,
+}
+
+/// Packs chunk color buffers into upload-ready buffers on a worker thread.
+///
+/// Jobs are handled in the order they are submitted by a single worker, so uploads for a chunk always
+/// come back oldest first. Every job gets a new version, which [`TextureVersions`] uses to reject
+/// anything that isn't newer than what the texture already has.
+pub struct ChunkTextureStreamer {
+    next_version: Cell<u64>,
+    jobs: Option<Sender<PackJob>>,
+    packed: Receiver<PackedTexture>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ChunkTextureStreamer {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let (jobs, job_rx) = mpsc::channel::<PackJob>();
+        let (packed_tx, packed) = mpsc::channel();
+
+        let worker = std::thread::Builder::new()
+            .name("chunk texture packer".to_string())
+            .spawn(move || {
+                for job in job_rx {
+                    let rgba = to_rgba(&job.colors);
+                    let packed = PackedTexture {
+                        chunk: job.chunk,
+                        version: job.version,
+                        rect: job.rect,
+                        rgba,
+                    };
+                    if packed_tx.send(packed).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn chunk texture packer thread");
+
+        Self {
+            next_version: Cell::new(1),
+            jobs: Some(jobs),
+            packed,
+            worker: Some(worker),
+        }
+    }
+
+    /// Queues `rect` of `colors` to be packed, returning the version of the upload it will produce.
+    ///
+    /// Only the pixels in `rect` are copied out of `colors` before handing them to the worker, so
+    ///   small changes stay cheap on the calling thread.
+    pub fn submit(&self, chunk: ChunkKey, rect: Rect<i32>, colors: &[Color; CHUNK_AREA]) -> u64 {
+        let version = self.next_version.get();
+        self.next_version.set(version + 1);
+
+        if let Some(jobs) = &self.jobs {
+            // can only fail if the worker panicked, in which case nothing will be uploaded anyway
+            let _ignore = jobs.send(PackJob {
+                chunk,
+                version,
+                rect,
+                colors: copy_rect(colors, rect),
+            });
+        }
+
+        version
+    }
+
+    /// Takes every upload that has finished packing so far, in submission order.
+    pub fn finished(&self) -> impl Iterator<Item = PackedTexture> + '_ {
+        self.packed.try_iter()
+    }
+}
+
+impl Drop for ChunkTextureStreamer {
+    fn drop(&mut self) {
+        // closing the channel stops the worker
+        self.jobs = None;
+        if let Some(worker) = self.worker.take() {
+            let _ignore = worker.join();
+        }
+    }
+}
+
+/// Copies the rows of `colors` covered by `rect` into a contiguous RGBA buffer.
+pub fn pack_rect(colors: &[Color; CHUNK_AREA], rect: Rect<i32>) -> Vec<u8> {
+    to_rgba(&copy_rect(colors, rect))
+}
+
+/// Copies the rows of `colors` covered by `rect` into a contiguous buffer, row by row.
+fn copy_rect(colors: &[Color; CHUNK_AREA], rect: Rect<i32>) -> Vec<Color> {
+    let x1 = rect.x1 as usize;
+    let x2 = rect.x2 as usize;
+    let mut out = Vec::with_capacity((rect.width() * rect.height()) as usize);
+    for y in rect.y1 as usize..rect.y2 as usize {
+        let row = y * CHUNK_WIDTH as usize;
+        out.extend_from_slice(&colors[row + x1..row + x2]);
+    }
+    out
+}
+
+fn to_rgba(colors: &[Color]) -> Vec<u8> {
+    colors.iter().flat_map(|c| [c.r, c.g, c.b, c.a]).collect()
+}
+
+/// Grows `dirty_rect` to also cover `rect`, for a texture that was already `dirty` before
+/// (otherwise `rect` is all that changed since the last upload).
+///
+/// `None` while `dirty` means the whole chunk has to be uploaded, which growing doesn't undo.
+pub fn add_dirty_rect(dirty_rect: &mut Option<Rect<i32>>, dirty: bool, rect: Rect<i32>) {
+    if !dirty {
+        *dirty_rect = Some(rect);
+    } else if let Some(r) = dirty_rect {
+        *r = r.union(rect);
+    }
+}
+
+/// Tracks which packed uploads a chunk's texture should accept.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TextureVersions {
+    first_submitted: Option<u64>,
+    latest_submitted: u64,
+    applied: u64,
+}
+
+impl TextureVersions {
+    pub fn submitted(&mut self, version: u64) {
+        self.first_submitted.get_or_insert(version);
+        self.latest_submitted = version;
+    }
+
+    /// Whether an upload should be written to the texture, marking it as applied if so.
+    ///
+    /// It has to have been submitted for this chunk (and not one previously loaded at the same
+    /// position), and be newer than what was last applied.
+    pub fn accept(&mut self, version: u64) -> bool {
+        let ours = self.first_submitted.map_or(false, |first| {
+            (first..=self.latest_submitted).contains(&version)
+        });
+
+        if ours && version > self.applied {
+            self.applied = version;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.applied < self.latest_submitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use fs_common::game::common::{
+        world::{material::color::Color, CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH},
+        Rect,
+    };
+
+    use super::{add_dirty_rect, pack_rect, ChunkTextureStreamer, PackedTexture, TextureVersions};
+
+    fn wait_for(streamer: &ChunkTextureStreamer, count: usize) -> Vec<PackedTexture> {
+        let start = Instant::now();
+        let mut out = Vec::new();
+        while out.len() < count && start.elapsed() < Duration::from_secs(5) {
+            out.extend(streamer.finished());
+        }
+        out
+    }
+
+    #[test]
+    fn pack_rect_rows() {
+        let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        colors[2 + 5 * CHUNK_WIDTH as usize] = Color::rgb(1, 2, 3);
+        colors[3 + 6 * CHUNK_WIDTH as usize] = Color::rgb(4, 5, 6);
+
+        let rgba = pack_rect(&colors, Rect::new(2, 5, 4, 7));
+        assert_eq!(
+            rgba,
+            vec![1, 2, 3, 255, 0, 0, 0, 0, 0, 0, 0, 0, 4, 5, 6, 255]
+        );
+    }
+
+    #[test]
+    fn dirty_rect_upload_covers_changes() {
+        let size = CHUNK_WIDTH as usize;
+        let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        let mut dirty_rect = None;
+        let mut dirty = false;
+        for (x, y, c) in [(7, 3, 10), (4, 5, 20), (9, 4, 30)] {
+            colors[x + y * size] = Color::rgb(c, 0, 0);
+            add_dirty_rect(
+                &mut dirty_rect,
+                dirty,
+                Rect::new_wh(x as i32, y as i32, 1, 1),
+            );
+            dirty = true;
+        }
+
+        // exactly the bounding box of the changed pixels
+        let rect = dirty_rect.unwrap();
+        assert_eq!((rect.x1, rect.y1, rect.x2, rect.y2), (4, 3, 10, 6));
+
+        // and the upload is those rows of the buffer, with every changed pixel in it
+        let rgba = pack_rect(&colors, rect);
+        assert_eq!(rgba.len(), 6 * 3 * 4);
+        for (i, px) in rgba.chunks_exact(4).enumerate() {
+            let (x, y) = (4 + i % 6, 3 + i / 6);
+            let c = colors[x + y * size];
+            assert_eq!(px, [c.r, c.g, c.b, c.a], "{x}, {y}");
+        }
+        assert_eq!(rgba.iter().step_by(4).filter(|&&r| r != 0).count(), 3);
+
+        // a new upload starts over from the next change
+        add_dirty_rect(&mut dirty_rect, false, Rect::new_wh(50, 50, 1, 1));
+        let rect = dirty_rect.unwrap();
+        assert_eq!((rect.x1, rect.y1, rect.x2, rect.y2), (50, 50, 51, 51));
+
+        // but the whole chunk stays the whole chunk
+        let mut dirty_rect = None;
+        add_dirty_rect(&mut dirty_rect, true, Rect::new_wh(1, 1, 1, 1));
+        assert!(dirty_rect.is_none());
+    }
+
+    #[test]
+    fn handoff_keeps_order() {
+        let streamer = ChunkTextureStreamer::new();
+        let mut versions = TextureVersions::default();
+        let full = Rect::new(0, 0, i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+
+        let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        for i in 0..10_u8 {
+            colors[0] = Color::rgb(i, 0, 0);
+            versions.submitted(streamer.submit((0, 0), full, &colors));
+        }
+        assert!(versions.is_pending());
+
+        // the buffer is snapshotted on submit, so later changes don't leak into queued jobs
+        colors[0] = Color::rgb(255, 0, 0);
+
+        let packed = wait_for(&streamer, 10);
+        assert_eq!(packed.len(), 10);
+        for (i, p) in packed.iter().enumerate() {
+            assert_eq!(p.rgba[0], i as u8);
+            assert!(versions.accept(p.version));
+        }
+        assert!(!versions.is_pending());
+
+        // re-delivering an older upload is rejected
+        assert!(!versions.accept(packed[3].version));
+    }
+
+    #[test]
+    fn submit_packs_only_rect() {
+        let streamer = ChunkTextureStreamer::new();
+        let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        colors[2 + 5 * CHUNK_WIDTH as usize] = Color::rgb(1, 2, 3);
+        colors[3 + 6 * CHUNK_WIDTH as usize] = Color::rgb(4, 5, 6);
+        let rect = Rect::new(2, 5, 4, 7);
+
+        streamer.submit((0, 0), rect, &colors);
+        let packed = wait_for(&streamer, 1);
+        assert_eq!(packed.len(), 1);
+        assert_eq!(packed[0].rgba, pack_rect(&colors, rect));
+        assert_eq!(packed[0].rgba.len(), 2 * 2 * 4);
+    }
+
+    #[test]
+    fn rejects_uploads_for_previous_chunk() {
+        let streamer = ChunkTextureStreamer::new();
+        let colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        let rect = Rect::new(0, 0, 1, 1);
+
+        // submitted for a chunk that was then unloaded and loaded again at the same position
+        let old = streamer.submit((0, 0), rect, &colors);
+
+        let mut versions = TextureVersions::default();
+        let new = streamer.submit((0, 0), rect, &colors);
+        versions.submitted(new);
+
+        assert!(!versions.accept(old));
+        assert!(versions.accept(new));
+    }
+}
@@ -1,22 +1,26 @@
 use std::sync::Arc;
 
 use chunksystem::ChunkQuery;
-use glium::{Blend, DrawParameters, PolygonMode};
+use glium::{Blend, DrawParameters, PolygonMode, Surface};
 use rapier2d::prelude::Shape;
 use specs::{Join, ReadStorage, WorldExt};
 
 use fs_common::game::common::{
     world::{
+        chunk_index::{ChunkLocalIndex, ChunkLocalPosition},
         entity::{
             GameEntity, Hitbox, PhysicsEntity, Player, PlayerGrappleState, PlayerMovementMode,
+            Sprite,
         },
+        flow,
         gen::structure::StructureNode,
         material::color::Color,
         particle::ParticleSystem,
         physics::PHYSICS_SCALE,
-        AutoTarget, Camera, Chunk, ChunkState, Position, SidedChunk, Velocity, World, CHUNK_SIZE,
+        sim_time, AutoTarget, Camera, Chunk, ChunkState, Position, SidedChunk, Velocity, World,
+        CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
-    FileHelper, Rect, Registries, Settings,
+    DebugColor, DebugPalette, FileHelper, Rect, Registries, Settings,
 };
 
 use crate::{
@@ -24,18 +28,42 @@ use crate::{
     Client,
 };
 
-use super::{chunk_data::tile_entity::ClientTileEntityExt, ClientChunk, ClientWorld};
+use super::{
+    background::draw_background,
+    chunk_data::tile_entity::ClientTileEntityExt,
+    chunk_debug_color::chunk_debug_color,
+    heat_haze::HeatHaze,
+    liquid_foam::liquid_surface,
+    material_texture::{run_quad, texture_runs, MaterialTextures},
+    particle_lod::{lod_particles, particle_keep_ratio},
+    pixel_snap::{snap_camera, snap_screen_center},
+    sprite::{sprite_quad, SpriteTextures},
+    ClientChunk, ClientWorld,
+};
 
-pub struct WorldRenderer {}
+pub struct WorldRenderer {
+    sprite_textures: SpriteTextures,
+    material_textures: MaterialTextures,
+    heat_haze: HeatHaze,
+}
 
 impl WorldRenderer {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            sprite_textures: SpriteTextures::new(),
+            material_textures: MaterialTextures::new(),
+            heat_haze: HeatHaze::new(),
+        }
     }
 
     #[allow(clippy::unused_self)]
     pub fn init(&self, _world: &mut World<ClientChunk>) {}
 
+    /// Loads the material textures again, after the materials were reloaded.
+    pub fn reload_materials(&mut self) {
+        self.material_textures.reload();
+    }
+
     #[profiling::function]
     pub fn render(
         &mut self,
@@ -75,11 +103,28 @@ impl WorldRenderer {
 
         let camera_scale = ctx.client.camera_scale;
 
+        // everything in the world (including interpolated entities and particles) goes through
+        //   this transform, so it all moves by the same whole number of pixels
+        let (camera_pos, screen_center) = if ctx.settings.pixel_snap {
+            let (x, y) = snap_camera((camera_pos.x, camera_pos.y), camera_scale);
+            (
+                Position { x, y },
+                snap_screen_center(target.width(), target.height()),
+            )
+        } else {
+            (
+                camera_pos,
+                (
+                    f64::from(target.width()) / 2.0,
+                    f64::from(target.height()) / 2.0,
+                ),
+            )
+        };
+
+        draw_background(target, ctx.settings, camera_pos.y, camera_scale);
+
         target.transform.push();
-        target.transform.translate(
-            f64::from(target.width()) / 2.0,
-            f64::from(target.height()) / 2.0,
-        );
+        target.transform.translate(screen_center.0, screen_center.1);
         target.transform.scale(camera_scale, camera_scale);
         target.transform.translate(-camera_pos.x, -camera_pos.y);
 
@@ -92,10 +137,10 @@ impl WorldRenderer {
             unsafe { world.chunk_handler.manager.raw_mut().iter_mut() }
                 .filter_map(|(_i, ch)| {
                     let rc = Rect::new_wh(
-                        ch.chunk_x() * i32::from(CHUNK_SIZE),
-                        ch.chunk_y() * i32::from(CHUNK_SIZE),
-                        CHUNK_SIZE,
-                        CHUNK_SIZE,
+                        ch.chunk_x() * i32::from(CHUNK_WIDTH),
+                        ch.chunk_y() * i32::from(CHUNK_HEIGHT),
+                        CHUNK_WIDTH,
+                        CHUNK_HEIGHT,
                     );
 
                     if (ctx.settings.debug && !ctx.settings.cull_chunks)
@@ -103,8 +148,8 @@ impl WorldRenderer {
                     {
                         target.transform.push();
                         target.transform.translate(
-                            ch.chunk_x() * i32::from(CHUNK_SIZE),
-                            ch.chunk_y() * i32::from(CHUNK_SIZE),
+                            ch.chunk_x() * i32::from(CHUNK_WIDTH),
+                            ch.chunk_y() * i32::from(CHUNK_HEIGHT),
                         );
 
                         ch.prep_render(target, ctx.settings, ctx.file_helper);
@@ -113,13 +158,13 @@ impl WorldRenderer {
 
                         // ch.render(target, settings);
                         // ch.graphics.texture
-                        // let image = glium::texture::RawImage2d::from_raw_rgba((&ch.graphics.pixel_data).to_vec(), (CHUNK_SIZE.into(), CHUNK_SIZE.into()));
-                        // Some(((ch.chunk_x as f32 * f32::from(CHUNK_SIZE), ch.chunk_y as f32 * f32::from(CHUNK_SIZE)), image))
+                        // let image = glium::texture::RawImage2d::from_raw_rgba((&ch.graphics.pixel_data).to_vec(), (CHUNK_WIDTH.into(), CHUNK_HEIGHT.into()));
+                        // Some(((ch.chunk_x as f32 * f32::from(CHUNK_WIDTH), ch.chunk_y as f32 * f32::from(CHUNK_HEIGHT)), image))
                         ch.graphics.data.as_ref().map(|t| {
                             (
                                 (
-                                    ch.chunk_x() as f32 * f32::from(CHUNK_SIZE),
-                                    ch.chunk_y() as f32 * f32::from(CHUNK_SIZE),
+                                    ch.chunk_x() as f32 * f32::from(CHUNK_WIDTH),
+                                    ch.chunk_y() as f32 * f32::from(CHUNK_HEIGHT),
                                 ),
                                 t.clone(),
                             )
@@ -133,6 +178,18 @@ impl WorldRenderer {
 
         target.draw_chunks(&chunk_tex_data);
 
+        if ctx.settings.draw_material_textures {
+            self.draw_material_textures(&screen_zone, world, target, &ctx);
+        }
+
+        if ctx.settings.draw_liquid_foam {
+            self.draw_liquid_foam(&screen_zone, world, target, &ctx);
+        }
+
+        if ctx.settings.draw_flow_overlay {
+            self.draw_flow_overlay(&screen_zone, world, target, &ctx);
+        }
+
         // draw tile entities
         for ch in world.chunk_handler.manager.chunks_iter_mut() {
             for te in ch.sided_tile_entities_mut() {
@@ -152,9 +209,18 @@ impl WorldRenderer {
         {
             profiling::scope!("particles");
             let particle_system = world.ecs.read_resource::<ParticleSystem>();
-            target.draw_particles(&particle_system.active, ctx.partial_ticks as f32);
+            let keep_ratio = particle_keep_ratio(camera_scale, ctx.settings.particle_lod_scale);
+            // fewer but bigger particles, so they cover about as much of the screen
+            target.draw_particles(
+                lod_particles(&particle_system.active, keep_ratio),
+                (1.0 / keep_ratio.sqrt()) as f32,
+                ctx.partial_ticks as f32,
+            );
         }
 
+        // draw foreground materials (eg. glass) over particles, but still lit
+        target.draw_chunks_foreground(&chunk_tex_data);
+
         // multiply lighting
         if ctx.settings.draw_lighting {
             target.draw_chunks_light(
@@ -167,6 +233,11 @@ impl WorldRenderer {
             );
         }
 
+        self.heat_haze.advance(ctx.delta_time);
+        if ctx.settings.heat_haze {
+            self.draw_heat_haze(&screen_zone, world, target, &ctx, camera_scale);
+        }
+
         // overlays
 
         self.draw_chunk_overlays(&screen_zone, world, target, &ctx);
@@ -174,15 +245,21 @@ impl WorldRenderer {
         self.draw_ecs_debug(world, target, &ctx);
 
         if ctx.settings.debug && ctx.settings.draw_chunk_grid {
-            self.draw_chunk_grid(&camera_pos, target);
+            self.draw_chunk_grid(&camera_pos, &ctx.settings.debug_colors, target);
         }
 
         if ctx.settings.debug && ctx.settings.draw_origin {
-            self.draw_origin(target);
+            self.draw_origin(&ctx.settings.debug_colors, target);
         }
 
         if ctx.settings.debug && ctx.settings.draw_load_zones {
-            self.draw_load_zones(loader_pos, Some(camera_pos.into()), world, target);
+            self.draw_load_zones(
+                loader_pos,
+                Some(camera_pos.into()),
+                world,
+                &ctx.settings.debug_colors,
+                target,
+            );
         }
 
         target.transform.pop();
@@ -206,6 +283,7 @@ impl WorldRenderer {
             hitbox_storage,
             target_storage,
             player_storage,
+            sprite_storage,
         ) = world.ecs.system_data::<(
             specs::Entities,
             ReadStorage<GameEntity>,
@@ -216,6 +294,7 @@ impl WorldRenderer {
             ReadStorage<Hitbox>,
             ReadStorage<AutoTarget>,
             ReadStorage<Player>,
+            ReadStorage<Sprite>,
         )>();
 
         // draw structure bounds
@@ -241,7 +320,10 @@ impl WorldRenderer {
 
                     snode_rects_1.push((
                         Rect::new(x1 as f32, y1 as f32, x2 as f32, y2 as f32),
-                        Color::rgba(64, 64, 255, alpha),
+                        ctx.settings
+                            .debug_colors
+                            .get(DebugColor::StructureNode)
+                            .faded(alpha),
                     ));
 
                     target.transform.pop();
@@ -254,7 +336,10 @@ impl WorldRenderer {
                                 gen.bounds.x2 as f32,
                                 gen.bounds.y2 as f32,
                             ),
-                            Color::rgba(64, 255, 255, alpha),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::StructureBounds)
+                                .faded(alpha),
                         ));
                     }
                 });
@@ -280,21 +365,44 @@ impl WorldRenderer {
             );
         }
 
-        // draw entity positions
+        // draw entity sprites, or their positions if they don't have one
         (
             &game_entity_storage,
             &position_storage,
             velocity_storage.maybe(),
             physics_storage.maybe(),
+            sprite_storage.maybe(),
         )
             .join()
             .for_each(
-                |(_ge, pos, vel, _phys): (
+                |(_ge, pos, vel, _phys, sprite): (
                     &GameEntity,
                     &Position,
                     Option<&Velocity>,
                     Option<&PhysicsEntity>,
+                    Option<&Sprite>,
                 )| {
+                    let lerp_x = pos.x + vel.map_or(0.0, |v| v.x) * ctx.partial_ticks;
+                    let lerp_y = pos.y + vel.map_or(0.0, |v| v.y) * ctx.partial_ticks;
+
+                    if let Some(sprite) = sprite {
+                        if let Some(tex) = self.sprite_textures.get(
+                            &sprite.texture,
+                            &target.display,
+                            ctx.file_helper,
+                        ) {
+                            target.draw_textured_quad(
+                                &sprite_quad(lerp_x, lerp_y, sprite),
+                                tex,
+                                DrawParameters {
+                                    blend: Blend::alpha_blending(),
+                                    ..DrawParameters::default()
+                                },
+                            );
+                            return;
+                        }
+                    }
+
                     let mut draw = |x: f64, y: f64, alpha: u8| {
                         target.transform.push();
                         target.transform.translate(x, y);
@@ -304,7 +412,10 @@ impl WorldRenderer {
 
                         target.rectangle(
                             Rect::new(x1 as f32, y1 as f32, x2 as f32, y2 as f32),
-                            Color::rgba(64, 255, 64, alpha),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::EntityPosition)
+                                .faded(alpha),
                             DrawParameters {
                                 polygon_mode: PolygonMode::Line,
                                 line_width: Some(1.0),
@@ -317,7 +428,10 @@ impl WorldRenderer {
                             target.line(
                                 (0.0, 0.0),
                                 (vel.x, vel.y),
-                                Color::rgba(64, 255, 64, alpha),
+                                ctx.settings
+                                    .debug_colors
+                                    .get(DebugColor::EntityPosition)
+                                    .faded(alpha),
                                 DrawParameters {
                                     polygon_mode: PolygonMode::Line,
                                     line_width: Some(1.0),
@@ -330,8 +444,6 @@ impl WorldRenderer {
                         target.transform.pop();
                     };
 
-                    let lerp_x = pos.x + vel.map_or(0.0, |v| v.x) * ctx.partial_ticks;
-                    let lerp_y = pos.y + vel.map_or(0.0, |v| v.y) * ctx.partial_ticks;
                     draw(lerp_x, lerp_y, 255);
                     draw(pos.x, pos.y, 80);
                 },
@@ -350,7 +462,10 @@ impl WorldRenderer {
 
                     target.rectangle(
                         Rect::new(x1 as f32, y1 as f32, x2 as f32, y2 as f32),
-                        Color::rgba(255, 64, 64, alpha),
+                        ctx.settings
+                            .debug_colors
+                            .get(DebugColor::EntityHitbox)
+                            .faded(alpha),
                         DrawParameters {
                             polygon_mode: PolygonMode::Line,
                             line_width: Some(1.0),
@@ -381,7 +496,10 @@ impl WorldRenderer {
 
                     target.rectangle(
                         Rect::new(x1 as f32, y1 as f32, x2 as f32, y2 as f32),
-                        Color::rgba(64, 255, 64, alpha),
+                        ctx.settings
+                            .debug_colors
+                            .get(DebugColor::EntityTarget)
+                            .faded(alpha),
                         DrawParameters {
                             polygon_mode: PolygonMode::Line,
                             line_width: Some(1.0),
@@ -390,7 +508,7 @@ impl WorldRenderer {
                         },
                     );
 
-                    let target_pos = at.get_target_pos(&position_storage);
+                    let target_pos = at.get_target_pos(&position_storage, &velocity_storage);
                     if let Some(target_pos) = target_pos {
                         let (line_x1, line_y1) = (0.0, 0.0);
                         let (line_x2, line_y2) = (target_pos.x - x, target_pos.y - y);
@@ -398,7 +516,10 @@ impl WorldRenderer {
                         target.line(
                             (line_x1 as f32, line_y1 as f32),
                             (line_x2 as f32, line_y2 as f32),
-                            Color::rgba(255, 255, 64, alpha / 2),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::EntityTargetLine)
+                                .faded(alpha),
                             DrawParameters {
                                 polygon_mode: PolygonMode::Line,
                                 line_width: Some(1.0),
@@ -536,6 +657,206 @@ impl WorldRenderer {
             });
     }
 
+    /// Draws the material textures over the pixels of textured materials in the visible chunks.
+    ///
+    /// The texture coordinates come from world positions, so they line up across chunks.
+    fn draw_material_textures(
+        &mut self,
+        screen_zone: &Rect<i32>,
+        world: &World<ClientChunk>,
+        target: &mut RenderTarget,
+        ctx: &RenderContext,
+    ) {
+        profiling::scope!("draw_material_textures");
+
+        let Some((texture, tile_size, layer_of)) =
+            self.material_textures.get(&target.display, ctx.file_helper)
+        else {
+            return;
+        };
+
+        let mut quads = vec![];
+        for ch in world.chunk_handler.manager.chunks_iter() {
+            let world_x = ch.chunk_x() * i32::from(CHUNK_WIDTH);
+            let world_y = ch.chunk_y() * i32::from(CHUNK_HEIGHT);
+            let rc = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+            let visible =
+                (ctx.settings.debug && !ctx.settings.cull_chunks) || rc.intersects(screen_zone);
+            if !visible {
+                continue;
+            }
+
+            let Some(pixels) = ch.pixels() else {
+                continue;
+            };
+            quads.extend(
+                texture_runs(pixels, &layer_of)
+                    .into_iter()
+                    .map(|(r, layer)| {
+                        let r = Rect::new(
+                            r.x1 + world_x,
+                            r.y1 + world_y,
+                            r.x2 + world_x,
+                            r.y2 + world_y,
+                        );
+                        run_quad(r, layer, tile_size)
+                    }),
+            );
+        }
+
+        target.draw_texture_array_quads(
+            &quads,
+            texture,
+            DrawParameters {
+                blend: Blend::alpha_blending(),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Draws a lighter line along the top of every body of liquid in the visible chunks.
+    #[allow(clippy::unused_self)]
+    fn draw_liquid_foam(
+        &mut self,
+        screen_zone: &Rect<i32>,
+        world: &World<ClientChunk>,
+        target: &mut RenderTarget,
+        ctx: &RenderContext,
+    ) {
+        profiling::scope!("draw_liquid_foam");
+
+        let color = Color::WHITE.with_a(ctx.settings.liquid_foam_alpha);
+        let mut rects = vec![];
+        for ch in world.chunk_handler.manager.chunks_iter() {
+            let world_x = ch.chunk_x() * i32::from(CHUNK_WIDTH);
+            let world_y = ch.chunk_y() * i32::from(CHUNK_HEIGHT);
+            let rc = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+            let visible =
+                (ctx.settings.debug && !ctx.settings.cull_chunks) || rc.intersects(screen_zone);
+            if !visible {
+                continue;
+            }
+
+            let Some(pixels) = ch.pixels() else {
+                continue;
+            };
+            let above = world
+                .chunk_handler
+                .manager
+                .chunk_at((ch.chunk_x(), ch.chunk_y() - 1))
+                .and_then(|c| c.pixels().as_ref())
+                .map(|p| &p[CHUNK_AREA - CHUNK_WIDTH as usize..]);
+
+            let surface = liquid_surface(pixels, above, &ctx.registries.materials);
+            rects.extend(surface.into_iter().map(|r| {
+                let r = Rect::new(
+                    r.x1 + world_x,
+                    r.y1 + world_y,
+                    r.x2 + world_x,
+                    r.y2 + world_y,
+                );
+                (r.into_f32(), color)
+            }));
+        }
+
+        target.rectangles_colored(
+            &rects,
+            DrawParameters {
+                blend: Blend::alpha_blending(),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Makes everything drawn so far shimmer over the hot parts of the visible chunks.
+    fn draw_heat_haze(
+        &mut self,
+        screen_zone: &Rect<i32>,
+        world: &World<ClientChunk>,
+        target: &mut RenderTarget,
+        ctx: &RenderContext,
+        camera_scale: f64,
+    ) {
+        profiling::scope!("draw_heat_haze");
+
+        let mut hot = vec![];
+        for ch in world.chunk_handler.manager.chunks_iter() {
+            let world_x = ch.chunk_x() * i32::from(CHUNK_WIDTH);
+            let world_y = ch.chunk_y() * i32::from(CHUNK_HEIGHT);
+            let rc = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+            let visible =
+                (ctx.settings.debug && !ctx.settings.cull_chunks) || rc.intersects(screen_zone);
+            if !visible || !ch.graphics.hot {
+                continue;
+            }
+
+            if let Some(data) = &ch.graphics.data {
+                hot.push(((world_x as f32, world_y as f32), data.clone()));
+            }
+        }
+
+        // nothing hot on screen, so no need to copy it
+        if hot.is_empty() {
+            return;
+        }
+
+        let phase = self.heat_haze.phase();
+        let strength = ctx.settings.heat_haze_strength * camera_scale as f32;
+        let screen = self
+            .heat_haze
+            .screen_texture(&target.display, target.frame.get_dimensions());
+        target.copy_to(screen);
+        target.draw_chunks_heat_haze(&hot, screen, phase, strength);
+    }
+
+    /// Draws every pixel that moved recently in the visible chunks, more red the more recently it moved.
+    #[allow(clippy::unused_self)]
+    fn draw_flow_overlay(
+        &mut self,
+        screen_zone: &Rect<i32>,
+        world: &World<ClientChunk>,
+        target: &mut RenderTarget,
+        ctx: &RenderContext,
+    ) {
+        profiling::scope!("draw_flow_overlay");
+
+        let mut rects = vec![];
+        for ch in world.chunk_handler.manager.chunks_iter() {
+            let world_x = ch.chunk_x() * i32::from(CHUNK_WIDTH);
+            let world_y = ch.chunk_y() * i32::from(CHUNK_HEIGHT);
+            let rc = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+            let visible =
+                (ctx.settings.debug && !ctx.settings.cull_chunks) || rc.intersects(screen_zone);
+            if !visible {
+                continue;
+            }
+
+            let Some(flow) = ch.flow() else {
+                continue;
+            };
+            for pos in ChunkLocalPosition::iter() {
+                let value = flow[ChunkLocalIndex::from(pos)];
+                if value > 0 {
+                    let r = Rect::new_wh(
+                        world_x + i32::from(pos.x()),
+                        world_y + i32::from(pos.y()),
+                        1,
+                        1,
+                    );
+                    rects.push((r.into_f32(), flow::color(value)));
+                }
+            }
+        }
+
+        target.rectangles_colored(
+            &rects,
+            DrawParameters {
+                blend: Blend::alpha_blending(),
+                ..Default::default()
+            },
+        );
+    }
+
     fn draw_chunk_overlays(
         &mut self,
         screen_zone: &Rect<i32>,
@@ -546,11 +867,14 @@ impl WorldRenderer {
         profiling::scope!("draw_chunk_overlays");
         let mut structure_lines = vec![];
         let mut state_rects = vec![];
+        let mut sim_time_rects = vec![];
+        let mut hash_rects = vec![];
+        let mut fog_rects = vec![];
 
         unsafe { world.chunk_handler.manager.raw_mut().iter_mut() }.for_each(|(_i, ch)| {
-            let world_x = ch.chunk_x() * i32::from(CHUNK_SIZE);
-            let world_y = ch.chunk_y() * i32::from(CHUNK_SIZE);
-            let rc = Rect::new_wh(world_x, world_y, CHUNK_SIZE, CHUNK_SIZE);
+            let world_x = ch.chunk_x() * i32::from(CHUNK_WIDTH);
+            let world_y = ch.chunk_y() * i32::from(CHUNK_HEIGHT);
+            let rc = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
 
             // queue structure set debug
             if let (true, Some(set)) = (ctx.settings.debug, ctx.settings.draw_structure_set.clone())
@@ -567,16 +891,18 @@ impl WorldRenderer {
                     structure_lines.push((
                         (world_x as f32, world_y as f32),
                         (
-                            (start_x * i32::from(CHUNK_SIZE)) as f32,
-                            (start_y * i32::from(CHUNK_SIZE)) as f32,
+                            (start_x * i32::from(CHUNK_WIDTH)) as f32,
+                            (start_y * i32::from(CHUNK_HEIGHT)) as f32,
+                        ),
+                        ctx.settings.debug_colors.get(
+                            if start_x == ch.chunk_x() && start_y == ch.chunk_y() {
+                                DebugColor::StructureStartHere
+                            } else if should_gen_start {
+                                DebugColor::StructureStartValid
+                            } else {
+                                DebugColor::StructureStartInvalid
+                            },
                         ),
-                        if start_x == ch.chunk_x() && start_y == ch.chunk_y() {
-                            Color::GREEN
-                        } else if should_gen_start {
-                            Color::ORANGE.with_a(0.25)
-                        } else {
-                            Color::RED.with_a(0.125)
-                        },
                     ));
                 }
             }
@@ -593,7 +919,7 @@ impl WorldRenderer {
                         let rect = dr.into_f32();
                         target.rectangle(
                             rect,
-                            Color::rgba(255, 64, 64, 127),
+                            ctx.settings.debug_colors.get(DebugColor::ChunkDirtyRect),
                             DrawParameters {
                                 blend: Blend::alpha_blending(),
                                 ..Default::default()
@@ -601,7 +927,7 @@ impl WorldRenderer {
                         );
                         target.rectangle(
                             rect,
-                            Color::rgba(255, 64, 64, 127),
+                            ctx.settings.debug_colors.get(DebugColor::ChunkDirtyRect),
                             DrawParameters {
                                 polygon_mode: PolygonMode::Line,
                                 line_width: Some(1.0),
@@ -612,12 +938,14 @@ impl WorldRenderer {
                     }
 
                     if ch.graphics.pixels_updated_last_update {
-                        let rect = Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE)
+                        let rect = Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT)
                             .into_f32()
                             .inflated(-2.0);
                         target.rectangle(
                             rect,
-                            Color::rgba(255, 255, 64, 80),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::ChunkPixelsUpdatedFill),
                             DrawParameters {
                                 blend: Blend::alpha_blending(),
                                 ..Default::default()
@@ -625,7 +953,9 @@ impl WorldRenderer {
                         );
                         target.rectangle(
                             rect,
-                            Color::rgba(255, 255, 64, 100),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::ChunkPixelsUpdatedOutline),
                             DrawParameters {
                                 polygon_mode: PolygonMode::Line,
                                 line_width: Some(1.0),
@@ -636,12 +966,14 @@ impl WorldRenderer {
                     }
 
                     if ch.graphics.lighting_updated_last_update {
-                        let rect = Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE)
+                        let rect = Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT)
                             .into_f32()
                             .inflated(-4.0);
                         target.rectangle(
                             rect,
-                            Color::rgba(64, 255, 255, 32),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::ChunkLightingUpdatedFill),
                             DrawParameters {
                                 blend: Blend::alpha_blending(),
                                 ..Default::default()
@@ -649,7 +981,9 @@ impl WorldRenderer {
                         );
                         target.rectangle(
                             rect,
-                            Color::rgba(64, 255, 255, 64),
+                            ctx.settings
+                                .debug_colors
+                                .get(DebugColor::ChunkLightingUpdatedOutline),
                             DrawParameters {
                                 polygon_mode: PolygonMode::Line,
                                 line_width: Some(1.0),
@@ -664,7 +998,9 @@ impl WorldRenderer {
                             let rect = Rect::new_wh(20 + i * 12, 20, 10, 10).into_f32();
                             target.rectangle(
                                 rect,
-                                Color::rgba(255, 64, 255, 32),
+                                ctx.settings
+                                    .debug_colors
+                                    .get(DebugColor::ChunkDirtyLightDistance),
                                 DrawParameters {
                                     blend: Blend::alpha_blending(),
                                     ..Default::default()
@@ -675,30 +1011,76 @@ impl WorldRenderer {
                 }
             }
 
+            // queue hash overlay
+            if ctx.settings.debug && ctx.settings.draw_chunk_hash_overlay {
+                let rect = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+                let color = chunk_debug_color(ch.chunk_x(), ch.chunk_y())
+                    .with_a(ctx.settings.draw_chunk_hash_overlay_alpha);
+                hash_rects.push((rect.into_f32(), color));
+            }
+
             // queue state overlay
             if ctx.settings.debug && ctx.settings.draw_chunk_state_overlay {
-                let rect = Rect::new_wh(world_x, world_y, CHUNK_SIZE, CHUNK_SIZE);
+                let rect = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
 
-                let alpha: u8 = (ctx.settings.draw_chunk_state_overlay_alpha * 255.0) as u8;
+                let palette = &ctx.settings.debug_colors;
                 let color = match ch.state() {
-                    ChunkState::NotGenerated => Color::rgba(127, 127, 127, alpha),
-                    ChunkState::Generating(stage) => Color::rgba(
-                        64,
-                        (f32::from(stage)
-                            / f32::from(world.chunk_handler.generator.max_gen_stage())
-                            * 255.0) as u8,
-                        255,
-                        alpha,
-                    ),
-                    ChunkState::Cached => Color::rgba(255, 127, 64, alpha),
-                    ChunkState::Active => Color::rgba(64, 255, 64, alpha),
-                };
+                    ChunkState::NotGenerated => palette.get(DebugColor::ChunkNotGenerated),
+                    ChunkState::Generating(stage) => {
+                        let base = palette.get(DebugColor::ChunkGenerating);
+                        Color {
+                            g: (f32::from(base.g) * f32::from(stage)
+                                / f32::from(world.chunk_handler.generator.max_gen_stage()))
+                                as u8,
+                            ..base
+                        }
+                    },
+                    ChunkState::Cached => palette.get(DebugColor::ChunkCached),
+                    ChunkState::Active => palette.get(DebugColor::ChunkActive),
+                }
+                .faded(ctx.settings.draw_chunk_state_overlay_alpha);
                 state_rects.push((rect.into_f32(), color));
             }
 
+            // queue sim time overlay
+            if ctx.settings.debug && ctx.settings.draw_chunk_sim_time_overlay {
+                if let Some(time) = ch.sim_time() {
+                    let rect = Rect::new_wh(world_x, world_y, CHUNK_WIDTH, CHUNK_HEIGHT);
+                    let alpha = (ctx.settings.draw_chunk_sim_time_overlay_alpha * 255.0) as u8;
+                    sim_time_rects.push((rect.into_f32(), sim_time::color(time, alpha)));
+                }
+            }
+
+            // queue fog over unexplored chunks
+            if ctx.settings.fog_of_war && !ch.revealed() && rc.intersects(screen_zone) {
+                let color = Color::BLACK.with_a(ctx.settings.fog_of_war_alpha);
+                fog_rects.push((rc.into_f32(), color));
+            }
+
             target.transform.pop();
         });
 
+        if ctx.settings.fog_of_war {
+            target.rectangles_colored(
+                &fog_rects,
+                DrawParameters {
+                    blend: Blend::alpha_blending(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // draw hash overlay
+        if ctx.settings.debug && ctx.settings.draw_chunk_hash_overlay {
+            target.rectangles_colored(
+                &hash_rects,
+                DrawParameters {
+                    blend: Blend::alpha_blending(),
+                    ..Default::default()
+                },
+            );
+        }
+
         // draw state overlay
         if ctx.settings.debug && ctx.settings.draw_chunk_state_overlay {
             target.rectangles_colored(
@@ -719,6 +1101,26 @@ impl WorldRenderer {
             );
         }
 
+        // draw sim time overlay
+        if ctx.settings.debug && ctx.settings.draw_chunk_sim_time_overlay {
+            target.rectangles_colored(
+                &sim_time_rects,
+                DrawParameters {
+                    blend: Blend::alpha_blending(),
+                    ..Default::default()
+                },
+            );
+            target.rectangles_colored(
+                &sim_time_rects,
+                DrawParameters {
+                    polygon_mode: PolygonMode::Line,
+                    line_width: Some(1.0),
+                    blend: Blend::alpha_blending(),
+                    ..Default::default()
+                },
+            );
+        }
+
         // draw structure set debug
         if ctx.settings.debug && ctx.settings.draw_structure_set.is_some() {
             target.lines(
@@ -893,13 +1295,17 @@ impl WorldRenderer {
                 target.line(
                     (com.x, com.y),
                     (com.x + 0.5, com.y),
-                    Color::BLUE,
+                    ctx.settings
+                        .debug_colors
+                        .get(DebugColor::PhysicsCenterOfMassX),
                     DrawParameters::default(),
                 );
                 target.line(
                     (com.x, com.y),
                     (com.x, com.y + 0.5),
-                    Color::RED,
+                    ctx.settings
+                        .debug_colors
+                        .get(DebugColor::PhysicsCenterOfMassY),
                     DrawParameters::default(),
                 );
             }
@@ -917,7 +1323,10 @@ impl WorldRenderer {
                         ry,
                         b.rotation().angle(),
                         target,
-                        Color::rgba(0x00, 0xff, 0x00, if b.is_sleeping() { 0x64 } else { 0xff }),
+                        ctx.settings
+                            .debug_colors
+                            .get(DebugColor::PhysicsShape)
+                            .faded(if b.is_sleeping() { 0x64 } else { 0xff }),
                     );
                 }
 
@@ -932,7 +1341,10 @@ impl WorldRenderer {
 
                     target.rectangle(
                         Rect::new(x1, y1, x2, y2),
-                        Color::rgba(0xff, 0, 0xff, if b.is_sleeping() { 0x64 } else { 0xff }),
+                        ctx.settings
+                            .debug_colors
+                            .get(DebugColor::PhysicsAabb)
+                            .faded(if b.is_sleeping() { 0x64 } else { 0xff }),
                         DrawParameters {
                             polygon_mode: PolygonMode::Line,
                             line_width: Some(1.0),
@@ -949,18 +1361,23 @@ impl WorldRenderer {
         target.transform.pop();
     }
 
-    fn draw_chunk_grid(&mut self, camera_pos: &Position, target: &mut RenderTarget) {
+    fn draw_chunk_grid(
+        &mut self,
+        camera_pos: &Position,
+        palette: &DebugPalette,
+        target: &mut RenderTarget,
+    ) {
         profiling::scope!("draw_chunk_grid");
         let rects = (-10..10)
             .flat_map(|x| {
                 (-8..8).map(move |y| {
-                    let rc_x = x + (camera_pos.x / f64::from(CHUNK_SIZE)) as i32;
-                    let rc_y = y + (camera_pos.y / f64::from(CHUNK_SIZE)) as i32;
+                    let rc_x = x + (camera_pos.x / f64::from(CHUNK_WIDTH)) as i32;
+                    let rc_y = y + (camera_pos.y / f64::from(CHUNK_HEIGHT)) as i32;
                     let rc = Rect::new_wh(
-                        rc_x * i32::from(CHUNK_SIZE),
-                        rc_y * i32::from(CHUNK_SIZE),
-                        CHUNK_SIZE,
-                        CHUNK_SIZE,
+                        rc_x * i32::from(CHUNK_WIDTH),
+                        rc_y * i32::from(CHUNK_HEIGHT),
+                        CHUNK_WIDTH,
+                        CHUNK_HEIGHT,
                     );
                     rc.into_f32()
                 })
@@ -969,7 +1386,7 @@ impl WorldRenderer {
 
         target.rectangles(
             &rects,
-            Color::rgba(64, 64, 64, 127),
+            palette.get(DebugColor::ChunkGrid),
             DrawParameters {
                 polygon_mode: PolygonMode::Line,
                 line_width: Some(1.0),
@@ -979,7 +1396,7 @@ impl WorldRenderer {
         );
     }
 
-    fn draw_origin(&mut self, target: &mut RenderTarget) {
+    fn draw_origin(&mut self, palette: &DebugPalette, target: &mut RenderTarget) {
         profiling::scope!("draw_origin");
         const LEN: f32 = 16.0;
         target.rectangles_colored(
@@ -987,25 +1404,28 @@ impl WorldRenderer {
                 // background X axis
                 (
                     Rect::new_wh(-LEN - 2.0, -1.5, LEN * 2.0 + 4.0, 3.0),
-                    Color::BLACK.with_a(0.5),
+                    palette.get(DebugColor::OriginBackground),
                 ),
                 // background Y axis
                 (
                     Rect::new_wh(-1.5, -LEN - 2.0, 3.0, LEN * 2.0 + 4.0),
-                    Color::BLACK.with_a(0.5),
+                    palette.get(DebugColor::OriginBackground),
                 ),
                 // X axis
                 (
                     Rect::new_wh(-LEN - 1.0, -0.5, LEN * 2.0 + 2.0, 1.0),
-                    Color::RED.with_a(0.5),
+                    palette.get(DebugColor::OriginX),
                 ),
                 // Y axis
                 (
                     Rect::new_wh(-0.5, -LEN - 1.0, 1.0, LEN * 2.0 + 2.0),
-                    Color::GREEN.with_a(0.5),
+                    palette.get(DebugColor::OriginY),
                 ),
                 // origin
-                (Rect::new_wh(-1.0, -1.0, 2.0, 2.0), Color::WHITE),
+                (
+                    Rect::new_wh(-1.0, -1.0, 2.0, 2.0),
+                    palette.get(DebugColor::OriginCenter),
+                ),
             ],
             DrawParameters {
                 blend: Blend::alpha_blending(),
@@ -1019,22 +1439,21 @@ impl WorldRenderer {
         loader: (f64, f64),
         camera: Option<(f64, f64)>,
         world: &mut World<ClientChunk>,
+        palette: &DebugPalette,
         target: &mut RenderTarget,
     ) {
         profiling::scope!("draw_load_zones");
-        let active_zone = world.chunk_handler.get_active_zone(loader);
-        let load_zone = world.chunk_handler.get_load_zone(loader);
-        let unload_zone = world.chunk_handler.get_unload_zone(loader);
+        let zones = world.chunk_handler.zones_at(loader);
 
         let mut rects = vec![
-            (unload_zone.into_f32(), Color::rgba(255, 0, 0, 127)),
-            (load_zone.into_f32(), Color::rgba(255, 127, 0, 127)),
-            (active_zone.into_f32(), Color::rgba(255, 255, 0, 127)),
+            (zones.unload.into_f32(), palette.get(DebugColor::UnloadZone)),
+            (zones.load.into_f32(), palette.get(DebugColor::LoadZone)),
+            (zones.active.into_f32(), palette.get(DebugColor::ActiveZone)),
         ];
 
         if let Some(camera) = camera {
             let screen_zone = world.chunk_handler.get_screen_zone(camera); // note we always use the camera for the screen zone
-            rects.push((screen_zone.into_f32(), Color::rgba(0, 255, 0, 127)));
+            rects.push((screen_zone.into_f32(), palette.get(DebugColor::ScreenZone)));
         }
 
         target.rectangles_colored(
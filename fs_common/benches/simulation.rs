@@ -0,0 +1,19 @@
+//! Measures how many pixels per second `Simulator::simulate_chunk` gets through.
+//!
+//! Run with `cargo bench -p fs_common --bench simulation`.
+
+use fs_common::game::common::world::bench::{simulation_throughput, BENCH_SEED};
+
+fn main() {
+    // warm up the caches and allocator
+    simulation_throughput(20, BENCH_SEED);
+
+    let result = simulation_throughput(500, BENCH_SEED);
+    println!(
+        "simulate_chunk: {} ticks in {:?}, {:.0} pixels/s ({:?}/tick)",
+        result.ticks,
+        result.elapsed,
+        result.pixels_per_second(),
+        result.elapsed / result.ticks,
+    );
+}
@@ -43,6 +43,14 @@ pub struct CLArgs {
     )]
     pub assets_dir: PathBuf,
 
+    #[arg(
+        long = "saves-dir",
+        value_name = "PATH",
+        action = clap::ArgAction::Append,
+        help = "Look for worlds in this directory instead of <game-dir>/saves, can be given more than once"
+    )]
+    pub saves_dirs: Vec<PathBuf>,
+
     #[command(subcommand)]
     pub subcommand: Option<CLSubcommand>,
 }
@@ -58,6 +66,47 @@ pub enum CLSubcommand {
             help = "The port to run the server on"
         )]
         port: u16,
+
+        #[arg(
+            long = "active-radius",
+            value_name = "PIXELS",
+            action,
+            help = "How far past the screen size around each player chunks are simulated"
+        )]
+        active_radius: Option<u16>,
+
+        #[arg(
+            long = "load-radius",
+            value_name = "PIXELS",
+            action,
+            help = "How far past the screen size around each player chunks are loaded"
+        )]
+        load_radius: Option<u16>,
+
+        #[arg(
+            long = "unload-radius",
+            value_name = "PIXELS",
+            action,
+            help = "How far past the screen size around each player chunks stay loaded"
+        )]
+        unload_radius: Option<u16>,
+
+        #[arg(
+            long,
+            value_name = "MIN_X,MIN_Y,MAX_X,MAX_Y",
+            value_delimiter = ',',
+            allow_negative_numbers = true,
+            help = "Limit the world to the chunks from MIN to MAX (inclusive)"
+        )]
+        border: Option<Vec<i32>>,
+
+        #[arg(
+            long = "open-border",
+            action,
+            requires = "border",
+            help = "Leave nothing past the border instead of a solid barrier"
+        )]
+        open_border: bool,
     },
 }
 
@@ -1,4 +1,4 @@
-use clap::{ArgMatches, Command};
+use clap::{value_parser, Arg, ArgMatches, Command};
 
 pub struct CommandHandler {
     commands: Command,
@@ -18,7 +18,45 @@ impl CommandHandler {
                         .aliases(["exit", "quit", "stop"])
                         .about("Exit the game"),
                 )
-                .subcommand(Command::new("save").about("Save the game")),
+                .subcommand(Command::new("save").about("Save the game"))
+                .subcommand(
+                    Command::new("generate")
+                        .about("Generate the chunks around a point right away")
+                        .arg(
+                            Arg::new("x")
+                                .required(true)
+                                .allow_negative_numbers(true)
+                                .value_parser(value_parser!(i64)),
+                        )
+                        .arg(
+                            Arg::new("y")
+                                .required(true)
+                                .allow_negative_numbers(true)
+                                .value_parser(value_parser!(i64)),
+                        )
+                        .arg(
+                            Arg::new("radius")
+                                .default_value("4")
+                                .value_parser(value_parser!(u16)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("tp")
+                        .alias("teleport")
+                        .about("Teleport every player to a point, generating the chunks there")
+                        .arg(
+                            Arg::new("x")
+                                .required(true)
+                                .allow_negative_numbers(true)
+                                .value_parser(value_parser!(f64)),
+                        )
+                        .arg(
+                            Arg::new("y")
+                                .required(true)
+                                .allow_negative_numbers(true)
+                                .value_parser(value_parser!(f64)),
+                        ),
+                ),
         }
     }
 
@@ -6,11 +6,28 @@ use std::{
 pub struct FileHelper {
     game_dir: PathBuf,
     asset_dir: PathBuf,
+    save_roots: Vec<PathBuf>,
 }
 
 impl FileHelper {
+    /// Worlds are saved in `saves` in `game_dir`, unless other roots are given with [`Self::with_save_roots`].
     pub fn new(game_dir: PathBuf, asset_dir: PathBuf) -> Self {
-        Self { game_dir, asset_dir }
+        let save_roots = vec![game_dir.join("saves")];
+        Self { game_dir, asset_dir, save_roots }
+    }
+
+    /// Replaces the folders worlds are looked for in. Empty keeps the default.
+    #[must_use]
+    pub fn with_save_roots(mut self, roots: Vec<PathBuf>) -> Self {
+        if !roots.is_empty() {
+            self.save_roots = roots;
+        }
+        self
+    }
+
+    /// Every folder worlds are looked for in, the first one being where new ones go.
+    pub fn save_roots(&self) -> &[PathBuf] {
+        &self.save_roots
     }
 
     pub fn game_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
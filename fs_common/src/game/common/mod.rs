@@ -6,11 +6,13 @@ pub mod hashmap_ext;
 mod registries;
 pub mod registry;
 mod settings;
+mod tick_scheduler;
 pub use registries::*;
 use std::ops::Range;
 
 use serde::{Deserialize, Serialize};
 pub use settings::*;
+pub use tick_scheduler::*;
 pub mod commands;
 
 mod file_helper;
@@ -114,6 +116,15 @@ impl<T: Copy + std::ops::Add<Output = T> + std::cmp::PartialOrd + std::ops::Sub<
         x >= self.left() && y >= self.top() && x <= self.right() && y <= self.bottom()
     }
 
+    /// The area covered by both rects, if there is any.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let x1 = partial_max(self.x1, other.x1);
+        let y1 = partial_max(self.y1, other.y1);
+        let x2 = partial_min(self.x2, other.x2);
+        let y2 = partial_min(self.y2, other.y2);
+        (x1 < x2 && y1 < y2).then_some(Self { x1, y1, x2, y2 })
+    }
+
     #[must_use]
     pub fn union(self, other: Self) -> Self {
         let x1 = partial_min(self.x1, other.x1);
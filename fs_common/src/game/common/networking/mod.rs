@@ -1,3 +1,5 @@
+use std::{borrow::Borrow, io::Write};
+
 use super::world::material::{color::Color, MaterialInstance};
 use serde::{Deserialize, Serialize};
 
@@ -26,4 +28,192 @@ pub enum PacketType {
         positions: Vec<PVec2>,
         velocities: Vec<PVec2>,
     },
+    /// Sent by the server for chunks it isn't sending in full, see [`chunk_checksum`].
+    ChunkChecksumPacket {
+        chunk_x: i32,
+        chunk_y: i32,
+        checksum: u64,
+    },
+    /// Sent by the client when its copy of a chunk doesn't match the server's, asking for a [`PacketType::SyncChunkPacket`].
+    RequestChunkSyncPacket { chunk_x: i32, chunk_y: i32 },
+    /// Sent by the client for pixels it changed with a tool (painting, erasing), which it already
+    ///   changed in its own copy of the world without waiting for the server.
+    ///
+    /// The server applies the ones the sending connection's player could have made itself (see [`World::apply_remote_edit`](crate::game::common::world::World::apply_remote_edit)),
+    ///   then answers with an [`PacketType::EditAckPacket`] followed by a [`PacketType::SyncChunkPacket`]
+    ///   for every chunk they touched, which has the final say.
+    EditPixelsPacket {
+        id: u32,
+        pixels: Vec<(i64, i64, MaterialInstance)>,
+    },
+    /// Sent by the server once it has applied the [`PacketType::EditPixelsPacket`] with this `id`.
+    EditAckPacket { id: u32 },
+}
+
+/// Writes a packet the way [`Packet`]s are framed on the wire: its length as a bincode `u32`, then the packet itself.
+pub fn write_packet(stream: &mut impl Write, packet: &Packet) -> std::io::Result<()> {
+    let buf = bincode::serialize(packet).map_err(std::io::Error::other)?;
+    let size_buf = bincode::serialize(&(buf.len() as u32)).map_err(std::io::Error::other)?;
+    stream.write_all(&size_buf)?;
+    stream.write_all(&buf)?;
+    stream.flush()
+}
+
+/// Largest packet body either side accepts, anything bigger means the stream is broken.
+pub const MAX_PACKET_SIZE: u32 = 2_000_000;
+
+/// Size of the length in front of every packet, a bincode `u32`.
+const HEADER_SIZE: usize = 4;
+
+/// Splits a byte stream back into packet bodies.
+///
+/// Every packet is sent as its length (a bincode `u32`) followed by that many bytes of body.
+/// Bytes can be pushed in however they arrive, bodies come out once they are complete.
+#[derive(Debug, Default)]
+pub struct PacketAssembler {
+    pending: Vec<u8>,
+}
+
+impl PacketAssembler {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Takes the next complete packet body, if it has fully arrived.
+    ///
+    /// Fails if the length can't be right (zero or more than [`MAX_PACKET_SIZE`]),
+    ///   since there's no way to find the start of the next packet after that.
+    pub fn next_body(&mut self) -> Result<Option<Vec<u8>>, String> {
+        if self.pending.len() < HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let size: u32 =
+            bincode::deserialize(&self.pending[..HEADER_SIZE]).map_err(|e| e.to_string())?;
+        if size == 0 {
+            return Err("Zero length packet".into());
+        }
+        if size > MAX_PACKET_SIZE {
+            return Err(format!("Packet is too big ({size} bytes)"));
+        }
+
+        let end = HEADER_SIZE + size as usize;
+        if self.pending.len() < end {
+            return Ok(None);
+        }
+
+        let body = self.pending[HEADER_SIZE..end].to_vec();
+        self.pending.drain(..end);
+        Ok(Some(body))
+    }
+}
+
+/// A hash of a chunk's pixels, for checking that the client and server agree on them.
+///
+/// Only the state that is synced is hashed (not light, which each side works out on its own).
+/// It's FNV-1a over the pixels in index order with fixed-size fields, so it comes out the same
+///   on every machine and build.
+pub fn chunk_checksum(pixels: &[MaterialInstance]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut write = |bytes: &[u8]| {
+        for b in bytes {
+            hash ^= u64::from(*b);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+
+    for p in pixels {
+        let id: &str = p.material_id.borrow();
+        write(&(id.len() as u32).to_le_bytes());
+        write(id.as_bytes());
+        write(&[
+            p.physics as u8,
+            p.color.r,
+            p.color.g,
+            p.color.b,
+            p.color.a,
+            p.fall,
+        ]);
+        write(&p.moisture.to_bits().to_le_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::{
+        material::{self, color::Color, MaterialInstance, PhysicsType},
+        CHUNK_AREA,
+    };
+
+    use super::{chunk_checksum, PacketAssembler, MAX_PACKET_SIZE};
+
+    #[test]
+    fn checksum_detects_changes() {
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, Color::rgb(90, 90, 100));
+        let mut a = vec![MaterialInstance::air(); CHUNK_AREA];
+        for p in &mut a[..CHUNK_AREA / 2] {
+            *p = stone.clone();
+        }
+        let mut b = a.clone();
+
+        assert_eq!(chunk_checksum(&a), chunk_checksum(&b));
+
+        // light isn't synced, so it doesn't count
+        b[10] = b[10].clone().with_light([1.0, 0.5, 0.0]);
+        assert_eq!(chunk_checksum(&a), chunk_checksum(&b));
+
+        b[CHUNK_AREA - 1] = stone;
+        assert_ne!(chunk_checksum(&a), chunk_checksum(&b));
+
+        // moving a pixel is a change too, even though the same pixels are there
+        let mut c = a.clone();
+        c.swap(0, CHUNK_AREA - 1);
+        assert_ne!(chunk_checksum(&a), chunk_checksum(&c));
+    }
+
+    fn frame(body: &[u8]) -> Vec<u8> {
+        let mut bytes = bincode::serialize(&(body.len() as u32)).unwrap();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn assembles_split_stream() {
+        let bodies: Vec<Vec<u8>> = vec![
+            vec![1],
+            (0..=255).collect(),
+            vec![7; 5000],
+            b"last packet".to_vec(),
+        ];
+        let stream: Vec<u8> = bodies.iter().flat_map(|b| frame(b)).collect();
+
+        // split in every way from one byte at a time to everything at once
+        for split in [1, 2, 3, 4, 5, 7, 13, 100, 4097, stream.len()] {
+            let mut assembler = PacketAssembler::default();
+            let mut out = vec![];
+            for bytes in stream.chunks(split) {
+                assembler.push(bytes);
+                while let Some(body) = assembler.next_body().unwrap() {
+                    out.push(body);
+                }
+            }
+            assert_eq!(out, bodies, "split into chunks of {split}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_lengths() {
+        let mut assembler = PacketAssembler::default();
+        assembler.push(&frame(&[]));
+        assert!(assembler.next_body().is_err());
+
+        let mut assembler = PacketAssembler::default();
+        assembler.push(&bincode::serialize(&(MAX_PACKET_SIZE + 1)).unwrap());
+        assert!(assembler.next_body().is_err());
+    }
 }
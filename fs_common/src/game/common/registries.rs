@@ -29,7 +29,7 @@ pub struct Registries {
 
 impl Registries {
     pub fn init(file_helper: &FileHelper) -> Self {
-        Self {
+        let registries = Self {
             materials: material::init_material_types(),
             material_placers: placer::init_material_placers(file_helper),
             structure_pieces: structure::piece::init_structure_pieces(file_helper),
@@ -39,9 +39,19 @@ impl Registries {
             ),
             structure_sets: structure::set::init_structure_sets(file_helper),
             biomes: biome::init_biomes(file_helper),
+        };
+
+        for issue in structure::validate::validate_structures(&registries) {
+            log::warn!("{issue}");
         }
+
+        registries
     }
 
+    /// Reloads the materials and material placers (and the textures they use), keeping everything else.
+    ///
+    /// Existing pixels aren't changed, use
+    ///   [`World::remap_materials`](super::world::World::remap_materials) for that.
     pub fn empty() -> Self {
         Self {
             materials: MaterialRegistry::new(),
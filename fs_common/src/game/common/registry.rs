@@ -6,16 +6,22 @@ use std::{
     sync::Arc,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize)]
+/// Serialized as just the id, the same as it's read back.
+#[derive(Deserialize)]
 #[serde(from = "String")]
 pub struct RegistryID<T> {
     value: Arc<String>,
-    #[serde(skip_serializing, default)]
     _phantom: PhantomData<T>,
 }
 
+impl<T> Serialize for RegistryID<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.value)
+    }
+}
+
 impl<T> Debug for RegistryID<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("RegistryID").field(&self.value).finish()
@@ -1,4 +1,13 @@
-use super::{registry::RegistryID, world::gen::structure::set::StructureSet};
+use super::{
+    registry::RegistryID,
+    world::{
+        entity::BrushPalette,
+        gen::structure::set::StructureSet,
+        material::{color::Color, PhysicsTypeSet, SandSpread},
+        particle::ParticleInteractions,
+    },
+    Rect,
+};
 
 pub struct Settings {
     pub debug: bool,
@@ -6,6 +15,12 @@ pub struct Settings {
     // rendering
     pub draw_chunk_state_overlay: bool,
     pub draw_chunk_state_overlay_alpha: f32,
+    /// Colors every chunk by how long it took to simulate, see `sim_time`. Chunks are only timed while this is on.
+    pub draw_chunk_sim_time_overlay: bool,
+    pub draw_chunk_sim_time_overlay_alpha: f32,
+    /// Colors every chunk by its position, see `chunk_debug_color`.
+    pub draw_chunk_hash_overlay: bool,
+    pub draw_chunk_hash_overlay_alpha: f32,
     pub draw_chunk_dirty_rects: bool,
     pub draw_chunk_grid: bool,
     pub draw_chunk_collision: ChunkCollisionOverlay,
@@ -13,12 +28,49 @@ pub struct Settings {
     pub draw_load_zones: bool,
     pub draw_structure_bounds: bool,
     pub draw_structure_set: Option<RegistryID<StructureSet>>,
+    pub draw_liquid_foam: bool,
+    pub liquid_foam_alpha: f32,
+    /// Makes the air over hot pixels shimmer, see `heat_haze`. Off by default, since it costs a copy
+    ///   of the screen each frame something hot is on screen.
+    pub heat_haze: bool,
+    /// How far [`Self::heat_haze`] moves things at most, in world pixels.
+    pub heat_haze_strength: f32,
+    /// Draws textures from `assets/texture/material/` over those materials, see `material_texture`.
+    pub draw_material_textures: bool,
+    /// Colors pixels by how recently they moved, see `flow`. Costs an extra buffer per chunk while on.
+    pub draw_flow_overlay: bool,
+    /// Covers chunks no loader has been near yet, see `Chunk::revealed`.
+    pub fog_of_war: bool,
+    /// How opaque [`Self::fog_of_war`] is, 1 hides unexplored chunks completely.
+    pub fog_of_war_alpha: f32,
+    /// Below this camera scale only some particles are drawn, see `particle_lod`. 0 draws all of them.
+    pub particle_lod_scale: f64,
     pub draw_lighting: bool,
     pub lighting_smooth: bool,
     pub lighting_dithering: bool,
     pub lighting_overlay: bool,
     pub lighting_linear_blend: bool,
+    /// How chunk and rigidbody pixels are smoothed when zoomed in.
+    pub pixel_filter: PixelFilter,
+    /// Rounds the camera to whole screen pixels so the world doesn't shimmer when it moves,
+    ///   at the cost of sub-pixel smooth scrolling.
+    pub pixel_snap: bool,
+    pub sun_light: bool,
+    pub sun_slope: f32,
+    pub sun_intensity: f32,
+    pub propagate_light: bool,
     pub cull_chunks: bool,
+    /// What is drawn behind the world, where there is nothing but air.
+    pub background: BackgroundStyle,
+    pub background_top: Color,
+    pub background_bottom: Color,
+    /// The world y coordinate the gradient is centered on, when the camera is at y = 0.
+    pub background_horizon: f64,
+    /// How tall the gradient is, in world pixels.
+    pub background_gradient_height: f64,
+    /// How much the background moves with the camera, from 0 (stuck to the screen)
+    ///   to 1 (stuck to the world).
+    pub background_parallax: f64,
     pub physics_dbg_draw: bool,
     pub physics_dbg_draw_shape: bool,
     pub physics_dbg_draw_joint: bool,
@@ -26,23 +78,91 @@ pub struct Settings {
     pub physics_dbg_draw_pair: bool,
     pub physics_dbg_draw_center_of_mass: bool,
     pub physics_dbg_draw_particle: bool,
+    /// Colors used by the debug overlays.
+    pub debug_colors: DebugPalette,
 
     // display
     pub fullscreen: bool,
     pub fullscreen_type: usize,
     pub vsync: bool,
     pub minimize_on_lost_focus: bool,
+    pub window_title: String,
+    /// How often the FPS counter and process stats are updated, in milliseconds.
+    pub stats_interval_ms: u32,
+    pub camera_offset_x: f64,
+    pub camera_offset_y: f64,
+    pub camera_look_ahead: f64,
+    pub camera_smoothing: f64,
+    pub camera_zoom_min: f64,
+    pub camera_zoom_max: f64,
+    /// How much each notch of the scroll wheel zooms by, as a fraction of the current scale.
+    pub camera_zoom_step: f64,
+    /// Roughly how many seconds zooming takes to reach the new scale, or 0 to zoom instantly.
+    pub camera_zoom_duration: f64,
+    /// How far the movement keys pan a free camera each tick, in screen pixels.
+    pub camera_pan_speed: f64,
+    /// What [`Self::camera_pan_speed`] is multiplied by while holding shift.
+    pub camera_pan_fast_multiplier: f64,
+    /// Exports numbered PNGs of the world while on, see `Timelapse`.
+    pub timelapse: bool,
+    /// How many ticks apart timelapse frames are.
+    pub timelapse_interval: u32,
+    /// Export [`Self::timelapse_region`] instead of what's on screen.
+    pub timelapse_fixed_region: bool,
+    /// In world pixels.
+    pub timelapse_region: Rect<i32>,
 
     // simulation
     pub tick: bool,
     pub tick_speed: u16,
+    pub tick_particles_speed: u16,
     pub tick_physics: bool,
     pub tick_physics_speed: u16,
     pub tick_physics_timestep: f32,
+    pub destructive_impacts: bool,
+    /// How far rigidbody colliders may stray from their pixels, in pixels.
+    pub rigidbody_mesh_tolerance: f64,
+    /// Leaves materials entities pass through out of rigidbody colliders too,
+    ///   see [`Material::entity_collidable`](crate::game::common::world::material::Material::entity_collidable).
+    pub rigidbody_passable_materials: bool,
     pub load_chunks: bool,
+    /// How long chunk population and meshing can take per tick, in milliseconds.
+    /// At least one chunk always makes progress each tick, even at 0.
+    pub chunk_gen_budget_ms: f32,
+    /// What happens to chunks that leave every loader's unload zone.
+    pub chunk_unload_policy: ChunkUnloadPolicy,
+    /// How many out of range chunks [`ChunkUnloadPolicy::Cache`] keeps before unloading the least recently used.
+    pub chunk_cache_cap: usize,
     pub simulate_chunks: bool,
+    pub simulate_chunks_parallel: bool,
+    /// Threads used for parallel chunk simulation, 0 for one per cpu.
+    pub simulation_threads: usize,
+    /// How many times chunks are simulated each tick.
+    pub simulate_chunks_substeps: u8,
+    /// How many dirty pixels chunk simulation can go through per tick (across all substeps), 0 for no limit.
+    /// Chunks past the limit wait for a later tick, so busy worlds simulate slower instead of lagging.
+    pub sim_pixel_budget: u32,
+    /// Pixels of the types not in here are frozen in place, for debugging.
+    pub simulate_physics_types: PhysicsTypeSet,
+    /// Sand walled in on both sides slides straight down instead of scattering or sticking,
+    /// so one pixel wide gaps fill densely.
+    pub sand_settle_narrow_gaps: bool,
+    /// How sand spreads out when it can't fall, for materials that don't set their own.
+    pub sand_spread: SandSpread,
+    /// Mixed into the seed of every chunk's rng while simulating.
+    /// Changing it gives different but reproducible pixel behavior, starting on the next tick.
+    pub simulation_seed: u32,
     pub simulate_particles: bool,
+    /// Threads used for particles, separate from [`Settings::simulation_threads`]. 0 for one per cpu.
+    pub particle_threads: usize,
+    pub particle_interactions: ParticleInteractions,
     pub pause_on_lost_focus: bool,
+    pub survival: bool,
+    /// What the number keys select outside of survival.
+    pub brush_palette: BrushPalette,
+    /// How tall a step players walk up without jumping, in pixels. 0 disables stepping up.
+    /// See [`PhysicsEntity::edge_clip_distance`](crate::game::common::world::entity::PhysicsEntity::edge_clip_distance).
+    pub player_edge_clip_distance: f32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -53,6 +173,193 @@ pub enum ChunkCollisionOverlay {
     Earcutr,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkUnloadPolicy {
+    /// Save and drop out of range chunks right away. Uses less memory, but coming back has to load them again.
+    Discard,
+    /// Keep out of range chunks in memory (up to [`Settings::chunk_cache_cap`]), so coming back is instant.
+    Cache,
+}
+
+impl ChunkUnloadPolicy {
+    pub fn values() -> &'static [Self] {
+        &[Self::Discard, Self::Cache]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFilter {
+    /// Crisp pixels.
+    Nearest,
+    /// Blends between neighboring pixels.
+    Linear,
+}
+
+impl PixelFilter {
+    pub fn values() -> &'static [Self] {
+        &[Self::Nearest, Self::Linear]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundStyle {
+    None,
+    Solid,
+    Gradient,
+}
+
+impl BackgroundStyle {
+    pub fn values() -> &'static [Self] {
+        &[Self::None, Self::Solid, Self::Gradient]
+    }
+}
+
+/// Every part of the debug overlays that has its own color in the [`DebugPalette`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugColor {
+    ChunkDirtyRect,
+    ChunkPixelsUpdatedFill,
+    ChunkPixelsUpdatedOutline,
+    ChunkLightingUpdatedFill,
+    ChunkLightingUpdatedOutline,
+    ChunkDirtyLightDistance,
+    ChunkNotGenerated,
+    /// The green channel is scaled by how far along generation is.
+    ChunkGenerating,
+    ChunkCached,
+    ChunkActive,
+    ChunkGrid,
+    UnloadZone,
+    LoadZone,
+    ActiveZone,
+    ScreenZone,
+    StructureNode,
+    StructureBounds,
+    /// Line from a chunk to the structure start it belongs to, when that's the chunk itself.
+    StructureStartHere,
+    StructureStartValid,
+    StructureStartInvalid,
+    EntityPosition,
+    EntityHitbox,
+    EntityTarget,
+    EntityTargetLine,
+    PhysicsShape,
+    PhysicsAabb,
+    PhysicsCenterOfMassX,
+    PhysicsCenterOfMassY,
+    OriginBackground,
+    OriginX,
+    OriginY,
+    OriginCenter,
+}
+
+impl DebugColor {
+    /// Every value, in declaration order.
+    pub fn values() -> &'static [Self] {
+        &[
+            Self::ChunkDirtyRect,
+            Self::ChunkPixelsUpdatedFill,
+            Self::ChunkPixelsUpdatedOutline,
+            Self::ChunkLightingUpdatedFill,
+            Self::ChunkLightingUpdatedOutline,
+            Self::ChunkDirtyLightDistance,
+            Self::ChunkNotGenerated,
+            Self::ChunkGenerating,
+            Self::ChunkCached,
+            Self::ChunkActive,
+            Self::ChunkGrid,
+            Self::UnloadZone,
+            Self::LoadZone,
+            Self::ActiveZone,
+            Self::ScreenZone,
+            Self::StructureNode,
+            Self::StructureBounds,
+            Self::StructureStartHere,
+            Self::StructureStartValid,
+            Self::StructureStartInvalid,
+            Self::EntityPosition,
+            Self::EntityHitbox,
+            Self::EntityTarget,
+            Self::EntityTargetLine,
+            Self::PhysicsShape,
+            Self::PhysicsAabb,
+            Self::PhysicsCenterOfMassX,
+            Self::PhysicsCenterOfMassY,
+            Self::OriginBackground,
+            Self::OriginX,
+            Self::OriginY,
+            Self::OriginCenter,
+        ]
+    }
+
+    pub fn default_color(self) -> Color {
+        match self {
+            Self::ChunkDirtyRect => Color::rgba(255, 64, 64, 127),
+            Self::ChunkPixelsUpdatedFill => Color::rgba(255, 255, 64, 80),
+            Self::ChunkPixelsUpdatedOutline => Color::rgba(255, 255, 64, 100),
+            Self::ChunkLightingUpdatedFill => Color::rgba(64, 255, 255, 32),
+            Self::ChunkLightingUpdatedOutline => Color::rgba(64, 255, 255, 64),
+            Self::ChunkDirtyLightDistance => Color::rgba(255, 64, 255, 32),
+            Self::ChunkNotGenerated => Color::rgb(127, 127, 127),
+            Self::ChunkGenerating | Self::StructureBounds => Color::rgb(64, 255, 255),
+            Self::ChunkCached => Color::rgb(255, 127, 64),
+            Self::ChunkActive | Self::EntityPosition | Self::EntityTarget => {
+                Color::rgb(64, 255, 64)
+            },
+            Self::ChunkGrid => Color::rgba(64, 64, 64, 127),
+            Self::UnloadZone => Color::rgba(255, 0, 0, 127),
+            Self::LoadZone => Color::rgba(255, 127, 0, 127),
+            Self::ActiveZone => Color::rgba(255, 255, 0, 127),
+            Self::ScreenZone => Color::rgba(0, 255, 0, 127),
+            Self::StructureNode => Color::rgb(64, 64, 255),
+            Self::StructureStartHere | Self::PhysicsShape => Color::GREEN,
+            Self::StructureStartValid => Color::ORANGE.with_a(0.25),
+            Self::StructureStartInvalid => Color::RED.with_a(0.125),
+            Self::EntityHitbox => Color::rgb(255, 64, 64),
+            Self::EntityTargetLine => Color::rgba(255, 255, 64, 127),
+            Self::PhysicsAabb => Color::MAGENTA,
+            Self::PhysicsCenterOfMassX => Color::BLUE,
+            Self::PhysicsCenterOfMassY => Color::RED,
+            Self::OriginBackground => Color::BLACK.with_a(0.5),
+            Self::OriginX => Color::RED.with_a(0.5),
+            Self::OriginY => Color::GREEN.with_a(0.5),
+            Self::OriginCenter => Color::WHITE,
+        }
+    }
+}
+
+/// The color of each [`DebugColor`], so the debug overlays can be changed to whatever is
+///   easiest to tell apart.
+///
+/// Overlays that fade things out (like sleeping rigidbodies) multiply these alphas rather than
+///   replacing them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugPalette {
+    /// Indexed by [`DebugColor`].
+    colors: Vec<Color>,
+}
+
+impl DebugPalette {
+    pub fn get(&self, element: DebugColor) -> Color {
+        self.colors[element as usize]
+    }
+
+    pub fn get_mut(&mut self, element: DebugColor) -> &mut Color {
+        &mut self.colors[element as usize]
+    }
+}
+
+impl Default for DebugPalette {
+    fn default() -> Self {
+        Self {
+            colors: DebugColor::values()
+                .iter()
+                .map(|c| c.default_color())
+                .collect(),
+        }
+    }
+}
+
 impl ChunkCollisionOverlay {
     pub fn values() -> &'static [Self] {
         &[
@@ -70,6 +377,10 @@ impl Default for Settings {
             debug: false,
             draw_chunk_state_overlay: false,
             draw_chunk_state_overlay_alpha: 0.5,
+            draw_chunk_sim_time_overlay: false,
+            draw_chunk_sim_time_overlay_alpha: 0.5,
+            draw_chunk_hash_overlay: false,
+            draw_chunk_hash_overlay_alpha: 0.5,
             draw_chunk_dirty_rects: false,
             draw_chunk_grid: false,
             draw_chunk_collision: ChunkCollisionOverlay::None,
@@ -77,14 +388,35 @@ impl Default for Settings {
             draw_load_zones: false,
             draw_structure_bounds: false,
             draw_structure_set: None,
+            draw_liquid_foam: true,
+            liquid_foam_alpha: 0.35,
+            heat_haze: false,
+            heat_haze_strength: 1.5,
+            draw_material_textures: true,
+            draw_flow_overlay: false,
+            fog_of_war: false,
+            fog_of_war_alpha: 1.0,
+            particle_lod_scale: 1.0,
 
             draw_lighting: true,
             lighting_smooth: true,
             lighting_dithering: true,
             lighting_overlay: false,
             lighting_linear_blend: true,
+            pixel_filter: PixelFilter::Nearest,
+            pixel_snap: false,
+            sun_light: false,
+            sun_slope: 0.3,
+            sun_intensity: 1.0,
+            propagate_light: true,
 
             cull_chunks: true,
+            background: BackgroundStyle::Gradient,
+            background_top: Color::rgb(96, 140, 200),
+            background_bottom: Color::rgb(16, 12, 12),
+            background_horizon: 0.0,
+            background_gradient_height: 800.0,
+            background_parallax: 0.25,
             physics_dbg_draw: false,
             physics_dbg_draw_shape: true,
             physics_dbg_draw_joint: true,
@@ -92,21 +424,99 @@ impl Default for Settings {
             physics_dbg_draw_pair: true,
             physics_dbg_draw_center_of_mass: true,
             physics_dbg_draw_particle: false,
+            debug_colors: DebugPalette::default(),
 
             fullscreen: false,
             fullscreen_type: 0,
             vsync: false,
             minimize_on_lost_focus: false,
+            window_title: "FallingSandRust ({fps} FPS) ({net_mode})".to_string(),
+            stats_interval_ms: 1000,
+            camera_offset_x: 0.0,
+            camera_offset_y: 0.0,
+            camera_look_ahead: 0.0,
+            camera_smoothing: 0.0,
+            camera_zoom_min: 0.01,
+            camera_zoom_max: 10.0,
+            camera_zoom_step: 0.1,
+            camera_zoom_duration: 0.15,
+            camera_pan_speed: 12.0,
+            camera_pan_fast_multiplier: 4.0,
+            timelapse: false,
+            timelapse_interval: 30,
+            timelapse_fixed_region: false,
+            timelapse_region: Rect::new_wh(-480, -270, 960, 540),
 
             tick: true,
             tick_speed: 30,
+            tick_particles_speed: 30,
             tick_physics: true,
             tick_physics_speed: 60,
             tick_physics_timestep: 1.0 / 45.0,
+            destructive_impacts: true,
+            rigidbody_mesh_tolerance: 1.0,
+            rigidbody_passable_materials: false,
             load_chunks: true,
+            chunk_gen_budget_ms: 4.0,
+            chunk_unload_policy: ChunkUnloadPolicy::Discard,
+            chunk_cache_cap: 256,
             simulate_chunks: true,
+            simulate_chunks_parallel: true,
+            simulation_threads: 0,
+            simulate_chunks_substeps: 1,
+            sim_pixel_budget: 0,
+            simulate_physics_types: PhysicsTypeSet::ALL,
+            sand_settle_narrow_gaps: true,
+            sand_spread: SandSpread::Diagonal,
+            simulation_seed: 0,
             simulate_particles: true,
+            particle_threads: 0,
+            particle_interactions: ParticleInteractions::ALL,
             pause_on_lost_focus: false,
+            survival: false,
+            brush_palette: BrushPalette::default(),
+            player_edge_clip_distance: 2.0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::material::color::Color;
+
+    use super::{DebugColor, DebugPalette};
+
+    #[test]
+    fn debug_palette_defaults() {
+        let palette = DebugPalette::default();
+        for (i, c) in DebugColor::values().iter().enumerate() {
+            // `get` indexes by discriminant, so `values` has to be in declaration order
+            assert_eq!(*c as usize, i);
+            assert_eq!(palette.get(*c), c.default_color());
+        }
+        assert_eq!(palette.colors.len(), DebugColor::values().len());
+
+        // the colors these overlays used before they were configurable
+        for (c, color) in [
+            (DebugColor::ChunkDirtyRect, Color::rgba(255, 64, 64, 127)),
+            (DebugColor::ChunkActive, Color::rgba(64, 255, 64, 255)),
+            (DebugColor::ChunkCached, Color::rgba(255, 127, 64, 255)),
+            (DebugColor::UnloadZone, Color::rgba(255, 0, 0, 127)),
+            (DebugColor::LoadZone, Color::rgba(255, 127, 0, 127)),
+            (DebugColor::ActiveZone, Color::rgba(255, 255, 0, 127)),
+            (DebugColor::ScreenZone, Color::rgba(0, 255, 0, 127)),
+            (DebugColor::ChunkGrid, Color::rgba(64, 64, 64, 127)),
+            (DebugColor::PhysicsAabb, Color::rgba(0xff, 0, 0xff, 0xff)),
+            (DebugColor::StructureStartValid, Color::ORANGE.with_a(0.25)),
+        ] {
+            assert_eq!(palette.get(c), color, "{c:?}");
+        }
+
+        // fading keeps the palette's own alpha in proportion
+        assert_eq!(Color::rgb(1, 2, 3).faded(80u8), Color::rgba(1, 2, 3, 80));
+        assert_eq!(
+            palette.get(DebugColor::EntityTargetLine).faded(255u8),
+            Color::rgba(255, 255, 64, 127)
+        );
+    }
+}
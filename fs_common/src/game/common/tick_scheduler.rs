@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use super::{world::WorldRules, Settings};
+
+/// The most ticks one subsystem will run in a single frame to catch up.
+/// Any time beyond that is dropped, so a subsystem that can't keep up doesn't starve the others.
+pub const MAX_CATCH_UP_TICKS: u32 = 5;
+
+/// Accumulates elapsed time for one subsystem and turns it into a number of fixed-rate ticks.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TickAccumulator {
+    accumulated: Duration,
+}
+
+impl TickAccumulator {
+    /// Adds `elapsed` time and returns how many ticks at `hz` are due.
+    pub fn advance(&mut self, elapsed: Duration, hz: u16, enabled: bool) -> u32 {
+        if !enabled || hz == 0 {
+            self.accumulated = Duration::ZERO;
+            return 0;
+        }
+
+        let period = Duration::from_nanos(1_000_000_000 / u64::from(hz));
+        self.accumulated += elapsed;
+
+        let mut ticks = 0;
+        while self.accumulated >= period && ticks < MAX_CATCH_UP_TICKS {
+            self.accumulated -= period;
+            ticks += 1;
+        }
+
+        if self.accumulated >= period {
+            log::warn!("{hz} Hz tick is behind, skipping some ticks to catch up...");
+            self.accumulated =
+                Duration::from_nanos((self.accumulated.as_nanos() % period.as_nanos()) as u64);
+        }
+
+        ticks
+    }
+
+    /// How far between the last tick and the next one we are, from 0 to 1.
+    pub fn partial_ticks(&self, hz: u16) -> f64 {
+        (self.accumulated.as_secs_f64() * f64::from(hz)).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledTicks {
+    pub chunks: u32,
+    pub particles: u32,
+    pub physics: u32,
+}
+
+/// How many times per second chunks, particles and physics tick.
+///
+/// A world's [`WorldRules`] can set its own rates, the rest come from [`Settings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickRates {
+    pub chunks: u16,
+    pub particles: u16,
+    pub physics: u16,
+}
+
+impl TickRates {
+    pub fn new(settings: &Settings, rules: Option<&WorldRules>) -> Self {
+        Self {
+            chunks: rules
+                .and_then(|r| r.tick_speed)
+                .unwrap_or(settings.tick_speed),
+            particles: rules
+                .and_then(|r| r.tick_particles_speed)
+                .unwrap_or(settings.tick_particles_speed),
+            physics: rules
+                .and_then(|r| r.tick_physics_speed)
+                .unwrap_or(settings.tick_physics_speed),
+        }
+    }
+}
+
+/// Runs chunk simulation, particles and physics each at their own rate, see [`TickRates`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TickScheduler {
+    pub chunks: TickAccumulator,
+    pub particles: TickAccumulator,
+    pub physics: TickAccumulator,
+}
+
+impl TickScheduler {
+    /// `rules` are the current world's, if there is one.
+    pub fn advance(
+        &mut self,
+        elapsed: Duration,
+        settings: &Settings,
+        rules: Option<&WorldRules>,
+    ) -> ScheduledTicks {
+        let rates = TickRates::new(settings, rules);
+        ScheduledTicks {
+            chunks: self.chunks.advance(elapsed, rates.chunks, settings.tick),
+            particles: self.particles.advance(
+                elapsed,
+                rates.particles,
+                settings.tick && settings.simulate_particles,
+            ),
+            physics: self
+                .physics
+                .advance(elapsed, rates.physics, settings.tick_physics),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::game::common::{world::WorldRules, Settings};
+
+    use super::{ScheduledTicks, TickScheduler, MAX_CATCH_UP_TICKS};
+
+    fn settings() -> Settings {
+        Settings {
+            tick_speed: 30,
+            tick_particles_speed: 60,
+            tick_physics_speed: 120,
+            ..Settings::default()
+        }
+    }
+
+    /// Simulates `millis` of 1ms frames, returning the total ticks per subsystem.
+    fn run(scheduler: &mut TickScheduler, settings: &Settings, millis: u32) -> ScheduledTicks {
+        let mut total = ScheduledTicks::default();
+        for _ in 0..millis {
+            let ticks = scheduler.advance(Duration::from_millis(1), settings, None);
+            total.chunks += ticks.chunks;
+            total.particles += ticks.particles;
+            total.physics += ticks.physics;
+        }
+        total
+    }
+
+    #[test]
+    fn independent_rates() {
+        let settings = settings();
+        let mut scheduler = TickScheduler::default();
+
+        assert_eq!(
+            run(&mut scheduler, &settings, 2000),
+            ScheduledTicks { chunks: 60, particles: 120, physics: 240 }
+        );
+    }
+
+    #[test]
+    fn slow_frame_does_not_starve() {
+        let settings = settings();
+        let mut scheduler = TickScheduler::default();
+
+        // a long stall only runs a bounded number of catch up ticks for every subsystem
+        let ticks = scheduler.advance(Duration::from_secs(2), &settings, None);
+        assert_eq!(
+            ticks,
+            ScheduledTicks {
+                chunks: MAX_CATCH_UP_TICKS,
+                particles: MAX_CATCH_UP_TICKS,
+                physics: MAX_CATCH_UP_TICKS,
+            }
+        );
+
+        // and afterwards everything is back on schedule
+        assert_eq!(
+            run(&mut scheduler, &settings, 100),
+            ScheduledTicks { chunks: 3, particles: 6, physics: 12 }
+        );
+    }
+
+    #[test]
+    fn world_rates_override_settings() {
+        let settings = settings();
+        let rules = WorldRules {
+            tick_speed: Some(10),
+            tick_physics_speed: Some(20),
+            ..WorldRules::default()
+        };
+        let mut scheduler = TickScheduler::default();
+
+        let mut total = ScheduledTicks::default();
+        for _ in 0..1000 {
+            let ticks = scheduler.advance(Duration::from_millis(1), &settings, Some(&rules));
+            total.chunks += ticks.chunks;
+            total.particles += ticks.particles;
+            total.physics += ticks.physics;
+        }
+        // particles aren't set by the world, so they keep the player's rate
+        assert_eq!(
+            total,
+            ScheduledTicks { chunks: 10, particles: 60, physics: 20 }
+        );
+    }
+
+    #[test]
+    fn disabled_subsystem() {
+        let settings = Settings { simulate_particles: false, ..settings() };
+        let mut scheduler = TickScheduler::default();
+
+        assert_eq!(
+            run(&mut scheduler, &settings, 100),
+            ScheduledTicks { chunks: 3, particles: 0, physics: 12 }
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    cell::UnsafeCell,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::game::common::{Rect, Registries};
+
+use super::{
+    material::{self, color::Color, MaterialInstance, PhysicsType},
+    simulator::{LightSettings, SimRules, Simulator, SimulatorChunkContext},
+    CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH,
+};
+
+/// The seed `benches/simulation.rs` uses, so runs can be compared.
+pub const BENCH_SEED: u64 = 0x5EED_5A4D;
+
+/// How fast [`simulation_throughput`] simulated its scene.
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationThroughput {
+    pub ticks: u32,
+    /// How many pixels were simulated over all ticks.
+    pub pixels: u64,
+    /// Time spent in [`Simulator::simulate_chunk`], not counting setting up the scene.
+    pub elapsed: Duration,
+}
+
+impl SimulationThroughput {
+    pub fn pixels_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.pixels as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// A chunk that's mostly sand over an empty chunk below it, the same for a given `seed`.
+fn dense_sand_scene(seed: u64) -> [Vec<MaterialInstance>; 9] {
+    let rng = fastrand::Rng::with_seed(seed);
+    let mut chunks: [Vec<MaterialInstance>; 9] =
+        std::array::from_fn(|_| vec![MaterialInstance::air(); CHUNK_AREA]);
+
+    for px in &mut chunks[4] {
+        if rng.f32() < 0.85 {
+            let shade = rng.u8(180..=255);
+            *px = material::TEST.instance(PhysicsType::Sand, Color::rgb(shade, shade - 40, 100));
+        }
+    }
+
+    chunks
+}
+
+#[allow(clippy::unnecessary_box_returns)] // too large for the stack
+fn cells<T>(new: impl Fn() -> T) -> Box<[UnsafeCell<T>; CHUNK_AREA]> {
+    let v: Vec<_> = (0..CHUNK_AREA).map(|_| UnsafeCell::new(new())).collect();
+    v.into_boxed_slice().try_into().ok().unwrap()
+}
+
+/// Simulates a chunk full of falling sand `ticks` times with [`Simulator::simulate_chunk`],
+///   starting from the same scene every tick so each one does about the same work.
+///
+/// The same `seed` always simulates the same pixels.
+pub fn simulation_throughput(ticks: u32, seed: u64) -> SimulationThroughput {
+    let scene = dense_sand_scene(seed);
+    let registries = Arc::new(Registries::empty());
+
+    let mut pixels: Vec<_> = (0..9).map(|_| cells(MaterialInstance::air)).collect();
+    let mut colors: Vec<_> = (0..9).map(|_| cells(|| Color::TRANSPARENT)).collect();
+    let lights: Vec<_> = (0..9).map(|_| cells(|| [0.0; 4])).collect();
+
+    let mut particles = vec![];
+    let mut settled = vec![];
+    let mut elapsed = Duration::ZERO;
+    for tick in 0..ticks {
+        for ((px, col), src) in pixels.iter_mut().zip(&mut colors).zip(&scene) {
+            for ((p, c), mat) in px.iter_mut().zip(col.iter_mut()).zip(src) {
+                *c.get_mut() = mat.color;
+                *p.get_mut() = mat.clone();
+            }
+        }
+        particles.clear();
+        settled.clear();
+
+        let mut chunk_data: [SimulatorChunkContext; 9] =
+            std::array::from_fn(|i| SimulatorChunkContext {
+                pixels: &pixels[i],
+                colors: &colors[i],
+                lights: &lights[i],
+                flow: None,
+                dirty: false,
+                dirty_rect: (i == 4).then_some(Rect::new_wh(
+                    0,
+                    0,
+                    i32::from(CHUNK_WIDTH),
+                    i32::from(CHUNK_HEIGHT),
+                )),
+            });
+
+        let start = Instant::now();
+        Simulator::simulate_chunk(
+            0,
+            0,
+            &mut chunk_data,
+            &mut particles,
+            &mut settled,
+            registries.clone(),
+            seed.wrapping_add(u64::from(tick)),
+            LightSettings::default(),
+            SimRules::default(),
+        );
+        elapsed += start.elapsed();
+    }
+
+    SimulationThroughput {
+        ticks,
+        pixels: u64::from(ticks) * CHUNK_AREA as u64,
+        elapsed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dense_sand_scene, simulation_throughput, BENCH_SEED};
+
+    #[test]
+    fn simulation_throughput_runs() {
+        assert_eq!(dense_sand_scene(BENCH_SEED), dense_sand_scene(BENCH_SEED));
+
+        let result = simulation_throughput(3, BENCH_SEED);
+        assert_eq!(result.ticks, 3);
+        assert!(result.pixels > 0);
+        assert!(result.pixels_per_second() > 0.0);
+    }
+}
@@ -0,0 +1,208 @@
+use chunksystem::{ChunkKey, ChunkManager, ChunkQueryOne};
+
+use crate::game::common::Rect;
+
+use super::{
+    entity::Hitbox,
+    material::{self, color::Color, MaterialInstance, PhysicsType},
+    Chunk, ChunkState, Position, Velocity, CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH,
+};
+
+/// Far enough out in every direction that nothing will ever get there, for borders open on some sides.
+const UNBOUNDED_CHUNKS: Rect<i32> = Rect {
+    x1: i32::MIN / 2,
+    y1: i32::MIN / 2,
+    x2: i32::MAX / 2,
+    y2: i32::MAX / 2,
+};
+
+/// Limits the world to a rectangle of chunks, for arena style maps,
+///   or to a range of depths, see [`WorldBorder::vertical`].
+///
+/// Chunks outside the border are never generated, they are filled with [`WorldBorder::outside`] instead.
+/// Pixels outside the border can't be edited, and reading an unloaded pixel outside of it
+///   gives `outside` instead of an error.
+#[derive(Debug, Clone)]
+pub struct WorldBorder {
+    /// The chunks inside the border. `x2` and `y2` are exclusive.
+    pub chunks: Rect<i32>,
+    /// What everything outside the border is made of.
+    pub outside: MaterialInstance,
+    /// What everything above the border is made of instead of [`Self::outside`], if set.
+    pub above: Option<MaterialInstance>,
+    /// What everything below the border is made of instead of [`Self::outside`], if set.
+    pub below: Option<MaterialInstance>,
+}
+
+impl WorldBorder {
+    /// A border around the chunks from `min` to `max` (inclusive).
+    pub fn new(min: ChunkKey, max: ChunkKey, outside: MaterialInstance) -> Self {
+        Self {
+            chunks: Rect::new(min.0, min.1, max.0 + 1, max.1 + 1),
+            outside,
+            above: None,
+            below: None,
+        }
+    }
+
+    /// A border that only limits how deep and how high the world goes, between the chunk rows
+    ///   `ceiling` and `floor` (inclusive). `None` leaves that side open.
+    ///
+    /// Below the floor is solid `floor_material`, so sand and liquids have something to land on.
+    /// Above the ceiling is air, but nothing can go up there.
+    pub fn vertical(
+        ceiling: Option<i32>,
+        floor: Option<i32>,
+        floor_material: MaterialInstance,
+    ) -> Self {
+        Self {
+            chunks: Rect::new(
+                UNBOUNDED_CHUNKS.x1,
+                ceiling.unwrap_or(UNBOUNDED_CHUNKS.y1),
+                UNBOUNDED_CHUNKS.x2,
+                floor.map_or(UNBOUNDED_CHUNKS.y2, |f| f + 1),
+            ),
+            outside: floor_material,
+            above: Some(MaterialInstance::air()),
+            below: None,
+        }
+    }
+
+    /// The area inside both `self` and `other`, eg. a rectangle of chunks that also has a floor.
+    ///
+    /// Past each side is whatever is past that side of the border that's further in.
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Self {
+        let (a, b) = (&self.chunks, &other.chunks);
+        let (x1, y1) = (a.x1.max(b.x1), a.y1.max(b.y1));
+        // borders that don't overlap leave nothing inside
+        let chunks = Rect::new(x1, y1, a.x2.min(b.x2).max(x1), a.y2.min(b.y2).max(y1));
+
+        let sides = if b.x2 - b.x1 < a.x2 - a.x1 {
+            other
+        } else {
+            self
+        };
+        let top = if b.y1 > a.y1 { other } else { self };
+        let bottom = if b.y2 < a.y2 { other } else { self };
+        Self {
+            chunks,
+            outside: sides.outside.clone(),
+            above: Some(top.above.as_ref().unwrap_or(&top.outside).clone()),
+            below: Some(bottom.below.as_ref().unwrap_or(&bottom.outside).clone()),
+        }
+    }
+
+    /// A border that nothing can pass through.
+    pub fn barrier(min: ChunkKey, max: ChunkKey) -> Self {
+        Self::new(
+            min,
+            max,
+            material::BEDROCK.instance(PhysicsType::Solid, Color::rgb(32, 32, 32)),
+        )
+    }
+
+    /// A border with nothing past it, anything that falls out is gone.
+    pub fn open(min: ChunkKey, max: ChunkKey) -> Self {
+        Self::new(min, max, MaterialInstance::air())
+    }
+
+    pub fn contains_chunk(&self, chunk: ChunkKey) -> bool {
+        (self.chunks.x1..self.chunks.x2).contains(&chunk.0)
+            && (self.chunks.y1..self.chunks.y2).contains(&chunk.1)
+    }
+
+    pub fn contains_pixel(&self, world_x: i64, world_y: i64) -> bool {
+        let px = self.pixels();
+        (px.x1..px.x2).contains(&world_x) && (px.y1..px.y2).contains(&world_y)
+    }
+
+    /// The area inside the border, in world pixel coordinates.
+    pub fn pixels(&self) -> Rect<i64> {
+        let (w, h) = (i64::from(CHUNK_WIDTH), i64::from(CHUNK_HEIGHT));
+        Rect::new(
+            i64::from(self.chunks.x1) * w,
+            i64::from(self.chunks.y1) * h,
+            i64::from(self.chunks.x2) * w,
+            i64::from(self.chunks.y2) * h,
+        )
+    }
+
+    /// What the world is made of at `world_y`, if that's outside the border.
+    pub fn outside_at(&self, world_y: i64) -> &MaterialInstance {
+        let px = self.pixels();
+        match (&self.above, &self.below) {
+            (Some(above), _) if world_y < px.y1 => above,
+            (_, Some(below)) if world_y >= px.y2 => below,
+            _ => &self.outside,
+        }
+    }
+
+    /// Fills a chunk outside the border with [`Self::outside`] (or [`Self::above`] or [`Self::below`]), in place of generating it.
+    pub fn fill_chunk(&self, chunk: &mut impl Chunk) {
+        let fill = self
+            .outside_at(i64::from(chunk.chunk_y()) * i64::from(CHUNK_HEIGHT))
+            .clone();
+        chunk.set_pixels(vec![fill.clone(); CHUNK_AREA].try_into().unwrap());
+        chunk.set_pixel_colors(vec![fill.color; CHUNK_AREA].try_into().unwrap());
+        chunk.set_state(ChunkState::Cached);
+        chunk.mark_dirty();
+        let _: Result<(), _> = chunk.generate_mesh();
+    }
+
+    /// Moves an entity back inside the border if its hitbox crossed it, stopping it from moving further out.
+    pub fn clamp_entity(&self, pos: &mut Position, vel: &mut Velocity, hitbox: &Hitbox) {
+        let px = self.pixels();
+        clamp_axis(
+            &mut pos.x,
+            &mut vel.x,
+            px.x1 as f64 - f64::from(hitbox.x1),
+            px.x2 as f64 - f64::from(hitbox.x2),
+        );
+        clamp_axis(
+            &mut pos.y,
+            &mut vel.y,
+            px.y1 as f64 - f64::from(hitbox.y1),
+            px.y2 as f64 - f64::from(hitbox.y2),
+        );
+    }
+
+    /// Moves a camera so a view of `size` world pixels around it stays inside the border.
+    ///
+    /// If the border is smaller than the view, the camera is centered on it instead.
+    pub fn clamp_view(&self, pos: &mut Position, size: (f64, f64)) {
+        let px = self.pixels();
+        let (half_w, half_h) = (size.0 / 2.0, size.1 / 2.0);
+        let clamp = |v: f64, min: f64, max: f64| {
+            if min > max {
+                (min + max) / 2.0
+            } else {
+                v.clamp(min, max)
+            }
+        };
+
+        pos.x = clamp(pos.x, px.x1 as f64 + half_w, px.x2 as f64 - half_w);
+        pos.y = clamp(pos.y, px.y1 as f64 + half_h, px.y2 as f64 - half_h);
+    }
+}
+
+fn clamp_axis(pos: &mut f64, vel: &mut f64, min: f64, max: f64) {
+    if *pos < min {
+        *pos = min;
+        *vel = vel.max(0.0);
+    } else if *pos > max {
+        *pos = max;
+        *vel = vel.min(0.0);
+    }
+}
+
+/// Chunk collections that might be limited by a [`WorldBorder`].
+pub trait WorldBounds {
+    fn border(&self) -> Option<&WorldBorder> {
+        None
+    }
+}
+
+impl<D> WorldBounds for ChunkManager<D> {}
+
+impl<D> WorldBounds for ChunkQueryOne<'_, D> {}
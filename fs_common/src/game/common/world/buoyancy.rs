@@ -0,0 +1,120 @@
+use rapier2d::{
+    na::{Point2, Vector2},
+    prelude::RigidBody,
+};
+
+use super::{
+    chunk_access::FSChunkAccess,
+    material::PhysicsType,
+    physics::{Physics, PHYSICS_SCALE},
+    rigidbody::FSRigidBody,
+};
+
+/// How dense liquid pixels are, compared to the density of a rigidbody's colliders (1 by default).
+///
+/// Bodies less dense than this float with that fraction of them under the surface, denser ones sink.
+pub const LIQUID_DENSITY: f32 = 0.8;
+
+/// How much of a fully submerged body's velocity the liquid takes away per second.
+pub const LIQUID_DRAG: f32 = 3.0;
+
+/// The part of a rigidbody that is in liquid, see [`submersion`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Submersion {
+    /// How many of the body's solid pixels are on liquid pixels.
+    pub submerged: u32,
+    /// How many solid pixels the body has.
+    pub total: u32,
+    /// The middle of the submerged pixels, in world pixels.
+    pub center: (f32, f32),
+}
+
+impl Submersion {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.submerged as f32 / self.total as f32
+        }
+    }
+}
+
+/// Samples the world under each of `rb`'s solid pixels to find how much of it is in liquid.
+pub fn submersion(rb: &FSRigidBody, physics: &Physics, chunks: &impl FSChunkAccess) -> Submersion {
+    let mut res = Submersion { submerged: 0, total: 0, center: (0.0, 0.0) };
+    let Some(body) = rb.get_body(physics) else {
+        return res;
+    };
+
+    let (s, c) = body.rotation().angle().sin_cos();
+    let pos_x = body.translation().x * PHYSICS_SCALE;
+    let pos_y = body.translation().y * PHYSICS_SCALE;
+
+    let (mut sum_x, mut sum_y) = (0.0, 0.0);
+    for rb_y in 0..rb.height {
+        for rb_x in 0..rb.width {
+            let px = &rb.pixels[rb_x as usize + rb_y as usize * rb.width as usize];
+            if px.physics != PhysicsType::Solid {
+                continue;
+            }
+            res.total += 1;
+
+            // middle of the pixel
+            let (lx, ly) = (f32::from(rb_x) + 0.5, f32::from(rb_y) + 0.5);
+            let tx = lx * c - ly * s + pos_x;
+            let ty = lx * s + ly * c + pos_y;
+
+            let in_liquid = chunks
+                .pixel(tx.floor() as i64, ty.floor() as i64)
+                .map_or(false, |m| m.physics == PhysicsType::Liquid);
+            if in_liquid {
+                res.submerged += 1;
+                sum_x += tx;
+                sum_y += ty;
+            }
+        }
+    }
+
+    if res.submerged > 0 {
+        res.center = (sum_x / res.submerged as f32, sum_y / res.submerged as f32);
+    }
+
+    res
+}
+
+/// Pushes rigidbodies in liquid up against [`Physics::gravity`] and slows them down.
+///
+/// The upwards force is the weight of the liquid the submerged pixels displace, applied at their
+///   middle so bodies right themselves. It stays on the body for the whole tick, so this
+///   replaces whatever force was left from the last one.
+///
+/// Sleeping bodies are skipped, so anything resting on the bottom stays asleep.
+pub fn apply_buoyancy(
+    rigidbodies: &[FSRigidBody],
+    physics: &mut Physics,
+    chunks: &impl FSChunkAccess,
+    time_step: f32,
+) {
+    let gravity = physics.gravity;
+    for rb in rigidbodies {
+        if rb.get_body(physics).map_or(true, RigidBody::is_sleeping) {
+            continue;
+        }
+
+        let sub = submersion(rb, physics, chunks);
+        let body = rb.get_body_mut(physics).unwrap();
+        body.reset_forces(false);
+        if sub.submerged == 0 {
+            continue;
+        }
+
+        let area = sub.submerged as f32 / (PHYSICS_SCALE * PHYSICS_SCALE);
+        let force: Vector2<f32> = -gravity * LIQUID_DENSITY * area;
+        let point = Point2::new(sub.center.0 / PHYSICS_SCALE, sub.center.1 / PHYSICS_SCALE);
+        body.add_force_at_point(force, point, true);
+
+        let keep = (1.0 - LIQUID_DRAG * sub.fraction() * time_step).max(0.0);
+        body.set_linvel(body.linvel() * keep, true);
+        body.set_angvel(body.angvel() * keep, true);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::game::common::Rect;
 
 use std::convert::TryInto;
+use std::time::Duration;
 
 use chunksystem::ChunkKey;
 use rapier2d::prelude::{Collider, RigidBody, RigidBodyHandle};
@@ -8,16 +9,24 @@ use std::fmt::Debug;
 
 use super::chunk_data::SidedChunkData;
 use super::chunk_index::ChunkLocalPosition;
+use super::flow::FlowBuffer;
 use super::material::color::Color;
 use super::mesh::Mesh;
 use super::tile_entity::{TileEntity, TileEntityCommon};
 use crate::game::common::world::material::MaterialInstance;
 
-pub const CHUNK_SIZE: u16 = 100;
-pub const CHUNK_AREA: usize = CHUNK_SIZE as usize * CHUNK_SIZE as usize;
-// must be a factor of CHUNK_SIZE
-// also (CHUNK_SIZE / LIGHT_SCALE)^2 must be <= 1024 for compute shader (and local_size needs to be set to CHUNK_SIZE / LIGHT_SCALE in the shader)
+/// Width of a chunk in pixels.
+pub const CHUNK_WIDTH: u16 = 100;
+/// Height of a chunk in pixels. Doesn't have to be the same as [`CHUNK_WIDTH`].
+pub const CHUNK_HEIGHT: u16 = 100;
+pub const CHUNK_AREA: usize = CHUNK_WIDTH as usize * CHUNK_HEIGHT as usize;
+// must be a factor of CHUNK_WIDTH and CHUNK_HEIGHT
+// also LIGHT_WIDTH * LIGHT_HEIGHT must be <= 1024 for the compute shaders
 pub const LIGHT_SCALE: u8 = 4;
+/// Width of a chunk's light grid, one cell for each [`LIGHT_SCALE`] pixels.
+pub const LIGHT_WIDTH: u16 = CHUNK_WIDTH / LIGHT_SCALE as u16;
+/// Height of a chunk's light grid, see [`LIGHT_WIDTH`].
+pub const LIGHT_HEIGHT: u16 = CHUNK_HEIGHT / LIGHT_SCALE as u16;
 
 pub trait Chunk {
     fn new_empty(chunk_x: i32, chunk_y: i32) -> Self
@@ -47,6 +56,12 @@ pub trait Chunk {
     fn set_background_pixel_colors(&mut self, colors: Box<[Color; CHUNK_AREA]>);
     fn background_colors_mut(&mut self) -> &mut [Color; CHUNK_AREA];
     fn background_colors(&self) -> &[Color; CHUNK_AREA];
+    fn flow_mut(&mut self) -> &mut Option<Box<FlowBuffer>>;
+    fn flow(&self) -> &Option<Box<FlowBuffer>>;
+    fn sim_time(&self) -> Option<Duration>;
+    fn set_sim_time(&mut self, time: Option<Duration>);
+    fn revealed(&self) -> bool;
+    fn set_revealed(&mut self, revealed: bool);
 
     fn generate_mesh(&mut self) -> Result<(), String>;
     // fn get_tris(&self) -> &Option<Vec<Vec<((f64, f64), (f64, f64), (f64, f64))>>>;
@@ -280,6 +295,25 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn chunk_update_phases() {
+        let keys: Vec<_> = (-5..5).flat_map(|x| (-5..5).map(move |y| (x, y))).collect();
+        let phases = super::chunk_update_phases(keys.iter().rev().copied());
+
+        assert_eq!(phases.iter().map(Vec::len).sum::<usize>(), keys.len());
+
+        for phase in &phases {
+            assert!(phase.windows(2).all(|w| w[0] < w[1]));
+
+            for a in phase {
+                for b in phase {
+                    // chunks in the same phase should never be neighbors
+                    assert!(a == b || (a.0 - b.0).abs() > 1 || (a.1 - b.1).abs() > 1);
+                }
+            }
+        }
+    }
 }
 
 // #[profiling::function]
@@ -287,8 +321,8 @@ mod tests {
 pub const fn pixel_to_chunk_pos(x: i64, y: i64) -> (i32, i32) {
     // div_euclid is the same as div_floor in this case (div_floor is currenlty unstable)
     (
-        x.div_euclid(CHUNK_SIZE as _) as _,
-        y.div_euclid(CHUNK_SIZE as _) as _,
+        x.div_euclid(CHUNK_WIDTH as _) as _,
+        y.div_euclid(CHUNK_HEIGHT as _) as _,
     )
 }
 
@@ -306,10 +340,10 @@ pub const fn pixel_to_pos_in_chunk(world_x: i64, world_y: i64) -> ChunkLocalPosi
     let (chunk_x, chunk_y) = pixel_to_chunk_pos(world_x, world_y);
     unsafe {
         // need to use unchecked for const
-        // Safety: math guarantees x and y are 0..CHUNK_SIZE
+        // Safety: math guarantees x and y are 0..CHUNK_WIDTH and 0..CHUNK_HEIGHT
         ChunkLocalPosition::new_unchecked(
-            (world_x - chunk_x as i64 * CHUNK_SIZE as i64) as u16,
-            (world_y - chunk_y as i64 * CHUNK_SIZE as i64) as u16,
+            (world_x - chunk_x as i64 * CHUNK_WIDTH as i64) as u16,
+            (world_y - chunk_y as i64 * CHUNK_HEIGHT as i64) as u16,
         )
     }
 }
@@ -319,10 +353,10 @@ pub const fn pixel_to_chunk(world_x: i64, world_y: i64) -> (ChunkKey, ChunkLocal
     let (chunk_x, chunk_y) = pixel_to_chunk_pos(world_x, world_y);
     ((chunk_x, chunk_y), unsafe {
         // need to use unchecked for const
-        // Safety: math guarantees x and y are 0..CHUNK_SIZE
+        // Safety: math guarantees x and y are 0..CHUNK_WIDTH and 0..CHUNK_HEIGHT
         ChunkLocalPosition::new_unchecked(
-            (world_x - chunk_x as i64 * CHUNK_SIZE as i64) as u16,
-            (world_y - chunk_y as i64 * CHUNK_SIZE as i64) as u16,
+            (world_x - chunk_x as i64 * CHUNK_WIDTH as i64) as u16,
+            (world_y - chunk_y as i64 * CHUNK_HEIGHT as i64) as u16,
         )
     })
 }
@@ -371,3 +405,21 @@ pub const fn chunk_update_order(chunk_x: i32, chunk_y: i32) -> u8 {
 
     yy * 2 + xx
 }
+
+/// Splits chunk keys into the four checkerboard phases from [`chunk_update_order`].
+///
+/// No two chunks in the same phase are adjacent (including diagonally), so every
+///   chunk in a phase can be simulated at the same time.
+/// Keys within a phase are sorted so the result does not depend on the input order.
+pub fn chunk_update_phases(keys: impl IntoIterator<Item = ChunkKey>) -> [Vec<ChunkKey>; 4] {
+    let mut phases = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+    for key in keys {
+        phases[chunk_update_order(key.0, key.1) as usize].push(key);
+    }
+
+    for phase in &mut phases {
+        phase.sort_unstable();
+    }
+
+    phases
+}
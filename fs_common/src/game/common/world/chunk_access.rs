@@ -1,11 +1,12 @@
 use chunksystem::{ChunkKey, ChunkQuery};
 
 use super::{
-    material::{MaterialInstance, PhysicsType},
+    border::WorldBounds,
+    material::{MaterialInstance, MaterialRegistry},
     pixel_to_chunk, pixel_to_chunk_pos, pixel_to_pos_in_chunk, Chunk,
 };
 
-pub trait FSChunkAccess {
+pub trait FSChunkAccess: WorldBounds {
     fn pixel(&self, world_x: i64, world_y: i64) -> Result<&MaterialInstance, String>;
     fn set_pixel(
         &mut self,
@@ -19,7 +20,13 @@ pub trait FSChunkAccess {
         Self: Sized,
         F: FnOnce(&MaterialInstance) -> Option<MaterialInstance>;
 
-    fn displace_pixel(&mut self, world_x: i64, world_y: i64, material: MaterialInstance) -> bool;
+    fn displace_pixel(
+        &mut self,
+        world_x: i64,
+        world_y: i64,
+        material: MaterialInstance,
+        materials: &MaterialRegistry,
+    ) -> bool;
 
     fn chunk_at_dyn(&self, chunk_pos: ChunkKey) -> Option<&dyn Chunk>;
     fn chunk_at_mut_dyn(&mut self, chunk_pos: ChunkKey) -> Option<&mut dyn Chunk>;
@@ -27,13 +34,23 @@ pub trait FSChunkAccess {
     fn is_pixel_loaded(&self, world_x: i64, world_y: i64) -> bool;
 }
 
-impl<Q: ChunkQuery> FSChunkAccess for Q
+pub(super) fn outside_border(q: &impl WorldBounds, world_x: i64, world_y: i64) -> bool {
+    q.border()
+        .map_or(false, |b| !b.contains_pixel(world_x, world_y))
+}
+
+impl<Q: ChunkQuery + WorldBounds> FSChunkAccess for Q
 where
     Q::D: Chunk,
 {
     #[inline]
     fn pixel(&self, world_x: i64, world_y: i64) -> Result<&MaterialInstance, String> {
         let Some(ch) = self.chunk_at(pixel_to_chunk_pos(world_x, world_y)) else {
+            if let Some(border) = self.border() {
+                if !border.contains_pixel(world_x, world_y) {
+                    return Ok(border.outside_at(world_y));
+                }
+            }
             return Err("Position is not loaded".into());
         };
 
@@ -48,6 +65,10 @@ where
         world_y: i64,
         mat: MaterialInstance,
     ) -> Result<(), String> {
+        if outside_border(self, world_x, world_y) {
+            return Err("Position is outside the world border".into());
+        }
+
         let Some(ch) = self.chunk_at_mut(pixel_to_chunk_pos(world_x, world_y)) else {
             return Err("Position is not loaded".into());
         };
@@ -63,6 +84,10 @@ where
         Self: Sized,
         F: FnOnce(&MaterialInstance) -> Option<MaterialInstance>,
     {
+        if outside_border(self, world_x, world_y) {
+            return Err("Position is outside the world border".into());
+        }
+
         let (chunk_pos, local) = pixel_to_chunk(world_x, world_y);
         let Some(ch) = self.chunk_at_mut(chunk_pos) else {
             return Err("Position is not loaded".into());
@@ -87,7 +112,13 @@ where
     }
 
     #[profiling::function]
-    fn displace_pixel(&mut self, world_x: i64, world_y: i64, material: MaterialInstance) -> bool {
+    fn displace_pixel(
+        &mut self,
+        world_x: i64,
+        world_y: i64,
+        material: MaterialInstance,
+        materials: &MaterialRegistry,
+    ) -> bool {
         let mut succeeded = false;
 
         let scan_w = 32;
@@ -107,7 +138,7 @@ where
                 if let Ok(true) = self.replace_pixel(
                     world_x + i64::from(scan_x),
                     world_y + i64::from(scan_y),
-                    |scan_mat| (scan_mat.physics == PhysicsType::Air).then_some(material.clone()),
+                    |scan_mat| scan_mat.is_empty(materials).then_some(material.clone()),
                 ) {
                     succeeded = true;
                     break;
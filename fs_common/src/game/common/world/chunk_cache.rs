@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use chunksystem::ChunkKey;
+
+/// Tracks chunks that left every loader's unload zone but are kept in memory anyway,
+///   see [`ChunkUnloadPolicy::Cache`](crate::game::common::ChunkUnloadPolicy::Cache).
+///
+/// Once there are too many, the ones that have been out of range the longest are
+///   evicted (saved and actually unloaded) first.
+#[derive(Debug, Default)]
+pub struct ChunkCache {
+    last_used: HashMap<ChunkKey, u64>,
+    counter: u64,
+}
+
+impl ChunkCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk that just went out of range.
+    ///
+    /// Chunks that are already cached keep their place, so staying out of range
+    ///   doesn't count as being used.
+    pub fn insert(&mut self, key: ChunkKey) {
+        if !self.last_used.contains_key(&key) {
+            self.counter += 1;
+            self.last_used.insert(key, self.counter);
+        }
+    }
+
+    /// Forgets a chunk, because it came back into range or was unloaded some other way.
+    pub fn remove(&mut self, key: ChunkKey) -> bool {
+        self.last_used.remove(&key).is_some()
+    }
+
+    pub fn contains(&self, key: ChunkKey) -> bool {
+        self.last_used.contains_key(&key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.last_used.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.last_used.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.last_used.clear();
+    }
+
+    /// Removes the least recently used chunks until at most `cap` are left, and returns them
+    ///   (least recent first) so they can be saved and unloaded.
+    pub fn evict(&mut self, cap: usize) -> Vec<ChunkKey> {
+        let mut evicted = vec![];
+        while self.last_used.len() > cap {
+            let Some((&key, _)) = self.last_used.iter().min_by_key(|&(_, used)| *used) else {
+                break;
+            };
+            self.last_used.remove(&key);
+            evicted.push(key);
+        }
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChunkCache;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = ChunkCache::new();
+        cache.insert((0, 0));
+        cache.insert((1, 0));
+        cache.insert((2, 0));
+        assert!(cache.evict(3).is_empty());
+
+        // (0, 0) came back into range and left again, so it's now the most recent
+        assert!(cache.remove((0, 0)));
+        cache.insert((0, 0));
+        // staying out of range doesn't refresh (1, 0)
+        cache.insert((1, 0));
+        cache.insert((3, 0));
+
+        assert_eq!(cache.evict(2), vec![(1, 0), (2, 0)]);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains((0, 0)));
+        assert!(cache.contains((3, 0)));
+
+        assert_eq!(cache.evict(0), vec![(0, 0), (3, 0)]);
+        assert!(cache.is_empty());
+    }
+}
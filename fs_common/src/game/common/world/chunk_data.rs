@@ -1,8 +1,11 @@
+use std::time::Duration;
+
 use crate::game::common::Rect;
 
 use super::{
-    chunk_index::ChunkLocalIndex, material::MaterialInstance, mesh::Mesh, tile_entity::TileEntity,
-    ChunkRigidBodyState, ChunkState, CHUNK_AREA, CHUNK_SIZE,
+    chunk_index::ChunkLocalIndex, flow::FlowBuffer, material::MaterialInstance, mesh::Mesh,
+    tile_entity::TileEntity, ChunkRigidBodyState, ChunkState, CHUNK_AREA, CHUNK_HEIGHT,
+    CHUNK_WIDTH,
 };
 
 pub struct CommonChunkData<S: SidedChunkData> {
@@ -12,6 +15,12 @@ pub struct CommonChunkData<S: SidedChunkData> {
     pub pixels: Option<Box<[MaterialInstance; CHUNK_AREA]>>,
     pub light: Option<Box<[[f32; 3]; CHUNK_AREA]>>,
     pub background: Option<Box<[MaterialInstance; CHUNK_AREA]>>,
+    /// Only kept while the flow overlay is on, see [`super::flow`].
+    pub flow: Option<Box<FlowBuffer>>,
+    /// How long this chunk's last simulation took, only measured while the sim time overlay is on, see [`super::sim_time`].
+    pub sim_time: Option<Duration>,
+    /// Whether a loader's active zone has ever reached this chunk, see `ChunkHandler::reveal_chunks`.
+    pub revealed: bool,
     pub dirty_rect: Option<Rect<i32>>,
     pub rigidbody: Option<ChunkRigidBodyState>,
     pub mesh_simplified: Option<Mesh>,
@@ -31,6 +40,9 @@ impl<S: SidedChunkData> CommonChunkData<S> {
             pixels: None,
             light: None,
             background: None,
+            flow: None,
+            sim_time: None,
+            revealed: false,
             dirty_rect: None,
             rigidbody: None,
             mesh_simplified: None,
@@ -49,7 +61,7 @@ impl<S: SidedChunkData> CommonChunkData<S> {
 
             px[pos.into()] = mat;
 
-            self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE));
+            self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT));
 
             return Ok(());
         }
@@ -62,7 +74,7 @@ impl<S: SidedChunkData> CommonChunkData<S> {
     pub unsafe fn set_unchecked(&mut self, pos: impl Into<ChunkLocalIndex>, mat: MaterialInstance) {
         self.pixels.as_mut().unwrap_unchecked()[pos.into()] = mat;
 
-        self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE));
+        self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT));
     }
 
     pub fn pixel(&self, pos: impl Into<ChunkLocalIndex>) -> Result<&MaterialInstance, String> {
@@ -96,7 +108,7 @@ impl<S: SidedChunkData> CommonChunkData<S> {
                 (chunk_cb)(&mat)?;
                 *px = mat;
 
-                self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE));
+                self.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT));
 
                 return Ok(true);
             }
@@ -118,7 +130,7 @@ impl<S: SidedChunkData> CommonChunkData<S> {
 
             li[pos.into()] = light;
 
-            // self.data.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE));
+            // self.data.dirty_rect = Some(Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT));
 
             Ok(())
         } else {
@@ -1,33 +1,48 @@
-use std::{cell::UnsafeCell, fmt::Debug, path::PathBuf, sync::Arc};
+use std::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use asefile::AsepriteFile;
 use chunksystem::{ChunkKey, ChunkManager, ChunkQuery};
 use futures::channel::oneshot::Receiver;
-use rand::{rngs::StdRng, SeedableRng};
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 use specs::{Join, ReadStorage, RunNow, WorldExt};
 
 use crate::game::common::{
     hashmap_ext::HashMapExt,
+    registry::RegistryID,
     world::{
-        chunk_index, chunk_update_order,
-        gen::{populator::ChunkContext, structure::UpdateStructureNodes, GenBuffers, GenContext},
+        chunk_update_phases,
+        gen::{budget::GenBudget, structure::UpdateStructureNodes},
         material::buf::MaterialRect,
         particle::{Particle, ParticleSystem},
         pixel_to_chunk_pos,
-        simulator::{Simulator, SimulatorChunkContext},
+        simulator::{LightSettings, SimRules, Simulator, SimulatorChunkContext, SunLight},
         tile_entity::{TileEntityCommon, TileEntityTickContext},
-        ChunkState, Loader, Position, CHUNK_SIZE,
+        ChunkState, Loader, Position, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
-    FileHelper, Rect, Registries, Settings,
+    ChunkUnloadPolicy, FileHelper, Rect, Registries, Settings,
 };
 
 use super::{
+    border::{WorldBorder, WorldBounds},
+    chunk_access::FSChunkAccess,
+    chunk_cache::ChunkCache,
     chunk_data::SidedChunkData,
+    edit::{self, Edit, EditHistory},
+    flow::{self, FlowBuffer},
     gen::WorldGenerator,
-    material::{color::Color, MaterialInstance},
+    journal::EditJournal,
+    material::{color::Color, Material, MaterialInstance, MaterialRegistry, PhysicsType},
     physics::Physics,
+    settle_events::{SettleEvent, SettleEvents},
+    sim_budget::SimBudget,
+    thread_pool::SizedThreadPool,
     tile_entity::TileEntitySided,
     Chunk, ChunkRigidBodyState, SidedChunk, CHUNK_AREA,
 };
@@ -37,10 +52,24 @@ pub struct ChunkHandler<C: Chunk> {
     pub load_queue: Vec<(i32, i32)>,
     pub gen_pool: rayon::ThreadPool,
     pub gen_threads: Vec<(ChunkKey, Receiver<ChunkGenOutput>)>,
+    /// Out of range chunks kept loaded by [`ChunkUnloadPolicy::Cache`].
+    pub cache: ChunkCache,
+    /// Runs chunk simulation when [`Settings::simulate_chunks_parallel`] is on.
+    pub sim_pool: SizedThreadPool,
+    /// Chunks put off by [`Settings::sim_pixel_budget`], see [`SimBudget`].
+    pub sim_budget: SimBudget,
     /** The size of the "presentable" area (not necessarily the current window size) */
     pub screen_size: (u16, u16),
     pub generator: Arc<dyn WorldGenerator<C>>,
     pub path: Option<PathBuf>,
+    pub edit_history: EditHistory,
+    pub journal: EditJournal,
+    /// How far past `screen_size` each loader's [`Zones`] reach.
+    pub zone_radii: ZoneRadii,
+    /// If set, the world doesn't extend past this border.
+    pub border: Option<WorldBorder>,
+    /// Whether liquids freeze over, see [`WorldRules::freezing`](super::world_loading::WorldRules::freezing).
+    pub freezing: bool,
 }
 
 impl<C: Chunk> Debug for ChunkHandler<C> {
@@ -49,8 +78,13 @@ impl<C: Chunk> Debug for ChunkHandler<C> {
             .field("load_queue", &self.load_queue)
             .field("gen_pool", &self.gen_pool)
             .field("gen_threads", &self.gen_threads)
+            .field("cache", &self.cache)
+            .field("sim_pool", &self.sim_pool)
+            .field("sim_budget", &self.sim_budget)
             .field("screen_size", &self.screen_size)
+            .field("zone_radii", &self.zone_radii)
             .field("path", &self.path)
+            .field("border", &self.border)
             .finish()
     }
 }
@@ -64,10 +98,90 @@ pub type ChunkGenOutput = (
     Box<[Color; CHUNK_AREA]>,
 );
 
+/// Bumped whenever [`ChunkSaveFormat`] changes.
+const CHUNK_SAVE_VERSION: u32 = 2;
+
 #[derive(Serialize, Deserialize)]
 struct ChunkSaveFormat {
+    version: u32,
+    pixels: Vec<MaterialInstance>,
+    colors: Vec<Color>,
+    /// Since version 1, see [`Chunk::lights`]. Empty if the save is older.
+    lights: Vec<[f32; 4]>,
+    /// Since version 2, see [`Chunk::revealed`].
+    /// Older saves count as revealed, since they were explored before there was fog.
+    revealed: bool,
+}
+
+/// What [`ChunkSaveFormat`] was before it had a version.
+#[derive(Deserialize)]
+struct ChunkSaveFormatV0 {
+    pixels: Vec<MaterialInstanceV0>,
+    colors: Vec<Color>,
+}
+
+/// What [`MaterialInstance`] was in [`ChunkSaveFormatV0`], before pixels had moisture or a fall count.
+#[derive(Deserialize)]
+pub(super) struct MaterialInstanceV0 {
+    material_id: RegistryID<Material>,
+    physics: PhysicsType,
+    color: Color,
+    light: [f32; 3],
+}
+
+impl From<MaterialInstanceV0> for MaterialInstance {
+    fn from(old: MaterialInstanceV0) -> Self {
+        Self {
+            material_id: old.material_id,
+            physics: old.physics,
+            color: old.color,
+            light: old.light,
+            moisture: 0.0,
+            fall: 0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ChunkSaveFormatV1 {
+    _version: u32,
     pixels: Vec<MaterialInstance>,
     colors: Vec<Color>,
+    lights: Vec<[f32; 4]>,
+}
+
+impl ChunkSaveFormat {
+    /// Reads a chunk saved with any version of the format.
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let version: u32 = bincode::deserialize(data).map_err(|e| e.to_string())?;
+        match version {
+            CHUNK_SAVE_VERSION => bincode::deserialize(data).map_err(|e| e.to_string()),
+            1 => {
+                let old: ChunkSaveFormatV1 =
+                    bincode::deserialize(data).map_err(|e| e.to_string())?;
+                Ok(Self {
+                    version: CHUNK_SAVE_VERSION,
+                    pixels: old.pixels,
+                    colors: old.colors,
+                    lights: old.lights,
+                    revealed: true,
+                })
+            },
+            // unversioned saves start with the length of `pixels` instead
+            v if v as usize == CHUNK_AREA => {
+                let old: ChunkSaveFormatV0 =
+                    bincode::deserialize(data).map_err(|e| e.to_string())?;
+                Ok(Self {
+                    version: CHUNK_SAVE_VERSION,
+                    pixels: old.pixels.into_iter().map(MaterialInstance::from).collect(),
+                    colors: old.colors,
+                    lights: Vec::new(),
+                    revealed: true,
+                })
+            },
+            v => Err(format!("unknown chunk save version {v}")),
+        }
+    }
 }
 
 pub struct ChunkTickContext<'a> {
@@ -80,12 +194,47 @@ pub struct ChunkTickContext<'a> {
     pub file_helper: &'a FileHelper,
 }
 
-struct Zones {
-    unload: Rect<i32>,
-    load: Rect<i32>,
-    active: Rect<i32>,
-    #[allow(dead_code)]
-    screen: Rect<i32>,
+/// How many pixels past the screen size each of a loader's [`Zones`] reaches on every side.
+///
+/// These (with [`ChunkHandler::screen_size`]) are all that decide which chunks get loaded and
+///   simulated, so a server without a renderer can pick its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZoneRadii {
+    pub active: u16,
+    pub load: u16,
+    pub unload: u16,
+}
+
+impl ZoneRadii {
+    /// Each zone has to contain the one before it, otherwise chunks would be unloaded as soon as
+    ///   they're loaded.
+    pub fn new(active: u16, load: u16, unload: u16) -> Result<Self, String> {
+        if active > load || load > unload {
+            return Err(format!(
+                "Zone radii must satisfy active <= load <= unload, got {active}, {load}, {unload}."
+            ));
+        }
+        Ok(Self { active, load, unload })
+    }
+}
+
+impl Default for ZoneRadii {
+    fn default() -> Self {
+        Self {
+            active: CHUNK_WIDTH.max(CHUNK_HEIGHT),
+            load: CHUNK_WIDTH.max(CHUNK_HEIGHT) * 10,
+            unload: CHUNK_WIDTH.max(CHUNK_HEIGHT) * 15,
+        }
+    }
+}
+
+/// The areas around a [`Loader`] where chunks are kept loaded, get loaded, and get simulated.
+#[derive(Debug, Clone, Copy)]
+pub struct Zones {
+    pub unload: Rect<i32>,
+    pub load: Rect<i32>,
+    pub active: Rect<i32>,
+    pub screen: Rect<i32>,
 }
 
 impl<C: Chunk + SidedChunk + Send + Sync + 'static> ChunkHandler<C>
@@ -104,7 +253,8 @@ where
     pub fn tick(&mut self, mut ctx: ChunkTickContext) {
         profiling::scope!("tick");
 
-        let loader_zones = self.calc_zones(ctx.world);
+        let loader_zones = self.loader_zones(ctx.world);
+        self.reveal_chunks(&loader_zones);
 
         if ctx.settings.load_chunks {
             self.queue_chunk_loading(&loader_zones);
@@ -133,36 +283,36 @@ where
 
             // unloading NotGenerated or Generating chunks
             // populate chunks
-            self.populate_chunks_and_check_unload_generating(&mut ctx, &loader_zones, num_active);
+            let mut budget = GenBudget::from_ms(ctx.settings.chunk_gen_budget_ms);
+            self.populate_chunks_and_check_unload_generating(
+                &mut ctx,
+                &loader_zones,
+                num_active,
+                &mut budget,
+            );
         }
 
         if ctx.settings.simulate_chunks {
-            self.simulate_chunks(&mut ctx);
+            if let Err(e) = self.sim_pool.resize(ctx.settings.simulation_threads) {
+                log::error!("{e}");
+            }
+
+            let mut budget =
+                (ctx.settings.sim_pixel_budget > 0).then_some(ctx.settings.sim_pixel_budget);
+            // each substep picks up the dirty rects left by the previous one
+            for substep in 0..ctx.settings.simulate_chunks_substeps.max(1) {
+                self.simulate_chunks(&mut ctx, substep, &mut budget);
+            }
         }
 
         self.tick_tile_entities(&mut ctx);
     }
 
-    fn calc_zones(&self, world: &specs::World) -> Vec<Zones> {
-        let (loaders, positions) =
-            world.system_data::<(ReadStorage<Loader>, ReadStorage<Position>)>();
-
-        (&loaders, &positions)
-            .join()
-            .map(|(_, pos)| Zones {
-                unload: self.get_unload_zone((pos.x, pos.y)),
-                load: self.get_load_zone((pos.x, pos.y)),
-                active: self.get_active_zone((pos.x, pos.y)),
-                screen: self.get_screen_zone((pos.x, pos.y)),
-            })
-            .collect()
-    }
-
     fn queue_chunk_loading(&mut self, loader_zones: &[Zones]) {
         profiling::scope!("queue_chunk_loading");
         for zones in loader_zones {
-            for px in zones.load.range_lr().step_by(CHUNK_SIZE.into()) {
-                for py in zones.load.range_tb().step_by(CHUNK_SIZE.into()) {
+            for px in zones.load.range_lr().step_by(CHUNK_WIDTH.into()) {
+                for py in zones.load.range_tb().step_by(CHUNK_HEIGHT.into()) {
                     let chunk_pos = pixel_to_chunk_pos(px.into(), py.into());
                     self.queue_load_chunk(chunk_pos.0, chunk_pos.1);
                 }
@@ -178,10 +328,10 @@ where
             .system_data::<(ReadStorage<Loader>, ReadStorage<Position>)>();
 
         self.load_queue.sort_by(|(a_x, a_y), (b_x, b_y)| {
-            let c1_x = a_x * i32::from(CHUNK_SIZE);
-            let c1_y = a_y * i32::from(CHUNK_SIZE);
-            let c2_x = b_x * i32::from(CHUNK_SIZE);
-            let c2_y = b_y * i32::from(CHUNK_SIZE);
+            let c1_x = a_x * i32::from(CHUNK_WIDTH);
+            let c1_y = a_y * i32::from(CHUNK_HEIGHT);
+            let c2_x = b_x * i32::from(CHUNK_WIDTH);
+            let c2_y = b_y * i32::from(CHUNK_HEIGHT);
 
             let d1 = (&loaders, &positions)
                 .join()
@@ -223,6 +373,24 @@ where
         }
     }
 
+    /// Marks every loaded chunk any loader's active zone reaches as revealed, for the fog overlay.
+    fn reveal_chunks(&mut self, loader_zones: &[Zones]) {
+        for (key, chunk) in self.manager.kv_iter_mut() {
+            if chunk.revealed() {
+                continue;
+            }
+            let rect = Rect::new_wh(
+                key.0 * i32::from(CHUNK_WIDTH),
+                key.1 * i32::from(CHUNK_HEIGHT),
+                CHUNK_WIDTH,
+                CHUNK_HEIGHT,
+            );
+            if loader_zones.iter().any(|z| rect.intersects(&z.active)) {
+                chunk.set_revealed(true);
+            }
+        }
+    }
+
     fn update_active(&mut self, ctx: &mut ChunkTickContext, loader_zones: &[Zones]) {
         profiling::scope!("chunk update A");
 
@@ -233,22 +401,29 @@ where
 
             let state = self.manager.chunk_at(key).unwrap().state(); // copy
             let rect = Rect::new_wh(
-                self.manager.chunk_at(key).unwrap().chunk_x() * i32::from(CHUNK_SIZE),
-                self.manager.chunk_at(key).unwrap().chunk_y() * i32::from(CHUNK_SIZE),
-                CHUNK_SIZE,
-                CHUNK_SIZE,
+                self.manager.chunk_at(key).unwrap().chunk_x() * i32::from(CHUNK_WIDTH),
+                self.manager.chunk_at(key).unwrap().chunk_y() * i32::from(CHUNK_HEIGHT),
+                CHUNK_WIDTH,
+                CHUNK_HEIGHT,
             );
+            let in_range = loader_zones.iter().any(|z| rect.intersects(&z.unload));
+            if in_range {
+                // came back before being evicted, so it was never unloaded
+                self.cache.remove(key);
+            }
 
             match state {
                 ChunkState::Cached => {
-                    if !loader_zones.iter().any(|z| rect.intersects(&z.unload)) {
-                        if let Err(e) = self.save_chunk(key) {
-                            log::error!("Chunk @ {}, {} failed to save: {:?}", key.0, key.1, e);
-                        }
-                        if let Err(e) = self.unload_chunk(key, ctx.physics) {
-                            log::error!("Chunk @ {}, {} failed to unload: {:?}", key.0, key.1, e);
+                    if !in_range {
+                        match ctx.settings.chunk_unload_policy {
+                            ChunkUnloadPolicy::Discard => {
+                                self.cache.remove(key);
+                                self.save_and_unload_chunk(key, ctx.physics);
+                                keep_map[i] = false;
+                            },
+                            // stays loaded until it's evicted below
+                            ChunkUnloadPolicy::Cache => self.cache.insert(key),
                         }
-                        keep_map[i] = false;
                     } else if loader_zones.iter().any(|z| rect.intersects(&z.active)) {
                         let (chunk_x, chunk_y) = key;
                         if [
@@ -276,10 +451,9 @@ where
                                 .chunk_at_mut(key)
                                 .unwrap()
                                 .set_state(ChunkState::Active);
-                            self.manager
-                                .chunk_at_mut(key)
-                                .unwrap()
-                                .set_dirty_rect(Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE)));
+                            self.manager.chunk_at_mut(key).unwrap().set_dirty_rect(Some(
+                                Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT),
+                            ));
                         }
                     }
                 },
@@ -295,6 +469,17 @@ where
             }
         }
 
+        let cap = match ctx.settings.chunk_unload_policy {
+            ChunkUnloadPolicy::Discard => 0,
+            ChunkUnloadPolicy::Cache => ctx.settings.chunk_cache_cap,
+        };
+        for key in self.cache.evict(cap) {
+            if let Some(i) = keys.iter().position(|k| *k == key) {
+                self.save_and_unload_chunk(key, ctx.physics);
+                keep_map[i] = false;
+            }
+        }
+
         if ctx.settings.load_chunks {
             let mut iter = keep_map.iter();
             unsafe { self.manager.raw_mut() }.retain(|_, _| *iter.next().unwrap());
@@ -331,10 +516,10 @@ where
             keys.sort_by(|a, b| {
                 let a = self.manager.chunk_at(*a).unwrap();
                 let b = self.manager.chunk_at(*b).unwrap();
-                let c1_x = a.chunk_x() * i32::from(CHUNK_SIZE);
-                let c1_y = a.chunk_y() * i32::from(CHUNK_SIZE);
-                let c2_x = b.chunk_x() * i32::from(CHUNK_SIZE);
-                let c2_y = b.chunk_y() * i32::from(CHUNK_SIZE);
+                let c1_x = a.chunk_x() * i32::from(CHUNK_WIDTH);
+                let c1_y = a.chunk_y() * i32::from(CHUNK_HEIGHT);
+                let c2_x = b.chunk_x() * i32::from(CHUNK_WIDTH);
+                let c2_y = b.chunk_y() * i32::from(CHUNK_HEIGHT);
 
                 let d1 = (&loaders, &positions)
                     .join()
@@ -364,114 +549,55 @@ where
 
         // list of chunks that need to be generated
         // u32 is key, i32s are chunk x and y
-        let to_generate = keys.iter().filter_map(|key| {
-            let rect = Rect::new_wh(
-                self.manager.chunk_at(*key).unwrap().chunk_x() * i32::from(CHUNK_SIZE),
-                self.manager.chunk_at(*key).unwrap().chunk_y() * i32::from(CHUNK_SIZE),
-                CHUNK_SIZE,
-                CHUNK_SIZE,
-            );
-
-            // keys are filtered by state == NotGenerated already
-            assert!(self.manager.chunk_at(*key).unwrap().state() == ChunkState::NotGenerated);
-
-            // start generating chunks waiting to generate
-            if loader_zones.iter().any(|z| rect.intersects(&z.unload)) && num_loaded_this_tick < Self::MAX_SPAWN_GENERATE_PER_TICK {
-                let chunk_x = self.manager.chunk_at_mut(*key).unwrap().chunk_x();
-                let chunk_y = self.manager.chunk_at_mut(*key).unwrap().chunk_y();
-
-                let mut should_generate = true;
+        let to_generate = keys
+            .iter()
+            .filter_map(|key| {
+                let rect = Rect::new_wh(
+                    self.manager.chunk_at(*key).unwrap().chunk_x() * i32::from(CHUNK_WIDTH),
+                    self.manager.chunk_at(*key).unwrap().chunk_y() * i32::from(CHUNK_HEIGHT),
+                    CHUNK_WIDTH,
+                    CHUNK_HEIGHT,
+                );
+
+                // keys are filtered by state == NotGenerated already
+                assert!(self.manager.chunk_at(*key).unwrap().state() == ChunkState::NotGenerated);
+
+                // start generating chunks waiting to generate
+                if loader_zones.iter().any(|z| rect.intersects(&z.unload))
+                    && num_loaded_this_tick < Self::MAX_SPAWN_GENERATE_PER_TICK
+                {
+                    let chunk_x = self.manager.chunk_at_mut(*key).unwrap().chunk_x();
+                    let chunk_y = self.manager.chunk_at_mut(*key).unwrap().chunk_y();
+
+                    // chunks outside the world border are filled in instead
+                    if let Some(border) = &self.border {
+                        if !border.contains_chunk(*key) {
+                            border.fill_chunk(&mut self.manager.chunk_at_mut(*key).unwrap().data);
+                            return None;
+                        }
+                    }
 
-                // skip if already generating this chunk
-                if self.gen_threads.iter().any(|(k, _)| k == key) {
-                    should_generate = false;
-                }
+                    let mut should_generate = true;
 
-                // try to load from file
-                if let Some(path) = &self.path {
-                    let chunk_path_root = path.join("chunks/");
-                    if !chunk_path_root.exists() {
-                        std::fs::create_dir_all(&chunk_path_root).expect(
-                            format!(
-                                "Failed to create chunk directory @ {chunk_path_root:?}"
-                            )
-                            .as_str(),
-                        );
+                    // skip if already generating this chunk
+                    if self.gen_threads.iter().any(|(k, _)| k == key) {
+                        should_generate = false;
                     }
-                    let chunk_path =
-                        chunk_path_root.join(format!("{chunk_x}_{chunk_y}.chunk"));
-                    if chunk_path.exists() {
-                        if let Ok(data) = std::fs::read(&chunk_path) {
-                            match bincode::deserialize(&data) {
-                                Ok(res) => {
-                                    let save: ChunkSaveFormat = res;
-
-                                    if save.pixels.len()
-                                        == (CHUNK_AREA)
-                                    {
-                                        let chunk =  self.manager.chunk_at_mut(*key).unwrap();
-                                        chunk.set_state(ChunkState::Cached);
-                                        chunk.set_pixels(save.pixels.try_into().unwrap());
-                                        chunk.mark_dirty();
-                                        let _: Result<(), _> = chunk.generate_mesh();
-
-                                        if save.colors.len()
-                                            == (CHUNK_SIZE as usize
-                                                * CHUNK_SIZE as usize
-                                                * 4)
-                                        {
-                                            chunk.set_pixel_colors(save.colors.try_into().unwrap());
-                                        } else {
-                                            log::error!("colors Vec is the wrong size: {} (expected {})", save.colors.len(), CHUNK_AREA * 4);
-                                            chunk.refresh();
-                                        }
-
-                                        should_generate = false;
-                                    } else {
-                                        log::error!("pixels Vec is the wrong size: {} (expected {})", save.pixels.len(), CHUNK_AREA);
-                                        self.manager
-                                            .chunk_at_mut(*key)
-                                            .unwrap()
-                                            .set_state(ChunkState::Cached);
-                                    }
-                                },
-                                Err(e) => {
-                                    log::error!(
-                                        "Chunk parse failed @ {},{} -> {:?}: {:?}",
-                                        chunk_x,
-                                        chunk_y,
-                                        chunk_path,
-                                        e
-                                    );
-                                    self.manager
-                                        .chunk_at_mut(*key)
-                                        .unwrap()
-                                        .set_state(ChunkState::Cached);
-                                },
-                            }
-                        } else {
-                            log::error!(
-                                "Chunk load failed @ {},{} -> {:?}",
-                                chunk_x,
-                                chunk_y,
-                                chunk_path
-                            );
-                            self.manager
-                                .chunk_at_mut(*key)
-                                .unwrap()
-                                .set_state(ChunkState::Cached);
-                        }
+
+                    // try to load from file
+                    if self.load_saved_chunk(*key) {
+                        should_generate = false;
                     }
-                }
 
-                if should_generate {
-                    num_loaded_this_tick += 1;
-                    return Some((*key, chunk_x, chunk_y));
+                    if should_generate {
+                        num_loaded_this_tick += 1;
+                        return Some((*key, chunk_x, chunk_y));
+                    }
                 }
-            }
 
-            None
-        }).collect::<Vec<_>>();
+                None
+            })
+            .collect::<Vec<_>>();
 
         // spawn chunk generation tasks
         {
@@ -530,6 +656,7 @@ where
                 .into_par_iter()
                 .for_each(|chunk| {
                     profiling::scope!("populate thread");
+                    // stage 0 has no features, so this is the same as `WorldGenerator::populate_stage`
                     pops.populate(0, &mut [&mut chunk.data], ctx.seed, ctx.registries);
                 });
         }
@@ -551,34 +678,134 @@ where
             profiling::register_thread!("Generation thread");
             profiling::scope!("chunk");
 
-            // these arrays are too large for the stack
+            let (pixels, colors, background, background_colors) =
+                generator.generate_buffers((chunk_x, chunk_y), seed, &reg);
 
-            let mut pixels = Box::new([(); CHUNK_AREA].map(|_| MaterialInstance::air()));
+            // the receiver is gone if the chunk was unloaded or the world closed in the meantime,
+            //   and panicking here would abort the whole process
+            let _ = tx.send((key, pixels, colors, background, background_colors));
+        });
 
-            #[allow(clippy::cast_lossless)]
-            let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        self.gen_threads.push((key, rx));
+    }
 
-            let mut background = Box::new([(); CHUNK_AREA].map(|_| MaterialInstance::air()));
+    /// Generates every chunk within `radius` chunks of `center` right away instead of over the
+    ///   next ticks, blocking until they're all [`ChunkState::Cached`]. Meant for teleporting and tools.
+    ///
+    /// Chunks are generated the same way [`ChunkHandler::tick`] would (same seed and populators),
+    ///   saved chunks are loaded instead, chunks already being generated are waited for and
+    ///   chunks that are already generated are left alone. Chunks up to [`WorldGenerator::max_gen_stage`]
+    ///   outside the radius are partly generated too, since populating a chunk needs its neighbors.
+    ///
+    /// Returns how many chunks were finished.
+    pub fn force_generate(
+        &mut self,
+        center: ChunkKey,
+        radius: u16,
+        seed: i32,
+        registries: &Arc<Registries>,
+        world: &mut specs::World,
+    ) -> usize {
+        profiling::scope!("force_generate");
+
+        let max_stage = self.generator.max_gen_stage();
+        let radius = i32::from(radius);
+        let keys_within = |r: i32| {
+            (center.1 - r..=center.1 + r)
+                .flat_map(move |y| (center.0 - r..=center.0 + r).map(move |x| (x, y)))
+        };
 
-            #[allow(clippy::cast_lossless)]
-            let mut background_colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        // stage 0, for the chunks in range and the neighbors needed to populate them
+        let mut generated = vec![];
+        for key in keys_within(radius + i32::from(max_stage)) {
+            if !self.is_chunk_loaded(key) {
+                self.load_chunk(key.0, key.1);
+                self.load_queue.retain(|k| *k != key);
+            }
+            if self.manager.chunk_at(key).unwrap().state() != ChunkState::NotGenerated {
+                continue;
+            }
 
-            generator.generate(
-                (chunk_x, chunk_y),
-                GenBuffers::new(
-                    &mut pixels,
-                    &mut colors,
-                    &mut background,
-                    &mut background_colors,
-                ),
-                GenContext { seed, registries: &reg },
-            );
+            if let Some(border) = &self.border {
+                if !border.contains_chunk(key) {
+                    border.fill_chunk(&mut self.manager.chunk_at_mut(key).unwrap().data);
+                    continue;
+                }
+            }
 
-            tx.send((key, pixels, colors, background, background_colors))
-                .unwrap();
-        });
+            if let Some(i) = self.gen_threads.iter().position(|(k, _)| *k == key) {
+                let (_, rx) = self.gen_threads.swap_remove(i);
+                if let Ok(output) = futures::executor::block_on(rx) {
+                    generated.push(output);
+                    continue;
+                }
+            }
 
-        self.gen_threads.push((key, rx));
+            if self.load_saved_chunk(key) {
+                continue;
+            }
+
+            let (pixels, colors, background, background_colors) =
+                self.generator.generate_buffers(key, seed, registries);
+            generated.push((key, pixels, colors, background, background_colors));
+        }
+
+        let pops = self.generator.populators();
+        for (key, pixels, colors, background, background_colors) in generated {
+            let chunk = self.manager.chunk_at_mut(key).unwrap();
+            chunk.set_state(ChunkState::Generating(0));
+            chunk.set_pixels(pixels);
+            chunk.set_pixel_colors(colors);
+            chunk.set_background_pixels(background);
+            chunk.set_background_pixel_colors(background_colors);
+            pops.populate(0, &mut [&mut chunk.data], seed, registries);
+        }
+
+        // each stage needs the previous one done one chunk further out
+        for stage in 1..=max_stage {
+            for key in keys_within(radius + i32::from(max_stage - stage)) {
+                let state = self.manager.chunk_at(key).map(|c| c.state());
+                if state != Some(ChunkState::Generating(stage - 1)) {
+                    continue;
+                }
+
+                let range = i32::from(stage);
+                let mut keys = Vec::with_capacity(((range * 2 + 1) * (range * 2 + 1)) as usize);
+                for y in -range..=range {
+                    for x in -range..=range {
+                        keys.push((key.0 + x, key.1 + y));
+                    }
+                }
+
+                let chunks = unsafe { self.manager.raw_mut().get_many_var_mut(&keys) };
+                let Some(chunks) = chunks else {
+                    continue;
+                };
+                if !chunks.iter().all(|c| c.pixels().is_some()) {
+                    continue;
+                }
+                let mut chunks_data: Vec<_> = chunks.into_iter().map(|c| &mut c.data).collect();
+                self.generator
+                    .populate_stage(stage, &mut chunks_data, seed, registries, world);
+
+                self.manager
+                    .chunk_at_mut(key)
+                    .unwrap()
+                    .set_state(ChunkState::Generating(stage));
+            }
+        }
+
+        let mut finished = 0;
+        for key in keys_within(radius) {
+            let chunk = self.manager.chunk_at_mut(key).unwrap();
+            if chunk.state() == ChunkState::Generating(max_stage) {
+                let _: Result<(), _> = chunk.generate_mesh();
+                chunk.set_state(ChunkState::Cached);
+                finished += 1;
+            }
+        }
+
+        finished
     }
 
     // TODO: split this (figure out why were these two tasks combined originally)
@@ -588,6 +815,7 @@ where
         ctx: &mut ChunkTickContext,
         loader_zones: &[Zones],
         num_active: usize,
+        budget: &mut GenBudget,
     ) {
         profiling::scope!("populate_chunks_and_check_unload_generating");
 
@@ -599,10 +827,10 @@ where
             let key = keys[i];
             let state = self.manager.chunk_at(key).unwrap().state(); // copy
             let rect = Rect::new_wh(
-                self.manager.chunk_at(key).unwrap().chunk_x() * i32::from(CHUNK_SIZE),
-                self.manager.chunk_at(key).unwrap().chunk_y() * i32::from(CHUNK_SIZE),
-                CHUNK_SIZE,
-                CHUNK_SIZE,
+                self.manager.chunk_at(key).unwrap().chunk_x() * i32::from(CHUNK_WIDTH),
+                self.manager.chunk_at(key).unwrap().chunk_y() * i32::from(CHUNK_HEIGHT),
+                CHUNK_WIDTH,
+                CHUNK_HEIGHT,
             );
 
             match state {
@@ -626,23 +854,28 @@ where
                     let max_stage = self.generator.max_gen_stage();
 
                     if cur_stage >= max_stage {
-                        profiling::scope!("finish");
-                        let _: Result<(), _> =
-                            self.manager.chunk_at_mut(key).unwrap().generate_mesh();
+                        // meshing is slow too, so it waits for the next tick if out of time
+                        if budget.has_time() {
+                            profiling::scope!("finish");
+                            let _: Result<(), _> =
+                                self.manager.chunk_at_mut(key).unwrap().generate_mesh();
 
-                        self.manager
-                            .chunk_at_mut(key)
-                            .unwrap()
-                            .set_state(ChunkState::Cached);
+                            self.manager
+                                .chunk_at_mut(key)
+                                .unwrap()
+                                .set_state(ChunkState::Cached);
+                            budget.spend();
+                        }
                     } else {
-                        if populated_num
-                            < if num_active < 16 {
-                                32
-                            } else if num_active < 64 {
-                                16
-                            } else {
-                                8
-                            }
+                        if budget.has_time()
+                            && populated_num
+                                < if num_active < 16 {
+                                    32
+                                } else if num_active < 64 {
+                                    16
+                                } else {
+                                    8
+                                }
                             && {
                                 profiling::scope!("check neighbors");
                                 [
@@ -698,33 +931,12 @@ where
                                 let mut chunks_data: Vec<_> =
                                     chunks.into_iter().map(|c| &mut c.data).collect();
 
-                                if cur_stage + 1 == 1 {
-                                    let mut chunk_ctx =
-                                        ChunkContext::<1, C>::new(&mut chunks_data).unwrap();
-                                    let mut rng = StdRng::seed_from_u64(
-                                        ctx.seed as u64
-                                            + u64::from(chunk_index(
-                                                chunk_ctx.center_chunk().0,
-                                                chunk_ctx.center_chunk().1,
-                                            )),
-                                    );
-                                    for feat in self.generator.features() {
-                                        feat.generate(
-                                            &mut chunk_ctx,
-                                            ctx.seed,
-                                            &mut rng,
-                                            ctx.registries,
-                                            ctx.world,
-                                        );
-                                        ctx.world.maintain();
-                                    }
-                                }
-
-                                self.generator.populators().populate(
+                                self.generator.populate_stage(
                                     cur_stage + 1,
                                     &mut chunks_data,
                                     ctx.seed,
                                     ctx.registries,
+                                    ctx.world,
                                 );
 
                                 self.manager
@@ -733,6 +945,7 @@ where
                                     .set_state(ChunkState::Generating(cur_stage + 1));
 
                                 populated_num += 1;
+                                budget.spend();
                             }
                         }
 
@@ -765,10 +978,8 @@ where
         }
 
         // tick structures
-        let mut update_structures = UpdateStructureNodes {
-            chunk_handler: self,
-            registries: ctx.registries.clone(),
-        };
+        let mut update_structures =
+            UpdateStructureNodes { chunk_handler: self, registries: ctx.registries };
         update_structures.run_now(ctx.world);
         ctx.world.maintain();
 
@@ -777,29 +988,63 @@ where
     }
 
     #[allow(clippy::too_many_lines)]
-    fn simulate_chunks(&mut self, ctx: &mut ChunkTickContext) {
+    fn simulate_chunks(
+        &mut self,
+        ctx: &mut ChunkTickContext,
+        substep: u8,
+        budget: &mut Option<u32>,
+    ) {
         profiling::scope!("simulate_chunks");
 
         let mut old_dirty_rects = ahash::AHashMap::with_capacity(128);
-        let mut keys_for_phases = [
-            Vec::with_capacity(32),
-            Vec::with_capacity(32),
-            Vec::with_capacity(32),
-            Vec::with_capacity(32),
-        ];
+        let mut active_keys = Vec::with_capacity(128);
 
         {
             profiling::scope!("pre prep");
+            let draw_flow = ctx.settings.draw_flow_overlay;
+            let time_sim = ctx.settings.draw_chunk_sim_time_overlay;
             for (key, ch) in unsafe { self.manager.raw_mut().iter_mut() } {
                 let rect = ch.dirty_rect();
                 ch.set_dirty_rect(None);
                 old_dirty_rects.insert(*key, rect);
+                if !draw_flow {
+                    *ch.flow_mut() = None;
+                } else if substep == 0 && ch.pixels().is_some() {
+                    flow::decay(ch.flow_mut().get_or_insert_with(flow::new_buffer));
+                }
+                // substeps add up into one time for the whole tick
+                if !time_sim || substep == 0 {
+                    ch.set_sim_time(None);
+                }
                 if ch.state() == ChunkState::Active {
-                    keys_for_phases[chunk_update_order(key.0, key.1) as usize].push(*key);
+                    active_keys.push(*key);
                 }
             }
         }
 
+        // chunks over the budget keep their dirty rect and wait for a later tick
+        let (active_keys, spent) = {
+            let (dirty, clean): (Vec<_>, Vec<_>) = active_keys
+                .into_iter()
+                .partition(|key| old_dirty_rects[key].is_some());
+            let dirty = dirty.into_iter().map(|key| {
+                let rect: Rect<i32> = old_dirty_rects[&key].unwrap();
+                (key, (rect.width() * rect.height()).unsigned_abs())
+            });
+            let (mut picked, spent) = self.sim_budget.select(dirty, *budget);
+            for &key in self.sim_budget.deferred() {
+                let ch = self.manager.chunk_at_mut(key).unwrap();
+                ch.set_dirty_rect(old_dirty_rects[&key]);
+            }
+            picked.extend(clean);
+            (picked, spent)
+        };
+        if let Some(b) = budget {
+            *b = b.saturating_sub(spent);
+        }
+
+        let keys_for_phases = chunk_update_phases(active_keys);
+
         #[allow(unused_variables)] // false positive
         for (tick_phase, keys) in keys_for_phases.into_iter().enumerate() {
             profiling::scope!("phase", format!("phase {tick_phase}").as_str());
@@ -811,7 +1056,7 @@ where
                     profiling::scope!("iter");
 
                     // SAFETY: the same chunks' arrays may be modified mutably on multiple threads at once, which is necessary for multithreading
-                    // However, ticking a chunk can only affect pixels within CHUNK_SIZE/2 of the center chunk (this is unchecked)
+                    // However, ticking a chunk can only affect pixels within CHUNK_WIDTH/2 and CHUNK_HEIGHT/2 of the center chunk (this is unchecked)
                     //   and the 4-phase thing ensures no chunks directly next to each other are ticked at the same time
                     //   so multiple threads will not modify the same index in the arrays at the same time
                     // The chunk arrays are cast to `&[UnsafeCell<T>; _]`, so there should be no actual `&mut`s involved
@@ -845,6 +1090,11 @@ where
                                     let raw: *mut [[f32; 4]; CHUNK_AREA] = c.lights_mut();
                                     let lights = unsafe { &*(raw as *const [UnsafeCell<[f32; 4]>; CHUNK_AREA]) };
 
+                                    let flow = c.flow_mut().as_mut().map(|raw| {
+                                        let raw: *mut FlowBuffer = raw.as_mut();
+                                        unsafe { &*(raw as *const [UnsafeCell<u8>; CHUNK_AREA]) }
+                                    });
+
                                     let dirty_rect = *old_dirty_rects
                                         .get(&(ch_pos.0 + x, ch_pos.1 + y))
                                         .unwrap();
@@ -853,6 +1103,7 @@ where
                                         pixels,
                                         colors,
                                         lights,
+                                        flow,
                                         dirty: false,
                                         dirty_rect,
                                     }
@@ -878,33 +1129,69 @@ where
                     (i32, i32),
                     [(bool, Option<Rect<i32>>); 9],
                     Vec<Particle>,
+                    Vec<SettleEvent>,
+                    Option<Duration>,
                 )> = {
                     profiling::scope!("par_iter");
                     let reg = ctx.registries.clone();
-                    to_exec
-                        .into_par_iter()
-                        .map(move |(ch_pos, mut chunk_data)| {
-                            profiling::register_thread!("Simulation thread");
-                            profiling::scope!("chunk");
-
-                            let mut particles = Vec::new();
-                            Simulator::simulate_chunk(
-                                ch_pos.0,
-                                ch_pos.1,
-                                &mut chunk_data,
-                                &mut particles,
-                                reg.clone(),
-                            );
+                    let (seed, sim_seed, tick_time) =
+                        (ctx.seed, ctx.settings.simulation_seed, ctx.tick_time);
+                    let light = LightSettings {
+                        sun: ctx.settings.sun_light.then_some(SunLight {
+                            slope: ctx.settings.sun_slope,
+                            intensity: ctx.settings.sun_intensity,
+                        }),
+                        propagate: ctx.settings.propagate_light,
+                    };
+                    let rules = SimRules {
+                        freezing: self.freezing,
+                        ..SimRules::from_settings(ctx.settings)
+                    };
+                    let time_sim = ctx.settings.draw_chunk_sim_time_overlay;
+                    let sim = move |(ch_pos, mut chunk_data): ((i32, i32), _)| {
+                        profiling::register_thread!("Simulation thread");
+                        profiling::scope!("chunk");
+
+                        let start = time_sim.then(Instant::now);
+                        let mut particles = Vec::new();
+                        let mut settled = Vec::new();
+                        Simulator::simulate_chunk(
+                            ch_pos.0,
+                            ch_pos.1,
+                            &mut chunk_data,
+                            &mut particles,
+                            &mut settled,
+                            reg.clone(),
+                            Simulator::chunk_seed(seed, sim_seed, tick_time, ch_pos.0, ch_pos.1)
+                                .wrapping_add(u64::from(substep)),
+                            light,
+                            rules,
+                        );
 
-                            let dirty_info = chunk_data.map(|d| (d.dirty, d.dirty_rect));
-                            (ch_pos, dirty_info, particles)
-                        })
-                        .collect()
+                        let time = start.map(|start| start.elapsed());
+
+                        let dirty_info = chunk_data.map(|d| (d.dirty, d.dirty_rect));
+                        (ch_pos, dirty_info, particles, settled, time)
+                    };
+
+                    // chunks in the same phase never touch the same pixels, so this gives the same result either way
+                    if ctx.settings.simulate_chunks_parallel {
+                        self.sim_pool
+                            .install(|| to_exec.into_par_iter().map(sim).collect())
+                    } else {
+                        to_exec.into_iter().map(sim).collect()
+                    }
                 };
 
                 for r in b {
                     profiling::scope!("apply");
-                    let (ch_pos, dirty_info, mut parts) = r;
+                    let (ch_pos, dirty_info, mut parts, settled, time) = r;
+
+                    if let Some(time) = time {
+                        let ch = self.manager.chunk_at_mut(ch_pos).unwrap();
+                        let t = ch.sim_time().unwrap_or_default() + time;
+                        ch.set_sim_time(Some(t));
+                    }
 
                     {
                         profiling::scope!("particles");
@@ -914,6 +1201,13 @@ where
                             .append(&mut parts);
                     }
 
+                    if !settled.is_empty() {
+                        let mut settle_events = ctx.world.write_resource::<SettleEvents>();
+                        for event in settled {
+                            settle_events.emit(event);
+                        }
+                    }
+
                     for i in 0..9 {
                         let rel_ch_x = (i % 3) - 1;
                         let rel_ch_y = (i / 3) - 1;
@@ -931,17 +1225,17 @@ where
 
                         if i != 4 && dirty_info[4].1.is_some() {
                             let neighbor_rect = Rect::new_wh(
-                                if rel_ch_x == -1 { CHUNK_SIZE / 2 } else { 0 },
-                                if rel_ch_y == -1 { CHUNK_SIZE / 2 } else { 0 },
+                                if rel_ch_x == -1 { CHUNK_WIDTH / 2 } else { 0 },
+                                if rel_ch_y == -1 { CHUNK_HEIGHT / 2 } else { 0 },
                                 if rel_ch_x == 0 {
-                                    CHUNK_SIZE
+                                    CHUNK_WIDTH
                                 } else {
-                                    CHUNK_SIZE / 2
+                                    CHUNK_WIDTH / 2
                                 },
                                 if rel_ch_y == 0 {
-                                    CHUNK_SIZE
+                                    CHUNK_HEIGHT
                                 } else {
-                                    CHUNK_SIZE / 2
+                                    CHUNK_HEIGHT / 2
                                 },
                             );
 
@@ -993,6 +1287,12 @@ where
     }
 }
 
+impl<C: Chunk> WorldBounds for ChunkHandler<C> {
+    fn border(&self) -> Option<&WorldBorder> {
+        self.border.as_ref()
+    }
+}
+
 impl<C: Chunk> ChunkQuery for ChunkHandler<C> {
     type D = C;
 
@@ -1055,10 +1355,83 @@ impl<C: Chunk> ChunkHandler<C> {
                 .build()
                 .expect("Failed to build gen_poool"),
             gen_threads: vec![],
+            cache: ChunkCache::new(),
+            sim_pool: SizedThreadPool::new("Simulation", 0).expect("Failed to build sim_pool"),
+            sim_budget: SimBudget::new(),
             screen_size: (1920 / 2, 1080 / 2),
             generator: Arc::new(generator),
             path,
+            edit_history: EditHistory::default(),
+            journal: EditJournal::default(),
+            zone_radii: ZoneRadii::default(),
+            border: None,
+            freezing: true,
+        }
+    }
+
+    /// Walks the pixels on the line from `from` to `to` (both included) and returns the first
+    ///   one that isn't empty, with its position.
+    ///
+    /// Every pixel the line passes through is checked, so rays can't slip between two
+    ///   diagonally touching pixels. Unloaded pixels are skipped.
+    pub fn raycast(
+        &self,
+        from: (i64, i64),
+        to: (i64, i64),
+        materials: &MaterialRegistry,
+    ) -> Option<(i64, i64, MaterialInstance)> {
+        self.raycast_filtered(from, to, |_, mat| !mat.is_empty(materials))
+            .map(|((x, y), mat)| (x, y, mat.clone()))
+    }
+
+    /// Like [`Self::raycast`], but stops at the first pixel `filter` returns `true` for instead.
+    pub fn raycast_filtered(
+        &self,
+        from: (i64, i64),
+        to: (i64, i64),
+        filter: impl Fn((i64, i64), &MaterialInstance) -> bool,
+    ) -> Option<((i64, i64), &MaterialInstance)> {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - from.0).abs();
+        let dy = (to.1 - from.1).abs();
+        let x_step = if to.0 > from.0 { 1 } else { -1 };
+        let y_step = if to.1 > from.1 { 1 } else { -1 };
+
+        // grid traversal (DDA) between pixel centers, stepping along whichever axis
+        //   the line crosses a pixel edge on first
+        let mut error = dx - dy;
+        for _ in 0..=(dx + dy) {
+            if let Ok(mat) = self.pixel(x, y) {
+                if filter((x, y), mat) {
+                    return Some(((x, y), mat));
+                }
+            }
+
+            if error > 0 {
+                x += x_step;
+                error -= 2 * dy;
+            } else {
+                y += y_step;
+                error += 2 * dx;
+            }
         }
+
+        None
+    }
+
+    /// Starts a batch of pixel changes, see [`Edit`].
+    pub fn begin_edit(&mut self) -> Edit<'_, C> {
+        Edit::new(self)
+    }
+
+    /// Reverts the most recently committed [`Edit`], returning `false` if there was nothing to undo.
+    pub fn undo_edit(&mut self) -> Result<bool, String> {
+        let Some(undo) = self.edit_history.pop() else {
+            return Ok(false);
+        };
+
+        edit::apply_journaled(self, undo.into_changes().collect())?;
+        Ok(true)
     }
 
     #[profiling::function]
@@ -1077,8 +1450,11 @@ impl<C: Chunk> ChunkHandler<C> {
                 let mut contents = Vec::new();
 
                 let save = ChunkSaveFormat {
+                    version: CHUNK_SAVE_VERSION,
                     pixels: pixels.to_vec(),
                     colors: chunk.colors().to_vec(),
+                    lights: chunk.lights().to_vec(),
+                    revealed: chunk.revealed(),
                 };
 
                 let pixel_data: Vec<u8> = bincode::serialize(&save)?;
@@ -1110,9 +1486,21 @@ impl<C: Chunk> ChunkHandler<C> {
             self.unload_chunk(i, physics)?;
         }
         self.manager.clear();
+        self.cache.clear();
         Ok(())
     }
 
+    /// Saves a chunk that went out of range and gets rid of its collision,
+    ///   it still has to be removed from the manager after.
+    fn save_and_unload_chunk(&mut self, key: ChunkKey, physics: &mut Physics) {
+        if let Err(e) = self.save_chunk(key) {
+            log::error!("Chunk @ {}, {} failed to save: {:?}", key.0, key.1, e);
+        }
+        if let Err(e) = self.unload_chunk(key, physics) {
+            log::error!("Chunk @ {}, {} failed to unload: {:?}", key.0, key.1, e);
+        }
+    }
+
     pub fn save_all_chunks(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         #[allow(clippy::for_kv_map)] // want ? to work
         let keys = self.manager.keys();
@@ -1122,6 +1510,102 @@ impl<C: Chunk> ChunkHandler<C> {
         Ok(())
     }
 
+    /// Loads a chunk's pixels from its save file, if there is one. Returns whether it was loaded.
+    ///
+    /// A chunk with a broken save file is left [`ChunkState::Cached`] without pixels.
+    fn load_saved_chunk(&mut self, key: ChunkKey) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+        let (chunk_x, chunk_y) = key;
+
+        let chunk_path_root = path.join("chunks/");
+        if !chunk_path_root.exists() {
+            std::fs::create_dir_all(&chunk_path_root)
+                .expect(format!("Failed to create chunk directory @ {chunk_path_root:?}").as_str());
+        }
+        let chunk_path = chunk_path_root.join(format!("{chunk_x}_{chunk_y}.chunk"));
+        if !chunk_path.exists() {
+            return false;
+        }
+
+        let Ok(data) = std::fs::read(&chunk_path) else {
+            log::error!(
+                "Chunk load failed @ {},{} -> {:?}",
+                chunk_x,
+                chunk_y,
+                chunk_path
+            );
+            self.manager
+                .chunk_at_mut(key)
+                .unwrap()
+                .set_state(ChunkState::Cached);
+            return false;
+        };
+
+        match ChunkSaveFormat::parse(&data) {
+            Ok(save) => {
+                if save.pixels.len() == (CHUNK_AREA) {
+                    let chunk = self.manager.chunk_at_mut(key).unwrap();
+                    chunk.set_state(ChunkState::Cached);
+                    chunk.set_revealed(save.revealed);
+                    chunk.set_pixels(save.pixels.try_into().unwrap());
+                    chunk.mark_dirty();
+                    let _: Result<(), _> = chunk.generate_mesh();
+
+                    if save.colors.len() == (CHUNK_AREA * 4) {
+                        chunk.set_pixel_colors(save.colors.try_into().unwrap());
+                    } else {
+                        log::error!(
+                            "colors Vec is the wrong size: {} (expected {})",
+                            save.colors.len(),
+                            CHUNK_AREA * 4
+                        );
+                        chunk.refresh();
+                    }
+
+                    // older saves start dark and get relit by the simulation
+                    if save.lights.len() == CHUNK_AREA {
+                        chunk.lights_mut().copy_from_slice(&save.lights);
+                    } else if !save.lights.is_empty() {
+                        log::error!(
+                            "lights Vec is the wrong size: {} (expected {})",
+                            save.lights.len(),
+                            CHUNK_AREA
+                        );
+                    }
+
+                    true
+                } else {
+                    log::error!(
+                        "pixels Vec is the wrong size: {} (expected {})",
+                        save.pixels.len(),
+                        CHUNK_AREA
+                    );
+                    self.manager
+                        .chunk_at_mut(key)
+                        .unwrap()
+                        .set_state(ChunkState::Cached);
+                    false
+                }
+            },
+            Err(e) => {
+                log::error!(
+                    "Chunk parse failed @ {},{} -> {:?}: {:?}",
+                    chunk_x,
+                    chunk_y,
+                    chunk_path,
+                    e
+                );
+                self.manager
+                    .chunk_at_mut(key)
+                    .unwrap()
+                    .set_state(ChunkState::Cached);
+                false
+            },
+        }
+    }
+
     #[profiling::function]
     pub fn queue_load_chunk(&mut self, chunk_x: i32, chunk_y: i32) -> bool {
         // make sure not loaded
@@ -1145,7 +1629,38 @@ impl<C: Chunk> ChunkHandler<C> {
 
     pub fn force_update_chunk(&mut self, chunk_x: i32, chunk_y: i32) {
         if let Some(ch) = self.manager.chunk_at_mut((chunk_x, chunk_y)) {
-            ch.set_dirty_rect(Some(Rect::new_wh(0, 0, CHUNK_SIZE, CHUNK_SIZE)));
+            ch.set_dirty_rect(Some(Rect::new_wh(0, 0, CHUNK_WIDTH, CHUNK_HEIGHT)));
+        }
+    }
+
+    /// The coordinates of every chunk in memory, in no particular order.
+    pub fn loaded_chunk_coords(&self) -> Vec<ChunkKey> {
+        self.manager.keys()
+    }
+
+    /// Calls `f` with the coordinates of each chunk in memory and the chunk.
+    ///
+    /// Chunks that are still generating are included, check [`Chunk::state`] if that matters.
+    pub fn for_each_loaded_chunk(&self, mut f: impl FnMut(ChunkKey, &C)) {
+        for (key, ch) in self.manager.kv_iter() {
+            f(key, ch);
+        }
+    }
+
+    /// Like [`Self::for_each_loaded_chunk`], but `f` can change the chunks.
+    ///
+    /// `f` returns the area it changed in chunk local pixels, if any, which is added to the
+    ///   chunk's dirty rect so it gets simulated, and the chunk is redrawn.
+    pub fn for_each_loaded_chunk_mut(
+        &mut self,
+        mut f: impl FnMut(ChunkKey, &mut C) -> Option<Rect<i32>>,
+    ) {
+        for (key, ch) in self.manager.kv_iter_mut() {
+            if let Some(changed) = f(key, ch) {
+                let rect = ch.dirty_rect().map_or(changed, |r| r.union(changed));
+                ch.set_dirty_rect(Some(rect));
+                ch.mark_dirty();
+            }
         }
     }
 
@@ -1176,8 +1691,8 @@ impl<C: Chunk> ChunkHandler<C> {
     #[profiling::function]
     #[inline]
     pub fn get_zone(&self, center: (f64, f64), padding: u16) -> Rect<i32> {
-        let width = self.screen_size.0 + padding * 2;
-        let height = self.screen_size.1 + padding * 2;
+        let width = self.screen_size.0.saturating_add(padding.saturating_mul(2));
+        let height = self.screen_size.1.saturating_add(padding.saturating_mul(2));
         Rect::new_wh(
             center.0 as i32 - i32::from(width / 2),
             center.1 as i32 - i32::from(height / 2),
@@ -1195,18 +1710,38 @@ impl<C: Chunk> ChunkHandler<C> {
     #[profiling::function]
     #[inline]
     pub fn get_active_zone(&self, center: (f64, f64)) -> Rect<i32> {
-        self.get_zone(center, CHUNK_SIZE)
+        self.get_zone(center, self.zone_radii.active)
     }
 
     #[profiling::function]
     #[inline]
     pub fn get_load_zone(&self, center: (f64, f64)) -> Rect<i32> {
-        self.get_zone(center, CHUNK_SIZE * 10)
+        self.get_zone(center, self.zone_radii.load)
     }
 
     #[profiling::function]
     #[inline]
     pub fn get_unload_zone(&self, center: (f64, f64)) -> Rect<i32> {
-        self.get_zone(center, CHUNK_SIZE * 15)
+        self.get_zone(center, self.zone_radii.unload)
+    }
+
+    pub fn zones_at(&self, center: (f64, f64)) -> Zones {
+        Zones {
+            unload: self.get_unload_zone(center),
+            load: self.get_load_zone(center),
+            active: self.get_active_zone(center),
+            screen: self.get_screen_zone(center),
+        }
+    }
+
+    /// The [`Zones`] around every [`Loader`] in `world`.
+    pub fn loader_zones(&self, world: &specs::World) -> Vec<Zones> {
+        let (loaders, positions) =
+            world.system_data::<(ReadStorage<Loader>, ReadStorage<Position>)>();
+
+        (&loaders, &positions)
+            .join()
+            .map(|(_, pos)| self.zones_at((pos.x, pos.y)))
+            .collect()
     }
 }
@@ -1,10 +1,10 @@
 use std::ops::{Deref, Index, IndexMut};
 
-use crate::game::common::world::{CHUNK_AREA, CHUNK_SIZE};
+use crate::game::common::world::{CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH};
 
 /// Local pixel position from the top left of a chunk.
 ///
-/// X and Y are within `0..`[`CHUNK_SIZE`].
+/// X is within `0..`[`CHUNK_WIDTH`] and Y is within `0..`[`CHUNK_HEIGHT`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ChunkLocalPosition((u16, u16));
 
@@ -23,11 +23,11 @@ impl ChunkLocalPosition {
     }
 
     /// # Safety
-    /// `x` and `y` must both be less than [`CHUNK_SIZE`] in order to uphold invariants.
+    /// `x` must be less than [`CHUNK_WIDTH`] and `y` less than [`CHUNK_HEIGHT`] in order to uphold invariants.
     #[inline]
     pub const unsafe fn new_unchecked(x: u16, y: u16) -> Self {
-        debug_assert!(x < CHUNK_SIZE);
-        debug_assert!(y < CHUNK_SIZE);
+        debug_assert!(x < CHUNK_WIDTH);
+        debug_assert!(y < CHUNK_HEIGHT);
         Self((x, y))
     }
 
@@ -42,8 +42,8 @@ impl ChunkLocalPosition {
     }
 
     pub fn iter() -> impl Iterator<Item = Self> {
-        (0..CHUNK_SIZE).flat_map(|x| {
-            (0..CHUNK_SIZE).map(move |y| {
+        (0..CHUNK_WIDTH).flat_map(|x| {
+            (0..CHUNK_HEIGHT).map(move |y| {
                 // Safety: x and y are generated from the valid range
                 unsafe { Self::new_unchecked(x, y) }
             })
@@ -56,7 +56,7 @@ impl TryFrom<(u16, u16)> for ChunkLocalPosition {
 
     #[inline]
     fn try_from(value: (u16, u16)) -> Result<Self, Self::Error> {
-        if value.0 < CHUNK_SIZE && value.1 < CHUNK_SIZE {
+        if value.0 < CHUNK_WIDTH && value.1 < CHUNK_HEIGHT {
             Ok(Self(value))
         } else {
             Err(format!("Invalid value for ChunkLocalPosition: {value:?}"))
@@ -68,8 +68,8 @@ impl From<ChunkLocalIndex> for ChunkLocalPosition {
     #[inline]
     fn from(value: ChunkLocalIndex) -> Self {
         Self((
-            (value.idx() % (CHUNK_SIZE as usize)) as _,
-            (value.idx() / (CHUNK_SIZE as usize)) as _,
+            (value.idx() % (CHUNK_WIDTH as usize)) as _,
+            (value.idx() / (CHUNK_WIDTH as usize)) as _,
         ))
     }
 }
@@ -128,7 +128,7 @@ impl TryFrom<usize> for ChunkLocalIndex {
 impl From<ChunkLocalPosition> for ChunkLocalIndex {
     #[inline]
     fn from(value: ChunkLocalPosition) -> Self {
-        Self(value.x() as usize + (value.y() as usize) * (CHUNK_SIZE as usize))
+        Self(value.x() as usize + (value.y() as usize) * (CHUNK_WIDTH as usize))
     }
 }
 
@@ -0,0 +1,84 @@
+use crate::game::common::Registries;
+
+use super::{
+    chunk_access::FSChunkAccess,
+    material::{DestroyParticles, MaterialInstance},
+    particle::Particle,
+    Position, Velocity,
+};
+
+/// A pixel taken out of the world by [`destroy_pixel`].
+#[derive(Debug, Clone)]
+pub struct Destroyed {
+    pub material: MaterialInstance,
+    /// How many particles it burst into, from its material's [`DestroyParticles`].
+    pub particles: usize,
+}
+
+/// Clears the pixel at `x`, `y` to air, adding the particles its material bursts into
+///   (see [`destroy_particles`](super::material::Material::destroy_particles)) to `particles`.
+///
+/// Digging with the air placer and rigidbody impacts go through this, so a material breaks the same
+///   way either way. Edits that replace pixels outright (painting, siphoning, transmuting, cutting
+///   into a rigidbody, remote [`EditPixelsPacket`](crate::game::common::networking::PacketType::EditPixelsPacket)s)
+///   don't, and never spawn particles.
+/// `vel` is added to the velocity of every particle, so impacts can throw them away from the hit.
+///
+/// Returns `None` if the pixel was already empty.
+pub fn destroy_pixel(
+    chunk_handler: &mut impl FSChunkAccess,
+    x: i64,
+    y: i64,
+    vel: &Velocity,
+    registries: &Registries,
+    particles: &mut Vec<Particle>,
+) -> Result<Option<Destroyed>, String> {
+    let mat = chunk_handler.pixel(x, y)?;
+    if mat.is_empty(&registries.materials) {
+        return Ok(None);
+    }
+    let mat = mat.clone();
+    chunk_handler.set_pixel(x, y, MaterialInstance::air())?;
+
+    let spawned = registries
+        .materials
+        .get(&mat.material_id)
+        .and_then(|m| m.destroy_particles.as_ref())
+        .map_or(0, |spec| {
+            burst(spec, &mat, x, y, vel, particles);
+            usize::from(spec.count)
+        });
+
+    Ok(Some(Destroyed { material: mat, particles: spawned }))
+}
+
+fn burst(
+    spec: &DestroyParticles,
+    mat: &MaterialInstance,
+    x: i64,
+    y: i64,
+    vel: &Velocity,
+    particles: &mut Vec<Particle>,
+) {
+    let part_mat = MaterialInstance {
+        physics: spec.physics,
+        color: spec.color.unwrap_or(mat.color),
+        ..mat.clone()
+    };
+
+    for _ in 0..spec.count {
+        let angle = fastrand::f64() * std::f64::consts::TAU;
+        let speed = f64::from(spec.speed) * (0.5 + fastrand::f64() * 0.5);
+        particles.push(
+            Particle::new(
+                part_mat.clone(),
+                Position { x: x as f64, y: y as f64 },
+                Velocity {
+                    x: vel.x + angle.cos() * speed,
+                    y: vel.y + angle.sin() * speed,
+                },
+            )
+            .with_drag(spec.drag),
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::ops::Deref;
 use bitflags::bitflags;
 use rapier2d::{
     na::{Isometry2, Vector2},
-    prelude::RigidBodyHandle,
+    prelude::{InteractionGroups, RigidBodyHandle},
 };
 use serde::{Deserialize, Serialize};
 use specs::{
@@ -13,7 +13,7 @@ use specs::{
     VecStorage, WorldExt, WriteStorage,
 };
 
-use crate::game::common::world::physics::PHYSICS_SCALE;
+use crate::game::common::{world::physics::PHYSICS_SCALE, Settings};
 
 use super::{chunk_access::FSChunkAccess, entity::Hitbox, physics::Physics, Chunk, World};
 
@@ -80,14 +80,64 @@ impl Camera {
             .with(AutoTarget {
                 target,
                 offset: (0.0, 0.0),
+                look_ahead: 0.0,
                 style: TargetStyle::Locked,
             })
             .build();
 
         camera
     }
+
+    /// Updates the [`AutoTarget`]s of all cameras to match the camera settings.
+    ///
+    /// Nothing is written unless the settings changed since the last call on this `ecs`,
+    ///   so it's cheap to call every frame and doesn't undo anything else that changed the targets.
+    pub fn apply_settings(ecs: &mut specs::World, settings: &Settings) {
+        let current = CameraSettings::of(settings);
+        let mut applied = ecs
+            .entry::<AppliedCameraSettings>()
+            .or_insert_with(AppliedCameraSettings::default);
+        if applied.0 == Some(current) {
+            return;
+        }
+        applied.0 = Some(current);
+        drop(applied);
+
+        let (cameras, mut targets) =
+            ecs.system_data::<(ReadStorage<Camera>, WriteStorage<AutoTarget>)>();
+        for (_, at) in (&cameras, &mut targets).join() {
+            at.offset = current.offset;
+            at.look_ahead = current.look_ahead;
+            at.style = if current.smoothing > 0.0 {
+                TargetStyle::EaseOut(1.0 / current.smoothing)
+            } else {
+                TargetStyle::Locked
+            };
+        }
+    }
+}
+
+/// The parts of [`Settings`] that [`Camera::apply_settings`] copies into the cameras.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CameraSettings {
+    offset: (f64, f64),
+    look_ahead: f64,
+    smoothing: f64,
+}
+
+impl CameraSettings {
+    fn of(settings: &Settings) -> Self {
+        Self {
+            offset: (settings.camera_offset_x, settings.camera_offset_y),
+            look_ahead: settings.camera_look_ahead,
+            smoothing: settings.camera_smoothing,
+        }
+    }
 }
 
+#[derive(Debug, Default)]
+struct AppliedCameraSettings(Option<CameraSettings>);
+
 impl Component for Camera {
     type Storage = NullStorage<Self>;
 }
@@ -113,19 +163,33 @@ pub enum TargetStyle {
 pub struct AutoTarget {
     pub target: Target,
     pub offset: (f64, f64),
+    /// How many ticks ahead of a moving target to aim, based on its velocity
+    pub look_ahead: f64,
     pub style: TargetStyle,
 }
 
 impl AutoTarget {
-    pub fn get_target_pos<S>(&self, pos_storage: &Storage<Position, S>) -> Option<Position>
+    pub fn get_target_pos<S, V>(
+        &self,
+        pos_storage: &Storage<Position, S>,
+        vel_storage: &Storage<Velocity, V>,
+    ) -> Option<Position>
     where
         S: Deref<Target = MaskedStorage<Position>>,
+        V: Deref<Target = MaskedStorage<Velocity>>,
     {
+        let (lead_x, lead_y) = self.get_target_vel(vel_storage).map_or((0.0, 0.0), |v| {
+            (v.x * self.look_ahead, v.y * self.look_ahead)
+        });
+
         match &self.target {
             Target::Entity(e) => pos_storage.get(*e).cloned(),
             Target::Position(p) => Some(p.clone()),
         }
-        .map(|p| Position { x: p.x + self.offset.0, y: p.y + self.offset.1 })
+        .map(|p| Position {
+            x: p.x + self.offset.0 + lead_x,
+            y: p.y + self.offset.1 + lead_y,
+        })
     }
 
     pub fn get_target_vel<S>(&self, vel_storage: &Storage<Velocity, S>) -> Option<Velocity>
@@ -161,7 +225,7 @@ impl<'a> System<'a> for UpdateAutoTargets {
         let (entities, delta_time, target, mut pos_storage, mut vel_storage) = data;
 
         (&entities, &target).join().for_each(|(entity, at)| {
-            if let Some(target_pos) = at.get_target_pos(&pos_storage) {
+            if let Some(target_pos) = at.get_target_pos(&pos_storage, &vel_storage) {
                 let pos = pos_storage
                     .get_mut(entity)
                     .expect("AutoTarget missing Position");
@@ -201,11 +265,62 @@ impl<'a> System<'a> for UpdateAutoTargets {
 }
 
 bitflags! {
+    /// Collision layers for rapier colliders, see [`CollisionLayers`].
     pub struct CollisionFlags: u32 {
-        const ENTITY    = 0b0000_0000_0000_0001;
-        const WORLD     = 0b0000_0000_0000_0010;
-        const RIGIDBODY = 0b0000_0000_0000_0100;
-        const PLAYER    = Self::ENTITY.bits;
+        const ENTITY     = 0b0000_0000_0000_0001;
+        const WORLD      = 0b0000_0000_0000_0010;
+        const RIGIDBODY  = 0b0000_0000_0000_0100;
+        const PROJECTILE = 0b0000_0000_0000_1000;
+        const PLAYER     = Self::ENTITY.bits;
+    }
+}
+
+/// Which [`CollisionFlags`] a collider is on, and which ones it collides with.
+///
+/// Two colliders only collide if each one's `filter` has one of the other's `memberships`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionLayers {
+    pub memberships: CollisionFlags,
+    pub filter: CollisionFlags,
+}
+
+impl CollisionLayers {
+    pub fn new(memberships: CollisionFlags, filter: CollisionFlags) -> Self {
+        Self { memberships, filter }
+    }
+
+    /// Players push rigidbodies and other entities around.
+    pub fn player() -> Self {
+        Self::new(
+            CollisionFlags::PLAYER,
+            CollisionFlags::RIGIDBODY | CollisionFlags::ENTITY,
+        )
+    }
+
+    /// Rigidbodies collide with everything.
+    pub fn rigidbody() -> Self {
+        Self::new(CollisionFlags::RIGIDBODY, CollisionFlags::all())
+    }
+
+    /// Chunk colliders stop rigidbodies and projectiles. Entities collide with the pixels instead.
+    pub fn terrain() -> Self {
+        Self::new(
+            CollisionFlags::WORLD,
+            CollisionFlags::RIGIDBODY | CollisionFlags::PROJECTILE,
+        )
+    }
+
+    pub fn collides_with(&self, other: &Self) -> bool {
+        self.filter.intersects(other.memberships) && other.filter.intersects(self.memberships)
+    }
+}
+
+impl From<CollisionLayers> for InteractionGroups {
+    fn from(layers: CollisionLayers) -> Self {
+        InteractionGroups::new(
+            layers.memberships.bits().into(),
+            layers.filter.bits().into(),
+        )
     }
 }
 
@@ -308,3 +423,91 @@ impl<'a> System<'a> for ApplyRigidBodies<'a> {
             });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use specs::{Builder, Join, ReadStorage, WorldExt};
+
+    use crate::game::common::Settings;
+
+    use super::{AutoTarget, Camera, Position, Target, TargetStyle, Velocity};
+
+    #[test]
+    fn target_pos_leads_moving_target() {
+        let mut ecs = specs::World::new();
+        ecs.register::<Position>();
+        ecs.register::<Velocity>();
+        ecs.register::<AutoTarget>();
+
+        let player = ecs
+            .create_entity()
+            .with(Position { x: 100.0, y: -50.0 })
+            .with(Velocity { x: 2.0, y: -1.0 })
+            .build();
+
+        ecs.create_entity()
+            .with(AutoTarget {
+                target: Target::Entity(player),
+                offset: (10.0, 5.0),
+                look_ahead: 4.0,
+                style: TargetStyle::Locked,
+            })
+            .build();
+
+        let (targets, pos_storage, vel_storage) = ecs.system_data::<(
+            ReadStorage<AutoTarget>,
+            ReadStorage<Position>,
+            ReadStorage<Velocity>,
+        )>();
+        let at = targets.join().next().unwrap();
+
+        let pos = at.get_target_pos(&pos_storage, &vel_storage).unwrap();
+        assert!((pos.x - 118.0).abs() < f64::EPSILON);
+        assert!((pos.y - -49.0).abs() < f64::EPSILON);
+
+        // a stationary target only gets the fixed offset
+        let at = AutoTarget {
+            target: Target::Position(Position { x: 3.0, y: 4.0 }),
+            ..at.clone()
+        };
+        let pos = at.get_target_pos(&pos_storage, &vel_storage).unwrap();
+        assert!((pos.x - 13.0).abs() < f64::EPSILON);
+        assert!((pos.y - 9.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn camera_settings_only_apply_when_changed() {
+        let mut ecs = specs::World::new();
+        ecs.register::<Camera>();
+        ecs.register::<AutoTarget>();
+
+        let camera = ecs
+            .create_entity()
+            .with(Camera)
+            .with(AutoTarget {
+                target: Target::Position(Position { x: 0.0, y: 0.0 }),
+                offset: (0.0, 0.0),
+                look_ahead: 0.0,
+                style: TargetStyle::Locked,
+            })
+            .build();
+        let offset =
+            |ecs: &specs::World| ecs.read_storage::<AutoTarget>().get(camera).unwrap().offset;
+
+        let mut settings = Settings { camera_offset_x: 4.0, ..Settings::default() };
+        Camera::apply_settings(&mut ecs, &settings);
+        assert_eq!(offset(&ecs), (4.0, 0.0));
+
+        // something else moves the target, which the same settings don't undo
+        ecs.write_storage::<AutoTarget>()
+            .get_mut(camera)
+            .unwrap()
+            .offset = (1.0, 2.0);
+        Camera::apply_settings(&mut ecs, &settings);
+        assert_eq!(offset(&ecs), (1.0, 2.0));
+
+        settings.camera_offset_y = 3.0;
+        Camera::apply_settings(&mut ecs, &settings);
+        assert_eq!(offset(&ecs), (4.0, 3.0));
+    }
+}
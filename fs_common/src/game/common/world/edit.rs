@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+use chunksystem::{ChunkKey, ChunkQuery};
+
+use super::{
+    chunk_access::{outside_border, FSChunkAccess},
+    chunk_handler::ChunkHandler,
+    chunk_index::ChunkLocalPosition,
+    journal,
+    material::MaterialInstance,
+    pixel_to_chunk_pos, pixel_to_pos_in_chunk, Chunk,
+};
+
+/// How many committed edits are kept around to be undone.
+pub const EDIT_HISTORY_LEN: usize = 64;
+
+/// How far from a player, in pixels, the server accepts edits sent by a client,
+///   see [`World::apply_remote_edit`](super::World::apply_remote_edit).
+pub const EDIT_REACH: f64 = 256.0;
+
+/// A batch of pixel changes that is applied all at once, see [`ChunkHandler::begin_edit`].
+///
+/// Nothing is written to the world until [`Edit::commit`]. Reads through [`Edit::pixel`]
+///   see the pending changes, so tools can build on what they already placed.
+pub struct Edit<'a, C: Chunk> {
+    chunk_handler: &'a mut ChunkHandler<C>,
+    changes: ahash::AHashMap<(i64, i64), MaterialInstance>,
+}
+
+/// The previous contents of every pixel a committed [`Edit`] changed.
+#[derive(Debug, Clone)]
+pub struct EditUndo {
+    pixels: Vec<(i64, i64, MaterialInstance)>,
+}
+
+impl EditUndo {
+    pub(super) fn into_changes(self) -> impl Iterator<Item = ((i64, i64), MaterialInstance)> {
+        self.pixels.into_iter().map(|(x, y, mat)| ((x, y), mat))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+}
+
+/// Committed edits that can be undone, most recent last.
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    entries: Vec<EditUndo>,
+}
+
+impl EditHistory {
+    pub fn push(&mut self, undo: EditUndo) {
+        if self.entries.len() >= EDIT_HISTORY_LEN {
+            self.entries.remove(0);
+        }
+        self.entries.push(undo);
+    }
+
+    pub fn pop(&mut self) -> Option<EditUndo> {
+        self.entries.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<'a, C: Chunk> Edit<'a, C> {
+    pub(super) fn new(chunk_handler: &'a mut ChunkHandler<C>) -> Self {
+        Self { chunk_handler, changes: ahash::AHashMap::new() }
+    }
+
+    /// Queues `mat` to be written at `x`, `y`. Fails if the pixel isn't loaded or is outside
+    ///   the [`WorldBorder`](super::border::WorldBorder), like [`FSChunkAccess::set_pixel`].
+    pub fn set(
+        &mut self,
+        x: impl Into<i64>,
+        y: impl Into<i64>,
+        mat: MaterialInstance,
+    ) -> Result<(), String> {
+        let (x, y) = (x.into(), y.into());
+        if outside_border(&*self.chunk_handler, x, y) {
+            return Err("Position is outside the world border".into());
+        }
+        if !self.chunk_handler.is_pixel_loaded(x, y) {
+            return Err("Position is not loaded".into());
+        }
+
+        self.changes.insert((x, y), mat);
+        Ok(())
+    }
+
+    /// The pixel at `x`, `y` as it will be once this edit is committed.
+    pub fn pixel(&self, x: impl Into<i64>, y: impl Into<i64>) -> Result<&MaterialInstance, String> {
+        let (x, y) = (x.into(), y.into());
+        match self.changes.get(&(x, y)) {
+            Some(mat) => Ok(mat),
+            None => self.chunk_handler.pixel(x, y),
+        }
+    }
+
+    /// How many pixels this edit will change.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Writes every change to the world, adding a single entry to the chunk handler's
+    ///   [`EditHistory`] to undo all of them.
+    ///
+    /// Changes are grouped by chunk so each chunk is only looked up once. If any of the chunks
+    ///   isn't ready, nothing is written.
+    pub fn commit(self) -> Result<(), String> {
+        let Self { chunk_handler, changes } = self;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let undo = apply_journaled(chunk_handler, changes.into_iter().collect())?;
+        chunk_handler.edit_history.push(undo);
+        Ok(())
+    }
+}
+
+/// Like [`apply`], but also appends the changes to the world's [`EditJournal`](super::journal::EditJournal).
+pub(super) fn apply_journaled<C: Chunk>(
+    chunk_handler: &mut ChunkHandler<C>,
+    mut changes: Vec<((i64, i64), MaterialInstance)>,
+) -> Result<EditUndo, String> {
+    if chunk_handler.path.is_none() {
+        return apply(chunk_handler, changes);
+    }
+
+    // the order doesn't matter when applying, but this keeps the journal the same between runs
+    changes.sort_unstable_by_key(|((x, y), _)| (*y, *x));
+    let undo = apply(chunk_handler, changes.iter().cloned())?;
+    journal::record(chunk_handler, changes);
+    Ok(undo)
+}
+
+/// Writes `changes` to the world, returning what they replaced.
+pub(super) fn apply<C: Chunk>(
+    chunk_handler: &mut ChunkHandler<C>,
+    changes: impl IntoIterator<Item = ((i64, i64), MaterialInstance)>,
+) -> Result<EditUndo, String> {
+    let mut by_chunk: BTreeMap<ChunkKey, Vec<(i64, i64, ChunkLocalPosition, MaterialInstance)>> =
+        BTreeMap::new();
+    for ((x, y), mat) in changes {
+        // a journal written before the border moved can have pixels that are outside of it now
+        if outside_border(&*chunk_handler, x, y) {
+            continue;
+        }
+        by_chunk.entry(pixel_to_chunk_pos(x, y)).or_default().push((
+            x,
+            y,
+            pixel_to_pos_in_chunk(x, y),
+            mat,
+        ));
+    }
+
+    if let Some(key) = by_chunk.keys().find(|key| {
+        chunk_handler
+            .chunk_at(**key)
+            .map_or(true, |c| c.pixels().is_none())
+    }) {
+        return Err(format!("Chunk {key:?} is not ready yet."));
+    }
+
+    let mut undo = EditUndo { pixels: Vec::new() };
+    for (key, pixels) in by_chunk {
+        let chunk = chunk_handler.chunk_at_mut(key).unwrap();
+        for (x, y, pos, mat) in pixels {
+            // Safety: every chunk was checked to be loaded above
+            unsafe {
+                undo.pixels.push((x, y, chunk.pixel_unchecked(pos).clone()));
+                chunk.set_color(pos, mat.color);
+                chunk.set_pixel_unchecked(pos, mat);
+            }
+        }
+    }
+
+    Ok(undo)
+}
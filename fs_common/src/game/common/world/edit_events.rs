@@ -0,0 +1,92 @@
+use crate::game::common::Rect;
+
+/// How many [`WorldEditEvent`]s are kept between drains. Past this, new edits are merged into the
+///   latest event with the same cause, so nothing is lost but the rects get coarser.
+pub const MAX_WORLD_EDIT_EVENTS: usize = 256;
+
+/// What made a [`WorldEditEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldEditCause {
+    /// A player painting, erasing, cutting or pasting.
+    Tool,
+    /// Terrain broken by a hard impact.
+    Explosion,
+    /// A structure piece being placed.
+    Structure,
+}
+
+/// Part of the world was deliberately changed.
+#[derive(Debug, Clone)]
+pub struct WorldEditEvent {
+    /// The pixels that may have changed, in world coordinates. `x2` and `y2` are exclusive.
+    pub rect: Rect<i64>,
+    pub cause: WorldEditCause,
+}
+
+/// Edits made to the world since the events were last taken, stored as an ECS resource.
+///
+/// Only deliberate edits end up here (tools, impacts, structures), not pixels moving around
+///   during simulation, so anything that wants to react to changes (scripting, networking,
+///   sounds) can listen here instead of polling the chunks.
+#[derive(Debug, Default)]
+pub struct WorldEditEvents {
+    events: Vec<WorldEditEvent>,
+}
+
+impl WorldEditEvents {
+    /// Adds an edit, or once there are [`MAX_WORLD_EDIT_EVENTS`], grows the latest one with the same cause to cover it.
+    pub fn emit(&mut self, rect: Rect<i64>, cause: WorldEditCause) {
+        if self.events.len() >= MAX_WORLD_EDIT_EVENTS {
+            if let Some(last) = self.events.iter_mut().rev().find(|e| e.cause == cause) {
+                last.rect = last.rect.union(rect);
+                return;
+            }
+        }
+        self.events.push(WorldEditEvent { rect, cause });
+    }
+
+    /// Takes every event so far, oldest first.
+    pub fn drain(&mut self) -> impl Iterator<Item = WorldEditEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::Rect;
+
+    use super::{WorldEditCause, WorldEditEvents, MAX_WORLD_EDIT_EVENTS};
+
+    #[test]
+    fn events_are_bounded() {
+        let mut events = WorldEditEvents::default();
+        for x in 0..MAX_WORLD_EDIT_EVENTS as i64 * 2 {
+            events.emit(Rect::new(x, 0, x + 1, 1), WorldEditCause::Tool);
+        }
+        assert_eq!(events.len(), MAX_WORLD_EDIT_EVENTS);
+
+        // other causes still get their own event
+        events.emit(Rect::new(0, 10, 1, 11), WorldEditCause::Explosion);
+        events.emit(Rect::new(5, 10, 6, 11), WorldEditCause::Explosion);
+        assert_eq!(events.len(), MAX_WORLD_EDIT_EVENTS + 1);
+
+        // the overflow is folded into the latest event, so every edit is still covered
+        let corners = |r: Rect<i64>| (r.x1, r.y1, r.x2, r.y2);
+        let drained = events.drain().collect::<Vec<_>>();
+        let max = MAX_WORLD_EDIT_EVENTS as i64;
+        assert_eq!(
+            corners(drained[MAX_WORLD_EDIT_EVENTS - 1].rect),
+            (max - 1, 0, max * 2, 1)
+        );
+        assert_eq!(corners(drained[MAX_WORLD_EDIT_EVENTS].rect), (0, 10, 6, 11));
+        assert!(events.is_empty());
+    }
+}
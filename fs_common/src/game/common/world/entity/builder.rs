@@ -0,0 +1,160 @@
+use rapier2d::{
+    na::{Isometry2, Vector2},
+    prelude::{ColliderBuilder, RigidBodyBuilder},
+};
+use specs::{Builder, Entity, WorldExt};
+
+use crate::game::common::world::{
+    physics::PHYSICS_SCALE, Chunk, CollisionLayers, Loader, Position, RigidBodyComponent, Velocity,
+    World,
+};
+
+use super::{GameEntity, Hitbox, Persistent, PhysicsEntity};
+
+/// Shape and collision settings for the rapier body of an entity, see [`EntityBuilder::rigid_body`].
+#[derive(Debug, Clone)]
+pub struct EntityBody {
+    /// Where the body starts relative to the entity's position, in physics units.
+    pub offset: Isometry2<f32>,
+    pub layers: CollisionLayers,
+    pub density: f32,
+    pub friction: f32,
+}
+
+/// Spawns an entity with the usual component bundles.
+///
+/// Everything gets a [`Position`] and [`Velocity`]. Entities with a rigid body also need a
+///   [`Hitbox`], which the collider is shaped after.
+pub struct EntityBuilder<'w, C: Chunk> {
+    world: &'w mut World<C>,
+    pos: Position,
+    vel: Velocity,
+    physics: Option<(Hitbox, PhysicsEntity)>,
+    body: Option<EntityBody>,
+    game_entity: bool,
+    persistent: bool,
+    loader: bool,
+}
+
+impl<'w, C: Chunk> EntityBuilder<'w, C> {
+    pub fn new(world: &'w mut World<C>, pos: Position) -> Self {
+        Self {
+            world,
+            pos,
+            vel: Velocity { x: 0.0, y: 0.0 },
+            physics: None,
+            body: None,
+            game_entity: false,
+            persistent: false,
+            loader: false,
+        }
+    }
+
+    #[must_use]
+    pub fn velocity(mut self, vel: Velocity) -> Self {
+        self.vel = vel;
+        self
+    }
+
+    /// Makes it a [`GameEntity`] that moves and collides as a [`PhysicsEntity`] with the given hitbox.
+    #[must_use]
+    pub fn physics(mut self, hitbox: Hitbox, physics: PhysicsEntity) -> Self {
+        self.physics = Some((hitbox, physics));
+        self.game_entity = true;
+        self
+    }
+
+    /// Adds a rapier body in the shape of its hitbox, linked through a [`RigidBodyComponent`].
+    ///
+    /// The body has no gravity or rotation of its own, it just follows the entity around
+    ///   so rigidbodies can push against it.
+    #[must_use]
+    pub fn rigid_body(mut self, body: EntityBody) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Saved with the world, see [`Persistent`].
+    #[must_use]
+    pub fn persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+
+    /// Keeps the chunks around it loaded, see [`Loader`].
+    #[must_use]
+    pub fn loader(mut self) -> Self {
+        self.loader = true;
+        self
+    }
+
+    pub fn build(self) -> Entity {
+        self.build_with(|b| b)
+    }
+
+    /// Like [`Self::build`], with `extra` adding any other components.
+    pub fn build_with(
+        self,
+        extra: impl FnOnce(specs::EntityBuilder) -> specs::EntityBuilder,
+    ) -> Entity {
+        let body = self.body.map(|body| {
+            let (hitbox, _) = self
+                .physics
+                .as_ref()
+                .expect("Entities with a rigid body need a hitbox");
+
+            let rigid_body = RigidBodyBuilder::dynamic()
+                .position(
+                    Isometry2::new(
+                        Vector2::new(
+                            self.pos.x as f32 / PHYSICS_SCALE,
+                            self.pos.y as f32 / PHYSICS_SCALE,
+                        ),
+                        0.0,
+                    ) * body.offset,
+                )
+                .lock_rotations()
+                .gravity_scale(0.0)
+                .build();
+            let handle = self.world.physics.bodies.insert(rigid_body);
+
+            let collider = ColliderBuilder::cuboid(
+                (hitbox.x2 - hitbox.x1) / PHYSICS_SCALE / 2.0,
+                (hitbox.y2 - hitbox.y1) / PHYSICS_SCALE / 2.0,
+            )
+            .translation(Vector2::new(
+                (hitbox.x1 + hitbox.x2) / PHYSICS_SCALE / 2.0,
+                (hitbox.y1 + hitbox.y2) / PHYSICS_SCALE / 2.0,
+            ))
+            .collision_groups(body.layers.into())
+            .density(body.density)
+            .friction(body.friction)
+            .build();
+            let physics = &mut self.world.physics;
+            physics
+                .colliders
+                .insert_with_parent(collider, handle, &mut physics.bodies);
+
+            RigidBodyComponent::of(handle)
+        });
+
+        let mut builder = self.world.ecs.create_entity().with(self.pos).with(self.vel);
+        if let Some((hitbox, physics)) = self.physics {
+            builder = builder.with(hitbox).with(physics);
+        }
+        if let Some(body) = body {
+            builder = builder.with(body);
+        }
+        if self.game_entity {
+            builder = builder.with(GameEntity);
+        }
+        if self.persistent {
+            builder = builder.with(Persistent);
+        }
+        if self.loader {
+            builder = builder.with(Loader);
+        }
+
+        extra(builder).build()
+    }
+}
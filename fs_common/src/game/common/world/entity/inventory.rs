@@ -0,0 +1,235 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use specs::{storage::BTreeStorage, Component};
+
+use crate::game::common::{
+    registry::RegistryID,
+    world::material::placer::{self, MaterialPlacer},
+};
+
+pub const HOTBAR_SIZE: usize = 9;
+/// Where [`BrushPalette::save`] keeps the palette, in the game directory.
+pub const BRUSH_PALETTE_FILE: &str = "brush_palette.ron";
+
+/// Materials the number keys put in their hotbar slot when selecting it, see [`Inventory::select_brush`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrushPalette {
+    pub slots: [Option<RegistryID<MaterialPlacer>>; HOTBAR_SIZE],
+}
+
+impl Default for BrushPalette {
+    fn default() -> Self {
+        Self {
+            slots: [
+                Some(placer::AIR_PLACER.clone()),
+                Some(placer::SMOOTH_STONE.clone()),
+                Some(placer::SMOOTH_DIRT.clone()),
+                Some(placer::COBBLE_STONE.clone()),
+                Some(placer::COBBLE_DIRT.clone()),
+                Some(placer::GLASS.clone()),
+                Some(placer::LAVA.clone()),
+                None,
+                None,
+            ],
+        }
+    }
+}
+
+impl BrushPalette {
+    /// Reads a palette written by [`Self::save`], or the default one if there isn't one
+    ///   (or it can't be read).
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| ron::from_str(&s).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                log::error!("Failed to read brush palette @ {}: {e}", path.display());
+                Self::default()
+            })
+    }
+
+    /// Writes the palette to `path`, so it's kept between launches.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| format!("Failed to serialize brush palette: {e}"))?;
+        std::fs::write(path, data)
+            .map_err(|e| format!("Failed to write brush palette @ {}: {e}", path.display()))
+    }
+}
+
+/// Counts of collected materials, plus a hotbar of slots choosing which one to place.
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    counts: HashMap<RegistryID<MaterialPlacer>, u32>,
+    pub hotbar: [Option<RegistryID<MaterialPlacer>>; HOTBAR_SIZE],
+    pub selected_slot: usize,
+}
+
+impl Inventory {
+    pub fn count(&self, id: &RegistryID<MaterialPlacer>) -> u32 {
+        self.counts.get(id).copied().unwrap_or(0)
+    }
+
+    /// Adds `amount` of `id`, putting it in the first empty hotbar slot if it isn't in the hotbar yet.
+    pub fn collect(&mut self, id: RegistryID<MaterialPlacer>, amount: u32) {
+        if !self.hotbar.iter().flatten().any(|h| *h == id) {
+            if let Some(slot) = self.hotbar.iter_mut().find(|h| h.is_none()) {
+                *slot = Some(id.clone());
+            }
+        }
+
+        *self.counts.entry(id).or_insert(0) += amount;
+    }
+
+    pub fn select_slot(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE {
+            self.selected_slot = slot;
+        }
+    }
+
+    /// Selects `slot`, putting `palette`'s material for it there first if it has one.
+    pub fn select_brush(&mut self, slot: usize, palette: &BrushPalette) {
+        if let Some(Some(id)) = palette.slots.get(slot) {
+            self.hotbar[slot] = Some(id.clone());
+        }
+        self.select_slot(slot);
+    }
+
+    pub fn active(&self) -> Option<&RegistryID<MaterialPlacer>> {
+        self.hotbar[self.selected_slot].as_ref()
+    }
+
+    pub fn set_active(&mut self, id: RegistryID<MaterialPlacer>) {
+        self.hotbar[self.selected_slot] = Some(id);
+    }
+
+    pub fn in_hotbar(&self, id: &RegistryID<MaterialPlacer>) -> bool {
+        self.hotbar.iter().flatten().any(|h| h == id)
+    }
+
+    /// Takes one of `id`, returning false if there isn't any.
+    pub fn take(&mut self, id: &RegistryID<MaterialPlacer>) -> bool {
+        match self.counts.get_mut(id).filter(|c| **c > 0) {
+            Some(count) => {
+                *count -= 1;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Takes one of the active material to place it, if there is any.
+    ///
+    /// If `infinite` is true (ie. creative mode), this never runs out.
+    pub fn take_active(&mut self, infinite: bool) -> Option<RegistryID<MaterialPlacer>> {
+        let id = self.active()?.clone();
+        if !infinite && !self.take(&id) {
+            return None;
+        }
+        Some(id)
+    }
+}
+
+impl Component for Inventory {
+    type Storage = BTreeStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::material::placer;
+
+    use super::{BrushPalette, Inventory};
+
+    #[test]
+    fn collect_and_select() {
+        let mut inv = Inventory::default();
+        assert_eq!(inv.active(), None);
+
+        inv.collect(placer::COBBLE_STONE.clone(), 3);
+        inv.collect(placer::SMOOTH_DIRT.clone(), 1);
+        inv.collect(placer::COBBLE_STONE.clone(), 2);
+
+        assert_eq!(inv.count(&placer::COBBLE_STONE), 5);
+        assert_eq!(inv.count(&placer::SMOOTH_DIRT), 1);
+        assert_eq!(inv.count(&placer::SMOOTH_STONE), 0);
+
+        // collected materials fill the hotbar in order
+        assert_eq!(inv.active(), Some(&*placer::COBBLE_STONE));
+        inv.select_slot(1);
+        assert_eq!(inv.active(), Some(&*placer::SMOOTH_DIRT));
+        inv.select_slot(2);
+        assert_eq!(inv.active(), None);
+
+        // out of range does nothing
+        inv.select_slot(100);
+        assert_eq!(inv.selected_slot, 2);
+    }
+
+    #[test]
+    fn take_active() {
+        let mut inv = Inventory::default();
+        inv.collect(placer::SMOOTH_DIRT.clone(), 2);
+
+        assert_eq!(inv.take_active(false), Some(placer::SMOOTH_DIRT.clone()));
+        assert_eq!(inv.take_active(false), Some(placer::SMOOTH_DIRT.clone()));
+        assert_eq!(inv.take_active(false), None);
+        assert_eq!(inv.count(&placer::SMOOTH_DIRT), 0);
+
+        // infinite in creative
+        assert_eq!(inv.take_active(true), Some(placer::SMOOTH_DIRT.clone()));
+        assert_eq!(inv.count(&placer::SMOOTH_DIRT), 0);
+    }
+
+    #[test]
+    fn select_brush() {
+        let mut inv = Inventory::default();
+        inv.collect(placer::SMOOTH_DIRT.clone(), 1);
+
+        let mut palette = BrushPalette::default();
+        palette.slots[0] = Some(placer::GLASS.clone());
+        palette.slots[1] = None;
+
+        inv.select_brush(0, &palette);
+        assert_eq!(inv.active(), Some(&*placer::GLASS));
+
+        // unbound slots keep what they had
+        inv.hotbar[1] = Some(placer::COBBLE_DIRT.clone());
+        inv.select_brush(1, &palette);
+        assert_eq!(inv.active(), Some(&*placer::COBBLE_DIRT));
+
+        // the mapping survives a round trip
+        let bytes = bincode::serialize(&palette).unwrap();
+        assert_eq!(
+            bincode::deserialize::<BrushPalette>(&bytes).unwrap(),
+            palette
+        );
+    }
+
+    #[test]
+    fn brush_palette_saved_between_launches() {
+        let path =
+            std::env::temp_dir().join(format!("fs_brush_palette_{}.ron", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // nothing saved yet
+        assert_eq!(BrushPalette::load(&path), BrushPalette::default());
+
+        let mut palette = BrushPalette::default();
+        palette.slots[0] = Some(placer::GLASS.clone());
+        palette.slots[8] = Some(placer::SMOOTH_DIRT.clone());
+        palette.slots[1] = None;
+        palette.save(&path).unwrap();
+        assert_eq!(BrushPalette::load(&path), palette);
+
+        // a broken file falls back to the default
+        std::fs::write(&path, "not a palette").unwrap();
+        assert_eq!(BrushPalette::load(&path), BrushPalette::default());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -2,12 +2,19 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use specs::{storage::BTreeStorage, Component, Entities, Join, System, Write, WriteStorage};
 
+mod builder;
+mod inventory;
 mod player;
+pub use builder::*;
+pub use inventory::*;
 pub use player::*;
 
-use crate::game::common::world::{
-    material::{color::Color, MaterialInstance, PhysicsType},
-    pixel_to_chunk_pos,
+use crate::game::common::{
+    world::{
+        material::{color::Color, MaterialInstance, PhysicsType},
+        pixel_to_chunk_pos,
+    },
+    Rect, Registries,
 };
 
 use super::{
@@ -35,10 +42,56 @@ impl Component for Hitbox {
     type Storage = BTreeStorage<Self>;
 }
 
+/// An image the client draws for the entity, instead of its debug rectangle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sprite {
+    /// Path of the image, relative to `assets/texture/`.
+    pub texture: String,
+    pub width: f32,
+    pub height: f32,
+    /// The point of the sprite that sits on the entity's [`Position`], as a fraction of its size.
+    /// `(0.5, 0.5)` is the center, `(0.5, 1.0)` is the middle of the bottom edge.
+    pub anchor: (f32, f32),
+}
+
+impl Sprite {
+    /// A sprite centered on the entity.
+    pub fn new(texture: impl Into<String>, width: f32, height: f32) -> Self {
+        Self {
+            texture: texture.into(),
+            width,
+            height,
+            anchor: (0.5, 0.5),
+        }
+    }
+
+    #[must_use]
+    pub fn anchored(mut self, x: f32, y: f32) -> Self {
+        self.anchor = (x, y);
+        self
+    }
+
+    /// The area the sprite covers, relative to the entity's [`Position`].
+    pub fn bounds(&self) -> Rect<f32> {
+        Rect::new_wh(
+            -self.width * self.anchor.0,
+            -self.height * self.anchor.1,
+            self.width,
+            self.height,
+        )
+    }
+}
+
+impl Component for Sprite {
+    type Storage = BTreeStorage<Self>;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhysicsEntity {
     pub gravity: f64,
     pub on_ground: bool,
+    /// How close to the bottom (or top) of the hitbox something it walks into can be for the
+    ///   entity to step over (or under) it instead of stopping, in pixels. 0 disables stepping.
     pub edge_clip_distance: f32,
     pub collision: bool,
     pub collide_with_sand: bool,
@@ -67,12 +120,19 @@ impl Component for Persistent {
 
 pub struct UpdatePhysicsEntities<'a, H: FSChunkAccess> {
     pub chunk_handler: &'a mut H,
+    /// For looking up which solids entities pass through, see [`Material::entity_collidable`](super::material::Material::entity_collidable).
+    pub registries: &'a Registries,
 }
 
 impl<'a, H: FSChunkAccess> UpdatePhysicsEntities<'a, H> {
     fn check_collide(&self, x: i64, y: i64, phys_ent: &PhysicsEntity) -> Option<&MaterialInstance> {
         self.chunk_handler.pixel(x, y).ok().filter(|mat| {
-            mat.physics == PhysicsType::Solid
+            (mat.physics == PhysicsType::Solid
+                && self
+                    .registries
+                    .materials
+                    .get(&mat.material_id)
+                    .map_or(true, |m| m.entity_collidable))
                 || (mat.physics == PhysicsType::Sand && phys_ent.collide_with_sand)
         })
     }
@@ -133,6 +193,10 @@ impl<'a, H: FSChunkAccess> System<'a> for UpdatePhysicsEntities<'a, H> {
                         hitbox,
                         collision_detect,
                     );
+
+                    if let Some(border) = self.chunk_handler.border() {
+                        border.clamp_entity(pos, vel, hitbox);
+                    }
                 },
             );
 
@@ -206,14 +270,21 @@ impl<H: FSChunkAccess> UpdatePhysicsEntities<'_, H> {
         let mut new_pos_x = pos.x;
         let mut new_pos_y = pos.y;
 
+        // each step moves less than a pixel (and so less than the hitbox) on each axis,
+        //   so fast entities can't skip over thin walls
         let steps = ((dx.abs() + dy.abs()) as u32 + 1).max(3);
+        let mut step_x = dx / f64::from(steps);
+        let mut step_y = dy / f64::from(steps);
         for _ in 0..steps {
-            new_pos_x += dx / f64::from(steps);
-            new_pos_y += dy / f64::from(steps);
+            new_pos_x += step_x;
+            new_pos_y += step_y;
 
             // check x motion
 
             let mut collided_x = false;
+            // the highest step up and lowest step down that would get past what was hit
+            let mut clip_up: Option<f64> = None;
+            let mut clip_down: Option<f64> = None;
             for &(h_dx, h_dy) in &r {
                 if let Some(mat) = self
                     .check_collide(
@@ -223,50 +294,17 @@ impl<H: FSChunkAccess> UpdatePhysicsEntities<'_, H> {
                     )
                     .cloned()
                 {
-                    let clip_ceil = (h_dy - hitbox.y1 < phys_ent.edge_clip_distance).then(|| {
-                        ((pos.y + f64::from(h_dy)).floor() + 1.0) - (pos.y + f64::from(hitbox.y1))
-                            + 0.05
-                    });
-                    let clip_floor = (hitbox.y2 - h_dy < phys_ent.edge_clip_distance).then(|| {
-                        (pos.y + f64::from(h_dy)).floor() - (pos.y + f64::from(hitbox.y2)) - 0.05
-                    });
-
-                    if let Some(clip_y) = clip_ceil.or(clip_floor) {
-                        let mut would_clip_collide = false;
-                        for &(h_dx, h_dy) in &r {
-                            if let Some(mat) = self
-                                .check_collide(
-                                    (new_pos_x + f64::from(h_dx)).floor() as i64,
-                                    (pos.y + clip_y + f64::from(h_dy)).floor() as i64,
-                                    phys_ent,
-                                )
-                                .cloned()
-                            {
-                                would_clip_collide = true;
-                                if DEBUG_VISUALIZE {
-                                    let _ignore = self.chunk_handler.set_pixel(
-                                        (new_pos_x + f64::from(h_dx)).floor() as i64,
-                                        (pos.y + clip_y + f64::from(h_dy)).floor() as i64,
-                                        MaterialInstance { color: Color::rgb(255, 255, 0), ..mat },
-                                    );
-                                }
-                                break;
-                            }
-                        }
-
-                        if would_clip_collide {
-                            collided_x = true;
-                        } else {
-                            new_pos_y += clip_y;
-                            pos.y += clip_y;
-
-                            // larger step means more slowdown
-                            // 1.0 -> 0.988
-                            // 2.0 -> 0.8
-                            // 2.5 -> 0.515
-                            // 3.0 -> 0.5 (clamped)
-                            vel.x *= (1.0 - (clip_y.abs() / 3.0).powi(4)).clamp(0.5, 1.0);
-                        }
+                    let edge_clip = f64::from(phys_ent.edge_clip_distance);
+                    let clip_floor =
+                        (pos.y + f64::from(h_dy)).floor() - (pos.y + f64::from(hitbox.y2)) - 0.05;
+                    let clip_ceil = ((pos.y + f64::from(h_dy)).floor() + 1.0)
+                        - (pos.y + f64::from(hitbox.y1))
+                        + 0.05;
+
+                    if -clip_floor <= edge_clip {
+                        clip_up = Some(clip_up.map_or(clip_floor, |c| c.min(clip_floor)));
+                    } else if clip_ceil <= edge_clip {
+                        clip_down = Some(clip_down.map_or(clip_ceil, |c| c.max(clip_ceil)));
                     } else if mat.physics == PhysicsType::Sand
                         && self
                             .chunk_handler
@@ -303,11 +341,64 @@ impl<H: FSChunkAccess> UpdatePhysicsEntities<'_, H> {
                 }
             }
 
+            // something to step over at both the top and bottom means there's no room to get through
+            let clip = match (clip_up, clip_down) {
+                (Some(clip_y), None) | (None, Some(clip_y)) => Some(clip_y),
+                (Some(_), Some(_)) => {
+                    collided_x = true;
+                    None
+                },
+                (None, None) => None,
+            };
+
+            if let (false, Some(clip_y)) = (collided_x, clip) {
+                let mut would_clip_collide = false;
+                for &(h_dx, h_dy) in &r {
+                    if let Some(mat) = self
+                        .check_collide(
+                            (new_pos_x + f64::from(h_dx)).floor() as i64,
+                            (pos.y + clip_y + f64::from(h_dy)).floor() as i64,
+                            phys_ent,
+                        )
+                        .cloned()
+                    {
+                        would_clip_collide = true;
+                        if DEBUG_VISUALIZE {
+                            let _ignore = self.chunk_handler.set_pixel(
+                                (new_pos_x + f64::from(h_dx)).floor() as i64,
+                                (pos.y + clip_y + f64::from(h_dy)).floor() as i64,
+                                MaterialInstance { color: Color::rgb(255, 255, 0), ..mat },
+                            );
+                        }
+                        break;
+                    }
+                }
+
+                if would_clip_collide {
+                    collided_x = true;
+                } else {
+                    new_pos_y += clip_y;
+                    pos.y += clip_y;
+
+                    // larger step means more slowdown
+                    // 1.0 -> 0.988
+                    // 2.0 -> 0.8
+                    // 2.5 -> 0.515
+                    // 3.0 -> 0.5 (clamped)
+                    vel.x *= (1.0 - (clip_y.abs() / 3.0).powi(4)).clamp(0.5, 1.0);
+                }
+            }
+
             if collided_x {
                 vel.x = if vel.x.abs() > 0.25 { vel.x * 0.5 } else { 0.0 };
                 if let Some(c) = &mut collision_detect {
                     c.collided = true;
                 }
+
+                // stop at the wall for the rest of the tick, otherwise the next steps carry on
+                //   into it and come out the other side once the whole hitbox is past it
+                new_pos_x = pos.x;
+                step_x = 0.0;
             } else {
                 pos.x = new_pos_x;
             }
@@ -378,6 +469,9 @@ impl<H: FSChunkAccess> UpdatePhysicsEntities<'_, H> {
                 if let Some(c) = &mut collision_detect {
                     c.collided = true;
                 }
+
+                new_pos_y = pos.y;
+                step_y = 0.0;
             } else {
                 pos.y = new_pos_y;
             }
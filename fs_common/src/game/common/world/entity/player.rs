@@ -1,16 +1,18 @@
-use rapier2d::{
-    na::Isometry2,
-    prelude::{ColliderBuilder, InteractionGroups, RigidBodyBuilder},
-};
+use rapier2d::na::Isometry2;
 use serde::{Deserialize, Serialize};
-use specs::{storage::BTreeStorage, Builder, Component, Entity, WorldExt};
-
-use crate::game::common::world::{
-    material::buf::MaterialBuf, physics::PHYSICS_SCALE, Chunk, CollisionFlags, Loader, Position,
-    RigidBodyComponent, Velocity, World,
+use specs::{storage::BTreeStorage, Builder, Component, Entity, Join, ReadStorage, WriteStorage};
+
+use crate::game::common::{
+    world::{
+        chunk_access::FSChunkAccess,
+        chunk_handler::ChunkHandler,
+        material::{buf::MaterialBuf, MaterialInstance, PhysicsType},
+        Chunk, CollisionLayers, Position, Velocity, World,
+    },
+    Settings,
 };
 
-use super::{GameEntity, Hitbox, Persistent, PhysicsEntity};
+use super::{EntityBody, EntityBuilder, Hitbox, Inventory, PhysicsEntity};
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum PlayerJumpState {
@@ -32,6 +34,8 @@ pub enum PlayerGrappleState {
     Out {
         can_cancel: bool,
         entity: Entity,
+        /// The pixel the hook is stuck in, or `None` while it's still flying.
+        anchor: Option<(i64, i64)>,
         tether_length: f64,
         desired_tether_length: f64,
         pivots: Vec<Position>,
@@ -42,6 +46,79 @@ pub enum PlayerGrappleState {
     Used,
 }
 
+/// Whether a grapple hook can hold onto `mat`.
+pub fn grapple_attaches_to(mat: &MaterialInstance) -> bool {
+    mat.physics == PhysicsType::Solid
+}
+
+/// Checks the path a flying grapple hook takes this tick (`pos` to `pos + vel`) for a pixel to
+///   attach to, see [`ChunkHandler::raycast_filtered`].
+///
+/// If there is one, the hook is stopped just in front of it and the pixel is returned as the anchor.
+pub fn grapple_hook_step<C: Chunk>(
+    chunk_handler: &ChunkHandler<C>,
+    pos: &mut Position,
+    vel: &mut Velocity,
+) -> Option<(i64, i64)> {
+    let from = (pos.x.floor() as i64, pos.y.floor() as i64);
+    let to = (
+        (pos.x + vel.x).floor() as i64,
+        (pos.y + vel.y).floor() as i64,
+    );
+    let (anchor, _) =
+        chunk_handler.raycast_filtered(from, to, |_, mat| grapple_attaches_to(mat))?;
+
+    // back off towards where it came from so the rope ends outside the pixel
+    let speed = vel.x.hypot(vel.y).max(f64::EPSILON);
+    pos.x = anchor.0 as f64 + 0.5 - vel.x / speed * 1.5;
+    pos.y = anchor.1 as f64 + 0.5 - vel.y / speed * 1.5;
+    *vel = Velocity { x: 0.0, y: 0.0 };
+
+    Some(anchor)
+}
+
+/// Whether the pixel a grapple is anchored to is still there to hold it.
+pub fn grapple_anchor_holds<C: Chunk>(chunk_handler: &ChunkHandler<C>, anchor: (i64, i64)) -> bool {
+    chunk_handler
+        .pixel(anchor.0, anchor.1)
+        .map_or(false, grapple_attaches_to)
+}
+
+/// Where a rope from `from` to `to` has to bend around a solid corner, if anywhere.
+///
+/// The pivot is put just outside the corner, on the side the rope is wrapping around.
+pub fn grapple_wrap_pivot<C: Chunk>(
+    chunk_handler: &ChunkHandler<C>,
+    from: &Position,
+    to: &Position,
+) -> Option<Position> {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let mag = dx.hypot(dy);
+    if mag < f64::EPSILON {
+        return None;
+    }
+
+    let ((x, y), _) = chunk_handler.raycast_filtered(
+        (from.x as i64, from.y as i64),
+        (to.x as i64, to.y as i64),
+        |_, mat| grapple_attaches_to(mat),
+    )?;
+
+    let side = chunk_handler.pixel(x + (dy / mag * 2.0) as i64, y + (-dx / mag * 2.0) as i64);
+    Some(if side.map_or(false, grapple_attaches_to) {
+        Position {
+            x: x as f64 + (-dy / mag) * 2.0,
+            y: y as f64 + (dx / mag) * 2.0,
+        }
+    } else {
+        Position {
+            x: x as f64 + (dy / mag) * 2.0,
+            y: y as f64 + (-dx / mag) * 2.0,
+        }
+    })
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum PlayerMovementMode {
     Normal {
@@ -110,60 +187,54 @@ pub struct Player {
 }
 
 impl Player {
+    /// Spawns a player at the default spawn point, see [`spawn_player`].
     pub fn create_and_add<C: Chunk>(world: &mut World<C>) -> Entity {
-        let rigid_body = RigidBodyBuilder::dynamic()
-            .position(Isometry2::new([0.0, 20.0].into(), 0.0))
-            .lock_rotations()
-            .gravity_scale(0.0)
-            .build();
-        let handle = world.physics.bodies.insert(rigid_body);
-        let collider =
-            ColliderBuilder::cuboid(11.9 / PHYSICS_SCALE / 2.0, 19.9 / PHYSICS_SCALE / 2.0)
-                .collision_groups(InteractionGroups::new(
-                    CollisionFlags::PLAYER.bits().into(),
-                    (CollisionFlags::RIGIDBODY | CollisionFlags::ENTITY)
-                        .bits()
-                        .into(),
-                ))
-                .density(1.5)
-                .friction(0.3)
-                .build();
-        let _co_handle =
-            world
-                .physics
-                .colliders
-                .insert_with_parent(collider, handle, &mut world.physics.bodies);
-
-        let player = world
-            .ecs
-            .create_entity()
-            .with(Player {
-                movement: PlayerMovementMode::default_normal(),
-                clipboard: PlayerClipboard::default(),
-            })
-            .with(GameEntity)
-            .with(PhysicsEntity {
+        spawn_player(world, Position { x: 0.0, y: -20.0 })
+    }
+
+    /// Updates every player's [`PhysicsEntity`] to match the player settings.
+    pub fn apply_settings(ecs: &specs::World, settings: &Settings) {
+        let (players, mut phys_ents) =
+            ecs.system_data::<(ReadStorage<Player>, WriteStorage<PhysicsEntity>)>();
+        for (_, phys_ent) in (&players, &mut phys_ents).join() {
+            phys_ent.edge_clip_distance = settings.player_edge_clip_distance;
+        }
+    }
+}
+
+/// Spawns a player entity with its physics body at `pos`.
+pub fn spawn_player<C: Chunk>(world: &mut World<C>, pos: Position) -> Entity {
+    EntityBuilder::new(world, pos)
+        .physics(
+            Hitbox {
+                x1: -11.9 / 2.0,
+                y1: -19.9 / 2.0,
+                x2: 11.9 / 2.0,
+                y2: 19.9 / 2.0,
+            },
+            PhysicsEntity {
                 on_ground: false,
                 gravity: 0.5,
                 edge_clip_distance: 2.0,
                 collision: true,
                 collide_with_sand: true,
+            },
+        )
+        .rigid_body(EntityBody {
+            offset: Isometry2::new([0.0, 20.0].into(), 0.0),
+            layers: CollisionLayers::player(),
+            density: 1.5,
+            friction: 0.3,
+        })
+        .persistent()
+        .loader()
+        .build_with(|b| {
+            b.with(Player {
+                movement: PlayerMovementMode::default_normal(),
+                clipboard: PlayerClipboard::default(),
             })
-            .with(Persistent)
-            .with(Position { x: 0.0, y: -20.0 })
-            .with(Velocity { x: 0.0, y: 0.0 })
-            .with(Hitbox {
-                x1: -11.9 / 2.0,
-                y1: -19.9 / 2.0,
-                x2: 11.9 / 2.0,
-                y2: 19.9 / 2.0,
-            })
-            .with(Loader)
-            .with(RigidBodyComponent::of(handle))
-            .build();
-
-        player
-    }
+            .with(Inventory::default())
+        })
 }
 
 impl Component for Player {
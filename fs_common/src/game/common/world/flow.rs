@@ -0,0 +1,45 @@
+use super::{material::color::Color, CHUNK_AREA};
+
+/// How recently each pixel of a chunk moved, for the flow debug overlay (see `Settings::draw_flow_overlay`).
+///
+/// The simulator sets a pixel to [`FLOW_MOVED`] whenever it writes to it,
+///   and [`decay`] brings every pixel a bit closer to 0 once per tick.
+pub type FlowBuffer = [u8; CHUNK_AREA];
+
+/// The value of a pixel that moved this tick.
+pub const FLOW_MOVED: u8 = u8::MAX;
+
+/// How much [`decay`] takes away each tick, so a pixel fades out about 50 ticks after it stops moving.
+pub const FLOW_DECAY: u8 = 5;
+
+pub fn new_buffer() -> Box<FlowBuffer> {
+    Box::new([0; CHUNK_AREA])
+}
+
+pub fn decay(flow: &mut FlowBuffer) {
+    for v in flow.iter_mut() {
+        *v = v.saturating_sub(FLOW_DECAY);
+    }
+}
+
+/// The overlay color for a flow value, from transparent blue (long settled) to red (just moved).
+pub fn color(value: u8) -> Color {
+    Color::rgba_const(value, 64, 255 - value, value / 2 + 64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decay, new_buffer, FLOW_DECAY, FLOW_MOVED};
+
+    #[test]
+    fn decay_saturates() {
+        let mut flow = new_buffer();
+        flow[0] = FLOW_MOVED;
+        flow[1] = FLOW_DECAY - 1;
+
+        decay(&mut flow);
+        assert_eq!(flow[0], FLOW_MOVED - FLOW_DECAY);
+        assert_eq!(flow[1], 0);
+        assert_eq!(flow[2], 0);
+    }
+}
@@ -9,7 +9,7 @@ use crate::game::common::{
             placer::{self, MaterialPlacer, MaterialPlacerSampler},
             MaterialInstance,
         },
-        pixel_to_chunk_pos, CHUNK_SIZE,
+        pixel_to_chunk_pos, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     FileHelper, Registries,
 };
@@ -80,8 +80,8 @@ impl BiomeRegistry {
         let (chunk_x, chunk_y) = pixel_to_chunk_pos(x, y);
 
         let (center_biome_point_x, center_biome_point_y) = nearest_biome_point_to(
-            (i64::from(chunk_x) * i64::from(CHUNK_SIZE)) + i64::from(CHUNK_SIZE / 2),
-            (i64::from(chunk_y) * i64::from(CHUNK_SIZE)) + i64::from(CHUNK_SIZE / 2),
+            (i64::from(chunk_x) * i64::from(CHUNK_WIDTH)) + i64::from(CHUNK_WIDTH / 2),
+            (i64::from(chunk_y) * i64::from(CHUNK_HEIGHT)) + i64::from(CHUNK_HEIGHT / 2),
         );
 
         let base_pts = (-2..=2)
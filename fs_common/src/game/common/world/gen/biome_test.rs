@@ -13,7 +13,7 @@ use crate::game::common::world::{
 use chunksystem::ChunkKey;
 use rand::Rng;
 
-use crate::game::common::world::CHUNK_SIZE;
+use crate::game::common::world::{CHUNK_HEIGHT, CHUNK_WIDTH};
 
 use super::{
     feature::{
@@ -52,7 +52,7 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
         populators.add(PlaceAbovePopulator {
             add_surface_height: 1,
             replace_surface_depth: 2,
-            searching_for: |m| m.material_id == *material::SMOOTH_DIRT,
+            searching_for: |m, _| m.material_id == *material::SMOOTH_DIRT,
             replace: |_mat, x, y, registries| {
                 Some(
                     registries
@@ -65,9 +65,9 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
         });
 
         populators.add(StalactitePopulator {
-            searching_for: |m| m.material_id == *material::SMOOTH_STONE,
+            searching_for: |m, _| m.material_id == *material::SMOOTH_STONE,
             replace: |mat, x, y, registries| {
-                if mat.material_id == *material::AIR {
+                if mat.is_empty(&registries.materials) {
                     Some(
                         registries
                             .material_placers
@@ -83,7 +83,7 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
 
         populators.add(NearbyReplacePopulator {
             radius: 10,
-            searching_for: |m| m.material_id == *material::AIR,
+            searching_for: |m, registries| m.is_empty(&registries.materials),
             replace: |mat, x, y, registries| {
                 if mat.material_id == *material::SMOOTH_STONE {
                     Some(
@@ -109,7 +109,7 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
 
         populators.add(NearbyReplacePopulator {
             radius: 6,
-            searching_for: |m| m.material_id == *material::AIR,
+            searching_for: |m, registries| m.is_empty(&registries.materials),
             replace: |mat, x, y, registries| {
                 if mat.material_id == *material::SMOOTH_STONE
                     || mat.material_id == *material::FADED_COBBLE_STONE
@@ -146,7 +146,7 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
             PlacedFeature::new(Blob::new(
                 placer::SMOOTH_DIRT.clone(),
                 Arc::new(|rng| rng.gen_range(16..64)),
-                Arc::new(|m| m.physics == PhysicsType::Solid),
+                Arc::new(|m, _| m.physics == PhysicsType::Solid),
                 false,
             ))
             .placement(Chance(0.25))
@@ -156,7 +156,7 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
             PlacedFeature::new(Blob::new(
                 placer::TEST_PLACER_2.clone(),
                 Arc::new(|rng| rng.gen_range(10..32)),
-                Arc::new(|m| m.physics == PhysicsType::Solid),
+                Arc::new(|m, _| m.physics == PhysicsType::Solid),
                 true,
             ))
             .placement(Chance(0.5))
@@ -174,13 +174,13 @@ impl<C: Chunk + 'static> BiomeTestGenerator<C> {
                 .placement(Spread {
                     count: 3,
                     min_dist: 10.0,
-                    x: 2..i32::from(CHUNK_SIZE) - 2,
+                    x: 2..i32::from(CHUNK_WIDTH) - 2,
                     y: 0..1,
                 })
                 .placement(RandomOffset::chunk_y())
-                .placement(OnGround { max_distance: Some(u32::from(CHUNK_SIZE / 2)) })
+                .placement(OnGround { max_distance: Some(u32::from(CHUNK_HEIGHT / 2)) })
                 .placement(MaterialMatchRange {
-                    matcher: MaterialMatch::physics(PhysicsType::Air),
+                    matcher: MaterialMatch::empty(),
                     x: 0..1,
                     y: -10..0,
                 }),
@@ -201,11 +201,11 @@ impl<C: Chunk + Send + Sync> WorldGenerator<C> for BiomeTestGenerator<C> {
     #[allow(clippy::cast_lossless)]
     #[profiling::function]
     fn generate(&self, chunk_pos: ChunkKey, mut buf: GenBuffers, ctx: GenContext) {
-        let chunk_pixel_x = chunk_pos.0 as i64 * CHUNK_SIZE as i64;
-        let chunk_pixel_y = chunk_pos.1 as i64 * CHUNK_SIZE as i64;
+        let chunk_pixel_x = chunk_pos.0 as i64 * CHUNK_WIDTH as i64;
+        let chunk_pixel_y = chunk_pos.1 as i64 * CHUNK_HEIGHT as i64;
 
         // `biome_block` always returns Vec with size W*H, but this cannot be expressed until `generic_const_exprs` is stable
-        let Ok(biomes): Result<[_; CHUNK_AREA], _> = ctx.registries.biomes.biome_block::<CHUNK_SIZE, CHUNK_SIZE>(
+        let Ok(biomes): Result<[_; CHUNK_AREA], _> = ctx.registries.biomes.biome_block::<CHUNK_WIDTH, CHUNK_HEIGHT>(
             chunk_pixel_x,
             chunk_pixel_y,
             ctx.seed,
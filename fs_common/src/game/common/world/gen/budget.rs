@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+/// Limits how long chunk population and meshing can run on the main thread in one tick,
+///   so loading lots of chunks at once is spread over several ticks instead of causing a hitch.
+///
+/// The first piece of work in a tick is always allowed, so generation keeps making progress
+///   no matter how small the budget is.
+#[derive(Debug, Clone)]
+pub struct GenBudget {
+    start: Instant,
+    limit: Duration,
+    spent: usize,
+}
+
+impl GenBudget {
+    pub fn new(limit: Duration) -> Self {
+        Self { start: Instant::now(), limit, spent: 0 }
+    }
+
+    /// A budget of `ms` milliseconds, negative values count as 0.
+    pub fn from_ms(ms: f32) -> Self {
+        Self::new(Duration::from_secs_f32(ms.max(0.0) / 1000.0))
+    }
+
+    /// Whether there's time left for another piece of work.
+    pub fn has_time(&self) -> bool {
+        self.spent == 0 || self.start.elapsed() < self.limit
+    }
+
+    /// Records that a piece of work was done.
+    pub fn spend(&mut self) {
+        self.spent += 1;
+    }
+
+    /// How many pieces of work were done so far.
+    pub fn spent(&self) -> usize {
+        self.spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::GenBudget;
+
+    #[test]
+    fn always_allows_one() {
+        let mut budget = GenBudget::new(Duration::ZERO);
+        assert!(budget.has_time());
+        budget.spend();
+        assert!(!budget.has_time());
+        assert_eq!(budget.spent(), 1);
+
+        let mut budget = GenBudget::from_ms(60_000.0);
+        for _ in 0..100 {
+            assert!(budget.has_time());
+            budget.spend();
+        }
+    }
+}
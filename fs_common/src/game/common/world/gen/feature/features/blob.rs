@@ -12,11 +12,8 @@ use crate::game::common::{
             },
             populator::ChunkContext,
         },
-        material::{
-            self,
-            placer::{MaterialPlacer, MaterialPlacerSampler},
-        },
-        Chunk, CHUNK_SIZE,
+        material::placer::{MaterialPlacer, MaterialPlacerSampler},
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -58,8 +55,8 @@ impl<C: Chunk> ConfiguredFeature<C> for Blob {
         _ecs: &mut specs::World,
     ) {
         let (chunk_x, chunk_y) = chunks.center_chunk();
-        let chunk_pixel_x = chunk_x * i32::from(CHUNK_SIZE);
-        let chunk_pixel_y = chunk_y * i32::from(CHUNK_SIZE);
+        let chunk_pixel_x = chunk_x * i32::from(CHUNK_WIDTH);
+        let chunk_pixel_y = chunk_y * i32::from(CHUNK_HEIGHT);
         let cofs_x = chunk_pixel_x as f32 + pos.0 as f32;
         let cofs_y = chunk_pixel_y as f32 + pos.1 as f32;
 
@@ -99,13 +96,12 @@ impl<C: Chunk> ConfiguredFeature<C> for Blob {
                     let x = pos.0 + dx;
                     let y = pos.1 + dy;
 
-                    if (self.replace)(chunks.get(x, y).unwrap()) {
+                    if (self.replace)(chunks.get(x, y).unwrap(), registries) {
                         let air_below = self.check_air_below
                             && (1..=4).any(|i| {
                                 chunks
                                     .get(x, y + i)
-                                    .map(|m| m.material_id == *material::AIR)
-                                    .unwrap_or(false)
+                                    .map_or(false, |m| m.is_empty(&registries.materials))
                             });
 
                         if !air_below {
@@ -10,7 +10,7 @@ use crate::game::common::{
                 ConfiguredStructure, ConfiguredStructurePlaceContext, ConfiguredStructurePlacer,
             },
         },
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -37,8 +37,8 @@ impl<C: Chunk> ConfiguredFeature<C> for ConfiguredStructureFeature {
         ecs: &mut specs::World,
     ) {
         let (cx, cy) = chunks.center_chunk();
-        let x = i64::from(cx * i32::from(CHUNK_SIZE)) + i64::from(pos.0);
-        let y = i64::from(cy * i32::from(CHUNK_SIZE)) + i64::from(pos.1);
+        let x = i64::from(cx * i32::from(CHUNK_WIDTH)) + i64::from(pos.0);
+        let y = i64::from(cy * i32::from(CHUNK_HEIGHT)) + i64::from(pos.1);
 
         let configured_structure = registries
             .configured_structures
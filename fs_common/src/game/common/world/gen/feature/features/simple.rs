@@ -5,7 +5,7 @@ use crate::game::common::{
     world::{
         gen::{feature::ConfiguredFeature, populator::ChunkContext},
         material::placer::MaterialPlacer,
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -38,8 +38,8 @@ impl<C: Chunk> ConfiguredFeature<C> for SinglePixel {
             .unwrap()
             .sampler
             .pixel(
-                i64::from(cx * i32::from(CHUNK_SIZE)) + i64::from(pos.0),
-                i64::from(cy * i32::from(CHUNK_SIZE)) + i64::from(pos.1),
+                i64::from(cx * i32::from(CHUNK_WIDTH)) + i64::from(pos.0),
+                i64::from(cy * i32::from(CHUNK_HEIGHT)) + i64::from(pos.1),
             );
         let _: Result<(), _> = chunks.set(pos.0, pos.1, m);
     }
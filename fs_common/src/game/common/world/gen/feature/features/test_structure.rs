@@ -7,7 +7,7 @@ use crate::game::common::{
                 ConfiguredStructurePlaceContext, ConfiguredStructurePlacer,
             },
         },
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -26,8 +26,8 @@ impl<C: Chunk> ConfiguredFeature<C> for TestStructure {
         ecs: &mut specs::World,
     ) {
         let (cx, cy) = chunks.center_chunk();
-        let x = i64::from(cx * i32::from(CHUNK_SIZE)) + i64::from(pos.0);
-        let y = i64::from(cy * i32::from(CHUNK_SIZE)) + i64::from(pos.1);
+        let x = i64::from(cx * i32::from(CHUNK_WIDTH)) + i64::from(pos.0);
+        let y = i64::from(cy * i32::from(CHUNK_HEIGHT)) + i64::from(pos.1);
 
         for (_, v) in &registries.structure_sets {
             if v.should_generate_at((cx, cy), world_seed as _, registries, true) {
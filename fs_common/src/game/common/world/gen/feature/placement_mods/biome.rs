@@ -4,7 +4,7 @@ use crate::game::common::{
     registry::RegistryID,
     world::{
         gen::{biome::Biome, feature::PlacementModifier, populator::ChunkContext},
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -41,8 +41,10 @@ impl<C: Chunk> PlacementModifier<C> for BiomeMatch {
         _rng: &mut dyn rand::RngCore,
         registries: &Registries,
     ) -> Vec<(i32, i32)> {
-        let world_x = i64::from(chunks.center_chunk().0) * i64::from(CHUNK_SIZE) + i64::from(pos.0);
-        let world_y = i64::from(chunks.center_chunk().1) * i64::from(CHUNK_SIZE) + i64::from(pos.1);
+        let world_x =
+            i64::from(chunks.center_chunk().0) * i64::from(CHUNK_WIDTH) + i64::from(pos.0);
+        let world_y =
+            i64::from(chunks.center_chunk().1) * i64::from(CHUNK_HEIGHT) + i64::from(pos.1);
 
         let (biome_id, biome) = registries.biomes.biome_at(world_x, world_y, seed);
 
@@ -4,13 +4,13 @@ use crate::game::common::{
     registry::RegistryID,
     world::{
         gen::{feature::PlacementModifier, populator::ChunkContext},
-        material::{self, Material, MaterialInstance, PhysicsType},
+        material::{Material, MaterialInstance, PhysicsType},
         Chunk,
     },
     Registries,
 };
 
-pub type MaterialMatchFn = dyn Fn(&MaterialInstance) -> bool + Send + Sync;
+pub type MaterialMatchFn = dyn Fn(&MaterialInstance, &Registries) -> bool + Send + Sync;
 
 pub struct MaterialMatch {
     predicate: Arc<MaterialMatchFn>,
@@ -21,16 +21,20 @@ impl MaterialMatch {
         Self { predicate }
     }
 
+    pub fn empty() -> Self {
+        Self::new(Arc::new(|m, registries| m.is_empty(&registries.materials)))
+    }
+
     pub fn non_air() -> Self {
-        Self::new(Arc::new(|m| m.material_id != *material::AIR))
+        Self::new(Arc::new(|m, registries| !m.is_empty(&registries.materials)))
     }
 
     pub fn physics(typ: PhysicsType) -> Self {
-        Self::new(Arc::new(move |m| m.physics == typ))
+        Self::new(Arc::new(move |m, _| m.physics == typ))
     }
 
     pub fn material(mat: RegistryID<Material>) -> Self {
-        Self::new(Arc::new(move |m| m.material_id == mat))
+        Self::new(Arc::new(move |m, _| m.material_id == mat))
     }
 }
 
@@ -47,9 +51,9 @@ impl<C: Chunk> PlacementModifier<C> for MaterialMatch {
         pos: (i32, i32),
         _seed: i32,
         _rng: &mut dyn rand::RngCore,
-        _registries: &Registries,
+        registries: &Registries,
     ) -> Vec<(i32, i32)> {
-        if (self.predicate)(chunks.get(pos.0, pos.1).unwrap()) {
+        if (self.predicate)(chunks.get(pos.0, pos.1).unwrap(), registries) {
             vec![pos]
         } else {
             vec![]
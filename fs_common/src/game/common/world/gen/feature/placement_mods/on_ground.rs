@@ -2,7 +2,7 @@ use crate::game::common::{
     world::{
         gen::{feature::PlacementModifier, populator::ChunkContext},
         material::PhysicsType,
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT,
     },
     Registries,
 };
@@ -25,7 +25,7 @@ impl<C: Chunk> PlacementModifier<C> for OnGround {
         while chunks.get(pos.0, pos.1).unwrap().physics != PhysicsType::Solid {
             dist += 1;
 
-            if pos.1 + 1 >= i32::from(CHUNK_SIZE * 2)
+            if pos.1 + 1 >= i32::from(CHUNK_HEIGHT * 2)
                 || (self.max_distance.is_some() && dist > self.max_distance.unwrap())
             {
                 return vec![];
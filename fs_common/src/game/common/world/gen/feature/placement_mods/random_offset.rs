@@ -5,7 +5,7 @@ use rand::{Rng, RngCore};
 use crate::game::common::{
     world::{
         gen::{feature::PlacementModifier, populator::ChunkContext},
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -23,17 +23,17 @@ impl RandomOffset {
 
     pub fn chunk() -> Self {
         Self {
-            x: 0..i32::from(CHUNK_SIZE),
-            y: 0..i32::from(CHUNK_SIZE),
+            x: 0..i32::from(CHUNK_WIDTH),
+            y: 0..i32::from(CHUNK_HEIGHT),
         }
     }
 
     pub fn chunk_x() -> Self {
-        Self { x: 0..i32::from(CHUNK_SIZE), y: 0..1 }
+        Self { x: 0..i32::from(CHUNK_WIDTH), y: 0..1 }
     }
 
     pub fn chunk_y() -> Self {
-        Self { x: 0..1, y: 0..i32::from(CHUNK_SIZE) }
+        Self { x: 0..1, y: 0..i32::from(CHUNK_HEIGHT) }
     }
 }
 
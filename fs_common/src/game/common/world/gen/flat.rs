@@ -0,0 +1,63 @@
+use chunksystem::ChunkKey;
+
+use crate::game::common::world::{
+    chunk_index::ChunkLocalPosition, material::MaterialInstance, Chunk, CHUNK_HEIGHT,
+};
+
+use super::{feature::PlacedFeature, GenBuffers, GenContext, PopulatorList, WorldGenerator};
+
+/// Generates the same flat ground everywhere (or nothing at all), see
+///   [`WorldPreset`](crate::game::common::world::WorldPreset).
+#[derive(Debug)]
+pub struct FlatGenerator<C: Chunk> {
+    /// The y of the top row of ground, and what the ground is made of.
+    ground: Option<(i64, MaterialInstance)>,
+    populators: PopulatorList<C>,
+}
+
+impl<C: Chunk + 'static> FlatGenerator<C> {
+    /// Everything at or below `height` (so with a y of at least `height`) is `material`.
+    pub fn new(height: i64, material: MaterialInstance) -> Self {
+        Self {
+            ground: Some((height, material)),
+            populators: PopulatorList::new(),
+        }
+    }
+
+    /// Only air.
+    pub fn empty() -> Self {
+        Self { ground: None, populators: PopulatorList::new() }
+    }
+}
+
+impl<C: Chunk + Send + Sync> WorldGenerator<C> for FlatGenerator<C> {
+    fn generate(&self, chunk_pos: ChunkKey, mut buf: GenBuffers, _ctx: GenContext) {
+        // the buffers start out as air
+        let Some((height, material)) = &self.ground else {
+            return;
+        };
+
+        let chunk_y = i64::from(chunk_pos.1) * i64::from(CHUNK_HEIGHT);
+        if chunk_y + i64::from(CHUNK_HEIGHT) <= *height {
+            return;
+        }
+
+        for p in ChunkLocalPosition::iter() {
+            if chunk_y + i64::from(p.y()) >= *height {
+                buf.set_pixel(p, material.clone());
+            }
+        }
+    }
+
+    fn max_gen_stage(&self) -> u8 {
+        0
+    }
+
+    fn populators(&self) -> &PopulatorList<C> {
+        &self.populators
+    }
+
+    fn features(&self) -> &[PlacedFeature<C>] {
+        &[]
+    }
+}
@@ -1,6 +1,8 @@
 pub mod biome;
 pub mod biome_test;
+pub mod budget;
 pub mod feature;
+pub mod flat;
 pub mod populator;
 pub mod structure;
 mod test;
@@ -10,15 +12,19 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::{any::Any, vec::Vec};
 
-use chunksystem::ChunkKey;
+use chunksystem::{ChunkKey, ChunkManager, ChunkQuery};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use specs::{Join, RunNow, WorldExt};
 pub use test::*;
 
 use crate::game::common::world::gen::populator::ChunkContext;
-use crate::game::common::world::Chunk;
+use crate::game::common::world::{chunk_index, ecs, Chunk, ChunkState};
 use crate::game::common::Registries;
 
 use self::feature::PlacedFeature;
 use self::populator::Populator;
+use self::structure::{StructureNode, UpdateStructureNodes};
 
 use super::chunk_index::ChunkLocalIndex;
 use super::material::color::Color;
@@ -130,9 +136,194 @@ impl<'a> GenBuffers<'a> {
     }
 }
 
+/// Pixels, colors, background pixels and background colors of a freshly generated chunk
+pub type GeneratedBuffers = (
+    Box<[MaterialInstance; CHUNK_AREA]>,
+    Box<[Color; CHUNK_AREA]>,
+    Box<[MaterialInstance; CHUNK_AREA]>,
+    Box<[Color; CHUNK_AREA]>,
+);
+
 pub trait WorldGenerator<C: Chunk>: Send + Sync {
     fn generate(&self, chunk_pos: ChunkKey, buf: GenBuffers, ctx: GenContext);
     fn max_gen_stage(&self) -> u8;
     fn populators(&self) -> &PopulatorList<C>;
     fn features(&self) -> &[PlacedFeature<C>];
+
+    /// Allocates buffers for a chunk and runs [`WorldGenerator::generate`] on them.
+    fn generate_buffers(
+        &self,
+        chunk_pos: ChunkKey,
+        seed: i32,
+        registries: &Registries,
+    ) -> GeneratedBuffers {
+        // these arrays are too large for the stack
+
+        let mut pixels = Box::new([(); CHUNK_AREA].map(|_| MaterialInstance::air()));
+        let mut colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+        let mut background = Box::new([(); CHUNK_AREA].map(|_| MaterialInstance::air()));
+        let mut background_colors = Box::new([Color::TRANSPARENT; CHUNK_AREA]);
+
+        self.generate(
+            chunk_pos,
+            GenBuffers::new(
+                &mut pixels,
+                &mut colors,
+                &mut background,
+                &mut background_colors,
+            ),
+            GenContext { seed, registries },
+        );
+
+        (pixels, colors, background, background_colors)
+    }
+
+    /// Runs population stage `stage` (features, then populators) centered on the middle chunk of `chunks`.
+    ///
+    /// `chunks` must hold the `(stage * 2 + 1)^2` chunks around the center in row-major order.
+    fn populate_stage(
+        &self,
+        stage: u8,
+        chunks: &mut [&mut C],
+        seed: i32,
+        registries: &Registries,
+        ecs: &mut specs::World,
+    ) where
+        C: 'static,
+    {
+        if stage == 1 {
+            let mut chunk_ctx = ChunkContext::<1, C>::new(chunks).unwrap();
+            let mut rng = StdRng::seed_from_u64(
+                seed as u64
+                    + u64::from(chunk_index(
+                        chunk_ctx.center_chunk().0,
+                        chunk_ctx.center_chunk().1,
+                    )),
+            );
+            for feat in self.features() {
+                feat.generate(&mut chunk_ctx, seed, &mut rng, registries, ecs);
+                ecs.maintain();
+            }
+        }
+
+        self.populators().populate(stage, chunks, seed, registries);
+    }
+
+    /// Generates a single chunk through every stage without a running world, for tooling and previews.
+    ///
+    /// Populators can write into neighboring chunks, so this generates every chunk that can affect the
+    /// target and runs each stage over them in a fixed order.
+    /// Structures spawned by features are placed once every stage is done, like they would be in
+    /// cached chunks of a live world. Pieces that would reach outside the generated chunks are left out.
+    fn generate_chunk(
+        &self,
+        chunk_x: i32,
+        chunk_y: i32,
+        seed: i32,
+        registries: &Registries,
+    ) -> (
+        Box<[MaterialInstance; CHUNK_AREA]>,
+        Box<[Color; CHUNK_AREA]>,
+    )
+    where
+        C: Send + 'static,
+    {
+        let max_stage = self.max_gen_stage();
+
+        // chunks within `radius[s]` of the target need to finish stage `s`
+        let mut radius = vec![0; usize::from(max_stage) + 1];
+        radius[usize::from(max_stage)] = i32::from(max_stage);
+        for s in (1..=max_stage).rev() {
+            radius[usize::from(s) - 1] = radius[usize::from(s)] + i32::from(s);
+        }
+
+        // row-major, same as `ChunkContext`
+        let r = radius[0];
+        let mut chunks: Vec<C> = (-r..=r)
+            .flat_map(|y| (-r..=r).map(move |x| (chunk_x + x, chunk_y + y)))
+            .map(|pos| {
+                let (pixels, colors, background, background_colors) =
+                    self.generate_buffers(pos, seed, registries);
+
+                let mut chunk = C::new_empty(pos.0, pos.1);
+                chunk.set_pixels(pixels);
+                chunk.set_pixel_colors(colors);
+                chunk.set_background_pixels(background);
+                chunk.set_background_pixel_colors(background_colors);
+                chunk
+            })
+            .collect();
+
+        let mut ecs = ecs();
+        for chunk in &mut chunks {
+            self.populate_stage(0, &mut [chunk], seed, registries, &mut ecs);
+        }
+
+        for stage in 1..=max_stage {
+            let range = i32::from(stage);
+            let stage_r = radius[usize::from(stage)];
+            for cy in -stage_r..=stage_r {
+                for cx in -stage_r..=stage_r {
+                    let mut area: Vec<&mut C> = chunks
+                        .iter_mut()
+                        .filter(|c| {
+                            (c.chunk_x() - chunk_x - cx).abs() <= range
+                                && (c.chunk_y() - chunk_y - cy).abs() <= range
+                        })
+                        .collect();
+                    self.populate_stage(stage, &mut area, seed, registries, &mut ecs);
+                }
+            }
+        }
+
+        let mut manager = ChunkManager::new_with_capacity(chunks.len());
+        for mut chunk in chunks {
+            // structures only go into chunks a live world would consider far enough along
+            let dist = (chunk.chunk_x() - chunk_x)
+                .abs()
+                .max((chunk.chunk_y() - chunk_y).abs());
+            let stage = (0..=max_stage)
+                .rev()
+                .find(|s| dist <= radius[usize::from(*s)])
+                .unwrap_or(0);
+            chunk.set_state(if stage == max_stage {
+                ChunkState::Cached
+            } else {
+                ChunkState::Generating(stage)
+            });
+            manager.insert((chunk.chunk_x(), chunk.chunk_y()), chunk);
+        }
+        place_structures(&mut manager, registries, &mut ecs);
+
+        let center = manager.chunk_at((chunk_x, chunk_y)).unwrap();
+        let pixels = center.pixels().clone().unwrap();
+        let colors = Box::new(*center.colors());
+        (pixels, colors)
+    }
+}
+
+/// Runs [`UpdateStructureNodes`] until every structure node in `ecs` that can be placed in `chunks` is.
+fn place_structures<C: Chunk + Send>(
+    chunks: &mut ChunkManager<C>,
+    registries: &Registries,
+    ecs: &mut specs::World,
+) {
+    // each run places one more level of every structure, stop once nothing changes
+    let progress = |ecs: &specs::World| {
+        let nodes = ecs.read_storage::<StructureNode>();
+        let placed = nodes.join().filter(|n| n.generated.is_some()).count();
+        (nodes.join().count(), placed)
+    };
+
+    let mut last = progress(ecs);
+    loop {
+        UpdateStructureNodes { chunk_handler: &mut *chunks, registries }.run_now(ecs);
+        ecs.maintain();
+
+        let now = progress(ecs);
+        if now == last {
+            break;
+        }
+        last = now;
+    }
 }
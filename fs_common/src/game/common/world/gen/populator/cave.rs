@@ -2,7 +2,7 @@ use bracket_noise::prelude::{FastNoise, NoiseType};
 use simdnoise::NoiseBuilder;
 
 use crate::game::common::{
-    world::{material::MaterialInstance, Chunk, CHUNK_SIZE},
+    world::{material::MaterialInstance, Chunk, CHUNK_HEIGHT, CHUNK_WIDTH},
     Registries,
 };
 
@@ -14,8 +14,8 @@ impl<C: Chunk> Populator<0, C> for CavePopulator {
     #[profiling::function]
     fn populate(&self, chunks: &mut ChunkContext<0, C>, seed: i32, _registries: &Registries) {
         let (chunk_x, chunk_y) = chunks.center_chunk();
-        let chunk_pixel_x = chunk_x * i32::from(CHUNK_SIZE);
-        let chunk_pixel_y = chunk_y * i32::from(CHUNK_SIZE);
+        let chunk_pixel_x = chunk_x * i32::from(CHUNK_WIDTH);
+        let chunk_pixel_y = chunk_y * i32::from(CHUNK_HEIGHT);
         let cofs_x = chunk_pixel_x as f32;
         let cofs_y = chunk_pixel_y as f32;
 
@@ -29,9 +29,9 @@ impl<C: Chunk> Populator<0, C> for CavePopulator {
             // offsetting by seed is a workaround for https://github.com/verpeteren/rust-simd-noise/issues/42
             let noise_turbulance = NoiseBuilder::fbm_2d_offset(
                 cofs_x + seed as f32 / 100_000.0,
-                CHUNK_SIZE.into(),
+                CHUNK_WIDTH.into(),
                 cofs_y,
-                CHUNK_SIZE.into(),
+                CHUNK_HEIGHT.into(),
             )
             .with_octaves(6)
             .with_lacunarity(2.0)
@@ -47,9 +47,9 @@ impl<C: Chunk> Populator<0, C> for CavePopulator {
 
             {
                 profiling::scope!("loop");
-                for x in 0..i32::from(CHUNK_SIZE) {
-                    for y in 0..i32::from(CHUNK_SIZE) {
-                        let i = (x + y * i32::from(CHUNK_SIZE)) as usize;
+                for x in 0..i32::from(CHUNK_WIDTH) {
+                    for y in 0..i32::from(CHUNK_HEIGHT) {
+                        let i = (x + y * i32::from(CHUNK_WIDTH)) as usize;
                         let tu = (noise_turbulance.0[i] - -0.03) / 0.06;
 
                         let t_ofs = (tu * turbulance_scale as f32)
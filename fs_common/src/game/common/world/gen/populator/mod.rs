@@ -8,7 +8,10 @@ pub mod test;
 use std::usize;
 
 use crate::game::common::{
-    world::{chunk_index::ChunkLocalPosition, material::MaterialInstance, Chunk, CHUNK_SIZE},
+    world::{
+        chunk_index::ChunkLocalPosition, material::MaterialInstance, Chunk, CHUNK_HEIGHT,
+        CHUNK_WIDTH,
+    },
     Registries,
 };
 
@@ -47,8 +50,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
     #[inline]
     pub fn pixel_to_chunk(x: i32, y: i32) -> (i8, i8) {
         (
-            x.div_euclid(i32::from(CHUNK_SIZE)) as i8,
-            y.div_euclid(i32::from(CHUNK_SIZE)) as i8,
+            x.div_euclid(i32::from(CHUNK_WIDTH)) as i8,
+            y.div_euclid(i32::from(CHUNK_HEIGHT)) as i8,
         )
     }
 
@@ -70,8 +73,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
             let ch = self.0.get_unchecked_mut(i);
             ch.set_pixel(
                 ChunkLocalPosition::new_unchecked(
-                    x.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
-                    y.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
+                    x.rem_euclid(i32::from(CHUNK_WIDTH)) as u16,
+                    y.rem_euclid(i32::from(CHUNK_HEIGHT)) as u16,
                 ),
                 mat,
             )
@@ -88,8 +91,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
         unsafe {
             let ch = self.0.get_unchecked(i);
             Ok(ch.pixel_unchecked(ChunkLocalPosition::new_unchecked(
-                x.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
-                y.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
+                x.rem_euclid(i32::from(CHUNK_WIDTH)) as u16,
+                y.rem_euclid(i32::from(CHUNK_HEIGHT)) as u16,
             )))
         }
     }
@@ -105,8 +108,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
         let (cx, cy) = Self::pixel_to_chunk(x, y);
         let i = Self::chunk_index(cx, cy);
 
-        let x = x.rem_euclid(i32::from(CHUNK_SIZE)) as u16;
-        let y = y.rem_euclid(i32::from(CHUNK_SIZE)) as u16;
+        let x = x.rem_euclid(i32::from(CHUNK_WIDTH)) as u16;
+        let y = y.rem_euclid(i32::from(CHUNK_HEIGHT)) as u16;
         unsafe {
             let ch = self.0.get_unchecked_mut(i);
             ch.replace_pixel(ChunkLocalPosition::new_unchecked(x, y), cb)
@@ -123,8 +126,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
             let ch = self.0.get_unchecked_mut(i);
             ch.set_background_unchecked(
                 ChunkLocalPosition::new_unchecked(
-                    x.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
-                    y.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
+                    x.rem_euclid(i32::from(CHUNK_WIDTH)) as u16,
+                    y.rem_euclid(i32::from(CHUNK_HEIGHT)) as u16,
                 ),
                 mat,
             );
@@ -146,8 +149,8 @@ impl<'a, 'b, const S: u8, C: Chunk> ChunkContext<'a, 'b, S, C> {
         unsafe {
             let ch = self.0.get_unchecked(i);
             Ok(ch.background_unchecked(ChunkLocalPosition::new_unchecked(
-                x.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
-                y.rem_euclid(i32::from(CHUNK_SIZE)) as u16,
+                x.rem_euclid(i32::from(CHUNK_WIDTH)) as u16,
+                y.rem_euclid(i32::from(CHUNK_HEIGHT)) as u16,
             )))
         }
     }
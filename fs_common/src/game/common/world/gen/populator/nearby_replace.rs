@@ -1,5 +1,5 @@
 use crate::game::common::{
-    world::{material::MaterialInstance, Chunk, CHUNK_SIZE},
+    world::{material::MaterialInstance, Chunk, CHUNK_HEIGHT, CHUNK_WIDTH},
     Registries,
 };
 
@@ -7,7 +7,7 @@ use super::{ChunkContext, Populator};
 
 pub struct NearbyReplacePopulator<
     R: Fn(&MaterialInstance, i64, i64, &Registries) -> Option<MaterialInstance>,
-    S: Fn(&MaterialInstance) -> bool,
+    S: Fn(&MaterialInstance, &Registries) -> bool,
 > {
     pub radius: u16,
     pub replace: R,
@@ -16,7 +16,7 @@ pub struct NearbyReplacePopulator<
 
 impl<
         R: Fn(&MaterialInstance, i64, i64, &Registries) -> Option<MaterialInstance>,
-        S: Fn(&MaterialInstance) -> bool,
+        S: Fn(&MaterialInstance, &Registries) -> bool,
         C: Chunk,
     > Populator<1, C> for NearbyReplacePopulator<R, S>
 {
@@ -26,19 +26,19 @@ impl<
         // otherwise this is basically just brute force
         // for each pixel that matches `searching_for`, scan around it and try to `replace`
 
-        let cofs_x = i64::from(chunks.center_chunk().0) * i64::from(CHUNK_SIZE);
-        let cofs_y = i64::from(chunks.center_chunk().1) * i64::from(CHUNK_SIZE);
+        let cofs_x = i64::from(chunks.center_chunk().0) * i64::from(CHUNK_WIDTH);
+        let cofs_y = i64::from(chunks.center_chunk().1) * i64::from(CHUNK_HEIGHT);
 
         const OVERSCAN: u16 = 4;
 
-        // const SIDE: usize = CHUNK_SIZE as usize + (OVERSCAN as usize * 2);
+        // const SIDE: usize = CHUNK_WIDTH.max(CHUNK_HEIGHT) as usize + (OVERSCAN as usize * 2);
         // let mut mask = [false; SIDE * SIDE];
 
         // {
         //     profiling::scope!("build mask");
         //     let range = i32::from(self.radius);
-        //     for y in -i32::from(OVERSCAN)..i32::from(CHUNK_SIZE) + i32::from(OVERSCAN) {
-        //         for x in -i32::from(OVERSCAN)..i32::from(CHUNK_SIZE) + i32::from(OVERSCAN) {
+        //     for y in -i32::from(OVERSCAN)..i32::from(CHUNK_HEIGHT) + i32::from(OVERSCAN) {
+        //         for x in -i32::from(OVERSCAN)..i32::from(CHUNK_WIDTH) + i32::from(OVERSCAN) {
         //             // let i = (x + i32::from(OVERSCAN)) as usize + (y + i32::from(OVERSCAN)) as usize * SIDE;
         //             // if !mask[i] {
         //             let m = unsafe { chunks.get(x, y).unwrap_unchecked() };
@@ -59,8 +59,8 @@ impl<
 
         // {
         //     profiling::scope!("loop");
-        //     for x in 0..CHUNK_SIZE {
-        //         for y in 0..CHUNK_SIZE {
+        //     for x in 0..CHUNK_WIDTH {
+        //         for y in 0..CHUNK_HEIGHT {
         //             let i = (x as i32 + i32::from(OVERSCAN)) as usize + (y as i32 + i32::from(OVERSCAN)) as usize * SIDE;
         //             if unsafe { *mask.get_unchecked(i) } {
         //                 chunks.replace(x, y, |mat| {
@@ -76,29 +76,29 @@ impl<
         //     }
         // }
 
-        let mut skip_y = [0; CHUNK_SIZE as usize + OVERSCAN as usize * 2];
+        let mut skip_y = [0; CHUNK_WIDTH as usize + OVERSCAN as usize * 2];
         {
             profiling::scope!("loop");
             let range = i32::from(self.radius);
-            for y in -i32::from(OVERSCAN)..i32::from(CHUNK_SIZE) + i32::from(OVERSCAN) {
+            for y in -i32::from(OVERSCAN)..i32::from(CHUNK_HEIGHT) + i32::from(OVERSCAN) {
                 let mut skip_x = 0;
-                for x in -i32::from(OVERSCAN)..i32::from(CHUNK_SIZE) + i32::from(OVERSCAN) {
+                for x in -i32::from(OVERSCAN)..i32::from(CHUNK_WIDTH) + i32::from(OVERSCAN) {
                     let m = unsafe { chunks.get(x, y).unwrap_unchecked() };
                     let cur_skip_y =
                         unsafe { skip_y.get_unchecked_mut((x + i32::from(OVERSCAN)) as usize) };
-                    if (self.searching_for)(m) {
+                    if (self.searching_for)(m, registries) {
                         for dx in (-range + i32::from(skip_x))..=range {
                             if x + dx < 0 {
                                 continue;
                             }
-                            if x + dx >= i32::from(CHUNK_SIZE) {
+                            if x + dx >= i32::from(CHUNK_WIDTH) {
                                 break;
                             }
                             for dy in (-range + i32::from(*cur_skip_y))..=range {
                                 if (dx == 0 && dy == 0) || y + dy < 0 {
                                     continue;
                                 }
-                                if y + dy >= i32::from(CHUNK_SIZE) {
+                                if y + dy >= i32::from(CHUNK_HEIGHT) {
                                     break;
                                 }
                                 chunks.replace(x + dx, y + dy, |mat| {
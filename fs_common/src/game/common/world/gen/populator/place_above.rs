@@ -1,8 +1,5 @@
 use crate::game::common::{
-    world::{
-        material::{self, MaterialInstance},
-        Chunk, CHUNK_SIZE,
-    },
+    world::{material::MaterialInstance, Chunk, CHUNK_HEIGHT, CHUNK_WIDTH},
     Registries,
 };
 
@@ -14,23 +11,26 @@ pub struct PlaceAbovePopulator {
     /// Amount to "bury" into the found surface
     pub replace_surface_depth: u8,
     pub replace: fn(&MaterialInstance, i64, i64, &Registries) -> Option<MaterialInstance>,
-    pub searching_for: fn(&MaterialInstance) -> bool,
+    pub searching_for: fn(&MaterialInstance, &Registries) -> bool,
 }
 
 impl<C: Chunk> Populator<1, C> for PlaceAbovePopulator {
     #[profiling::function]
     fn populate(&self, chunks: &mut ChunkContext<1, C>, _seed: i32, registries: &Registries) {
-        let cofs_x = i64::from(chunks.center_chunk().0) * i64::from(CHUNK_SIZE);
-        let cofs_y = i64::from(chunks.center_chunk().1) * i64::from(CHUNK_SIZE);
+        let cofs_x = i64::from(chunks.center_chunk().0) * i64::from(CHUNK_WIDTH);
+        let cofs_y = i64::from(chunks.center_chunk().1) * i64::from(CHUNK_HEIGHT);
 
         let replace_surface_depth = i32::from(self.replace_surface_depth);
         let add_surface_height = i32::from(self.add_surface_height);
 
-        for x in 0..i32::from(CHUNK_SIZE) {
-            for y in 0..i32::from(CHUNK_SIZE) {
+        for x in 0..i32::from(CHUNK_WIDTH) {
+            for y in 0..i32::from(CHUNK_HEIGHT) {
                 let m = chunks.get(x, y).unwrap();
-                if (self.searching_for)(m)
-                    && chunks.get(x, y - 1).unwrap().material_id == *material::AIR
+                if (self.searching_for)(m, registries)
+                    && chunks
+                        .get(x, y - 1)
+                        .unwrap()
+                        .is_empty(&registries.materials)
                 {
                     for dy in -add_surface_height..replace_surface_depth {
                         let m2 = chunks.get(x, y + dy).unwrap();
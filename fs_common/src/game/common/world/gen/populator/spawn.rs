@@ -4,7 +4,7 @@ use crate::game::common::{
             placer::{self, MaterialPlacerSampler},
             MaterialInstance,
         },
-        Chunk, CHUNK_SIZE,
+        Chunk, CHUNK_HEIGHT, CHUNK_WIDTH,
     },
     Registries,
 };
@@ -17,11 +17,11 @@ impl<C: Chunk> Populator<0, C> for SpawnPopulator {
     #[profiling::function]
     fn populate(&self, chunks: &mut ChunkContext<0, C>, _seed: i32, registries: &Registries) {
         let (chunk_x, chunk_y) = chunks.center_chunk();
-        let chunk_pixel_x = i64::from(chunk_x) * i64::from(CHUNK_SIZE);
-        let chunk_pixel_y = i64::from(chunk_y) * i64::from(CHUNK_SIZE);
+        let chunk_pixel_x = i64::from(chunk_x) * i64::from(CHUNK_WIDTH);
+        let chunk_pixel_y = i64::from(chunk_y) * i64::from(CHUNK_HEIGHT);
 
-        for x in 0..i32::from(CHUNK_SIZE) {
-            for y in 0..i32::from(CHUNK_SIZE) {
+        for x in 0..i32::from(CHUNK_WIDTH) {
+            for y in 0..i32::from(CHUNK_HEIGHT) {
                 let wx = chunk_pixel_x + i64::from(x);
                 let wy = chunk_pixel_y + i64::from(y);
 
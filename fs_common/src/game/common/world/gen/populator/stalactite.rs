@@ -1,17 +1,14 @@
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::game::common::{
-    world::{
-        material::{self, MaterialInstance},
-        Chunk, CHUNK_SIZE,
-    },
+    world::{material::MaterialInstance, Chunk, CHUNK_HEIGHT, CHUNK_WIDTH},
     Registries,
 };
 
 use super::{ChunkContext, Populator};
 
 pub struct StalactitePopulator {
-    pub searching_for: fn(&MaterialInstance) -> bool,
+    pub searching_for: fn(&MaterialInstance, &Registries) -> bool,
     pub replace: fn(&MaterialInstance, i64, i64, &Registries) -> Option<MaterialInstance>,
 }
 
@@ -21,16 +18,23 @@ impl<C: Chunk> Populator<1, C> for StalactitePopulator {
         let mut rng = StdRng::seed_from_u64(seed as u64);
 
         'skip: for _ in 0..1000 {
-            let x = rng.gen_range(0..i32::from(CHUNK_SIZE));
-            let y = rng.gen_range(0..i32::from(CHUNK_SIZE));
+            let x = rng.gen_range(0..i32::from(CHUNK_WIDTH));
+            let y = rng.gen_range(0..i32::from(CHUNK_HEIGHT));
 
             let m = chunks.get(x, y).unwrap();
-            if (self.searching_for)(m)
-                && chunks.get(x, y + 1).unwrap().material_id == *material::AIR
+            if (self.searching_for)(m, registries)
+                && chunks
+                    .get(x, y + 1)
+                    .unwrap()
+                    .is_empty(&registries.materials)
             {
                 for dx in -4..=4 {
                     for dy in 0..=2 {
-                        if chunks.get(x + dx, y - dy).unwrap().material_id == *material::AIR {
+                        if chunks
+                            .get(x + dx, y - dy)
+                            .unwrap()
+                            .is_empty(&registries.materials)
+                        {
                             continue 'skip;
                         }
                     }
@@ -45,10 +49,10 @@ impl<C: Chunk> Populator<1, C> for StalactitePopulator {
                         let m2 = chunks.get(x + dx, y + dy).unwrap();
                         if let Some(rep) = (self.replace)(
                             m2,
-                            (i64::from(chunks.center_chunk().0) * i64::from(CHUNK_SIZE))
+                            (i64::from(chunks.center_chunk().0) * i64::from(CHUNK_WIDTH))
                                 + i64::from(x)
                                 + i64::from(dx),
-                            (i64::from(chunks.center_chunk().1) * i64::from(CHUNK_SIZE))
+                            (i64::from(chunks.center_chunk().1) * i64::from(CHUNK_HEIGHT))
                                 + i64::from(y)
                                 + i64::from(dy),
                             registries,
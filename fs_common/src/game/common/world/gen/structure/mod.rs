@@ -2,22 +2,26 @@ pub mod configured_structure;
 pub mod piece;
 pub mod pool;
 pub mod set;
-
-use std::sync::Arc;
+pub mod validate;
 
 use rand::{
-    distributions::Standard, prelude::Distribution, rngs::StdRng, seq::SliceRandom, Rng, RngCore,
+    distributions::Standard, prelude::Distribution, rngs::StdRng, seq::SliceRandom, Rng,
     SeedableRng,
 };
 use specs::{
-    Builder, Component, Entities, Entity, HashMapStorage, Join, System, WorldExt, WriteStorage,
+    Builder, Component, Entities, Entity, HashMapStorage, Join, System, WorldExt, Write,
+    WriteStorage,
 };
 
 use crate::game::common::{
     registry::RegistryID,
     world::{
-        self, chunk_access::FSChunkAccess, entity::Persistent,
-        gen::structure::piece::StructureNodeConfig, ChunkState, Position,
+        self,
+        chunk_access::FSChunkAccess,
+        edit_events::{WorldEditCause, WorldEditEvents},
+        entity::Persistent,
+        gen::structure::piece::StructureNodeConfig,
+        ChunkState, Position,
     },
     Rect, Registries,
 };
@@ -145,14 +149,15 @@ impl AngleDiff {
 
     #[inline]
     pub fn rotate_point(&self, point: (i64, i64), pivot: (i64, i64)) -> (i64, i64) {
-        let sin = self.degrees().to_radians().sin();
-        let cos = self.degrees().to_radians().cos();
-        (
-            (cos * (point.0 - pivot.0) as f32 - sin * (point.1 - pivot.1) as f32 + pivot.0 as f32)
-                as i64,
-            (sin * (point.0 - pivot.0) as f32 + cos * (point.1 - pivot.1) as f32 + pivot.1 as f32)
-                as i64,
-        )
+        // done with integers since rounding errors from sin/cos would put things off by a pixel
+        let dx = point.0 - pivot.0;
+        let dy = point.1 - pivot.1;
+        match self {
+            Self::None => point,
+            Self::Clockwise90 => (pivot.0 - dy, pivot.1 + dx),
+            Self::CounterClockwise90 => (pivot.0 + dy, pivot.1 - dx),
+            Self::Angle180 => (pivot.0 - dx, pivot.1 - dy),
+        }
     }
 }
 
@@ -173,7 +178,7 @@ pub struct StructureNode {
     pub generated: Option<Result<StructureNodeGenData, ()>>,
     pub depth: u8,
     pub max_distance: u16,
-    pub rng: Box<dyn RngCore + Send + Sync>,
+    pub rng: StdRng,
     /// Direction to parent
     pub direction: Direction,
     pub config: StructureNodeConfig,
@@ -203,7 +208,7 @@ impl StructureNode {
                 depth,
                 max_distance,
                 direction: override_dir.unwrap_or_else(|| rng.gen()),
-                rng: Box::new(rng),
+                rng,
                 config,
             })
             .with(Persistent)
@@ -218,9 +223,23 @@ impl Component for StructureNode {
     type Storage = HashMapStorage<Self>;
 }
 
+/// Whether a chunk is far enough along in generation that a structure can be pasted into it
+///   without later generation stages overwriting it.
+fn ready_for_structures(state: ChunkState) -> bool {
+    matches!(state, ChunkState::Cached | ChunkState::Active)
+        || matches!(state, ChunkState::Generating(n) if n >= 2)
+}
+
+enum PlaceResult {
+    Placed(Vec<(Entity, StructureNode, Position)>),
+    /// A piece fit, but not all of the chunks it covers are ready yet
+    Deferred,
+    NoFit,
+}
+
 pub struct UpdateStructureNodes<'a, H: FSChunkAccess + Send> {
     pub chunk_handler: &'a mut H,
-    pub registries: Arc<Registries>,
+    pub registries: &'a Registries,
 }
 
 // fn is_finished(p: Entity, node_storage: &WriteStorage<StructureNode>) -> bool {
@@ -261,12 +280,13 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
         Entities<'a>,
         WriteStorage<'a, StructureNode>,
         WriteStorage<'a, Position>,
+        Write<'a, WorldEditEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         profiling::scope!("UpdateStructureNodes::run");
 
-        let (entities, mut node_storage, mut pos_storage) = data;
+        let (entities, mut node_storage, mut pos_storage, mut edit_events) = data;
 
         let mut to_check = vec![];
 
@@ -305,9 +325,7 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
                 continue;
             };
 
-            if matches!(ch.state(), ChunkState::Cached | ChunkState::Active)
-                || matches!(ch.state(), ChunkState::Generating(n) if n >= 2)
-            {
+            if ready_for_structures(ch.state()) {
                 node_storage.insert(entity, node).unwrap();
                 pos_storage.insert(entity, pos).unwrap();
                 let root = root(entity, node_storage.get(entity).unwrap(), &node_storage);
@@ -318,6 +336,9 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
 
                 node.generated = Some(Err(()));
 
+                // saved so a deferred node makes the same choices next time
+                let rng_before = node.rng.clone();
+
                 // try every structure in desired pool
                 let mut pool = self
                     .registries
@@ -329,7 +350,7 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
                 pool.shuffle(&mut node.rng);
 
                 // try placing normal pool
-                if let Some(mut children) = self.place(
+                let mut result = self.place(
                     &pool,
                     &pos,
                     &mut node,
@@ -337,9 +358,11 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
                     &root_pos,
                     entity,
                     false,
-                ) {
-                    to_add.append(&mut children);
-                } else if let Some(fallback_pool) = &node.config.fallback_pool {
+                );
+
+                if let (PlaceResult::NoFit, Some(fallback_pool)) =
+                    (&result, &node.config.fallback_pool)
+                {
                     // if normal pool failed, try placing fallback pool
                     let mut fallback_pool = self
                         .registries
@@ -350,7 +373,7 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
                         .clone();
                     fallback_pool.shuffle(&mut node.rng);
 
-                    if let Some(mut children) = self.place(
+                    result = self.place(
                         &fallback_pool,
                         &pos,
                         &mut node,
@@ -358,10 +381,15 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
                         &root_pos,
                         entity,
                         true,
-                    ) {
-                        to_add.append(&mut children);
-                    }
+                    );
                 }
+
+                to_add.append(&mut Self::finish_placing(
+                    result,
+                    &mut node,
+                    rng_before,
+                    &mut edit_events,
+                ));
             }
 
             // log::trace!("add {entity:?}");
@@ -388,6 +416,33 @@ impl<'a, H: FSChunkAccess + Send> System<'a> for UpdateStructureNodes<'a, H> {
 }
 
 impl<H: FSChunkAccess + Send> UpdateStructureNodes<'_, H> {
+    /// Handles the outcome of trying to place `node`, returning the children to spawn.
+    ///
+    /// A placed piece is announced as a [`WorldEditCause::Structure`] edit. A deferred node is
+    ///   reset to how it was before, so it makes the same choices when it tries again.
+    fn finish_placing(
+        result: PlaceResult,
+        node: &mut StructureNode,
+        rng_before: StdRng,
+        edit_events: &mut WorldEditEvents,
+    ) -> Vec<(Entity, StructureNode, Position)> {
+        match result {
+            PlaceResult::Placed(children) => {
+                if let Some(Ok(gen)) = &node.generated {
+                    edit_events.emit(gen.bounds, WorldEditCause::Structure);
+                }
+                children
+            },
+            PlaceResult::Deferred => {
+                // try again once the chunks it needs are generated
+                node.generated = None;
+                node.rng = rng_before;
+                vec![]
+            },
+            PlaceResult::NoFit => vec![],
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn place(
         &mut self,
@@ -398,7 +453,7 @@ impl<H: FSChunkAccess + Send> UpdateStructureNodes<'_, H> {
         root_pos: &Position,
         entity: Entity,
         ignore_restrictions: bool,
-    ) -> Option<Vec<(Entity, StructureNode, Position)>> {
+    ) -> PlaceResult {
         // for every structure piece in the pool
         for pool_structure in pool
             .iter()
@@ -417,6 +472,11 @@ impl<H: FSChunkAccess + Send> UpdateStructureNodes<'_, H> {
                         .any(|r| r.inflated(-1).intersects(&bounds));
 
                 if ok {
+                    // pasting into chunks that aren't loaded would silently drop pixels
+                    if !self.can_place_in(&bounds) {
+                        return PlaceResult::Deferred;
+                    }
+
                     place_fn(pool_structure, self.chunk_handler).unwrap();
 
                     node.generated = Some(Ok(StructureNodeGenData { bounds }));
@@ -441,7 +501,7 @@ impl<H: FSChunkAccess + Send> UpdateStructureNodes<'_, H> {
                                     generated: None,
                                     depth: if node.depth == 0 { 0 } else { node.depth - 1 },
                                     max_distance: node.max_distance,
-                                    rng: Box::new(rng),
+                                    rng,
                                     direction: placement.direction_out,
                                     config,
                                 },
@@ -450,11 +510,26 @@ impl<H: FSChunkAccess + Send> UpdateStructureNodes<'_, H> {
                         })
                         .collect();
 
-                    return Some(children);
+                    return PlaceResult::Placed(children);
                 }
             }
         }
 
-        None
+        PlaceResult::NoFit
+    }
+
+    fn can_place_in(&self, bounds: &Rect<i64>) -> bool {
+        let (min_x, min_y) = world::pixel_to_chunk_pos(bounds.left(), bounds.top());
+        let (max_x, max_y) = world::pixel_to_chunk_pos(bounds.right(), bounds.bottom());
+
+        (min_x..=max_x).all(|chunk_x| {
+            (min_y..=max_y).all(|chunk_y| {
+                self.chunk_handler
+                    .chunk_at_dyn((chunk_x, chunk_y))
+                    .map_or(false, |ch| {
+                        ch.pixels().is_some() && ready_for_structures(ch.state())
+                    })
+            })
+        })
     }
 }
@@ -0,0 +1,284 @@
+use std::fmt::Display;
+
+use crate::game::common::{registry::RegistryID, Registries};
+
+use super::{piece::StructurePiece, pool::StructurePool, Direction};
+
+/// A problem with how structure pieces and pools reference each other.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructureIssue {
+    /// A pool lists a piece that isn't registered.
+    MissingPiece {
+        pool: RegistryID<StructurePool>,
+        piece: RegistryID<StructurePiece>,
+    },
+    /// A node on `piece` uses a pool that isn't registered.
+    MissingPool {
+        piece: RegistryID<StructurePiece>,
+        node: usize,
+        pool: RegistryID<StructurePool>,
+    },
+    /// No piece in the node's pools (including the fallback) can ever attach to it,
+    ///   since they all block connections coming from every way the node can face once its
+    ///   piece is rotated into place. `direction_out` is the node's unrotated direction.
+    UnconnectableNode {
+        piece: RegistryID<StructurePiece>,
+        node: usize,
+        direction_out: Direction,
+    },
+}
+
+impl Display for StructureIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingPiece { pool, piece } => {
+                write!(f, "structure pool \"{pool}\" has missing piece \"{piece}\"")
+            },
+            Self::MissingPool { piece, node, pool } => write!(
+                f,
+                "structure piece \"{piece}\" node {node} uses missing pool \"{pool}\""
+            ),
+            Self::UnconnectableNode { piece, node, direction_out } => write!(
+                f,
+                "structure piece \"{piece}\" node {node} ({direction_out:?}) can never connect"
+            ),
+        }
+    }
+}
+
+/// Checks that every pool and piece referenced by structure pieces exists, and that every node
+///   could be connected to by at least one piece.
+///
+/// Pieces are rotated when they're placed (see [`StructurePiece::options`]), so a node is only
+///   unconnectable if it's blocked whichever way it can end up facing.
+///
+/// Issues are sorted by piece/pool name so the output is stable.
+pub fn validate_structures(registries: &Registries) -> Vec<StructureIssue> {
+    let mut issues = vec![];
+
+    let mut pools: Vec<_> = (&registries.structure_pools).into_iter().collect();
+    pools.sort_by(|a, b| a.0.cmp(b.0));
+    for (pool_id, pool) in pools {
+        for piece in &pool.pool {
+            if registries.structure_pieces.get(piece).is_none() {
+                issues.push(StructureIssue::MissingPiece {
+                    pool: pool_id.clone(),
+                    piece: piece.clone(),
+                });
+            }
+        }
+    }
+
+    // whether any piece in `pool` can be attached to a node facing `dir_in`
+    let can_connect = |pool: &StructurePool, dir_in: Direction| {
+        pool.pool
+            .iter()
+            .filter_map(|id| registries.structure_pieces.get(id))
+            .flat_map(|piece| &piece.child_nodes)
+            .any(|(_, config)| {
+                !config
+                    .block_in_dirs
+                    .as_ref()
+                    .map_or(false, |block| block.contains(&dir_in))
+            })
+    };
+
+    let mut pieces: Vec<_> = (&registries.structure_pieces).into_iter().collect();
+    pieces.sort_by(|a, b| a.0.cmp(b.0));
+    for (piece_id, piece) in pieces {
+        for (node, (placement, config)) in piece.child_nodes.iter().enumerate() {
+            let facing = facing_dirs(piece, node);
+
+            let mut connectable = false;
+            for pool_id in std::iter::once(&config.pool).chain(&config.fallback_pool) {
+                match registries.structure_pools.get(pool_id) {
+                    Some(pool) => {
+                        connectable |= facing.iter().any(|dir| can_connect(pool, *dir));
+                    },
+                    None => issues.push(StructureIssue::MissingPool {
+                        piece: piece_id.clone(),
+                        node,
+                        pool: pool_id.clone(),
+                    }),
+                }
+            }
+
+            // a node that's never facing anywhere is only ever used to attach the piece itself
+            if !connectable && !facing.is_empty() {
+                issues.push(StructureIssue::UnconnectableNode {
+                    piece: piece_id.clone(),
+                    node,
+                    direction_out: placement.direction_out,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Every direction child node `node` of `piece` can face once the piece is placed.
+///
+/// The piece is attached by one of its other nodes, to a parent node facing any direction that
+///   node doesn't block, and rotated so they line up (like [`StructurePiece::options`] does).
+fn facing_dirs(piece: &StructurePiece, node: usize) -> Vec<Direction> {
+    let direction_out = piece.child_nodes[node].0.direction_out;
+    let mut dirs = vec![];
+    for (i, (attach, config)) in piece.child_nodes.iter().enumerate() {
+        if i == node {
+            continue;
+        }
+
+        for dir_in in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            if config
+                .block_in_dirs
+                .as_ref()
+                .map_or(false, |block| block.contains(&dir_in))
+            {
+                continue;
+            }
+
+            let dir = direction_out.rotated(attach.direction_out.angle(dir_in.opposite()));
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::{
+        world::{
+            gen::structure::{
+                piece::{StructureNodeConfig, StructureNodeLocalPlacement, StructurePiece},
+                pool::StructurePool,
+                Direction,
+            },
+            material::{buf::MaterialBuf, MaterialInstance},
+        },
+        Registries,
+    };
+
+    use super::{validate_structures, StructureIssue};
+
+    fn piece(nodes: Vec<(Direction, StructureNodeConfig)>) -> StructurePiece {
+        StructurePiece {
+            buf: MaterialBuf::new(8, 8, vec![MaterialInstance::air(); 8 * 8]).unwrap(),
+            child_nodes: nodes
+                .into_iter()
+                .map(|(direction_out, config)| {
+                    (
+                        StructureNodeLocalPlacement { x: 0, y: 4, direction_out },
+                        config,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn dangling_pool_reference() {
+        let mut registries = Registries::empty();
+        registries.structure_pieces.register(
+            "room",
+            piece(vec![
+                (Direction::Left, StructureNodeConfig::new("hallways")),
+                // typo
+                (Direction::Right, StructureNodeConfig::new("halways")),
+            ]),
+        );
+        registries.structure_pieces.register(
+            "hallway",
+            piece(vec![(
+                Direction::Left,
+                StructureNodeConfig::new("rooms")
+                    .block_in_dirs(vec![Direction::Up, Direction::Down]),
+            )]),
+        );
+        registries
+            .structure_pools
+            .register("hallways", StructurePool::from(vec!["hallway"]));
+        registries
+            .structure_pools
+            .register("rooms", StructurePool::from(vec!["room", "attic"]));
+
+        assert_eq!(
+            validate_structures(&registries),
+            vec![
+                StructureIssue::MissingPiece { pool: "rooms".into(), piece: "attic".into() },
+                StructureIssue::MissingPool {
+                    piece: "room".into(),
+                    node: 1,
+                    pool: "halways".into(),
+                },
+                StructureIssue::UnconnectableNode {
+                    piece: "room".into(),
+                    node: 1,
+                    direction_out: Direction::Right,
+                },
+            ]
+        );
+
+        // hallways only connect sideways, but a room can be turned so a node going up faces sideways
+        registries.structure_pieces.register(
+            "room",
+            piece(vec![
+                (Direction::Left, StructureNodeConfig::new("hallways")),
+                (Direction::Up, StructureNodeConfig::new("hallways")),
+            ]),
+        );
+        registries
+            .structure_pools
+            .register("rooms", StructurePool::from(vec!["room"]));
+        assert_eq!(validate_structures(&registries), vec![]);
+
+        // unless it can only be attached from the side, which always leaves that node facing up or down
+        registries.structure_pieces.register(
+            "room",
+            piece(vec![
+                (
+                    Direction::Left,
+                    StructureNodeConfig::new("hallways")
+                        .block_in_dirs(vec![Direction::Up, Direction::Down]),
+                ),
+                (Direction::Up, StructureNodeConfig::new("hallways")),
+            ]),
+        );
+        assert_eq!(
+            validate_structures(&registries),
+            vec![StructureIssue::UnconnectableNode {
+                piece: "room".into(),
+                node: 1,
+                direction_out: Direction::Up,
+            }]
+        );
+
+        // and a node going left can still never face sideways, if attaching turns the room a quarter
+        registries.structure_pieces.register(
+            "room",
+            piece(vec![
+                (
+                    Direction::Up,
+                    StructureNodeConfig::new("hallways")
+                        .block_in_dirs(vec![Direction::Up, Direction::Down]),
+                ),
+                (Direction::Left, StructureNodeConfig::new("hallways")),
+            ]),
+        );
+        assert_eq!(
+            validate_structures(&registries),
+            vec![StructureIssue::UnconnectableNode {
+                piece: "room".into(),
+                node: 1,
+                direction_out: Direction::Left,
+            }]
+        );
+    }
+}
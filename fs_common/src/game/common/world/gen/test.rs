@@ -7,7 +7,7 @@ use crate::game::common::world::{
 use chunksystem::ChunkKey;
 use simdnoise::NoiseBuilder;
 
-use crate::game::common::world::CHUNK_SIZE;
+use crate::game::common::world::{CHUNK_HEIGHT, CHUNK_WIDTH};
 
 use super::{feature::PlacedFeature, GenBuffers, GenContext, PopulatorList, WorldGenerator};
 
@@ -27,24 +27,28 @@ impl<C: Chunk + Send + Sync> WorldGenerator<C> for TestGenerator<C> {
     #[allow(clippy::cast_lossless)]
     #[profiling::function]
     fn generate(&self, chunk_pos: ChunkKey, mut buf: GenBuffers, ctx: GenContext) {
-        let cofs_x = (chunk_pos.0 * CHUNK_SIZE as i32) as f32;
-        let cofs_y = (chunk_pos.1 * CHUNK_SIZE as i32) as f32;
+        let cofs_x = (chunk_pos.0 * CHUNK_WIDTH as i32) as f32;
+        let cofs_y = (chunk_pos.1 * CHUNK_HEIGHT as i32) as f32;
 
         // gradient_2d_offset always returns a Vec with size W*H
-        let noise_cave_2: [f32; CHUNK_AREA] =
-            NoiseBuilder::gradient_2d_offset(cofs_x, CHUNK_SIZE.into(), cofs_y, CHUNK_SIZE.into())
-                .with_freq(0.002)
-                .with_seed(ctx.seed)
-                .generate()
-                .0
-                .try_into()
-                .unwrap();
+        let noise_cave_2: [f32; CHUNK_AREA] = NoiseBuilder::gradient_2d_offset(
+            cofs_x,
+            CHUNK_WIDTH.into(),
+            cofs_y,
+            CHUNK_HEIGHT.into(),
+        )
+        .with_freq(0.002)
+        .with_seed(ctx.seed)
+        .generate()
+        .0
+        .try_into()
+        .unwrap();
 
         let noise2_r = NoiseBuilder::gradient_2d_offset(
             cofs_x + 1238.651,
-            CHUNK_SIZE.into(),
+            CHUNK_WIDTH.into(),
             cofs_y + 1378.529,
-            CHUNK_SIZE.into(),
+            CHUNK_HEIGHT.into(),
         )
         .with_freq(0.004)
         .with_seed(ctx.seed)
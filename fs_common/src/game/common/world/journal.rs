@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread::JoinHandle,
+};
+
+use bincode::Options;
+use chunksystem::{ChunkKey, ChunkQuery};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    chunk_handler::ChunkHandler, edit, material::MaterialInstance, pixel_to_chunk_pos, Chunk,
+    CHUNK_AREA,
+};
+
+/// The journal's file name within the world's folder.
+pub const JOURNAL_FILE: &str = "journal.dat";
+
+/// How many edits are journaled between snapshots of the chunks they touched.
+pub const JOURNAL_SNAPSHOT_INTERVAL: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JournalEntry {
+    /// The pixels written by one committed edit (or undo).
+    Edit(Vec<((i64, i64), MaterialInstance)>),
+    /// The full contents of a chunk.
+    Snapshot {
+        key: ChunkKey,
+        pixels: Vec<MaterialInstance>,
+    },
+}
+
+impl JournalEntry {
+    /// The chunks replaying this entry writes to.
+    pub fn chunks(&self) -> BTreeSet<ChunkKey> {
+        match self {
+            Self::Edit(pixels) => pixels
+                .iter()
+                .map(|((x, y), _)| pixel_to_chunk_pos(*x, *y))
+                .collect(),
+            Self::Snapshot { key, .. } => BTreeSet::from([*key]),
+        }
+    }
+}
+
+/// An append-only log of committed [`Edit`](super::edit::Edit)s, so a crash doesn't lose
+///   everything since the last save.
+///
+/// Every edit is appended to [`JOURNAL_FILE`] in the world's folder as it's committed, and every
+///   [`JOURNAL_SNAPSHOT_INTERVAL`] edits the full contents of the chunks touched since the last
+///   snapshot are appended too, which also catches what the simulation did to them in the meantime.
+///   The journal is emptied whenever the world is saved, so it only ever covers unsaved progress.
+///
+/// The file is written on a worker thread, so committing an edit never waits on the disk.
+///
+/// Replaying only writes exact pixels in order, so the same journal always gives the same result,
+///   see [`World::recover_journal`](super::World::recover_journal).
+#[derive(Debug, Default)]
+pub struct EditJournal {
+    edits_since_snapshot: usize,
+    /// Chunks edited since the last snapshot.
+    touched: BTreeSet<ChunkKey>,
+    /// Started by the first edit, so worlds that aren't saved to disk never start one.
+    writer: Option<JournalWriter>,
+}
+
+/// Appends journal entries to a file on a worker thread.
+///
+/// Entries are written by a single worker in the order they were sent. Dropping the writer waits
+///   until everything sent so far is written, see [`flush`].
+#[derive(Debug)]
+struct JournalWriter {
+    entries: Option<Sender<Vec<JournalEntry>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl JournalWriter {
+    fn new(path: PathBuf) -> Result<Self, String> {
+        let (entries, entries_rx) = mpsc::channel::<Vec<JournalEntry>>();
+
+        let worker = std::thread::Builder::new()
+            .name("edit journal writer".to_string())
+            .spawn(move || {
+                // kept open between writes, and opened again after a failed one
+                let mut file = None;
+                for entries in entries_rx {
+                    if let Err(e) = append(&path, &mut file, &entries) {
+                        log::error!("{e}");
+                        file = None;
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to spawn edit journal writer thread: {e}"))?;
+
+        Ok(Self { entries: Some(entries), worker: Some(worker) })
+    }
+
+    fn send(&self, entries: Vec<JournalEntry>) {
+        if let Some(tx) = &self.entries {
+            // can only fail if the worker panicked, which it already logged
+            let _ignore = tx.send(entries);
+        }
+    }
+}
+
+impl Drop for JournalWriter {
+    fn drop(&mut self) {
+        // closing the channel stops the worker once it has written everything
+        self.entries = None;
+        if let Some(worker) = self.worker.take() {
+            let _ignore = worker.join();
+        }
+    }
+}
+
+pub fn journal_path(world_path: &Path) -> PathBuf {
+    world_path.join(JOURNAL_FILE)
+}
+
+/// Appends an edit to the [`EditJournal`], along with a snapshot if one is due.
+/// Does nothing if the world isn't saved to disk.
+pub(super) fn record<C: Chunk>(
+    chunk_handler: &mut ChunkHandler<C>,
+    pixels: Vec<((i64, i64), MaterialInstance)>,
+) {
+    let Some(path) = chunk_handler.path.as_deref().map(journal_path) else {
+        return;
+    };
+
+    let edit = JournalEntry::Edit(pixels);
+    let journal = &mut chunk_handler.journal;
+    journal.touched.extend(edit.chunks());
+    journal.edits_since_snapshot += 1;
+
+    let mut entries = vec![edit];
+    if journal.edits_since_snapshot >= JOURNAL_SNAPSHOT_INTERVAL {
+        journal.edits_since_snapshot = 0;
+        for key in std::mem::take(&mut journal.touched) {
+            if let Some(pixels) = chunk_handler
+                .manager
+                .chunk_at(key)
+                .and_then(|c| c.pixels().as_ref())
+            {
+                entries.push(JournalEntry::Snapshot { key, pixels: pixels.to_vec() });
+            }
+        }
+    }
+
+    if journal.writer.is_none() {
+        match JournalWriter::new(path) {
+            Ok(writer) => journal.writer = Some(writer),
+            Err(e) => log::error!("{e}"),
+        }
+    }
+    if let Some(writer) = &journal.writer {
+        writer.send(entries);
+    }
+}
+
+/// Waits until everything recorded so far is written to the journal file.
+pub fn flush<C: Chunk>(chunk_handler: &mut ChunkHandler<C>) {
+    // the next edit starts a new writer
+    chunk_handler.journal.writer = None;
+}
+
+/// The same encoding as [`bincode::serialize`], so each entry can be read on its own.
+fn bincode_options() -> impl Options {
+    bincode::options()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+/// Appends `entries` to the journal at `path`, opening it into `file` first if it isn't open yet.
+fn append(path: &Path, file: &mut Option<File>, entries: &[JournalEntry]) -> Result<(), String> {
+    let mut data = Vec::new();
+    for entry in entries {
+        bincode_options()
+            .serialize_into(&mut data, entry)
+            .map_err(|e| format!("Failed to serialize edit journal entry: {e}"))?;
+    }
+
+    if file.is_none() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                format!(
+                    "Failed to create edit journal directory @ {}: {e}",
+                    dir.display()
+                )
+            })?;
+        }
+        *file = Some(
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open edit journal @ {}: {e}", path.display()))?,
+        );
+    }
+
+    file.as_mut()
+        .unwrap()
+        .write_all(&data)
+        .map_err(|e| format!("Failed to write edit journal @ {}: {e}", path.display()))
+}
+
+/// Reads every entry in the journal at `path`.
+///
+/// A crash in the middle of a write can leave a partial entry at the end, which is skipped.
+/// No entry can be bigger than what's left of the file, so a broken length can't make it allocate more.
+pub fn read(path: &Path) -> Result<Vec<JournalEntry>, String> {
+    let data = fs::read(path)
+        .map_err(|e| format!("Failed to read edit journal @ {}: {e}", path.display()))?;
+
+    let mut reader = data.as_slice();
+    let mut entries = Vec::new();
+    while !reader.is_empty() {
+        match bincode_options()
+            .with_limit(reader.len() as u64)
+            .deserialize_from(&mut reader)
+        {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                log::warn!(
+                    "Edit journal @ {} ends with a broken entry: {e}",
+                    path.display()
+                );
+                break;
+            },
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Writes `entries` to the world in order. Every chunk they touch needs to be loaded.
+///
+/// Replayed entries aren't journaled again, and can't be undone.
+pub fn replay<C: Chunk>(
+    chunk_handler: &mut ChunkHandler<C>,
+    entries: &[JournalEntry],
+) -> Result<(), String> {
+    for entry in entries {
+        match entry {
+            JournalEntry::Edit(pixels) => {
+                edit::apply(chunk_handler, pixels.iter().cloned())?;
+            },
+            JournalEntry::Snapshot { key, pixels } => {
+                let chunk = chunk_handler
+                    .manager
+                    .chunk_at_mut(*key)
+                    .filter(|c| c.pixels().is_some())
+                    .ok_or_else(|| format!("Chunk {key:?} is not ready yet."))?;
+                if pixels.len() != CHUNK_AREA {
+                    return Err(format!("Snapshot of chunk {key:?} has the wrong size."));
+                }
+                chunk.set_pixels(pixels.clone().try_into().unwrap());
+                chunk.mark_dirty();
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Empties the journal, call this after everything in it has been saved.
+pub fn truncate<C: Chunk>(chunk_handler: &mut ChunkHandler<C>) -> Result<(), String> {
+    // waits for the writer, so nothing it still had queued ends up in the emptied journal
+    chunk_handler.journal = EditJournal::default();
+
+    let Some(path) = chunk_handler.path.as_deref().map(journal_path) else {
+        return Ok(());
+    };
+    if path.exists() {
+        fs::remove_file(&path)
+            .map_err(|e| format!("Failed to truncate edit journal @ {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
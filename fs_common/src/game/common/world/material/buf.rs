@@ -205,9 +205,27 @@ impl MaterialBuf {
 
     #[must_use]
     pub fn rotated(&self, angle: AngleDiff) -> Self {
-        let (new_w, new_h) = match angle {
-            AngleDiff::Clockwise90 | AngleDiff::CounterClockwise90 => (self.height, self.width),
-            _ => (self.width, self.height),
+        self.transformed(angle.into())
+    }
+
+    /// Flips the buffer left to right.
+    #[must_use]
+    pub fn mirrored_x(&self) -> Self {
+        self.transformed(Orientation::MirrorX)
+    }
+
+    /// Flips the buffer top to bottom.
+    #[must_use]
+    pub fn mirrored_y(&self) -> Self {
+        self.transformed(Orientation::MirrorY)
+    }
+
+    #[must_use]
+    pub fn transformed(&self, orientation: Orientation) -> Self {
+        let (new_w, new_h) = if orientation.swaps_axes() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
         };
 
         let mut new = Self {
@@ -218,11 +236,15 @@ impl MaterialBuf {
 
         for new_x in 0..new_w {
             for new_y in 0..new_h {
-                let (old_x, old_y) = match angle {
-                    AngleDiff::None => (new_x, new_y),
-                    AngleDiff::Clockwise90 => (new_y, new_w - new_x - 1),
-                    AngleDiff::CounterClockwise90 => (new_h - new_y - 1, new_x),
-                    AngleDiff::Angle180 => (new_w - new_x - 1, new_h - new_y - 1),
+                let (old_x, old_y) = match orientation {
+                    Orientation::None => (new_x, new_y),
+                    Orientation::Clockwise90 => (new_y, new_w - new_x - 1),
+                    Orientation::CounterClockwise90 => (new_h - new_y - 1, new_x),
+                    Orientation::Angle180 => (new_w - new_x - 1, new_h - new_y - 1),
+                    Orientation::MirrorX => (new_w - new_x - 1, new_y),
+                    Orientation::MirrorY => (new_x, new_h - new_y - 1),
+                    Orientation::Transpose => (new_y, new_x),
+                    Orientation::AntiTranspose => (new_h - new_y - 1, new_w - new_x - 1),
                 };
                 new.set(
                     new_x,
@@ -235,6 +257,88 @@ impl MaterialBuf {
 
         new
     }
+
+    pub fn paste_transformed(
+        &self,
+        chunk_handler: &mut dyn FSChunkAccess,
+        x: impl Into<i64>,
+        y: impl Into<i64>,
+        orientation: Orientation,
+    ) -> Result<(), String> {
+        if orientation == Orientation::None {
+            self.paste(chunk_handler, x, y)
+        } else {
+            self.transformed(orientation).paste(chunk_handler, x, y)
+        }
+    }
+}
+
+/// One of the 8 ways a [`MaterialBuf`] can be rotated and/or mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    None,
+    Clockwise90,
+    CounterClockwise90,
+    Angle180,
+    /// Flipped left to right.
+    MirrorX,
+    /// Flipped top to bottom.
+    MirrorY,
+    /// Flipped across the top left to bottom right diagonal.
+    Transpose,
+    /// Flipped across the top right to bottom left diagonal.
+    AntiTranspose,
+}
+
+impl Orientation {
+    pub const ALL: [Self; 8] = [
+        Self::None,
+        Self::Clockwise90,
+        Self::CounterClockwise90,
+        Self::Angle180,
+        Self::MirrorX,
+        Self::MirrorY,
+        Self::Transpose,
+        Self::AntiTranspose,
+    ];
+
+    /// Combines a rotation with an optional left to right flip applied before it.
+    pub fn new(angle: AngleDiff, mirror_x: bool) -> Self {
+        match (angle, mirror_x) {
+            (AngleDiff::None, false) => Self::None,
+            (AngleDiff::Clockwise90, false) => Self::Clockwise90,
+            (AngleDiff::CounterClockwise90, false) => Self::CounterClockwise90,
+            (AngleDiff::Angle180, false) => Self::Angle180,
+            (AngleDiff::None, true) => Self::MirrorX,
+            (AngleDiff::Clockwise90, true) => Self::AntiTranspose,
+            (AngleDiff::CounterClockwise90, true) => Self::Transpose,
+            (AngleDiff::Angle180, true) => Self::MirrorY,
+        }
+    }
+
+    /// Whether width and height are swapped by this orientation.
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Self::Clockwise90 | Self::CounterClockwise90 | Self::Transpose | Self::AntiTranspose
+        )
+    }
+
+    /// The orientation that undoes this one.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Clockwise90 => Self::CounterClockwise90,
+            Self::CounterClockwise90 => Self::Clockwise90,
+            other => other,
+        }
+    }
+}
+
+impl From<AngleDiff> for Orientation {
+    fn from(angle: AngleDiff) -> Self {
+        Self::new(angle, false)
+    }
 }
 
 impl Debug for MaterialBuf {
@@ -297,3 +401,96 @@ impl MaterialRect {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::{
+        gen::structure::AngleDiff,
+        material::{color::Color, MaterialInstance},
+    };
+
+    use super::{MaterialBuf, Orientation};
+
+    fn mat(n: u8) -> MaterialInstance {
+        MaterialInstance {
+            color: Color::rgb(n, 0, 0),
+            ..MaterialInstance::air()
+        }
+    }
+
+    /// Builds a buffer from rows of ids, which show up in the red channel.
+    fn buf(rows: &[&[u8]]) -> MaterialBuf {
+        MaterialBuf::new(
+            rows[0].len() as u16,
+            rows.len() as u16,
+            rows.iter()
+                .flat_map(|r| r.iter().copied().map(mat))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    fn asymmetric() -> MaterialBuf {
+        buf(&[&[1, 2, 3], &[4, 5, 6]])
+    }
+
+    #[test]
+    fn all_orientations() {
+        let b = asymmetric();
+        let cases = [
+            (Orientation::None, buf(&[&[1, 2, 3], &[4, 5, 6]])),
+            (Orientation::Clockwise90, buf(&[&[4, 1], &[5, 2], &[6, 3]])),
+            (
+                Orientation::CounterClockwise90,
+                buf(&[&[3, 6], &[2, 5], &[1, 4]]),
+            ),
+            (Orientation::Angle180, buf(&[&[6, 5, 4], &[3, 2, 1]])),
+            (Orientation::MirrorX, buf(&[&[3, 2, 1], &[6, 5, 4]])),
+            (Orientation::MirrorY, buf(&[&[4, 5, 6], &[1, 2, 3]])),
+            (Orientation::Transpose, buf(&[&[1, 4], &[2, 5], &[3, 6]])),
+            (
+                Orientation::AntiTranspose,
+                buf(&[&[6, 3], &[5, 2], &[4, 1]]),
+            ),
+        ];
+
+        for (orientation, expected) in cases {
+            let t = b.transformed(orientation);
+            assert!(t == expected, "{orientation:?}");
+            assert!(t.transformed(orientation.inverse()) == b, "{orientation:?}");
+        }
+    }
+
+    #[test]
+    fn rotation_and_mirror_combine() {
+        let b = asymmetric();
+        for angle in [
+            AngleDiff::None,
+            AngleDiff::Clockwise90,
+            AngleDiff::CounterClockwise90,
+            AngleDiff::Angle180,
+        ] {
+            assert!(b.rotated(angle) == b.transformed(angle.into()));
+            assert!(
+                b.mirrored_x().rotated(angle) == b.transformed(Orientation::new(angle, true)),
+                "{angle:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let b = asymmetric();
+
+        let mut r = b.clone();
+        for _ in 0..4 {
+            r = r.rotated(AngleDiff::Clockwise90);
+        }
+        assert!(r == b);
+
+        assert!(b.mirrored_x().mirrored_x() == b);
+        assert!(b.mirrored_y().mirrored_y() == b);
+        assert!(b.mirrored_x() != b);
+        assert!(b.mirrored_y() != b);
+    }
+}
@@ -62,6 +62,26 @@ impl Color {
         Self::rgba_const(self.r, self.g, self.b, a.col_num())
     }
 
+    /// Multiplies the alpha by `a`, so `a = 1` keeps it as it is.
+    #[inline]
+    #[must_use]
+    pub fn faded(self, a: impl IntoColorNum) -> Self {
+        let a = u16::from(self.a) * u16::from(a.col_num()) / u16::from(u8::MAX);
+        self.with_a(u8::try_from(a).unwrap_or(u8::MAX))
+    }
+
+    /// Blends from `self` at `t = 0` to `other` at `t = 1`.
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let mix = |a: u8, b: u8| f32::from(a) + (f32::from(b) - f32::from(a)) * t.clamp(0.0, 1.0);
+        Self::rgba(
+            mix(self.r, other.r).round() as u8,
+            mix(self.g, other.g).round() as u8,
+            mix(self.b, other.b).round() as u8,
+            mix(self.a, other.a).round() as u8,
+        )
+    }
+
     #[inline]
     pub fn r_f32(&self) -> f32 {
         f32::from(self.r) / f32::from(u8::MAX)
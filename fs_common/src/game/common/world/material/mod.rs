@@ -1,13 +1,21 @@
 pub mod buf;
 pub mod color;
 pub mod placer;
+pub mod remap;
+pub mod render_layer;
+pub mod tag;
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
 use crate::game::common::registry::{Registry, RegistryID};
 
-use self::color::Color;
+use self::{
+    color::Color,
+    placer::MaterialPlacer,
+    render_layer::RenderLayer,
+    tag::{MaterialMatch, MaterialRules, MaterialTag, MaterialTagSet},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum PhysicsType {
@@ -19,9 +27,203 @@ pub enum PhysicsType {
     Object,
 }
 
+impl PhysicsType {
+    /// How much light a pixel of this type blocks, for materials that don't set their own opacity
+    pub fn default_opacity(self) -> f32 {
+        match self {
+            Self::Air | Self::Gas => 0.0,
+            Self::Liquid => 0.15,
+            Self::Solid | Self::Sand | Self::Object => 1.0,
+        }
+    }
+
+    /// How hard a pixel of this type is to break with impacts, for materials that don't set their own strength.
+    ///
+    /// An impact breaks a pixel if its impulse (relative to the minimum needed to break anything,
+    ///   and falling off with distance) is more than this, so infinite strength can never break.
+    pub fn default_strength(self) -> f32 {
+        match self {
+            Self::Sand => 1.0,
+            Self::Solid => 4.0,
+            Self::Air | Self::Liquid | Self::Gas | Self::Object => f32::INFINITY,
+        }
+    }
+}
+
+/// Strengths for tagged materials that don't set their own, checked before [`PhysicsType::default_strength`].
+pub fn tag_strengths() -> MaterialRules<f32> {
+    MaterialRules::new().with(MaterialMatch::Tag(MaterialTag::BRITTLE), 2.0)
+}
+
+/// A set of [`PhysicsType`]s, like which ones get simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicsTypeSet(u8);
+
+impl PhysicsTypeSet {
+    pub const ALL: Self = Self(u8::MAX);
+    pub const NONE: Self = Self(0);
+
+    fn bit(physics: PhysicsType) -> u8 {
+        1 << physics as u8
+    }
+
+    pub fn contains(self, physics: PhysicsType) -> bool {
+        self.0 & Self::bit(physics) != 0
+    }
+
+    #[must_use]
+    pub fn with(self, physics: PhysicsType, enabled: bool) -> Self {
+        if enabled {
+            Self(self.0 | Self::bit(physics))
+        } else {
+            Self(self.0 & !Self::bit(physics))
+        }
+    }
+}
+
+impl Default for PhysicsTypeSet {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Where sand pixels go when they can't fall straight down.
+///
+/// Set for everything with [`Settings::sand_spread`](crate::game::common::Settings::sand_spread),
+///   or per material with [`Material::sand_spread`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandSpread {
+    /// Slide down to an empty diagonal neighbor, which piles up at about 45 degrees.
+    Diagonal,
+    /// Step to an empty side neighbor, only where the ground drops off by at least 2 pixels,
+    ///   and fall from there. Piles come out about twice as steep.
+    Cardinal,
+}
+
+impl SandSpread {
+    pub fn values() -> &'static [Self] {
+        &[Self::Diagonal, Self::Cardinal]
+    }
+}
+
 #[derive(Debug)]
 pub struct Material {
     pub display_name: String,
+    /// How much light is blocked by one pixel of this material, from 0.0 (clear) to 1.0 (opaque).
+    ///
+    /// If `None`, [`PhysicsType::default_opacity`] is used.
+    pub opacity: Option<f32>,
+    /// How hard a pixel of this material is to break with impacts. `f32::INFINITY` can never break, like bedrock.
+    ///
+    /// If `None`, the [`tag_strengths`] for its tags are used, or [`PhysicsType::default_strength`] if none match.
+    pub strength: Option<f32>,
+    /// What a pixel of this material bursts into when it is destroyed, see [`crate::game::common::world::destroy::destroy_pixel`].
+    ///
+    /// If `None`, destroyed pixels just disappear.
+    pub destroy_particles: Option<DestroyParticles>,
+    /// Groups this material belongs to, so [`tag::MaterialRules`] can apply to it.
+    pub tags: MaterialTagSet,
+    /// Which pass this material is drawn in, see [`RenderLayer`].
+    pub render_layer: RenderLayer,
+    /// How see-through this material is drawn, from 0 (invisible) to 255 (opaque).
+    ///
+    /// Multiplied into each pixel's own alpha when its color is drawn, so air stays invisible,
+    ///   see [`RenderLayers::color_of`](render_layer::RenderLayers::color_of). If `None`, pixels are drawn as they are.
+    pub alpha: Option<u8>,
+    /// Makes solid pixels of this material add or remove material around them every tick.
+    pub emitter: Option<Emitter>,
+    /// Whether solid pixels of this material block entities, see [`UpdatePhysicsEntities`](super::entity::UpdatePhysicsEntities).
+    ///
+    /// Sand and liquid rest on them either way, so this is for decorations entities pass in front of.
+    pub entity_collidable: bool,
+    /// Makes liquid pixels of this material freeze solid, starting from the surface.
+    pub freeze: Option<Freeze>,
+    /// How sand pixels of this material spread out. If `None`, [`Settings::sand_spread`](crate::game::common::Settings::sand_spread) is used.
+    pub sand_spread: Option<SandSpread>,
+    /// How much of its speed a particle keeps when it bounces, from 0 (sticks where it lands) to 1.
+    ///
+    /// Both the particle's and the surface's material count, see [`super::particle::bounce_restitution`].
+    pub restitution: f32,
+    /// What digging up a pixel of this material puts in an [`Inventory`](super::entity::Inventory).
+    ///
+    /// If `None`, it can't be collected.
+    pub placer: Option<RegistryID<MaterialPlacer>>,
+    /// Whether pixels of this material count as empty space, see [`MaterialInstance::is_empty`].
+    pub empty: bool,
+    /// How hot pixels of this material are, in degrees Celsius.
+    ///
+    /// Pixels don't heat up or cool down on their own, so this is the same for every pixel of the material.
+    pub temperature: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            display_name: String::new(),
+            opacity: None,
+            strength: None,
+            destroy_particles: None,
+            tags: MaterialTagSet::NONE,
+            render_layer: RenderLayer::Main,
+            alpha: None,
+            emitter: None,
+            entity_collidable: true,
+            freeze: None,
+            sand_spread: None,
+            restitution: 0.0,
+            placer: None,
+            empty: false,
+            temperature: AMBIENT_TEMPERATURE,
+        }
+    }
+}
+
+/// The [`Material::temperature`] of materials that don't set their own, in degrees Celsius.
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+
+/// Particles spawned in place of a destroyed pixel, like glass shattering into shards.
+#[derive(Debug, Clone)]
+pub struct DestroyParticles {
+    /// How many particles one destroyed pixel turns into.
+    pub count: u8,
+    /// How the particles behave once they land.
+    pub physics: PhysicsType,
+    /// The particles' color, or `None` to keep the color of the destroyed pixel.
+    pub color: Option<Color>,
+    /// How fast the particles are thrown out in a random direction, in pixels per tick.
+    pub speed: f32,
+    /// Air resistance of the particles, see [`super::particle::Particle::drag`].
+    pub drag: f64,
+}
+
+/// Solid pixels that keep adding or removing material next to them, see [`Material::emitter`].
+#[derive(Debug, Clone)]
+pub enum Emitter {
+    /// Puts `output` into an empty neighbor (below first, then the sides, then above),
+    ///   on average `rate` times per tick, like a spring or a faucet.
+    ///
+    /// If there's no empty neighbor it waits until there is.
+    Source { output: MaterialInstance, rate: f32 },
+    /// Deletes sand and liquid pixels next to it, each with a `rate` chance per tick.
+    Drain { rate: f32 },
+}
+
+/// Liquid pixels turning into something solid, like water into ice, see [`Material::freeze`].
+///
+/// Only resting pixels touching something other than liquid (air, the walls of their container,
+///   or pixels that already froze) can freeze, so a pool grows a crust inwards instead of
+///   freezing all at once.
+///
+/// Temperatures are only drawn (see [`Material::temperature`]), so this happens wherever the liquid
+///   rests, not just somewhere cold.
+#[derive(Debug, Clone)]
+pub struct Freeze {
+    /// What a pixel turns into when it freezes.
+    pub into: MaterialInstance,
+    /// Chance per tick for a pixel exposed on all 4 sides to freeze.
+    ///
+    /// Pixels with fewer exposed sides freeze proportionally slower.
+    pub rate: f32,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
@@ -30,6 +232,10 @@ pub struct MaterialInstance {
     pub physics: PhysicsType,
     pub color: Color,
     pub light: [f32; 3],
+    /// How wet this pixel is, from 0.0 (dry) to 1.0 (soaked). Only used by powders.
+    pub moisture: f32,
+    /// How many updates in a row this pixel has been falling. Only used by liquids.
+    pub fall: u8,
 }
 
 impl MaterialInstance {
@@ -43,10 +249,22 @@ impl MaterialInstance {
         Self { light, ..self }
     }
 
+    /// Whether this pixel counts as empty space, which other pixels can move into.
+    ///
+    /// This is any material with [`Material::empty`] set, not just [`AIR`], so things like
+    ///   [`VACUUM`] and [`CAVE_AIR`] are empty too. Materials that aren't registered are empty
+    ///   if they were instanced as [`PhysicsType::Air`].
+    #[inline(always)] // this function is very hot
+    pub fn is_empty(&self, materials: &MaterialRegistry) -> bool {
+        materials
+            .get(&self.material_id)
+            .map_or(self.physics == PhysicsType::Air, |m| m.empty)
+    }
+
     /// Any instance that returns false will be skipped when simulating
     #[inline(always)] // this function is very hot
     pub fn dynamic(&self) -> bool {
-        self.physics == PhysicsType::Sand
+        matches!(self.physics, PhysicsType::Sand | PhysicsType::Liquid)
     }
 }
 
@@ -64,11 +282,15 @@ impl RegistryID<Material> {
             physics,
             color,
             light: [0.0; 3],
+            moisture: 0.0,
+            fall: 0,
         }
     }
 }
 
 pub static AIR: Lazy<RegistryID<Material>> = Lazy::new(|| "air".into());
+pub static VACUUM: Lazy<RegistryID<Material>> = Lazy::new(|| "vacuum".into());
+pub static CAVE_AIR: Lazy<RegistryID<Material>> = Lazy::new(|| "cave_air".into());
 pub static TEST: Lazy<RegistryID<Material>> = Lazy::new(|| "test".into());
 
 pub static COBBLE_STONE: Lazy<RegistryID<Material>> = Lazy::new(|| "cobble_stone".into());
@@ -79,6 +301,16 @@ pub static FADED_COBBLE_DIRT: Lazy<RegistryID<Material>> = Lazy::new(|| "faded_c
 pub static SMOOTH_STONE: Lazy<RegistryID<Material>> = Lazy::new(|| "smooth_stone".into());
 pub static SMOOTH_DIRT: Lazy<RegistryID<Material>> = Lazy::new(|| "smooth_dirt".into());
 
+pub static GLASS: Lazy<RegistryID<Material>> = Lazy::new(|| "glass".into());
+pub static SNOW: Lazy<RegistryID<Material>> = Lazy::new(|| "snow".into());
+pub static WATER: Lazy<RegistryID<Material>> = Lazy::new(|| "water".into());
+pub static ICE: Lazy<RegistryID<Material>> = Lazy::new(|| "ice".into());
+pub static SPRING: Lazy<RegistryID<Material>> = Lazy::new(|| "spring".into());
+pub static DRAIN: Lazy<RegistryID<Material>> = Lazy::new(|| "drain".into());
+pub static LAVA: Lazy<RegistryID<Material>> = Lazy::new(|| "lava".into());
+
+pub static BEDROCK: Lazy<RegistryID<Material>> = Lazy::new(|| "bedrock".into());
+
 pub static STRUCTURE_VOID: Lazy<RegistryID<Material>> = Lazy::new(|| "structure_void".into());
 
 pub type MaterialRegistry = Registry<Material>;
@@ -86,36 +318,211 @@ pub type MaterialRegistry = Registry<Material>;
 pub fn init_material_types() -> MaterialRegistry {
     let mut registry = Registry::new();
 
-    registry.register(AIR.clone(), Material { display_name: "Air".to_string() });
-    registry.register(TEST.clone(), Material { display_name: "Test".to_string() });
+    register_empty(&mut registry);
+    register_terrain(&mut registry);
+    register_liquids(&mut registry);
+    register_misc(&mut registry);
+
+    registry
+}
+
+/// Materials that count as empty space.
+fn register_empty(registry: &mut MaterialRegistry) {
+    registry.register(
+        AIR.clone(),
+        Material {
+            display_name: "Air".to_string(),
+            empty: true,
+            ..Material::default()
+        },
+    );
+    registry.register(
+        VACUUM.clone(),
+        Material {
+            display_name: "Vacuum".to_string(),
+            empty: true,
+            ..Material::default()
+        },
+    );
+    registry.register(
+        CAVE_AIR.clone(),
+        Material {
+            display_name: "Cave Air".to_string(),
+            empty: true,
+            ..Material::default()
+        },
+    );
+}
+
+/// Materials worlds are generated from.
+fn register_terrain(registry: &mut MaterialRegistry) {
+    registry.register(
+        TEST.clone(),
+        Material {
+            display_name: "Test".to_string(),
+            ..Material::default()
+        },
+    );
     registry.register(
         COBBLE_STONE.clone(),
-        Material { display_name: "Cobblestone".to_string() },
+        Material {
+            display_name: "Cobblestone".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            restitution: 0.5,
+            placer: Some(placer::COBBLE_STONE.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
         COBBLE_DIRT.clone(),
-        Material { display_name: "Cobbledirt".to_string() },
+        Material {
+            display_name: "Cobbledirt".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::SOIL]),
+            placer: Some(placer::COBBLE_DIRT.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
         FADED_COBBLE_STONE.clone(),
-        Material { display_name: "Faded Cobblestone".to_string() },
+        Material {
+            display_name: "Faded Cobblestone".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            restitution: 0.5,
+            placer: Some(placer::FADED_COBBLE_STONE.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
         FADED_COBBLE_DIRT.clone(),
-        Material { display_name: "Faded Cobbledirt".to_string() },
+        Material {
+            display_name: "Faded Cobbledirt".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::SOIL]),
+            placer: Some(placer::FADED_COBBLE_DIRT.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
         SMOOTH_STONE.clone(),
-        Material { display_name: "Smoth Stone".to_string() },
+        Material {
+            display_name: "Smoth Stone".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            restitution: 0.6,
+            placer: Some(placer::SMOOTH_STONE.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
         SMOOTH_DIRT.clone(),
-        Material { display_name: "Dirt".to_string() },
+        Material {
+            display_name: "Dirt".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::SOIL]),
+            placer: Some(placer::SMOOTH_DIRT.clone()),
+            ..Material::default()
+        },
     );
     registry.register(
-        STRUCTURE_VOID.clone(),
-        Material { display_name: "Structure Void".to_string() },
+        SNOW.clone(),
+        Material {
+            display_name: "Snow".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::COHESIVE]),
+            ..Material::default()
+        },
     );
+    registry.register(
+        BEDROCK.clone(),
+        Material {
+            display_name: "Bedrock".to_string(),
+            strength: Some(f32::INFINITY),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            restitution: 0.6,
+            ..Material::default()
+        },
+    );
+}
 
-    registry
+/// Liquids and the blocks that add or remove them.
+fn register_liquids(registry: &mut MaterialRegistry) {
+    registry.register(
+        WATER.clone(),
+        Material {
+            display_name: "Water".to_string(),
+            alpha: Some(200),
+            freeze: Some(Freeze {
+                into: ICE.instance(PhysicsType::Solid, Color::rgba(180, 220, 255, 220)),
+                rate: 0.0005,
+            }),
+            ..Material::default()
+        },
+    );
+    registry.register(
+        ICE.clone(),
+        Material {
+            display_name: "Ice".to_string(),
+            opacity: Some(0.3),
+            tags: MaterialTagSet::of(&[MaterialTag::BRITTLE]),
+            alpha: Some(220),
+            restitution: 0.1,
+            ..Material::default()
+        },
+    );
+    registry.register(
+        SPRING.clone(),
+        Material {
+            display_name: "Spring".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            emitter: Some(Emitter::Source {
+                output: WATER.instance(PhysicsType::Liquid, Color::rgba(48, 96, 255, 200)),
+                rate: 0.5,
+            }),
+            ..Material::default()
+        },
+    );
+    registry.register(
+        DRAIN.clone(),
+        Material {
+            display_name: "Drain".to_string(),
+            tags: MaterialTagSet::of(&[MaterialTag::STONE]),
+            emitter: Some(Emitter::Drain { rate: 1.0 }),
+            ..Material::default()
+        },
+    );
+    registry.register(
+        LAVA.clone(),
+        Material {
+            display_name: "Lava".to_string(),
+            placer: Some(placer::LAVA.clone()),
+            temperature: 1100.0,
+            ..Material::default()
+        },
+    );
+}
+
+fn register_misc(registry: &mut MaterialRegistry) {
+    registry.register(
+        GLASS.clone(),
+        Material {
+            display_name: "Glass".to_string(),
+            opacity: Some(0.1),
+            destroy_particles: Some(DestroyParticles {
+                count: 3,
+                physics: PhysicsType::Sand,
+                color: None,
+                speed: 1.5,
+                drag: 0.05,
+            }),
+            tags: MaterialTagSet::of(&[MaterialTag::BRITTLE]),
+            render_layer: RenderLayer::Foreground,
+            alpha: Some(160),
+            restitution: 0.5,
+            placer: Some(placer::GLASS.clone()),
+            ..Material::default()
+        },
+    );
+    registry.register(
+        STRUCTURE_VOID.clone(),
+        Material {
+            display_name: "Structure Void".to_string(),
+            ..Material::default()
+        },
+    );
 }
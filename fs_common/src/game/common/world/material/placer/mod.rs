@@ -60,6 +60,8 @@ pub static FADED_COBBLE_DIRT: Lazy<RegistryID<MaterialPlacer>> =
     Lazy::new(|| "faded_cobble_dirt".into());
 pub static SMOOTH_STONE: Lazy<RegistryID<MaterialPlacer>> = Lazy::new(|| "smooth_stone".into());
 pub static SMOOTH_DIRT: Lazy<RegistryID<MaterialPlacer>> = Lazy::new(|| "smooth_dirt".into());
+pub static GLASS: Lazy<RegistryID<MaterialPlacer>> = Lazy::new(|| "glass".into());
+pub static LAVA: Lazy<RegistryID<MaterialPlacer>> = Lazy::new(|| "lava".into());
 
 pub type MaterialPlacerRegistry = Registry<MaterialPlacer>;
 
@@ -188,6 +190,28 @@ pub fn init_material_placers(file_helper: &FileHelper) -> MaterialPlacerRegistry
         file_helper,
     );
 
+    registry.register(
+        GLASS.clone(),
+        MaterialPlacer {
+            meta: MaterialPlacerMeta { display_name: "Glass".to_string() },
+            sampler: Box::new(
+                super::GLASS.instance(PhysicsType::Solid, Color::rgba(200, 230, 255, 96)),
+            ),
+        },
+    );
+
+    registry.register(
+        LAVA.clone(),
+        MaterialPlacer {
+            meta: MaterialPlacerMeta { display_name: "Lava".to_string() },
+            sampler: Box::new(
+                super::LAVA
+                    .instance(PhysicsType::Liquid, Color::rgb(255, 96, 16))
+                    .with_light([1.0, 0.4, 0.1]),
+            ),
+        },
+    );
+
     // test placers
 
     let register_test = |color: &str, registry: &mut MaterialPlacerRegistry| {
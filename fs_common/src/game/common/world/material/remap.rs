@@ -0,0 +1,100 @@
+use std::borrow::Borrow;
+
+use crate::game::common::Registries;
+
+use super::{
+    placer::{MaterialPlacerRegistry, MaterialPlacerSampler},
+    MaterialInstance, MaterialRegistry,
+};
+
+/// Updates existing pixels after the registries were loaded again with [`Registries::init`].
+///
+/// Pixels of materials that no longer exist turn into air. Pixels of materials that still exist
+///   keep their physics and state, but take their color from the placer with the same id,
+///   so changed textures and colors show up without regenerating anything.
+pub struct MaterialRemap<'a> {
+    materials: &'a MaterialRegistry,
+    placers: &'a MaterialPlacerRegistry,
+}
+
+impl<'a> MaterialRemap<'a> {
+    pub fn new(registries: &'a Registries) -> Self {
+        Self {
+            materials: &registries.materials,
+            placers: &registries.material_placers,
+        }
+    }
+
+    /// What the pixel at `x`, `y` should become, or `None` if it doesn't change.
+    pub fn remap(&self, mat: &MaterialInstance, x: i64, y: i64) -> Option<MaterialInstance> {
+        if mat.is_empty(self.materials) {
+            return None;
+        }
+
+        let id: &str = mat.material_id.borrow();
+        if self.materials.get(id).is_none() {
+            return Some(MaterialInstance::air());
+        }
+
+        let sampled = self.placers.get(id)?.pixel(x, y);
+        (sampled.material_id == mat.material_id && sampled.color != mat.color)
+            .then(|| MaterialInstance { color: sampled.color, ..mat.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::{
+        world::material::{
+            color::Color,
+            placer::{MaterialPlacer, MaterialPlacerMeta},
+            Material, MaterialInstance, PhysicsType, TEST,
+        },
+        Registries,
+    };
+
+    use super::MaterialRemap;
+
+    fn test_registries(color: Color) -> Registries {
+        let mut registries = Registries::empty();
+        registries.materials.register(
+            TEST.clone(),
+            Material {
+                display_name: "Test".to_string(),
+                ..Material::default()
+            },
+        );
+        registries.material_placers.register(
+            "test",
+            MaterialPlacer {
+                meta: MaterialPlacerMeta { display_name: "Test".to_string() },
+                sampler: Box::new(TEST.instance(PhysicsType::Solid, color)),
+            },
+        );
+        registries
+    }
+
+    #[test]
+    fn reloaded_color_and_removed_material() {
+        let sand = MaterialInstance {
+            moisture: 0.5,
+            ..TEST.instance(PhysicsType::Sand, Color::rgb(255, 0, 0))
+        };
+
+        // same color as the placer, nothing to do
+        let registries = test_registries(Color::rgb(255, 0, 0));
+        assert_eq!(MaterialRemap::new(&registries).remap(&sand, 3, 4), None);
+
+        // only the color changes, it's still wet sand
+        let registries = test_registries(Color::rgb(0, 0, 255));
+        assert_eq!(
+            MaterialRemap::new(&registries).remap(&sand, 3, 4),
+            Some(MaterialInstance { color: Color::rgb(0, 0, 255), ..sand.clone() })
+        );
+
+        let registries = Registries::empty();
+        let remap = MaterialRemap::new(&registries);
+        assert_eq!(remap.remap(&sand, 3, 4), Some(MaterialInstance::air()));
+        assert_eq!(remap.remap(&MaterialInstance::air(), 3, 4), None);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use crate::game::common::registry::RegistryID;
+
+use super::{color::Color, Material, MaterialInstance, MaterialRegistry};
+
+/// Which pass a material's pixels are drawn in.
+///
+/// Both layers are lit the same way, lighting is applied after all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenderLayer {
+    /// Drawn with the rest of the terrain, behind entities, rigidbodies and particles.
+    #[default]
+    Main,
+    /// Drawn in front of entities, rigidbodies and particles, like glass.
+    Foreground,
+}
+
+impl RenderLayer {
+    pub fn values() -> &'static [Self] {
+        &[Self::Main, Self::Foreground]
+    }
+}
+
+/// The [`RenderLayer`] and [`Material::alpha`] of every material, so pixels can be sorted into
+///   layers without going through the registry each time.
+#[derive(Debug, Clone, Default)]
+pub struct RenderLayers {
+    /// Only materials not on [`RenderLayer::Main`].
+    layers: HashMap<RegistryID<Material>, RenderLayer, ahash::RandomState>,
+    /// Only materials with an alpha.
+    alphas: HashMap<RegistryID<Material>, u8, ahash::RandomState>,
+}
+
+impl RenderLayers {
+    pub fn from_registry(materials: &MaterialRegistry) -> Self {
+        Self {
+            layers: materials
+                .into_iter()
+                .filter(|(_, mat)| mat.render_layer != RenderLayer::Main)
+                .map(|(id, mat)| (id.clone(), mat.render_layer))
+                .collect(),
+            alphas: materials
+                .into_iter()
+                .filter_map(|(id, mat)| mat.alpha.map(|a| (id.clone(), a)))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn layer_of(&self, mat: &MaterialInstance) -> RenderLayer {
+        self.layers
+            .get(&mat.material_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether any material is drawn outside of [`RenderLayer::Main`].
+    pub fn any_outside_main(&self) -> bool {
+        !self.layers.is_empty()
+    }
+
+    /// Whether any material has an alpha, so colors need to go through [`RenderLayers::color_of`].
+    pub fn any_translucent(&self) -> bool {
+        !self.alphas.is_empty()
+    }
+
+    /// The color a pixel is drawn with, its `color` with the material's alpha multiplied in.
+    ///
+    /// Multiplying keeps transparent pixels (like air) transparent, and each translucent layer
+    ///   drawn over another lets some of it show through.
+    #[inline]
+    pub fn color_of(&self, mat: &MaterialInstance, color: Color) -> Color {
+        match self.alphas.get(&mat.material_id) {
+            Some(&alpha) => color.with_a((u16::from(color.a) * u16::from(alpha) / 255) as u8),
+            None => color,
+        }
+    }
+
+    /// The materials in `layer`, sorted by id.
+    pub fn materials_in(
+        &self,
+        materials: &MaterialRegistry,
+        layer: RenderLayer,
+    ) -> Vec<RegistryID<Material>> {
+        let mut ids: Vec<_> = materials
+            .into_iter()
+            .filter(|(id, _)| self.layers.get(*id).copied().unwrap_or_default() == layer)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::{
+        registry::Registry,
+        world::material::{self, color::Color, Material, MaterialInstance, PhysicsType},
+    };
+
+    use super::{RenderLayer, RenderLayers};
+
+    fn material(render_layer: RenderLayer) -> Material {
+        Material { render_layer, ..Material::default() }
+    }
+
+    #[test]
+    fn materials_bucketed_by_layer() {
+        let mut registry = Registry::new();
+        registry.register("stone", material(RenderLayer::Main));
+        registry.register("glass", material(RenderLayer::Foreground));
+        registry.register("ice", material(RenderLayer::Foreground));
+        registry.register("dirt", material(RenderLayer::Main));
+
+        let layers = RenderLayers::from_registry(&registry);
+        assert!(layers.any_outside_main());
+        assert_eq!(
+            layers.materials_in(&registry, RenderLayer::Main),
+            vec!["dirt".into(), "stone".into()]
+        );
+        assert_eq!(
+            layers.materials_in(&registry, RenderLayer::Foreground),
+            vec!["glass".into(), "ice".into()]
+        );
+
+        let instance =
+            |id: &str| MaterialInstance { material_id: id.into(), ..MaterialInstance::air() };
+        assert_eq!(layers.layer_of(&instance("ice")), RenderLayer::Foreground);
+        assert_eq!(layers.layer_of(&instance("stone")), RenderLayer::Main);
+        // unknown materials stay with everything else
+        assert_eq!(layers.layer_of(&instance("missing")), RenderLayer::Main);
+
+        // built in glass is drawn in front
+        let builtin = material::init_material_types();
+        let layers = RenderLayers::from_registry(&builtin);
+        let glass = material::GLASS.instance(PhysicsType::Solid, Color::WHITE);
+        assert_eq!(layers.layer_of(&glass), RenderLayer::Foreground);
+        assert_eq!(layers.layer_of(&MaterialInstance::air()), RenderLayer::Main);
+    }
+
+    #[test]
+    fn translucent_colors() {
+        let mut registry = Registry::new();
+        registry.register(
+            "glass",
+            Material { alpha: Some(128), ..material(RenderLayer::Main) },
+        );
+        registry.register("stone", material(RenderLayer::Main));
+
+        let layers = RenderLayers::from_registry(&registry);
+        assert!(layers.any_translucent());
+
+        let instance = |id: &str, color| MaterialInstance {
+            material_id: id.into(),
+            color,
+            ..MaterialInstance::air()
+        };
+        let glass = instance("glass", Color::rgb(200, 220, 255));
+        assert_eq!(
+            layers.color_of(&glass, glass.color),
+            Color::rgba(200, 220, 255, 128)
+        );
+        let stone = instance("stone", Color::rgb(80, 80, 80));
+        assert_eq!(layers.color_of(&stone, stone.color), stone.color);
+
+        // an already see-through pixel gets more see-through
+        let tinted = Color::rgb(200, 220, 255).with_a(128);
+        assert_eq!(layers.color_of(&glass, tinted).a, 64);
+
+        // air stays invisible, even as a translucent material
+        let air = MaterialInstance::air();
+        assert_eq!(layers.color_of(&air, air.color).a, 0);
+        assert_eq!(
+            layers.color_of(&instance("glass", air.color), air.color).a,
+            0
+        );
+
+        // built in glass is see-through
+        let builtin = material::init_material_types();
+        let layers = RenderLayers::from_registry(&builtin);
+        let glass = material::GLASS.instance(PhysicsType::Solid, Color::WHITE);
+        assert_eq!(layers.color_of(&glass, glass.color).a, 160);
+    }
+}
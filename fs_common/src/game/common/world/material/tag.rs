@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::game::common::registry::RegistryID;
+
+use super::{Material, MaterialRegistry};
+
+/// A group of materials that rules can apply to all at once, like everything that can burn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialTag(u8);
+
+impl MaterialTag {
+    pub const STONE: Self = Self(0);
+    pub const SOIL: Self = Self(1);
+    pub const METAL: Self = Self(2);
+    pub const ORGANIC: Self = Self(3);
+    pub const FLAMMABLE: Self = Self(4);
+    pub const BRITTLE: Self = Self(5);
+    /// Powders that stick to their own kind, so they can hold arches and overhangs like snow.
+    pub const COHESIVE: Self = Self(6);
+}
+
+/// The tags on a [`Material`], as a bitset so checking for one is just a mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MaterialTagSet(u32);
+
+impl MaterialTagSet {
+    pub const NONE: Self = Self(0);
+
+    pub const fn of(tags: &[MaterialTag]) -> Self {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < tags.len() {
+            bits |= 1 << tags[i].0;
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    #[inline]
+    pub const fn contains(self, tag: MaterialTag) -> bool {
+        self.0 & (1 << tag.0) != 0
+    }
+}
+
+/// What a rule in [`MaterialRules`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaterialMatch {
+    Material(RegistryID<Material>),
+    Tag(MaterialTag),
+}
+
+impl MaterialMatch {
+    pub fn matches(&self, id: &RegistryID<Material>, material: &Material) -> bool {
+        match self {
+            Self::Material(m) => m == id,
+            Self::Tag(tag) => material.tags.contains(*tag),
+        }
+    }
+}
+
+/// A table of per-material values (like how likely something is to catch fire) that can be
+///   set for a single material or for every material with a tag.
+///
+/// Rules for a specific material win over tag rules, otherwise the first matching rule is used.
+/// Use [`MaterialRules::resolve`] to look values up in hot loops.
+#[derive(Debug, Clone)]
+pub struct MaterialRules<T> {
+    rules: Vec<(MaterialMatch, T)>,
+}
+
+impl<T> MaterialRules<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    #[must_use]
+    pub fn with(mut self, matches: MaterialMatch, value: T) -> Self {
+        self.rules.push((matches, value));
+        self
+    }
+
+    pub fn get(&self, id: &RegistryID<Material>, material: &Material) -> Option<&T> {
+        self.rules
+            .iter()
+            .find(|(m, _)| matches!(m, MaterialMatch::Material(m) if m == id))
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .find(|(m, _)| matches!(m, MaterialMatch::Tag(_)) && m.matches(id, material))
+            })
+            .map(|(_, v)| v)
+    }
+
+    /// Works out the value for every registered material up front.
+    pub fn resolve(
+        &self,
+        materials: &MaterialRegistry,
+    ) -> HashMap<RegistryID<Material>, T, ahash::RandomState>
+    where
+        T: Clone,
+    {
+        materials
+            .into_iter()
+            .filter_map(|(id, mat)| self.get(id, mat).map(|v| (id.clone(), v.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::{
+        registry::Registry,
+        world::material::{self, Material},
+    };
+
+    use super::{MaterialMatch, MaterialRules, MaterialTag, MaterialTagSet};
+
+    fn material(name: &str, tags: MaterialTagSet) -> Material {
+        Material {
+            display_name: name.to_string(),
+            tags,
+            ..Material::default()
+        }
+    }
+
+    #[test]
+    fn tag_rules_apply_to_tagged_materials() {
+        let mut registry = Registry::new();
+        registry.register(
+            "wood",
+            material(
+                "Wood",
+                MaterialTagSet::of(&[MaterialTag::ORGANIC, MaterialTag::FLAMMABLE]),
+            ),
+        );
+        registry.register(
+            "coal",
+            material("Coal", MaterialTagSet::of(&[MaterialTag::FLAMMABLE])),
+        );
+        registry.register(
+            "oil",
+            material("Oil", MaterialTagSet::of(&[MaterialTag::FLAMMABLE])),
+        );
+        registry.register(
+            "stone",
+            material("Stone", MaterialTagSet::of(&[MaterialTag::STONE])),
+        );
+
+        // chance to catch fire
+        let rules = MaterialRules::new()
+            .with(MaterialMatch::Tag(MaterialTag::FLAMMABLE), 0.25)
+            .with(MaterialMatch::Material("oil".into()), 0.9);
+        let resolved = rules.resolve(&registry);
+
+        assert_eq!(resolved.get("wood"), Some(&0.25));
+        assert_eq!(resolved.get("coal"), Some(&0.25));
+        // the material rule wins even though it was added after the tag
+        assert_eq!(resolved.get("oil"), Some(&0.9));
+        assert_eq!(resolved.get("stone"), None);
+        assert_eq!(resolved.len(), 3);
+
+        // the built in materials are tagged too
+        let builtin = material::init_material_types();
+        let stone = builtin.get(&*material::SMOOTH_STONE).unwrap();
+        assert!(stone.tags.contains(MaterialTag::STONE));
+        assert!(!stone.tags.contains(MaterialTag::FLAMMABLE));
+
+        // glass gets its strength from being brittle
+        let strengths = material::tag_strengths().resolve(&builtin);
+        assert_eq!(builtin.get(&*material::GLASS).unwrap().strength, None);
+        assert_eq!(strengths.get(&*material::GLASS), Some(&2.0));
+        assert_eq!(strengths.get(&*material::SMOOTH_STONE), None);
+    }
+}
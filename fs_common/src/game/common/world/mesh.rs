@@ -9,11 +9,22 @@ pub type Poly = Vec<VPoint>;
 pub type Loop = Vec<Poly>;
 pub type Mesh = Vec<Loop>;
 
+/// How far (in pixels) a simplified outline may stray from the pixel outline by default.
+pub const DEFAULT_SIMPLIFY_TOLERANCE: f64 = 1.0;
+
 pub fn pixels_to_valuemap(pixels: &[MaterialInstance]) -> Vec<f64> {
+    pixels_to_valuemap_filtered(pixels, |_| true)
+}
+
+/// Like [`pixels_to_valuemap`], but only solid pixels that `include` accepts are filled in.
+pub fn pixels_to_valuemap_filtered(
+    pixels: &[MaterialInstance],
+    include: impl Fn(&MaterialInstance) -> bool,
+) -> Vec<f64> {
     pixels
         .iter()
         .map(|p| {
-            if p.physics == PhysicsType::Solid {
+            if p.physics == PhysicsType::Solid && include(p) {
                 1.0
             } else {
                 0.0
@@ -43,6 +54,17 @@ pub fn generate_mesh_with_simplified(
     values: &[f64],
     width: u32,
     height: u32,
+) -> Result<(Mesh, Mesh), String> {
+    generate_mesh_with_tolerance(values, width, height, DEFAULT_SIMPLIFY_TOLERANCE)
+}
+
+/// Like [`generate_mesh_with_simplified`], but the simplified outlines can be up to `tolerance`
+///   pixels away from the pixel outlines. Bigger tolerances give fewer vertices.
+pub fn generate_mesh_with_tolerance(
+    values: &[f64],
+    width: u32,
+    height: u32,
+    tolerance: f64,
 ) -> Result<(Mesh, Mesh), String> {
     if values.len() as u32 != width * height {
         return Err(format!(
@@ -107,7 +129,7 @@ pub fn generate_mesh_with_simplified(
                                 })
                                 .collect();
 
-                        let keep = ramer_douglas_peucker::rdp(&pts, 1.0);
+                        let keep = ramer_douglas_peucker::rdp(&pts, tolerance);
 
                         let p1: Poly = pts.iter().map(|p| vec![p.x, p.y]).collect();
                         let p2: Poly = pts
@@ -160,3 +182,73 @@ pub fn triangulate(mesh: &Mesh) -> Vec<Vec<Tri>> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_mesh_with_tolerance, Mesh};
+
+    fn area(mesh: &Mesh) -> f64 {
+        mesh.iter()
+            .flatten()
+            .map(|poly| {
+                let twice: f64 = (0..poly.len())
+                    .map(|i| {
+                        let (a, b) = (&poly[i], &poly[(i + 1) % poly.len()]);
+                        a[0] * b[1] - b[0] * a[1]
+                    })
+                    .sum();
+                twice / 2.0
+            })
+            .sum::<f64>()
+            .abs()
+    }
+
+    fn perimeter(mesh: &Mesh) -> f64 {
+        mesh.iter()
+            .flatten()
+            .map(|poly| {
+                (0..poly.len())
+                    .map(|i| {
+                        let (a, b) = (&poly[i], &poly[(i + 1) % poly.len()]);
+                        (b[0] - a[0]).hypot(b[1] - a[1])
+                    })
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+
+    fn vertices(mesh: &Mesh) -> usize {
+        mesh.iter().flatten().map(Vec::len).sum()
+    }
+
+    #[test]
+    fn simplify_large_blob() {
+        let size = 200;
+        let values: Vec<f64> = (0..size * size)
+            .map(|i| {
+                let (x, y) = (f64::from(i % size) - 100.0, f64::from(i / size) - 100.0);
+                // a bumpy circle
+                let r = 80.0 + 6.0 * (y.atan2(x) * 7.0).sin();
+                if x * x + y * y < r * r {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let (full, default) = generate_mesh_with_tolerance(&values, size, size, 1.0).unwrap();
+        let (_, coarse) = generate_mesh_with_tolerance(&values, size, size, 3.0).unwrap();
+
+        assert!(vertices(&default) < vertices(&full) / 2);
+        assert!(vertices(&coarse) < vertices(&default));
+
+        // still about the same shape: no point on the outline moves further than the tolerance,
+        //   so the area can't change by more than a strip that wide around it
+        for (tolerance, simplified) in [(1.0, &default), (3.0, &coarse)] {
+            let diff = (area(simplified) - area(&full)).abs();
+            assert!(diff <= tolerance * perimeter(&full));
+            assert!(diff / area(&full) < 0.05, "area off by {diff}");
+        }
+    }
+}
@@ -5,17 +5,31 @@ pub mod material;
 pub mod mesh;
 pub mod particle;
 pub mod rigidbody;
-mod simulator;
+pub mod simulator;
 mod world;
 mod world_loading;
 
+pub mod bench;
+pub mod border;
+pub mod buoyancy;
 pub mod chunk_access;
+pub mod chunk_cache;
 pub mod chunk_data;
 pub mod chunk_handler;
 pub mod chunk_index;
+pub mod destroy;
+pub mod edit;
+pub mod edit_events;
+pub mod flow;
 pub mod gen;
+pub mod journal;
 pub mod physics;
+pub mod settle_events;
+pub mod sim_budget;
+pub mod sim_time;
+pub mod thread_pool;
 pub mod tile_entity;
+pub mod timelapse;
 
 pub use chunk::*;
 pub use ecs::*;
@@ -1,12 +1,26 @@
-use std::{collections::HashMap, hash::BuildHasherDefault, sync::Arc};
+use std::{
+    collections::HashMap,
+    hash::BuildHasherDefault,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use super::{
-    chunk_access::FSChunkAccess, entity::Hitbox, material::MaterialInstance, Position, TickTime,
-    Velocity,
+    chunk_access::FSChunkAccess,
+    chunk_handler::MaterialInstanceV0,
+    entity::Hitbox,
+    material::MaterialInstance,
+    settle_events::{SettleEvent, SettleEvents},
+    Position, TickTime, Velocity,
 };
-use crate::game::common::world::{
-    chunk_index, chunk_update_order, material::PhysicsType, pixel_to_chunk_pos,
-    pixel_to_chunk_pos_with_chunk_size, ChunkState, PassThroughHasherU32,
+use crate::game::common::{
+    world::{
+        chunk_index, chunk_update_order, material::PhysicsType, pixel_to_chunk_pos,
+        pixel_to_chunk_pos_with_chunk_size, ChunkState, PassThroughHasherU32,
+    },
+    Registries,
 };
 
 use itertools::Itertools;
@@ -17,6 +31,33 @@ use specs::{Entities, Join, Read, ReadStorage, System, Write};
 
 const PARTICLE_CHUNK_SIZE: u16 = 64;
 
+/// Default downwards acceleration of particles, in pixels per tick per tick.
+pub const PARTICLE_GRAVITY: f64 = 0.1;
+
+/// Particles that would bounce away slower than this, in pixels per tick, settle instead.
+const MIN_BOUNCE_SPEED: f64 = 0.5;
+
+/// Even the bounciest materials lose some speed every bounce, so particles always settle eventually.
+const MAX_RESTITUTION: f64 = 0.9;
+
+/// How much of its speed a particle of `particle` keeps when it bounces off a pixel of `surface`,
+///   see [`Material::restitution`](super::material::Material::restitution).
+///
+/// Nothing bounces if either material has no restitution (or isn't registered).
+pub fn bounce_restitution(
+    registries: &Registries,
+    particle: &MaterialInstance,
+    surface: &MaterialInstance,
+) -> f64 {
+    let restitution = |m: &MaterialInstance| {
+        registries
+            .materials
+            .get(&m.material_id)
+            .map_or(0.0, |m| f64::from(m.restitution))
+    };
+    (restitution(particle) * restitution(surface)).clamp(0.0, MAX_RESTITUTION)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Particle {
     pub material: MaterialInstance,
@@ -24,6 +65,24 @@ pub struct Particle {
     pub vel: Velocity,
     pub in_object_state: InObjectState,
     pub chunk_cache: (u32, u8), // (chunk index, chunk update order) TODO: make chunk update order a type?
+    /// Downwards acceleration, in pixels per tick per tick. Negative values float upwards.
+    pub gravity: f64,
+    /// Air resistance, the fraction of its velocity the particle keeps each tick is `e^-drag`.
+    ///
+    /// With drag the particle falls at most `gravity / drag` pixels per tick.
+    pub drag: f64,
+    /// Unique per spawned particle and kept for its whole lifetime, unlike its index in the
+    ///   particle list, which shifts whenever an earlier particle is removed.
+    ///
+    /// Not saved, loaded particles get new ones.
+    #[serde(skip, default = "next_particle_id")]
+    pub id: u64,
+}
+
+static NEXT_PARTICLE_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_particle_id() -> u64 {
+    NEXT_PARTICLE_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 impl Particle {
@@ -39,8 +98,60 @@ impl Particle {
             pos,
             vel,
             in_object_state: InObjectState::FirstFrame,
+            gravity: PARTICLE_GRAVITY,
+            drag: 0.0,
+            id: next_particle_id(),
         }
     }
+
+    #[must_use]
+    pub fn with_gravity(self, gravity: f64) -> Self {
+        Self { gravity, ..self }
+    }
+
+    #[must_use]
+    pub fn with_drag(self, drag: f64) -> Self {
+        Self { drag, ..self }
+    }
+
+    /// Applies `dt` ticks worth of gravity and drag to the velocity.
+    ///
+    /// This is solved exactly instead of stepped, so splitting a tick into smaller steps
+    ///   gives the same result.
+    pub fn accelerate(&mut self, dt: f64) {
+        if self.drag > 0.0 {
+            let decay = (-self.drag * dt).exp();
+            let terminal = self.gravity / self.drag;
+            self.vel.x *= decay;
+            self.vel.y = terminal + (self.vel.y - terminal) * decay;
+        } else {
+            self.vel.y += self.gravity * dt;
+        }
+    }
+
+    /// Bounces off whatever the particle ran into coming from `from`, moving it back there.
+    ///
+    /// The velocity is reflected along whichever axes `blocked` says are in the way, then scaled by `restitution`.
+    pub fn bounce(
+        &mut self,
+        from: (f64, f64),
+        restitution: f64,
+        blocked: impl Fn(f64, f64) -> bool,
+    ) {
+        let blocked_x = blocked(self.pos.x, from.1);
+        let blocked_y = blocked(from.0, self.pos.y);
+        // neither alone means it hit a corner head on, so it goes straight back
+        if blocked_x || !blocked_y {
+            self.vel.x = -self.vel.x;
+        }
+        if blocked_y || !blocked_x {
+            self.vel.y = -self.vel.y;
+        }
+        self.vel.x *= restitution;
+        self.vel.y *= restitution;
+        self.pos.x = from.0;
+        self.pos.y = from.1;
+    }
 }
 
 // #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,8 +193,116 @@ pub struct ParticleSystem {
     pub sleeping: Vec<Particle>,
 }
 
+/// Bumped whenever how [`ParticleSystem`] is saved changes.
+const PARTICLE_SAVE_VERSION: u32 = 1;
+
+/// Starts every versioned particle save. Unversioned saves start with how many particles
+///   were active instead, which is never anywhere near as big as this reads as.
+const PARTICLE_SAVE_MAGIC: [u8; 4] = *b"FSPS";
+
+#[derive(Serialize, Deserialize)]
+struct ParticleSaveHeader {
+    magic: [u8; 4],
+    version: u32,
+}
+
+/// What [`ParticleSystem`] was saved as before it had a version.
+#[derive(Deserialize)]
+struct ParticleSystemV0 {
+    active: Vec<ParticleV0>,
+    sleeping: Vec<ParticleV0>,
+}
+
+/// What [`Particle`] was in [`ParticleSystemV0`], before particles had their own gravity and drag.
+#[derive(Deserialize)]
+struct ParticleV0 {
+    material: MaterialInstanceV0,
+    pos: Position,
+    vel: Velocity,
+    in_object_state: InObjectState,
+    chunk_cache: (u32, u8),
+}
+
+impl From<ParticleV0> for Particle {
+    fn from(old: ParticleV0) -> Self {
+        Self {
+            material: old.material.into(),
+            pos: old.pos,
+            vel: old.vel,
+            in_object_state: old.in_object_state,
+            chunk_cache: old.chunk_cache,
+            gravity: PARTICLE_GRAVITY,
+            drag: 0.0,
+            id: next_particle_id(),
+        }
+    }
+}
+
+impl ParticleSystem {
+    /// Writes the particles the way [`Self::read_save`] reads them.
+    pub fn write_save(&self, w: impl std::io::Write) -> Result<(), String> {
+        let header = ParticleSaveHeader {
+            magic: PARTICLE_SAVE_MAGIC,
+            version: PARTICLE_SAVE_VERSION,
+        };
+        bincode::serialize_into(w, &(header, self)).map_err(|e| e.to_string())
+    }
+
+    /// Reads particles saved with any version of the format.
+    ///
+    /// Particles saved before they had their own gravity and drag get [`PARTICLE_GRAVITY`] and no drag.
+    pub fn read_save(data: &[u8]) -> Result<Self, String> {
+        let header: ParticleSaveHeader = bincode::deserialize(data).map_err(|e| e.to_string())?;
+        if header.magic != PARTICLE_SAVE_MAGIC {
+            let old: ParticleSystemV0 = bincode::deserialize(data).map_err(|e| e.to_string())?;
+            return Ok(Self {
+                active: old.active.into_iter().map(Particle::from).collect(),
+                sleeping: old.sleeping.into_iter().map(Particle::from).collect(),
+            });
+        }
+
+        match header.version {
+            PARTICLE_SAVE_VERSION => {
+                let (_, system): (ParticleSaveHeader, Self) =
+                    bincode::deserialize(data).map_err(|e| e.to_string())?;
+                Ok(system)
+            },
+            v => Err(format!("unknown particle save version {v}")),
+        }
+    }
+}
+
+/// Which ways particles affect (and are affected by) the pixels around them.
+///
+/// Turning them off trades fidelity for performance, with everything off particles are purely visual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParticleInteractions {
+    /// Particles stop when they hit a pixel. Otherwise they fly through everything,
+    ///   and are dropped once they leave the loaded chunks.
+    pub collide: bool,
+    /// Particles that land turn back into a pixel where they stopped. Otherwise they disappear.
+    pub settle: bool,
+    /// Particles that end up inside loose powder or anything else push themselves into the nearest gap,
+    ///   see [`FSChunkAccess::displace_pixel`]. Otherwise they disappear.
+    pub displace: bool,
+}
+
+impl ParticleInteractions {
+    pub const ALL: Self = Self { collide: true, settle: true, displace: true };
+    pub const NONE: Self = Self { collide: false, settle: false, displace: false };
+}
+
+impl Default for ParticleInteractions {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
 pub struct UpdateParticles<'a, H: FSChunkAccess + Send + Sync> {
     pub chunk_handler: &'a mut H,
+    pub interactions: ParticleInteractions,
+    /// For how bouncy materials are, see [`bounce_restitution`].
+    pub registries: &'a Registries,
 }
 
 impl<'a, H: FSChunkAccess + Send + Sync> System<'a> for UpdateParticles<'a, H> {
@@ -95,10 +314,11 @@ impl<'a, H: FSChunkAccess + Send + Sync> System<'a> for UpdateParticles<'a, H> {
         ReadStorage<'a, Velocity>,
         ReadStorage<'a, Hitbox>,
         Read<'a, TickTime>,
+        Write<'a, SettleEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut system, pos, vel, hitbox, tick_time) = data;
+        let (entities, mut system, pos, vel, hitbox, tick_time, mut settle_events) = data;
         profiling::scope!(
             "UpdateParticles::run",
             format!("n = {}/{}", system.active.len(), system.sleeping.len()).as_str()
@@ -139,20 +359,24 @@ impl<'a, H: FSChunkAccess + Send + Sync> System<'a> for UpdateParticles<'a, H> {
             system.active.append(&mut removed);
         }
 
-        self.move_particles(&mut system);
+        self.move_particles(&mut system, &mut settle_events);
 
         Self::interact_with_entities(&mut system, &entities, &hitbox, &pos, &vel);
     }
 }
 
 impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
-    fn move_particles(&mut self, system: &mut Write<ParticleSystem>) {
+    fn move_particles(
+        &mut self,
+        system: &mut Write<ParticleSystem>,
+        settle_events: &mut SettleEvents,
+    ) {
         profiling::scope!("move_particles");
 
         // ok so there's some pretty unsafe stuff going on here in order to get parallelism working
         // chunk_handler is mutably borrowed many times on different threads in order to call get, set, and displace
         // I don't know if HashMap::get is "thread-sound". Assuming it is, the reads/writes to the chunk buffer arrays
-        //   should be sound assuming our invariant of no particle being allowed to move > CHUNK_SIZE/2 per tick (TODO: this is not enforced anywhere)
+        //   should be sound assuming our invariant of no particle being allowed to move more than half a chunk per tick (TODO: this is not enforced anywhere)
         // TODO: look into replacing the chunks HashMap with https://crates.io/crates/evmap or https://crates.io/crates/chashmap so it's actually sound
 
         struct ForceSendSync<T> {
@@ -162,8 +386,11 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
         unsafe impl<T> Send for ForceSendSync<T> {}
         unsafe impl<T> Sync for ForceSendSync<T> {}
 
+        let interactions = self.interactions;
+        let registries = self.registries;
         let async_chunk_handler =
             Arc::new(ForceSendSync::<*mut &mut H> { value: &mut self.chunk_handler });
+        let settled = Mutex::new(vec![]);
 
         let parts: Vec<_> = {
             profiling::scope!("sort+group");
@@ -195,7 +422,13 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
                     let unsafe_async_chunk_handler =
                         unsafe { &mut **((async_chunk_handler.clone()).value) };
 
-                    let res = Self::process_particle(part, unsafe_async_chunk_handler);
+                    let res = Self::process_particle(
+                        part,
+                        unsafe_async_chunk_handler,
+                        interactions,
+                        registries,
+                        &settled,
+                    );
 
                     let (chunk_x, chunk_y) = pixel_to_chunk_pos_with_chunk_size(
                         part.pos.x as i64,
@@ -217,6 +450,10 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
                 system.active.par_extend(v);
             }
         }
+
+        for event in settled.into_inner().unwrap() {
+            settle_events.emit(event);
+        }
     }
 
     fn interact_with_entities(
@@ -258,15 +495,27 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
         }
     }
 
-    fn process_particle(part: &mut Particle, chunk_handler: &mut impl FSChunkAccess) -> bool {
+    fn process_particle(
+        part: &mut Particle,
+        chunk_handler: &mut impl FSChunkAccess,
+        interactions: ParticleInteractions,
+        registries: &Registries,
+        settled: &Mutex<Vec<SettleEvent>>,
+    ) -> bool {
         let lx = part.pos.x;
         let ly = part.pos.y;
 
-        part.vel.y += 0.1;
+        part.accelerate(1.0);
 
         let dx = part.vel.x;
         let dy = part.vel.y;
 
+        if !interactions.collide {
+            part.pos.x = lx + dx;
+            part.pos.y = ly + dy;
+            return chunk_handler.is_pixel_loaded(part.pos.x as i64, part.pos.y as i64);
+        }
+
         let steps = (dx.abs() + dy.abs()).sqrt() as u32 + 1;
         {
             // profiling::scope!("loop", format!("steps = {}", steps).as_str());
@@ -283,7 +532,7 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
                 // this check does catch repeated steps, but actually makes performance slightly worse
                 // if pos.x as i64 != last_step_x || pos.y as i64 != last_step_y {
                 if let Ok(mat) = chunk_handler.pixel(part.pos.x as i64, part.pos.y as i64) {
-                    if mat.physics == PhysicsType::Air {
+                    if mat.is_empty(&registries.materials) {
                         part.in_object_state = InObjectState::Outside;
                     } else {
                         let is_object = mat.physics == PhysicsType::Object;
@@ -305,12 +554,18 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
                         }
 
                         if !is_object || part.in_object_state == InObjectState::Outside {
+                            let restitution = bounce_restitution(registries, &part.material, mat);
                             match chunk_handler.pixel(lx as i64, ly as i64) {
-                                Ok(m) if m.physics != PhysicsType::Air => {
+                                Ok(m) if !m.is_empty(&registries.materials) => {
+                                    if !interactions.displace {
+                                        return false;
+                                    }
+
                                     let succeeded = chunk_handler.displace_pixel(
                                         part.pos.x as i64,
                                         part.pos.y as i64,
                                         part.material.clone(),
+                                        &registries.materials,
                                     );
 
                                     if succeeded {
@@ -324,10 +579,38 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
                                     break;
                                 },
                                 _ => {
+                                    if part.vel.x.hypot(part.vel.y) * restitution
+                                        >= MIN_BOUNCE_SPEED
+                                    {
+                                        let thru = f64::from(s) / f64::from(steps);
+                                        part.bounce(
+                                            (lx + dx * thru, ly + dy * thru),
+                                            restitution,
+                                            |x, y| {
+                                                chunk_handler
+                                                    .pixel(x as i64, y as i64)
+                                                    .map_or(false, |m| {
+                                                        !m.is_empty(&registries.materials)
+                                                    })
+                                            },
+                                        );
+                                        return true;
+                                    }
+
+                                    if !interactions.settle {
+                                        return false;
+                                    }
+
                                     if chunk_handler
                                         .set_pixel(lx as i64, ly as i64, part.material.clone())
                                         .is_ok()
                                     {
+                                        settled.lock().unwrap().push(SettleEvent {
+                                            x: lx as i64,
+                                            y: ly as i64,
+                                            material: part.material.material_id.clone(),
+                                            velocity: part.vel.x.hypot(part.vel.y),
+                                        });
                                         return false;
                                     }
                                 },
@@ -345,3 +628,123 @@ impl<H: FSChunkAccess + Send + Sync> UpdateParticles<'_, H> {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::{
+        material::{self, color::Color, PhysicsType},
+        Position, Velocity,
+    };
+
+    use super::{Particle, ParticleSystem, PARTICLE_GRAVITY};
+
+    fn particle(drag: f64) -> Particle {
+        Particle::new(
+            material::TEST.instance(PhysicsType::Sand, Color::rgb(128, 128, 128)),
+            Position { x: 0.0, y: 0.0 },
+            Velocity { x: 5.0, y: -2.0 },
+        )
+        .with_drag(drag)
+    }
+
+    #[test]
+    fn drag_slows_particles() {
+        let mut heavy = particle(0.02);
+        let mut floaty = particle(0.3);
+        for _ in 0..10 {
+            heavy.accelerate(1.0);
+            floaty.accelerate(1.0);
+        }
+
+        assert!(floaty.vel.x < heavy.vel.x);
+        assert!(floaty.vel.x > 0.0);
+
+        // the floaty one is already falling, but never faster than its terminal velocity
+        assert!(floaty.vel.y > 0.0);
+        assert!(floaty.vel.y < floaty.gravity / floaty.drag);
+    }
+
+    #[test]
+    fn drag_is_step_independent() {
+        for drag in [0.0, 0.05, 0.5] {
+            let mut whole = particle(drag);
+            let mut split = particle(drag);
+            for _ in 0..10 {
+                whole.accelerate(1.0);
+                for _ in 0..4 {
+                    split.accelerate(0.25);
+                }
+            }
+
+            assert!((whole.vel.x - split.vel.x).abs() < 1e-9);
+            assert!((whole.vel.y - split.vel.y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn reads_particles_saved_before_gravity() {
+        let mat = material::TEST.instance(PhysicsType::Sand, Color::rgb(128, 128, 128));
+        let heavy = particle(0.2).with_gravity(0.5);
+
+        let mut saved = vec![];
+        ParticleSystem { active: vec![heavy.clone()], sleeping: vec![] }
+            .write_save(&mut saved)
+            .unwrap();
+        let loaded = ParticleSystem::read_save(&saved).unwrap();
+        assert_eq!(loaded.active.len(), 1);
+        assert!((loaded.active[0].gravity - 0.5).abs() < 1e-9);
+        assert!((loaded.active[0].drag - 0.2).abs() < 1e-9);
+
+        // a save from before particles had gravity, drag, or pixels had moisture or a fall count
+        //   (tuples serialize the same as the old structs with these fields)
+        let old_mat = (mat.material_id.clone(), mat.physics, mat.color, mat.light);
+        let old_particle = (
+            old_mat,
+            heavy.pos.clone(),
+            heavy.vel.clone(),
+            heavy.in_object_state.clone(),
+            heavy.chunk_cache,
+        );
+        let old = bincode::serialize(&(vec![old_particle.clone()], vec![old_particle; 2])).unwrap();
+        let loaded = ParticleSystem::read_save(&old).unwrap();
+        assert_eq!(loaded.active.len(), 1);
+        assert_eq!(loaded.sleeping.len(), 2);
+        assert_eq!(loaded.active[0].material, mat);
+        assert!((loaded.active[0].vel.x - 5.0).abs() < 1e-9);
+        #[allow(clippy::float_cmp)]
+        let default_gravity = loaded.active[0].gravity == PARTICLE_GRAVITY;
+        assert!(default_gravity);
+        assert!(loaded.active[0].drag.abs() < 1e-9);
+
+        // a version from the future
+        let mut future = saved.clone();
+        future[4] = 99;
+        assert!(ParticleSystem::read_save(&future).is_err());
+    }
+
+    #[test]
+    fn bounce_reflects_blocked_axes() {
+        // landing on a floor at y >= 10 keeps going sideways
+        let mut part = particle(0.0);
+        part.vel = Velocity { x: 2.0, y: 4.0 };
+        part.pos = Position { x: 3.0, y: 10.5 };
+        part.bounce((1.0, 8.0), 0.5, |_, y| y >= 10.0);
+        assert!((part.vel.x - 1.0).abs() < 1e-9);
+        assert!((part.vel.y + 2.0).abs() < 1e-9);
+        assert!((part.pos.x - 1.0).abs() < 1e-9 && (part.pos.y - 8.0).abs() < 1e-9);
+
+        // running into a wall at x >= 5 keeps falling
+        part.vel = Velocity { x: 2.0, y: 4.0 };
+        part.pos = Position { x: 5.5, y: 3.0 };
+        part.bounce((4.0, 1.0), 0.5, |x, _| x >= 5.0);
+        assert!((part.vel.x + 1.0).abs() < 1e-9);
+        assert!((part.vel.y - 2.0).abs() < 1e-9);
+
+        // and clipping a corner goes straight back
+        part.vel = Velocity { x: 2.0, y: 4.0 };
+        part.pos = Position { x: 5.5, y: 10.5 };
+        part.bounce((4.0, 8.0), 0.5, |x, y| x >= 5.0 && y >= 10.0);
+        assert!((part.vel.x + 1.0).abs() < 1e-9);
+        assert!((part.vel.y + 2.0).abs() < 1e-9);
+    }
+}
@@ -1,11 +1,22 @@
+use std::{collections::HashSet, sync::Mutex};
+
 use rapier2d::{
-    na::Vector2,
+    na::{Point2, Vector2},
     prelude::{
-        BroadPhase, CCDSolver, ColliderSet, EventHandler, ImpulseJointSet, IntegrationParameters,
-        IslandManager, MultibodyJointSet, NarrowPhase, PhysicsHooks, PhysicsPipeline, RigidBody,
-        RigidBodyHandle, RigidBodySet,
+        BroadPhase, CCDSolver, Collider, ColliderHandle, ColliderSet, CollisionEvent, ContactPair,
+        EventHandler, ImpulseJointSet, IntegrationParameters, IslandManager, MultibodyJointSet,
+        NarrowPhase, PhysicsHooks, PhysicsPipeline, RigidBody, RigidBodyHandle, RigidBodySet,
+        RigidBodyType,
     },
 };
+
+use crate::game::common::registry::RegistryID;
+
+use super::{
+    material::{Material, MaterialInstance},
+    mesh,
+};
+
 // use salva2d::{
 //     integrations::rapier::{ColliderSampling, FluidsPipeline},
 //     object::Boundary,
@@ -13,6 +24,14 @@ use rapier2d::{
 
 pub const PHYSICS_SCALE: f32 = 10.0;
 
+/// Contacts between a body and the terrain with less impulse than this never break anything,
+///   so bodies resting or sliding on the ground leave it alone.
+pub const IMPACT_MIN_IMPULSE: f32 = 20.0;
+/// Radius in pixels of the area affected by an impact of [`IMPACT_MIN_IMPULSE`].
+/// Grows with the square root of the impulse, up to [`IMPACT_MAX_RADIUS`].
+pub const IMPACT_BASE_RADIUS: f32 = 2.0;
+pub const IMPACT_MAX_RADIUS: f32 = 12.0;
+
 // const PARTICLE_RADIUS: f32 = 0.19;
 // const SMOOTHING_FACTOR: f32 = 2.0;
 
@@ -30,7 +49,90 @@ pub struct Physics {
     pub impulse_joints: ImpulseJointSet,
     pub multibody_joints: MultibodyJointSet,
     pub hooks: Box<dyn PhysicsHooks>,
-    pub event_handler: Box<dyn EventHandler>,
+    pub impacts: ImpactCollector,
+    /// How far rigidbody colliders may stray from their pixels, in pixels.
+    /// Higher values make big bodies cheaper to simulate,
+    ///   see [`mesh::generate_mesh_with_tolerance`].
+    pub body_mesh_tolerance: f64,
+    /// Materials left out of rigidbody colliders, so bodies made of them can overlap other bodies.
+    /// Filled from [`Material::entity_collidable`] if [`Settings::rigidbody_passable_materials`](crate::game::common::Settings::rigidbody_passable_materials) is on.
+    pub body_passable: HashSet<RegistryID<Material>>,
+}
+
+/// A dynamic body hitting the terrain (any fixed body) hard.
+#[derive(Debug, Clone, Copy)]
+pub struct Impact {
+    /// Where the bodies touched, in physics units.
+    pub point: Point2<f32>,
+    pub impulse: f32,
+}
+
+/// Collects [`Impact`]s from contact force events while stepping.
+///
+/// Bodies only report contact forces if their colliders have [`ActiveEvents::CONTACT_FORCE_EVENTS`](rapier2d::prelude::ActiveEvents::CONTACT_FORCE_EVENTS) set.
+#[derive(Default)]
+pub struct ImpactCollector {
+    impacts: Mutex<Vec<Impact>>,
+}
+
+impl EventHandler for ImpactCollector {
+    fn handle_collision_event(
+        &self,
+        _bodies: &RigidBodySet,
+        _colliders: &ColliderSet,
+        _event: CollisionEvent,
+        _contact_pair: Option<&ContactPair>,
+    ) {
+    }
+
+    fn handle_contact_force_event(
+        &self,
+        dt: f32,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        contact_pair: &ContactPair,
+        total_force_magnitude: f32,
+    ) {
+        let impulse = total_force_magnitude * dt;
+        if impulse < IMPACT_MIN_IMPULSE {
+            return;
+        }
+
+        let body_type = |handle: ColliderHandle| {
+            colliders
+                .get(handle)
+                .and_then(Collider::parent)
+                .and_then(|b| bodies.get(b))
+                .map(RigidBody::body_type)
+        };
+        if !matches!(
+            (
+                body_type(contact_pair.collider1),
+                body_type(contact_pair.collider2)
+            ),
+            (Some(RigidBodyType::Dynamic), Some(RigidBodyType::Fixed))
+                | (Some(RigidBodyType::Fixed), Some(RigidBodyType::Dynamic))
+        ) {
+            return;
+        }
+
+        // solver contacts are already in world space
+        let (sum, count) = contact_pair
+            .manifolds
+            .iter()
+            .flat_map(|m| &m.data.solver_contacts)
+            .fold((Vector2::zeros(), 0_u32), |(sum, count), c| {
+                (sum + c.point.coords, count + 1)
+            });
+        if count == 0 {
+            return;
+        }
+
+        self.impacts
+            .lock()
+            .unwrap()
+            .push(Impact { point: Point2::from(sum / count as f32), impulse });
+    }
 }
 
 impl Physics {
@@ -94,7 +196,9 @@ impl Physics {
             colliders,
             gravity: Vector2::y() * 3.0,
             hooks: Box::new(()),
-            event_handler: Box::new(()),
+            impacts: ImpactCollector::default(),
+            body_mesh_tolerance: mesh::DEFAULT_SIMPLIFY_TOLERANCE,
+            body_passable: HashSet::new(),
             integration_parameters: Default::default(),
             physics_pipeline: Default::default(),
             islands: Default::default(),
@@ -123,10 +227,15 @@ impl Physics {
             &mut self.ccd_solver,
             None,
             &*self.hooks,
-            &*self.event_handler,
+            &self.impacts,
         );
     }
 
+    /// Takes the [`Impact`]s that happened since this was last called.
+    pub fn take_impacts(&mut self) -> Vec<Impact> {
+        std::mem::take(self.impacts.impacts.get_mut().unwrap())
+    }
+
     pub fn remove_rigidbody(&mut self, handle: RigidBodyHandle) -> Option<RigidBody> {
         self.bodies.remove(
             handle,
@@ -137,6 +246,21 @@ impl Physics {
             true,
         )
     }
+
+    /// The value map a rigidbody's colliders are generated from, leaving out [`Self::body_passable`] pixels.
+    ///
+    /// A body made of nothing but passable pixels still needs a shape, so it keeps all of them.
+    pub fn body_valuemap(&self, pixels: &[MaterialInstance]) -> Vec<f64> {
+        if !self.body_passable.is_empty() {
+            let values = mesh::pixels_to_valuemap_filtered(pixels, |p| {
+                !self.body_passable.contains(&p.material_id)
+            });
+            if values.iter().any(|v| *v > 0.0) {
+                return values;
+            }
+        }
+        mesh::pixels_to_valuemap(pixels)
+    }
 }
 
 impl Default for Physics {
@@ -144,3 +268,75 @@ impl Default for Physics {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rapier2d::{
+        na::Vector2,
+        prelude::{ColliderBuilder, RigidBodyBuilder},
+    };
+
+    use crate::game::common::world::{CollisionFlags, CollisionLayers};
+
+    use super::Physics;
+
+    #[test]
+    fn collision_layers_filter_contacts() {
+        // drops a ball onto a fixed box, returning whether it landed on it
+        let lands_on = |ground: CollisionLayers, ball: CollisionLayers| {
+            let mut physics = Physics::new();
+            physics.gravity = Vector2::new(0.0, 10.0);
+
+            let ground_body = physics.bodies.insert(
+                RigidBodyBuilder::fixed()
+                    .translation(Vector2::new(0.0, 2.0))
+                    .build(),
+            );
+            physics.colliders.insert_with_parent(
+                ColliderBuilder::cuboid(5.0, 0.5)
+                    .collision_groups(ground.into())
+                    .build(),
+                ground_body,
+                &mut physics.bodies,
+            );
+            let ball_body = physics.bodies.insert(RigidBodyBuilder::dynamic().build());
+            physics.colliders.insert_with_parent(
+                ColliderBuilder::ball(0.5)
+                    .collision_groups(ball.into())
+                    .build(),
+                ball_body,
+                &mut physics.bodies,
+            );
+
+            for _ in 0..120 {
+                physics.step(1.0 / 60.0);
+            }
+            physics.bodies[ball_body].translation().y < 2.0
+        };
+
+        let projectile = CollisionLayers::new(
+            CollisionFlags::PROJECTILE,
+            CollisionFlags::WORLD | CollisionFlags::RIGIDBODY,
+        );
+
+        // the defaults are unchanged
+        assert!(CollisionLayers::rigidbody().collides_with(&CollisionLayers::player()));
+        assert!(CollisionLayers::rigidbody().collides_with(&CollisionLayers::terrain()));
+        assert!(!CollisionLayers::player().collides_with(&CollisionLayers::terrain()));
+        assert!(lands_on(
+            CollisionLayers::terrain(),
+            CollisionLayers::rigidbody()
+        ));
+        assert!(lands_on(
+            CollisionLayers::player(),
+            CollisionLayers::rigidbody()
+        ));
+
+        // projectiles hit terrain and rigidbodies but pass through the player
+        assert!(projectile.collides_with(&CollisionLayers::terrain()));
+        assert!(!projectile.collides_with(&CollisionLayers::player()));
+        assert!(lands_on(CollisionLayers::terrain(), projectile));
+        assert!(lands_on(CollisionLayers::rigidbody(), projectile));
+        assert!(!lands_on(CollisionLayers::player(), projectile));
+    }
+}
@@ -2,8 +2,7 @@ use glium::texture::Texture2d;
 use rapier2d::{
     na::{Isometry2, Point2, Vector2},
     prelude::{
-        ColliderBuilder, InteractionGroups, RigidBody, RigidBodyBuilder, RigidBodyHandle,
-        SharedShape,
+        ActiveEvents, ColliderBuilder, RigidBody, RigidBodyBuilder, RigidBodyHandle, SharedShape,
     },
 };
 // use salva2d::{integrations::rapier::ColliderSampling, object::Boundary};
@@ -12,7 +11,7 @@ use super::{
     material::MaterialInstance,
     mesh,
     physics::{Physics, PHYSICS_SCALE},
-    CollisionFlags,
+    CollisionLayers,
 };
 
 pub struct FSRigidBody {
@@ -104,11 +103,11 @@ impl FSRigidBody {
         }
 
         let collider = ColliderBuilder::compound(shapes)
-            .collision_groups(InteractionGroups::new(
-                CollisionFlags::RIGIDBODY.bits().into(),
-                CollisionFlags::all().bits().into(),
-            ))
+            .collision_groups(CollisionLayers::rigidbody().into())
             .density(1.0)
+            // forces are checked against `IMPACT_MIN_IMPULSE` when collected
+            .active_events(ActiveEvents::CONTACT_FORCE_EVENTS)
+            .contact_force_event_threshold(0.0)
             .build();
         let _co_handle =
             physics
@@ -134,6 +133,17 @@ impl FSRigidBody {
         })
     }
 
+    /// The body's image as RGBA bytes, row by row, using each pixel's own color.
+    ///
+    /// This is what gets uploaded when [`FSRigidBody::image_dirty`] is set,
+    ///   so the body looks exactly like the pixels it's made of.
+    pub fn image_data(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|m| [m.color.r, m.color.g, m.color.b, m.color.a])
+            .collect()
+    }
+
     pub fn get_body<'a>(&self, physics: &'a Physics) -> Option<&'a RigidBody> {
         self.body.and_then(|b| physics.bodies.get(b))
     }
@@ -142,6 +152,45 @@ impl FSRigidBody {
         self.body.and_then(|b| physics.bodies.get_mut(b))
     }
 
+    /// Moves the body's colliders to other collision layers. New bodies are on [`CollisionLayers::rigidbody`],
+    ///   and so is the collider made by [`FSRigidBody::make_body`] if the body is rebuilt.
+    pub fn set_collision_layers(&self, physics: &mut Physics, layers: CollisionLayers) {
+        let Some(body) = self.get_body(physics) else {
+            return;
+        };
+        for handle in body.colliders().to_vec() {
+            if let Some(collider) = physics.colliders.get_mut(handle) {
+                collider.set_collision_groups(layers.into());
+            }
+        }
+    }
+
+    /// Changes one of the body's pixels and wakes the body up, so the change gets simulated.
+    ///
+    /// The collider isn't rebuilt, use [`FSRigidBody::make_body`] if the shape changed.
+    pub fn set_pixel(
+        &mut self,
+        physics: &mut Physics,
+        x: u16,
+        y: u16,
+        mat: MaterialInstance,
+    ) -> Result<(), String> {
+        if x >= self.width || y >= self.height {
+            return Err(format!(
+                "FSRigidBody::set_pixel out of bounds: ({x}, {y}) in {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        self.pixels[x as usize + y as usize * self.width as usize] = mat;
+        self.image_dirty = true;
+        if let Some(body) = self.get_body_mut(physics) {
+            body.wake_up(true);
+        }
+
+        Ok(())
+    }
+
     pub fn make_bodies(
         pixels: &[MaterialInstance],
         width: u16,
@@ -149,9 +198,13 @@ impl FSRigidBody {
         physics: &mut Physics,
         position: (f32, f32),
     ) -> Result<Vec<FSRigidBody>, String> {
-        let values = mesh::pixels_to_valuemap(pixels);
-        let mesh =
-            mesh::generate_mesh_only_simplified(&values, u32::from(width), u32::from(height))?;
+        let values = physics.body_valuemap(pixels);
+        let (_, mesh) = mesh::generate_mesh_with_tolerance(
+            &values,
+            u32::from(width),
+            u32::from(height),
+            physics.body_mesh_tolerance,
+        )?;
 
         let loops = mesh::triangulate(&mesh);
 
@@ -228,11 +281,12 @@ impl FSRigidBody {
             );
         }
 
-        let values = mesh::pixels_to_valuemap(&self.pixels);
-        let mesh = mesh::generate_mesh_only_simplified(
+        let values = physics.body_valuemap(&self.pixels);
+        let (_, mesh) = mesh::generate_mesh_with_tolerance(
             &values,
             u32::from(self.width),
             u32::from(self.height),
+            physics.body_mesh_tolerance,
         )?;
 
         let loops = mesh::triangulate(&mesh);
@@ -280,7 +334,10 @@ impl FSRigidBody {
             }
         }
 
-        let collider = ColliderBuilder::compound(shapes).build();
+        let collider = ColliderBuilder::compound(shapes)
+            .active_events(ActiveEvents::CONTACT_FORCE_EVENTS)
+            .contact_force_event_threshold(0.0)
+            .build();
         let _co_handle =
             physics
                 .colliders
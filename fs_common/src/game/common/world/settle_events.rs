@@ -0,0 +1,95 @@
+use crate::game::common::registry::RegistryID;
+
+use super::material::Material;
+
+/// How many [`SettleEvent`]s are kept between drains. Past this only the fastest ones are kept,
+///   so a big pour doesn't flood whatever is listening.
+pub const MAX_SETTLE_EVENTS: usize = 64;
+
+/// A falling particle or liquid pixel came to rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettleEvent {
+    /// Where it stopped, in world coordinates.
+    pub x: i64,
+    pub y: i64,
+    pub material: RegistryID<Material>,
+    /// How fast it was going when it landed, in pixels per tick.
+    pub velocity: f64,
+}
+
+/// Things that landed since the events were last taken, stored as an ECS resource.
+///
+/// Meant for sounds, which can scale their volume and pitch with [`SettleEvent::velocity`].
+/// Cleared at the start of every [`World::tick`](super::World::tick), so anything not taken by then is dropped.
+#[derive(Debug, Default)]
+pub struct SettleEvents {
+    events: Vec<SettleEvent>,
+}
+
+impl SettleEvents {
+    /// Adds `event`, or once there are [`MAX_SETTLE_EVENTS`], replaces the slowest one if `event` is faster.
+    pub fn emit(&mut self, event: SettleEvent) {
+        if self.events.len() < MAX_SETTLE_EVENTS {
+            self.events.push(event);
+        } else if let Some(slowest) = self
+            .events
+            .iter_mut()
+            .min_by(|a, b| a.velocity.total_cmp(&b.velocity))
+        {
+            if event.velocity > slowest.velocity {
+                *slowest = event;
+            }
+        }
+    }
+
+    /// Takes every event so far, in no particular order.
+    pub fn drain(&mut self) -> impl Iterator<Item = SettleEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::world::material;
+
+    use super::{SettleEvent, SettleEvents, MAX_SETTLE_EVENTS};
+
+    fn event(velocity: f64) -> SettleEvent {
+        SettleEvent {
+            x: 0,
+            y: 0,
+            material: material::TEST.clone(),
+            velocity,
+        }
+    }
+
+    #[test]
+    fn keeps_fastest_when_full() {
+        let mut events = SettleEvents::default();
+        for _ in 0..MAX_SETTLE_EVENTS * 10 {
+            events.emit(event(0.5));
+        }
+        assert_eq!(events.len(), MAX_SETTLE_EVENTS);
+
+        // a hard landing still gets through a flood of gentle ones, a gentler one doesn't
+        events.emit(event(4.0));
+        events.emit(event(0.1));
+        let velocities: Vec<_> = events.drain().map(|e| e.velocity).collect();
+        assert_eq!(velocities.len(), MAX_SETTLE_EVENTS);
+        assert_eq!(velocities.iter().filter(|v| **v > 1.0).count(), 1);
+        assert!(velocities.iter().all(|v| *v > 0.2));
+        assert!(events.is_empty());
+    }
+}
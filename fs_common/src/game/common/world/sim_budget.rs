@@ -0,0 +1,113 @@
+use chunksystem::ChunkKey;
+
+/// Limits how many dirty pixels chunk simulation goes through in one tick, see
+///   [`Settings::sim_pixel_budget`](crate::game::common::Settings::sim_pixel_budget).
+///
+/// Chunks that don't fit are put off until a later tick, and go before everything else then
+///   (oldest first), so a busy world simulates slower instead of some chunks never updating.
+#[derive(Debug, Default)]
+pub struct SimBudget {
+    /// Chunks that were put off, in the order they get picked up again.
+    deferred: Vec<ChunkKey>,
+}
+
+impl SimBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Picks which of the `dirty` chunks (with how many dirty pixels each has) get simulated
+    ///   with `remaining` pixels left this tick, `None` for no limit.
+    ///
+    /// Returns the picked chunks and how many pixels they use. The first chunk is always picked
+    ///   while there's any budget left, so a chunk bigger than the whole budget still gets its turn.
+    pub fn select(
+        &mut self,
+        dirty: impl IntoIterator<Item = (ChunkKey, u32)>,
+        remaining: Option<u32>,
+    ) -> (Vec<ChunkKey>, u32) {
+        let mut dirty: Vec<_> = dirty.into_iter().collect();
+        dirty.sort_unstable();
+
+        // chunks put off last time first, in the same order, then the rest
+        let mut order = Vec::with_capacity(dirty.len());
+        for key in &self.deferred {
+            if let Ok(i) = dirty.binary_search_by_key(key, |(k, _)| *k) {
+                order.push(dirty[i]);
+            }
+        }
+        order.extend(
+            dirty
+                .iter()
+                .copied()
+                .filter(|(k, _)| !self.deferred.contains(k)),
+        );
+
+        let mut picked = Vec::with_capacity(order.len());
+        let mut deferred = vec![];
+        let mut spent = 0_u32;
+        for (key, cost) in order {
+            let fits = remaining.map_or(true, |r| {
+                (spent == 0 && r > 0) || spent.saturating_add(cost) <= r
+            });
+            if fits {
+                picked.push(key);
+                spent = spent.saturating_add(cost);
+            } else {
+                deferred.push(key);
+            }
+        }
+
+        self.deferred = deferred;
+        (picked, spent)
+    }
+
+    /// Chunks waiting for their turn, in the order they'll be picked.
+    pub fn deferred(&self) -> &[ChunkKey] {
+        &self.deferred
+    }
+
+    pub fn clear(&mut self) {
+        self.deferred.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimBudget;
+
+    #[test]
+    fn deferred_chunks_go_first() {
+        let mut budget = SimBudget::new();
+        let dirty = [((0, 0), 10), ((1, 0), 10), ((2, 0), 10), ((3, 0), 10)];
+
+        let (picked, spent) = budget.select(dirty, Some(20));
+        assert_eq!(picked, vec![(0, 0), (1, 0)]);
+        assert_eq!(spent, 20);
+        assert_eq!(budget.deferred(), &[(2, 0), (3, 0)]);
+
+        // the ones left over get their turn before the ones that just ran
+        let (picked, _) = budget.select(dirty, Some(20));
+        assert_eq!(picked, vec![(2, 0), (3, 0)]);
+        assert_eq!(budget.deferred(), &[(0, 0), (1, 0)]);
+
+        // a chunk bigger than the whole budget still runs when it's first in line
+        let (picked, spent) = budget.select([((5, 5), 100), ((0, 0), 10)], Some(20));
+        assert_eq!(picked, vec![(0, 0)]);
+        assert_eq!(spent, 10);
+        let (picked, spent) = budget.select([((5, 5), 100), ((0, 0), 10)], Some(20));
+        assert_eq!(picked, vec![(5, 5)]);
+        assert_eq!(spent, 100);
+
+        // nothing left this tick
+        let (picked, _) = budget.select(dirty, Some(0));
+        assert!(picked.is_empty());
+        assert_eq!(budget.deferred().len(), 4);
+
+        // no limit
+        let (picked, spent) = budget.select(dirty, None);
+        assert_eq!(picked.len(), 4);
+        assert_eq!(spent, 40);
+        assert!(budget.deferred().is_empty());
+    }
+}
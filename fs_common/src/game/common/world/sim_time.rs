@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use super::material::color::Color;
+
+/// Upper bounds of the bins [`bin`] sorts a chunk's simulation time into, see `Settings::draw_chunk_sim_time_overlay`.
+///
+/// Anything slower than the last one goes in one more bin past the end.
+pub const SIM_TIME_BINS: [Duration; 4] = [
+    Duration::from_micros(50),
+    Duration::from_micros(200),
+    Duration::from_micros(500),
+    Duration::from_millis(2),
+];
+
+/// Which of [`SIM_TIME_BINS`] `time` falls in, from 0 (fastest) to `SIM_TIME_BINS.len()` (slowest).
+pub fn bin(time: Duration) -> usize {
+    SIM_TIME_BINS
+        .iter()
+        .position(|max| time < *max)
+        .unwrap_or(SIM_TIME_BINS.len())
+}
+
+/// The overlay color for a chunk that took `time` to simulate, from blue (cheap) to red (hotspot).
+pub fn color(time: Duration, alpha: u8) -> Color {
+    match bin(time) {
+        0 => Color::rgba_const(64, 64, 255, alpha),
+        1 => Color::rgba_const(64, 255, 64, alpha),
+        2 => Color::rgba_const(255, 255, 64, alpha),
+        3 => Color::rgba_const(255, 127, 64, alpha),
+        _ => Color::rgba_const(255, 32, 32, alpha),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{bin, color, SIM_TIME_BINS};
+
+    #[test]
+    fn bins() {
+        assert_eq!(bin(Duration::ZERO), 0);
+        assert_eq!(bin(Duration::from_micros(49)), 0);
+        assert_eq!(bin(Duration::from_micros(50)), 1);
+        assert_eq!(bin(Duration::from_micros(300)), 2);
+        assert_eq!(bin(Duration::from_millis(1)), 3);
+        assert_eq!(bin(Duration::from_millis(2)), SIM_TIME_BINS.len());
+        assert_eq!(bin(Duration::from_secs(1)), SIM_TIME_BINS.len());
+
+        // every bin gets its own color
+        let colors = [0, 100, 300, 1000, 5000].map(|us| color(Duration::from_micros(us), 127));
+        for (i, a) in colors.iter().enumerate() {
+            assert_eq!(a.a, 127);
+            for b in &colors[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
@@ -1,38 +1,129 @@
 use std::cell::UnsafeCell;
 use std::sync::Arc;
 
-use chunksystem::ChunkQuery;
 use fastrand::Rng;
 use rapier2d::na::Isometry2;
 
-use crate::game::common::world::material::{MaterialInstance, PhysicsType};
-use crate::game::common::world::{rigidbody, CHUNK_SIZE};
-use crate::game::common::{Rect, Registries};
+use crate::game::common::registry::RegistryID;
+use crate::game::common::world::material::{
+    tag::MaterialTag, Emitter, Freeze, Material, MaterialInstance, MaterialRegistry, PhysicsType,
+    PhysicsTypeSet, SandSpread, BEDROCK,
+};
+use crate::game::common::world::{rigidbody, CHUNK_HEIGHT, CHUNK_WIDTH};
+use crate::game::common::{Rect, Registries, Settings};
 
 use super::chunk_access::FSChunkAccess;
 use super::chunk_handler::ChunkHandler;
 use super::chunk_index::ChunkLocalPosition;
+use super::flow::FLOW_MOVED;
 use super::material::color::Color;
-use super::particle::Particle;
+use super::particle::{Particle, PARTICLE_GRAVITY};
 use super::rigidbody::FSRigidBody;
-use super::{material, pixel_to_chunk_pos, CHUNK_AREA};
+use super::settle_events::SettleEvent;
 use super::{
     physics::{Physics, PHYSICS_SCALE},
     Chunk, Position, Velocity,
 };
+use super::{pixel_to_chunk_pos, CHUNK_AREA};
 
 pub struct Simulator {}
 
+/// How much moisture a powder pixel loses each update while not touching any liquid.
+pub const SAND_DRY_RATE: f32 = 1.0 / 300.0;
+/// Powder pixels at or above this moisture never slide down to the sides.
+pub const SAND_CLUMP_MOISTURE: f32 = 0.5;
+/// [`MaterialTag::COHESIVE`] powder pixels with at least this many of their 8 neighbors being
+///   the same material don't slide, and can rest on a diagonal neighbor instead of falling.
+pub const SAND_COHESION_NEIGHBORS: u8 = 3;
+/// How far a powder pixel's color moves towards the color of each (opaque) liquid it touches every update.
+pub const SAND_STAIN_RATE: f32 = 0.02;
+/// Liquid that lands after falling for at least this many updates can splash into spray particles.
+pub const LIQUID_SPRAY_FALL: u8 = 20;
+/// Falling for longer than this many updates doesn't make the spray any faster.
+pub const LIQUID_SPRAY_MAX_FALL: u8 = 60;
+/// Chance for each liquid pixel landing after a long fall to splash instead of stacking up.
+pub const LIQUID_SPRAY_CHANCE: f32 = 0.5;
+/// Resting liquid with at least this many pixels of liquid stacked on top of it jets out of
+///   an opening beside it instead of just dribbling out.
+pub const LIQUID_JET_MIN_DEPTH: u8 = 4;
+/// Liquid deeper than this doesn't jet out any faster. Also how far up the depth is measured.
+pub const LIQUID_JET_MAX_DEPTH: u8 = 32;
+/// Sideways speed of a jet at [`LIQUID_JET_MAX_DEPTH`], in pixels per tick.
+pub const LIQUID_JET_SPEED: f64 = 4.0;
+/// How far to the side liquid resting on more liquid looks for a lower spot to flow towards.
+pub const LIQUID_LEVEL_SEARCH: i32 = 4;
+/// Fraction of sunlight that spreads to the neighboring pixels each row, which softens shadow edges.
+pub const SUN_SCATTER: f32 = 0.1;
+
+/// How many pixels light from a fully bright emissive pixel spreads, see [`Simulator::propagate_light`].
+pub const LIGHT_RADIUS: i32 = 16;
+/// How much emitted light fades for each pixel it travels.
+pub const LIGHT_FALLOFF: f32 = 1.0 / LIGHT_RADIUS as f32;
+/// How many times the light grid is swept, alternating direction. More lets light bend further around corners.
+const LIGHT_SWEEPS: usize = 4;
+
+/// Direction and strength of sunlight, see [`Simulator::cast_sun_light`].
+#[derive(Debug, Clone, Copy)]
+pub struct SunLight {
+    /// How many pixels sideways the light moves for each pixel down
+    pub slope: f32,
+    pub intensity: f32,
+}
+
+/// Which light passes run after a chunk is simulated, see [`Simulator::update_light`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LightSettings {
+    pub sun: Option<SunLight>,
+    /// Spread light from emissive pixels to the pixels around them
+    pub propagate: bool,
+}
+
+/// Which pixels move and how sand settles, taken from [`Settings`].
+#[derive(Debug, Clone, Copy)]
+pub struct SimRules {
+    /// Pixels with a physics type outside of this stay where they are
+    pub enabled: PhysicsTypeSet,
+    /// See [`Settings::sand_settle_narrow_gaps`]
+    pub narrow_gaps: bool,
+    /// Used for materials without their own [`SandSpread`]
+    pub sand_spread: SandSpread,
+    /// Whether liquids freeze, from the world's rules rather than [`Settings`]
+    pub freezing: bool,
+}
+
+impl SimRules {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            enabled: settings.simulate_physics_types,
+            narrow_gaps: settings.sand_settle_narrow_gaps,
+            sand_spread: settings.sand_spread,
+            freezing: true,
+        }
+    }
+}
+
+impl Default for SimRules {
+    fn default() -> Self {
+        Self {
+            enabled: PhysicsTypeSet::ALL,
+            narrow_gaps: true,
+            sand_spread: SandSpread::Diagonal,
+            freezing: true,
+        }
+    }
+}
+
 trait SimulationHelper {
     fn pixel_local(&self, x: i32, y: i32) -> &MaterialInstance;
     fn set_pixel_local(&mut self, x: i32, y: i32, mat: MaterialInstance);
-    fn color_local(&self, x: i32, y: i32) -> Color;
     fn set_color_local(&mut self, x: i32, y: i32, col: Color);
     fn light_local(&self, x: i32, y: i32) -> &[f32; 3];
     fn set_light_local(&mut self, x: i32, y: i32, light: [f32; 3]);
 
     fn set_all_local(&mut self, x: i32, y: i32, mat: MaterialInstance);
     fn add_particle(&mut self, material: MaterialInstance, pos: Position, vel: Velocity);
+    /// A falling pixel landed at `x`, `y` going `velocity` pixels per tick, see [`SettleEvent`].
+    fn settle(&mut self, x: i32, y: i32, material: &MaterialInstance, velocity: f64);
 }
 
 struct SimulationHelperChunk<'a, 'b> {
@@ -42,10 +133,38 @@ struct SimulationHelperChunk<'a, 'b> {
     max_x: [u16; 9],
     max_y: [u16; 9],
     particles: &'a mut Vec<Particle>,
+    settled: &'a mut Vec<SettleEvent>,
     chunk_x: i32,
     chunk_y: i32,
 }
 
+/// Splits a position relative to the top left of the center chunk in a 3x3 area of
+///   `width` by `height` chunks into (chunk index, pixel index, pixel x in chunk, pixel y in chunk).
+#[inline(always)]
+fn neighborhood_indices(x: i32, y: i32, width: u16, height: u16) -> (usize, usize, u16, u16) {
+    let (w, h) = (i32::from(width), i32::from(height));
+    // past the 3x3 chunks this would wrap around to the wrong chunk instead of going out of bounds
+    if cfg!(feature = "checked_simulation") {
+        assert!(
+            (-w..w * 2).contains(&x) && (-h..h * 2).contains(&y),
+            "simulation local position out of bounds: ({x}, {y})"
+        );
+    }
+    // div_euclid is the same as div_floor in this case (div_floor is currenlty unstable)
+    let rel_chunk_x = x.div_euclid(w) as i8;
+    let rel_chunk_y = y.div_euclid(h) as i8;
+
+    let chunk_px_x = x.rem_euclid(w) as u16;
+    let chunk_px_y = y.rem_euclid(h) as u16;
+
+    (
+        (rel_chunk_x + 1) as usize + (rel_chunk_y + 1) as usize * 3,
+        usize::from(chunk_px_x) + usize::from(chunk_px_y) * usize::from(width),
+        chunk_px_x,
+        chunk_px_y,
+    )
+}
+
 #[allow(unused)]
 impl SimulationHelperChunk<'_, '_> {
     #[inline]
@@ -58,6 +177,7 @@ impl SimulationHelperChunk<'_, '_> {
         &self,
         (ch, px, ..): (usize, usize, u16, u16),
     ) -> &MaterialInstance {
+        Self::check_indices(ch, px);
         &*self
             .chunk_data
             .get_unchecked(ch)
@@ -79,6 +199,9 @@ impl SimulationHelperChunk<'_, '_> {
     ) {
         unsafe {
             *self.chunk_data[ch].pixels[px].get() = mat;
+            if let Some(flow) = self.chunk_data[ch].flow {
+                *flow[px].get() = FLOW_MOVED;
+            }
         }
 
         self.min_x[ch] = self.min_x[ch].min(ch_x);
@@ -93,12 +216,12 @@ impl SimulationHelperChunk<'_, '_> {
         (ch, px, ch_x, ch_y): (usize, usize, u16, u16),
         mat: MaterialInstance,
     ) {
-        *self
-            .chunk_data
-            .get_unchecked_mut(ch)
-            .pixels
-            .get_unchecked(px)
-            .get() = mat;
+        Self::check_indices(ch, px);
+        let data = self.chunk_data.get_unchecked_mut(ch);
+        *data.pixels.get_unchecked(px).get() = mat;
+        if let Some(flow) = data.flow {
+            *flow.get_unchecked(px).get() = FLOW_MOVED;
+        }
 
         *self.min_x.get_unchecked_mut(ch) = (*self.min_x.get_unchecked_mut(ch)).min(ch_x);
         *self.min_y.get_unchecked_mut(ch) = (*self.min_y.get_unchecked_mut(ch)).min(ch_y);
@@ -119,6 +242,7 @@ impl SimulationHelperChunk<'_, '_> {
     #[inline]
     #[allow(dead_code)]
     unsafe fn color_from_index_unchecked(&self, (ch, px, ..): (usize, usize, u16, u16)) -> Color {
+        Self::check_indices(ch, px);
         *self
             .chunk_data
             .get_unchecked(ch)
@@ -148,6 +272,7 @@ impl SimulationHelperChunk<'_, '_> {
         (ch, px, ..): (usize, usize, u16, u16),
         color: Color,
     ) {
+        Self::check_indices(ch, px);
         *self
             .chunk_data
             .get_unchecked_mut(ch)
@@ -167,7 +292,7 @@ impl SimulationHelperChunk<'_, '_> {
     fn light_from_index(&self, (ch, px, ..): (usize, usize, u16, u16)) -> &[f32; 3] {
         // Safety: slicing [f32; 4] as &[f32; 3] will never fail
         unsafe {
-            (*self.chunk_data[ch].lights[px].get())[0..3]
+            (&(*self.chunk_data[ch].lights[px].get()))[0..3]
                 .try_into()
                 .unwrap_unchecked()
         }
@@ -183,12 +308,13 @@ impl SimulationHelperChunk<'_, '_> {
         &self,
         (ch, px, ..): (usize, usize, u16, u16),
     ) -> [f32; 3] {
-        (*self
+        Self::check_indices(ch, px);
+        (&(*self
             .chunk_data
             .get_unchecked(ch)
             .lights
             .get_unchecked(px)
-            .get())[0..3]
+            .get()))[0..3]
             .try_into()
             .unwrap_unchecked()
     }
@@ -206,6 +332,7 @@ impl SimulationHelperChunk<'_, '_> {
         (ch, px, ..): (usize, usize, u16, u16),
         light: [f32; 3],
     ) {
+        Self::check_indices(ch, px);
         *self
             .chunk_data
             .get_unchecked_mut(ch)
@@ -214,36 +341,94 @@ impl SimulationHelperChunk<'_, '_> {
             .get() = [light[0], light[1], light[2], 1.0];
     }
 
+    /// With the `checked_simulation` feature, panics if the indices are outside of the 3x3 chunks
+    ///   instead of letting the `*_unchecked` fns read or write out of bounds.
+    #[inline(always)]
+    fn check_indices(ch: usize, px: usize) {
+        if cfg!(feature = "checked_simulation") {
+            assert!(
+                ch < 9 && px < CHUNK_AREA,
+                "simulation index out of bounds: chunk {ch}, pixel {px}"
+            );
+        }
+    }
+
     // (chunk index, pixel index, pixel x in chunk, pixel y in chunk)
     #[inline(always)]
     fn local_to_indices(x: i32, y: i32) -> (usize, usize, u16, u16) {
-        let size = i32::from(CHUNK_SIZE);
-        // div_euclid is the same as div_floor in this case (div_floor is currenlty unstable)
-        let rel_chunk_x = x.div_euclid(size) as i8;
-        let rel_chunk_y = y.div_euclid(size) as i8;
-
-        let chunk_px_x = x.rem_euclid(size) as u16;
-        let chunk_px_y = y.rem_euclid(size) as u16;
-
-        (
-            (rel_chunk_x + 1) as usize + (rel_chunk_y + 1) as usize * 3,
-            (chunk_px_x + chunk_px_y * CHUNK_SIZE) as usize,
-            chunk_px_x,
-            chunk_px_y,
-        )
+        neighborhood_indices(x, y, CHUNK_WIDTH, CHUNK_HEIGHT)
     }
 
+    /// Turns the changed pixel bounds into each chunk's dirty rect for the next tick.
+    ///
+    /// Anything next to a changed pixel might be able to move now, so the bounds are grown by a pixel,
+    ///   and where that crosses a chunk border the neighbor's dirty rect gets the overlapping part.
     fn finish_dirty_rects(&mut self) {
+        let (w, h) = (i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+        let chunk_rect = |i: usize| {
+            let i = i as u8;
+            let (rel_x, rel_y) = (i32::from(i % 3) - 1, i32::from(i / 3) - 1);
+            Rect::new_wh(rel_x * w, rel_y * h, w, h)
+        };
+
+        let mut rects = [None; 9];
         for i in 0..9 {
-            if self.min_x[i] == CHUNK_SIZE + 1 {
-                self.chunk_data[i].dirty_rect = None;
-            } else {
-                self.chunk_data[i].dirty_rect = Some(Rect::new_wh(
-                    i32::from(self.min_x[i]),
-                    i32::from(self.min_y[i]),
-                    self.max_x[i] - self.min_x[i] + 1,
-                    self.max_y[i] - self.min_y[i] + 1,
-                ));
+            if self.min_x[i] == CHUNK_WIDTH + 1 {
+                continue;
+            }
+
+            let chunk = chunk_rect(i);
+            let changed = Rect::new(
+                chunk.x1 + i32::from(self.min_x[i]),
+                chunk.y1 + i32::from(self.min_y[i]),
+                chunk.x1 + i32::from(self.max_x[i]) + 1,
+                chunk.y1 + i32::from(self.max_y[i]) + 1,
+            )
+            .inflated(1);
+
+            for (j, rect) in rects.iter_mut().enumerate() {
+                let other = chunk_rect(j);
+                if let Some(overlap) = changed.intersection(&other) {
+                    let local = Rect::new(
+                        overlap.x1 - other.x1,
+                        overlap.y1 - other.y1,
+                        overlap.x2 - other.x1,
+                        overlap.y2 - other.y1,
+                    );
+                    *rect = Some(rect.map_or(local, |r: Rect<i32>| r.union(local)));
+                }
+            }
+        }
+
+        for (data, rect) in self.chunk_data.iter_mut().zip(rects) {
+            data.dirty_rect = rect;
+        }
+    }
+
+    /// Adds dirty rects to the neighboring chunks within [`LIGHT_RADIUS`] of `changed`
+    ///   (in center chunk coordinates), so they pick up the light spilling over the border.
+    ///
+    /// Must be called after [`Self::finish_dirty_rects`].
+    fn wake_for_light(&mut self, changed: Rect<i32>) {
+        let (w, h) = (i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+        let reach = changed.inflated(LIGHT_RADIUS);
+
+        for (i, data) in self.chunk_data.iter_mut().enumerate() {
+            if i == 4 {
+                continue;
+            }
+
+            let i = i as u8;
+            let (rel_x, rel_y) = (i32::from(i % 3) - 1, i32::from(i / 3) - 1);
+            let other = Rect::new_wh(rel_x * w, rel_y * h, w, h);
+            if let Some(overlap) = reach.intersection(&other) {
+                let local = Rect::new(
+                    overlap.x1 - other.x1,
+                    overlap.y1 - other.y1,
+                    overlap.x2 - other.x1,
+                    overlap.y2 - other.y1,
+                );
+                data.dirty_rect = Some(data.dirty_rect.map_or(local, |r| r.union(local)));
             }
         }
     }
@@ -260,11 +445,6 @@ impl SimulationHelper for SimulationHelperChunk<'_, '_> {
         self.set_pixel_from_index(Self::local_to_indices(x, y), mat);
     }
 
-    #[inline]
-    fn color_local(&self, x: i32, y: i32) -> Color {
-        self.color_from_index(Self::local_to_indices(x, y))
-    }
-
     #[inline]
     fn set_color_local(&mut self, x: i32, y: i32, col: Color) {
         self.set_color_from_index(Self::local_to_indices(x, y), col);
@@ -283,13 +463,23 @@ impl SimulationHelper for SimulationHelperChunk<'_, '_> {
         self.particles.push(Particle::new(
             material,
             Position {
-                x: pos.x + f64::from(self.chunk_x) * f64::from(CHUNK_SIZE),
-                y: pos.y + f64::from(self.chunk_y) * f64::from(CHUNK_SIZE),
+                x: pos.x + f64::from(self.chunk_x) * f64::from(CHUNK_WIDTH),
+                y: pos.y + f64::from(self.chunk_y) * f64::from(CHUNK_HEIGHT),
             },
             vel,
         ));
     }
 
+    #[inline]
+    fn settle(&mut self, x: i32, y: i32, material: &MaterialInstance, velocity: f64) {
+        self.settled.push(SettleEvent {
+            x: i64::from(x) + i64::from(self.chunk_x) * i64::from(CHUNK_WIDTH),
+            y: i64::from(y) + i64::from(self.chunk_y) * i64::from(CHUNK_HEIGHT),
+            material: material.material_id.clone(),
+            velocity,
+        });
+    }
+
     fn light_local(&self, x: i32, y: i32) -> &[f32; 3] {
         self.light_from_index(Self::local_to_indices(x, y))
     }
@@ -301,6 +491,7 @@ impl SimulationHelper for SimulationHelperChunk<'_, '_> {
 
 struct SimulationHelperRigidBody<'a, C: Chunk> {
     air: MaterialInstance,
+    materials: &'a MaterialRegistry,
     chunk_handler: &'a mut ChunkHandler<C>,
     rigidbodies: &'a mut Vec<FSRigidBody>,
     particles: &'a mut Vec<Particle>,
@@ -311,7 +502,7 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
     fn pixel_local(&self, x: i32, y: i32) -> &MaterialInstance {
         let world_mat = self.chunk_handler.pixel(i64::from(x), i64::from(y)); // TODO: consider changing the args to i64
         if let Ok(m) = world_mat {
-            if m.material_id != *material::AIR {
+            if !m.is_empty(self.materials) {
                 return m;
             }
         }
@@ -331,7 +522,7 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
                 if nt_x >= 0 && nt_y >= 0 && nt_x < cur.width.into() && nt_y < cur.width.into() {
                     let px = &cur.pixels[(nt_x + nt_y * i32::from(cur.width)) as usize];
 
-                    if px.material_id != *material::AIR {
+                    if !px.is_empty(self.materials) {
                         return px;
                     }
                 }
@@ -347,49 +538,6 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
             .set_pixel(i64::from(x), i64::from(y), mat); // TODO: consider changing the args to i64
     }
 
-    fn color_local(&self, x: i32, y: i32) -> Color {
-        let (chunk_x, chunk_y) = pixel_to_chunk_pos(i64::from(x), i64::from(y));
-        let chunk = self.chunk_handler.chunk_at((chunk_x, chunk_y));
-
-        if let Some(ch) = chunk {
-            let col = ch.color(
-                // TODO: test if using unchecked would matter
-                ChunkLocalPosition::new(
-                    (i64::from(x) - i64::from(chunk_x) * i64::from(CHUNK_SIZE)) as u16,
-                    (i64::from(y) - i64::from(chunk_y) * i64::from(CHUNK_SIZE)) as u16,
-                )
-                .unwrap(),
-            );
-            if col.a > 0 {
-                return col;
-            }
-        }
-
-        for i in 0..self.rigidbodies.len() {
-            let cur = &self.rigidbodies[i];
-            if let Some(body) = cur.get_body(self.physics) {
-                let s = (-body.rotation().angle()).sin();
-                let c = (-body.rotation().angle()).cos();
-
-                let tx = x as f32 - body.translation().x * PHYSICS_SCALE;
-                let ty = y as f32 - body.translation().y * PHYSICS_SCALE;
-
-                let nt_x = (tx * c - ty * s) as i32;
-                let nt_y = (tx * s + ty * c) as i32;
-
-                if nt_x >= 0 && nt_y >= 0 && nt_x < cur.width.into() && nt_y < cur.width.into() {
-                    let px = cur.pixels[(nt_x + nt_y * i32::from(cur.width)) as usize].clone();
-
-                    if px.material_id != *material::AIR {
-                        return px.color;
-                    }
-                }
-            }
-        }
-
-        Color::rgba(0, 0, 0, 0)
-    }
-
     fn set_color_local(&mut self, x: i32, y: i32, col: Color) {
         let (chunk_x, chunk_y) = pixel_to_chunk_pos(i64::from(x), i64::from(y));
         let chunk = self.chunk_handler.chunk_at_mut_dyn((chunk_x, chunk_y));
@@ -398,8 +546,8 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
             ch.set_color(
                 // TODO: test if using unchecked would matter
                 ChunkLocalPosition::new(
-                    (i64::from(x) - i64::from(chunk_x) * i64::from(CHUNK_SIZE)) as u16,
-                    (i64::from(y) - i64::from(chunk_y) * i64::from(CHUNK_SIZE)) as u16,
+                    (i64::from(x) - i64::from(chunk_x) * i64::from(CHUNK_WIDTH)) as u16,
+                    (i64::from(y) - i64::from(chunk_y) * i64::from(CHUNK_HEIGHT)) as u16,
                 )
                 .unwrap(),
                 col,
@@ -412,6 +560,10 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
         self.particles.push(Particle::new(material, pos, vel));
     }
 
+    fn settle(&mut self, _x: i32, _y: i32, _material: &MaterialInstance, _velocity: f64) {
+        // pixels in a rigidbody move along with it, so they never really land anywhere
+    }
+
     fn light_local(&self, _x: i32, _y: i32) -> &[f32; 3] {
         // TODO
         &[0.0; 3]
@@ -430,27 +582,266 @@ impl<C: Chunk + Send> SimulationHelper for SimulationHelperRigidBody<'_, C> {
     }
 }
 
+/// A standalone grid of pixels that can be simulated without any chunks or physics,
+///   so material behavior can be tested on its own, see [`Simulator::simulate_grid`].
+///
+/// Everything outside the grid acts as a solid wall.
+#[derive(Debug, Clone)]
+pub struct SimulationGrid {
+    width: u16,
+    height: u16,
+    pixels: Vec<MaterialInstance>,
+    colors: Vec<Color>,
+    lights: Vec<[f32; 3]>,
+    /// Particles spawned while simulating, in grid coordinates.
+    pub particles: Vec<Particle>,
+    /// Pixels that landed while simulating, in grid coordinates.
+    pub settled: Vec<SettleEvent>,
+    wall: MaterialInstance,
+}
+
+impl SimulationGrid {
+    /// A grid filled with air.
+    pub fn new(width: u16, height: u16) -> Self {
+        let area = usize::from(width) * usize::from(height);
+        Self {
+            width,
+            height,
+            pixels: vec![MaterialInstance::air(); area],
+            colors: vec![Color::TRANSPARENT; area],
+            lights: vec![[0.0; 3]; area],
+            particles: vec![],
+            settled: vec![],
+            wall: BEDROCK.instance(PhysicsType::Solid, Color::TRANSPARENT),
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        (x >= 0 && y >= 0 && x < i32::from(self.width) && y < i32::from(self.height))
+            .then(|| (x + y * i32::from(self.width)) as usize)
+    }
+
+    /// The pixel at `x`, `y`, or `None` if it's outside the grid.
+    pub fn get(&self, x: i32, y: i32) -> Option<&MaterialInstance> {
+        self.index(x, y).map(|i| &self.pixels[i])
+    }
+
+    /// Sets the pixel at `x`, `y` along with its color and light.
+    pub fn set(&mut self, x: i32, y: i32, mat: MaterialInstance) -> Result<(), String> {
+        if self.index(x, y).is_none() {
+            return Err(format!("Invalid grid position: {x}, {y}"));
+        }
+        self.set_all_local(x, y, mat);
+        Ok(())
+    }
+}
+
+impl SimulationHelper for SimulationGrid {
+    fn pixel_local(&self, x: i32, y: i32) -> &MaterialInstance {
+        self.get(x, y).unwrap_or(&self.wall)
+    }
+
+    fn set_pixel_local(&mut self, x: i32, y: i32, mat: MaterialInstance) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i] = mat;
+        }
+    }
+
+    fn set_color_local(&mut self, x: i32, y: i32, col: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.colors[i] = col;
+        }
+    }
+
+    fn light_local(&self, x: i32, y: i32) -> &[f32; 3] {
+        self.index(x, y)
+            .map_or(&self.wall.light, |i| &self.lights[i])
+    }
+
+    fn set_light_local(&mut self, x: i32, y: i32, light: [f32; 3]) {
+        if let Some(i) = self.index(x, y) {
+            self.lights[i] = light;
+        }
+    }
+
+    fn set_all_local(&mut self, x: i32, y: i32, mat: MaterialInstance) {
+        self.set_color_local(x, y, mat.color);
+        self.set_light_local(x, y, mat.light);
+        self.set_pixel_local(x, y, mat);
+    }
+
+    fn add_particle(&mut self, material: MaterialInstance, pos: Position, vel: Velocity) {
+        self.particles.push(Particle::new(material, pos, vel));
+    }
+
+    fn settle(&mut self, x: i32, y: i32, material: &MaterialInstance, velocity: f64) {
+        self.settled.push(SettleEvent {
+            x: i64::from(x),
+            y: i64::from(y),
+            material: material.material_id.clone(),
+            velocity,
+        });
+    }
+}
+
 #[derive(Debug)]
 pub struct SimulatorChunkContext<'a> {
     // using UnsafeCell to allow mutations to disjoint indices from different threads
     pub pixels: &'a [UnsafeCell<MaterialInstance>; CHUNK_AREA],
     pub colors: &'a [UnsafeCell<Color>; CHUNK_AREA],
     pub lights: &'a [UnsafeCell<[f32; 4]>; CHUNK_AREA],
+    /// Set to [`FLOW_MOVED`] for every pixel that's written to, if the flow overlay is on.
+    pub flow: Option<&'a [UnsafeCell<u8>; CHUNK_AREA]>,
     pub dirty: bool,
     pub dirty_rect: Option<Rect<i32>>,
 }
 unsafe impl<'a> Send for SimulatorChunkContext<'a> {}
 unsafe impl<'a> Sync for SimulatorChunkContext<'a> {}
 
+/// Looks up something about pixels' materials with `get`, remembering every material it has seen.
+///
+/// Pixels are mostly next to the same few materials, so this is much cheaper than going through
+///   the registry for every pixel.
+struct MaterialLookup<'a, T> {
+    registries: &'a Registries,
+    get: fn(&'a Material) -> T,
+    seen: Vec<(RegistryID<Material>, Option<T>)>,
+}
+
+impl<'a, T: Copy> MaterialLookup<'a, T> {
+    fn new(registries: &'a Registries, get: fn(&'a Material) -> T) -> Self {
+        Self { registries, get, seen: Vec::new() }
+    }
+
+    /// `None` if the pixel's material isn't registered.
+    #[inline]
+    fn get(&mut self, mat: &MaterialInstance) -> Option<T> {
+        if let Some((_, v)) = self.seen.iter().find(|(id, _)| *id == mat.material_id) {
+            return *v;
+        }
+
+        let v = self
+            .registries
+            .materials
+            .get(&mat.material_id)
+            .map(self.get);
+        self.seen.push((mat.material_id.clone(), v));
+        v
+    }
+}
+
+/// The material properties [`Simulator::simulate_pixel`] looks up for the pixels it moves.
+struct PixelLookups<'a> {
+    empty: MaterialLookup<'a, bool>,
+    cohesive: MaterialLookup<'a, bool>,
+    emitter: MaterialLookup<'a, Option<&'a Emitter>>,
+    freeze: MaterialLookup<'a, Option<&'a Freeze>>,
+    spread: MaterialLookup<'a, Option<SandSpread>>,
+    /// Used for materials without their own [`SandSpread`]
+    sand_spread: SandSpread,
+    /// See [`SimRules::freezing`]
+    freezing: bool,
+}
+
+impl<'a> PixelLookups<'a> {
+    fn new(registries: &'a Registries, rules: SimRules) -> Self {
+        Self {
+            empty: MaterialLookup::new(registries, |m| m.empty),
+            cohesive: MaterialLookup::new(registries, |m| m.tags.contains(MaterialTag::COHESIVE)),
+            emitter: MaterialLookup::new(registries, |m| m.emitter.as_ref()),
+            freeze: MaterialLookup::new(registries, |m| m.freeze.as_ref()),
+            spread: MaterialLookup::new(registries, |m| m.sand_spread),
+            sand_spread: rules.sand_spread,
+            freezing: rules.freezing,
+        }
+    }
+
+    /// Same as [`MaterialInstance::is_empty`].
+    #[inline]
+    fn empty(&mut self, mat: &MaterialInstance) -> bool {
+        self.empty
+            .get(mat)
+            .unwrap_or(mat.physics == PhysicsType::Air)
+    }
+
+    /// Whether the pixel's material is [`MaterialTag::COHESIVE`].
+    #[inline]
+    fn cohesive(&mut self, mat: &MaterialInstance) -> bool {
+        self.cohesive.get(mat).unwrap_or(false)
+    }
+
+    #[inline]
+    fn emitter(&mut self, mat: &MaterialInstance) -> Option<&'a Emitter> {
+        self.emitter.get(mat).flatten()
+    }
+
+    /// Nothing freezes if [`SimRules::freezing`] is off.
+    #[inline]
+    fn freeze(&mut self, mat: &MaterialInstance) -> Option<&'a Freeze> {
+        if self.freezing {
+            self.freeze.get(mat).flatten()
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn spread(&mut self, mat: &MaterialInstance) -> SandSpread {
+        self.spread.get(mat).flatten().unwrap_or(self.sand_spread)
+    }
+}
+
 impl Simulator {
-    #[warn(clippy::too_many_arguments)]
+    /// Seed for the rng used when simulating a chunk on a given tick.
+    ///
+    /// Deriving this from the position and tick (instead of using a random seed) makes the result
+    ///   of a tick independent of which thread a chunk is simulated on or in what order.
+    /// `simulation_seed` is [`Settings::simulation_seed`](crate::game::common::Settings::simulation_seed),
+    ///   so changing it gives a different (but still reproducible) result.
+    pub fn chunk_seed(
+        world_seed: i32,
+        simulation_seed: u32,
+        tick_time: u32,
+        chunk_x: i32,
+        chunk_y: i32,
+    ) -> u64 {
+        let mut h = u64::from(world_seed as u32);
+        for v in [
+            u64::from(simulation_seed),
+            u64::from(tick_time),
+            u64::from(chunk_x as u32),
+            u64::from(chunk_y as u32),
+        ] {
+            // splitmix64
+            h = h.wrapping_add(v).wrapping_add(0x9E37_79B9_7F4A_7C15);
+            h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            h ^= h >> 31;
+        }
+        h
+    }
+
+    #[allow(clippy::too_many_arguments)]
     #[profiling::function]
     pub fn simulate_chunk(
         chunk_x: i32,
         chunk_y: i32,
         chunk_data: &mut [SimulatorChunkContext; 9],
         particles: &mut Vec<Particle>,
+        settled: &mut Vec<SettleEvent>,
         registries: Arc<Registries>,
+        seed: u64,
+        light: LightSettings,
+        rules: SimRules,
     ) {
         const CENTER_CHUNK: usize = 4;
 
@@ -465,39 +856,45 @@ impl Simulator {
 
         let mut helper = SimulationHelperChunk {
             chunk_data,
-            min_x: [CHUNK_SIZE + 1; 9],
-            min_y: [CHUNK_SIZE + 1; 9],
+            min_x: [CHUNK_WIDTH + 1; 9],
+            min_y: [CHUNK_HEIGHT + 1; 9],
             max_x: [0; 9],
             max_y: [0; 9],
             particles,
+            settled,
             chunk_x,
             chunk_y,
         };
 
-        let rng = fastrand::Rng::new();
+        let rng = fastrand::Rng::with_seed(seed);
         {
-            /// `x` and `y` MUST be in `0..CHUNK_SIZE` (unchecked)
+            /// `x` MUST be in `0..CHUNK_WIDTH` and `y` in `0..CHUNK_HEIGHT` (unchecked)
             // this being inlined is important for performance
             #[inline(always)]
-            fn process(
+            fn process<'r>(
                 x: i32,
                 y: i32,
                 helper: &mut SimulationHelperChunk,
                 rng: &Rng,
-                _registries: &Registries,
+                rules: SimRules,
+                lookups: &mut PixelLookups<'r>,
             ) {
                 // Safety: x and y are assumed to be within the chunk
 
                 // no real performance benefit so it probably figures this out from the other `unchecked` calls
-                // if x < 0 || x >= i32::from(CHUNK_SIZE) || y < 0 || y >= i32::from(CHUNK_SIZE) {
+                // if x < 0 || x >= i32::from(CHUNK_WIDTH) || y < 0 || y >= i32::from(CHUNK_HEIGHT) {
                 //     unsafe { std::hint::unreachable_unchecked() }
                 // }
 
                 let cur = unsafe { helper.pixel_local_unchecked(x, y) };
 
                 // having this check before the clone reduces update time by like 90%
-                if cur.dynamic() {
-                    if let Some(mat) = Simulator::simulate_pixel(x, y, &cur.clone(), helper, rng) {
+                if cur.dynamic()
+                    || (cur.physics == PhysicsType::Solid && lookups.emitter(cur).is_some())
+                {
+                    if let Some(mat) =
+                        Simulator::simulate_pixel(x, y, &cur.clone(), helper, rng, rules, lookups)
+                    {
                         unsafe {
                             helper.set_color_local_unchecked(x, y, mat.color);
                             helper.set_light_local_unchecked(x, y, mat.light);
@@ -508,24 +905,239 @@ impl Simulator {
             }
 
             profiling::scope!("loop");
+            let mut lookups = PixelLookups::new(&registries, rules);
             if rng.bool() {
                 for y in my_dirty_rect.range_tb().rev() {
                     for x in my_dirty_rect.range_lr() {
                         // Safety: dirty rects are always within the chunk
-                        process(x, y, &mut helper, &rng, &registries);
+                        process(x, y, &mut helper, &rng, rules, &mut lookups);
                     }
                 }
             } else {
                 for y in my_dirty_rect.range_tb().rev() {
                     for x in my_dirty_rect.range_lr().rev() {
                         // Safety: dirty rects are always within the chunk
-                        process(x, y, &mut helper, &rng, &registries);
+                        process(x, y, &mut helper, &rng, rules, &mut lookups);
                     }
                 }
             }
         }
 
+        let light_changed = if light.sun.is_some() || light.propagate {
+            Self::update_light(&mut helper, light, &registries)
+        } else {
+            None
+        };
+
         helper.finish_dirty_rects();
+        if let Some(changed) = light_changed {
+            helper.wake_for_light(changed);
+        }
+    }
+
+    /// Recalculates the center chunk's light buffer as the max of each pixel's own light,
+    ///   sunlight and light spread from emissive pixels nearby.
+    ///
+    /// This only depends on the pixels around the chunk (not on the previous light),
+    ///   so running it again without anything changing gives the same result.
+    /// Returns the area of the center chunk where the light changed.
+    fn update_light(
+        helper: &mut SimulationHelperChunk,
+        light: LightSettings,
+        registries: &Registries,
+    ) -> Option<Rect<i32>> {
+        let (w, h) = (i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+        let mut opacities = MaterialLookup::new(registries, |m| m.opacity);
+        let mut opacity = |mat: &MaterialInstance| {
+            opacities
+                .get(mat)
+                .flatten()
+                .unwrap_or_else(|| mat.physics.default_opacity())
+        };
+
+        let mut lit = Vec::with_capacity(CHUNK_AREA);
+        for y in 0..h {
+            for x in 0..w {
+                lit.push(helper.pixel_local(x, y).light);
+            }
+        }
+
+        if let Some(sun) = light.sun {
+            Self::cast_sun_light(helper, sun, &mut opacity, &mut lit);
+        }
+        if light.propagate {
+            Self::propagate_light(helper, &mut opacity, &mut lit);
+        }
+
+        let mut changed: Option<Rect<i32>> = None;
+        for y in 0..h {
+            for x in 0..w {
+                let l = lit[(x + y * w) as usize];
+                #[allow(clippy::float_cmp)]
+                if *helper.light_local(x, y) != l {
+                    helper.set_light_local(x, y, l);
+                    let px = Rect::new(x, y, x + 1, y + 1);
+                    changed = Some(changed.map_or(px, |r| r.union(px)));
+                }
+            }
+        }
+
+        if changed.is_some() {
+            helper.chunk_data[4].dirty = true;
+        }
+        changed
+    }
+
+    /// Brightens `lit` (the center chunk's light) with sunlight.
+    ///
+    /// Sunlight is scanned down row by row, starting half a chunk above the center chunk
+    ///   (the furthest a chunk update is allowed to touch), so anything higher up is treated as open sky.
+    /// Each pixel is lit by the light reaching it and passes on what its opacity doesn't block.
+    fn cast_sun_light(
+        helper: &SimulationHelperChunk,
+        sun: SunLight,
+        opacity: &mut impl FnMut(&MaterialInstance) -> f32,
+        lit: &mut [[f32; 3]],
+    ) {
+        #[allow(clippy::cast_lossless)]
+        const REACH_X: i32 = CHUNK_WIDTH as i32 / 2;
+        #[allow(clippy::cast_lossless)]
+        const REACH_Y: i32 = CHUNK_HEIGHT as i32 / 2;
+        #[allow(clippy::cast_lossless)]
+        const WIDTH: usize = CHUNK_WIDTH as usize * 2;
+
+        let mut row = [1.0; WIDTH];
+        let mut next = [0.0; WIDTH];
+
+        for y in -REACH_Y..i32::from(CHUNK_HEIGHT) {
+            for (x, transmitted) in (-REACH_X..).zip(next.iter_mut()) {
+                // light coming from outside the scanned area is unobstructed
+                let sample = |p: f32| {
+                    let p0 = p.floor();
+                    let t = p - p0;
+                    let at = |j: f32| {
+                        let i = j + REACH_X as f32;
+                        if i < 0.0 || i >= WIDTH as f32 {
+                            1.0
+                        } else {
+                            row[i as usize]
+                        }
+                    };
+                    at(p0) * (1.0 - t) + at(p0 + 1.0) * t
+                };
+
+                // where the ray hitting this pixel was in the row above
+                let src = x as f32 - sun.slope;
+                let incoming = sample(src) * (1.0 - SUN_SCATTER)
+                    + (sample(src - 1.0) + sample(src + 1.0)) * SUN_SCATTER / 2.0;
+
+                let mat = helper.pixel_local(x, y);
+                *transmitted = incoming * (1.0 - opacity(mat));
+
+                if y >= 0 && (0..i32::from(CHUNK_WIDTH)).contains(&x) {
+                    let sun_light = incoming * sun.intensity;
+                    let l = &mut lit[(x + y * i32::from(CHUNK_WIDTH)) as usize];
+                    *l = l.map(|l| l.max(sun_light));
+                }
+            }
+
+            std::mem::swap(&mut row, &mut next);
+        }
+    }
+
+    /// Brightens `lit` (the center chunk's light) with light spreading out from emissive pixels,
+    ///   including ones in the neighboring chunks up to [`LIGHT_RADIUS`] away.
+    ///
+    /// Light fades by [`LIGHT_FALLOFF`] for each pixel it travels, and passes through pixels
+    ///   the same way sunlight does. Opaque pixels are still lit on the side facing the light.
+    fn propagate_light(
+        helper: &SimulationHelperChunk,
+        opacity: &mut impl FnMut(&MaterialInstance) -> f32,
+        lit: &mut [[f32; 3]],
+    ) {
+        #[allow(clippy::cast_lossless)]
+        const GRID_W: usize = CHUNK_WIDTH as usize + LIGHT_RADIUS as usize * 2;
+        #[allow(clippy::cast_lossless)]
+        const GRID_H: usize = CHUNK_HEIGHT as usize + LIGHT_RADIUS as usize * 2;
+        const AREA: usize = GRID_W * GRID_H;
+        const DIAGONAL_FALLOFF: f32 = LIGHT_FALLOFF * std::f32::consts::SQRT_2;
+
+        let mut emitted = Vec::with_capacity(AREA);
+        let mut transmit = Vec::with_capacity(AREA);
+        for y in -LIGHT_RADIUS..i32::from(CHUNK_HEIGHT) + LIGHT_RADIUS {
+            for x in -LIGHT_RADIUS..i32::from(CHUNK_WIDTH) + LIGHT_RADIUS {
+                let mat = helper.pixel_local(x, y);
+                emitted.push(mat.light);
+                transmit.push(1.0 - opacity(mat));
+            }
+        }
+
+        // most chunks don't have anything glowing near them
+        if !emitted.iter().flatten().any(|l| *l > 0.0) {
+            return;
+        }
+
+        // light reaching each pixel, and light leaving it
+        let mut incoming = vec![[0.0_f32; 3]; AREA];
+        let mut outgoing = emitted.clone();
+
+        // each sweep pulls light from the neighbors already visited in that direction,
+        //   light only ever gets brighter so this always settles on the same result
+        for sweep in 0..LIGHT_SWEEPS {
+            let forward = sweep % 2 == 0;
+            for step in 0..AREA {
+                let i = if forward { step } else { AREA - 1 - step };
+                let (x, y) = (i % GRID_W, i / GRID_W);
+
+                let mut inc = incoming[i];
+                let mut pull = |j: usize, falloff: f32| {
+                    for (l, o) in inc.iter_mut().zip(outgoing[j]) {
+                        *l = l.max(o - falloff);
+                    }
+                };
+                if forward {
+                    if x > 0 {
+                        pull(i - 1, LIGHT_FALLOFF);
+                    }
+                    if y > 0 {
+                        pull(i - GRID_W, LIGHT_FALLOFF);
+                        if x > 0 {
+                            pull(i - GRID_W - 1, DIAGONAL_FALLOFF);
+                        }
+                        if x + 1 < GRID_W {
+                            pull(i - GRID_W + 1, DIAGONAL_FALLOFF);
+                        }
+                    }
+                } else {
+                    if x + 1 < GRID_W {
+                        pull(i + 1, LIGHT_FALLOFF);
+                    }
+                    if y + 1 < GRID_H {
+                        pull(i + GRID_W, LIGHT_FALLOFF);
+                        if x + 1 < GRID_W {
+                            pull(i + GRID_W + 1, DIAGONAL_FALLOFF);
+                        }
+                        if x > 0 {
+                            pull(i + GRID_W - 1, DIAGONAL_FALLOFF);
+                        }
+                    }
+                }
+                incoming[i] = inc;
+
+                for ((o, e), l) in outgoing[i].iter_mut().zip(emitted[i]).zip(inc) {
+                    *o = e.max(l * transmit[i]);
+                }
+            }
+        }
+
+        let width = usize::from(CHUNK_WIDTH);
+        let offset = LIGHT_RADIUS as usize;
+        for (i, l) in lit.iter_mut().enumerate() {
+            let g = (i % width + offset) + (i / width + offset) * GRID_W;
+            for (l, inc) in l.iter_mut().zip(incoming[g]) {
+                *l = l.max(inc);
+            }
+        }
     }
 
     #[allow(clippy::unnecessary_unwrap)]
@@ -536,7 +1148,10 @@ impl Simulator {
         rigidbodies: &mut Vec<FSRigidBody>,
         physics: &mut Physics,
         particles: &mut Vec<Particle>,
+        registries: &Registries,
+        rules: SimRules,
     ) {
+        let mut lookups = PixelLookups::new(registries, rules);
         let mut dirty = vec![false; rigidbodies.len()];
         let mut needs_remesh = vec![false; rigidbodies.len()];
         for i in 0..rigidbodies.len() {
@@ -544,7 +1159,9 @@ impl Simulator {
             let rb_h = rigidbodies[i].height;
             let body_opt = rigidbodies[i].get_body(physics);
 
-            if let Some(body) = body_opt {
+            // sleeping bodies are woken up when they're hit or their pixels are edited,
+            //   until then nothing in them can change
+            if let Some(body) = body_opt.filter(|b| !b.is_sleeping()) {
                 let s = body.rotation().angle().sin();
                 let c = body.rotation().angle().cos();
                 let pos_x = body.translation().x * PHYSICS_SCALE;
@@ -552,6 +1169,7 @@ impl Simulator {
 
                 let mut helper = SimulationHelperRigidBody {
                     air: MaterialInstance::air(),
+                    materials: &registries.materials,
                     chunk_handler,
                     rigidbodies,
                     particles,
@@ -568,8 +1186,15 @@ impl Simulator {
                         let cur =
                             helper.rigidbodies[i].pixels[(rb_x + rb_y * rb_w) as usize].clone();
 
-                        let res =
-                            Self::simulate_pixel(tx as i32, ty as i32, &cur, &mut helper, &rng);
+                        let res = Self::simulate_pixel(
+                            tx as i32,
+                            ty as i32,
+                            &cur,
+                            &mut helper,
+                            &rng,
+                            rules,
+                            &mut lookups,
+                        );
 
                         if let Some(mat) = res {
                             helper.rigidbodies[i].pixels[(rb_x + rb_y * rb_w) as usize] =
@@ -589,9 +1214,15 @@ impl Simulator {
         }
 
         for i in 0..rigidbodies.len() {
-            if dirty[i] && !needs_remesh[i] {
-                // don't bother updating the image if it's going to be destroyed anyway
-                rigidbodies[i].image_dirty = true;
+            if dirty[i] {
+                // keep it awake while its pixels are still moving
+                if let Some(body) = rigidbodies[i].get_body_mut(physics) {
+                    body.wake_up(true);
+                }
+                if !needs_remesh[i] {
+                    // don't bother updating the image if it's going to be destroyed anyway
+                    rigidbodies[i].image_dirty = true;
+                }
             }
         }
 
@@ -645,96 +1276,1195 @@ impl Simulator {
         rigidbodies.append(&mut new_rb);
     }
 
+    /// How many of the 8 pixels around `x`, `y` are the same material as `cur`.
+    fn same_neighbors(
+        helper: &impl SimulationHelper,
+        x: i32,
+        y: i32,
+        cur: &MaterialInstance,
+    ) -> u8 {
+        let mut count = 0;
+        for (dx, dy) in [
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ] {
+            if helper.pixel_local(x + dx, y + dy).material_id == cur.material_id {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Runs one update over every pixel in `grid`, the same way [`Self::simulate_chunk`] does
+    ///   for a chunk, and returns the result.
+    ///
+    /// The same `seed` always gives the same result.
+    pub fn simulate_grid(
+        mut grid: SimulationGrid,
+        registries: &Registries,
+        seed: u64,
+        rules: SimRules,
+    ) -> SimulationGrid {
+        let rng = fastrand::Rng::with_seed(seed);
+        let mut lookups = PixelLookups::new(registries, rules);
+        let (width, height) = (i32::from(grid.width), i32::from(grid.height));
+        let right_to_left = rng.bool();
+
+        for y in (0..height).rev() {
+            for i in 0..width {
+                let x = if right_to_left { width - 1 - i } else { i };
+
+                let cur = grid.pixel_local(x, y);
+                if cur.dynamic()
+                    || (cur.physics == PhysicsType::Solid && lookups.emitter(cur).is_some())
+                {
+                    let cur = cur.clone();
+                    if let Some(mat) =
+                        Self::simulate_pixel(x, y, &cur, &mut grid, &rng, rules, &mut lookups)
+                    {
+                        grid.set_all_local(x, y, mat);
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
     #[allow(clippy::inline_always)]
+    #[allow(clippy::too_many_lines)]
     #[inline(always)] // speeds up simulate_chunk by ~35%
-    fn simulate_pixel(
+    fn simulate_pixel<'r>(
         x: i32,
         y: i32,
         cur: &MaterialInstance,
         helper: &mut impl SimulationHelper,
         rng: &fastrand::Rng,
+        rules: SimRules,
+        lookups: &mut PixelLookups<'r>,
     ) -> Option<MaterialInstance> {
+        // disabled pixels stay where they are, but are still there for everything else to run into
+        if !rules.enabled.contains(cur.physics) {
+            return None;
+        }
+
         let mut new_mat = None;
 
-        #[allow(clippy::single_match)]
         match cur.physics {
             PhysicsType::Sand => {
-                let can_move_down = helper.pixel_local(x, y + 1).physics == PhysicsType::Air;
-                let can_move_down_left =
-                    helper.pixel_local(x - 1, y + 1).physics == PhysicsType::Air;
-                let can_move_down_right =
-                    helper.pixel_local(x + 1, y + 1).physics == PhysicsType::Air;
+                // touching liquid soaks and stains the pixel, otherwise it slowly dries out
+                let mut touching_liquid = false;
+                let mut color = cur.color;
+                for (dx, dy) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                    let neighbor = helper.pixel_local(x + dx, y + dy);
+                    if neighbor.physics == PhysicsType::Liquid {
+                        touching_liquid = true;
+                        color = Self::stain(color, neighbor.color);
+                    }
+                }
+                let moisture = if touching_liquid {
+                    1.0
+                } else {
+                    (cur.moisture - SAND_DRY_RATE).max(0.0)
+                };
+                let original_moisture = cur.moisture;
+                let original_color = cur.color;
+                let cur = &MaterialInstance { color, moisture, ..cur.clone() };
+
+                let can_move_down = lookups.empty(helper.pixel_local(x, y + 1));
+                let can_move_down_left = lookups.empty(helper.pixel_local(x - 1, y + 1));
+                let can_move_down_right = lookups.empty(helper.pixel_local(x + 1, y + 1));
 
                 let can_move_dl_or_dr = can_move_down_right || can_move_down_left;
 
-                if can_move_down && (!can_move_dl_or_dr || rng.u8(0..10) != 0) {
+                // walled in on both sides (a one wide gap), so scattering would just bounce off the
+                //   walls and sticking to them would leave a hole, just slide straight down instead
+                let confined = rules.narrow_gaps
+                    && !can_move_dl_or_dr
+                    && !lookups.empty(helper.pixel_local(x - 1, y))
+                    && !lookups.empty(helper.pixel_local(x + 1, y));
+
+                // cohesive pixels stuck to enough of their own kind can rest on a diagonal neighbor
+                //   like an arch, but fall normally once that's gone too
+                let held = !confined
+                    && lookups.cohesive(cur)
+                    && Self::same_neighbors(&*helper, x, y, cur) >= SAND_COHESION_NEIGHBORS;
+                let resting = held && !(can_move_down_left && can_move_down_right);
+
+                let cardinal = lookups.spread(cur) == SandSpread::Cardinal;
+
+                if can_move_down
+                    && !resting
+                    && (cardinal || !can_move_dl_or_dr || rng.u8(0..10) != 0)
+                {
                     // are a few pixels below clear
-                    let empty_below = (0..4).all(|i| {
-                        // don't include self or one below
-                        helper.pixel_local(x, y + i + 2).physics == PhysicsType::Air
-                    });
+                    let empty_below = !confined
+                        && (0..4).all(|i| {
+                            // don't include self or one below
+                            lookups.empty(helper.pixel_local(x, y + i + 2))
+                        });
 
-                    if empty_below {
+                    new_mat = Some(if empty_below {
                         // if a few pixels below are clear, become a particle
                         helper.add_particle(
                             cur.clone(),
                             Position { x: f64::from(x), y: f64::from(y) },
                             Velocity { x: (rng.f64() - 0.5) * 0.5, y: 1.0 + rng.f64() },
                         );
+                        helper.pixel_local(x, y + 1).clone()
                     } else {
                         // otherwise move 1 or 2 pixels down
-                        if rng.bool() && helper.pixel_local(x, y + 2).physics == PhysicsType::Air {
-                            helper.set_all_local(x, y + 2, cur.clone());
+                        if rng.bool() && lookups.empty(helper.pixel_local(x, y + 2)) {
+                            Self::move_into(helper, x, y + 2, cur.clone())
                         } else {
-                            helper.set_all_local(x, y + 1, cur.clone());
+                            Self::move_into(helper, x, y + 1, cur.clone())
                         }
-                    }
-
-                    new_mat = Some(MaterialInstance::air());
+                    });
                 } else {
                     // !can_move_down && can_move_dl_or_dr
 
-                    let above_is_air = helper.pixel_local(x, y - 1).physics == PhysicsType::Air;
+                    let above_is_air = lookups.empty(helper.pixel_local(x, y - 1));
+
+                    // wet pixels clump together and are less likely to slide
+                    let clumped = held || cur.moisture > rng.f32() * SAND_CLUMP_MOISTURE;
 
                     // covered pixels are less likely to move down to the sides
-                    if above_is_air || rng.bool() {
-                        if can_move_down_left && can_move_down_right {
+                    if !clumped && (above_is_air || rng.bool()) {
+                        if cardinal {
+                            // only step off where it drops by 2 or more, so piles end up twice
+                            //   as steep as sliding diagonally, then fall from there
+                            let mut can_step = |dx: i32| {
+                                (0..3).all(|dy| lookups.empty(helper.pixel_local(x + dx, y + dy)))
+                            };
+                            let dx = match (can_step(-1), can_step(1)) {
+                                (true, true) => Some(if rng.bool() { 1 } else { -1 }),
+                                (true, false) => Some(-1),
+                                (false, true) => Some(1),
+                                (false, false) => None,
+                            };
+                            if let Some(dx) = dx {
+                                new_mat = Some(Self::move_into(helper, x + dx, y, cur.clone()));
+                            }
+                        } else if can_move_down_left && can_move_down_right {
                             // randomly pick a direction
-                            helper.set_all_local(
+                            new_mat = Some(Self::move_into(
+                                helper,
                                 x + if rng.bool() { 1 } else { -1 },
                                 y + 1,
                                 cur.clone(),
-                            );
-                            new_mat = Some(MaterialInstance::air());
+                            ));
                         } else if can_move_down_left {
                             // chance to move by 2
                             if rng.bool()
-                                && helper.pixel_local(x - 2, y + 1).physics == PhysicsType::Air
-                                && helper.pixel_local(x - 2, y + 2).physics != PhysicsType::Air
+                                && lookups.empty(helper.pixel_local(x - 2, y + 1))
+                                && !lookups.empty(helper.pixel_local(x - 2, y + 2))
                             {
-                                helper.set_all_local(x - 2, y + 1, cur.clone());
+                                new_mat = Some(Self::move_into(helper, x - 2, y + 1, cur.clone()));
                             } else {
-                                helper.set_all_local(x - 1, y + 1, cur.clone());
+                                new_mat = Some(Self::move_into(helper, x - 1, y + 1, cur.clone()));
                             }
-                            new_mat = Some(MaterialInstance::air());
                         } else if can_move_down_right {
                             // chance to move by 2
                             if rng.bool()
-                                && helper.pixel_local(x + 2, y + 1).physics == PhysicsType::Air
-                                && helper.pixel_local(x + 2, y + 2).physics != PhysicsType::Air
+                                && lookups.empty(helper.pixel_local(x + 2, y + 1))
+                                && !lookups.empty(helper.pixel_local(x + 2, y + 2))
                             {
-                                helper.set_all_local(x + 2, y + 1, cur.clone());
+                                new_mat = Some(Self::move_into(helper, x + 2, y + 1, cur.clone()));
                             } else {
-                                helper.set_all_local(x + 1, y + 1, cur.clone());
+                                new_mat = Some(Self::move_into(helper, x + 1, y + 1, cur.clone()));
                             }
-                            new_mat = Some(MaterialInstance::air());
                         }
                     }
                 }
+
+                #[allow(clippy::float_cmp)]
+                if new_mat.is_none()
+                    && (cur.moisture != original_moisture || cur.color != original_color)
+                {
+                    new_mat = Some(cur.clone());
+                }
+            },
+            PhysicsType::Liquid => {
+                // resting pixels freeze from whatever they touch that isn't liquid,
+                //   so only the surface of a pool can freeze and the ice grows inwards from there
+                let mut freezing = false;
+                if let Some(f) = lookups
+                    .freeze(cur)
+                    .filter(|_| !lookups.empty(helper.pixel_local(x, y + 1)))
+                {
+                    let exposed: u8 = [(0, -1), (-1, 0), (1, 0), (0, 1)]
+                        .into_iter()
+                        .map(|(dx, dy)| {
+                            u8::from(
+                                helper.pixel_local(x + dx, y + dy).physics != PhysicsType::Liquid,
+                            )
+                        })
+                        .sum();
+                    if exposed > 0 {
+                        if rng.f32() < f.rate * f32::from(exposed) / 4.0 {
+                            return Some(f.into.clone());
+                        }
+                        // stay awake until it freezes
+                        freezing = true;
+                    }
+                }
+
+                if lookups.empty(helper.pixel_local(x, y + 1)) {
+                    let cur = MaterialInstance { fall: cur.fall.saturating_add(1), ..cur.clone() };
+
+                    // move 1 or 2 pixels down
+                    new_mat = Some(
+                        if rng.bool() && lookups.empty(helper.pixel_local(x, y + 2)) {
+                            Self::move_into(helper, x, y + 2, cur)
+                        } else {
+                            Self::move_into(helper, x, y + 1, cur)
+                        },
+                    );
+                } else if cur.fall > 0 {
+                    let landed = MaterialInstance { fall: 0, ..cur.clone() };
+
+                    new_mat = Some(
+                        if cur.fall >= LIQUID_SPRAY_FALL && rng.f32() < LIQUID_SPRAY_CHANCE {
+                            // hit something after a long fall, so splash outwards instead of stacking up
+                            // the pixel itself becomes the spray, which lands back as liquid
+                            let speed = f64::from(cur.fall.min(LIQUID_SPRAY_MAX_FALL))
+                                / f64::from(LIQUID_SPRAY_MAX_FALL);
+                            let side = if rng.bool() { 1.0 } else { -1.0 };
+                            helper.add_particle(
+                                landed,
+                                Position { x: f64::from(x), y: f64::from(y) },
+                                Velocity {
+                                    x: side * (0.5 + rng.f64() * 1.5) * speed,
+                                    y: -(1.0 + rng.f64()) * speed,
+                                },
+                            );
+                            MaterialInstance::air()
+                        } else {
+                            // as fast as something falling for that long would be going
+                            let velocity = f64::from(cur.fall) * PARTICLE_GRAVITY;
+                            helper.settle(x, y, &landed, velocity);
+                            landed
+                        },
+                    );
+                } else if let Some((side, depth)) = Self::breach(helper, x, y, lookups) {
+                    // the weight of the liquid above pushes the pixel out through the opening
+                    let speed =
+                        LIQUID_JET_SPEED * f64::from(depth) / f64::from(LIQUID_JET_MAX_DEPTH);
+                    helper.add_particle(
+                        cur.clone(),
+                        Position { x: f64::from(x), y: f64::from(y) },
+                        Velocity {
+                            x: f64::from(side) * speed * (0.9 + rng.f64() * 0.2),
+                            y: -rng.f64() * 0.1 * speed,
+                        },
+                    );
+                    new_mat = Some(MaterialInstance::air());
+                } else {
+                    // flow down a diagonal if it can, otherwise to the side, so pools level out
+                    // liquid only moves sideways when there's liquid on top pushing it, or it's
+                    //   sitting on liquid and the surface is lower nearby, so a lone droplet or a
+                    //   level surface comes to rest instead of wandering forever
+                    let side = if rng.bool() { 1 } else { -1 };
+                    let pressed = helper.pixel_local(x, y - 1).physics == PhysicsType::Liquid;
+                    let on_liquid = helper.pixel_local(x, y + 1).physics == PhysicsType::Liquid;
+                    let mut flow = None;
+                    for (dx, dy) in [(side, 1), (-side, 1), (side, 0), (-side, 0)] {
+                        if !lookups.empty(helper.pixel_local(x + dx, y + dy)) {
+                            continue;
+                        }
+                        if dy == 1 || pressed {
+                            flow = Some((dx, dy));
+                            break;
+                        }
+                        if on_liquid {
+                            let mut lower = false;
+                            for i in 1..=LIQUID_LEVEL_SEARCH {
+                                if !lookups.empty(helper.pixel_local(x + dx * i, y)) {
+                                    break;
+                                }
+                                if lookups.empty(helper.pixel_local(x + dx * i, y + 1)) {
+                                    lower = true;
+                                    break;
+                                }
+                            }
+                            if lower {
+                                flow = Some((dx, dy));
+                                break;
+                            }
+                        }
+                    }
+                    if let Some((dx, dy)) = flow {
+                        new_mat = Some(Self::move_into(helper, x + dx, y + dy, cur.clone()));
+                    }
+                }
+
+                if new_mat.is_none() && freezing {
+                    new_mat = Some(cur.clone());
+                }
+            },
+            PhysicsType::Solid => {
+                if let Some(e) = lookups.emitter(cur) {
+                    new_mat = Self::emit(x, y, cur, e, helper, rng, lookups);
+                }
             },
             _ => {},
         }
 
         new_mat
     }
+
+    /// Finds an opening beside a resting liquid pixel that it should jet out of,
+    ///   returning which way it's facing (`-1` or `1`) and how deep the liquid above is.
+    ///
+    /// The depth is a coarse stand-in for pressure: only the column right above is counted.
+    ///   Openings are [`Material::empty`] pixels, like everywhere else in the simulator.
+    ///   Pixels with an opening on both sides aren't held in by anything, so they never jet.
+    #[inline]
+    fn breach(
+        helper: &impl SimulationHelper,
+        x: i32,
+        y: i32,
+        lookups: &mut PixelLookups,
+    ) -> Option<(i32, u8)> {
+        let left = lookups.empty(helper.pixel_local(x - 1, y));
+        let right = lookups.empty(helper.pixel_local(x + 1, y));
+        let side = match (left, right) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => return None,
+        };
+
+        let mut depth = 0;
+        while depth < LIQUID_JET_MAX_DEPTH
+            && helper.pixel_local(x, y - 1 - i32::from(depth)).physics == PhysicsType::Liquid
+        {
+            depth += 1;
+        }
+
+        (depth >= LIQUID_JET_MIN_DEPTH).then_some((side, depth))
+    }
+
+    /// Runs an [`Emitter`] pixel.
+    ///
+    /// Returns the pixel unchanged to keep it awake while it has something to do,
+    ///   so one that's boxed in (or has nothing to drain) sleeps until a neighbor changes.
+    #[inline]
+    fn emit(
+        x: i32,
+        y: i32,
+        cur: &MaterialInstance,
+        emitter: &Emitter,
+        helper: &mut impl SimulationHelper,
+        rng: &fastrand::Rng,
+        lookups: &mut PixelLookups,
+    ) -> Option<MaterialInstance> {
+        match emitter {
+            Emitter::Source { output, rate } => {
+                let side = if rng.bool() { 1 } else { -1 };
+                let (dx, dy) = [(0, 1), (side, 0), (-side, 0), (0, -1)]
+                    .into_iter()
+                    .find(|&(dx, dy)| lookups.empty(helper.pixel_local(x + dx, y + dy)))?;
+                if rng.f32() < *rate {
+                    helper.set_all_local(x + dx, y + dy, output.clone());
+                }
+                Some(cur.clone())
+            },
+            Emitter::Drain { rate } => {
+                let mut draining = false;
+                for (dx, dy) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                    if helper.pixel_local(x + dx, y + dy).dynamic() {
+                        draining = true;
+                        if rng.f32() < *rate {
+                            helper.set_all_local(x + dx, y + dy, MaterialInstance::air());
+                        }
+                    }
+                }
+                draining.then(|| cur.clone())
+            },
+        }
+    }
+
+    /// Moves `mat` into the empty pixel at `x`, `y`, returning what was there to be left behind in its place.
+    #[inline(always)]
+    fn move_into(
+        helper: &mut impl SimulationHelper,
+        x: i32,
+        y: i32,
+        mat: MaterialInstance,
+    ) -> MaterialInstance {
+        let empty = helper.pixel_local(x, y).clone();
+        helper.set_all_local(x, y, mat);
+        empty
+    }
+
+    /// Blends `color` a bit towards the color of a `liquid` it's touching, keeping its own alpha.
+    ///
+    /// Each channel is rounded towards the liquid's and never past it, so repeated contact ends up
+    ///   at exactly the liquid's color instead of drifting towards white or black.
+    #[inline]
+    fn stain(color: Color, liquid: Color) -> Color {
+        let amount = SAND_STAIN_RATE * liquid.a_f32();
+        let blend = |c: u8, l: u8| {
+            let mixed = f32::from(c) + (f32::from(l) - f32::from(c)) * amount;
+            if l > c {
+                mixed.ceil().min(f32::from(l)) as u8
+            } else {
+                mixed.floor().max(f32::from(l)) as u8
+            }
+        };
+
+        Color {
+            r: blend(color.r, liquid.r),
+            g: blend(color.g, liquid.g),
+            b: blend(color.b, liquid.b),
+            a: color.a,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::common::{
+        registry::RegistryID,
+        world::material::{
+            self, color::Color, Freeze, Material, MaterialInstance, PhysicsType, PhysicsTypeSet,
+            SandSpread, COBBLE_STONE, DRAIN, ICE, SNOW, SPRING, TEST, WATER,
+        },
+        Registries,
+    };
+
+    use super::{neighborhood_indices, PixelLookups, SimRules, SimulationGrid, Simulator};
+
+    fn sand() -> MaterialInstance {
+        TEST.instance(PhysicsType::Sand, Color::rgb(255, 220, 100))
+    }
+
+    fn water() -> MaterialInstance {
+        TEST.instance(PhysicsType::Liquid, Color::rgb(60, 90, 160))
+    }
+
+    fn stone() -> MaterialInstance {
+        COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127))
+    }
+
+    #[test]
+    fn sand_falls_one_cell() {
+        let registries = Registries::empty();
+        // the grid's walls stop it from sliding sideways or falling any further
+        let mut grid = SimulationGrid::new(1, 2);
+        grid.set(0, 0, sand()).unwrap();
+
+        for seed in 0..16 {
+            let next =
+                Simulator::simulate_grid(grid.clone(), &registries, seed, SimRules::default());
+            assert!(next.get(0, 0).unwrap().is_empty(&registries.materials));
+            assert_eq!(next.get(0, 1).unwrap(), &sand());
+            assert!(next.particles.is_empty());
+        }
+    }
+
+    #[test]
+    fn sand_on_flat_stone_stays() {
+        let registries = Registries::empty();
+        let mut grid = SimulationGrid::new(3, 2);
+        grid.set(1, 0, sand()).unwrap();
+        for x in 0..3 {
+            grid.set(x, 1, stone()).unwrap();
+        }
+
+        for seed in 0..16 {
+            let next =
+                Simulator::simulate_grid(grid.clone(), &registries, seed, SimRules::default());
+            for y in 0..2 {
+                for x in 0..3 {
+                    assert_eq!(next.get(x, y), grid.get(x, y));
+                }
+            }
+        }
+
+        // disabled pixels never move
+        let mut grid = SimulationGrid::new(1, 2);
+        grid.set(0, 0, sand()).unwrap();
+        let next = Simulator::simulate_grid(
+            grid,
+            &registries,
+            0,
+            SimRules {
+                enabled: PhysicsTypeSet::ALL.with(PhysicsType::Sand, false),
+                ..SimRules::default()
+            },
+        );
+        assert_eq!(next.get(0, 0).unwrap(), &sand());
+    }
+
+    #[test]
+    fn disabled_physics_types_stay_frozen() {
+        let registries = Registries::empty();
+        let rules = SimRules {
+            enabled: PhysicsTypeSet::ALL.with(PhysicsType::Sand, false),
+            ..SimRules::default()
+        };
+        // sand hanging in the air next to a column of water
+        let mut grid = SimulationGrid::new(20, 10);
+        for y in 0..5 {
+            grid.set(2, y, sand()).unwrap();
+            grid.set(12, y, water()).unwrap();
+        }
+
+        for seed in 0..100 {
+            grid = Simulator::simulate_grid(grid, &registries, seed, rules);
+        }
+
+        for y in 0..5 {
+            assert_eq!(grid.get(2, y).unwrap(), &sand());
+        }
+        // the water still flowed out over the floor
+        let spread = (0..20)
+            .filter(|&x| grid.get(x, 9).unwrap().physics == PhysicsType::Liquid)
+            .count();
+        assert!(
+            spread > 3,
+            "water didn't flow, only {spread} pixels on the floor"
+        );
+    }
+
+    #[test]
+    fn sand_gets_wet_touching_liquid() {
+        let registries = Registries::empty();
+        // the water is boxed in by the sand and a stone pillar, so it stays put
+        let mut grid = SimulationGrid::new(4, 2);
+        for x in 0..4 {
+            grid.set(x, 1, stone()).unwrap();
+        }
+        grid.set(0, 0, sand()).unwrap();
+        grid.set(1, 0, water()).unwrap();
+        grid.set(2, 0, stone()).unwrap();
+        grid.set(3, 0, sand()).unwrap();
+
+        let next = Simulator::simulate_grid(grid, &registries, 0, SimRules::default());
+        assert!(next.get(0, 0).unwrap().moisture > 0.9);
+        assert!(next.get(3, 0).unwrap().moisture <= 0.0);
+    }
+
+    #[test]
+    fn liquid_stains_sand() {
+        let registries = Registries::empty();
+        let sand_color = sand().color;
+        let dye_color = Color::rgb(200, 0, 220);
+        let dye = TEST.instance(PhysicsType::Liquid, dye_color);
+
+        // a layer of dye on top of a layer of sand, held in by a stone pillar,
+        //   and one more sand pixel past it that the dye can't reach
+        let mut grid = SimulationGrid::new(7, 2);
+        for x in 0..7 {
+            grid.set(x, 1, sand()).unwrap();
+        }
+        for x in 0..5 {
+            grid.set(x, 0, dye.clone()).unwrap();
+        }
+        grid.set(5, 0, stone()).unwrap();
+
+        let between = |c: u8, a: u8, b: u8| (a.min(b)..=a.max(b)).contains(&c);
+        let mut last = sand_color;
+        let mut seed = 0;
+        for _ in 0..20 {
+            for _ in 0..10 {
+                grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+                seed += 1;
+            }
+
+            let stained = grid.get(2, 1).unwrap();
+            assert_eq!(stained.material_id, *TEST);
+            assert_eq!(stained.physics, PhysicsType::Sand);
+
+            // every channel only moves towards the dye and never past it
+            let c = stained.color;
+            assert!(between(c.r, last.r, dye_color.r));
+            assert!(between(c.g, last.g, dye_color.g));
+            assert!(between(c.b, last.b, dye_color.b));
+            assert_eq!(c.a, sand_color.a);
+            last = c;
+        }
+
+        // it saturates at the dye's color
+        assert_eq!(last, dye_color);
+        for _ in 0..50 {
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            seed += 1;
+        }
+        assert_eq!(grid.get(2, 1).unwrap().color, dye_color);
+
+        // sand not touching the dye keeps its color
+        assert_eq!(grid.get(6, 1).unwrap().color, sand_color);
+    }
+
+    #[test]
+    fn snow_holds_overhang() {
+        let registries = Registries {
+            materials: material::init_material_types(),
+            ..Registries::empty()
+        };
+
+        // a 3 pixel deep layer lying on a ledge, sticking out 4 pixels over a deep drop
+        let layer = |powder: &MaterialInstance| {
+            let mut grid = SimulationGrid::new(24, 24);
+            for y in 8..24 {
+                for x in 0..8 {
+                    grid.set(x, y, stone()).unwrap();
+                }
+            }
+            for y in 5..8 {
+                for x in 0..12 {
+                    grid.set(x, y, powder.clone()).unwrap();
+                }
+            }
+            grid
+        };
+        let run = |mut grid: SimulationGrid| {
+            for seed in 0..100 {
+                grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            }
+            grid
+        };
+        let overhang = |grid: &SimulationGrid, powder: &MaterialInstance| {
+            (0..8)
+                .flat_map(|y| (8..24).map(move |x| (x, y)))
+                .filter(|&(x, y)| grid.get(x, y).unwrap().material_id == powder.material_id)
+                .count()
+        };
+
+        let snow = SNOW.instance(PhysicsType::Sand, Color::rgb(240, 245, 255));
+        let mut grid = layer(&snow);
+        // a lone pixel and a small clump out over the drop with nothing holding them up
+        grid.set(20, 0, snow.clone()).unwrap();
+        for y in 2..5 {
+            for x in 16..19 {
+                grid.set(x, y, snow.clone()).unwrap();
+            }
+        }
+
+        let grid = run(grid);
+        // the pixels resting diagonally on the edge of the ledge stay, propping up the ones above
+        for (x, y) in [(8, 5), (8, 6), (8, 7), (9, 5), (9, 6)] {
+            assert_eq!(grid.get(x, y).unwrap(), &snow, "{x}, {y}");
+        }
+        // the rest of the overhang, the lone pixel and the clump all fell
+        assert_eq!(overhang(&grid, &snow), 5);
+        // and the part on the ledge didn't slide off
+        assert!((0..8).all(|x| grid.get(x, 7).unwrap() == &snow));
+
+        // plain sand can't overhang at all
+        let grid = run(layer(&sand()));
+        assert_eq!(overhang(&grid, &sand()), 0);
+    }
+
+    #[test]
+    fn narrow_funnel_packs_densely() {
+        let registries = Registries {
+            materials: material::init_material_types(),
+            ..Registries::empty()
+        };
+        let snow = SNOW.instance(PhysicsType::Sand, Color::rgb(240, 245, 255));
+
+        // a one pixel wide neck with a 3 wide mouth, plugged by a clump of snow wedged in the top
+        //   of the neck and some loose sand further down
+        let funnel = || {
+            let mut grid = SimulationGrid::new(3, 24);
+            for y in 1..24 {
+                grid.set(0, y, stone()).unwrap();
+                grid.set(2, y, stone()).unwrap();
+            }
+            for (x, y) in [(0, 0), (1, 0), (2, 0), (1, 1)] {
+                grid.set(x, y, snow.clone()).unwrap();
+            }
+            for y in (3..18).step_by(3) {
+                grid.set(1, y, sand()).unwrap();
+            }
+            grid
+        };
+        let run = |mut grid: SimulationGrid, narrow_gaps: bool| {
+            for seed in 0..300 {
+                grid = Simulator::simulate_grid(
+                    grid,
+                    &registries,
+                    seed,
+                    SimRules { narrow_gaps, ..SimRules::default() },
+                );
+            }
+            grid
+        };
+        let count = |grid: &SimulationGrid, mat: &MaterialInstance| {
+            (0..24)
+                .flat_map(|y| (0..3).map(move |x| (x, y)))
+                .filter(|&(x, y)| grid.get(x, y).unwrap().material_id == mat.material_id)
+                .count()
+        };
+
+        let grid = run(funnel(), true);
+        // nothing scattered, stuck or got lost on the way down
+        assert!(grid.particles.is_empty());
+        assert_eq!(count(&grid, &snow), 4);
+        assert_eq!(count(&grid, &sand()), 5);
+        // and it all ended up packed at the bottom of the neck with no gaps
+        for y in 0..15 {
+            assert_eq!(grid.get(1, y).unwrap().physics, PhysicsType::Air, "{y}");
+        }
+        for y in 15..24 {
+            assert_eq!(grid.get(1, y).unwrap().physics, PhysicsType::Sand, "{y}");
+        }
+
+        // without it the snow stays wedged in the neck
+        let grid = run(funnel(), false);
+        assert_eq!(grid.get(1, 1).unwrap(), &snow);
+    }
+
+    #[test]
+    fn water_freezes_into_ice() {
+        let registries = Registries {
+            materials: material::init_material_types(),
+            ..Registries::empty()
+        };
+
+        // a puddle on the floor of the grid
+        let mut grid = SimulationGrid::new(4, 2);
+        for x in 0..4 {
+            grid.set(
+                x,
+                1,
+                WATER.instance(PhysicsType::Liquid, Color::rgba(48, 96, 255, 200)),
+            )
+            .unwrap();
+        }
+
+        let mut seed = 0;
+        while (0..4).any(|x| grid.get(x, 1).unwrap().physics == PhysicsType::Liquid) {
+            assert!(seed < 100_000, "the puddle never froze");
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            seed += 1;
+        }
+        assert!((0..4).all(|x| grid.get(x, 1).unwrap().material_id == *ICE));
+
+        // unless the world's rules turn freezing off
+        let water = WATER.instance(PhysicsType::Liquid, Color::rgba(48, 96, 255, 200));
+        assert!(PixelLookups::new(&registries, SimRules::default())
+            .freeze(&water)
+            .is_some());
+        assert!(PixelLookups::new(
+            &registries,
+            SimRules { freezing: false, ..SimRules::default() }
+        )
+        .freeze(&water)
+        .is_none());
+    }
+
+    #[test]
+    fn spring_fills_well_and_drain_empties_it() {
+        let mut registries = Registries {
+            materials: material::init_material_types(),
+            ..Registries::empty()
+        };
+        // water standing in the well would slowly freeze and throw off the counts
+        registries.materials.register(
+            WATER.clone(),
+            Material {
+                display_name: "Water".to_string(),
+                ..Material::default()
+            },
+        );
+        let liquid = |grid: &SimulationGrid| {
+            (0..14)
+                .filter(|&y| grid.get(0, y).unwrap().physics == PhysicsType::Liquid)
+                .count()
+        };
+
+        // a spring over a 12 pixel deep well, boxed in by the grid's walls
+        let mut grid = SimulationGrid::new(1, 14);
+        grid.set(
+            0,
+            0,
+            SPRING.instance(PhysicsType::Solid, Color::rgb(60, 90, 160)),
+        )
+        .unwrap();
+        grid.set(0, 13, stone()).unwrap();
+
+        let mut seed = 0;
+        let mut run = |mut grid: SimulationGrid, ticks: u64| {
+            for _ in 0..ticks {
+                grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+                seed += 1;
+            }
+            grid
+        };
+
+        grid = run(grid, 10);
+        let partly = liquid(&grid);
+        assert!(partly > 0 && partly < 12, "{partly} pixels after 10 ticks");
+
+        // once it's full the spring has nowhere to put more
+        grid = run(grid, 200);
+        assert_eq!(liquid(&grid), 12);
+        grid = run(grid, 100);
+        assert_eq!(liquid(&grid), 12);
+        assert_eq!(grid.get(0, 0).unwrap().material_id, *SPRING);
+        assert!(grid.particles.is_empty());
+
+        // turn off the spring and open a drain at the bottom
+        grid.set(0, 0, stone()).unwrap();
+        grid.set(
+            0,
+            13,
+            DRAIN.instance(PhysicsType::Solid, Color::rgb(30, 30, 30)),
+        )
+        .unwrap();
+        grid = run(grid, 100);
+        assert_eq!(liquid(&grid), 0);
+        assert_eq!(grid.get(0, 13).unwrap().material_id, *DRAIN);
+    }
+
+    #[test]
+    fn punctured_column_jets_sideways() {
+        let registries = Registries::empty();
+        // a 2 pixel wide, 20 pixel tall column of liquid held in by a stone wall on the right,
+        //   with a hole punched through the wall at `hole`
+        let punctured = |hole: i32| {
+            let mut grid = SimulationGrid::new(8, 20);
+            for y in 0..20 {
+                grid.set(0, y, water()).unwrap();
+                grid.set(1, y, water()).unwrap();
+                grid.set(2, y, stone()).unwrap();
+            }
+            grid.set(2, hole, MaterialInstance::air()).unwrap();
+            grid
+        };
+
+        let jet_speed = |hole: i32| {
+            let next =
+                Simulator::simulate_grid(punctured(hole), &registries, 0, SimRules::default());
+            assert!(!next.particles.is_empty(), "hole at {hole}");
+            for p in &next.particles {
+                #[allow(clippy::float_cmp)]
+                let at_hole = p.pos.y == f64::from(hole);
+                assert!(at_hole, "{:?}", p.pos);
+                // out through the hole, not up or down
+                let sideways = p.vel.x > 0.0 && p.vel.x > p.vel.y.abs() * 4.0;
+                assert!(sideways, "{:?}", p.vel);
+            }
+            next.particles[0].vel.x
+        };
+
+        // deeper holes are under more pressure
+        assert!(jet_speed(18) > jet_speed(8));
+
+        // not enough liquid above to push anything out
+        let next = Simulator::simulate_grid(punctured(2), &registries, 0, SimRules::default());
+        assert!(next.particles.is_empty());
+
+        // a hole filled with a custom empty material is still a hole
+        let hole_id: RegistryID<Material> = "hole".into();
+        let mut materials = material::init_material_types();
+        materials.register(
+            hole_id.clone(),
+            Material { empty: true, ..Material::default() },
+        );
+        let registries = Registries { materials, ..Registries::empty() };
+        let mut grid = punctured(18);
+        grid.set(
+            2,
+            18,
+            hole_id.instance(PhysicsType::Solid, Color::TRANSPARENT),
+        )
+        .unwrap();
+        let next = Simulator::simulate_grid(grid, &registries, 0, SimRules::default());
+        assert!(!next.particles.is_empty());
+    }
+
+    #[test]
+    fn resting_liquid_levels_out() {
+        let registries = Registries::empty();
+        // a short column of liquid in the corner of an empty floor
+        let mut grid = SimulationGrid::new(12, 4);
+        for y in 1..4 {
+            grid.set(0, y, water()).unwrap();
+        }
+
+        for seed in 0..200 {
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+        }
+
+        // it spread out into a single layer on the floor without losing any
+        let liquid = |y: i32| {
+            (0..12)
+                .filter(|&x| grid.get(x, y).unwrap().physics == PhysicsType::Liquid)
+                .count()
+        };
+        assert_eq!(liquid(3), 3);
+        assert!((0..3).all(|y| liquid(y) == 0));
+        assert!(grid.particles.is_empty());
+    }
+
+    #[test]
+    fn lone_droplet_comes_to_rest() {
+        let registries = Registries::empty();
+        let mut grid = SimulationGrid::new(12, 4);
+        grid.set(5, 3, water()).unwrap();
+
+        for seed in 0..20 {
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            assert_eq!(grid.get(5, 3).unwrap().physics, PhysicsType::Liquid);
+        }
+    }
+
+    #[test]
+    fn landing_liquid_settles_with_velocity() {
+        let registries = Registries::empty();
+        // a drop of liquid in a 1 pixel wide shaft `height` pixels tall
+        let land = |height: u16| {
+            let mut grid = SimulationGrid::new(1, height);
+            grid.set(0, 0, water()).unwrap();
+            for seed in 0..u64::from(height) {
+                grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            }
+            assert_eq!(grid.settled.len(), 1, "{:?}", grid.settled);
+            let event = &grid.settled[0];
+            assert_eq!((event.x, event.y), (0, i64::from(height) - 1));
+            event.velocity
+        };
+
+        // well short of splashing, but a longer fall still lands harder
+        assert!(land(12) > land(2));
+    }
+
+    #[test]
+    fn pool_freezes_from_the_surface() {
+        let mut registries = Registries::empty();
+        registries.materials.register(
+            TEST.clone(),
+            Material {
+                display_name: "Test".to_string(),
+                freeze: Some(Freeze { into: stone(), rate: 0.5 }),
+                ..Material::default()
+            },
+        );
+
+        // a 16x16 pool with some air above it
+        let mut grid = SimulationGrid::new(16, 18);
+        for y in 2..18 {
+            for x in 0..16 {
+                grid.set(x, y, water()).unwrap();
+            }
+        }
+        let frozen = |grid: &SimulationGrid, x: i32, y: i32| {
+            grid.get(x, y).unwrap().physics == PhysicsType::Solid
+        };
+
+        let mut seed = 0;
+        while (0..16).filter(|&x| frozen(&grid, x, 2)).count() < 8 {
+            assert!(seed < 1000, "the surface never froze");
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            seed += 1;
+        }
+
+        // half the surface is ice, but the middle of the pool hasn't started freezing
+        for y in 8..12 {
+            for x in 6..10 {
+                assert!(!frozen(&grid, x, y), "{x}, {y} froze after {seed} ticks");
+            }
+        }
+
+        // the crust keeps growing until the whole pool is ice
+        for _ in 0..2000 {
+            grid = Simulator::simulate_grid(grid, &registries, seed, SimRules::default());
+            seed += 1;
+        }
+        assert!((2..18).all(|y| (0..16).all(|x| frozen(&grid, x, y))));
+        // nothing above the pool to freeze
+        assert!((0..16).all(|x| grid.get(x, 1).unwrap().is_empty(&registries.materials)));
+    }
+
+    #[test]
+    fn cardinal_spread_piles_steeper() {
+        // pours sand onto the middle of the floor one pixel at a time, just above the top of the
+        //   pile so nothing falls far enough to turn into a particle
+        let pile = |registries: &Registries, default: SandSpread| {
+            let mut grid = SimulationGrid::new(41, 30);
+            let mut poured = 0;
+            for seed in 0..1500 {
+                if poured < 80 && seed % 4 == 0 {
+                    let top = (0..30)
+                        .find(|&y| !grid.get(20, y).unwrap().is_empty(&registries.materials))
+                        .unwrap_or(30);
+                    if top > 0 {
+                        grid.set(20, top - 1, sand()).unwrap();
+                        poured += 1;
+                    }
+                }
+                grid = Simulator::simulate_grid(
+                    grid,
+                    registries,
+                    seed,
+                    SimRules { sand_spread: default, ..SimRules::default() },
+                );
+            }
+            assert_eq!(poured, 80);
+            grid
+        };
+        // how tall the pile is in each column
+        let heights = |grid: &SimulationGrid, registries: &Registries| {
+            (0..41)
+                .map(|x| {
+                    (0..30)
+                        .filter(|&y| !grid.get(x, y).unwrap().is_empty(&registries.materials))
+                        .count()
+                })
+                .collect::<Vec<_>>()
+        };
+        let check = |grid: SimulationGrid,
+                     registries: &Registries,
+                     default: SandSpread,
+                     max_step: usize| {
+            // no pixels lost or turned into particles on the way
+            assert!(grid.particles.is_empty());
+            let h = heights(&grid, registries);
+            assert_eq!(h.iter().sum::<usize>(), 80);
+            // neighboring columns are never further apart than the mode allows
+            assert!(
+                h.windows(2).all(|w| w[0].abs_diff(w[1]) <= max_step),
+                "{h:?}"
+            );
+            // and it's settled, nothing moves anymore
+            let mut next = grid;
+            for seed in 0..50 {
+                next = Simulator::simulate_grid(
+                    next,
+                    registries,
+                    seed,
+                    SimRules { sand_spread: default, ..SimRules::default() },
+                );
+            }
+            assert_eq!(heights(&next, registries), h);
+            h
+        };
+        let width = |h: &[usize]| h.iter().filter(|&&h| h > 0).count();
+
+        let registries = Registries::empty();
+        let run = |registries: &Registries, default: SandSpread, max_step: usize| {
+            check(pile(registries, default), registries, default, max_step)
+        };
+        let diagonal = run(&registries, SandSpread::Diagonal, 1);
+        let cardinal = run(&registries, SandSpread::Cardinal, 2);
+        assert!(cardinal[20] > diagonal[20], "{cardinal:?} {diagonal:?}");
+        assert!(
+            width(&cardinal) < width(&diagonal),
+            "{cardinal:?} {diagonal:?}"
+        );
+
+        // a material can pick its own, whatever the default is
+        let mut registries = Registries::empty();
+        registries.materials.register(
+            TEST.clone(),
+            Material {
+                display_name: "Test".to_string(),
+                sand_spread: Some(SandSpread::Cardinal),
+                ..Material::default()
+            },
+        );
+        let own = run(&registries, SandSpread::Diagonal, 2);
+        assert!(own[20] > diagonal[20], "{own:?} {diagonal:?}");
+    }
+
+    #[cfg(feature = "checked_simulation")]
+    #[test]
+    #[should_panic(expected = "simulation local position out of bounds")]
+    fn checked_simulation_catches_out_of_range_access() {
+        use std::cell::UnsafeCell;
+
+        use crate::game::common::world::{CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH};
+
+        use super::{SimulationHelperChunk, SimulatorChunkContext};
+
+        fn cells<T: Clone>(v: T) -> Box<[UnsafeCell<T>; CHUNK_AREA]> {
+            let cells: Vec<_> = (0..CHUNK_AREA)
+                .map(|_| UnsafeCell::new(v.clone()))
+                .collect();
+            cells.into_boxed_slice().try_into().ok().unwrap()
+        }
+
+        let pixels: Vec<_> = (0..9).map(|_| cells(MaterialInstance::air())).collect();
+        let colors: Vec<_> = (0..9).map(|_| cells(Color::rgba(0, 0, 0, 0))).collect();
+        let lights: Vec<_> = (0..9).map(|_| cells([0.0; 4])).collect();
+        let mut chunk_data = std::array::from_fn(|i| SimulatorChunkContext {
+            pixels: &pixels[i],
+            colors: &colors[i],
+            lights: &lights[i],
+            flow: None,
+            dirty: false,
+            dirty_rect: None,
+        });
+        let mut particles = vec![];
+        let mut settled = vec![];
+        let helper = SimulationHelperChunk {
+            chunk_data: &mut chunk_data,
+            min_x: [CHUNK_WIDTH + 1; 9],
+            min_y: [CHUNK_HEIGHT + 1; 9],
+            max_x: [0; 9],
+            max_y: [0; 9],
+            particles: &mut particles,
+            settled: &mut settled,
+            chunk_x: 0,
+            chunk_y: 0,
+        };
+
+        let (w, h) = (i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+        // the corner of the 3x3 chunks is fine
+        let registries = Registries::empty();
+        assert!(unsafe { helper.pixel_local_unchecked(-w, -h) }.is_empty(&registries.materials));
+        // one chunk past the right edge, which unchecked would wrap around into the left column
+        unsafe { helper.pixel_local_unchecked(w * 2, 0) };
+    }
+
+    /// Size of the chunks for checking neighborhood indexing, wider than they are tall.
+    const RECT_W: u16 = 12;
+    const RECT_H: u16 = 6;
+
+    #[test]
+    fn neighborhood_indices_rectangular() {
+        let (w, h) = (i32::from(RECT_W), i32::from(RECT_H));
+        let width = usize::from(RECT_W);
+        let bottom_right = (width - 1) + (usize::from(RECT_H) - 1) * width;
+
+        assert_eq!(neighborhood_indices(0, 0, RECT_W, RECT_H), (4, 0, 0, 0));
+        assert_eq!(
+            neighborhood_indices(w - 1, h - 1, RECT_W, RECT_H),
+            (4, bottom_right, RECT_W - 1, RECT_H - 1)
+        );
+        assert_eq!(
+            neighborhood_indices(-1, -1, RECT_W, RECT_H),
+            (0, bottom_right, RECT_W - 1, RECT_H - 1)
+        );
+        assert_eq!(
+            neighborhood_indices(3, -1, RECT_W, RECT_H),
+            (1, 3 + (usize::from(RECT_H) - 1) * width, 3, RECT_H - 1)
+        );
+        // one row past the bottom is the chunk below, not a row further along in the center chunk
+        assert_eq!(neighborhood_indices(0, h, RECT_W, RECT_H), (7, 0, 0, 0));
+        assert_eq!(
+            neighborhood_indices(w, 2, RECT_W, RECT_H),
+            (5, 2 * width, 0, 2)
+        );
+        assert_eq!(
+            neighborhood_indices(-1, 2, RECT_W, RECT_H),
+            (3, (width - 1) + 2 * width, RECT_W - 1, 2)
+        );
+        assert_eq!(neighborhood_indices(w, h, RECT_W, RECT_H), (8, 0, 0, 0));
+        assert_eq!(
+            neighborhood_indices(w * 2 - 1, h * 2 - 1, RECT_W, RECT_H),
+            (8, bottom_right, RECT_W - 1, RECT_H - 1)
+        );
+    }
 }
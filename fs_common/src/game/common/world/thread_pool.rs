@@ -0,0 +1,87 @@
+use std::fmt::Debug;
+
+/// A rayon thread pool with a configurable number of threads, rebuilt when that changes.
+///
+/// Chunk simulation and particles each get their own so they don't compete for threads,
+///   see [`Settings::simulation_threads`](crate::game::common::Settings::simulation_threads)
+///   and [`Settings::particle_threads`](crate::game::common::Settings::particle_threads).
+pub struct SizedThreadPool {
+    name: &'static str,
+    threads: usize,
+    pool: rayon::ThreadPool,
+}
+
+impl SizedThreadPool {
+    /// `threads` of 0 uses rayon's default (one per cpu).
+    pub fn new(name: &'static str, threads: usize) -> Result<Self, String> {
+        Ok(Self { name, threads, pool: Self::build(name, threads)? })
+    }
+
+    fn build(name: &'static str, threads: usize) -> Result<rayon::ThreadPool, String> {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(move |i| format!("{name} {i}"))
+            .build()
+            .map_err(|e| format!("Failed to build {name} thread pool: {e}"))
+    }
+
+    /// The configured number of threads, 0 meaning rayon's default.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// How many threads the pool actually has.
+    pub fn current_num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Rebuilds the pool if `threads` is different from before.
+    pub fn resize(&mut self, threads: usize) -> Result<(), String> {
+        if threads != self.threads {
+            self.pool = Self::build(self.name, threads)?;
+            self.threads = threads;
+        }
+        Ok(())
+    }
+
+    /// Runs `op` in the pool, so any rayon parallel iterators in it use the pool's threads.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(op)
+    }
+
+    /// Runs `op` in the background on one of the pool's threads, without waiting for it.
+    pub fn spawn(&self, op: impl FnOnce() + Send + 'static) {
+        self.pool.spawn(op);
+    }
+}
+
+impl Debug for SizedThreadPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SizedThreadPool")
+            .field("name", &self.name)
+            .field("threads", &self.threads)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+    use super::SizedThreadPool;
+
+    #[test]
+    fn configured_pool_runs_work() {
+        let mut pool = SizedThreadPool::new("test", 3).unwrap();
+        assert_eq!(pool.current_num_threads(), 3);
+
+        let sum: u64 = pool.install(|| (0..10_000_u64).into_par_iter().map(|i| i * 2).sum());
+        assert_eq!(sum, 9_999 * 10_000);
+        assert!(pool.install(rayon::current_thread_index).is_some());
+
+        pool.resize(2).unwrap();
+        assert_eq!(pool.threads(), 2);
+        assert_eq!(pool.current_num_threads(), 2);
+        assert_eq!(pool.install(rayon::current_num_threads), 2);
+    }
+}
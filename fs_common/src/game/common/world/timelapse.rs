@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+use chunksystem::ChunkQuery;
+use image::RgbaImage;
+
+use super::{
+    chunk_handler::ChunkHandler, pixel_to_chunk_pos, thread_pool::SizedThreadPool, Chunk,
+    CHUNK_HEIGHT, CHUNK_WIDTH,
+};
+use crate::game::common::Rect;
+
+/// The colors of every pixel in `region` (in world pixels, `x2`/`y2` excluded).
+///
+/// Pixels in chunks that aren't loaded or generated yet are transparent.
+pub fn export_region<C: Chunk>(chunk_handler: &ChunkHandler<C>, region: Rect<i32>) -> RgbaImage {
+    let width = u32::try_from(region.width()).unwrap_or(0);
+    let height = u32::try_from(region.height()).unwrap_or(0);
+    let mut image = RgbaImage::new(width, height);
+    if width == 0 || height == 0 {
+        return image;
+    }
+
+    let (w, h) = (i32::from(CHUNK_WIDTH), i32::from(CHUNK_HEIGHT));
+    let (cx1, cy1) = pixel_to_chunk_pos(region.x1.into(), region.y1.into());
+    let (cx2, cy2) = pixel_to_chunk_pos((region.x2 - 1).into(), (region.y2 - 1).into());
+    for cy in cy1..=cy2 {
+        for cx in cx1..=cx2 {
+            let Some(chunk) = chunk_handler
+                .manager
+                .chunk_at((cx, cy))
+                .filter(|c| c.pixels().is_some())
+            else {
+                continue;
+            };
+            let colors = chunk.colors();
+
+            let x_range = (cx * w).max(region.x1)..((cx + 1) * w).min(region.x2);
+            for y in (cy * h).max(region.y1)..((cy + 1) * h).min(region.y2) {
+                for x in x_range.clone() {
+                    let c = colors[((x - cx * w) + (y - cy * h) * w) as usize];
+                    image.put_pixel(
+                        (x - region.x1) as u32,
+                        (y - region.y1) as u32,
+                        image::Rgba([c.r, c.g, c.b, c.a]),
+                    );
+                }
+            }
+        }
+    }
+
+    image
+}
+
+/// Periodically exports a region of the world to numbered PNGs, for assembling into a timelapse.
+///
+/// Only copying the pixels happens on the calling thread, encoding and writing each frame is done
+///   in the background so the simulation doesn't wait on the disk.
+#[derive(Debug)]
+pub struct Timelapse {
+    pub dir: PathBuf,
+    next_frame: u32,
+    last_capture: Option<u32>,
+    writer: SizedThreadPool,
+}
+
+impl Timelapse {
+    pub fn new(dir: PathBuf) -> Result<Self, String> {
+        Ok(Self {
+            dir,
+            next_frame: 0,
+            last_capture: None,
+            // one thread, so frames are written in order
+            writer: SizedThreadPool::new("Timelapse", 1)?,
+        })
+    }
+
+    /// Where frame number `frame` is written to, zero padded so the files sort in order.
+    pub fn frame_path(dir: &Path, frame: u32) -> PathBuf {
+        dir.join(format!("frame_{frame:06}.png"))
+    }
+
+    /// If a frame is due at `tick_time`, returns the path it should be written to and moves on to
+    ///   the next one.
+    ///
+    /// The first call always captures, after that one every `interval` ticks.
+    pub fn schedule(&mut self, tick_time: u32, interval: u32) -> Option<PathBuf> {
+        let due = self
+            .last_capture
+            .is_none_or(|last| tick_time.saturating_sub(last) >= interval.max(1));
+        if !due {
+            return None;
+        }
+
+        self.last_capture = Some(tick_time);
+        let path = Self::frame_path(&self.dir, self.next_frame);
+        self.next_frame += 1;
+        Some(path)
+    }
+
+    /// Exports `region` if a frame is due, see [`Timelapse::schedule`]. Returns whether it did.
+    pub fn capture<C: Chunk>(
+        &mut self,
+        chunk_handler: &ChunkHandler<C>,
+        tick_time: u32,
+        interval: u32,
+        region: Rect<i32>,
+    ) -> bool {
+        let Some(path) = self.schedule(tick_time, interval) else {
+            return false;
+        };
+
+        let image = export_region(chunk_handler, region);
+        let dir = self.dir.clone();
+        self.writer.spawn(move || {
+            let res = std::fs::create_dir_all(&dir)
+                .map_err(|e| e.to_string())
+                .and_then(|()| image.save(&path).map_err(|e| e.to_string()));
+            if let Err(e) = res {
+                log::error!("Failed to write timelapse frame @ {path:?}: {e}");
+            }
+        });
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::Timelapse;
+
+    #[test]
+    fn frames_every_interval() {
+        let dir = Path::new("timelapse");
+        let mut timelapse = Timelapse::new(dir.into()).unwrap();
+
+        // starting part way into the world, 10 ticks apart
+        let frames: Vec<(u32, PathBuf)> = (100..=145)
+            .filter_map(|t| timelapse.schedule(t, 10).map(|p| (t, p)))
+            .collect();
+        assert_eq!(
+            frames,
+            vec![
+                (100, dir.join("frame_000000.png")),
+                (110, dir.join("frame_000001.png")),
+                (120, dir.join("frame_000002.png")),
+                (130, dir.join("frame_000003.png")),
+                (140, dir.join("frame_000004.png")),
+            ]
+        );
+
+        // a tick that's skipped over (like when the game lags) still gives the next frame
+        assert_eq!(timelapse.schedule(147, 10), None);
+        assert_eq!(
+            timelapse.schedule(153, 10),
+            Some(dir.join("frame_000005.png"))
+        );
+
+        // an interval of 0 captures every tick instead of dividing by 0
+        assert_eq!(
+            timelapse.schedule(154, 0),
+            Some(dir.join("frame_000006.png"))
+        );
+        assert_eq!(
+            Timelapse::frame_path(dir, 123_456),
+            dir.join("frame_123456.png")
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
     hash::{Hash, Hasher},
     path::PathBuf,
     sync::Arc,
@@ -7,41 +7,58 @@ use std::{
 };
 
 use crate::game::common::{
+    registry::RegistryID,
     world::{physics::PHYSICS_SCALE, ChunkRigidBodyState},
-    FileHelper, Registries, Settings,
+    FileHelper, Rect, Registries, Settings,
 };
 
-use chunksystem::ChunkQuery;
+use chunksystem::{ChunkKey, ChunkQuery};
 use rapier2d::{
-    na::{Point2, Vector2},
-    prelude::{ColliderBuilder, InteractionGroups, RigidBodyBuilder, RigidBodyType},
+    na::{Isometry2, Point2, Vector2},
+    prelude::{ColliderBuilder, RigidBodyBuilder, RigidBodyType},
 };
 // use salva2d::{integrations::rapier::ColliderSampling, object::Boundary};
 use specs::{
     saveload::{SimpleMarker, SimpleMarkerAllocator},
-    Join, Read, ReadStorage, RunNow, WorldExt,
+    Entities, Entity, Join, Read, ReadStorage, RunNow, WorldExt, WriteStorage,
 };
 
 use super::{
+    buoyancy,
     chunk_access::FSChunkAccess,
     chunk_data::SidedChunkData,
     chunk_handler::{ChunkHandler, ChunkTickContext},
+    chunk_index::ChunkLocalPosition,
+    destroy::destroy_pixel,
+    edit::EDIT_REACH,
+    edit_events::{WorldEditCause, WorldEditEvents},
     entity::{
-        CollisionDetector, GameEntity, Hitbox, Persistent, PhysicsEntity, Player,
-        UpdatePhysicsEntities,
+        CollisionDetector, GameEntity, Hitbox, Inventory, Persistent, PhysicsEntity, Player,
+        Sprite, UpdatePhysicsEntities,
+    },
+    gen::structure::StructureNode,
+    journal::{self, JournalEntry},
+    material::{
+        self, buf::MaterialBuf, color::Color, remap::MaterialRemap, Material, MaterialInstance,
+        MaterialRegistry, PhysicsType,
     },
-    gen::{biome_test::BiomeTestGenerator, structure::StructureNode},
-    material::{self, color::Color, MaterialInstance, PhysicsType},
     particle::{Particle, ParticleSystem, UpdateParticles},
-    physics::Physics,
+    physics::{Impact, Physics, IMPACT_BASE_RADIUS, IMPACT_MAX_RADIUS, IMPACT_MIN_IMPULSE},
+    pixel_to_chunk_pos,
     rigidbody::FSRigidBody,
+    settle_events::SettleEvents,
     simulator,
+    thread_pool::SizedThreadPool,
     tile_entity::TileEntitySided,
-    ApplyRigidBodies, AutoTarget, Camera, Chunk, CollisionFlags, DeltaTime, FilePersistent, Loader,
-    Position, RigidBodyComponent, SidedChunk, TickTime, UpdateAutoTargets, UpdateRigidBodies,
-    Velocity, CHUNK_SIZE,
+    ApplyRigidBodies, AutoTarget, Camera, Chunk, ChunkState, CollisionLayers, DeltaTime,
+    FilePersistent, Loader, Position, RigidBodyComponent, SidedChunk, Target, TickTime,
+    UpdateAutoTargets, UpdateRigidBodies, Velocity, WorldMeta, WorldPreset, WorldRules, CHUNK_AREA,
+    CHUNK_HEIGHT, CHUNK_WIDTH,
 };
 
+/// How many chunks around the destination [`World::teleport`] generates right away.
+pub const TELEPORT_GENERATE_RADIUS: u16 = 3;
+
 #[derive(Debug)]
 pub enum WorldNetworkMode {
     Local,
@@ -56,6 +73,12 @@ pub struct World<C: Chunk> {
     pub rigidbodies: Vec<FSRigidBody>,
     pub physics: Physics,
     pub seed: i32,
+    /// Loaded from `world_info.toml`, see [`World::apply_rules`].
+    pub rules: WorldRules,
+    /// What [`World::chunk_handler`] generates new chunks with, saved in `world_info.toml`.
+    pub preset: WorldPreset,
+    /// Runs particles, so they don't compete with chunk simulation for threads.
+    pub particle_pool: SizedThreadPool,
 }
 
 pub fn ecs() -> specs::World {
@@ -65,13 +88,17 @@ pub fn ecs() -> specs::World {
     ecs.insert(DeltaTime(Duration::from_millis(1)));
     ecs.insert(TickTime(0));
     ecs.insert(ParticleSystem::default());
+    ecs.insert(WorldEditEvents::default());
+    ecs.insert(SettleEvents::default());
     ecs.register::<Position>();
     ecs.register::<Velocity>();
     ecs.register::<GameEntity>();
     ecs.register::<Loader>();
     ecs.register::<Player>();
+    ecs.register::<Inventory>();
     ecs.register::<PhysicsEntity>();
     ecs.register::<Hitbox>();
+    ecs.register::<Sprite>();
     ecs.register::<AutoTarget>();
     ecs.register::<Camera>();
     ecs.register::<Persistent>();
@@ -82,18 +109,46 @@ pub fn ecs() -> specs::World {
 }
 
 impl<C: Chunk + Send + Sync + 'static> World<C> {
-    #[profiling::function]
     pub fn create(path: Option<PathBuf>, seed: Option<i32>) -> Self {
+        Self::create_with_preset(path, seed, WorldPreset::default())
+    }
+
+    /// Like [`World::create`], but new worlds start out as `preset`.
+    ///
+    /// Worlds that already have a `world_info.toml` use the preset saved in it instead,
+    ///   so their new chunks keep matching the old ones. New worlds with a `path` get a
+    ///   `world_info.toml` right away.
+    #[profiling::function]
+    pub fn create_with_preset(
+        path: Option<PathBuf>,
+        seed: Option<i32>,
+        mut preset: WorldPreset,
+    ) -> Self {
         let mut ecs = ecs();
+        let mut rules = WorldRules::default();
 
         if let Some(path) = &path {
+            let meta_path = path.join("world_info.toml");
+            if meta_path.exists() {
+                match Self::parse_file_meta(&meta_path) {
+                    Ok(meta) => {
+                        rules = meta.rules;
+                        if let Some(saved) = meta.preset {
+                            preset = saved;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Failed to read world meta @ {:?}: {:?}", meta_path, e);
+                    },
+                }
+            }
+
             let particles_path = path.join("particles.dat");
             if particles_path.exists() {
-                match std::fs::File::open(particles_path.clone()) {
-                    Ok(f) => {
-                        match bincode::deserialize_from(f) {
+                match std::fs::read(&particles_path) {
+                    Ok(data) => {
+                        match ParticleSystem::read_save(&data) {
                             Ok(ps) => {
-                                let ps: ParticleSystem = ps;
                                 *ecs.write_resource::<ParticleSystem>() = ps;
                             },
                             Err(e) => {
@@ -129,7 +184,7 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
 
         let mut w = World {
             ecs,
-            chunk_handler: ChunkHandler::new(BiomeTestGenerator::new(), path.clone()),
+            chunk_handler: preset.chunk_handler(path.clone()),
             path,
             net_mode: WorldNetworkMode::Local,
             rigidbodies: Vec::new(),
@@ -143,7 +198,16 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
                     .hash(&mut h);
                 h.finish() as i32
             }),
+            rules,
+            preset,
+            particle_pool: SizedThreadPool::new("Particles", 0)
+                .expect("Failed to build particle_pool"),
         };
+        w.apply_rules();
+
+        if let Err(e) = w.write_meta() {
+            log::error!("Failed to write world meta: {e}");
+        }
 
         // sample rigidbodies
         {
@@ -274,15 +338,62 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
         Ok(())
     }
 
+    /// Applies [`World::rules`] to the simulation, call this after changing them.
+    pub fn apply_rules(&mut self) {
+        self.physics.gravity = Vector2::y() * self.rules.gravity;
+        self.chunk_handler.border = self.rules.world_border();
+        self.chunk_handler.freezing = self.rules.freezing;
+    }
+
+    /// Writes the rules and preset to `world_info.toml`, creating it (named after the folder) for new worlds.
+    fn write_meta(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let meta_path = path.join("world_info.toml");
+        let existing = if meta_path.exists() {
+            Self::parse_file_meta(&meta_path)
+                .map_err(|e| {
+                    log::error!(
+                        "Failed to read world meta @ {:?}, writing a new one: {:?}",
+                        meta_path,
+                        e
+                    );
+                })
+                .ok()
+        } else {
+            std::fs::create_dir_all(path)?;
+            None
+        };
+
+        let meta = if let Some(mut meta) = existing {
+            meta.rules = self.rules.clone();
+            meta.preset = Some(self.preset.clone());
+            meta
+        } else {
+            let name = path
+                .file_name()
+                .map_or_else(|| "World".to_string(), |n| n.to_string_lossy().into_owned());
+            WorldMeta::new(name, self.rules.clone(), self.preset.clone())
+        };
+        Self::write_file_meta(&meta_path, &meta)
+    }
+
+    /// Saves the meta, particles and every loaded chunk.
+    ///
+    /// Failing to write the meta or the particles is logged rather than returned, so it never
+    ///   keeps the chunks from being saved.
     pub fn save(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Err(e) = self.write_meta() {
+            log::error!("Failed to write world meta: {:?}", e);
+        }
         if let Some(path) = &self.path {
             let particles_path = path.join("particles.dat");
 
             match std::fs::File::create(particles_path.clone()) {
                 Ok(f) => {
-                    if let Err(e) =
-                        bincode::serialize_into(f, &*self.ecs.read_resource::<ParticleSystem>())
-                    {
+                    if let Err(e) = self.ecs.read_resource::<ParticleSystem>().write_save(f) {
                         log::error!(
                             "Failed to write particles to file @ {:?}: {:?}",
                             particles_path,
@@ -301,17 +412,30 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
         }
 
         self.chunk_handler.save_all_chunks()?;
+        // everything in the journal is in the chunk files now
+        journal::truncate(&mut self.chunk_handler)?;
 
         Ok(())
     }
 
     #[profiling::function]
-    pub fn tick_physics(&mut self, settings: &Settings) {
+    pub fn tick_physics(&mut self, settings: &Settings, registries: &Registries) {
         // need to do this here since 'self' isn't mut in render
 
         let mut update_bodies = UpdateRigidBodies { physics: &mut self.physics };
         update_bodies.run_now(&self.ecs);
 
+        self.physics.body_mesh_tolerance = settings.rigidbody_mesh_tolerance;
+        self.physics.body_passable.clear();
+        if settings.rigidbody_passable_materials {
+            self.physics.body_passable.extend(
+                (&registries.materials)
+                    .into_iter()
+                    .filter(|(_, m)| !m.entity_collidable)
+                    .map(|(id, _)| id.clone()),
+            );
+        }
+
         let time_step = settings.tick_physics_timestep;
         // match self.net_mode {
         //     WorldNetworkMode::Local => {
@@ -323,72 +447,731 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
         //     WorldNetworkMode::Remote => {},
         // }
 
-        self.physics.step(time_step / 3.0);
-        self.physics.step(time_step / 3.0);
-        self.physics.step(time_step / 3.0);
+        self.recycle_rigidbodies(registries);
+
+        buoyancy::apply_buoyancy(
+            &self.rigidbodies,
+            &mut self.physics,
+            &self.chunk_handler,
+            time_step,
+        );
+
+        let substeps = self.rules.physics_substeps.max(1);
+        for _ in 0..substeps {
+            self.physics.step(time_step / f32::from(substeps));
+        }
+
+        let impacts = self.physics.take_impacts();
+        if settings.destructive_impacts && self.rules.destructive_impacts {
+            self.apply_impacts(&impacts, registries);
+        }
 
         let mut apply_bodies = ApplyRigidBodies { physics: &mut self.physics };
         apply_bodies.run_now(&self.ecs);
     }
 
-    pub fn frame(&mut self, delta_time: Duration) {
-        *self.ecs.write_resource::<DeltaTime>() = DeltaTime(delta_time);
+    /// Breaks the terrain around hard impacts into particles.
+    ///
+    /// The force of an impact is its impulse relative to [`IMPACT_MIN_IMPULSE`], falling off to 0 at the edge of its radius.
+    /// Pixels break where that is more than their material's strength (see [`Material::strength`]).
+    fn apply_impacts(&mut self, impacts: &[Impact], registries: &Registries) {
+        if impacts.is_empty() {
+            return;
+        }
+        let tag_strengths = material::tag_strengths().resolve(&registries.materials);
+        let mut particles = Vec::new();
+        let mut broken_chunks = HashSet::new();
+
+        for impact in impacts {
+            let force = impact.impulse / IMPACT_MIN_IMPULSE;
+            let radius = (IMPACT_BASE_RADIUS * force.sqrt()).min(IMPACT_MAX_RADIUS);
+            let center_x = impact.point.x * PHYSICS_SCALE;
+            let center_y = impact.point.y * PHYSICS_SCALE;
+
+            let r = radius.ceil() as i64;
+            let (cx, cy) = (center_x.floor() as i64, center_y.floor() as i64);
+            let mut broken: Option<Rect<i64>> = None;
+            for y in cy - r..=cy + r {
+                for x in cx - r..=cx + r {
+                    let dx = x as f32 + 0.5 - center_x;
+                    let dy = y as f32 + 0.5 - center_y;
+                    let dist = (dx * dx + dy * dy).sqrt();
+                    if dist > radius {
+                        continue;
+                    }
 
-        let mut update_auto_targets = UpdateAutoTargets;
-        update_auto_targets.run_now(&self.ecs);
+                    let Ok(mat) = self.chunk_handler.pixel(x, y) else {
+                        continue;
+                    };
+                    let strength = registries
+                        .materials
+                        .get(&mat.material_id)
+                        .and_then(|m| m.strength)
+                        .or_else(|| tag_strengths.get(&mat.material_id).copied())
+                        .unwrap_or_else(|| mat.physics.default_strength());
+                    let local_force = force * (1.0 - dist / radius);
+                    if local_force <= strength {
+                        continue;
+                    }
+
+                    // thrown away from the impact and a bit upwards
+                    let (dir_x, dir_y) = if dist > 0.0 {
+                        (dx / dist, dy / dist)
+                    } else {
+                        (0.0, -1.0)
+                    };
+                    let speed = 1.0 + local_force.min(3.0);
+                    let vel = Velocity {
+                        x: f64::from(dir_x * speed),
+                        y: f64::from(dir_y * speed - 1.0),
+                    };
+
+                    if let Ok(Some(destroyed)) = destroy_pixel(
+                        &mut self.chunk_handler,
+                        x,
+                        y,
+                        &vel,
+                        registries,
+                        &mut particles,
+                    ) {
+                        if destroyed.material.physics == PhysicsType::Solid {
+                            broken_chunks.insert(pixel_to_chunk_pos(x, y));
+                        }
+                        let px = Rect::new(x, y, x + 1, y + 1);
+                        broken = Some(broken.map_or(px, |b| b.union(px)));
+
+                        // materials that don't shatter into anything else break up into loose debris
+                        if destroyed.particles == 0 {
+                            particles.push(Particle::new(
+                                MaterialInstance {
+                                    physics: PhysicsType::Sand,
+                                    ..destroyed.material
+                                },
+                                Position { x: x as f64, y: y as f64 },
+                                vel,
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if let Some(rect) = broken {
+                self.ecs
+                    .write_resource::<WorldEditEvents>()
+                    .emit(rect, WorldEditCause::Explosion);
+            }
+        }
+
+        self.ecs
+            .write_resource::<ParticleSystem>()
+            .active
+            .append(&mut particles);
+
+        for key in broken_chunks {
+            self.rebuild_chunk_collision(key);
+        }
     }
 
-    pub fn raycast(
-        &self,
-        mut x1: i64,
-        mut y1: i64,
-        x2: i64,
-        y2: i64,
-        collide_filder: fn((i64, i64), &MaterialInstance) -> bool,
-    ) -> Option<((i64, i64), &MaterialInstance)> {
-        let check_pixel = |x: i64, y: i64| {
-            let r = self.chunk_handler.pixel(x, y);
-            if let Ok(m) = r {
-                if m.physics != PhysicsType::Air {
-                    return Some(((x, y), m));
+    /// Applies pixels a client already changed with a tool, see
+    ///   [`PacketType::EditPixelsPacket`](crate::game::common::networking::PacketType::EditPixelsPacket).
+    ///
+    /// `player` is the entity of the connection that sent them, and only pixels it could have
+    ///   changed itself are applied: within [`EDIT_REACH`] of it, and either erased (set to an
+    ///   empty material, see [`MaterialInstance::is_empty`]) or set to the material of a placer in
+    ///   its hotbar (or [`Settings::brush_palette`] outside of survival).
+    ///   In survival this follows the paint tool: erasing collects the pixel into the player's
+    ///   [`Inventory`], and placing needs an empty pixel and takes one from it, so a player without
+    ///   an inventory can't change anything.
+    /// Anything else, or pixels in chunks that aren't loaded, are left as they are.
+    /// Returns every chunk the pixels are in, applied or not, which the client should be sent so its
+    ///   copy matches again.
+    pub fn apply_remote_edit(
+        &mut self,
+        player: Entity,
+        pixels: Vec<(i64, i64, MaterialInstance)>,
+        settings: &Settings,
+        registries: &Registries,
+    ) -> Result<Vec<ChunkKey>, String> {
+        let pos = self
+            .ecs
+            .read_storage::<Position>()
+            .get(player)
+            .map(|p| (p.x, p.y));
+        let mut inventories = self.ecs.write_storage::<Inventory>();
+        let mut inventory = inventories.get_mut(player);
+
+        let mut chunks = vec![];
+        let mut changed: Option<Rect<i64>> = None;
+        let mut edit = self.chunk_handler.begin_edit();
+        for (x, y, mat) in pixels {
+            let key = pixel_to_chunk_pos(x, y);
+            if !chunks.contains(&key) {
+                chunks.push(key);
+            }
+
+            let in_reach = pos.is_some_and(|(px, py)| {
+                let (dx, dy) = (x as f64 - px, y as f64 - py);
+                dx * dx + dy * dy <= EDIT_REACH * EDIT_REACH
+            });
+            if !in_reach || (settings.survival && inventory.is_none()) {
+                continue;
+            }
+            let Ok(current) = edit.pixel(x, y) else {
+                continue;
+            };
+
+            let (mut collected, mut taken) = (None, None);
+            let allowed = if mat.is_empty(&registries.materials) {
+                if settings.survival {
+                    collected = registries
+                        .materials
+                        .get(&current.material_id)
+                        .and_then(|m| m.placer.clone());
+                    !current.is_empty(&registries.materials)
+                } else {
+                    true
+                }
+            } else {
+                let placer = registries
+                    .materials
+                    .get(&mat.material_id)
+                    .and_then(|m| m.placer.clone());
+                match (placer, inventory.as_deref()) {
+                    (Some(placer), Some(inventory)) if settings.survival => {
+                        let placeable = inventory.in_hotbar(&placer)
+                            && inventory.count(&placer) > 0
+                            && current.is_empty(&registries.materials);
+                        taken = Some(placer);
+                        placeable
+                    },
+                    (Some(placer), inventory) => {
+                        inventory.is_some_and(|inventory| inventory.in_hotbar(&placer))
+                            || settings
+                                .brush_palette
+                                .slots
+                                .iter()
+                                .flatten()
+                                .any(|p| *p == placer)
+                    },
+                    (None, _) => false,
                 }
+            };
+
+            if allowed && edit.set(x, y, mat).is_ok() {
+                // only set in survival, which needs an inventory
+                if let Some(inventory) = inventory.as_deref_mut() {
+                    if let Some(collected) = collected {
+                        inventory.collect(collected, 1);
+                    }
+                    if let Some(taken) = taken {
+                        inventory.take(&taken);
+                    }
+                }
+                let px = Rect::new(x, y, x + 1, y + 1);
+                changed = Some(changed.map_or(px, |c| c.union(px)));
             }
-            None
+        }
+        edit.commit()?;
+        drop(inventories);
+
+        if let Some(rect) = changed {
+            self.ecs
+                .write_resource::<WorldEditEvents>()
+                .emit(rect, WorldEditCause::Tool);
+        }
+
+        Ok(chunks)
+    }
+
+    /// Cuts the pixels in a selection out of the world and turns them into rigidbodies,
+    ///   each pixel keeping its material and color. Returns how many bodies were made.
+    ///
+    /// If nothing in the selection can become a body (eg. it's all air), the world is left unchanged.
+    pub fn selection_to_rigidbodies(
+        &mut self,
+        x: i64,
+        y: i64,
+        width: u16,
+        height: u16,
+    ) -> Result<usize, String> {
+        if width == 0 || height == 0 {
+            return Ok(0);
+        }
+
+        let buf = MaterialBuf::cut(&mut self.chunk_handler, x, y, width, height)?;
+
+        let position = (x as f32 / PHYSICS_SCALE, y as f32 / PHYSICS_SCALE);
+        let mut bodies = match FSRigidBody::make_bodies(
+            &buf.materials,
+            width,
+            height,
+            &mut self.physics,
+            position,
+        ) {
+            Ok(bodies) if !bodies.is_empty() => bodies,
+            res => {
+                // put everything back
+                buf.paste(&mut self.chunk_handler, x, y)?;
+                return res.map(|_| 0);
+            },
+        };
+
+        let rect = Rect::new_wh(x, y, i64::from(width), i64::from(height));
+        self.ecs
+            .write_resource::<WorldEditEvents>()
+            .emit(rect, WorldEditCause::Tool);
+
+        let (cx1, cy1) = pixel_to_chunk_pos(rect.x1, rect.y1);
+        let (cx2, cy2) = pixel_to_chunk_pos(rect.x2 - 1, rect.y2 - 1);
+        for cx in cx1..=cx2 {
+            for cy in cy1..=cy2 {
+                self.rebuild_chunk_collision((cx, cy));
+            }
+        }
+
+        let count = bodies.len();
+        self.rigidbodies.append(&mut bodies);
+        Ok(count)
+    }
+
+    /// Settles the oldest rigidbodies into the world until at most [`WorldRules::max_rigidbodies`]
+    ///   are left in physics, see [`World::settle_rigidbody`]. Returns how many were settled.
+    pub fn recycle_rigidbodies(&mut self, registries: &Registries) -> usize {
+        let active = self
+            .rigidbodies
+            .iter()
+            .filter(|rb| rb.body.is_some())
+            .count();
+        let mut excess = active.saturating_sub(self.rules.max_rigidbodies);
+
+        let mut settled = 0;
+        let mut i = 0;
+        // bodies are appended as they're made, so the oldest are first
+        while excess > 0 && i < self.rigidbodies.len() {
+            if self.rigidbodies[i].body.is_some() {
+                let rb = self.rigidbodies.remove(i);
+                self.settle_rigidbody(&rb, registries);
+                excess -= 1;
+                settled += 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        settled
+    }
+
+    /// Removes a rigidbody from physics and puts its pixels into the chunks where it is.
+    ///
+    /// Pixels that would land on something that's already there (or in an unloaded chunk)
+    ///   become particles instead, so none of them are lost.
+    pub fn settle_rigidbody(&mut self, rb: &FSRigidBody, registries: &Registries) {
+        let Some(body) = rb.body.and_then(|b| self.physics.remove_rigidbody(b)) else {
+            return;
         };
 
-        let x_dist = (x2 - x1).abs();
-        let y_dist = -(y2 - y1).abs();
-        let x_step = if x1 < x2 { 1 } else { -1 };
-        let y_step = if y1 < y2 { 1 } else { -1 };
-        let mut error = x_dist + y_dist;
+        let (s, c) = body.rotation().angle().sin_cos();
+        let pos_x = body.translation().x * PHYSICS_SCALE;
+        let pos_y = body.translation().y * PHYSICS_SCALE;
+
+        let mut particles = vec![];
+        let mut broken_chunks = HashSet::new();
+        for rb_y in 0..rb.height {
+            for rb_x in 0..rb.width {
+                let mat = &rb.pixels[(rb_x + rb_y * rb.width) as usize];
+                if mat.is_empty(&registries.materials) {
+                    continue;
+                }
+                // pixels filled in by the world tick would be cleared again
+                let mat = if mat.physics == PhysicsType::Object {
+                    MaterialInstance { physics: PhysicsType::Solid, ..mat.clone() }
+                } else {
+                    mat.clone()
+                };
+
+                let tx = f32::from(rb_x) * c - f32::from(rb_y) * s + pos_x;
+                let ty = f32::from(rb_x) * s + f32::from(rb_y) * c + pos_y;
+                let (wx, wy) = (tx as i64, ty as i64);
+
+                let free = self
+                    .chunk_handler
+                    .pixel(wx, wy)
+                    .map_or(false, |m| m.is_empty(&registries.materials));
+                if free {
+                    if mat.physics == PhysicsType::Solid {
+                        broken_chunks.insert(pixel_to_chunk_pos(wx, wy));
+                    }
+                    // checked that the chunk is loaded just above
+                    let _ignore = self.chunk_handler.set_pixel(wx, wy, mat);
+                } else {
+                    particles.push(Particle::new(
+                        mat,
+                        Position { x: f64::from(tx), y: f64::from(ty) },
+                        Velocity { x: 0.0, y: 0.0 },
+                    ));
+                }
+            }
+        }
+
+        self.ecs
+            .write_resource::<ParticleSystem>()
+            .active
+            .append(&mut particles);
+        for key in broken_chunks {
+            self.rebuild_chunk_collision(key);
+        }
+    }
+
+    /// Updates every loaded pixel, rigidbody pixel and particle after the material registries
+    ///   were reloaded, see [`MaterialRemap`]. Returns how many pixels changed.
+    ///
+    /// Rigidbody colliders aren't rebuilt, so a body made of a removed material keeps its shape.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn remap_materials(&mut self, registries: &Registries) -> usize {
+        let remap = MaterialRemap::new(registries);
+        let mut changed = 0;
+
+        let mut broken_chunks = vec![];
+        for (key, ch) in unsafe { self.chunk_handler.manager.raw_mut() }.iter_mut() {
+            let (base_x, base_y) = (
+                i64::from(key.0) * i64::from(CHUNK_WIDTH),
+                i64::from(key.1) * i64::from(CHUNK_HEIGHT),
+            );
+
+            let mut updates = vec![];
+            let mut bg_updates = vec![];
+            for y in 0..CHUNK_HEIGHT {
+                for x in 0..CHUNK_WIDTH {
+                    let pos = ChunkLocalPosition::new(x, y).unwrap();
+                    let (wx, wy) = (base_x + i64::from(x), base_y + i64::from(y));
+                    if let Some(new) = ch
+                        .pixel(pos)
+                        .ok()
+                        .and_then(|m| remap.remap(m, wx, wy).map(|new| (m.physics, new)))
+                    {
+                        updates.push((pos, new));
+                    }
+                    if let Some(new) = ch.background(pos).ok().and_then(|m| remap.remap(m, wx, wy))
+                    {
+                        bg_updates.push((pos, new));
+                    }
+                }
+            }
+
+            changed += updates.len() + bg_updates.len();
+            if updates.iter().any(|(_, (old, new))| {
+                (*old == PhysicsType::Solid) != (new.physics == PhysicsType::Solid)
+            }) {
+                broken_chunks.push(*key);
+            }
+            for (pos, (_, new)) in updates {
+                ch.set_color(pos, new.color);
+                let _: Result<(), _> = ch.set_pixel(pos, new);
+            }
+            for (pos, new) in bg_updates {
+                let _: Result<(), _> = ch.set_background(pos, new);
+            }
+        }
+        for key in broken_chunks {
+            self.rebuild_chunk_collision(key);
+        }
+
+        for rb in &mut self.rigidbodies {
+            for (i, px) in rb.pixels.iter_mut().enumerate() {
+                let (x, y) = (i % usize::from(rb.width), i / usize::from(rb.width));
+                if let Some(new) = remap.remap(px, x as i64, y as i64) {
+                    *px = new;
+                    rb.image_dirty = true;
+                    changed += 1;
+                }
+            }
+        }
+
+        let mut particles = self.ecs.write_resource::<ParticleSystem>();
+        let particles = &mut *particles;
+        for p in particles.active.iter_mut().chain(&mut particles.sleeping) {
+            if let Some(new) = remap.remap(&p.material, p.pos.x as i64, p.pos.y as i64) {
+                p.material = new;
+                changed += 1;
+            }
+        }
 
-        if let Some(r) = check_pixel(x1, y1) {
-            if collide_filder(r.0, r.1) {
-                return Some(r);
+        changed
+    }
+
+    /// Turns every pixel of the `from` material in `rect` (`x2`/`y2` exclusive) into `to`, as a
+    ///   single [`Edit`](super::edit::Edit) that is undone all at once. Returns how many pixels changed.
+    ///
+    /// Pixels take `to`'s physics type too, so eg. sand turned into a solid stops falling. Chunks
+    ///   with no matching pixels aren't touched, and collision is only rebuilt for chunks where
+    ///   a pixel became or stopped being solid. Unloaded parts of `rect`, and anything outside
+    ///   the world border, are skipped.
+    pub fn transmute(
+        &mut self,
+        rect: Rect<i64>,
+        from: &RegistryID<Material>,
+        to: &MaterialInstance,
+    ) -> Result<usize, String> {
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return Ok(0);
+        }
+
+        let mut broken_chunks = HashSet::new();
+        let mut edit = self.chunk_handler.begin_edit();
+        for y in rect.y1..rect.y2 {
+            for x in rect.x1..rect.x2 {
+                let Ok(mat) = edit.pixel(x, y) else {
+                    continue;
+                };
+                if mat.material_id != *from || mat == to {
+                    continue;
+                }
+
+                let breaks =
+                    (mat.physics == PhysicsType::Solid) != (to.physics == PhysicsType::Solid);
+                // outside the border can be read but not changed
+                if edit.set(x, y, to.clone()).is_ok() && breaks {
+                    broken_chunks.insert(pixel_to_chunk_pos(x, y));
+                }
             }
         }
 
-        while x1 != x2 || y1 != y2 {
-            let tmp = 2 * error;
+        let changed = edit.len();
+        edit.commit()?;
+        if changed == 0 {
+            return Ok(0);
+        }
+
+        self.ecs
+            .write_resource::<WorldEditEvents>()
+            .emit(rect, WorldEditCause::Tool);
+        for key in broken_chunks {
+            self.rebuild_chunk_collision(key);
+        }
+
+        Ok(changed)
+    }
+
+    /// Fills the air in a circle of `radius` around (`x`, `y`) with `liquid`, as a single
+    ///   [`Edit`](super::edit::Edit). Returns how many pixels were filled.
+    ///
+    /// Unlike painting, anything that isn't empty is left alone, so pouring into a container never
+    ///   eats its walls. The filled chunks are marked dirty so the liquid starts flowing on the next tick.
+    pub fn pour_liquid(
+        &mut self,
+        x: i64,
+        y: i64,
+        radius: u16,
+        liquid: &MaterialInstance,
+        materials: &MaterialRegistry,
+    ) -> Result<usize, String> {
+        if liquid.physics != PhysicsType::Liquid {
+            return Err(format!("{} is not a liquid.", liquid.material_id));
+        }
+
+        self.replace_in_circle(x, y, radius, |mat| {
+            mat.is_empty(materials).then(|| liquid.clone())
+        })
+    }
+
+    /// Removes the liquid in a circle of `radius` around (`x`, `y`), leaving air, as a single
+    ///   [`Edit`](super::edit::Edit). Returns how many pixels were removed.
+    ///
+    /// Only liquid pixels are touched, see [`Self::pour_liquid`].
+    pub fn siphon_liquid(&mut self, x: i64, y: i64, radius: u16) -> Result<usize, String> {
+        self.replace_in_circle(x, y, radius, |mat| {
+            (mat.physics == PhysicsType::Liquid).then(MaterialInstance::air)
+        })
+    }
 
-            if tmp > y_dist {
-                error += y_dist;
-                x1 += x_step;
+    /// Replaces every pixel in a circle where `replace` returns `Some`, skipping unloaded ones
+    ///   and ones outside the world border.
+    fn replace_in_circle(
+        &mut self,
+        x: i64,
+        y: i64,
+        radius: u16,
+        replace: impl Fn(&MaterialInstance) -> Option<MaterialInstance>,
+    ) -> Result<usize, String> {
+        let r = i64::from(radius);
+        let mut edit = self.chunk_handler.begin_edit();
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy > r * r {
+                    continue;
+                }
+                let (px, py) = (x + dx, y + dy);
+                let Ok(mat) = edit.pixel(px, py) else {
+                    continue;
+                };
+                if let Some(new) = replace(mat) {
+                    // outside the border can be read but not changed
+                    let _: Result<(), _> = edit.set(px, py, new);
+                }
             }
+        }
+
+        let changed = edit.len();
+        edit.commit()?;
+        if changed > 0 {
+            self.ecs.write_resource::<WorldEditEvents>().emit(
+                Rect::new(x - r, y - r, x + r + 1, y + r + 1),
+                WorldEditCause::Tool,
+            );
+        }
 
-            if tmp < x_dist {
-                error += x_dist;
-                y1 += y_step;
+        Ok(changed)
+    }
+
+    /// Regenerates a chunk's mesh and collision after some of its solid pixels changed.
+    fn rebuild_chunk_collision(&mut self, key: (i32, i32)) {
+        let Some(c) = self.chunk_handler.manager.chunk_at_mut(key) else {
+            return;
+        };
+        let _: Result<(), _> = c.generate_mesh();
+
+        // inactive collision is rebuilt by the next tick
+        if let Some(ChunkRigidBodyState::Active(handle)) = c.rigidbody_mut().take() {
+            self.physics.remove_rigidbody(handle);
+            let state =
+                chunk_collision(&c.data).map(|s| activate_chunk_collision(&mut self.physics, s));
+            c.set_rigidbody(state);
+        }
+    }
+
+    /// Simulates the pixels inside rigidbodies (sand in a cup, etc.), skipping sleeping bodies.
+    #[profiling::function]
+    pub fn simulate_rigidbody_pixels(&mut self, settings: &Settings, registries: &Registries) {
+        let mut new_parts = Vec::new();
+        simulator::Simulator::simulate_rigidbodies(
+            &mut self.chunk_handler,
+            &mut self.rigidbodies,
+            &mut self.physics,
+            &mut new_parts,
+            registries,
+            simulator::SimRules {
+                freezing: self.rules.freezing,
+                ..simulator::SimRules::from_settings(settings)
+            },
+        );
+        self.ecs
+            .write_resource::<ParticleSystem>()
+            .active
+            .append(&mut new_parts);
+    }
+
+    /// Particles are ticked separately from [`World::tick`] so they can run at their own rate.
+    #[profiling::function]
+    pub fn tick_particles(&mut self, settings: &Settings, registries: &Registries) {
+        if settings.simulate_particles {
+            if let Err(e) = self.particle_pool.resize(settings.particle_threads) {
+                log::error!("{e}");
             }
 
-            if let Some(r) = check_pixel(x1, y1) {
-                if collide_filder(r.0, r.1) {
-                    return Some(r);
+            let mut update_particles = UpdateParticles {
+                chunk_handler: &mut self.chunk_handler,
+                interactions: settings.particle_interactions,
+                registries,
+            };
+            let ecs = &self.ecs;
+            self.particle_pool.install(|| update_particles.run_now(ecs));
+            self.ecs.maintain();
+        }
+    }
+
+    /// Counts pixels by material in `rect` (`x2`/`y2` exclusive), for checking generation.
+    ///
+    /// Chunks that aren't loaded or haven't finished generating are skipped, unless
+    /// `generate_missing` is given, in which case they are generated standalone with
+    /// [`WorldGenerator::generate_chunk`](super::gen::WorldGenerator::generate_chunk)
+    /// (without being added to the world).
+    pub fn material_histogram(
+        &self,
+        rect: Rect<i64>,
+        generate_missing: Option<&Registries>,
+    ) -> HashMap<RegistryID<Material>, u64> {
+        let mut histogram = HashMap::new();
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return histogram;
+        }
+
+        let (chunk_w, chunk_h) = (i64::from(CHUNK_WIDTH), i64::from(CHUNK_HEIGHT));
+        let (min_cx, min_cy) = pixel_to_chunk_pos(rect.x1, rect.y1);
+        let (max_cx, max_cy) = pixel_to_chunk_pos(rect.x2 - 1, rect.y2 - 1);
+
+        for cy in min_cy..=max_cy {
+            for cx in min_cx..=max_cx {
+                let generated;
+                let pixels: &[MaterialInstance; CHUNK_AREA] = if let Some(pixels) = self
+                    .chunk_handler
+                    .chunk_at_dyn((cx, cy))
+                    .filter(|ch| matches!(ch.state(), ChunkState::Cached | ChunkState::Active))
+                    .and_then(|ch| ch.pixels().as_deref())
+                {
+                    pixels
+                } else {
+                    let Some(registries) = generate_missing else {
+                        continue;
+                    };
+                    generated = self
+                        .chunk_handler
+                        .generator
+                        .generate_chunk(cx, cy, self.seed, registries)
+                        .0;
+                    &generated
+                };
+
+                // the part of the rect inside this chunk, in chunk local coordinates
+                let chunk_x = i64::from(cx) * chunk_w;
+                let chunk_y = i64::from(cy) * chunk_h;
+                let x1 = (rect.x1 - chunk_x).max(0) as usize;
+                let x2 = (rect.x2 - chunk_x).min(chunk_w) as usize;
+                let y1 = (rect.y1 - chunk_y).max(0) as usize;
+                let y2 = (rect.y2 - chunk_y).min(chunk_h) as usize;
+
+                for y in y1..y2 {
+                    let row = y * CHUNK_WIDTH as usize;
+                    for mat in &pixels[row + x1..row + x2] {
+                        *histogram.entry(mat.material_id.clone()).or_insert(0) += 1;
+                    }
                 }
             }
         }
 
-        None
+        histogram
+    }
+
+    /// Updates cameras, keeping them inside the border if there is one.
+    ///
+    /// `view_size` is how much of the world is on screen, in world pixels (the window size divided
+    ///   by the camera scale). Without one (like on a server) cameras aren't clamped.
+    pub fn frame(&mut self, delta_time: Duration, view_size: Option<(f64, f64)>) {
+        *self.ecs.write_resource::<DeltaTime>() = DeltaTime(delta_time);
+
+        let mut update_auto_targets = UpdateAutoTargets;
+        update_auto_targets.run_now(&self.ecs);
+
+        if let (Some(border), Some(view_size)) = (&self.chunk_handler.border, view_size) {
+            let (cameras, mut positions) = self
+                .ecs
+                .system_data::<(ReadStorage<Camera>, WriteStorage<Position>)>();
+            for (_, pos) in (&cameras, &mut positions).join() {
+                border.clamp_view(pos, view_size);
+            }
+        }
+    }
+
+    /// See [`ChunkHandler::raycast_filtered`].
+    pub fn raycast(
+        &self,
+        x1: i64,
+        y1: i64,
+        x2: i64,
+        y2: i64,
+        collide_filder: fn((i64, i64), &MaterialInstance) -> bool,
+    ) -> Option<((i64, i64), &MaterialInstance)> {
+        self.chunk_handler
+            .raycast_filtered((x1, y1), (x2, y2), collide_filder)
     }
 }
 
@@ -396,6 +1179,144 @@ impl<C: Chunk + SidedChunk + Send + Sync + 'static> World<C>
 where
     <<C as SidedChunk>::S as SidedChunkData>::TileEntityData: TileEntitySided<D = C>,
 {
+    /// Generates the chunks within `radius` chunks of a pixel right away with this world's seed,
+    ///   see [`ChunkHandler::force_generate`]. Returns how many chunks were finished.
+    pub fn force_generate_around(
+        &mut self,
+        x: i64,
+        y: i64,
+        radius: u16,
+        registries: &Arc<Registries>,
+    ) -> usize {
+        self.chunk_handler.force_generate(
+            pixel_to_chunk_pos(x, y),
+            radius,
+            self.seed,
+            registries,
+            &mut self.ecs,
+        )
+    }
+
+    /// Moves `entity` to `x`, `y`, generating the chunks around it first so it doesn't land in
+    ///   ungenerated space.
+    ///
+    /// The entity is stopped and its rapier body (if it has one) is moved along with it.
+    ///   Anything following it with an [`AutoTarget`] (like the camera) jumps straight there too,
+    ///   instead of sweeping across everything in between.
+    pub fn teleport(
+        &mut self,
+        entity: Entity,
+        x: f64,
+        y: f64,
+        registries: &Arc<Registries>,
+    ) -> Result<(), String> {
+        if !self.ecs.is_alive(entity) {
+            return Err(format!("Can't teleport dead entity {entity:?}."));
+        }
+
+        self.force_generate_around(x as i64, y as i64, TELEPORT_GENERATE_RADIUS, registries);
+
+        let (entities, mut positions, mut velocities, bodies, targets) = self.ecs.system_data::<(
+            Entities,
+            WriteStorage<Position>,
+            WriteStorage<Velocity>,
+            ReadStorage<RigidBodyComponent>,
+            ReadStorage<AutoTarget>,
+        )>();
+
+        let pos = positions
+            .get_mut(entity)
+            .ok_or_else(|| format!("Can't teleport {entity:?} without a Position."))?;
+        *pos = Position { x, y };
+        if let Some(vel) = velocities.get_mut(entity) {
+            *vel = Velocity { x: 0.0, y: 0.0 };
+        }
+
+        if let Some(body) = bodies
+            .get(entity)
+            .and_then(|b| self.physics.bodies.get_mut(b.body))
+        {
+            let np = Vector2::new(x as f32 / PHYSICS_SCALE, y as f32 / PHYSICS_SCALE);
+            body.set_position(Isometry2::new(np, 0.0), true);
+            body.set_linvel(Vector2::zeros(), true);
+        }
+
+        let followers: Vec<(Entity, Position)> = (&entities, &targets)
+            .join()
+            .filter(|(_, at)| matches!(&at.target, Target::Entity(e) if *e == entity))
+            .filter_map(|(e, at)| Some((e, at.get_target_pos(&positions, &velocities)?)))
+            .collect();
+        for (follower, target_pos) in followers {
+            if let Some(pos) = positions.get_mut(follower) {
+                *pos = target_pos;
+            }
+            if let Some(vel) = velocities.get_mut(follower) {
+                *vel = Velocity { x: 0.0, y: 0.0 };
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like [`World::create_with_preset`], then replays the edits a crash left unsaved (see
+    ///   [`World::recover_journal`]). Anything loading a world to play in should use this, so no
+    ///   host ever skips the recovery.
+    pub fn load(
+        path: Option<PathBuf>,
+        seed: Option<i32>,
+        preset: WorldPreset,
+        registries: &Arc<Registries>,
+    ) -> Self {
+        let mut world = Self::create_with_preset(path, seed, preset);
+        match world.recover_journal(registries) {
+            Ok(0) => {},
+            Ok(n) => log::info!("Recovered {n} unsaved edits"),
+            Err(e) => log::error!("Failed to recover unsaved edits: {e}"),
+        }
+        world
+    }
+
+    /// Replays the edit journal left behind if the game crashed before saving, see [`EditJournal`](journal::EditJournal).
+    ///   Returns how many entries were replayed.
+    ///
+    /// The chunks it touches are loaded (or generated) first, so this can be called right after [`World::create`],
+    ///   which [`World::load`] does.
+    ///   The journal is kept until the next [`World::save`].
+    pub fn recover_journal(&mut self, registries: &Arc<Registries>) -> Result<usize, String> {
+        let Some(path) = self
+            .chunk_handler
+            .path
+            .as_deref()
+            .map(journal::journal_path)
+        else {
+            return Ok(0);
+        };
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let entries = journal::read(&path)?;
+        let keys: BTreeSet<ChunkKey> = entries.iter().flat_map(JournalEntry::chunks).collect();
+        for key in &keys {
+            let loaded = self
+                .chunk_handler
+                .manager
+                .chunk_at(*key)
+                .map_or(false, |c| c.pixels().is_some());
+            if !loaded {
+                self.chunk_handler
+                    .force_generate(*key, 0, self.seed, registries, &mut self.ecs);
+            }
+        }
+
+        journal::replay(&mut self.chunk_handler, &entries)?;
+        for key in keys {
+            self.rebuild_chunk_collision(key);
+        }
+
+        Ok(entries.len())
+    }
+
     #[profiling::function]
     pub fn tick(
         &mut self,
@@ -405,6 +1326,7 @@ where
         file_helper: &FileHelper,
     ) {
         *self.ecs.write_resource::<TickTime>() = TickTime(tick_time);
+        self.ecs.write_resource::<SettleEvents>().clear();
 
         {
             profiling::scope!("fill rigidbodies");
@@ -424,10 +1346,10 @@ where
                             let ty = f32::from(rb_x) * s + f32::from(rb_y) * c + pos_y;
 
                             let cur = &rb.pixels[(rb_x + rb_y * rb_w) as usize];
-                            if cur.physics != PhysicsType::Air {
+                            if !cur.is_empty(&registries.materials) {
                                 let world = self.chunk_handler.pixel(tx as i64, ty as i64);
                                 if let Ok(mat) = world {
-                                    if mat.material_id == *material::AIR {
+                                    if mat.is_empty(&registries.materials) {
                                         // ok to fail since the chunk might just not be ready
                                         let _ignore = self.chunk_handler.set_pixel(
                                             tx as i64,
@@ -482,7 +1404,8 @@ where
                                                     (part_pos.y + part_vel.y) as i64,
                                                 ) {
                                                     Ok(m_test)
-                                                        if m_test.physics != PhysicsType::Air =>
+                                                        if !m_test
+                                                            .is_empty(&registries.materials) =>
                                                     {
                                                         part_vel.x *= -1.0;
                                                         part_vel.y *= -1.0;
@@ -525,6 +1448,7 @@ where
                                                             tx as i64,
                                                             ty as i64,
                                                             m.clone(),
+                                                            &registries.materials,
                                                         ) {
                                                             let part = Particle::new(
                                                                 m, part_pos, part_vel,
@@ -643,11 +1567,12 @@ where
                         // ok to fail since the chunk might just not be ready
                         let _ignore =
                             ch.replace_pixel(pos_x.floor() as i64, pos_y.floor() as i64, |mat| {
-                                (mat.material_id == *material::AIR).then_some(MaterialInstance {
-                                    physics: PhysicsType::Object,
-                                    color: Color::rgb(0, 255, 0),
-                                    ..mat.clone()
-                                })
+                                mat.is_empty(&registries.materials)
+                                    .then_some(MaterialInstance {
+                                        physics: PhysicsType::Object,
+                                        color: Color::rgb(0, 255, 0),
+                                        ..mat.clone()
+                                    })
                             });
                     }
                 });
@@ -673,12 +1598,6 @@ where
             file_helper,
         });
 
-        if settings.simulate_particles {
-            let mut update_particles = UpdateParticles { chunk_handler: &mut self.chunk_handler };
-            update_particles.run_now(&self.ecs);
-            self.ecs.maintain();
-        }
-
         {
             profiling::scope!("unfill Objects");
             let (position_storage, velocity_storage, phys_ent_storage, hitbox_storage) =
@@ -732,8 +1651,10 @@ where
                 });
         }
 
-        let mut update_physics_entities =
-            UpdatePhysicsEntities { chunk_handler: &mut self.chunk_handler };
+        let mut update_physics_entities = UpdatePhysicsEntities {
+            chunk_handler: &mut self.chunk_handler,
+            registries: &registries,
+        };
         update_physics_entities.run_now(&self.ecs);
         self.ecs.maintain();
 
@@ -753,7 +1674,7 @@ where
                                 let rb_mat = unsafe {
                                     rb.pixels.get_unchecked((rb_x + rb_y * rb.width) as usize)
                                 };
-                                if rb_mat.physics != PhysicsType::Air {
+                                if !rb_mat.is_empty(&registries.materials) {
                                     let wx =
                                         (f32::from(rb_x) * c - f32::from(rb_y) * s + pos_x) as i64;
                                     let wy =
@@ -773,20 +1694,7 @@ where
             }
         }
 
-        {
-            profiling::scope!("sim rigidbodies");
-            let mut new_parts = Vec::new();
-            simulator::Simulator::simulate_rigidbodies(
-                &mut self.chunk_handler,
-                &mut self.rigidbodies,
-                &mut self.physics,
-                &mut new_parts,
-            );
-            self.ecs
-                .write_resource::<ParticleSystem>()
-                .active
-                .append(&mut new_parts);
-        }
+        self.simulate_rigidbody_pixels(settings, &registries);
 
         {
             profiling::scope!("update chunk collision");
@@ -794,7 +1702,7 @@ where
                 if c.rigidbody().is_none() {
                     // if let Some(tr) = c.get_tris() {
                     //     let mut body_def = BodyDef::default();
-                    //     body_def.position.set((c.get_chunk_x() * CHUNK_SIZE as i32) as f32 / PHYSICS_SCALE, (c.get_chunk_y() * CHUNK_SIZE as i32) as f32 / PHYSICS_SCALE);
+                    //     body_def.position.set((c.get_chunk_x() * CHUNK_WIDTH as i32) as f32 / PHYSICS_SCALE, (c.get_chunk_y() * CHUNK_HEIGHT as i32) as f32 / PHYSICS_SCALE);
                     //     let body = self.lqf_world.create_body(&body_def);
 
                     //     tr.iter().for_each(|tris| {
@@ -815,52 +1723,20 @@ where
                     //     c.set_b2_body(Some(body));
                     // }
 
-                    if let Some(loops) = c.mesh_loops() {
-                        let rigid_body = RigidBodyBuilder::fixed()
-                            .translation(Vector2::new(
-                                (c.chunk_x() * i32::from(CHUNK_SIZE)) as f32 / PHYSICS_SCALE,
-                                (c.chunk_y() * i32::from(CHUNK_SIZE)) as f32 / PHYSICS_SCALE,
-                            ))
-                            .build();
-                        let mut colliders = Vec::new();
-
-                        for a_loop in loops.iter() {
-                            for pts in a_loop.iter() {
-                                let mut verts: Vec<Point2<f32>> = Vec::new();
-
-                                for p in pts.iter() {
-                                    verts.push(Point2::new(
-                                        p[0] as f32 / PHYSICS_SCALE,
-                                        p[1] as f32 / PHYSICS_SCALE,
-                                    ));
-                                }
-
-                                let collider = ColliderBuilder::polyline(verts, None)
-                                    .collision_groups(InteractionGroups::new(
-                                        CollisionFlags::WORLD.bits().into(),
-                                        CollisionFlags::RIGIDBODY.bits().into(),
-                                    ))
-                                    .density(0.0)
-                                    .build();
-                                colliders.push(collider);
-                            }
-                        }
-
-                        c.set_rigidbody(Some(ChunkRigidBodyState::Inactive(
-                            Box::new(rigid_body),
-                            colliders,
-                        )));
+                    if let Some(state) = chunk_collision(&c.data) {
+                        c.set_rigidbody(Some(state));
                     }
                 } else {
                     // TODO: profile this and if it's too slow, could stagger it based on tick_time
 
                     let chunk_center_x =
-                        c.chunk_x() * i32::from(CHUNK_SIZE) + i32::from(CHUNK_SIZE) / 2;
+                        c.chunk_x() * i32::from(CHUNK_WIDTH) + i32::from(CHUNK_WIDTH) / 2;
                     let chunk_center_y =
-                        c.chunk_y() * i32::from(CHUNK_SIZE) + i32::from(CHUNK_SIZE) / 2;
+                        c.chunk_y() * i32::from(CHUNK_HEIGHT) + i32::from(CHUNK_HEIGHT) / 2;
 
-                    // let dist_particle = f32::from(CHUNK_SIZE) * 0.6;
-                    let dist_body = f32::from(CHUNK_SIZE) * 1.0;
+                    // let dist_particle = f32::from(CHUNK_WIDTH) * 0.6;
+                    let dist_body_x = f32::from(CHUNK_WIDTH) * 1.0;
+                    let dist_body_y = f32::from(CHUNK_HEIGHT) * 1.0;
 
                     let mut should_be_active = false;
 
@@ -886,7 +1762,7 @@ where
                             let pos = rb.translation();
                             let dist_x = (pos.x * PHYSICS_SCALE - chunk_center_x as f32).abs();
                             let dist_y = (pos.y * PHYSICS_SCALE - chunk_center_y as f32).abs();
-                            if dist_x < dist_body && dist_y < dist_body {
+                            if dist_x < dist_body_x && dist_y < dist_body_y {
                                 should_be_active = true;
                             }
                             // }
@@ -930,30 +1806,11 @@ where
 
                         if should_be_active && matches!(state, ChunkRigidBodyState::Inactive(_, _))
                         {
-                            match c.rigidbody_mut().take().unwrap() {
-                                ChunkRigidBodyState::Inactive(rb, colls) if should_be_active => {
-                                    let rb_handle = self.physics.bodies.insert(*rb);
-                                    for collider in colls {
-                                        // let bo_handle = self
-                                        //     .physics
-                                        //     .fluid_pipeline
-                                        //     .liquid_world
-                                        //     .add_boundary(Boundary::new(Vec::new()));
-                                        let _co_handle = self.physics.colliders.insert_with_parent(
-                                            collider,
-                                            rb_handle,
-                                            &mut self.physics.bodies,
-                                        );
-                                        // self.physics.fluid_pipeline.coupling.register_coupling(
-                                        //     bo_handle,
-                                        //     co_handle,
-                                        //     ColliderSampling::DynamicContactSampling,
-                                        // );
-                                    }
-                                    c.set_rigidbody(Some(ChunkRigidBodyState::Active(rb_handle)));
-                                },
-                                _ => {},
-                            }
+                            let state = c.rigidbody_mut().take().unwrap();
+                            c.set_rigidbody(Some(activate_chunk_collision(
+                                &mut self.physics,
+                                state,
+                            )));
                         }
                     }
                 }
@@ -973,3 +1830,70 @@ where
 fn replace_object_with_air(mat: &MaterialInstance) -> Option<MaterialInstance> {
     (mat.physics == PhysicsType::Object).then(MaterialInstance::air)
 }
+
+/// Builds the fixed body and colliders for a chunk's mesh, which are added to the physics world
+///   by [`activate_chunk_collision`] once a body gets close.
+fn chunk_collision<C: Chunk>(c: &C) -> Option<ChunkRigidBodyState> {
+    let loops = c.mesh_loops().as_ref()?;
+
+    let rigid_body = RigidBodyBuilder::fixed()
+        .translation(Vector2::new(
+            (c.chunk_x() * i32::from(CHUNK_WIDTH)) as f32 / PHYSICS_SCALE,
+            (c.chunk_y() * i32::from(CHUNK_HEIGHT)) as f32 / PHYSICS_SCALE,
+        ))
+        .build();
+    let mut colliders = Vec::new();
+
+    for a_loop in loops.iter() {
+        for pts in a_loop.iter() {
+            let mut verts: Vec<Point2<f32>> = Vec::new();
+
+            for p in pts.iter() {
+                verts.push(Point2::new(
+                    p[0] as f32 / PHYSICS_SCALE,
+                    p[1] as f32 / PHYSICS_SCALE,
+                ));
+            }
+
+            let collider = ColliderBuilder::polyline(verts, None)
+                .collision_groups(CollisionLayers::terrain().into())
+                .density(0.0)
+                .build();
+            colliders.push(collider);
+        }
+    }
+
+    Some(ChunkRigidBodyState::Inactive(
+        Box::new(rigid_body),
+        colliders,
+    ))
+}
+
+/// Adds an inactive chunk body and its colliders to the physics world.
+fn activate_chunk_collision(
+    physics: &mut Physics,
+    state: ChunkRigidBodyState,
+) -> ChunkRigidBodyState {
+    match state {
+        ChunkRigidBodyState::Inactive(rb, colls) => {
+            let rb_handle = physics.bodies.insert(*rb);
+            for collider in colls {
+                // let bo_handle = physics
+                //     .fluid_pipeline
+                //     .liquid_world
+                //     .add_boundary(Boundary::new(Vec::new()));
+                let _co_handle =
+                    physics
+                        .colliders
+                        .insert_with_parent(collider, rb_handle, &mut physics.bodies);
+                // physics.fluid_pipeline.coupling.register_coupling(
+                //     bo_handle,
+                //     co_handle,
+                //     ColliderSampling::DynamicContactSampling,
+                // );
+            }
+            ChunkRigidBodyState::Active(rb_handle)
+        },
+        active @ ChunkRigidBodyState::Active(_) => active,
+    }
+}
@@ -1,10 +1,19 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     fs,
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use super::{Chunk, World};
+use crate::game::common::registry::RegistryID;
+
+use super::{
+    border::WorldBorder,
+    chunk_handler::ChunkHandler,
+    gen::{biome_test::BiomeTestGenerator, flat::FlatGenerator},
+    material::{color::Color, Material, PhysicsType},
+    Chunk, World,
+};
 
 #[derive(Debug)]
 pub enum WorldTreeNode<F, T> {
@@ -12,12 +21,223 @@ pub enum WorldTreeNode<F, T> {
     World(T),
 }
 
-#[derive(Debug, Deserialize)]
+impl<F, T> WorldTreeNode<F, T> {
+    /// Every world in this tree, depth first.
+    pub fn worlds(&self) -> Vec<&T> {
+        match self {
+            Self::Folder(_, children) => children.iter().flat_map(Self::worlds).collect(),
+            Self::World(w) => vec![w],
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct WorldMeta {
     pub name: String,
     pub last_played_version: String,
     pub save_format: String,
     pub last_played_time: toml::value::Datetime,
+    /// Worlds saved before this existed get [`WorldRules::default`].
+    #[serde(default)]
+    pub rules: WorldRules,
+    /// What new chunks are generated with, so they keep matching after a reload.
+    ///
+    /// Worlds saved before this existed don't have one, and keep whatever preset they are
+    ///   opened with ([`WorldPreset::Generated`] from the world list).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<WorldPreset>,
+}
+
+impl WorldMeta {
+    /// The meta for a world that was just created.
+    pub fn new(name: String, rules: WorldRules, preset: WorldPreset) -> Self {
+        Self {
+            name,
+            last_played_version: env!("CARGO_PKG_VERSION").to_string(),
+            save_format: "1".to_string(),
+            last_played_time: datetime_now(),
+            rules,
+            preset: Some(preset),
+        }
+    }
+}
+
+/// The current time in UTC, down to the second.
+fn datetime_now() -> toml::value::Datetime {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+
+    // days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    toml::value::Datetime {
+        date: Some(toml::value::Date {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+        }),
+        time: Some(toml::value::Time {
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+            nanosecond: 0,
+        }),
+        offset: Some(toml::value::Offset::Z),
+    }
+}
+
+/// What a world starts out as, picked when it's created and saved in the `[preset]` table
+///   of `world_info.toml`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorldPreset {
+    /// The normal generated terrain.
+    #[default]
+    Generated,
+    /// Nothing but air.
+    Empty,
+    /// Solid ground everywhere at or below `height` (so with a y of at least `height`), air above it.
+    Flat {
+        height: i64,
+        material: String,
+        color: Color,
+    },
+}
+
+impl WorldPreset {
+    /// A chunk handler using this preset's [`WorldGenerator`](super::gen::WorldGenerator).
+    pub fn chunk_handler<C: Chunk + Send + Sync + 'static>(
+        &self,
+        path: Option<PathBuf>,
+    ) -> ChunkHandler<C> {
+        match self {
+            Self::Generated => ChunkHandler::new(BiomeTestGenerator::new(), path),
+            Self::Empty => ChunkHandler::new(FlatGenerator::empty(), path),
+            Self::Flat { height, material, color } => {
+                let material = RegistryID::<Material>::from(material.as_str())
+                    .instance(PhysicsType::Solid, *color);
+                ChunkHandler::new(FlatGenerator::new(*height, material), path)
+            },
+        }
+    }
+}
+
+/// Simulation constants that belong to a world rather than to the player's [`Settings`](crate::game::common::Settings),
+///   saved in the `[rules]` table of `world_info.toml`.
+///
+/// Missing fields fall back to their defaults, so new rules don't break old saves.
+/// There is no fire, and temperatures are only drawn rather than simulated, so the only feature
+///   toggle is [`Self::freezing`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldRules {
+    /// Downwards acceleration of rigidbodies, see [`Physics::gravity`](super::physics::Physics::gravity).
+    ///
+    /// Particles and falling pixels don't use this, they keep falling at
+    ///   [`PARTICLE_GRAVITY`](super::particle::PARTICLE_GRAVITY) so they stay in step with each other.
+    pub gravity: f32,
+    /// How many steps each physics tick is split into.
+    pub physics_substeps: u8,
+    /// Chunk ticks per second in this world, see [`TickRates`](crate::game::common::TickRates).
+    /// `None` uses [`Settings::tick_speed`](crate::game::common::Settings::tick_speed).
+    pub tick_speed: Option<u16>,
+    /// Particle ticks per second in this world.
+    /// `None` uses [`Settings::tick_particles_speed`](crate::game::common::Settings::tick_particles_speed).
+    pub tick_particles_speed: Option<u16>,
+    /// Physics ticks per second in this world.
+    /// `None` uses [`Settings::tick_physics_speed`](crate::game::common::Settings::tick_physics_speed).
+    pub tick_physics_speed: Option<u16>,
+    /// Whether liquids freeze over, see [`Material::freeze`].
+    pub freezing: bool,
+    /// Whether hard rigidbody impacts can break terrain.
+    /// Both this and [`Settings::destructive_impacts`](crate::game::common::Settings::destructive_impacts) need to be on.
+    pub destructive_impacts: bool,
+    /// How many rigidbodies can be simulated at once. Past this, the oldest ones are settled back
+    ///   into the world, see [`World::recycle_rigidbodies`].
+    pub max_rigidbodies: usize,
+    /// The lowest row of chunks that is generated, everything below it is solid [`Self::floor_material`].
+    /// `None` lets the world go down forever.
+    pub floor_chunk: Option<i32>,
+    /// The highest row of chunks that is generated, nothing can go above it. `None` for no limit.
+    pub ceiling_chunk: Option<i32>,
+    pub floor_material: String,
+    pub floor_color: Color,
+    /// Limits the world to a rectangle of chunks, within [`Self::floor_chunk`] and [`Self::ceiling_chunk`].
+    /// `None` lets it go on forever sideways.
+    pub border: Option<BorderRules>,
+}
+
+/// A [`WorldBorder`] around a rectangle of chunks, saved in the `[rules.border]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BorderRules {
+    /// The top left chunk inside the border.
+    pub min: (i32, i32),
+    /// The bottom right chunk inside the border.
+    pub max: (i32, i32),
+    /// Whether past the border is solid like [`WorldBorder::barrier`], or empty like [`WorldBorder::open`].
+    pub solid: bool,
+}
+
+impl WorldRules {
+    /// The [`WorldBorder`] for [`Self::border`] and [`Self::vertical_border`], keeping the world inside both.
+    pub fn world_border(&self) -> Option<WorldBorder> {
+        let border = self.border.as_ref().map(|b| {
+            if b.solid {
+                WorldBorder::barrier(b.min, b.max)
+            } else {
+                WorldBorder::open(b.min, b.max)
+            }
+        });
+        match (border, self.vertical_border()) {
+            (Some(border), Some(vertical)) => Some(border.intersect(&vertical)),
+            (border, vertical) => border.or(vertical),
+        }
+    }
+
+    /// The [`WorldBorder`] for [`Self::floor_chunk`] and [`Self::ceiling_chunk`], if either is set.
+    pub fn vertical_border(&self) -> Option<WorldBorder> {
+        if self.floor_chunk.is_none() && self.ceiling_chunk.is_none() {
+            return None;
+        }
+
+        let floor = RegistryID::<Material>::from(self.floor_material.as_str())
+            .instance(PhysicsType::Solid, self.floor_color);
+        Some(WorldBorder::vertical(
+            self.ceiling_chunk,
+            self.floor_chunk,
+            floor,
+        ))
+    }
+}
+
+impl Default for WorldRules {
+    fn default() -> Self {
+        Self {
+            gravity: 3.0,
+            physics_substeps: 3,
+            tick_speed: None,
+            tick_particles_speed: None,
+            tick_physics_speed: None,
+            freezing: true,
+            destructive_impacts: true,
+            max_rigidbodies: 256,
+            floor_chunk: None,
+            ceiling_chunk: None,
+            floor_material: "bedrock".to_string(),
+            floor_color: Color::rgb(32, 32, 32),
+            border: None,
+        }
+    }
 }
 
 impl<C: Chunk + Send + Sync + 'static> World<C> {
@@ -36,6 +256,42 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
         Ok(WorldTreeNode::Folder(root, res))
     }
 
+    /// Finds the worlds in each of `roots` (see [`FileHelper::save_roots`](crate::game::common::FileHelper::save_roots)),
+    ///   each root being a folder in the returned tree.
+    ///
+    /// Roots that don't exist yet are skipped, as are ones that are the same folder as an earlier
+    ///   root written differently, like `saves` and `./saves/` (or `Saves` on Windows).
+    pub fn find_worlds(
+        roots: &[PathBuf],
+    ) -> Result<WorldTreeNode<PathBuf, PathBuf>, std::io::Error> {
+        let mut seen = Vec::new();
+        let mut res = Vec::new();
+        for root in roots {
+            if !root.is_dir() {
+                continue;
+            }
+            let canonical = fs::canonicalize(root)?;
+            if seen.contains(&canonical) {
+                continue;
+            }
+            seen.push(canonical);
+            res.push(Self::find_files(root.clone())?);
+        }
+
+        Ok(WorldTreeNode::Folder(PathBuf::new(), res))
+    }
+
+    /// A folder in `root` for a new world that isn't taken yet, like `world`, `world_2`, `world_3`...
+    pub fn new_world_dir(root: &Path) -> PathBuf {
+        let mut dir = root.join("world");
+        let mut i = 1;
+        while dir.exists() {
+            i += 1;
+            dir = root.join(format!("world_{i}"));
+        }
+        dir
+    }
+
     pub fn parse_file_tree_metas(
         tree: WorldTreeNode<PathBuf, PathBuf>,
     ) -> Result<WorldTreeNode<PathBuf, (PathBuf, WorldMeta)>, Box<dyn std::error::Error>> {
@@ -60,6 +316,14 @@ impl<C: Chunk + Send + Sync + 'static> World<C> {
         Ok(toml::from_str::<WorldMeta>(&fs::read_to_string(path)?)?)
     }
 
+    pub fn write_file_meta<P: AsRef<Path>>(
+        path: P,
+        meta: &WorldMeta,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, toml::to_string(meta)?)?;
+        Ok(())
+    }
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
         Self::create(Some(path.as_ref().to_path_buf()), None)
     }
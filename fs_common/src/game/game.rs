@@ -4,7 +4,7 @@ use crate::game::common::Settings;
 use super::common::world::Chunk;
 use super::common::{FileHelper, Registries};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub struct GameData<C: Chunk> {
     pub world: Option<World<C>>,
@@ -23,9 +23,115 @@ pub struct BuildData {
     pub git_hash: Option<&'static str>,
 }
 
+/// How many samples of [`ProcessStats`] history are kept. One sample is taken every
+/// [`Settings::stats_interval_ms`], so the history spans that many intervals.
+pub const PROCESS_HISTORY_LEN: usize = 60;
+
 pub struct ProcessStats {
     pub cpu_usage: Option<f32>,
     pub memory: Option<u64>,
+    pub cpu_history: [f32; PROCESS_HISTORY_LEN],
+    pub memory_history: [u64; PROCESS_HISTORY_LEN],
+}
+
+impl ProcessStats {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            cpu_usage: None,
+            memory: None,
+            cpu_history: [0.0; PROCESS_HISTORY_LEN],
+            memory_history: [0; PROCESS_HISTORY_LEN],
+        }
+    }
+
+    /// Sets the current values and pushes them onto the history.
+    pub fn record(&mut self, cpu_usage: f32, memory: u64) {
+        self.cpu_usage = Some(cpu_usage);
+        self.memory = Some(memory);
+
+        self.cpu_history.rotate_left(1);
+        self.cpu_history[PROCESS_HISTORY_LEN - 1] = cpu_usage;
+        self.memory_history.rotate_left(1);
+        self.memory_history[PROCESS_HISTORY_LEN - 1] = memory;
+    }
+}
+
+/// Formats a CPU usage percentage rounded to a whole number, eg. `"12%"`.
+pub fn format_cpu(usage: f32) -> String {
+    format!("{:.0}%", usage.max(0.0).round())
+}
+
+/// Formats a byte count with the largest (decimal) unit that keeps it above 1, eg. `"52.4 MB"`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Values for the placeholders in [`Settings::window_title`].
+pub struct TitleStats<'a> {
+    pub fps: u16,
+    pub net_mode: &'a str,
+    pub world_name: &'a str,
+    pub tick_time: u32,
+    pub process: &'a ProcessStats,
+}
+
+/// Fills in a window title template.
+///
+/// Supported placeholders are `{fps}`, `{net_mode}`, `{world}`, `{tick}`, `{cpu}` and `{memory}`.
+/// Unknown placeholders and unmatched braces are left in the title as-is.
+pub fn format_title(template: &str, stats: &TitleStats) -> String {
+    let mut title = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        title.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let value = match &rest[1..end] {
+            "fps" => stats.fps.to_string(),
+            "net_mode" => stats.net_mode.to_string(),
+            "world" => stats.world_name.to_string(),
+            "tick" => stats.tick_time.to_string(),
+            "cpu" => stats
+                .process
+                .cpu_usage
+                .map_or_else(|| "n/a".to_string(), format_cpu),
+            "memory" => stats
+                .process
+                .memory
+                .map_or_else(|| "n/a".to_string(), format_bytes),
+            _ => {
+                // not a placeholder, keep the brace and look for the next one after it
+                title.push('{');
+                rest = &rest[1..];
+                continue;
+            },
+        };
+
+        title.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+
+    title.push_str(rest);
+    title
 }
 
 pub struct FPSCounter {
@@ -39,6 +145,28 @@ pub struct FPSCounter {
     pub tick_physics_times: [f32; 200],
 }
 
+impl FPSCounter {
+    /// Updates [`Self::display_value`] and [`Self::tick_display_value`] to the frames and ticks
+    ///   per second counted since the last update, if at least `interval` has passed since then.
+    ///
+    /// Returns whether they were updated, which is when anything else sampled at the same rate
+    ///   (like [`ProcessStats`]) should be updated too. The per-frame timing buffers aren't touched.
+    pub fn sample(&mut self, now: Instant, interval: Duration) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_update);
+        if elapsed < interval || elapsed.is_zero() {
+            return false;
+        }
+
+        let per_second = |n: u16| (f64::from(n) / elapsed.as_secs_f64()).round() as u16;
+        self.display_value = per_second(self.frames);
+        self.tick_display_value = per_second(self.ticks);
+        self.frames = 0;
+        self.ticks = 0;
+        self.last_update = now;
+        true
+    }
+}
+
 impl<C: Chunk + Send + Sync + 'static> GameData<C> {
     #[profiling::function]
     pub fn new(file_helper: FileHelper, build_data: BuildData) -> Self {
@@ -56,7 +184,7 @@ impl<C: Chunk + Send + Sync + 'static> GameData<C> {
                 tick_times: [0.0; 200],
                 tick_physics_times: [0.0; 200],
             },
-            process_stats: ProcessStats { cpu_usage: None, memory: None },
+            process_stats: ProcessStats::new(),
             settings: Settings::default(),
             registries: Arc::new(Registries::init(&file_helper)),
             file_helper,
@@ -64,3 +192,124 @@ impl<C: Chunk + Send + Sync + 'static> GameData<C> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        format_bytes, format_cpu, format_title, FPSCounter, ProcessStats, TitleStats,
+        PROCESS_HISTORY_LEN,
+    };
+
+    #[test]
+    fn fps_sample_interval() {
+        let start = Instant::now();
+        let mut counter = FPSCounter {
+            frames: 0,
+            last_update: start,
+            display_value: 0,
+            ticks: 0,
+            tick_display_value: 0,
+            frame_times: [0.0; 200],
+            tick_times: [0.0; 200],
+            tick_physics_times: [0.0; 200],
+        };
+        let interval = Duration::from_millis(250);
+
+        // 60 fps, 30 tps
+        let mut samples = 0;
+        for frame in 1..=120 {
+            let now = start + Duration::from_secs_f64(f64::from(frame) / 60.0);
+            counter.frames += 1;
+            if frame % 2 == 0 {
+                counter.ticks += 1;
+            }
+            if counter.sample(now, interval) {
+                samples += 1;
+                assert!((59..=61).contains(&counter.display_value));
+                assert!((28..=32).contains(&counter.tick_display_value));
+            }
+        }
+        // 2 seconds at every 250ms (give or take a frame)
+        assert!((7..=8).contains(&samples), "{samples}");
+
+        // not long enough yet
+        let now = start + Duration::from_secs(2);
+        counter.last_update = now;
+        counter.frames = 1;
+        assert!(!counter.sample(now + Duration::from_millis(100), interval));
+        assert_eq!(counter.frames, 1);
+
+        // a longer interval averages over more frames
+        counter.frames = 30;
+        assert!(counter.sample(now + Duration::from_millis(500), Duration::from_millis(500)));
+        assert_eq!(counter.display_value, 60);
+        assert_eq!(counter.frames, 0);
+    }
+
+    #[test]
+    fn title_substitution() {
+        let mut process = ProcessStats::new();
+        process.record(12.3, 52_400_000);
+        let stats = TitleStats {
+            fps: 60,
+            net_mode: "Local",
+            world_name: "my world",
+            tick_time: 1234,
+            process: &process,
+        };
+
+        assert_eq!(
+            format_title("FallingSandRust ({fps} FPS) ({net_mode})", &stats),
+            "FallingSandRust (60 FPS) (Local)"
+        );
+        assert_eq!(
+            format_title("{world} @ {tick} | {cpu} {memory}", &stats),
+            "my world @ 1234 | 12% 52.4 MB"
+        );
+
+        // unknown placeholders and stray braces are kept
+        assert_eq!(
+            format_title("{fps} {nope} {{fps}} {fps", &stats),
+            "60 {nope} {60} {fps"
+        );
+        assert_eq!(format_title("}{", &stats), "}{");
+
+        let process = ProcessStats::new();
+        let stats = TitleStats { process: &process, ..stats };
+        assert_eq!(format_title("{cpu}/{memory}", &stats), "n/a/n/a");
+    }
+
+    #[test]
+    fn process_stat_formatting() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(999), "999 B");
+        assert_eq!(format_bytes(1000), "1.0 KB");
+        assert_eq!(format_bytes(52_400_000), "52.4 MB");
+        assert_eq!(format_bytes(1_260_000_000), "1.3 GB");
+        assert_eq!(format_bytes(u64::MAX), "18446744.1 TB");
+
+        assert_eq!(format_cpu(0.0), "0%");
+        assert_eq!(format_cpu(12.4), "12%");
+        assert_eq!(format_cpu(12.6), "13%");
+        assert_eq!(format_cpu(99.7), "100%");
+        assert_eq!(format_cpu(-0.2), "0%");
+    }
+
+    #[test]
+    fn process_history() {
+        let mut process = ProcessStats::new();
+        process.record(10.0, 100);
+        process.record(20.0, 200);
+
+        assert_eq!(process.cpu_usage, Some(20.0));
+        assert_eq!(process.memory, Some(200));
+        assert_eq!(process.cpu_history[PROCESS_HISTORY_LEN - 2..], [10.0, 20.0]);
+        assert_eq!(
+            process.memory_history[PROCESS_HISTORY_LEN - 2..],
+            [100, 200]
+        );
+        assert_eq!(process.memory_history[0], 0);
+    }
+}
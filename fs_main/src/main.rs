@@ -46,7 +46,8 @@ pub fn main() -> Result<(), String> {
 
     let cl_args = CLArgs::parse_args();
 
-    let file_helper = FileHelper::new(cl_args.game_dir.clone(), cl_args.assets_dir.clone());
+    let file_helper = FileHelper::new(cl_args.game_dir.clone(), cl_args.assets_dir.clone())
+        .with_save_roots(cl_args.saves_dirs.clone());
 
     if !file_helper.game_path("").exists() {
         info!("game dir missing, creating it...");
@@ -2,9 +2,8 @@ use clap::error::ContextKind;
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
 use log::{debug, error, info, warn};
 use std::{
-    io::{Read, Write},
+    io::{ErrorKind, Read, Write},
     net::{SocketAddr, TcpListener, TcpStream},
-    ops::Add,
     time::{Duration, Instant},
 };
 use tui::{
@@ -18,19 +17,50 @@ use tui::{
 use tui_logger::{TuiLoggerSmartWidget, TuiWidgetState};
 
 use super::world::ServerChunk;
+use chunksystem::ChunkQuery;
 use fs_common::game::{
     common::{
         cli::{CLArgs, CLSubcommand},
         commands::CommandHandler,
-        networking::{Packet, PacketType},
-        world::{Chunk, ChunkState, CHUNK_AREA},
-        FileHelper,
+        networking::{chunk_checksum, write_packet, Packet, PacketAssembler, PacketType},
+        world::{
+            chunk_handler::ZoneRadii,
+            entity::{Persistent, Player},
+            BorderRules, Chunk, ChunkState, CHUNK_AREA,
+        },
+        FileHelper, TickScheduler,
     },
     BuildData, GameData,
 };
+use specs::{Entity, Join, WorldExt};
+
+/// How often (in ticks) clients get sent checksums of the active chunks that didn't change.
+///
+/// Must be a multiple of 4, since chunks are only synced every 4 ticks.
+const CHUNK_CHECKSUM_INTERVAL: u32 = 64;
 
 pub struct ServerGame(pub GameData<ServerChunk>);
 
+/// Moves everything that has arrived on a nonblocking `stream` so far into `assembler`,
+///   without waiting for the rest of a packet.
+///
+/// Returns `Ok(false)` once the other end has closed the connection.
+fn read_available(
+    stream: &mut impl Read,
+    assembler: &mut PacketAssembler,
+) -> std::io::Result<bool> {
+    let mut buf = [0; 16 * 1024];
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) => return Ok(false),
+            Ok(read) => assembler.push(&buf[..read]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(true),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 impl ServerGame {
     pub fn new(file_helper: FileHelper, build_data: BuildData) -> Self {
         Self(GameData::new(file_helper, build_data))
@@ -60,7 +90,35 @@ impl ServerGame {
 
         term.clear().unwrap();
 
-        let CLSubcommand::Server { port } = args.subcommand.as_ref().unwrap();
+        let CLSubcommand::Server {
+            port,
+            active_radius,
+            load_radius,
+            unload_radius,
+            border,
+            open_border,
+        } = args.subcommand.as_ref().unwrap();
+
+        if let Some(w) = &mut self.0.world {
+            let default = ZoneRadii::default();
+            w.chunk_handler.zone_radii = ZoneRadii::new(
+                active_radius.unwrap_or(default.active),
+                load_radius.unwrap_or(default.load),
+                unload_radius.unwrap_or(default.unload),
+            )?;
+
+            if let Some(border) = border {
+                let &[min_x, min_y, max_x, max_y] = border.as_slice() else {
+                    return Err("--border needs exactly 4 values".into());
+                };
+                w.rules.border = Some(BorderRules {
+                    min: (min_x, min_y),
+                    max: (max_x, max_y),
+                    solid: !open_border,
+                });
+                w.apply_rules();
+            }
+        }
         let net_listener =
             TcpListener::bind(format!("127.0.0.1:{port}")).map_err(|e| e.to_string())?;
         net_listener
@@ -69,17 +127,17 @@ impl ServerGame {
 
         info!(target: "", "Server listening on port {}...", port);
 
-        let mut connections: Vec<(TcpStream, SocketAddr)> = Vec::new();
+        // each connection gets its own player, which edits it sends are checked against,
+        //   and its own assembler, since packets can arrive a few bytes at a time
+        let mut connections: Vec<(TcpStream, SocketAddr, Option<Entity>, PacketAssembler)> =
+            Vec::new();
 
-        let mut prev_tick_time = std::time::Instant::now();
-        let mut prev_tick_physics_time = std::time::Instant::now();
+        let mut tick_scheduler = TickScheduler::default();
+        let mut last_tick_schedule = Instant::now();
 
         let mut last_frame = Instant::now();
         let mut counter_last_frame = Instant::now();
 
-        let mut do_tick_next = false;
-        let mut do_tick_physics_next = false;
-
         let mut physics_ticks = 0;
 
         let mut input: String = String::new();
@@ -117,59 +175,153 @@ impl ServerGame {
                     }
                 }
                 stream.set_nonblocking(true).unwrap();
-                connections.push((stream, addr));
+                // a new one is spawned for every connection, so it isn't saved with the world
+                let player = self.0.world.as_mut().map(|w| {
+                    let player = Player::create_and_add(w);
+                    w.ecs.write_storage::<Persistent>().remove(player);
+                    player
+                });
+                connections.push((stream, addr, player, PacketAssembler::default()));
             }
 
-            for c in &mut connections {
-                let mut buf = [0; 4];
-                if c.0.read_exact(&mut buf).is_ok() {
-                    let size: u32 = bincode::deserialize(&buf).unwrap();
-                    debug!("Incoming packet, size = {}.", size);
-
-                    let mut buf = Vec::with_capacity(size as usize);
-
-                    debug!("read_to_end...");
-                    match std::io::Read::by_ref(&mut c.0)
-                        .take(u64::from(size))
-                        .read_to_end(&mut buf)
-                    {
-                        Ok(_) => {
-                            debug!("Read {} bytes.", buf.len());
-                            let p: Packet =
-                                bincode::deserialize(&buf).expect("Failed to deserialize packet.");
-                            debug!(
-                                "Recieved packet from {:?}: {:?}",
-                                c.1,
-                                match p.packet_type {
-                                    PacketType::SyncChunkPacket { .. } => "SyncChunkPacket",
-                                    PacketType::SyncLiquidFunPacket { .. } => "SyncLiquidFunPacket",
-                                }
-                            );
+            let mut closed = vec![];
+            for (i, c) in connections.iter_mut().enumerate() {
+                let mut open = read_available(&mut c.0, &mut c.3).unwrap_or_else(|e| {
+                    warn!("Lost connection to {:?}: {}", c.1, e);
+                    false
+                });
+
+                let mut packets = vec![];
+                loop {
+                    match c.3.next_body() {
+                        Ok(Some(body)) => match bincode::deserialize::<Packet>(&body) {
+                            Ok(packet) => packets.push(packet),
+                            Err(e) => warn!("Dropped a bad packet from {:?}: {}", c.1, e),
                         },
+                        Ok(None) => break,
                         Err(e) => {
-                            // TODO: this needs to be handled correctly like in client::game
-                            //         since when read_to_end fails, it can still have read some of the bytes
-                            panic!("read_to_end failed: {e}");
+                            // there's no telling where the next packet starts
+                            error!("Bad packet stream from {:?}, disconnecting: {}", c.1, e);
+                            open = false;
+                            break;
                         },
                     }
                 }
+                if !open {
+                    closed.push(i);
+                }
+
+                for p in packets {
+                    debug!(
+                        "Recieved packet from {:?}: {:?}",
+                        c.1,
+                        match p.packet_type {
+                            PacketType::SyncChunkPacket { .. } => "SyncChunkPacket",
+                            PacketType::SyncLiquidFunPacket { .. } => "SyncLiquidFunPacket",
+                            PacketType::ChunkChecksumPacket { .. } => "ChunkChecksumPacket",
+                            PacketType::RequestChunkSyncPacket { .. } => "RequestChunkSyncPacket",
+                            PacketType::EditPixelsPacket { .. } => "EditPixelsPacket",
+                            PacketType::EditAckPacket { .. } => "EditAckPacket",
+                        }
+                    );
+
+                    // the client's copy of the chunk is out of date, send all of it
+                    if let PacketType::RequestChunkSyncPacket { chunk_x, chunk_y } = p.packet_type {
+                        let chunk = self
+                            .0
+                            .world
+                            .as_ref()
+                            .and_then(|w| w.chunk_handler.manager.chunk_at((chunk_x, chunk_y)));
+                        if let Some((ch, pixels)) =
+                            chunk.and_then(|ch| ch.pixels().as_ref().map(|px| (ch, px)))
+                        {
+                            let packet = Packet {
+                                packet_type: PacketType::SyncChunkPacket {
+                                    chunk_x,
+                                    chunk_y,
+                                    pixels: pixels.to_vec(),
+                                    colors: ch.colors().to_vec(),
+                                },
+                            };
+                            c.0.set_nonblocking(false).unwrap();
+                            if let Err(e) = write_packet(&mut c.0, &packet) {
+                                error!("Failed to resync chunk for {:?}: {}", c.1, e);
+                            }
+                            c.0.set_nonblocking(true).unwrap();
+                        }
+                    }
+
+                    // the client already made these edits on its side, apply them here and
+                    //   send back how the chunks ended up so it can fix anything that's different
+                    if let PacketType::EditPixelsPacket { id, pixels } = p.packet_type {
+                        if let (Some(w), Some(player)) = (&mut self.0.world, c.2) {
+                            // edits the server refuses are still answered, the chunks it
+                            //   sends back correct the client
+                            let chunks = w
+                                .apply_remote_edit(
+                                    player,
+                                    pixels,
+                                    &self.0.settings,
+                                    &self.0.registries,
+                                )
+                                .unwrap_or_else(|e| {
+                                    error!("Failed to apply edit from {:?}: {}", c.1, e);
+                                    vec![]
+                                });
+
+                            let mut packets =
+                                vec![Packet { packet_type: PacketType::EditAckPacket { id } }];
+                            for (chunk_x, chunk_y) in chunks {
+                                let chunk = w.chunk_handler.manager.chunk_at((chunk_x, chunk_y));
+                                if let Some((ch, pixels)) =
+                                    chunk.and_then(|ch| ch.pixels().as_ref().map(|px| (ch, px)))
+                                {
+                                    packets.push(Packet {
+                                        packet_type: PacketType::SyncChunkPacket {
+                                            chunk_x,
+                                            chunk_y,
+                                            pixels: pixels.to_vec(),
+                                            colors: ch.colors().to_vec(),
+                                        },
+                                    });
+                                }
+                            }
+
+                            c.0.set_nonblocking(false).unwrap();
+                            for packet in &packets {
+                                if let Err(e) = write_packet(&mut c.0, packet) {
+                                    error!("Failed to answer edit from {:?}: {}", c.1, e);
+                                    break;
+                                }
+                            }
+                            c.0.set_nonblocking(true).unwrap();
+                        }
+                    }
+                }
+            }
+
+            for i in closed.into_iter().rev() {
+                let (_, addr, player, _) = connections.remove(i);
+                info!("Connection closed: {}", addr);
+                if let (Some(w), Some(player)) = (&mut self.0.world, player) {
+                    if let Err(e) = w.ecs.delete_entity(player) {
+                        error!("Failed to remove the player of {:?}: {}", addr, e);
+                    }
+                }
             }
 
             let now = std::time::Instant::now();
 
             // tick
 
-            let can_tick = self.0.settings.tick;
+            let ticks = tick_scheduler.advance(
+                now.saturating_duration_since(last_tick_schedule),
+                &self.0.settings,
+                self.0.world.as_ref().map(|w| &w.rules),
+            );
+            last_tick_schedule = now;
 
-            if do_tick_next && can_tick {
-                if now.saturating_duration_since(prev_tick_time).as_millis() > 500 {
-                    warn!(target: "", "50+ ms behind, skipping some ticks to catch up...");
-                    prev_tick_time = now;
-                } else {
-                    prev_tick_time = prev_tick_time.add(Duration::from_nanos(
-                        1_000_000_000 / u64::from(self.0.settings.tick_speed),
-                    ));
-                }
+            for _ in 0..ticks.chunks {
                 let st = Instant::now();
                 self.tick();
 
@@ -195,11 +347,11 @@ impl ServerGame {
                                         CHUNK_AREA
                                     );
 
-                                    if colors_vec.len() != CHUNK_AREA * 4 {
+                                    if colors_vec.len() != CHUNK_AREA {
                                         panic!(
                                             "Almost sent wrong size colors Vec: {} (expected {})",
                                             colors_vec.len(),
-                                            CHUNK_AREA * 4
+                                            CHUNK_AREA
                                         );
                                     }
 
@@ -225,6 +377,31 @@ impl ServerGame {
 
                                     // println!("Wrote SyncChunkPacket");
                                 }
+                            } else if ci.1.state() == ChunkState::Active
+                                && !ci.1.dirty
+                                && self.0.tick_time % CHUNK_CHECKSUM_INTERVAL == 0
+                            {
+                                // lets clients find out if they missed a change, without sending the whole chunk
+                                if let Some(pixels) = ci.1.pixels() {
+                                    let (chunk_x, chunk_y) = *ci.0;
+                                    let packet = Packet {
+                                        packet_type: PacketType::ChunkChecksumPacket {
+                                            chunk_x,
+                                            chunk_y,
+                                            checksum: chunk_checksum(&pixels[..]),
+                                        },
+                                    };
+                                    for c in &mut connections {
+                                        c.0.set_nonblocking(false).unwrap();
+                                        if let Err(e) = write_packet(&mut c.0, &packet) {
+                                            error!(
+                                                "Failed to send chunk checksum to {:?}: {}",
+                                                c.1, e
+                                            );
+                                        }
+                                        c.0.set_nonblocking(true).unwrap();
+                                    }
+                                }
                             }
                         }
                     }
@@ -263,6 +440,41 @@ impl ServerGame {
                                             if m.subcommand_matches("shutdown").is_some() {
                                                 break 'mainLoop;
                                             }
+                                            if let (Some(m), Some(w)) = (
+                                                m.subcommand_matches("generate"),
+                                                &mut self.0.world,
+                                            ) {
+                                                let x = *m.get_one::<i64>("x").unwrap();
+                                                let y = *m.get_one::<i64>("y").unwrap();
+                                                let radius = *m.get_one::<u16>("radius").unwrap();
+                                                let n = w.force_generate_around(
+                                                    x,
+                                                    y,
+                                                    radius,
+                                                    &self.0.registries,
+                                                );
+                                                info!(target: "", "Generated {n} chunks");
+                                            }
+                                            if let (Some(m), Some(w)) =
+                                                (m.subcommand_matches("tp"), &mut self.0.world)
+                                            {
+                                                let x = *m.get_one::<f64>("x").unwrap();
+                                                let y = *m.get_one::<f64>("y").unwrap();
+                                                let players: Vec<_> = (
+                                                    &w.ecs.entities(),
+                                                    &w.ecs.read_storage::<Player>(),
+                                                )
+                                                    .join()
+                                                    .map(|(e, _)| e)
+                                                    .collect();
+                                                for player in players {
+                                                    if let Err(e) =
+                                                        w.teleport(player, x, y, &self.0.registries)
+                                                    {
+                                                        error!(target: "", "{e}");
+                                                    }
+                                                }
+                                            }
                                         },
                                         Err(e)
                                             if e.kind()
@@ -302,30 +514,21 @@ impl ServerGame {
 
                 self.0.fps_counter.ticks += 1;
             }
-            do_tick_next = can_tick
-                && now.saturating_duration_since(prev_tick_time).as_nanos()
-                    > 1_000_000_000 / u128::from(self.0.settings.tick_speed); // intended is 30 ticks per second
 
-            // tick liquidfun
+            // tick particles
 
-            let can_tick = self.0.settings.tick_physics;
-
-            if do_tick_physics_next && can_tick {
-                if now
-                    .saturating_duration_since(prev_tick_physics_time)
-                    .as_millis()
-                    > 500
-                {
-                    warn!(target: "", "liquidfun 50+ ms behind, skipping some ticks to catch up...");
-                    prev_tick_physics_time = now;
-                } else {
-                    prev_tick_physics_time = prev_tick_physics_time.add(Duration::from_nanos(
-                        1_000_000_000 / u64::from(self.0.settings.tick_physics_speed),
-                    ));
+            if let Some(w) = &mut self.0.world {
+                for _ in 0..ticks.particles {
+                    w.tick_particles(&self.0.settings, &self.0.registries);
                 }
+            }
+
+            // tick liquidfun
+
+            for _ in 0..ticks.physics {
                 if let Some(w) = &mut self.0.world {
                     let st = Instant::now();
-                    w.tick_physics(&self.0.settings);
+                    w.tick_physics(&self.0.settings, &self.0.registries);
                     physics_ticks += 1;
 
                     if physics_ticks % 10 == 0 {
@@ -370,11 +573,6 @@ impl ServerGame {
                         Instant::now().saturating_duration_since(st).as_nanos() as f32;
                 }
             }
-            do_tick_physics_next = can_tick
-                && now
-                    .saturating_duration_since(prev_tick_physics_time)
-                    .as_nanos()
-                    > 1_000_000_000 / u128::from(self.0.settings.tick_physics_speed); // intended is 60 ticks per second
 
             // render
 
@@ -382,20 +580,14 @@ impl ServerGame {
             let delta = now.saturating_duration_since(last_frame);
             last_frame = now;
             if let Some(w) = &mut self.0.world {
-                w.frame(delta); // this delta is more accurate than the one based on counter_last_frame
+                w.frame(delta, None); // this delta is more accurate than the one based on counter_last_frame
             }
 
             self.0.fps_counter.frames += 1;
-            if now
-                .saturating_duration_since(self.0.fps_counter.last_update)
-                .as_millis()
-                >= 1000
-            {
-                self.0.fps_counter.display_value = self.0.fps_counter.frames;
-                self.0.fps_counter.frames = 0;
-                self.0.fps_counter.tick_display_value = self.0.fps_counter.ticks;
-                self.0.fps_counter.ticks = 0;
-                self.0.fps_counter.last_update = now;
+            if self.0.fps_counter.sample(
+                now,
+                Duration::from_millis(self.0.settings.stats_interval_ms.into()),
+            ) {
 
                 // let nums: Vec<f32> = self.0.fps_counter.frame_times.iter().filter(|n| **n != 0.0).map(|f| *f).collect();
                 // let avg_mspf: f32 = nums.iter().map(|f| f / 1_000_000.0).sum::<f32>() / nums.len() as f32;
@@ -418,7 +610,7 @@ impl ServerGame {
 
             profiling::finish_frame!();
             // sleep
-            if !do_tick_next {
+            if ticks.chunks == 0 {
                 profiling::scope!("sleep");
                 // let now = Instant::now();
 
@@ -604,3 +796,58 @@ impl ServerGame {
         frame.render_widget(paragraph, main_chunks[1]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        net::{TcpListener, TcpStream},
+    };
+
+    use fs_common::game::common::networking::{write_packet, Packet, PacketAssembler, PacketType};
+
+    use super::read_available;
+
+    #[test]
+    fn reads_packets_that_arrive_in_pieces() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        server.set_nonblocking(true).unwrap();
+
+        let mut bytes = vec![];
+        write_packet(
+            &mut bytes,
+            &Packet {
+                packet_type: PacketType::RequestChunkSyncPacket { chunk_x: 3, chunk_y: -2 },
+            },
+        )
+        .unwrap();
+
+        let mut assembler = PacketAssembler::default();
+        assert!(read_available(&mut server, &mut assembler).unwrap());
+
+        // half a packet isn't a packet yet
+        let (first, rest) = bytes.split_at(bytes.len() / 2);
+        client.write_all(first).unwrap();
+        assert!(read_available(&mut server, &mut assembler).unwrap());
+        assert_eq!(assembler.next_body().unwrap(), None);
+
+        client.write_all(rest).unwrap();
+        let body = loop {
+            assert!(read_available(&mut server, &mut assembler).unwrap());
+            if let Some(body) = assembler.next_body().unwrap() {
+                break body;
+            }
+        };
+        let packet: Packet = bincode::deserialize(&body).unwrap();
+        assert!(matches!(
+            packet.packet_type,
+            PacketType::RequestChunkSyncPacket { chunk_x: 3, chunk_y: -2 }
+        ));
+
+        // and a closed connection is noticed instead of read forever
+        drop(client);
+        while read_available(&mut server, &mut assembler).unwrap() {}
+    }
+}
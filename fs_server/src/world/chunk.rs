@@ -2,6 +2,7 @@ use fs_common::game::common::world::chunk_data::CommonChunkData;
 use fs_common::game::common::world::chunk_data::SidedChunkData;
 use fs_common::game::common::world::chunk_index::ChunkLocalIndex;
 use fs_common::game::common::world::chunk_index::ChunkLocalPosition;
+use fs_common::game::common::world::flow::FlowBuffer;
 use fs_common::game::common::world::material::color::Color;
 use fs_common::game::common::world::material::MaterialInstance;
 use fs_common::game::common::world::mesh;
@@ -13,8 +14,10 @@ use fs_common::game::common::world::ChunkRigidBodyState;
 use fs_common::game::common::world::ChunkState;
 use fs_common::game::common::world::SidedChunk;
 use fs_common::game::common::world::CHUNK_AREA;
-use fs_common::game::common::world::CHUNK_SIZE;
+use fs_common::game::common::world::CHUNK_HEIGHT;
+use fs_common::game::common::world::CHUNK_WIDTH;
 use fs_common::game::common::Rect;
+use std::time::Duration;
 
 pub struct ServerChunk {
     pub data: CommonChunkData<Self>,
@@ -160,6 +163,30 @@ impl Chunk for ServerChunk {
         &self.data.background
     }
 
+    fn flow_mut(&mut self) -> &mut Option<Box<FlowBuffer>> {
+        &mut self.data.flow
+    }
+
+    fn flow(&self) -> &Option<Box<FlowBuffer>> {
+        &self.data.flow
+    }
+
+    fn sim_time(&self) -> Option<Duration> {
+        self.data.sim_time
+    }
+
+    fn set_sim_time(&mut self, time: Option<Duration>) {
+        self.data.sim_time = time;
+    }
+
+    fn revealed(&self) -> bool {
+        self.data.revealed
+    }
+
+    fn set_revealed(&mut self, revealed: bool) {
+        self.data.revealed = revealed;
+    }
+
     fn set_background_pixel_colors(&mut self, colors: Box<[Color; CHUNK_AREA]>) {
         self.background_data = colors;
     }
@@ -183,8 +210,11 @@ impl Chunk for ServerChunk {
 
         let vs: Vec<f64> = mesh::pixels_to_valuemap(self.data.pixels.as_ref().unwrap().as_ref());
 
-        let generated =
-            mesh::generate_mesh_only_simplified(&vs, u32::from(CHUNK_SIZE), u32::from(CHUNK_SIZE));
+        let generated = mesh::generate_mesh_only_simplified(
+            &vs,
+            u32::from(CHUNK_WIDTH),
+            u32::from(CHUNK_HEIGHT),
+        );
 
         self.data.mesh_simplified = generated.ok();
 
@@ -5,15 +5,55 @@ pub use chunk::*;
 #[cfg(test)]
 mod tests {
     use chunksystem::ChunkQuery;
+    use fs_common::game::common::registry::RegistryID;
+    use fs_common::game::common::world::border::WorldBorder;
+    use fs_common::game::common::world::buoyancy::LIQUID_DENSITY;
     use fs_common::game::common::world::chunk_access::FSChunkAccess;
-    use fs_common::game::common::world::chunk_handler::{ChunkHandler, ChunkTickContext};
-    use fs_common::game::common::world::physics::Physics;
-    use fs_common::game::common::world::{self, Loader, Position};
-    use fs_common::game::common::Settings;
-    use fs_common::game::common::{FileHelper, Registries};
+    use fs_common::game::common::world::chunk_handler::{
+        ChunkHandler, ChunkTickContext, ZoneRadii,
+    };
+    use fs_common::game::common::world::destroy::destroy_pixel;
+    use fs_common::game::common::world::edit_events::{WorldEditCause, WorldEditEvents};
+    use fs_common::game::common::world::entity::{
+        grapple_anchor_holds, grapple_hook_step, spawn_player, GameEntity, Hitbox, Inventory,
+        Persistent, PhysicsEntity, Player, UpdatePhysicsEntities,
+    };
+    use fs_common::game::common::world::flow::{FLOW_DECAY, FLOW_MOVED};
+    use fs_common::game::common::world::journal::{
+        self, journal_path, JournalEntry, JOURNAL_SNAPSHOT_INTERVAL,
+    };
+    use fs_common::game::common::world::material::color::Color;
+    use fs_common::game::common::world::material::placer::{
+        self, MaterialPlacer, MaterialPlacerMeta,
+    };
+    use fs_common::game::common::world::material::{self, MaterialInstance, PhysicsType};
+    use fs_common::game::common::world::particle::{
+        Particle, ParticleInteractions, ParticleSystem, UpdateParticles,
+    };
+    use fs_common::game::common::world::physics::{Physics, PHYSICS_SCALE};
+    use fs_common::game::common::world::settle_events::{SettleEvent, SettleEvents};
+    use fs_common::game::common::world::{
+        self, AutoTarget, BorderRules, Camera, Chunk, ChunkState, Loader, Position,
+        RigidBodyComponent, Target, TargetStyle, Velocity, World, WorldPreset, WorldRules,
+        CHUNK_AREA, CHUNK_HEIGHT, CHUNK_WIDTH, TELEPORT_GENERATE_RADIUS,
+    };
+    use fs_common::game::common::{FileHelper, Rect, Registries};
+    use fs_common::game::common::{Settings, TickRates};
 
+    use fs_common::game::common::world::gen::biome_test::BiomeTestGenerator;
+    use fs_common::game::common::world::gen::structure::piece::{
+        StructureNodeConfig, StructureNodeLocalPlacement, StructurePiece,
+    };
+    use fs_common::game::common::world::gen::structure::pool::StructurePool;
+    use fs_common::game::common::world::gen::structure::{
+        Direction, StructureNode, UpdateStructureNodes,
+    };
     use fs_common::game::common::world::gen::TestGenerator;
-    use specs::{Builder, WorldExt};
+    use fs_common::game::common::world::material::buf::MaterialBuf;
+    use fs_common::game::common::world::rigidbody::FSRigidBody;
+    use rapier2d::na::Vector2;
+    use rapier2d::prelude::{ColliderBuilder, RigidBodyBuilder};
+    use specs::{Builder, Join, RunNow, WorldExt};
 
     use crate::world::ServerChunk;
 
@@ -125,6 +165,63 @@ mod tests {
         assert!(!ch.is_chunk_loaded((-3, 2)));
     }
 
+    #[test]
+    fn force_generate_radius() {
+        let mut scene = TestScene::empty();
+        let registries = scene.registries.clone();
+
+        let center = (5, -4);
+        assert_eq!(
+            scene
+                .ch
+                .force_generate(center, 1, 2, &registries, &mut scene.ecs),
+            9
+        );
+
+        let has_terrain =
+            |pixels: &[MaterialInstance]| pixels.iter().any(|p| !p.is_empty(&registries.materials));
+        let mut terrain_chunks = 0;
+        for cy in -5..=-3 {
+            for cx in 4..=6 {
+                // every chunk in range is finished, not just some of them
+                let chunk = scene.ch.manager.chunk_at((cx, cy)).unwrap();
+                assert_eq!(chunk.state(), ChunkState::Cached, "{cx}, {cy}");
+                assert!(chunk.pixels().is_some(), "{cx}, {cy}");
+                let pixels = chunk.pixels().as_ref().unwrap();
+
+                // the same as if it was generated normally
+                let (expected, ..) = scene
+                    .ch
+                    .generator
+                    .generate_buffers((cx, cy), 2, &registries);
+                assert!(pixels[..] == expected[..]);
+
+                // chunks the generator put terrain in actually have it
+                if has_terrain(&expected[..]) {
+                    assert!(has_terrain(&pixels[..]), "{cx}, {cy}");
+                    terrain_chunks += 1;
+                }
+            }
+        }
+        assert!(terrain_chunks > 0);
+        // neighbors needed for populating are only partly generated
+        assert!(matches!(
+            scene.ch.manager.chunk_at((3, -4)).unwrap().state(),
+            ChunkState::Generating(_)
+        ));
+
+        // generated chunks aren't generated again
+        let marker = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        scene.ch.set_pixel(500, -400, marker.clone()).unwrap();
+        assert_eq!(
+            scene
+                .ch
+                .force_generate(center, 1, 2, &registries, &mut scene.ecs),
+            0
+        );
+        assert_eq!(scene.ch.pixel(500, -400).unwrap(), &marker);
+    }
+
     #[test]
     fn zones() {
         let ch: ChunkHandler<ServerChunk> =
@@ -140,4 +237,3204 @@ mod tests {
         assert!(active.width() < load.width() && active.height() < load.height());
         assert!(load.width() < unload.width() && load.height() < unload.height());
     }
+
+    #[test]
+    fn zones_without_renderer() {
+        let mut ch: ChunkHandler<ServerChunk> =
+            ChunkHandler::<ServerChunk>::new(TestGenerator::new(), None);
+        ch.screen_size = (200, 100);
+        ch.zone_radii = ZoneRadii::new(10, 50, 100).unwrap();
+
+        let zones = ch.zones_at((0.0, 0.0));
+        assert_eq!((zones.screen.x1, zones.screen.y1), (-100, -50));
+        assert_eq!((zones.screen.x2, zones.screen.y2), (100, 50));
+        assert_eq!((zones.active.x1, zones.active.y2), (-110, 60));
+        assert_eq!((zones.load.x2, zones.load.y1), (150, -100));
+        assert_eq!((zones.unload.x1, zones.unload.y1), (-200, -150));
+
+        let mut ecs = world::ecs();
+        ecs.create_entity()
+            .with(Position { x: 1000.0, y: -500.0 })
+            .with(Loader)
+            .build();
+        ecs.create_entity()
+            .with(Position { x: 0.0, y: 0.0 })
+            .build();
+
+        let loader_zones = ch.loader_zones(&ecs);
+        assert_eq!(loader_zones.len(), 1);
+        assert_eq!(loader_zones[0].active.x1, 1000 - 110);
+        assert_eq!(loader_zones[0].active.y1, -500 - 60);
+
+        assert!(ZoneRadii::new(50, 10, 100).is_err());
+    }
+
+    /// A 7x7 area of active, empty chunks centered around (0, 0), kept active by a loader.
+    struct TestScene {
+        ch: ChunkHandler<ServerChunk>,
+        ecs: specs::World,
+        phys: Physics,
+        registries: std::sync::Arc<Registries>,
+        file_helper: FileHelper,
+        tick_time: u32,
+    }
+
+    impl TestScene {
+        fn new() -> Self {
+            let mut scene = Self::empty();
+
+            for cx in -3..=3 {
+                for cy in -3..=3 {
+                    let mut chunk = ServerChunk::new_empty(cx, cy);
+                    chunk.set_pixels(
+                        vec![MaterialInstance::air(); CHUNK_AREA]
+                            .try_into()
+                            .unwrap(),
+                    );
+                    chunk.set_state(ChunkState::Active);
+                    scene.ch.manager.insert((cx, cy), chunk);
+                }
+            }
+
+            scene
+        }
+
+        /// No chunks loaded, using [`TestGenerator`] to generate new ones.
+        fn empty() -> Self {
+            let ch: ChunkHandler<ServerChunk> =
+                ChunkHandler::<ServerChunk>::new(TestGenerator::new(), None);
+
+            let mut ecs = world::ecs();
+            ecs.create_entity()
+                .with(Position { x: 50.0, y: 50.0 })
+                .with(Loader)
+                .build();
+
+            Self {
+                ch,
+                ecs,
+                phys: Physics::new(),
+                registries: std::sync::Arc::new(Self::registries()),
+                file_helper: FileHelper::new("../gamedir/".into(), "../gamedir/assets/".into()),
+                tick_time: 0,
+            }
+        }
+
+        /// The game's materials, so the simulation goes by their flags (empty, spread, freeze, ...)
+        ///   instead of falling back to each pixel's physics type.
+        fn registries() -> Registries {
+            Registries {
+                materials: material::init_material_types(),
+                ..Registries::empty()
+            }
+        }
+
+        /// A world with [`TestScene::new`]'s chunks and none of the sample rigidbodies.
+        fn world() -> World<ServerChunk> {
+            let mut world = World::<ServerChunk>::create(None, Some(2));
+            world.chunk_handler = Self::new().ch;
+            world.rigidbodies.clear();
+            world.physics = Physics::new();
+            world
+        }
+
+        /// Whether the pixel at `x`, `y` is empty. Panics if it isn't loaded.
+        fn empty_at(&self, x: i64, y: i64) -> bool {
+            self.ch
+                .pixel(x, y)
+                .unwrap()
+                .is_empty(&self.registries.materials)
+        }
+
+        fn tick(&mut self, settings: &Settings, ticks: u32) {
+            for _ in 0..ticks {
+                self.ch.tick(ChunkTickContext {
+                    tick_time: self.tick_time,
+                    settings,
+                    world: &mut self.ecs,
+                    physics: &mut self.phys,
+                    registries: &self.registries,
+                    seed: 2,
+                    file_helper: &self.file_helper,
+                });
+                self.tick_time += 1;
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn simulate_test_scene(
+        parallel: bool,
+        ticks: u32,
+        simulation_seed: u32,
+    ) -> (
+        Vec<((i32, i32), Vec<MaterialInstance>)>,
+        Vec<(f64, f64, f64, f64)>,
+    ) {
+        let mut scene = TestScene::new();
+
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+
+        // floor and a ledge, both crossing chunk boundaries
+        for x in -250..250 {
+            scene.ch.set_pixel(x, 180, solid.clone()).unwrap();
+        }
+        for x in -60..60 {
+            scene.ch.set_pixel(x, 40, solid.clone()).unwrap();
+        }
+
+        // a block of sand with holes in it spanning several chunks
+        for x in -150..150 {
+            for y in -150..-40 {
+                if (x + y) % 3 != 0 {
+                    scene.ch.set_pixel(x, y, sand.clone()).unwrap();
+                }
+            }
+        }
+
+        let settings = Settings {
+            load_chunks: false,
+            simulate_chunks_parallel: parallel,
+            simulation_seed,
+            ..Settings::default()
+        };
+        scene.tick(&settings, ticks);
+
+        let mut pixels: Vec<_> = unsafe { scene.ch.manager.raw().iter() }
+            .map(|(k, c)| (*k, c.pixels().as_ref().unwrap().to_vec()))
+            .collect();
+        pixels.sort_by_key(|(k, _)| *k);
+
+        let particles = scene
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .iter()
+            .map(|p| (p.pos.x, p.pos.y, p.vel.x, p.vel.y))
+            .collect();
+
+        (pixels, particles)
+    }
+
+    #[test]
+    fn parallel_simulation_matches_serial() {
+        let (initial, _) = simulate_test_scene(false, 0, 0);
+        let (serial, serial_particles) = simulate_test_scene(false, 200, 0);
+        let (parallel, parallel_particles) = simulate_test_scene(true, 200, 0);
+
+        // make sure something actually happened
+        assert_ne!(initial, serial);
+        assert!(!serial_particles.is_empty());
+
+        assert_eq!(serial.len(), parallel.len());
+        for ((s_key, s_pixels), (p_key, p_pixels)) in serial.iter().zip(&parallel) {
+            assert_eq!(s_key, p_key);
+            assert!(
+                s_pixels == p_pixels,
+                "chunk {s_key:?} differs between serial and parallel simulation"
+            );
+        }
+        assert_eq!(serial_particles, parallel_particles);
+    }
+
+    #[test]
+    fn simulation_seed_is_reproducible() {
+        let first = simulate_test_scene(true, 100, 1234);
+        let second = simulate_test_scene(true, 100, 1234);
+        assert!(
+            first == second,
+            "same simulation seed gave different results"
+        );
+
+        // a different seed changes how the sand falls
+        let other = simulate_test_scene(true, 100, 4321);
+        assert!(first != other);
+    }
+
+    /// Builds a 20x60 column of sand standing on a floor at y = 150 and returns the scene.
+    fn sand_column_scene(moisture: f32) -> TestScene {
+        let mut scene = TestScene::new();
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let sand = MaterialInstance {
+            moisture,
+            ..material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100))
+        };
+
+        for x in -100..100 {
+            scene.ch.set_pixel(x, 150, solid.clone()).unwrap();
+        }
+        for x in -10..10 {
+            for y in 90..150 {
+                scene.ch.set_pixel(x, y, sand.clone()).unwrap();
+            }
+        }
+
+        scene
+    }
+
+    /// Height of the sand pile in the column at x = 0, which sits on a floor at y = 150.
+    fn pile_height(scene: &TestScene) -> usize {
+        (0..150)
+            .take_while(|i| {
+                scene
+                    .ch
+                    .pixel(0, 149 - i)
+                    .map_or(false, |m| m.physics == PhysicsType::Sand)
+            })
+            .count()
+    }
+
+    #[test]
+    fn wet_sand_clumps_then_dries() {
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+
+        let mut dry = sand_column_scene(0.0);
+        let mut wet = sand_column_scene(1.0);
+
+        dry.tick(&settings, 100);
+        wet.tick(&settings, 100);
+
+        let dry_height = pile_height(&dry);
+        let wet_height = pile_height(&wet);
+
+        assert!(
+            wet_height > dry_height + 10,
+            "wet pile ({wet_height}) should be steeper than dry pile ({dry_height})"
+        );
+
+        // once it dries out it should collapse like the dry pile
+        wet.tick(&settings, 400);
+        let collapsed_height = pile_height(&wet);
+
+        assert!(wet.ch.pixel(0, 149).unwrap().moisture <= 0.0);
+        assert!(collapsed_height < wet_height);
+        assert!(collapsed_height <= dry_height + 5);
+    }
+
+    #[test]
+    fn sand_settles_across_chunk_border() {
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+
+        // the column is centered on the border between chunks -1 and 0
+        let mut scene = sand_column_scene(0.0);
+        for _ in 0..400 {
+            scene.tick(&settings, 1);
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+        }
+
+        let is_sand = |x: i64, y: i64| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand;
+        let height = |x: i64| (0..150).filter(|&y| is_sand(x, y)).count();
+
+        // nothing is stuck where it could still fall or slide, including on the chunk line
+        for x in -100..100 {
+            for y in 0..150 {
+                if is_sand(x, y) {
+                    assert!(
+                        !scene.empty_at(x, y + 1)
+                            && !scene.empty_at(x - 1, y + 1)
+                            && !scene.empty_at(x + 1, y + 1),
+                        "sand at {x}, {y} hasn't settled"
+                    );
+                }
+            }
+        }
+
+        // it spread out about the same amount to both sides
+        let left: usize = (-100..0).map(height).sum();
+        let right: usize = (0..100).map(height).sum();
+        assert!(
+            left.abs_diff(right) <= (left + right) / 10,
+            "{left} vs {right}"
+        );
+
+        // and didn't pile up on the border
+        let highest_nearby = height(-3).max(height(-2)).max(height(1)).max(height(2));
+        assert!(height(-1).max(height(0)) <= highest_nearby + 3);
+    }
+
+    #[test]
+    fn sand_crosses_chunk_borders_both_ways() {
+        let (w, h) = (i64::from(CHUNK_WIDTH), i64::from(CHUNK_HEIGHT));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+
+        for simulation_seed in 0..8 {
+            let settings = Settings {
+                load_chunks: false,
+                simulation_seed,
+                ..Settings::default()
+            };
+            let mut scene = TestScene::new();
+            let mut set = |x: i64, y: i64, mat: &MaterialInstance| {
+                scene.ch.set_pixel(x, y, mat.clone()).unwrap();
+            };
+
+            // straight down out of the bottom row, into a pit in the chunk below
+            set(5, h - 1, &sand);
+            for x in 3..8 {
+                set(x, h + 1, &solid);
+            }
+            set(4, h, &solid);
+            set(6, h, &solid);
+
+            // off the right edge into a cup in the chunk beside it
+            set(w - 1, 5, &sand);
+            for x in w - 2..w + 2 {
+                set(x, 7, &solid);
+            }
+            set(w - 2, 6, &solid);
+            set(w - 1, 6, &solid);
+            set(w + 1, 6, &solid);
+
+            // off the left edge into a cup in the chunk beside it
+            set(0, 5, &sand);
+            for x in -2..2 {
+                set(x, 7, &solid);
+            }
+            set(1, 6, &solid);
+            set(0, 6, &solid);
+            set(-2, 6, &solid);
+
+            // off the bottom right corner into a cup in the chunk diagonally below
+            set(w - 1, h - 1, &sand);
+            for x in w - 2..w + 2 {
+                set(x, h + 1, &solid);
+            }
+            set(w - 2, h, &solid);
+            set(w - 1, h, &solid);
+            set(w + 1, h, &solid);
+
+            for _ in 0..20 {
+                scene.tick(&settings, 1);
+                UpdateParticles {
+                    chunk_handler: &mut scene.ch,
+                    interactions: ParticleInteractions::ALL,
+                    registries: &scene.registries,
+                }
+                .run_now(&scene.ecs);
+            }
+
+            assert!(scene
+                .ecs
+                .read_resource::<ParticleSystem>()
+                .active
+                .is_empty());
+            let is_sand =
+                |x: i64, y: i64| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand;
+            for (x, y) in [(5, h), (w, 6), (-1, 6), (w, h)] {
+                assert!(
+                    is_sand(x, y),
+                    "no sand at {x}, {y} with seed {simulation_seed}"
+                );
+            }
+            let in_start_chunk = (0..w)
+                .flat_map(|x| (0..h).map(move |y| (x, y)))
+                .filter(|&(x, y)| is_sand(x, y));
+            assert_eq!(in_start_chunk.count(), 0);
+        }
+    }
+
+    /// Whether every piece of sand in [`sand_column_scene`] is resting on something and there are no
+    ///   particles left in the air.
+    fn sand_settled(scene: &TestScene) -> bool {
+        let is_empty = |x: i64, y: i64| scene.empty_at(x, y);
+        scene
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .is_empty()
+            && (-100..100).all(|x| {
+                (0..150).all(|y| {
+                    scene.ch.pixel(x, y).unwrap().physics != PhysicsType::Sand
+                        || !(is_empty(x, y + 1) || is_empty(x - 1, y + 1) || is_empty(x + 1, y + 1))
+                })
+            })
+    }
+
+    #[test]
+    fn substeps_settle_sand_faster() {
+        let settle = |substeps: u8| {
+            let settings = Settings {
+                load_chunks: false,
+                simulate_chunks_substeps: substeps,
+                ..Settings::default()
+            };
+
+            let mut scene = sand_column_scene(0.0);
+            let mut ticks = 0;
+            while !sand_settled(&scene) {
+                assert!(ticks < 1000, "sand didn't settle with {substeps} substeps");
+                scene.tick(&settings, 1);
+                UpdateParticles {
+                    chunk_handler: &mut scene.ch,
+                    interactions: ParticleInteractions::ALL,
+                    registries: &scene.registries,
+                }
+                .run_now(&scene.ecs);
+                ticks += 1;
+            }
+
+            let sand = (-100..100)
+                .flat_map(|x| (0..150).map(move |y| (x, y)))
+                .filter(|&(x, y)| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand)
+                .count();
+            (ticks, sand, pile_height(&scene))
+        };
+
+        let (one_ticks, one_sand, one_height) = settle(1);
+        let (two_ticks, two_sand, two_height) = settle(2);
+
+        assert!(
+            two_ticks < one_ticks,
+            "2 substeps took {two_ticks} ticks, 1 substep took {one_ticks}"
+        );
+
+        // the pile ends up the same either way
+        assert_eq!(one_sand, 20 * 60);
+        assert_eq!(two_sand, one_sand);
+        assert!(
+            one_height.abs_diff(two_height) <= 3,
+            "{one_height} vs {two_height}"
+        );
+    }
+
+    #[test]
+    fn particle_interaction_toggles() {
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+
+        // a particle dropped just above a floor at y = 30
+        let run = |interactions: ParticleInteractions| {
+            let mut scene = TestScene::new();
+            for x in -10..10 {
+                scene.ch.set_pixel(x, 30, solid.clone()).unwrap();
+            }
+            scene
+                .ecs
+                .write_resource::<ParticleSystem>()
+                .active
+                .push(Particle::new(
+                    sand.clone(),
+                    Position { x: 0.5, y: 28.5 },
+                    Velocity { x: 0.0, y: 0.0 },
+                ));
+
+            for _ in 0..20 {
+                UpdateParticles {
+                    chunk_handler: &mut scene.ch,
+                    interactions,
+                    registries: &scene.registries,
+                }
+                .run_now(&scene.ecs);
+            }
+            scene
+        };
+        let particles = |scene: &TestScene| {
+            scene
+                .ecs
+                .read_resource::<ParticleSystem>()
+                .active
+                .iter()
+                .map(|p| (p.pos.x, p.pos.y))
+                .collect::<Vec<_>>()
+        };
+
+        // it lands and turns back into a pixel on top of the floor
+        let scene = run(ParticleInteractions::ALL);
+        assert!(particles(&scene).is_empty());
+        assert_eq!(scene.ch.pixel(0, 29).unwrap(), &sand);
+
+        // without settling it's gone once it lands, leaving nothing behind
+        let scene = run(ParticleInteractions { settle: false, ..ParticleInteractions::ALL });
+        assert!(particles(&scene).is_empty());
+        for y in 0..30 {
+            assert!(scene.empty_at(0, y), "{y}");
+        }
+
+        // with nothing on it falls straight through the floor like it isn't there
+        let scene = run(ParticleInteractions::NONE);
+        let left = particles(&scene);
+        assert_eq!(left.len(), 1);
+        assert!(left[0].1 > 31.0, "{left:?}");
+        assert_eq!(scene.ch.pixel(0, 30).unwrap(), &solid);
+        for y in 0..30 {
+            assert!(scene.empty_at(0, y), "{y}");
+        }
+    }
+
+    #[test]
+    fn settle_events_carry_impact_velocity() {
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+
+        let mut scene = TestScene::new();
+        // thick enough that fast particles can't skip over it between steps
+        for x in -10..10 {
+            for y in 80..90 {
+                scene.ch.set_pixel(x, y, solid.clone()).unwrap();
+            }
+        }
+        // one dropped from high up at x = 2, one just above the floor at x = 6
+        let mut system = scene.ecs.write_resource::<ParticleSystem>();
+        for (x, y) in [(2.5, 0.5), (6.5, 78.5)] {
+            system.active.push(Particle::new(
+                sand.clone(),
+                Position { x, y },
+                Velocity { x: 0.0, y: 0.0 },
+            ));
+        }
+        drop(system);
+
+        for _ in 0..100 {
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+        }
+
+        let events: Vec<_> = scene.ecs.write_resource::<SettleEvents>().drain().collect();
+        assert_eq!(events.len(), 2, "{events:?}");
+        let velocity = |x: i64| events.iter().find(|e| e.x == x).unwrap().velocity;
+        assert!(velocity(2) > velocity(6) * 5.0, "{events:?}");
+        assert!(events.iter().all(|e| e.material == sand.material_id));
+    }
+
+    #[test]
+    fn bouncy_particles_bounce_until_they_settle() {
+        let material =
+            |restitution| material::Material { restitution, ..material::Material::default() };
+        let sticky_id: RegistryID<material::Material> = "sticky".into();
+        let mut registries = Registries::empty();
+        registries
+            .materials
+            .register(material::TEST.clone(), material(1.0));
+        registries
+            .materials
+            .register(sticky_id.clone(), material(0.0));
+        registries
+            .materials
+            .register(material::COBBLE_STONE.clone(), material(0.8));
+
+        let bouncy = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 80, 80));
+        let sticky = sticky_id.instance(PhysicsType::Sand, Color::rgb(80, 255, 80));
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+
+        let mut scene = TestScene::new();
+        for x in 0..20 {
+            scene.ch.set_pixel(x, 80, stone.clone()).unwrap();
+        }
+        let mut system = scene.ecs.write_resource::<ParticleSystem>();
+        for (m, x) in [(&bouncy, 5.5), (&sticky, 15.5)] {
+            system.active.push(Particle::new(
+                m.clone(),
+                Position { x, y: 40.5 },
+                Velocity { x: 0.0, y: 3.0 },
+            ));
+        }
+        drop(system);
+
+        let mut bounces = 0;
+        let mut sticky_rose = false;
+        let mut was_falling = true;
+        for _ in 0..1000 {
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &registries,
+            }
+            .run_now(&scene.ecs);
+
+            let system = scene.ecs.read_resource::<ParticleSystem>();
+            for p in &system.active {
+                if p.material.material_id == sticky_id {
+                    sticky_rose |= p.vel.y < 0.0;
+                } else {
+                    if was_falling && p.vel.y < 0.0 {
+                        bounces += 1;
+                    }
+                    was_falling = p.vel.y > 0.0;
+                }
+            }
+        }
+
+        // the sticky one stopped the first time it hit the floor
+        assert!(!sticky_rose);
+        let events: Vec<_> = scene.ecs.write_resource::<SettleEvents>().drain().collect();
+        let settled = |id: &RegistryID<material::Material>| {
+            events.iter().filter(|e| e.material == *id).count()
+        };
+        assert_eq!(settled(&sticky_id), 1, "{events:?}");
+
+        // the bouncy one bounced, but lost speed every time until it settled too
+        assert!(bounces >= 2, "{bounces}");
+        assert_eq!(settled(&material::TEST), 1, "{events:?}");
+        assert!(scene
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .is_empty());
+        assert!((40..80).any(|y| scene.ch.pixel(5, y).unwrap().material_id == *material::TEST));
+    }
+
+    #[test]
+    fn settle_events_cleared_each_tick() {
+        let mut world = TestScene::world();
+        let file_helper = FileHelper::new("../gamedir/".into(), "../gamedir/assets/".into());
+
+        world
+            .ecs
+            .write_resource::<SettleEvents>()
+            .emit(SettleEvent {
+                x: 5,
+                y: 5,
+                material: material::TEST.clone(),
+                velocity: 2.0,
+            });
+        world.tick(
+            0,
+            &Settings::default(),
+            std::sync::Arc::new(TestScene::registries()),
+            &file_helper,
+        );
+        assert!(world.ecs.read_resource::<SettleEvents>().is_empty());
+    }
+
+    #[test]
+    fn sand_falls_through_custom_empty_material() {
+        // emptiness comes from the material, not the physics it was instanced with
+        let hole_id: RegistryID<material::Material> = "hole".into();
+        let mut materials = material::init_material_types();
+        materials.register(
+            hole_id.clone(),
+            material::Material { empty: true, ..material::Material::default() },
+        );
+
+        let hole = hole_id.instance(PhysicsType::Solid, Color::TRANSPARENT);
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        assert!(hole.is_empty(&materials));
+        assert!(material::VACUUM
+            .instance(PhysicsType::Air, Color::TRANSPARENT)
+            .is_empty(&materials));
+        assert!(MaterialInstance::air().is_empty(&materials));
+        assert!(!sand.is_empty(&materials));
+        assert!(!material::TEST
+            .instance(PhysicsType::Air, Color::TRANSPARENT)
+            .is_empty(&materials));
+
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+        let mut scene = TestScene::new();
+        scene.registries = std::sync::Arc::new(Registries { materials, ..Registries::empty() });
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        // the floor is thick so the falling particle can't step over it
+        for x in -30..30 {
+            for y in 50..55 {
+                scene.ch.set_pixel(x, y, solid.clone()).unwrap();
+            }
+            for y in 0..50 {
+                scene.ch.set_pixel(x, y, hole.clone()).unwrap();
+            }
+        }
+        scene.ch.set_pixel(0, 10, sand).unwrap();
+
+        // it turns into a particle while falling
+        for _ in 0..100 {
+            scene.tick(&settings, 1);
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+        }
+
+        let landed = (-30..30)
+            .filter(|&x| scene.ch.pixel(x, 49).unwrap().physics == PhysicsType::Sand)
+            .count();
+        assert_eq!(landed, 1);
+
+        // it leaves the hole behind instead of replacing it with air
+        for x in -30..30 {
+            for y in 0..49 {
+                assert_eq!(scene.ch.pixel(x, y).unwrap().material_id, hole_id);
+            }
+        }
+    }
+
+    #[test]
+    fn falling_liquid_sprays_on_impact() {
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+        let mut scene = TestScene::new();
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(0, 0, 255));
+
+        // a tall column of water high above a thick floor across the whole scene,
+        //   so fast spray can't skip over it
+        for x in -300..400 {
+            for y in 150..160 {
+                scene.ch.set_pixel(x, y, solid.clone()).unwrap();
+            }
+        }
+        let mut volume = 0;
+        for x in -5..5 {
+            for y in -80..0 {
+                scene.ch.set_pixel(x, y, water.clone()).unwrap();
+                volume += 1;
+            }
+        }
+
+        let mut max_spray = 0;
+        for _ in 0..400 {
+            scene.tick(&settings, 1);
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+            max_spray = max_spray.max(scene.ecs.read_resource::<ParticleSystem>().active.len());
+        }
+
+        // some of it splashed, but never more than there was water
+        assert!(max_spray > 0);
+        assert!(max_spray < volume);
+
+        // and the spray settled back down as water
+        assert!(scene
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .is_empty());
+        let settled = (-300..400)
+            .flat_map(|x| (-300..150).map(move |y| (x, y)))
+            .filter(|&(x, y)| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Liquid)
+            .count();
+        assert!(settled <= volume);
+        assert!(settled >= volume * 95 / 100);
+    }
+
+    #[test]
+    fn edit_matches_individual_sets() {
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+
+        let mut batched = TestScene::new();
+        let mut individual = TestScene::new();
+
+        // a line across a chunk border, then every other pixel of it is replaced
+        let mut edit = batched.ch.begin_edit();
+        for x in -20..20 {
+            edit.set(x, 5, solid.clone()).unwrap();
+        }
+        for x in -20..20 {
+            // reads see earlier writes in the same edit
+            assert_eq!(edit.pixel(x, 5).unwrap().physics, PhysicsType::Solid);
+            if x % 2 == 0 {
+                edit.set(x, 5, sand.clone()).unwrap();
+                edit.set(x, 4, solid.clone()).unwrap();
+            }
+        }
+        assert_eq!(edit.pixel(0, 5).unwrap().physics, PhysicsType::Sand);
+        assert!(edit.set(10_000, 5, solid.clone()).is_err());
+        assert_eq!(edit.len(), 60);
+        edit.commit().unwrap();
+
+        for x in -20..20 {
+            individual.ch.set_pixel(x, 5, solid.clone()).unwrap();
+        }
+        for x in -20..20 {
+            if x % 2 == 0 {
+                individual.ch.set_pixel(x, 5, sand.clone()).unwrap();
+                individual.ch.set_pixel(x, 4, solid.clone()).unwrap();
+            }
+        }
+
+        for x in -30..30 {
+            for y in 0..10 {
+                assert_eq!(
+                    batched.ch.pixel(x, y).unwrap(),
+                    individual.ch.pixel(x, y).unwrap()
+                );
+            }
+        }
+
+        // the whole edit is undone at once
+        assert_eq!(batched.ch.edit_history.len(), 1);
+        assert!(batched.ch.undo_edit().unwrap());
+        assert!(batched.ch.edit_history.is_empty());
+        for x in -30..30 {
+            for y in 0..10 {
+                assert!(batched.empty_at(x, y));
+            }
+        }
+        assert!(!batched.ch.undo_edit().unwrap());
+    }
+
+    #[test]
+    fn raycast_across_chunks() {
+        let mut scene = TestScene::new();
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+
+        // a wall right on the border between chunks -1 and 0, with a gap in it
+        for y in -150..150 {
+            if !(-10..10).contains(&y) {
+                scene.ch.set_pixel(-1, y, solid.clone()).unwrap();
+            }
+        }
+
+        // through the gap, starting and ending in other chunks
+        assert_eq!(
+            scene
+                .ch
+                .raycast((-150, 0), (150, 5), &scene.registries.materials),
+            None
+        );
+        assert_eq!(
+            scene
+                .ch
+                .raycast((150, -5), (-150, 0), &scene.registries.materials),
+            None
+        );
+
+        // into the wall from both sides
+        assert_eq!(
+            scene
+                .ch
+                .raycast((150, -120), (-150, -120), &scene.registries.materials),
+            Some((-1, -120, solid.clone()))
+        );
+        assert_eq!(
+            scene
+                .ch
+                .raycast((-150, 120), (150, 120), &scene.registries.materials),
+            Some((-1, 120, solid.clone()))
+        );
+        // along the wall, hitting the end of it after the gap
+        assert_eq!(
+            scene
+                .ch
+                .raycast((-1, 5), (-1, 50), &scene.registries.materials),
+            Some((-1, 10, solid.clone()))
+        );
+
+        // can't slip between two diagonally touching pixels
+        scene.ch.set_pixel(120, 121, solid.clone()).unwrap();
+        scene.ch.set_pixel(121, 120, solid.clone()).unwrap();
+        assert_eq!(
+            scene
+                .ch
+                .raycast((119, 119), (122, 122), &scene.registries.materials),
+            Some((120, 121, solid))
+        );
+
+        // only pixels the filter accepts stop the ray
+        let sand_only = |_, m: &MaterialInstance| m.physics == PhysicsType::Sand;
+        assert_eq!(
+            scene
+                .ch
+                .raycast_filtered((150, -120), (-150, -120), sand_only),
+            None
+        );
+    }
+
+    #[test]
+    fn grapple_anchors_at_raycast_hit() {
+        let mut scene = TestScene::new();
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        for y in -50..50 {
+            scene.ch.set_pixel(-130, y, solid.clone()).unwrap();
+        }
+
+        // fired left at the wall, a chunk away
+        let mut pos = Position { x: 20.5, y: -3.5 };
+        let mut vel = Velocity { x: -16.0, y: 0.0 };
+        let mut anchor = None;
+        for _ in 0..20 {
+            anchor = grapple_hook_step(&scene.ch, &mut pos, &mut vel);
+            if anchor.is_some() {
+                break;
+            }
+            pos.x += vel.x;
+            pos.y += vel.y;
+        }
+
+        let (hit_x, hit_y, _) = scene
+            .ch
+            .raycast((20, -4), (-200, -4), &scene.registries.materials)
+            .unwrap();
+        assert_eq!(anchor, Some((hit_x, hit_y)));
+        assert_eq!(anchor, Some((-130, -4)));
+        // stopped in front of the wall
+        assert_eq!((vel.x, vel.y), (0.0, 0.0));
+        assert!(scene.empty_at(pos.x.floor() as i64, pos.y.floor() as i64));
+
+        // lets go once the pixel it's stuck in is gone
+        assert!(grapple_anchor_holds(&scene.ch, (-130, -4)));
+        scene
+            .ch
+            .set_pixel(-130, -4, MaterialInstance::air())
+            .unwrap();
+        assert!(!grapple_anchor_holds(&scene.ch, (-130, -4)));
+    }
+
+    #[test]
+    fn overhang_casts_sun_shadow() {
+        let settings = Settings {
+            load_chunks: false,
+            sun_light: true,
+            sun_slope: 0.0,
+            ..Settings::default()
+        };
+        let mut scene = TestScene::new();
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        for x in 20..80 {
+            for y in 10..15 {
+                scene.ch.set_pixel(x, y, solid.clone()).unwrap();
+            }
+        }
+
+        scene.tick(&settings, 1);
+
+        let light = |x: usize, y: usize| {
+            scene.ch.chunk_at((0, 0)).unwrap().lights()[x + y * CHUNK_WIDTH as usize][0]
+        };
+
+        // on top of the overhang and out in the open are fully lit
+        assert!(light(50, 10) > 0.9);
+        assert!(light(5, 60) > 0.9);
+        assert!(light(95, 60) > 0.9);
+
+        // under the overhang is in shadow
+        assert!(light(50, 15) < 0.1);
+        assert!(light(50, 60) < 0.5);
+        assert!(light(50, 60) < light(5, 60));
+    }
+
+    #[test]
+    fn light_spreads_into_neighbor_chunk() {
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+        let mut scene = TestScene::new();
+
+        // one pixel away from the border with chunk (1, 0)
+        let lamp = material::TEST
+            .instance(PhysicsType::Solid, Color::rgb(255, 255, 200))
+            .with_light([1.0, 1.0, 0.8]);
+        scene.ch.set_pixel(98, 50, lamp).unwrap();
+
+        scene.tick(&settings, 3);
+
+        let lights = |scene: &TestScene, chunk: (i32, i32)| {
+            scene.ch.chunk_at(chunk).unwrap().lights().to_vec()
+        };
+        let light = |chunk: (i32, i32), x: usize, y: usize| {
+            lights(&scene, chunk)[x + y * CHUNK_WIDTH as usize][0]
+        };
+
+        // the border cells of the neighbor are lit, fading with distance
+        assert!(light((1, 0), 0, 50) > 0.8);
+        assert!(light((1, 0), 0, 45) > 0.0);
+        assert!(light((1, 0), 5, 50) < light((1, 0), 0, 50));
+
+        // but only up to the light radius
+        assert!(light((1, 0), 30, 50) <= 0.0);
+        assert!(light((0, 0), 60, 50) <= 0.0);
+
+        // and nothing changes once it has spread
+        let before = (lights(&scene, (0, 0)), lights(&scene, (1, 0)));
+        scene.tick(&settings, 10);
+        assert!(before == (lights(&scene, (0, 0)), lights(&scene, (1, 0))));
+    }
+
+    #[test]
+    fn chunk_lights_survive_reload() {
+        let dir = std::env::temp_dir().join(format!("fs_chunk_lights_{}", std::process::id()));
+        let _ignore = std::fs::remove_dir_all(&dir);
+
+        let mut scene = TestScene::new();
+        scene.ch.path = Some(dir.clone());
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        scene.ch.set_pixel(10, 20, stone.clone()).unwrap();
+        let chunk = scene.ch.manager.chunk_at_mut((0, 0)).unwrap();
+        for (i, light) in chunk.lights_mut().iter_mut().enumerate() {
+            *light = [(i % 7) as f32 / 7.0, 0.5, 0.25, 1.0];
+        }
+        let lights = chunk.lights().to_vec();
+        scene.ch.save_chunk((0, 0)).unwrap();
+
+        // a chunk saved before lights were, next to it, from before pixels had moisture or a fall count
+        //   (a tuple serializes the same as the old struct with these fields)
+        let old_stone = (
+            stone.material_id.clone(),
+            stone.physics,
+            stone.color,
+            stone.light,
+        );
+        let old = (
+            vec![old_stone; CHUNK_AREA],
+            vec![Color::rgb(127, 127, 127); CHUNK_AREA],
+        );
+        std::fs::write(
+            dir.join("chunks/1_0.chunk"),
+            bincode::serialize(&old).unwrap(),
+        )
+        .unwrap();
+
+        let mut scene = TestScene::empty();
+        scene.ch.path = Some(dir.clone());
+        scene
+            .ch
+            .force_generate((0, 0), 1, 2, &scene.registries, &mut scene.ecs);
+
+        let chunk = scene.ch.chunk_at((0, 0)).unwrap();
+        assert!(chunk.lights().to_vec() == lights);
+        assert_eq!(scene.ch.pixel(10, 20).unwrap(), &stone);
+
+        // which still loads, starting dark until it's relit
+        let chunk = scene.ch.chunk_at((1, 0)).unwrap();
+        assert!(chunk.lights().iter().all(|l| *l == [0.0; 4]));
+        assert!(chunk.revealed());
+        assert_eq!(
+            scene.ch.pixel(i64::from(CHUNK_WIDTH) + 5, 5).unwrap(),
+            &stone
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn loader_history_reveals_chunks() {
+        let dir = std::env::temp_dir().join(format!("fs_reveal_{}", std::process::id()));
+        let _ignore = std::fs::remove_dir_all(&dir);
+
+        let settings = Settings {
+            load_chunks: false,
+            simulate_chunks: false,
+            ..Settings::default()
+        };
+        let mut scene = TestScene::new();
+        scene.ch.path = Some(dir.clone());
+        scene.ch.screen_size = (20, 20);
+        scene.ch.zone_radii = ZoneRadii::new(0, 50, 100).unwrap();
+
+        // the loader starts in the middle of (0, 0), then walks over to (2, 0)
+        scene.tick(&settings, 1);
+        for (pos, _) in (
+            &mut scene.ecs.write_storage::<Position>(),
+            &scene.ecs.read_storage::<Loader>(),
+        )
+            .join()
+        {
+            pos.x = 250.0;
+        }
+        scene.tick(&settings, 1);
+
+        let revealed = |scene: &TestScene, key| scene.ch.chunk_at(key).unwrap().revealed();
+        assert!(revealed(&scene, (0, 0)));
+        assert!(revealed(&scene, (2, 0)));
+        for key in [(1, 0), (-1, 0), (0, 1), (3, 0), (-3, -3)] {
+            assert!(!revealed(&scene, key), "{key:?}");
+        }
+
+        // and it's remembered after unloading
+        scene.ch.save_all_chunks().unwrap();
+        let mut scene = TestScene::empty();
+        scene.ch.path = Some(dir.clone());
+        scene
+            .ch
+            .force_generate((1, 0), 1, 2, &scene.registries, &mut scene.ecs);
+        assert!(revealed(&scene, (0, 0)));
+        assert!(revealed(&scene, (2, 0)));
+        assert!(!revealed(&scene, (1, 0)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn world_border_skips_generation() {
+        let mut scene = TestScene::empty();
+        let border = WorldBorder::barrier((-1, -1), (1, 1));
+        scene.ch.border = Some(border.clone());
+
+        let settings = Settings::default();
+        let ready = |scene: &TestScene, chunk| {
+            scene.ch.chunk_at(chunk).map_or(false, |c| {
+                matches!(c.state(), ChunkState::Cached | ChunkState::Active)
+            })
+        };
+
+        let start = std::time::Instant::now();
+        while !(ready(&scene, (0, 0)) && ready(&scene, (2, 0))) {
+            assert!(start.elapsed().as_secs() < 30, "chunks didn't load");
+            scene.tick(&settings, 1);
+
+            // nothing outside the border is ever sent off to be generated
+            assert!(scene
+                .ch
+                .gen_threads
+                .iter()
+                .all(|(key, _)| border.contains_chunk(*key)));
+        }
+
+        // outside the border is filled with the barrier, loaded or not
+        let outside = scene.ch.chunk_at((2, 0)).unwrap();
+        assert!(outside
+            .pixels()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|m| *m == border.outside));
+        assert_eq!(scene.ch.pixel(250, 50), Ok(&border.outside));
+        assert_eq!(scene.ch.pixel(100_000, -100_000), Ok(&border.outside));
+
+        // inside is generated as usual
+        assert!(scene
+            .ch
+            .chunk_at((0, 0))
+            .unwrap()
+            .pixels()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|m| *m != border.outside));
+
+        // and edits stop at the border
+        assert!(scene.ch.set_pixel(99, 50, MaterialInstance::air()).is_ok());
+        assert!(scene
+            .ch
+            .set_pixel(200, 50, MaterialInstance::air())
+            .is_err());
+        assert_eq!(scene.ch.pixel(200, 50), Ok(&border.outside));
+    }
+
+    #[test]
+    fn sand_rests_on_bedrock_floor() {
+        let mut scene = TestScene::empty();
+        let rules = WorldRules {
+            floor_chunk: Some(0),
+            ceiling_chunk: Some(-1),
+            ..WorldRules::default()
+        };
+        let border = rules.vertical_border().unwrap();
+        scene.ch.border = Some(border.clone());
+
+        let settings = Settings::default();
+        let ready = |scene: &TestScene, chunk| {
+            scene.ch.chunk_at(chunk).map_or(false, |c| {
+                matches!(c.state(), ChunkState::Cached | ChunkState::Active)
+            })
+        };
+        let start = std::time::Instant::now();
+        while !(-1..=1).all(|x| (-1..=1).all(|y| ready(&scene, (x, y)))) {
+            assert!(start.elapsed().as_secs() < 30, "chunks didn't load");
+            scene.tick(&settings, 1);
+        }
+
+        // the chunks under the floor are all bedrock, and so is everything further down
+        let floor = scene.ch.chunk_at((0, 1)).unwrap();
+        assert!(floor
+            .pixels()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .all(|m| *m == border.outside && m.material_id == *material::BEDROCK));
+        assert_eq!(scene.ch.pixel(0, 100_000), Ok(&border.outside));
+        // above the ceiling is empty, but out of reach
+        assert!(scene.empty_at(0, -100_000));
+        assert!(scene
+            .ch
+            .set_pixel(0, -101, MaterialInstance::air())
+            .is_err());
+
+        // clear out the generated terrain and drop some sand onto the floor
+        for x in -100..200 {
+            for y in -100..100 {
+                scene.ch.set_pixel(x, y, MaterialInstance::air()).unwrap();
+            }
+        }
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        scene.ch.set_pixel(50, 10, sand.clone()).unwrap();
+        for _ in 0..200 {
+            scene.tick(&settings, 1);
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+        }
+
+        // it lands somewhere near where it was dropped, right on top of the floor
+        let landed: Vec<_> = (0..100)
+            .filter(|&x| scene.ch.pixel(x, 99) == Ok(&sand))
+            .collect();
+        assert_eq!(landed.len(), 1, "{landed:?}");
+        assert_eq!(scene.ch.pixel(landed[0], 100), Ok(&border.outside));
+    }
+
+    #[test]
+    fn border_rules_combine_with_floor() {
+        let rules = WorldRules {
+            border: Some(BorderRules { min: (-2, -5), max: (2, 5), solid: false }),
+            floor_chunk: Some(0),
+            ceiling_chunk: Some(-1),
+            ..WorldRules::default()
+        };
+        let border = rules.world_border().unwrap();
+
+        // inside the rectangle and between the ceiling and floor
+        assert!(border.contains_chunk((2, 0)));
+        assert!(border.contains_chunk((-2, -1)));
+        assert!(!border.contains_chunk((3, 0)));
+        assert!(!border.contains_chunk((0, 1)));
+        assert!(!border.contains_chunk((0, -2)));
+
+        // open to the sides, but the floor is still solid and above the ceiling is air
+        assert_eq!(border.outside_at(50), &MaterialInstance::air());
+        assert_eq!(border.outside_at(100).material_id, *material::BEDROCK);
+        assert_eq!(border.outside_at(-101), &MaterialInstance::air());
+
+        // a rectangle inside the floor and ceiling isn't changed by them
+        let rules = WorldRules {
+            border: Some(BorderRules { min: (-1, -1), max: (1, -1), solid: true }),
+            floor_chunk: Some(3),
+            ..rules
+        };
+        let border = rules.world_border().unwrap();
+        assert_eq!(
+            (
+                border.chunks.x1,
+                border.chunks.y1,
+                border.chunks.x2,
+                border.chunks.y2
+            ),
+            (-1, -1, 2, 0)
+        );
+        assert!([-200, 0, 500]
+            .into_iter()
+            .all(|y| border.outside_at(y).material_id == *material::BEDROCK));
+    }
+
+    #[test]
+    fn structure_across_chunk_corner_waits_for_chunks() {
+        let mut scene = TestScene::new();
+
+        // one of the four chunks around the corner at (100, 100) isn't generated yet
+        scene
+            .ch
+            .manager
+            .insert((1, 1), ServerChunk::new_empty(1, 1));
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let mut registries = Registries::empty();
+        registries.structure_pieces.register(
+            "corner",
+            StructurePiece {
+                buf: MaterialBuf::new(60, 40, vec![solid; 60 * 40]).unwrap(),
+                child_nodes: vec![(
+                    StructureNodeLocalPlacement { x: 0, y: 20, direction_out: Direction::Left },
+                    StructureNodeConfig::new("empty"),
+                )],
+            },
+        );
+        registries
+            .structure_pools
+            .register("corner", StructurePool::from(vec!["corner"]));
+        registries
+            .structure_pools
+            .register("empty", StructurePool::from(vec![]));
+
+        // covers x 70..130, y 80..120
+        let node = StructureNode::create_and_add(
+            &mut scene.ecs,
+            Position { x: 70.0, y: 100.0 },
+            0,
+            1000,
+            2,
+            StructureNodeConfig::new("corner"),
+            Some(Direction::Right),
+        );
+
+        let update = |scene: &mut TestScene| {
+            UpdateStructureNodes {
+                chunk_handler: &mut scene.ch,
+                registries: &registries,
+            }
+            .run_now(&scene.ecs);
+            scene.ecs.maintain();
+        };
+
+        update(&mut scene);
+
+        assert!(scene
+            .ecs
+            .read_storage::<StructureNode>()
+            .get(node)
+            .unwrap()
+            .generated
+            .is_none());
+        assert_eq!(scene.ch.pixel(70, 80).unwrap().physics, PhysicsType::Air);
+
+        // once the chunk is ready the whole structure should be placed
+        let chunk = scene.ch.manager.chunk_at_mut((1, 1)).unwrap();
+        chunk.set_pixels(
+            vec![MaterialInstance::air(); CHUNK_AREA]
+                .try_into()
+                .unwrap(),
+        );
+        chunk.set_state(ChunkState::Active);
+
+        update(&mut scene);
+
+        assert!(matches!(
+            scene
+                .ecs
+                .read_storage::<StructureNode>()
+                .get(node)
+                .unwrap()
+                .generated,
+            Some(Ok(_))
+        ));
+        for x in 70..130 {
+            for y in 80..120 {
+                assert_eq!(
+                    scene.ch.pixel(x, y).unwrap().physics,
+                    PhysicsType::Solid,
+                    "missing pixel at {x}, {y}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn edit_events_only_for_deliberate_edits() {
+        let mut scene = TestScene::new();
+
+        // falling sand is just the simulation moving things around, not an edit
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        for x in 10..20 {
+            for y in 10..20 {
+                scene.ch.set_pixel(x, y, sand.clone()).unwrap();
+            }
+        }
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+        scene.tick(&settings, 20);
+        assert_ne!(scene.ch.pixel(15, 10).unwrap().physics, PhysicsType::Sand);
+        assert!(scene.ecs.read_resource::<WorldEditEvents>().is_empty());
+
+        // placing a structure is
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let mut registries = Registries::empty();
+        registries.structure_pieces.register(
+            "block",
+            StructurePiece {
+                buf: MaterialBuf::new(30, 20, vec![solid; 30 * 20]).unwrap(),
+                child_nodes: vec![(
+                    StructureNodeLocalPlacement { x: 0, y: 10, direction_out: Direction::Left },
+                    StructureNodeConfig::new("empty"),
+                )],
+            },
+        );
+        registries
+            .structure_pools
+            .register("block", StructurePool::from(vec!["block"]));
+        registries
+            .structure_pools
+            .register("empty", StructurePool::from(vec![]));
+
+        StructureNode::create_and_add(
+            &mut scene.ecs,
+            Position { x: -40.0, y: 60.0 },
+            0,
+            1000,
+            2,
+            StructureNodeConfig::new("block"),
+            Some(Direction::Right),
+        );
+        UpdateStructureNodes {
+            chunk_handler: &mut scene.ch,
+            registries: &registries,
+        }
+        .run_now(&scene.ecs);
+
+        let events: Vec<_> = scene
+            .ecs
+            .write_resource::<WorldEditEvents>()
+            .drain()
+            .collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cause, WorldEditCause::Structure);
+        let rect = events[0].rect;
+        for x in rect.range_lr() {
+            for y in rect.range_tb() {
+                assert_eq!(scene.ch.pixel(x, y).unwrap().physics, PhysicsType::Solid);
+            }
+        }
+        assert_eq!((rect.width(), rect.height()), (30, 20));
+    }
+
+    /// Generates the chunk at the origin with a `budget_ms` generation budget, returning how
+    ///   many ticks it took and the most steps (populating or meshing a chunk) done in one tick.
+    fn generate_with_budget(budget_ms: f32) -> (u32, u32) {
+        let mut scene = TestScene::empty();
+        let settings = Settings {
+            chunk_gen_budget_ms: budget_ms,
+            simulate_chunks: false,
+            ..Settings::default()
+        };
+
+        let progress = |scene: &TestScene| -> u32 {
+            scene
+                .ch
+                .manager
+                .chunks_iter()
+                .map(|c| match c.state() {
+                    ChunkState::Generating(stage) => u32::from(stage),
+                    ChunkState::Cached | ChunkState::Active => 3,
+                    _ => 0,
+                })
+                .sum()
+        };
+        let done = |scene: &TestScene| {
+            matches!(
+                scene.ch.manager.chunk_at((0, 0)).map(|c| c.state()),
+                Some(ChunkState::Cached | ChunkState::Active)
+            )
+        };
+
+        let mut last = 0;
+        let mut most = 0;
+        let mut ticks = 0;
+        while !done(&scene) {
+            scene.tick(&settings, 1);
+            let now = progress(&scene);
+            most = most.max(now - last);
+            last = now;
+
+            ticks += 1;
+            assert!(ticks < 10_000, "generation stalled");
+            if !scene.ch.gen_threads.is_empty() {
+                std::thread::yield_now();
+            }
+        }
+        (ticks, most)
+    }
+
+    #[test]
+    fn generation_budget_spreads_work() {
+        let (ticks, most) = generate_with_budget(0.0);
+        // with no time to spare, only one step happens each tick
+        assert_eq!(most, 1);
+        // takes a while but still gets there
+        assert!(ticks > 3);
+    }
+
+    #[test]
+    fn generation_budget_allows_more_work() {
+        let (slow_ticks, _) = generate_with_budget(0.0);
+        let (ticks, most) = generate_with_budget(60_000.0);
+        // with plenty of time, several steps fit in one tick
+        assert!(most > 1, "only {most} step per tick");
+        assert!(
+            ticks < slow_ticks,
+            "{ticks} ticks, {slow_ticks} with no budget"
+        );
+    }
+
+    #[test]
+    fn standalone_chunk_generation_matches_live() {
+        let settings = Settings { simulate_chunks: false, ..Settings::default() };
+        let mut scene = TestScene::empty();
+
+        let is_generated = |scene: &TestScene| {
+            scene.ch.chunk_at((0, 0)).map_or(false, |c| {
+                matches!(c.state(), ChunkState::Cached | ChunkState::Active)
+            })
+        };
+
+        // generation happens on other threads
+        for _ in 0..5000 {
+            if is_generated(&scene) {
+                break;
+            }
+            scene.tick(&settings, 1);
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        assert!(
+            is_generated(&scene),
+            "chunk (0, 0) never finished generating"
+        );
+
+        let (pixels, colors) = scene
+            .ch
+            .generator
+            .generate_chunk(0, 0, 2, &scene.registries);
+
+        let live = scene.ch.chunk_at((0, 0)).unwrap();
+        assert!(live.pixels().as_ref().unwrap()[..] == pixels[..]);
+        assert!(live.colors()[..] == colors[..]);
+    }
+
+    #[test]
+    fn standalone_chunk_generation_places_structures() {
+        let mut scene = TestScene::empty();
+        // has populators that reach into neighbors and features that spawn structures
+        scene.ch = ChunkHandler::new(BiomeTestGenerator::new(), None);
+        scene.registries = std::sync::Arc::new(Registries::init(&scene.file_helper));
+
+        // live generation order depends on timing, so compare against a forced one instead
+        scene
+            .ch
+            .force_generate((0, 0), 3, 2, &scene.registries, &mut scene.ecs);
+        loop {
+            let placed = |scene: &TestScene| {
+                let nodes = scene.ecs.read_storage::<StructureNode>();
+                let placed = nodes.join().filter(|n| n.generated.is_some()).count();
+                (nodes.join().count(), placed)
+            };
+            let before = placed(&scene);
+            UpdateStructureNodes {
+                chunk_handler: &mut scene.ch,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+            scene.ecs.maintain();
+            if placed(&scene) == before {
+                break;
+            }
+        }
+        assert!(
+            scene
+                .ecs
+                .read_storage::<StructureNode>()
+                .join()
+                .any(|n| n.generated.is_some()),
+            "no structures were placed"
+        );
+
+        let (pixels, colors) = scene
+            .ch
+            .generator
+            .generate_chunk(0, 0, 2, &scene.registries);
+
+        let forced = scene.ch.chunk_at((0, 0)).unwrap();
+        assert!(forced.pixels().as_ref().unwrap()[..] == pixels[..]);
+        assert!(forced.colors()[..] == colors[..]);
+    }
+
+    #[test]
+    fn collected_materials_have_placers() {
+        let scene = TestScene::empty();
+        let registries = Registries::init(&scene.file_helper);
+
+        let mut collectible = 0;
+        for (id, material) in &registries.materials {
+            if let Some(placer) = &material.placer {
+                assert!(
+                    registries.material_placers.get(placer).is_some(),
+                    "{id} is collected as {placer}, which isn't registered"
+                );
+                collectible += 1;
+            }
+        }
+        assert!(collectible > 0);
+
+        assert_eq!(
+            registries.materials.get(&*material::GLASS).unwrap().placer,
+            Some(placer::GLASS.clone())
+        );
+    }
+
+    #[test]
+    fn material_histogram_counts_region() {
+        let mut world = TestScene::world();
+
+        let stone = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let cobble = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(64, 64, 64));
+
+        // crosses chunk boundaries on both axes
+        for x in -20..30 {
+            for y in -10..10 {
+                world.chunk_handler.set_pixel(x, y, stone.clone()).unwrap();
+            }
+        }
+        for x in 0..10 {
+            for y in 20..25 {
+                world.chunk_handler.set_pixel(x, y, cobble.clone()).unwrap();
+            }
+        }
+
+        let histogram = world.material_histogram(Rect::new(-50, -50, 50, 50), None);
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(histogram[&*material::TEST], 1000);
+        assert_eq!(histogram[&*material::COBBLE_STONE], 50);
+        assert_eq!(histogram[&*material::AIR], 100 * 100 - 1050);
+
+        // only part of the rect is inside the region
+        let histogram = world.material_histogram(Rect::new(25, 5, 125, 15), None);
+        assert_eq!(histogram[&*material::TEST], 25);
+        assert_eq!(histogram[&*material::AIR], 1000 - 25);
+
+        // chunk (4, 0) isn't loaded
+        let missing = Rect::new(400, 0, 500, 100);
+        assert!(world.material_histogram(missing, None).is_empty());
+        let generated = world.material_histogram(missing, Some(&Registries::empty()));
+        assert_eq!(generated.values().sum::<u64>(), CHUNK_AREA as u64);
+    }
+
+    /// Drops a `size`x`size` pixel body from 100 pixels up onto a 20 pixel deep layer of sand,
+    ///   returning the world and how many pixels of sand were knocked out.
+    fn drop_body_on_sand(size: u16) -> (World<ServerChunk>, usize) {
+        let mut world = TestScene::world();
+
+        let registries = TestScene::registries();
+        let settings = Settings::default();
+
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let bedrock = material::BEDROCK.instance(PhysicsType::Solid, Color::rgb(20, 20, 20));
+        for x in -50..50 {
+            for y in 0..20 {
+                world.chunk_handler.set_pixel(x, y, sand.clone()).unwrap();
+            }
+        }
+        // right where the body lands
+        for x in -1..=0 {
+            for y in 0..=1 {
+                world
+                    .chunk_handler
+                    .set_pixel(x, y, bedrock.clone())
+                    .unwrap();
+            }
+        }
+
+        // stands in for the terrain's collision, with its top at y = 0
+        let ground = world.physics.bodies.insert(
+            RigidBodyBuilder::fixed()
+                .translation(Vector2::new(0.0, 1.0))
+                .build(),
+        );
+        world.physics.colliders.insert_with_parent(
+            ColliderBuilder::cuboid(5.0, 1.0).build(),
+            ground,
+            &mut world.physics.bodies,
+        );
+
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let pixels = vec![solid; size as usize * size as usize];
+        let half = f32::from(size) / 2.0 / PHYSICS_SCALE;
+        let mut bodies = FSRigidBody::make_bodies(
+            &pixels,
+            size,
+            size,
+            &mut world.physics,
+            (-half, -10.0 - half * 2.0),
+        )
+        .unwrap();
+        assert!(!bodies.is_empty());
+        world.rigidbodies.append(&mut bodies);
+
+        // long enough to land and settle
+        for _ in 0..200 {
+            world.tick_physics(&settings, &registries);
+        }
+
+        // the bedrock can't be broken
+        for x in -1..=0 {
+            for y in 0..=1 {
+                assert_eq!(
+                    world.chunk_handler.pixel(x, y).unwrap().material_id,
+                    *material::BEDROCK
+                );
+            }
+        }
+
+        let broken = (-50..50)
+            .flat_map(|x| (0..20).map(move |y| (x, y)))
+            .filter(|&(x, y)| world.chunk_handler.pixel(x, y).unwrap().physics == PhysicsType::Air)
+            .count();
+
+        (world, broken)
+    }
+
+    #[test]
+    fn heavy_impact_makes_crater() {
+        let (world, broken) = drop_body_on_sand(60);
+        assert!(broken > 0, "heavy body didn't leave a crater");
+        assert!(!world
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .is_empty());
+
+        // a light body hitting just as fast doesn't break anything
+        let (_, broken) = drop_body_on_sand(12);
+        assert_eq!(broken, 0);
+    }
+
+    #[test]
+    fn light_body_floats_heavy_body_sinks() {
+        let mut world = TestScene::world();
+        let registries = TestScene::registries();
+        let settings = Settings::default();
+
+        // a pool with its surface at y = 0
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(40, 80, 200));
+        for x in -100..100 {
+            for y in 0..300 {
+                world.chunk_handler.set_pixel(x, y, water.clone()).unwrap();
+            }
+        }
+
+        // wide planks so they don't roll over, dropped from just above the surface
+        let (w, h) = (20, 6);
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(120, 90, 60));
+        let mut spawn = |x: f32, density: f32| {
+            let mut rb =
+                FSRigidBody::from_pixels(vec![solid.clone(); w * h], w as u16, h as u16).unwrap();
+            rb.make_body(
+                &mut world.physics,
+                (x / PHYSICS_SCALE, -10.0 / PHYSICS_SCALE),
+            )
+            .unwrap();
+            let handle = rb.body.unwrap();
+            let collider = world.physics.bodies.get(handle).unwrap().colliders()[0];
+            world
+                .physics
+                .colliders
+                .get_mut(collider)
+                .unwrap()
+                .set_density(density);
+            world.rigidbodies.push(rb);
+            handle
+        };
+        let light = spawn(-60.0, 0.3);
+        let heavy = spawn(40.0, 3.0);
+
+        for _ in 0..400 {
+            world.tick_physics(&settings, &registries);
+        }
+
+        // floats with 0.3 / LIQUID_DENSITY of its height under the surface
+        let body = world.physics.bodies.get(light).unwrap();
+        let depth = 0.3 / LIQUID_DENSITY * h as f32;
+        let expected_y = depth - h as f32 / 2.0;
+        let y = body.center_of_mass().y * PHYSICS_SCALE;
+        assert!(
+            (y - expected_y).abs() < 1.5,
+            "light body at {y}, expected about {expected_y}"
+        );
+        assert!(body.linvel().norm() * PHYSICS_SCALE < 1.0);
+        assert!(body.rotation().angle().abs() < 0.1);
+
+        // sinks all the way under and keeps going
+        let body = world.physics.bodies.get(heavy).unwrap();
+        let y = body.center_of_mass().y * PHYSICS_SCALE;
+        assert!(y > 30.0, "heavy body at {y}");
+        assert!(body.linvel().y > 0.0);
+    }
+
+    #[test]
+    fn sleeping_body_pixels_wait_for_wake() {
+        let mut world = TestScene::world();
+        let registries = TestScene::registries();
+        let settings = Settings::default();
+
+        // an 8x8 cup: solid bottom half, with a grain of sand floating at the top
+        let solid = material::TEST.instance(PhysicsType::Solid, Color::rgb(80, 80, 80));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let mut pixels = vec![MaterialInstance::air(); 8 * 8];
+        for px in &mut pixels[8 * 4..] {
+            *px = solid.clone();
+        }
+        pixels[3] = sand.clone();
+
+        let mut rb = FSRigidBody::from_pixels(pixels, 8, 8).unwrap();
+        rb.make_body(&mut world.physics, (0.0, 0.0)).unwrap();
+        rb.get_body_mut(&mut world.physics).unwrap().sleep();
+        world.rigidbodies.push(rb);
+
+        for _ in 0..10 {
+            world.simulate_rigidbody_pixels(&settings, &registries);
+        }
+        let rb = &world.rigidbodies[0];
+        assert_eq!(rb.pixels[3], sand);
+        assert!(rb.get_body(&world.physics).unwrap().is_sleeping());
+
+        // editing it wakes it up, then the sand can fall
+        world.rigidbodies[0]
+            .set_pixel(&mut world.physics, 5, 0, sand.clone())
+            .unwrap();
+        assert!(!world.rigidbodies[0]
+            .get_body(&world.physics)
+            .unwrap()
+            .is_sleeping());
+
+        for _ in 0..10 {
+            world.simulate_rigidbody_pixels(&settings, &registries);
+        }
+        assert_eq!(world.rigidbodies.len(), 1);
+        assert_ne!(world.rigidbodies[0].pixels[3].physics, PhysicsType::Sand);
+    }
+
+    #[test]
+    fn selection_rigidbody_keeps_colors() {
+        let mut world = TestScene::world();
+
+        // a 6x6 block with a different color in every pixel
+        let (x, y) = (20, 20);
+        let mut expected = vec![];
+        for dy in 0..6_u8 {
+            for dx in 0..6_u8 {
+                let color = Color::rgb(dx * 40, dy * 40, 200);
+                let mat = material::TEST.instance(PhysicsType::Solid, color);
+                world
+                    .chunk_handler
+                    .set_pixel(x + i64::from(dx), y + i64::from(dy), mat.clone())
+                    .unwrap();
+                expected.push(mat);
+            }
+        }
+
+        // selecting a bit of air around it too
+        assert_eq!(
+            world
+                .selection_to_rigidbodies(x - 2, y - 2, 10, 10)
+                .unwrap(),
+            1
+        );
+        for dy in 0..6 {
+            for dx in 0..6 {
+                assert!(world
+                    .chunk_handler
+                    .pixel(x + dx, y + dy)
+                    .unwrap()
+                    .is_empty(&TestScene::registries().materials));
+            }
+        }
+
+        let rb = &world.rigidbodies[0];
+        assert_eq!((rb.width, rb.height), (10, 10));
+        let image = rb.image_data();
+        assert_eq!(image.len(), 10 * 10 * 4);
+        for dy in 0..6 {
+            for dx in 0..6 {
+                let mat = &expected[dx + dy * 6];
+                let i = (dx + 2) + (dy + 2) * 10;
+                assert_eq!(&rb.pixels[i], mat);
+                assert_eq!(
+                    image[i * 4..i * 4 + 4],
+                    [mat.color.r, mat.color.g, mat.color.b, mat.color.a]
+                );
+            }
+        }
+
+        // nothing to make a body out of, so nothing changes
+        assert_eq!(world.selection_to_rigidbodies(-60, -60, 10, 10).unwrap(), 0);
+        assert_eq!(world.rigidbodies.len(), 1);
+    }
+
+    #[test]
+    fn exceeding_max_rigidbodies_settles_the_oldest() {
+        let mut world = TestScene::world();
+        world.rules.max_rigidbodies = 1;
+
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for (x, y) in [(20, 20), (60, 20)] {
+            for dy in 0..5 {
+                for dx in 0..5 {
+                    world
+                        .chunk_handler
+                        .set_pixel(x + dx, y + dy, stone.clone())
+                        .unwrap();
+                }
+            }
+        }
+
+        assert_eq!(world.selection_to_rigidbodies(20, 20, 5, 5).unwrap(), 1);
+        assert_eq!(world.recycle_rigidbodies(&TestScene::registries()), 0);
+        let oldest = world.rigidbodies[0].body.unwrap();
+        assert_eq!(world.selection_to_rigidbodies(60, 20, 5, 5).unwrap(), 1);
+        assert!(world
+            .chunk_handler
+            .pixel(20, 20)
+            .unwrap()
+            .is_empty(&TestScene::registries().materials));
+
+        world.tick_physics(&Settings::default(), &TestScene::registries());
+
+        // the oldest body is back in the world where it was, and out of physics
+        assert_eq!(world.rigidbodies.len(), 1);
+        assert_ne!(world.rigidbodies[0].body, Some(oldest));
+        assert!(world.physics.bodies.get(oldest).is_none());
+        for dy in 0..5 {
+            for dx in 0..5 {
+                assert_eq!(world.chunk_handler.pixel(20 + dx, 20 + dy).unwrap(), &stone);
+            }
+        }
+        assert!(world
+            .ecs
+            .read_resource::<ParticleSystem>()
+            .active
+            .is_empty());
+    }
+
+    #[test]
+    fn reloaded_material_color_remaps_pixels() {
+        let mut world = TestScene::world();
+
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        let sand = material::TEST.instance(PhysicsType::Sand, red);
+        let gone = material::GLASS.instance(PhysicsType::Solid, red);
+        world.chunk_handler.set_pixel(10, 20, sand.clone()).unwrap();
+        world.chunk_handler.set_pixel(11, 20, gone).unwrap();
+        world
+            .rigidbodies
+            .push(FSRigidBody::from_pixels(vec![sand.clone(); 4], 2, 2).unwrap());
+        world
+            .ecs
+            .write_resource::<ParticleSystem>()
+            .active
+            .push(Particle::new(
+                sand,
+                Position { x: 5.0, y: 5.0 },
+                Velocity { x: 0.0, y: 0.0 },
+            ));
+
+        // the test material's color was changed and glass was removed
+        let mut registries = Registries::empty();
+        registries.materials.register(
+            material::TEST.clone(),
+            material::Material {
+                display_name: "Test".to_string(),
+                ..material::Material::default()
+            },
+        );
+        registries.material_placers.register(
+            "test",
+            MaterialPlacer {
+                meta: MaterialPlacerMeta { display_name: "Test".to_string() },
+                sampler: Box::new(material::TEST.instance(PhysicsType::Solid, blue)),
+            },
+        );
+
+        assert_eq!(world.remap_materials(&registries), 4 + 1 + 2);
+
+        let px = world.chunk_handler.pixel(10, 20).unwrap();
+        assert_eq!(px.physics, PhysicsType::Sand);
+        assert_eq!(px.material_id, *material::TEST);
+        assert_eq!(px.color, blue);
+        assert!(world
+            .chunk_handler
+            .pixel(11, 20)
+            .unwrap()
+            .is_empty(&TestScene::registries().materials));
+
+        let rb = &world.rigidbodies[0];
+        assert!(rb.image_dirty);
+        assert!(rb
+            .pixels
+            .iter()
+            .all(|p| p.color == blue && p.physics == PhysicsType::Sand));
+        let particles = world.ecs.read_resource::<ParticleSystem>();
+        assert_eq!(particles.active[0].material.color, blue);
+
+        // running it again doesn't change anything else
+        drop(particles);
+        assert_eq!(world.remap_materials(&registries), 0);
+    }
+
+    #[test]
+    fn flow_tracks_moving_pixels() {
+        let mut scene = TestScene::new();
+        let settings = Settings {
+            load_chunks: false,
+            draw_flow_overlay: true,
+            ..Settings::default()
+        };
+
+        let floor = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for x in 0..30 {
+            scene.ch.set_pixel(x, 14, floor.clone()).unwrap();
+        }
+        // close enough to the floor that it falls as a pixel instead of turning into a particle
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        scene.ch.set_pixel(10, 10, sand).unwrap();
+
+        let flow_at = |scene: &TestScene, x: usize, y: usize| {
+            let chunk = scene.ch.manager.chunk_at((0, 0)).unwrap();
+            chunk.flow().as_ref().unwrap()[x + y * CHUNK_WIDTH as usize]
+        };
+        let sand_pos = |scene: &TestScene| {
+            (0..30)
+                .flat_map(|x| (0..14).map(move |y| (x, y)))
+                .find(|&(x, y)| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand)
+                .map(|(x, y)| (x as usize, y as usize))
+                .unwrap()
+        };
+
+        // the pixel it left and the one it moved to were both written this tick
+        scene.tick(&settings, 1);
+        let (x, y) = sand_pos(&scene);
+        assert!(y > 10);
+        assert_eq!(flow_at(&scene, 10, 10), FLOW_MOVED);
+        assert_eq!(flow_at(&scene, x, y), FLOW_MOVED);
+
+        // once it's resting on the floor it fades out
+        scene.tick(&settings, 30);
+        let (x, y) = sand_pos(&scene);
+        assert_eq!(y, 13);
+        let settled = flow_at(&scene, x, y);
+        scene.tick(&settings, 3);
+        assert_eq!(sand_pos(&scene), (x, y));
+        assert_eq!(
+            flow_at(&scene, x, y),
+            settled.saturating_sub(FLOW_DECAY * 3)
+        );
+        assert!(flow_at(&scene, x, y) < FLOW_MOVED);
+
+        // and the buffers are dropped when the overlay is turned off
+        scene.tick(&Settings { load_chunks: false, ..Settings::default() }, 1);
+        assert!(scene.ch.manager.chunk_at((0, 0)).unwrap().flow().is_none());
+    }
+
+    #[test]
+    fn sim_time_recorded_per_chunk() {
+        let mut scene = TestScene::new();
+        let settings = Settings {
+            load_chunks: false,
+            draw_chunk_sim_time_overlay: true,
+            simulate_chunks_substeps: 2,
+            ..Settings::default()
+        };
+
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        scene.ch.set_pixel(10, 10, sand).unwrap();
+
+        // both substeps add up into the chunk's time
+        scene.tick(&settings, 1);
+        let chunk = scene.ch.manager.chunk_at((0, 0)).unwrap();
+        assert!(chunk.sim_time().is_some());
+
+        // timing is skipped entirely when the overlay is off
+        scene.tick(&Settings { load_chunks: false, ..Settings::default() }, 1);
+        let mut chunks = scene.ch.manager.chunks_iter();
+        assert!(chunks.all(|c| c.sim_time().is_none()));
+    }
+
+    #[test]
+    fn sim_pixel_budget_spreads_chunks_over_ticks() {
+        let mut scene = TestScene::new();
+        // room for 5 fully dirty chunks per tick
+        let settings = Settings {
+            load_chunks: false,
+            sim_pixel_budget: CHUNK_AREA as u32 * 5,
+            simulate_chunks_substeps: 2,
+            ..Settings::default()
+        };
+
+        // only the chunks with all their neighbors loaded can be simulated
+        let keys: Vec<_> = (-2..=2)
+            .flat_map(|x| (-2..=2).map(move |y| (x, y)))
+            .collect();
+        for ch in scene.ch.manager.chunks_iter_mut() {
+            ch.set_dirty_rect(None);
+        }
+        for &(x, y) in &keys {
+            scene.ch.force_update_chunk(x, y);
+        }
+        let dirty = |scene: &TestScene| {
+            keys.iter()
+                .filter(|&&k| scene.ch.manager.chunk_at(k).unwrap().dirty_rect().is_some())
+                .copied()
+                .collect::<Vec<_>>()
+        };
+
+        let mut handled_at = std::collections::HashMap::new();
+        for tick in 1..=5 {
+            let before = dirty(&scene);
+            scene.tick(&settings, 1);
+            let after = dirty(&scene);
+            // the second substep has nothing left to spend
+            assert_eq!(after.len(), 25 - tick * 5);
+            for k in before.into_iter().filter(|k| !after.contains(k)) {
+                assert!(handled_at.insert(k, tick).is_none());
+            }
+            // the chunks still waiting are the ones first in line next tick
+            assert_eq!(scene.ch.sim_budget.deferred().len(), after.len());
+        }
+        // every chunk got its turn exactly once
+        assert_eq!(handled_at.len(), 25);
+
+        // no limit handles everything right away
+        for &(x, y) in &keys {
+            scene.ch.force_update_chunk(x, y);
+        }
+        scene.tick(&Settings { load_chunks: false, ..Settings::default() }, 1);
+        assert!(dirty(&scene).is_empty());
+    }
+
+    #[test]
+    fn visit_loaded_chunks() {
+        let mut scene = TestScene::new();
+        let mut expected: Vec<_> = (-3..=3)
+            .flat_map(|x| (-3..=3).map(move |y| (x, y)))
+            .collect();
+        expected.sort_unstable();
+
+        let mut coords = scene.ch.loaded_chunk_coords();
+        coords.sort_unstable();
+        assert_eq!(coords, expected);
+
+        let mut visited = vec![];
+        scene.ch.for_each_loaded_chunk(|key, ch| {
+            assert_eq!(key, (ch.chunk_x(), ch.chunk_y()));
+            visited.push(key);
+        });
+        visited.sort_unstable();
+        assert_eq!(visited, expected);
+
+        // changes made through the visitor get simulated
+        for ch in scene.ch.manager.chunks_iter_mut() {
+            ch.set_dirty_rect(None);
+        }
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        scene.ch.for_each_loaded_chunk_mut(|key, ch| {
+            (key == (0, 0)).then(|| {
+                ch.pixels_mut().as_mut().unwrap()[3 + 4 * CHUNK_WIDTH as usize] = sand.clone();
+                Rect::new_wh(3, 4, 1, 1)
+            })
+        });
+        let dirty: Vec<_> = scene
+            .ch
+            .manager
+            .kv_iter()
+            .filter_map(|(key, ch)| ch.dirty_rect().map(|r| (key, (r.x1, r.y1, r.x2, r.y2))))
+            .collect();
+        assert_eq!(dirty, vec![((0, 0), (3, 4, 4, 5))]);
+
+        scene.tick(&Settings { load_chunks: false, ..Settings::default() }, 1);
+        assert_ne!(scene.ch.pixel(3, 4).unwrap(), &sand);
+    }
+
+    #[test]
+    fn transmuted_sand_stops_falling() {
+        let mut world = TestScene::world();
+
+        // two floating blocks of sand, only the left one is transmuted
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        for x in (0..20).chain(40..60) {
+            for y in 20..30 {
+                world.chunk_handler.set_pixel(x, y, sand.clone()).unwrap();
+            }
+        }
+
+        let solid = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        let rect = Rect::new(-10, 10, 30, 40);
+        assert_eq!(world.transmute(rect, &material::TEST, &solid).unwrap(), 200);
+        assert_eq!(world.chunk_handler.edit_history.len(), 1);
+        assert_eq!(world.ecs.read_resource::<WorldEditEvents>().len(), 1);
+
+        // nothing left to transmute
+        assert_eq!(world.transmute(rect, &material::TEST, &solid).unwrap(), 0);
+        assert_eq!(world.chunk_handler.edit_history.len(), 1);
+
+        let mut scene = TestScene::new();
+        std::mem::swap(&mut scene.ch, &mut world.chunk_handler);
+        scene.tick(&Settings { load_chunks: false, ..Settings::default() }, 5);
+
+        for x in 0..20 {
+            for y in 0..60 {
+                let px = scene.ch.pixel(x, y).unwrap();
+                if (20..30).contains(&y) {
+                    assert_eq!(px, &solid);
+                } else {
+                    assert!(px.is_empty(&scene.registries.materials));
+                }
+            }
+        }
+        // the other block kept falling, partly as particles
+        let still_there = (40..60)
+            .flat_map(|x| (20..30).map(move |y| (x, y)))
+            .filter(|&(x, y)| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand)
+            .count();
+        assert!(still_there < 200);
+
+        // and undoing it brings the sand back
+        std::mem::swap(&mut scene.ch, &mut world.chunk_handler);
+        assert!(world.chunk_handler.undo_edit().unwrap());
+        assert_eq!(world.chunk_handler.pixel(0, 20).unwrap(), &sand);
+    }
+
+    #[test]
+    fn pour_and_siphon_liquid() {
+        let mut world = TestScene::world();
+
+        // a cup with walls at x = 10 and 20 and a floor at y = 20
+        let wall = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for i in 10..=20 {
+            world.chunk_handler.set_pixel(10, i, wall.clone()).unwrap();
+            world.chunk_handler.set_pixel(20, i, wall.clone()).unwrap();
+            world.chunk_handler.set_pixel(i, 20, wall.clone()).unwrap();
+        }
+        world
+            .chunk_handler
+            .manager
+            .chunk_at_mut((0, 0))
+            .unwrap()
+            .set_dirty_rect(None);
+
+        let count = |world: &World<ServerChunk>, physics: PhysicsType| {
+            (5..=25)
+                .flat_map(|x| (5..=25).map(move |y| (x, y)))
+                .filter(|&(x, y)| world.chunk_handler.pixel(x, y).unwrap().physics == physics)
+                .count()
+        };
+        let walls = count(&world, PhysicsType::Solid);
+
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        assert!(world
+            .pour_liquid(15, 15, 6, &sand, &TestScene::registries().materials)
+            .is_err());
+
+        // the brush overlaps the walls, but only the air gets filled
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(64, 64, 255));
+        let poured = world
+            .pour_liquid(15, 15, 6, &water, &TestScene::registries().materials)
+            .unwrap();
+        assert!(poured > 0);
+        assert_eq!(count(&world, PhysicsType::Liquid), poured);
+        assert_eq!(count(&world, PhysicsType::Solid), walls);
+        assert_eq!(world.chunk_handler.pixel(15, 15).unwrap(), &water);
+        assert_eq!(world.chunk_handler.pixel(10, 15).unwrap(), &wall);
+        // so it starts flowing right away
+        let chunk = world.chunk_handler.manager.chunk_at((0, 0)).unwrap();
+        assert!(chunk.dirty_rect().is_some());
+        assert_eq!(world.ecs.read_resource::<WorldEditEvents>().len(), 1);
+
+        // siphoning takes out the liquid and nothing else
+        assert_eq!(world.siphon_liquid(15, 15, 6).unwrap(), poured);
+        assert_eq!(count(&world, PhysicsType::Liquid), 0);
+        assert_eq!(count(&world, PhysicsType::Solid), walls);
+        assert!(world
+            .chunk_handler
+            .pixel(15, 15)
+            .unwrap()
+            .is_empty(&TestScene::registries().materials));
+        assert_eq!(world.siphon_liquid(15, 15, 6).unwrap(), 0);
+    }
+
+    #[test]
+    fn tools_stop_at_world_border() {
+        let mut world = TestScene::world();
+        // the chunks left of the border are loaded, the ones right of it aren't
+        world.chunk_handler.border = Some(WorldBorder::open((0, 0), (3, 0)));
+
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(64, 64, 255));
+        let materials = TestScene::registries().materials;
+
+        // an edit can't reach past the border, even into a loaded chunk
+        let mut edit = world.chunk_handler.begin_edit();
+        assert!(edit.set(-5, 50, water.clone()).is_err());
+        assert!(edit.set(5, 50, water.clone()).is_ok());
+        edit.commit().unwrap();
+
+        // pouring over either side only fills what's inside
+        assert!(world.pour_liquid(0, 50, 4, &water, &materials).unwrap() > 0);
+        assert!(world.pour_liquid(398, 50, 4, &water, &materials).unwrap() > 0);
+        for y in 46..=54 {
+            for x in (-4..0).chain(400..=402) {
+                assert!(world
+                    .chunk_handler
+                    .pixel(x, y)
+                    .unwrap()
+                    .is_empty(&materials));
+            }
+        }
+        assert_eq!(world.chunk_handler.pixel(0, 50).unwrap(), &water);
+        assert_eq!(world.chunk_handler.pixel(398, 50).unwrap(), &water);
+
+        // the barrier past a solid border reads as bedrock, but transmuting it does nothing
+        let barrier = WorldBorder::barrier((0, 0), (3, 0));
+        world.chunk_handler.border = Some(barrier.clone());
+        let rect = Rect::new(390, 40, 410, 60);
+        assert_eq!(
+            world
+                .transmute(rect, &material::BEDROCK, &MaterialInstance::air())
+                .unwrap(),
+            0
+        );
+        assert_eq!(world.chunk_handler.pixel(405, 50), Ok(&barrier.outside));
+    }
+
+    #[test]
+    fn journal_replay_reproduces_edits() {
+        let dir = std::env::temp_dir().join(format!("fs_world_journal_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut world = TestScene::world();
+        world.chunk_handler.path = Some(dir.clone());
+
+        // enough edits to get a snapshot in
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for x in 0..JOURNAL_SNAPSHOT_INTERVAL as i64 + 3 {
+            let mut edit = world.chunk_handler.begin_edit();
+            edit.set(x, 10, stone.clone()).unwrap();
+            edit.commit().unwrap();
+        }
+        let water = material::TEST.instance(PhysicsType::Liquid, Color::rgb(64, 64, 255));
+        world
+            .pour_liquid(50, 50, 4, &water, &TestScene::registries().materials)
+            .unwrap();
+        world.siphon_liquid(50, 52, 2).unwrap();
+        assert!(world.chunk_handler.undo_edit().unwrap());
+        world.siphon_liquid(48, 50, 1).unwrap();
+
+        // it's written in the background
+        journal::flush(&mut world.chunk_handler);
+        let entries = journal::read(&journal_path(&dir)).unwrap();
+        let is_snapshot = |e: &&JournalEntry| matches!(e, JournalEntry::Snapshot { .. });
+        assert!(entries.iter().any(|e| is_snapshot(&e)));
+        // 35 edits, a pour, two siphons and an undo
+        assert_eq!(entries.iter().filter(|e| !is_snapshot(e)).count(), 39);
+
+        // replaying on top of the world as it was reproduces it exactly, every time
+        for _ in 0..2 {
+            let mut base = TestScene::new().ch;
+            journal::replay(&mut base, &entries).unwrap();
+            for x in 0..i64::from(CHUNK_WIDTH) {
+                for y in 0..i64::from(CHUNK_HEIGHT) {
+                    assert_eq!(base.pixel(x, y), world.chunk_handler.pixel(x, y));
+                }
+            }
+        }
+
+        // an entry cut off in the middle of its length claims way more than is left
+        let mut broken = 0_u32.to_le_bytes().to_vec();
+        broken.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+        broken.extend_from_slice(&[1, 2, 3]);
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal_path(&dir))
+            .and_then(|mut f| std::io::Write::write_all(&mut f, &broken))
+            .unwrap();
+        assert_eq!(journal::read(&journal_path(&dir)).unwrap(), entries);
+
+        // once everything is saved the journal isn't needed anymore
+        world.save().unwrap();
+        assert!(!journal_path(&dir).exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn loading_a_world_recovers_its_journal() {
+        let dir = std::env::temp_dir().join(format!("fs_world_recover_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        // reloading generates the chunks the journal touches, so this needs the real biomes
+        let file_helper = FileHelper::new("../gamedir/".into(), "../gamedir/assets/".into());
+        let registries = std::sync::Arc::new(Registries::init(&file_helper));
+
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        {
+            let mut world = World::<ServerChunk>::load(
+                Some(dir.clone()),
+                Some(2),
+                WorldPreset::default(),
+                &registries,
+            );
+            world.chunk_handler = TestScene::new().ch;
+            world.chunk_handler.path = Some(dir.clone());
+            let mut edit = world.chunk_handler.begin_edit();
+            edit.set(5, 10, stone.clone()).unwrap();
+            edit.commit().unwrap();
+            // the game crashes here, without saving
+        }
+
+        let world = World::<ServerChunk>::load(
+            Some(dir.clone()),
+            Some(2),
+            WorldPreset::default(),
+            &registries,
+        );
+        assert_eq!(world.chunk_handler.pixel(5, 10), Ok(&stone));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn erasing_glass_spawns_shards() {
+        let mut scene = TestScene::new();
+        let registries = TestScene::registries();
+        let shards = registries
+            .materials
+            .get(&*material::GLASS)
+            .and_then(|m| m.destroy_particles.as_ref())
+            .map(|spec| usize::from(spec.count))
+            .unwrap();
+        assert!(shards > 0);
+
+        let glass = material::GLASS.instance(PhysicsType::Solid, Color::rgba(200, 230, 255, 96));
+        scene.ch.set_pixel(10, 10, glass).unwrap();
+
+        let still = Velocity { x: 0.0, y: 0.0 };
+        let mut particles = vec![];
+        let destroyed = destroy_pixel(&mut scene.ch, 10, 10, &still, &registries, &mut particles)
+            .unwrap()
+            .unwrap();
+        assert_eq!(destroyed.particles, shards);
+        assert_eq!(particles.len(), shards);
+        assert!(particles.iter().all(|p| {
+            p.material.material_id == *material::GLASS && p.material.physics == PhysicsType::Sand
+        }));
+        assert!(scene.empty_at(10, 10));
+
+        // erasing air doesn't make anything
+        let erased = destroy_pixel(&mut scene.ch, 10, 10, &still, &registries, &mut particles);
+        assert!(erased.unwrap().is_none());
+        assert_eq!(particles.len(), shards);
+    }
+
+    #[test]
+    fn spawn_player_components() {
+        let mut world = World::<ServerChunk>::create(None, Some(2));
+        let pos = Position { x: 10.0, y: -30.0 };
+        let player = spawn_player(&mut world, pos.clone());
+
+        let ecs = &world.ecs;
+        assert!(ecs.read_storage::<Player>().contains(player));
+        assert!(ecs.read_storage::<Inventory>().contains(player));
+        assert!(ecs.read_storage::<GameEntity>().contains(player));
+        assert!(ecs.read_storage::<PhysicsEntity>().contains(player));
+        assert!(ecs.read_storage::<Persistent>().contains(player));
+        assert!(ecs.read_storage::<Hitbox>().contains(player));
+        assert!(ecs.read_storage::<Loader>().contains(player));
+        assert!(ecs.read_storage::<Velocity>().contains(player));
+        assert_eq!(ecs.read_storage::<Position>().get(player), Some(&pos));
+
+        // the physics body exists and is linked to the entity
+        let handle = ecs
+            .read_storage::<RigidBodyComponent>()
+            .get(player)
+            .unwrap()
+            .body;
+        let body = world.physics.bodies.get(handle).unwrap();
+        assert_eq!(body.colliders().len(), 1);
+        assert!(body.is_dynamic());
+        // offset from the entity like it always was
+        assert!((body.translation().x - 10.0 / PHYSICS_SCALE).abs() < 0.001);
+        assert!((body.translation().y - (-30.0 / PHYSICS_SCALE + 20.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn remote_edit_within_reach() {
+        let mut world = TestScene::world();
+        let settings = Settings::default();
+        let registries = TestScene::registries();
+        let player = spawn_player(&mut world, Position { x: 10.0, y: 10.0 });
+        // somebody else's player doesn't give this connection any reach
+        spawn_player(&mut world, Position { x: 310.0, y: 10.0 });
+
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, Color::rgb(90, 90, 100));
+        let chunks = world
+            .apply_remote_edit(
+                player,
+                vec![
+                    (12, 12, stone.clone()),
+                    // loaded, but too far from the player
+                    (310, 10, stone.clone()),
+                    // not loaded at all
+                    (5000, 10, stone.clone()),
+                ],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+
+        // every chunk is sent back, so the client gets corrected where the edit was refused
+        assert_eq!(chunks, vec![(0, 0), (3, 0), (50, 0)]);
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &stone);
+        let chunk = world.chunk_handler.manager.chunk_at((0, 0)).unwrap();
+        assert_eq!(
+            chunk.color(world::pixel_to_pos_in_chunk(12, 12)),
+            stone.color
+        );
+        assert_eq!(
+            world.chunk_handler.pixel(310, 10).unwrap(),
+            &MaterialInstance::air()
+        );
+
+        // it's a single edit that can be undone, like one made on the server
+        assert_eq!(world.chunk_handler.edit_history.len(), 1);
+        let events: Vec<_> = world
+            .ecs
+            .write_resource::<WorldEditEvents>()
+            .drain()
+            .collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cause, WorldEditCause::Tool);
+        let rect = &events[0].rect;
+        assert_eq!((rect.x1, rect.y1, rect.x2, rect.y2), (12, 12, 13, 13));
+    }
+
+    #[test]
+    fn remote_edit_only_places_what_the_player_could() {
+        let mut world = TestScene::world();
+        let mut settings = Settings::default();
+        let registries = TestScene::registries();
+        let player = spawn_player(&mut world, Position { x: 10.0, y: 10.0 });
+
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, Color::rgb(90, 90, 100));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+        let air = MaterialInstance::air();
+
+        // in creative anything in the brush palette can be placed, but not materials without a placer
+        world
+            .apply_remote_edit(
+                player,
+                vec![(12, 12, stone.clone()), (13, 12, sand)],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &stone);
+        assert_eq!(world.chunk_handler.pixel(13, 12).unwrap(), &air);
+
+        // in survival it has to come out of the player's inventory
+        settings.survival = true;
+        world
+            .apply_remote_edit(
+                player,
+                vec![(14, 12, stone.clone())],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(14, 12).unwrap(), &air);
+
+        // erasing collects the pixel, which can then be placed again, but only into air
+        world
+            .apply_remote_edit(player, vec![(12, 12, air.clone())], &settings, &registries)
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &air);
+        {
+            let inventories = world.ecs.read_storage::<Inventory>();
+            assert_eq!(
+                inventories
+                    .get(player)
+                    .unwrap()
+                    .count(&placer::SMOOTH_STONE),
+                1
+            );
+        }
+        world
+            .chunk_handler
+            .set_pixel(15, 12, stone.clone())
+            .unwrap();
+        world
+            .apply_remote_edit(
+                player,
+                vec![
+                    (15, 12, stone.clone()),
+                    (14, 12, stone.clone()),
+                    (16, 12, stone.clone()),
+                ],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(14, 12).unwrap(), &stone);
+        // there was only one to place
+        assert_eq!(world.chunk_handler.pixel(16, 12).unwrap(), &air);
+        let inventories = world.ecs.read_storage::<Inventory>();
+        assert_eq!(
+            inventories
+                .get(player)
+                .unwrap()
+                .count(&placer::SMOOTH_STONE),
+            0
+        );
+    }
+
+    #[test]
+    fn remote_edit_without_inventory() {
+        let mut world = TestScene::world();
+        let mut settings = Settings::default();
+        let registries = TestScene::registries();
+        let player = spawn_player(&mut world, Position { x: 10.0, y: 10.0 });
+        world.ecs.write_storage::<Inventory>().remove(player);
+
+        let stone = material::SMOOTH_STONE.instance(PhysicsType::Solid, Color::rgb(90, 90, 100));
+        let vacuum = material::VACUUM.instance(PhysicsType::Air, Color::TRANSPARENT);
+
+        // outside of survival the inventory isn't needed, and erasing can be to any empty material
+        world
+            .apply_remote_edit(
+                player,
+                vec![(12, 12, stone.clone())],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &stone);
+        world
+            .apply_remote_edit(
+                player,
+                vec![(12, 12, vacuum.clone())],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &vacuum);
+
+        // survival has nowhere to collect into or take from
+        settings.survival = true;
+        world
+            .chunk_handler
+            .set_pixel(13, 12, stone.clone())
+            .unwrap();
+        world
+            .apply_remote_edit(
+                player,
+                vec![(12, 12, stone.clone()), (13, 12, MaterialInstance::air())],
+                &settings,
+                &registries,
+            )
+            .unwrap();
+        assert_eq!(world.chunk_handler.pixel(12, 12).unwrap(), &vacuum);
+        assert_eq!(world.chunk_handler.pixel(13, 12).unwrap(), &stone);
+    }
+
+    #[test]
+    fn teleport_generates_destination() {
+        let mut world = World::<ServerChunk>::create(None, Some(2));
+        world.chunk_handler = TestScene::empty().ch;
+        world.rigidbodies.clear();
+        world.physics = Physics::new();
+        let registries = std::sync::Arc::new(TestScene::registries());
+
+        let player = spawn_player(&mut world, Position { x: 10.0, y: -30.0 });
+        world
+            .ecs
+            .write_storage::<Velocity>()
+            .insert(player, Velocity { x: 8.0, y: -3.0 })
+            .unwrap();
+        let camera = Camera::create_and_add(&mut world, Target::Entity(player));
+        world
+            .ecs
+            .write_storage::<AutoTarget>()
+            .get_mut(camera)
+            .unwrap()
+            .style = TargetStyle::EaseOut(1.0);
+
+        let (x, y) = (5030.0, -2980.0);
+        world.teleport(player, x, y, &registries).unwrap();
+
+        let positions = world.ecs.read_storage::<Position>();
+        let velocities = world.ecs.read_storage::<Velocity>();
+        assert_eq!(positions.get(player), Some(&Position { x, y }));
+        let vel = velocities.get(player).unwrap();
+        assert_eq!((vel.x, vel.y), (0.0, 0.0));
+
+        let handle = world
+            .ecs
+            .read_storage::<RigidBodyComponent>()
+            .get(player)
+            .unwrap()
+            .body;
+        let body = world.physics.bodies.get(handle).unwrap();
+        assert!((body.translation().x - x as f32 / PHYSICS_SCALE).abs() < 0.001);
+        assert!((body.translation().y - y as f32 / PHYSICS_SCALE).abs() < 0.001);
+        assert_eq!(body.linvel(), &Vector2::zeros());
+
+        // the camera doesn't ease its way across the world
+        assert_eq!(positions.get(camera), Some(&Position { x, y }));
+        let vel = velocities.get(camera).unwrap();
+        assert_eq!((vel.x, vel.y), (0.0, 0.0));
+
+        let (cx, cy) = world::pixel_to_chunk_pos(x as i64, y as i64);
+        let r = i32::from(TELEPORT_GENERATE_RADIUS);
+        for cy in cy - r..=cy + r {
+            for cx in cx - r..=cx + r {
+                let chunk = world.chunk_handler.manager.chunk_at((cx, cy)).unwrap();
+                assert_eq!(chunk.state(), ChunkState::Cached, "{cx}, {cy}");
+            }
+        }
+
+        // only entities that exist can be teleported
+        drop((positions, velocities));
+        world.ecs.delete_entity(player).unwrap();
+        world.ecs.maintain();
+        assert!(world.teleport(player, 0.0, 0.0, &registries).is_err());
+    }
+
+    #[test]
+    fn edge_clip_steps_over_small_ledges() {
+        let mut scene = TestScene::new();
+
+        // flat ground at y = 30, with a 2 pixel step up from x = 40
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for x in 0..90 {
+            for y in 30..35 {
+                scene.ch.set_pixel(x, y, stone.clone()).unwrap();
+            }
+            if x >= 40 {
+                scene.ch.set_pixel(x, 28, stone.clone()).unwrap();
+                scene.ch.set_pixel(x, 29, stone.clone()).unwrap();
+            }
+        }
+
+        let mut ecs = world::ecs();
+        let mut walker = |edge_clip_distance| {
+            ecs.create_entity()
+                .with(GameEntity)
+                .with(Persistent)
+                .with(Position { x: 20.0, y: 24.9 })
+                .with(Velocity { x: 0.0, y: 0.0 })
+                .with(Hitbox { x1: -3.0, y1: -5.0, x2: 3.0, y2: 5.0 })
+                .with(PhysicsEntity {
+                    gravity: 0.5,
+                    on_ground: false,
+                    edge_clip_distance,
+                    collision: true,
+                    collide_with_sand: true,
+                })
+                .build()
+        };
+        let climber = walker(2.0);
+        let stopper = walker(0.0);
+
+        for _ in 0..60 {
+            // holding right
+            for vel in (&mut ecs.write_storage::<Velocity>()).join() {
+                vel.x = 1.0;
+            }
+            UpdatePhysicsEntities {
+                chunk_handler: &mut scene.ch,
+                registries: &scene.registries,
+            }
+            .run_now(&ecs);
+        }
+
+        let positions = ecs.read_storage::<Position>();
+        let climbed = positions.get(climber).unwrap();
+        assert!(climbed.x > 50.0, "{climbed:?}");
+        // standing on top of the step
+        assert!((climbed.y + 5.0 - 28.0).abs() < 0.5, "{climbed:?}");
+
+        let stopped = positions.get(stopper).unwrap();
+        assert!(stopped.x < 40.0 - 3.0, "{stopped:?}");
+        assert!((stopped.y + 5.0 - 30.0).abs() < 0.5, "{stopped:?}");
+    }
+
+    #[test]
+    fn fast_entity_stops_at_thin_wall() {
+        let mut scene = TestScene::new();
+
+        // a one pixel thick wall at x = 60
+        let stone = material::COBBLE_STONE.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        for y in 0..40 {
+            scene.ch.set_pixel(60, y, stone.clone()).unwrap();
+        }
+
+        // moving many times its own width every tick
+        let entity = scene
+            .ecs
+            .create_entity()
+            .with(GameEntity)
+            .with(Persistent)
+            .with(Position { x: 20.0, y: 20.0 })
+            .with(Velocity { x: 100.0, y: 0.0 })
+            .with(Hitbox { x1: -3.0, y1: -5.0, x2: 3.0, y2: 5.0 })
+            .with(PhysicsEntity {
+                gravity: 0.0,
+                on_ground: false,
+                edge_clip_distance: 0.0,
+                collision: true,
+                collide_with_sand: true,
+            })
+            .build();
+
+        for _ in 0..10 {
+            UpdatePhysicsEntities {
+                chunk_handler: &mut scene.ch,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+
+            // the right edge of the hitbox never gets past the wall
+            let pos = scene
+                .ecs
+                .read_storage::<Position>()
+                .get(entity)
+                .unwrap()
+                .clone();
+            assert!(pos.x + 3.0 <= 60.0, "{pos:?}");
+        }
+
+        // and ends up right up against it
+        let pos = scene
+            .ecs
+            .read_storage::<Position>()
+            .get(entity)
+            .unwrap()
+            .clone();
+        assert!(pos.x + 3.0 > 59.0, "{pos:?}");
+        assert_eq!(scene.ch.pixel(60, 20).unwrap(), &stone);
+    }
+
+    #[test]
+    fn entities_pass_through_non_collidable_solids() {
+        let mut scene = TestScene::new();
+
+        let material = |entity_collidable| material::Material {
+            entity_collidable,
+            ..material::Material::default()
+        };
+        let decor_id: RegistryID<material::Material> = "decor".into();
+        let mut registries = Registries::empty();
+        registries
+            .materials
+            .register(material::TEST.clone(), material(true));
+        registries
+            .materials
+            .register(decor_id.clone(), material(false));
+        scene.registries = std::sync::Arc::new(registries);
+
+        let stone = material::TEST.instance(PhysicsType::Solid, Color::rgb(127, 127, 127));
+        let decor = decor_id.instance(PhysicsType::Solid, Color::rgb(40, 160, 40));
+        let sand = material::TEST.instance(PhysicsType::Sand, Color::rgb(255, 200, 100));
+
+        // a decorative ledge at y = 30 over a stone floor at y = 60, with a column of sand on the ledge
+        for x in 0..40 {
+            for y in 30..33 {
+                scene.ch.set_pixel(x, y, decor.clone()).unwrap();
+            }
+            for y in 60..65 {
+                scene.ch.set_pixel(x, y, stone.clone()).unwrap();
+            }
+        }
+        for y in 20..30 {
+            scene.ch.set_pixel(20, y, sand.clone()).unwrap();
+        }
+
+        let entity = scene
+            .ecs
+            .create_entity()
+            .with(GameEntity)
+            .with(Persistent)
+            .with(Position { x: 8.0, y: 24.9 })
+            .with(Velocity { x: 0.0, y: 0.0 })
+            .with(Hitbox { x1: -3.0, y1: -5.0, x2: 3.0, y2: 5.0 })
+            .with(PhysicsEntity {
+                gravity: 0.5,
+                on_ground: false,
+                edge_clip_distance: 0.0,
+                collision: true,
+                collide_with_sand: true,
+            })
+            .build();
+
+        let settings = Settings { load_chunks: false, ..Settings::default() };
+        for _ in 0..120 {
+            UpdatePhysicsEntities {
+                chunk_handler: &mut scene.ch,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+            scene.tick(&settings, 1);
+            UpdateParticles {
+                chunk_handler: &mut scene.ch,
+                interactions: ParticleInteractions::ALL,
+                registries: &scene.registries,
+            }
+            .run_now(&scene.ecs);
+        }
+
+        // the entity fell through the ledge onto the floor
+        let pos = scene
+            .ecs
+            .read_storage::<Position>()
+            .get(entity)
+            .unwrap()
+            .clone();
+        assert!((pos.y + 5.0 - 60.0).abs() < 0.5, "{pos:?}");
+
+        // but the sand is still piled on top of it
+        let sand_on_ledge = (0..40)
+            .flat_map(|x| (0..30).map(move |y| (x, y)))
+            .filter(|&(x, y)| scene.ch.pixel(x, y).unwrap().physics == PhysicsType::Sand)
+            .count();
+        assert_eq!(sand_on_ledge, 10);
+
+        // rigidbody colliders only leave it out if asked to
+        let mut pixels = vec![stone; 100];
+        for y in 0..10 {
+            for x in 5..10 {
+                pixels[x + y * 10] = decor.clone();
+            }
+        }
+        let mass = |physics: &mut Physics| {
+            let rbs = FSRigidBody::make_bodies(&pixels, 10, 10, physics, (0.0, 0.0)).unwrap();
+            rbs.iter()
+                .map(|rb| rb.get_body(physics).unwrap().mass())
+                .sum::<f32>()
+        };
+        let mut physics = Physics::new();
+        let full = mass(&mut physics);
+        physics.body_passable.insert(decor_id);
+        let filtered = mass(&mut physics);
+        assert!(filtered < full * 0.75, "{filtered} of {full}");
+    }
+
+    #[test]
+    fn find_worlds_across_save_roots() {
+        let dir = std::env::temp_dir().join(format!("fs_save_roots_{}", std::process::id()));
+        let _ignore = std::fs::remove_dir_all(&dir);
+        let (a, b) = (dir.join("a"), dir.join("b"));
+        let meta = |name: &str| {
+            format!("name = \"{name}\"\nlast_played_version = \"0.0.0\"\nsave_format = \"1\"\nlast_played_time = 2021-08-09 03:44:01+00:00\n")
+        };
+        for (world_dir, name) in [
+            (a.join("one"), "One"),
+            (a.join("group").join("two"), "Two"),
+            (b.join("three"), "Three"),
+        ] {
+            std::fs::create_dir_all(&world_dir).unwrap();
+            std::fs::write(world_dir.join("world_info.toml"), meta(name)).unwrap();
+        }
+
+        let file_helper = FileHelper::new(dir.clone(), dir.join("assets")).with_save_roots(vec![
+            a.clone(),
+            b.clone(),
+            // missing roots are skipped and the same root twice is only listed once
+            dir.join("missing"),
+            a.join("."),
+        ]);
+        let tree = World::<ServerChunk>::find_worlds(file_helper.save_roots()).unwrap();
+        let tree = World::<ServerChunk>::parse_file_tree_metas(tree).unwrap();
+        let mut names: Vec<_> = tree.worlds().iter().map(|(_, m)| m.name.clone()).collect();
+        names.sort();
+        assert_eq!(names, ["One", "Three", "Two"]);
+        for (path, meta) in tree.worlds() {
+            assert!(path.starts_with(&a) || path.starts_with(&b), "{path:?}");
+            assert_eq!(
+                World::<ServerChunk>::parse_file_meta(path).unwrap().name,
+                meta.name
+            );
+        }
+
+        // without any configured roots it falls back to the game dir
+        let file_helper = FileHelper::new(dir.clone(), dir.join("assets")).with_save_roots(vec![]);
+        assert_eq!(file_helper.save_roots(), [dir.join("saves")]);
+        let tree = World::<ServerChunk>::find_worlds(file_helper.save_roots()).unwrap();
+        assert!(tree.worlds().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn world_rules_saved_in_meta() {
+        let dir = std::env::temp_dir().join(format!("fs_world_rules_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let meta_path = dir.join("world_info.toml");
+        // a world saved before rules existed
+        std::fs::write(
+            &meta_path,
+            "name = \"Old World\"\nlast_played_version = \"0.0.0\"\nsave_format = \"1\"\nlast_played_time = 2021-08-09 03:44:01+00:00\n",
+        )
+        .unwrap();
+
+        let mut world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        assert_eq!(world.rules, WorldRules::default());
+        assert!((world.physics.gravity.y - 3.0).abs() < f32::EPSILON);
+
+        world.rules.gravity = 7.5;
+        world.rules.destructive_impacts = false;
+        world.rules.tick_speed = Some(15);
+        world.rules.freezing = false;
+        let border = BorderRules { min: (-2, -1), max: (3, 4), solid: true };
+        world.rules.border = Some(border.clone());
+        world.apply_rules();
+        world.save().unwrap();
+        world.close().unwrap();
+
+        let meta = World::<ServerChunk>::parse_file_meta(&meta_path).unwrap();
+        assert_eq!(meta.name, "Old World");
+        assert!((meta.rules.gravity - 7.5).abs() < f32::EPSILON);
+
+        let world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        assert!((world.rules.gravity - 7.5).abs() < f32::EPSILON);
+        assert!(!world.rules.destructive_impacts);
+        assert_eq!(world.physics.gravity, Vector2::new(0.0, 7.5));
+        assert_eq!(world.rules.border, Some(border));
+        let applied = world.chunk_handler.border.as_ref().unwrap();
+        assert!(applied.contains_chunk((-2, -1)) && applied.contains_chunk((3, 4)));
+        assert!(!applied.contains_chunk((4, 4)) && !applied.contains_chunk((-2, -2)));
+        assert_eq!(applied.outside.material_id, *material::BEDROCK);
+        assert_eq!(world.rules.tick_speed, Some(15));
+        assert_eq!(world.rules.tick_physics_speed, None);
+        assert!(!world.chunk_handler.freezing);
+        let rates = TickRates::new(&Settings::default(), Some(&world.rules));
+        assert_eq!(rates.chunks, 15);
+        assert_eq!(rates.physics, Settings::default().tick_physics_speed);
+
+        // a meta that can't be read doesn't stop the world from saving, it gets a new one
+        let mut world = world;
+        std::fs::write(&meta_path, "name = ").unwrap();
+        world.save().unwrap();
+        let meta = World::<ServerChunk>::parse_file_meta(&meta_path).unwrap();
+        assert!((meta.rules.gravity - 7.5).abs() < f32::EPSILON);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn new_world_meta_keeps_rules() {
+        let dir = std::env::temp_dir().join(format!("fs_new_world_meta_{}", std::process::id()));
+        let _ignore = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let meta_path = dir.join("world_info.toml");
+
+        let mut world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        let meta = World::<ServerChunk>::parse_file_meta(&meta_path).unwrap();
+        assert_eq!(meta.rules, WorldRules::default());
+        let rules = WorldRules {
+            gravity: 1.5,
+            physics_substeps: 3,
+            destructive_impacts: false,
+            ..WorldRules::default()
+        };
+        world.rules = rules.clone();
+        world.apply_rules();
+        world.save().unwrap();
+        world.close().unwrap();
+
+        // a brand new world has a whole meta, so it shows up in the world list
+        let meta = World::<ServerChunk>::parse_file_meta(&meta_path).unwrap();
+        assert_eq!(meta.name, dir.file_name().unwrap().to_string_lossy());
+        assert_eq!(meta.rules, rules);
+        let tree = World::<ServerChunk>::find_files(dir.clone()).unwrap();
+        assert_eq!(tree.worlds().len(), 1);
+
+        let world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        assert_eq!(world.rules, rules);
+        assert_eq!(world.physics.gravity, Vector2::new(0.0, 1.5));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cameras_stay_inside_border() {
+        let mut world = World::<ServerChunk>::create(None, Some(2));
+        world.rules.border = Some(BorderRules { min: (0, 0), max: (1, 1), solid: true });
+        world.apply_rules();
+        let width = f64::from(CHUNK_WIDTH) * 2.0;
+        let height = f64::from(CHUNK_HEIGHT) * 2.0;
+
+        // no auto target, so only the border moves it
+        let camera = world
+            .ecs
+            .create_entity()
+            .with(Camera)
+            .with(Position { x: 0.0, y: 0.0 })
+            .build();
+        let camera_pos = |world: &World<ServerChunk>| {
+            let pos = world
+                .ecs
+                .read_storage::<Position>()
+                .get(camera)
+                .unwrap()
+                .clone();
+            (pos.x, pos.y)
+        };
+        let move_camera = |world: &mut World<ServerChunk>, x, y| {
+            *world
+                .ecs
+                .write_storage::<Position>()
+                .get_mut(camera)
+                .unwrap() = Position { x, y };
+        };
+
+        // without a view to keep inside, nothing happens
+        move_camera(&mut world, -500.0, 5000.0);
+        world.frame(std::time::Duration::from_millis(16), None);
+        assert_eq!(camera_pos(&world), (-500.0, 5000.0));
+
+        // zoomed in, the edge of the view stops at the border
+        world.frame(std::time::Duration::from_millis(16), Some((100.0, 50.0)));
+        assert_eq!(camera_pos(&world), (50.0, height - 25.0));
+
+        // zoomed out past the border, it's centered
+        move_camera(&mut world, -500.0, 5000.0);
+        world.frame(
+            std::time::Duration::from_millis(16),
+            Some((width * 3.0, 50.0)),
+        );
+        assert_eq!(camera_pos(&world), (width / 2.0, height - 25.0));
+    }
+
+    #[test]
+    fn flat_preset_is_flat_and_saved() {
+        let dir = std::env::temp_dir().join(format!("fs_world_preset_{}", std::process::id()));
+        let _ignore = std::fs::remove_dir_all(&dir);
+        let meta_path = dir.join("world_info.toml");
+
+        let preset = WorldPreset::Flat {
+            height: 150,
+            material: material::COBBLE_STONE.to_string(),
+            color: Color::rgb(127, 127, 127),
+        };
+        let mut world =
+            World::<ServerChunk>::create_with_preset(Some(dir.clone()), Some(2), preset.clone());
+        assert_eq!(world.preset, preset);
+        // a new world gets its meta right away, before it's ever saved
+        let meta = World::<ServerChunk>::parse_file_meta(&meta_path).unwrap();
+        assert_eq!(meta.preset, Some(preset.clone()));
+        assert_eq!(meta.name, dir.file_name().unwrap().to_string_lossy());
+        world.save().unwrap();
+        world.close().unwrap();
+
+        // reloading it without asking for a preset uses the saved one
+        let world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        assert_eq!(world.preset, preset);
+
+        let registries = Registries::empty();
+        let area =
+            |y1, y2| world.material_histogram(Rect::new(-250, y1, 250, y2), Some(&registries));
+        // the surface is in the middle of a chunk
+        let air = area(-200, 150);
+        assert_eq!(air.len(), 1);
+        assert_eq!(air[&*material::AIR], 500 * 350);
+        let ground = area(150, 400);
+        assert_eq!(ground.len(), 1);
+        assert_eq!(ground[&*material::COBBLE_STONE], 500 * 250);
+        drop(world);
+
+        // a meta written before presets existed doesn't override the one asked for
+        std::fs::write(
+            &meta_path,
+            "name = \"Flat\"\nlast_played_version = \"0.0.0\"\nsave_format = \"1\"\nlast_played_time = 2021-08-09 03:44:01+00:00\n",
+        )
+        .unwrap();
+        let world =
+            World::<ServerChunk>::create_with_preset(Some(dir.clone()), Some(2), preset.clone());
+        assert_eq!(world.preset, preset);
+        drop(world);
+        // and it's kept from then on
+        let world = World::<ServerChunk>::create(Some(dir.clone()), Some(2));
+        assert_eq!(world.preset, preset);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -7,7 +7,7 @@ out vec4 color;
 uniform vec2 player_light_world_pos;
 uniform bool smooth_lighting;
 uniform bool dithering;
-uniform int chunk_size;
+uniform vec2 chunk_size;
 uniform sampler2D tex;
 uniform sampler2D light_tex;
 
@@ -0,0 +1,30 @@
+#version 140
+
+in vec2 tex_c;
+in vec2 world_pos;
+out vec4 color;
+
+uniform sampler2D heat;
+uniform sampler2D screen;
+uniform vec2 screen_size;
+// how far through its loop the animation is, from 0 to 1
+uniform float phase;
+// the furthest the screen gets moved, in screen pixels
+uniform float strength;
+
+const float TAU = 6.28318530718;
+
+void main() {
+    float h = texture(heat, tex_c).r;
+    if (h <= 0.0) discard;
+
+    // whole numbers of waves per loop, so nothing jumps when the phase wraps around
+    float t = phase * TAU;
+    vec2 wobble = vec2(
+        (sin(world_pos.y * 0.35 + t * 7.0) + 0.5 * sin(world_pos.y * 0.8 - t * 11.0)) / 1.5,
+        sin(world_pos.x * 0.25 + t * 5.0)
+    );
+
+    vec2 coord = (gl_FragCoord.xy + wobble * strength * h) / screen_size;
+    color = vec4(texture(screen, coord).rgb, 1.0);
+}